@@ -0,0 +1,16 @@
+-- Slows down all units around the target of the fireball for a while.
+radius = 4.0
+slow = 0.5
+seconds = 3.0
+
+function on_cast(caster, target)
+    local center = position(target)
+    if center == nil then
+        return
+    end
+    for _, unit in ipairs(units_in_radius(center, radius)) do
+        if unit ~= caster then
+            modify_stat(unit, "Speed", slow, seconds)
+        end
+    end
+end
@@ -0,0 +1,14 @@
+-- Rallies all units at the center of the outpost every minute.
+interval = 60.0
+elapsed = 0.0
+
+function on_update(dt)
+    elapsed = elapsed + dt
+    if elapsed < interval then
+        return
+    end
+    elapsed = 0.0
+    for _, unit in ipairs(units()) do
+        order(unit, vec3(20.0, 0.0, 20.0))
+    end
+end
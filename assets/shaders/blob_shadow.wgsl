@@ -0,0 +1,12 @@
+#import bevy_pbr::forward_io::VertexOutput
+
+@group(2) @binding(0)
+var<uniform> color: vec4<f32>;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // hard-edged disc with a lighter rim, soft gradients would dither at pixel-art resolutions
+    let r = length(in.uv * 2.0 - 1.0);
+    let alpha = color.a * (1.0 - step(1.0, r)) * mix(1.0, 0.6, step(0.7, r));
+    return vec4<f32>(color.rgb, alpha);
+}
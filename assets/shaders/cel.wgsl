@@ -46,7 +46,8 @@ fn fragment(
 
     // convert back to srgb
     let out_rgb = colors::oklch2srgb(vec3<f32>(luminance, oklch.y, oklch.z));
-    out.color = vec4(out_rgb, pbr_output_color.a);
+    // emissive is added after quantizing so it can exceed 1.0 & bloom on hdr cameras
+    out.color = vec4(out_rgb + pbr_input.material.emissive.rgb, pbr_output_color.a);
 
     // apply in-shader post processing (fog, alpha-premultiply, and also tonemapping, debanding if the camera is non-hdr)
     // note this does not include fullscreen postprocessing effects like bloom.
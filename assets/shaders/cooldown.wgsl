@@ -0,0 +1,20 @@
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+struct Cooldown {
+    color: vec4<f32>,
+    fraction: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> cooldown: Cooldown;
+
+const TAU: f32 = 6.28318530718;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    // angle clockwise from the top in [0, 1)
+    let p = in.uv * 2.0 - 1.0;
+    let angle = fract(atan2(p.x, -p.y) / TAU + 1.0);
+    let covered = step(angle, cooldown.fraction) * step(0.0001, cooldown.fraction);
+    return vec4<f32>(cooldown.color.rgb, cooldown.color.a * covered);
+}
@@ -0,0 +1,62 @@
+#import bevy_pbr::{
+    forward_io::{Vertex, VertexOutput},
+    mesh_functions::{get_model_matrix, mesh_position_local_to_world, mesh_normal_local_to_world, mesh_tangent_local_to_world},
+    mesh_view_bindings::globals,
+    view_transformations::position_world_to_clip,
+}
+#import motte::utils::{random};
+
+struct DetailMaterial {
+    wind: vec4<f32>,
+    focus: vec2<f32>,
+    fade: vec2<f32>,
+    sway: f32,
+    density: f32,
+}
+
+@group(2) @binding(100)
+var<uniform> material: DetailMaterial;
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    let model = get_model_matrix(vertex.instance_index);
+    let origin = model[3].xyz;
+
+    // shrink into the ground with the distance to the focus, instances over the budget are collapsed entirely
+    let focus_distance = length(origin.xz - material.focus);
+    let fade = 1.0 - smoothstep(material.fade.x, material.fade.y, focus_distance);
+    let kept = step(random(origin.xz), material.density);
+    let local_position = vertex.position * fade * kept;
+
+    var world_position = mesh_position_local_to_world(model, vec4<f32>(local_position, 1.0));
+
+    // bend along the wind, the base stays in place & each instance is out of phase with its neighbours
+    let height = max(local_position.y, 0.0);
+    let phase = dot(origin.xz, material.wind.xy) * 0.35 + random(origin.zx) * 6.28;
+    let gust = sin(globals.time * material.wind.w + phase) * 0.5 + 0.5;
+    world_position.x += material.wind.x * material.wind.z * gust * height * height * material.sway;
+    world_position.z += material.wind.y * material.wind.z * gust * height * height * material.sway;
+
+    out.world_position = world_position;
+    out.position = position_world_to_clip(world_position.xyz);
+
+#ifdef VERTEX_NORMALS
+    out.world_normal = mesh_normal_local_to_world(vertex.normal, vertex.instance_index);
+#endif
+#ifdef VERTEX_UVS
+    out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_TANGENTS
+    out.world_tangent = mesh_tangent_local_to_world(model, vertex.tangent, vertex.instance_index);
+#endif
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
+    return out;
+}
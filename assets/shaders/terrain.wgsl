@@ -0,0 +1,77 @@
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{
+        apply_pbr_lighting,
+        main_pass_post_lighting_processing
+    }
+}
+
+struct TerrainMaterial {
+    tiling: vec4<f32>,
+    slope: f32,
+}
+
+@group(2) @binding(100)
+var<uniform> material: TerrainMaterial;
+@group(2) @binding(101)
+var splat_map: texture_2d<f32>;
+@group(2) @binding(102)
+var splat_sampler: sampler;
+@group(2) @binding(103)
+var layer_0: texture_2d<f32>;
+@group(2) @binding(104)
+var layer_0_sampler: sampler;
+@group(2) @binding(105)
+var layer_1: texture_2d<f32>;
+@group(2) @binding(106)
+var layer_1_sampler: sampler;
+@group(2) @binding(107)
+var layer_2: texture_2d<f32>;
+@group(2) @binding(108)
+var layer_2_sampler: sampler;
+@group(2) @binding(109)
+var layer_3: texture_2d<f32>;
+@group(2) @binding(110)
+var layer_3_sampler: sampler;
+
+/// Samples a layer projected along each axis, blended by `weights`.
+fn triplanar(t: texture_2d<f32>, s: sampler, position: vec3<f32>, weights: vec3<f32>, tiling: f32) -> vec4<f32> {
+    let p = position / max(tiling, 1e-4);
+    return textureSample(t, s, p.zy) * weights.x
+        + textureSample(t, s, p.xz) * weights.y
+        + textureSample(t, s, p.xy) * weights.z;
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    // project from above on flat ground, blend the projections by the normal on steep slopes
+    let n = abs(normalize(in.world_normal));
+    let blend = n * n * n * n;
+    let weights = select(blend / (blend.x + blend.y + blend.z), vec3<f32>(0.0, 1.0, 0.0), n.y >= material.slope);
+
+#ifdef VERTEX_UVS
+    var splat = textureSample(splat_map, splat_sampler, in.uv);
+#else
+    var splat = vec4<f32>(1.0);
+#endif
+    splat /= max(splat.r + splat.g + splat.b + splat.a, 1e-4);
+
+    let position = in.world_position.xyz;
+    let albedo = triplanar(layer_0, layer_0_sampler, position, weights, material.tiling.x) * splat.r
+        + triplanar(layer_1, layer_1_sampler, position, weights, material.tiling.y) * splat.g
+        + triplanar(layer_2, layer_2_sampler, position, weights, material.tiling.z) * splat.b
+        + triplanar(layer_3, layer_3_sampler, position, weights, material.tiling.w) * splat.a;
+    pbr_input.material.base_color *= albedo;
+
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+
+    return out;
+}
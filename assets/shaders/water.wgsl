@@ -0,0 +1,76 @@
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    mesh_view_bindings::globals,
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{
+        apply_pbr_lighting,
+        main_pass_post_lighting_processing
+    },
+    prepass_utils,
+    view_transformations::{depth_ndc_to_view_z, position_world_to_view},
+}
+#import motte::colors;
+#import motte::utils::{clamp01};
+
+struct WaterMaterial {
+    shallow_color: vec4<f32>,
+    deep_color: vec4<f32>,
+    foam_color: vec4<f32>,
+    scroll: vec2<f32>,
+    wave_scale: f32,
+    wave_strength: f32,
+    depth_fade: f32,
+    foam_width: f32,
+    lit: f32,
+    shadow: f32,
+    cut_off: f32,
+}
+
+@group(2) @binding(100)
+var<uniform> material: WaterMaterial;
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    // two layers of sine waves scrolling in different directions, the slopes perturb the normal
+    let t = globals.time;
+    let a = in.world_position.xz * material.wave_scale + material.scroll * t;
+    let b = in.world_position.xz * material.wave_scale * 1.7 - material.scroll.yx * t * 0.8;
+    let slope = vec2<f32>(
+        cos(a.x + sin(a.y)) + cos(b.x - b.y * 0.7),
+        sin(a.y + cos(a.x)) + sin(b.y - b.x * 0.7),
+    ) * 0.5 * material.wave_strength;
+    pbr_input.N = normalize(vec3<f32>(-slope.x, 1.0, -slope.y));
+
+    // depth of the water below the fragment, from the depth prepass
+    var depth = material.depth_fade;
+#ifdef DEPTH_PREPASS
+    let scene_z = depth_ndc_to_view_z(prepass_utils::prepass_depth(in.position, 0u));
+    let water_z = position_world_to_view(in.world_position.xyz).z;
+    depth = max(water_z - scene_z, 0.0);
+#endif
+
+    let base_color = mix(material.shallow_color, material.deep_color, clamp01(depth / material.depth_fade));
+    pbr_input.material.base_color = base_color;
+
+    // quantize luminance, same as the cel material
+    let oklch = colors::srgb2oklch(base_color.rgb);
+    let pbr_output_color = apply_pbr_lighting(pbr_input);
+    var luminance = colors::srgb2oklch(pbr_output_color.rgb).x;
+    luminance = clamp01(clamp(step(clamp01(material.cut_off), luminance), material.shadow * oklch.x, material.lit * oklch.x));
+    var rgb = colors::oklch2srgb(vec3<f32>(luminance, oklch.y, oklch.z));
+
+    // hard-edged foam where the water meets geometry
+    let foam = (1.0 - step(material.foam_width, depth)) * material.foam_color.a;
+    rgb = mix(rgb, material.foam_color.rgb, foam);
+
+    var out: FragmentOutput;
+    out.color = vec4<f32>(rgb, mix(base_color.a, 1.0, foam));
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+
+    return out;
+}
@@ -1,14 +1,31 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+#[cfg(not(any(feature = "presentation", feature = "headless")))]
+compile_error!("motte requires the `presentation` or `headless` feature");
+
+#[cfg(feature = "presentation")]
 use std::io::Cursor;
 
+#[cfg(feature = "presentation")]
 use bevy::{
+    log::{BoxedSubscriber, LogPlugin},
     prelude::*,
-    render::RenderPlugin,
-    window::{PresentMode, PrimaryWindow, WindowPlugin},
+    render::{settings::Backends, RenderPlugin},
+    window::{PresentMode, PrimaryWindow, WindowMode, WindowPlugin},
     winit::WinitWindows,
 };
+#[cfg(all(feature = "presentation", not(feature = "hot_reload"), target_arch = "wasm32"))]
 use bevy_embedded_assets::{EmbeddedAssetPlugin, PluginMode};
+#[cfg(all(feature = "presentation", not(target_arch = "wasm32")))]
+use motte_lib::crash::{self, CrashReportPlugin};
+use motte_lib::launch::LaunchOptions;
+#[cfg(feature = "presentation")]
+use motte_lib::launch::LogFilter;
+#[cfg(all(feature = "presentation", not(feature = "hot_reload"), not(target_arch = "wasm32")))]
+use {
+    bevy_embedded_assets::EmbeddedAssetReader,
+    motte_lib::modding::{self, ModdingPlugin},
+};
 
 pub fn name() -> &'static str {
     env!("CARGO_PKG_NAME")
@@ -18,36 +35,100 @@ pub fn main() {
     #[cfg(all(debug_assertions, target_arch = "wasm32"))]
     console_error_panic_hook::set_once();
 
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    // Builds without the presentation always run headless.
+    #[cfg(feature = "headless")]
+    if !cfg!(feature = "presentation") || LaunchOptions::headless(&args) {
+        if let Err(err) = motte_lib::headless::run(args.into_iter()) {
+            eprintln!("{err:?}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    #[cfg(feature = "presentation")]
+    run(args);
+}
+
+/// Runs the game in a window.
+#[cfg(feature = "presentation")]
+fn run(args: Vec<String>) {
+    #[cfg(not(target_arch = "wasm32"))]
+    crash::install(true);
+
+    let launch = match LaunchOptions::parse(args) {
+        Ok(launch) => launch,
+        Err(err) => {
+            eprintln!("{err:?}");
+            std::process::exit(1);
+        }
+    };
+
+    // Vulkan is preferred, see https://github.com/bevyengine/bevy/issues/9975. Falls back to the others in order
+    // instead of failing to create the renderer.
+    #[cfg(not(target_arch = "wasm32"))]
+    let backend = launch.negotiate_backend();
+    #[cfg(target_arch = "wasm32")]
+    let backend = Some(Backends::GL);
+
     let mut app = App::new();
 
+    let mut log = LogPlugin::default();
+    match &launch.log {
+        Some(LogFilter::Level(level)) => log.level = *level,
+        Some(LogFilter::Filter(filter)) => log.filter = format!("{},{filter}", log.filter),
+        None => {}
+    }
+    log.update_subscriber = Some(update_subscriber);
+
     let default_plugins = DefaultPlugins
         .set(WindowPlugin {
             primary_window: Some(Window {
                 title: format!("{} {}", name(), motte_lib::version()),
-                present_mode: PresentMode::AutoNoVsync,
-                resolution: (1280., 720.).into(),
-                #[cfg(debug_assertions)]
-                position: WindowPosition::Centered(MonitorSelection::Index(1)),
+                present_mode: launch.present_mode.unwrap_or(PresentMode::AutoNoVsync),
+                resolution: launch.resolution.unwrap_or(Vec2::new(1280., 720.)).into(),
+                mode: if launch.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed },
                 ..default()
             }),
             ..default()
         })
         .set(RenderPlugin {
-            // note: https://github.com/bevyengine/bevy/issues/9975
             render_creation: bevy::render::settings::RenderCreation::Automatic(bevy::render::settings::WgpuSettings {
-                backends: Some(bevy::render::settings::Backends::VULKAN),
+                backends: Some(backend.unwrap_or(Backends::all())),
                 ..default()
             }),
             ..default()
-        });
+        })
+        .set(log);
 
+    // Embedded assets can't be watched, read them from disk when hot reloading. Mods are layered on top of the
+    // embedded assets, when hot reloading assets can be edited directly instead.
+    #[cfg(all(not(feature = "hot_reload"), not(target_arch = "wasm32")))]
+    app.add_plugins(default_plugins.build().add_before::<bevy::asset::AssetPlugin, _>(ModdingPlugin::new(
+        launch.mods.clone().unwrap_or_else(modding::default_directory),
+        || Box::new(EmbeddedAssetReader::preloaded()),
+    )));
+    #[cfg(all(not(feature = "hot_reload"), target_arch = "wasm32"))]
     app.add_plugins(
         default_plugins
             .build()
             .add_before::<bevy::asset::AssetPlugin, _>(EmbeddedAssetPlugin { mode: PluginMode::ReplaceDefault }),
     );
+    #[cfg(feature = "hot_reload")]
+    app.add_plugins(
+        default_plugins.set(bevy::asset::AssetPlugin { watch_for_changes_override: Some(true), ..default() }),
+    );
 
+    match backend {
+        Some(backend) => info!("using renderer backend {backend:?}"),
+        None => warn!("none of the renderer backends {:?} are available", launch.backends),
+    }
+
+    app.insert_resource(launch);
     app.add_plugins(motte_lib::Plugin);
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_plugins(CrashReportPlugin);
 
     #[cfg(not(target_arch = "wasm32"))]
     app.add_systems(Startup, set_window_icon);
@@ -55,7 +136,18 @@ pub fn main() {
     app.run();
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+/// Adds the log layers of the crash reports & the log panel of the dev tools.
+#[cfg(feature = "presentation")]
+#[allow(clippy::let_and_return)]
+fn update_subscriber(subscriber: BoxedSubscriber) -> BoxedSubscriber {
+    #[cfg(not(target_arch = "wasm32"))]
+    let subscriber = crash::capture_logs(subscriber);
+    #[cfg(feature = "dev_tools")]
+    let subscriber = motte_lib::capture_dev_logs(subscriber);
+    subscriber
+}
+
+#[cfg(all(feature = "presentation", not(target_arch = "wasm32")))]
 fn set_window_icon(windows: NonSend<WinitWindows>, primary_window: Query<Entity, With<PrimaryWindow>>) {
     let primary_entity = primary_window.single();
     let primary = windows.get_window(primary_entity).unwrap();
@@ -0,0 +1,145 @@
+//! Benchmarks of the navigation hot paths, run with `cargo bench -p motte_lib --features bench`.
+//!
+//! Maps are filled with randomly placed square obstacles from a fixed seed, so runs are comparable.
+
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+use std::borrow::Cow;
+
+use bevy::math::Vec2;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use motte_lib::navigation::{
+    agent::Agent,
+    flow_field::{
+        fields::{
+            flow::FlowField,
+            obstacle::{Cost, ObstacleField, Occupant},
+            Cell,
+        },
+        layout::FieldLayout,
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const SEED: u64 = 0x6D6F_7474_65;
+const SIZES: [u8; 3] = [64, 128, 255];
+/// Percentage of the map covered by obstacles.
+const DENSITIES: [usize; 3] = [0, 10, 30];
+
+/// Cells of square obstacles covering roughly `density` percent of the layout.
+fn obstacles(layout: &FieldLayout, density: usize) -> Vec<Vec<Cell>> {
+    const SIZE: u8 = 3;
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let count = layout.len() * density / 100 / (SIZE as usize * SIZE as usize);
+    (0..count)
+        .map(|_| {
+            let x = rng.gen_range(0..layout.width().saturating_sub(SIZE));
+            let y = rng.gen_range(0..layout.height().saturating_sub(SIZE));
+            (x..x + SIZE).flat_map(|x| (y..y + SIZE).map(move |y| Cell::new(x, y))).collect()
+        })
+        .collect()
+}
+
+fn obstacle_field(layout: &FieldLayout, density: usize) -> ObstacleField {
+    let mut obstacle_field = ObstacleField::from_layout(layout);
+    for cells in obstacles(layout, density) {
+        obstacle_field.splat(&cells, Cost::Blocked, Occupant::Obstacle);
+    }
+    obstacle_field
+}
+
+fn build<const AGENT: Agent>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(name);
+    for size in SIZES {
+        let layout = FieldLayout::new(size, size);
+        for density in DENSITIES {
+            let obstacle_field = obstacle_field(&layout, density);
+            let mut flow_field = FlowField::<AGENT>::from_layout(&layout);
+            let goal = Cell::splat(size / 2);
+            group.bench_with_input(
+                BenchmarkId::new(format!("{size}x{size}"), format!("{density}%")),
+                &obstacle_field,
+                |b, obstacle_field| b.iter(|| flow_field.build(std::iter::once(black_box(goal)), obstacle_field)),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn flow_field_build(c: &mut Criterion) {
+    build::<{ Agent::Small }>(c, "flow_field/build/small");
+    build::<{ Agent::Huge }>(c, "flow_field/build/huge");
+}
+
+fn obstacle_splat(c: &mut Criterion) {
+    let mut group = c.benchmark_group("obstacle_field/splat");
+    for size in SIZES {
+        let layout = FieldLayout::new(size, size);
+        for density in DENSITIES {
+            let obstacles = obstacles(&layout, density);
+            group.bench_with_input(
+                BenchmarkId::new(format!("{size}x{size}"), format!("{density}%")),
+                &obstacles,
+                |b, obstacles| {
+                    b.iter_batched_ref(
+                        || ObstacleField::from_layout(&layout),
+                        |obstacle_field| {
+                            obstacle_field.clear();
+                            for cells in obstacles {
+                                obstacle_field.splat(cells, Cost::Traversable(Agent::Medium), Occupant::Obstacle);
+                            }
+                        },
+                        BatchSize::LargeInput,
+                    )
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn avoidance(c: &mut Criterion) {
+    const NEIGHBORS: [usize; 4] = [0, 4, 16, 64];
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let options = dodgy_2d::AvoidanceOptions { obstacle_margin: 0.1, time_horizon: 3.0, obstacle_time_horizon: 0.1 };
+    let agent = dodgy_2d::Agent {
+        position: Vec2::ZERO,
+        velocity: Vec2::X,
+        radius: Agent::Small.radius(),
+        avoidance_responsibility: 1.0,
+    };
+    let obstacles: Vec<Cow<'static, dodgy_2d::Obstacle>> = vec![Cow::Owned(dodgy_2d::Obstacle::Closed {
+        vertices: vec![Vec2::new(2.0, -1.0), Vec2::new(3.0, -1.0), Vec2::new(3.0, 1.0), Vec2::new(2.0, 1.0)],
+    })];
+
+    let mut group = c.benchmark_group("avoidance/rvo2");
+    for count in NEIGHBORS {
+        let neighbors: Vec<Cow<'static, dodgy_2d::Agent>> = (0..count)
+            .map(|_| {
+                Cow::Owned(dodgy_2d::Agent {
+                    position: Vec2::new(rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0)),
+                    velocity: Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)),
+                    radius: Agent::Small.radius(),
+                    avoidance_responsibility: 1.0,
+                })
+            })
+            .collect();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &neighbors, |b, neighbors| {
+            b.iter(|| {
+                agent.compute_avoiding_velocity(
+                    black_box(neighbors),
+                    &obstacles,
+                    Vec2::X,
+                    1.2,
+                    1.0 / 64.0,
+                    &options,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, flow_field_build, obstacle_splat, avoidance);
+criterion_main!(benches);
@@ -1,11 +1,25 @@
 use std::marker::ConstParamTy;
 
 use bevy::{prelude::*, reflect::Reflect};
+use motte_macros::app_register_types;
+
+pub struct AppStatePlugin;
+
+impl Plugin for AppStatePlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(AppState, InGameState);
+        app.init_state::<AppState>();
+        app.add_sub_state(AppState::InGame, InGameState::Playing);
+    }
+}
 
 #[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash, ConstParamTy, Reflect)]
 pub enum AppState {
     #[default]
     Loading,
+    MainMenu,
+    MapSelect,
+    LoadingMap,
     InGame,
 }
 
@@ -14,3 +28,59 @@ impl std::fmt::Display for AppState {
         write!(f, "{:?}", self)
     }
 }
+
+/// Sub-state of [`AppState::InGame`], [`InGameState::Disabled`] outside of it.
+#[derive(States, Default, Clone, Copy, Eq, PartialEq, Debug, Hash, Reflect)]
+pub enum InGameState {
+    #[default]
+    Disabled,
+    Playing,
+    Paused,
+    GameOver,
+    /// Simulation runs but player input is disabled, entered by scripted sequences.
+    #[allow(unused)]
+    Cinematic,
+}
+
+impl InGameState {
+    /// Returns true if the game simulation should advance.
+    pub fn simulating(self) -> bool {
+        matches!(self, InGameState::Playing | InGameState::Cinematic)
+    }
+}
+
+/// Run condition for systems that advance the game simulation, see [`InGameState::simulating`].
+pub fn simulating(state: Res<State<InGameState>>) -> bool {
+    state.simulating()
+}
+
+pub trait AppSubStateExt {
+    /// Adds state `S` which is entered as `initial` when `parent` is entered & reset to its default when `parent` is
+    /// exited, both applied in the same frame as the parent transition.
+    fn add_sub_state<P: States, S: States + Default>(&mut self, parent: P, initial: S) -> &mut Self;
+}
+
+impl AppSubStateExt for App {
+    fn add_sub_state<P: States, S: States + Default>(&mut self, parent: P, initial: S) -> &mut Self {
+        self.init_state::<S>();
+        self.add_systems(
+            StateTransition,
+            sub_state(parent, initial).after(apply_state_transition::<P>).before(apply_state_transition::<S>),
+        )
+    }
+}
+
+fn sub_state<P: States, S: States + Default>(
+    parent: P,
+    initial: S,
+) -> impl FnMut(EventReader<StateTransitionEvent<P>>, ResMut<NextState<S>>) {
+    move |mut transitions, mut next_state| {
+        for transition in transitions.read() {
+            if transition.after == parent {
+                next_state.set(initial.clone());
+            } else if transition.before == parent {
+                next_state.set(S::default());
+            }
+        }
+    }
+}
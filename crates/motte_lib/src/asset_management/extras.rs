@@ -0,0 +1,97 @@
+//! Gameplay markers read from glTF node extras (custom properties in Blender), applied when a scene is spawned.
+//!
+//! - `obstacle`: static [`Obstacle`] with a convex hull collider generated from the node's meshes.
+//! - `nav_blocker`: like `obstacle` but only blocks navigation, the collider is a [`Sensor`].
+//! - `spawn_point`: [`SpawnPoint`] with the given group, e.g. `"team_a"`.
+//! - `surface`: [`SurfaceRegion`] of the node's meshes, e.g. `"road"` or `"water"`.
+
+use bevy::gltf::GltfExtras;
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    navigation::{
+        flow_field::{
+            fields::surface::{Surface, SurfaceRegion},
+            footprint::Footprint,
+            CellIndex,
+        },
+        obstacle::Obstacle,
+    },
+    physics::layers,
+    prelude::*,
+    scenario::SpawnPoint,
+};
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct Extras {
+    #[serde(deserialize_with = "truthy")]
+    obstacle: bool,
+    #[serde(deserialize_with = "truthy")]
+    nav_blocker: bool,
+    spawn_point: Option<String>,
+    surface: Option<Surface>,
+}
+
+/// Blender exports boolean properties as `0`/`1` unless explicitly typed, accept anything but `false`, `0` & `null`.
+fn truthy<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Null => false,
+        serde_json::Value::Bool(value) => value,
+        serde_json::Value::Number(value) => value.as_f64() != Some(0.0),
+        _ => true,
+    })
+}
+
+pub(super) fn markers(
+    mut commands: Commands,
+    nodes: Query<(Entity, &GltfExtras, Option<&Name>), Added<GltfExtras>>,
+    children: Query<&Children>,
+    meshes: Query<(), With<Handle<Mesh>>>,
+) {
+    for (entity, extras, name) in &nodes {
+        let extras: Extras = match serde_json::from_str(&extras.value) {
+            Ok(extras) => extras,
+            Err(err) => {
+                warn!("Invalid glTF extras on {}: {err}", name.map_or("unnamed node", Name::as_str));
+                continue;
+            }
+        };
+
+        if let Some(group) = extras.spawn_point {
+            commands.entity(entity).insert(SpawnPoint(group));
+        }
+
+        // Extras are stored on the node, meshes are either on the node itself or its primitives.
+        let targets: SmallVec<[Entity; 4]> = std::iter::once(entity)
+            .chain(children.iter_descendants(entity))
+            .filter(|&entity| meshes.contains(entity))
+            .collect();
+
+        if let Some(surface) = extras.surface {
+            for &target in &targets {
+                commands.entity(target).insert(SurfaceRegion(surface));
+            }
+        }
+
+        if !extras.obstacle && !extras.nav_blocker {
+            continue;
+        }
+
+        for target in targets {
+            let mut commands = commands.entity(target);
+            commands.insert((
+                Obstacle::default(),
+                Footprint::default(),
+                CellIndex::default(),
+                RigidBody::Static,
+                AsyncCollider(ComputedCollider::ConvexHull),
+            ));
+            if extras.obstacle {
+                commands.insert(layers::terrain());
+            } else {
+                commands.insert((Sensor, layers::sensor()));
+            }
+        }
+    }
+}
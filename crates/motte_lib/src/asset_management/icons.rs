@@ -0,0 +1,108 @@
+//! UI icons (abilities, portraits, buffs) packed into a single atlas, `assets/icons/icons.atlas.ron` maps icon ids to
+//! their rect in `assets/icons/icons.png`. Add an [`Icon`] to a UI node to display it by id.
+
+use std::borrow::Cow;
+
+use bevy::ui::UiSystem;
+use bevy_asset_loader::asset_collection::AssetCollection;
+use bevy_common_assets::ron::RonAssetPlugin;
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+/// Icon displayed for ids missing from the manifest.
+const MISSING: &str = "missing";
+
+pub struct IconsPlugin;
+
+impl Plugin for IconsPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(IconAssets, IconManifest, IconRect, IconAtlas, Icon);
+        app.add_plugins(RonAssetPlugin::<IconManifest>::new(&["atlas.ron"]));
+        app.add_systems(PostUpdate, icon.run_if(resource_exists::<IconAtlas>).before(UiSystem::Layout));
+    }
+}
+
+#[derive(AssetCollection, Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct IconAssets {
+    #[asset(path = "icons/icons.png")]
+    pub image: Handle<Image>,
+
+    #[asset(path = "icons/icons.atlas.ron")]
+    pub manifest: Handle<IconManifest>,
+}
+
+#[derive(Asset, Reflect, Deserialize, Default, Clone, Debug)]
+pub struct IconManifest {
+    /// Pixel rect of each icon in the atlas image.
+    pub icons: HashMap<String, IconRect>,
+}
+
+#[derive(Reflect, Deserialize, Clone, Copy, Debug)]
+pub struct IconRect {
+    pub min: UVec2,
+    pub max: UVec2,
+}
+
+impl From<IconRect> for Rect {
+    fn from(rect: IconRect) -> Self {
+        URect::from_corners(rect.min, rect.max).as_rect()
+    }
+}
+
+/// Atlas layout built from the [`IconManifest`] once [`IconAssets`] are loaded.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct IconAtlas {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    indices: HashMap<String, usize>,
+}
+
+impl IconAtlas {
+    pub fn get(&self, id: &str) -> Option<TextureAtlas> {
+        self.indices.get(id).map(|&index| TextureAtlas { layout: self.layout.clone(), index })
+    }
+}
+
+impl FromWorld for IconAtlas {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<IconAssets>();
+        let image = assets.image.clone();
+        let manifest =
+            world.resource::<Assets<IconManifest>>().get(&assets.manifest).expect("manifest should be loaded");
+        let size = world.resource::<Assets<Image>>().get(&image).expect("atlas should be loaded").size();
+
+        let mut layout = TextureAtlasLayout::new_empty(size.as_vec2());
+        let indices =
+            manifest.icons.iter().map(|(id, rect)| (id.clone(), layout.add_texture((*rect).into()))).collect();
+
+        let layout = world.resource_mut::<Assets<TextureAtlasLayout>>().add(layout);
+        Self { image, layout, indices }
+    }
+}
+
+/// Displays the icon with given id from the [`IconAtlas`], use on a UI node e.g. [`AtlasImageBundle`].
+#[derive(Component, Reflect, Default, Clone, Debug, PartialEq, Eq, Deref)]
+#[reflect(Component)]
+pub struct Icon(pub Cow<'static, str>);
+
+impl Icon {
+    pub fn new(id: impl Into<Cow<'static, str>>) -> Self {
+        Self(id.into())
+    }
+}
+
+fn icon(mut commands: Commands, icons: Query<(Entity, &Icon), Changed<Icon>>, atlas: Res<IconAtlas>) {
+    for (entity, icon) in &icons {
+        let texture_atlas = atlas.get(icon).or_else(|| {
+            warn!("Missing icon {:?}", **icon);
+            atlas.get(MISSING)
+        });
+        let Some(texture_atlas) = texture_atlas else {
+            continue;
+        };
+        commands.entity(entity).insert((UiImage::new(atlas.image.clone()), texture_atlas));
+    }
+}
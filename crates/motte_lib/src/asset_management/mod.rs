@@ -1,26 +1,98 @@
 use bevy_asset_loader::{
     asset_collection::AssetCollection,
+    dynamic_asset::DynamicAssets,
     loading_state::{config::ConfigureLoadingState, LoadingStateAppExt},
     prelude::LoadingState,
+    standard_dynamic_asset::StandardDynamicAsset,
 };
 
-use crate::{app_state::AppState, prelude::*};
+mod extras;
+pub mod icons;
+mod variants;
+
+use crate::{
+    app_state::{AppState, AppSubStateExt},
+    asset_management::icons::{IconAssets, IconAtlas},
+    audio::AudioAssets,
+    config::GameConfig,
+    in_game::map::{MapDef, SelectedMap},
+    prefab::Prefab,
+    prelude::*,
+    settings::Settings,
+    spells::SpellDef,
+    tech::TechTree,
+};
 
 pub struct AssetManagementPlugin;
 
 impl Plugin for AssetManagementPlugin {
     fn build(&self, app: &mut App) {
-        app_register_types!(FontAssets, GlbAssets, ImageAssets);
+        app_register_types!(
+            FontAssets,
+            GlbAssets,
+            ImageAssets,
+            PrefabAssets,
+            ConfigAssets,
+            MapDefAssets,
+            SpellDefAssets,
+            MapAssets
+        );
+        app.add_plugins(icons::IconsPlugin);
+        app.init_state::<LoadingStep>();
+        app.add_sub_state(AppState::LoadingMap, MapLoadingStep::Variants);
+        app.init_resource::<variants::Variants>();
         app.add_loading_state(
-            LoadingState::new(AppState::Loading)
+            LoadingState::new(LoadingStep::Assets)
                 .load_collection::<FontAssets>()
                 .load_collection::<GlbAssets>()
                 .load_collection::<ImageAssets>()
-                .continue_to_state(AppState::InGame),
+                .load_collection::<PrefabAssets>()
+                .load_collection::<ConfigAssets>()
+                .load_collection::<AudioAssets>()
+                .load_collection::<MapDefAssets>()
+                .load_collection::<SpellDefAssets>()
+                .load_collection::<IconAssets>()
+                .init_resource::<IconAtlas>()
+                .continue_to_state(LoadingStep::Done),
         );
+        app.add_loading_state(
+            LoadingState::new(MapLoadingStep::Assets)
+                .load_collection::<MapAssets>()
+                .continue_to_state(MapLoadingStep::Done),
+        );
+        app.add_systems(OnEnter(LoadingStep::Variants), variants::request);
+        app.add_systems(Update, variants::register.run_if(in_state(LoadingStep::Variants)));
+        app.add_systems(OnEnter(LoadingStep::Done), (variants::release, continue_to(AppState::MainMenu)));
+        app.add_systems(OnEnter(MapLoadingStep::Variants), request_map);
+        app.add_systems(Update, register_map.run_if(in_state(MapLoadingStep::Variants)));
+        app.add_systems(OnEnter(MapLoadingStep::Done), (variants::release, continue_to(AppState::InGame)));
+        app.add_systems(PreUpdate, extras::markers);
     }
 }
 
+/// Steps of [`AppState::Loading`], the variants of the assets are resolved before the assets are loaded.
+#[derive(States, Default, Clone, Copy, Eq, PartialEq, Debug, Hash)]
+enum LoadingStep {
+    #[default]
+    Variants,
+    Assets,
+    Done,
+}
+
+/// Steps of [`AppState::LoadingMap`], see [`LoadingStep`].
+#[derive(States, Default, Clone, Copy, Eq, PartialEq, Debug, Hash)]
+enum MapLoadingStep {
+    #[default]
+    Disabled,
+    Variants,
+    Assets,
+    Done,
+}
+
+fn continue_to(state: AppState) -> impl Fn(ResMut<NextState<AppState>>) {
+    move |mut next_state| next_state.set(state.clone())
+}
+
 #[derive(AssetCollection, Resource, Default, Reflect)]
 #[reflect(Resource)]
 pub struct FontAssets {
@@ -37,19 +109,19 @@ pub struct FontAssets {
 #[derive(AssetCollection, Resource, Default, Reflect)]
 #[reflect(Resource)]
 pub struct GlbAssets {
-    #[asset(path = "glb/monkey.glb#Scene0")]
+    #[asset(key = "glb.monkey")]
     pub monkey: Handle<Scene>,
 
-    #[asset(path = "glb/fox.glb#Scene0")]
+    #[asset(key = "glb.fox")]
     pub fox: Handle<Scene>,
 
-    #[asset(path = "glb/frog.glb#Scene0")]
+    #[asset(key = "glb.frog")]
     pub frog: Handle<Scene>,
 
-    #[asset(path = "glb/ramp.glb#Scene0")]
+    #[asset(key = "glb.ramp")]
     pub ramp: Handle<Scene>,
 
-    #[asset(path = "glb/crystal.glb#Scene0")]
+    #[asset(key = "glb.crystal")]
     pub crystal: Handle<Scene>,
 }
 
@@ -59,6 +131,121 @@ pub struct ImageAssets {
     #[asset(path = "images/bevy.png")]
     pub bevy: Handle<Image>,
 
-    #[asset(path = "images/proto_dark.png")]
+    #[asset(key = "images.proto_dark")]
     pub proto_dark: Handle<Image>,
 }
+
+#[derive(AssetCollection, Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct PrefabAssets {
+    #[asset(path = "prefabs", collection(typed))]
+    pub prefabs: Vec<Handle<Prefab>>,
+}
+
+#[derive(AssetCollection, Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct ConfigAssets {
+    #[asset(path = "config/game.config.ron")]
+    pub game: Handle<GameConfig>,
+
+    #[asset(path = "config/default.tech.ron")]
+    pub tech_tree: Handle<TechTree>,
+}
+
+#[derive(AssetCollection, Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct MapDefAssets {
+    #[asset(path = "maps", collection(typed))]
+    pub maps: Vec<Handle<MapDef>>,
+}
+
+#[derive(AssetCollection, Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SpellDefAssets {
+    #[asset(path = "spells", collection(typed))]
+    pub spells: Vec<Handle<SpellDef>>,
+}
+
+/// Assets of the [`SelectedMap`], loaded in [`AppState::LoadingMap`].
+#[derive(AssetCollection, Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct MapAssets {
+    #[asset(key = "map.floor")]
+    pub floor: Handle<Image>,
+
+    #[asset(key = "map.terrain_layers", collection(typed))]
+    pub terrain_layers: Vec<Handle<Image>>,
+
+    /// Empty without a terrain, a collection so the key of a previous map is always replaced.
+    #[asset(key = "map.splat_map", collection(typed))]
+    pub splat_map: Vec<Handle<Image>>,
+
+    /// Empty without a density mask for the map's details.
+    #[asset(key = "map.density_mask", collection(typed))]
+    pub density_mask: Vec<Handle<Image>>,
+
+    #[asset(key = "map.scenes", collection(typed))]
+    pub scenes: Vec<Handle<Scene>>,
+}
+
+/// Paths of the assets of a map.
+fn map_paths(def: &MapDef) -> impl Iterator<Item = &String> {
+    let terrain = def.terrain.iter().flat_map(|terrain| terrain.layers.iter().chain([&terrain.splat_map]));
+    let density_mask = def.details.iter().flat_map(|details| details.density_mask.as_ref());
+    [&def.floor].into_iter().chain(terrain).chain(density_mask).chain(&def.scenes)
+}
+
+/// Requests the variants of the assets of the [`SelectedMap`].
+fn request_map(
+    selected: Res<SelectedMap>,
+    defs: Res<Assets<MapDef>>,
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+    mut variants: ResMut<variants::Variants>,
+) {
+    let def = defs.get(&**selected).expect("selected map should be loaded");
+    for path in map_paths(def) {
+        variants.request(&asset_server, settings.graphics.quality, path);
+    }
+}
+
+/// Registers the dynamic keys of [`MapAssets`] from the [`SelectedMap`] once its variants are resolved.
+fn register_map(
+    selected: Res<SelectedMap>,
+    defs: Res<Assets<MapDef>>,
+    asset_server: Res<AssetServer>,
+    mut variants: ResMut<variants::Variants>,
+    mut dynamic_assets: ResMut<DynamicAssets>,
+    mut next_step: ResMut<NextState<MapLoadingStep>>,
+) {
+    if !variants.poll(&asset_server) {
+        return;
+    }
+    let def = defs.get(&**selected).expect("selected map should be loaded");
+    let path = |path: &String| variants.get(path);
+
+    dynamic_assets.register_asset("map.floor", Box::new(StandardDynamicAsset::File { path: path(&def.floor) }));
+    let terrain = def.terrain.as_ref();
+    dynamic_assets.register_asset(
+        "map.terrain_layers",
+        Box::new(StandardDynamicAsset::Files {
+            paths: terrain.map_or_else(Vec::new, |terrain| terrain.layers.iter().map(path).collect()),
+        }),
+    );
+    dynamic_assets.register_asset(
+        "map.splat_map",
+        Box::new(StandardDynamicAsset::Files {
+            paths: terrain.map(|terrain| path(&terrain.splat_map)).into_iter().collect(),
+        }),
+    );
+    let density_mask = def.details.as_ref().and_then(|details| details.density_mask.as_ref());
+    dynamic_assets.register_asset(
+        "map.density_mask",
+        Box::new(StandardDynamicAsset::Files { paths: density_mask.map(path).into_iter().collect() }),
+    );
+    dynamic_assets.register_asset(
+        "map.scenes",
+        Box::new(StandardDynamicAsset::Files { paths: def.scenes.iter().map(path).collect() }),
+    );
+    next_step.set(MapLoadingStep::Assets);
+}
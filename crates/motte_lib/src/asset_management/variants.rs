@@ -0,0 +1,136 @@
+//! Picks asset variants based on [`crate::settings::Quality`], variants are stored next to the original as
+//! `{name}.{quality}.{ext}`, e.g. `images/proto_dark.low.png`. Falls back to the next higher quality if a variant
+//! doesn't exist.
+
+use bevy::asset::{LoadState, LoadedUntypedAsset};
+use bevy_asset_loader::{dynamic_asset::DynamicAssets, standard_dynamic_asset::StandardDynamicAsset};
+
+use super::LoadingStep;
+use crate::{
+    prelude::*,
+    settings::{Quality, Settings},
+};
+
+/// Dynamic asset keys with variants & the path of the original (highest quality) asset.
+const VARIANTS: &[(&str, &str)] = &[
+    ("images.proto_dark", "images/proto_dark.png"),
+    ("glb.monkey", "glb/monkey.glb#Scene0"),
+    ("glb.fox", "glb/fox.glb#Scene0"),
+    ("glb.frog", "glb/frog.glb#Scene0"),
+    ("glb.ramp", "glb/ramp.glb#Scene0"),
+    ("glb.crystal", "glb/crystal.glb#Scene0"),
+];
+
+/// Variants of asset paths for the current [`crate::settings::Quality`], changes to the quality are applied on the
+/// next start. A variant is resolved by loading it through the [`AssetServer`] & skipped if it fails to load, so
+/// resolving never blocks on the asset source.
+#[derive(Resource, Default)]
+pub(super) struct Variants {
+    /// Resolved path of each requested path.
+    resolved: HashMap<String, String>,
+    pending: Vec<Pending>,
+    /// Handles of the resolved variants, kept until their collection is loaded so they aren't loaded twice.
+    loaded: Vec<Handle<LoadedUntypedAsset>>,
+}
+
+/// A requested path being resolved.
+struct Pending {
+    path: String,
+    /// Variant being loaded.
+    variant: String,
+    handle: Handle<LoadedUntypedAsset>,
+    /// Variants left to try if it fails, in order of preference.
+    fallbacks: std::vec::IntoIter<String>,
+}
+
+impl Variants {
+    /// Starts resolving the variant of `path` for `quality`, unless it's already resolved or being resolved.
+    pub(super) fn request(&mut self, asset_server: &AssetServer, quality: Quality, path: &str) {
+        if self.resolved.contains_key(path) || self.pending.iter().any(|pending| pending.path == path) {
+            return;
+        }
+        let variants = quality.fallbacks().filter_map(|quality| quality.suffix()).map(|suffix| variant(path, suffix));
+        let mut fallbacks = variants.collect_vec().into_iter();
+        match fallbacks.next() {
+            Some(variant) => self.pending.push(Pending {
+                path: path.to_owned(),
+                handle: asset_server.load_untyped(variant.clone()),
+                variant,
+                fallbacks,
+            }),
+            None => {
+                self.resolved.insert(path.to_owned(), path.to_owned());
+            }
+        }
+    }
+
+    /// Resolved variant of `path`, or `path` itself if there are no variants.
+    pub(super) fn get(&self, path: &str) -> String {
+        self.resolved.get(path).cloned().unwrap_or_else(|| path.to_owned())
+    }
+
+    /// Advances the variants being loaded, returns true once every requested path is resolved.
+    pub(super) fn poll(&mut self, asset_server: &AssetServer) -> bool {
+        let (resolved, loaded) = (&mut self.resolved, &mut self.loaded);
+        self.pending.retain_mut(|pending| match asset_server.load_state(pending.handle.id()) {
+            LoadState::Loaded => {
+                resolved.insert(pending.path.clone(), pending.variant.clone());
+                loaded.push(pending.handle.clone());
+                false
+            }
+            LoadState::Failed => match pending.fallbacks.next() {
+                Some(variant) => {
+                    pending.handle = asset_server.load_untyped(variant.clone());
+                    pending.variant = variant;
+                    true
+                }
+                None => {
+                    resolved.insert(pending.path.clone(), pending.path.clone());
+                    false
+                }
+            },
+            _ => true,
+        });
+        self.pending.is_empty()
+    }
+}
+
+/// Requests the variants of the assets loaded in [`crate::app_state::AppState::Loading`].
+pub(super) fn request(settings: Res<Settings>, asset_server: Res<AssetServer>, mut variants: ResMut<Variants>) {
+    for &(_, path) in VARIANTS {
+        variants.request(&asset_server, settings.graphics.quality, path);
+    }
+}
+
+/// Registers the resolved variants once every variant is resolved & moves on to loading the assets.
+pub(super) fn register(
+    asset_server: Res<AssetServer>,
+    mut variants: ResMut<Variants>,
+    mut dynamic_assets: ResMut<DynamicAssets>,
+    mut next_step: ResMut<NextState<LoadingStep>>,
+) {
+    if !variants.poll(&asset_server) {
+        return;
+    }
+    for &(key, path) in VARIANTS {
+        dynamic_assets.register_asset(key, Box::new(StandardDynamicAsset::File { path: variants.get(path) }));
+    }
+    next_step.set(LoadingStep::Assets);
+}
+
+/// Drops the handles kept while resolving, the assets are held by their collections.
+pub(super) fn release(mut variants: ResMut<Variants>) {
+    variants.loaded.clear();
+}
+
+fn variant(path: &str, suffix: &str) -> String {
+    let (path, label) = path.split_once('#').map_or((path, None), |(path, label)| (path, Some(label)));
+    let variant = match path.rsplit_once('.') {
+        Some((stem, extension)) => format!("{stem}.{suffix}.{extension}"),
+        None => format!("{path}.{suffix}"),
+    };
+    match label {
+        Some(label) => format!("{variant}#{label}"),
+        None => variant,
+    }
+}
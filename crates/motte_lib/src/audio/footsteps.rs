@@ -0,0 +1,59 @@
+//! Footsteps of [`Moving`] units, a step every few radii travelled on the ground with a sound depending on the
+//! [`Surface`](crate::navigation::flow_field::fields::surface::Surface) below.
+
+use super::{PlaySound, Sound};
+use crate::{
+    movement::motor::{Airborne, Moving},
+    navigation::{
+        agent::Agent,
+        flow_field::{fields::surface::SurfaceField, layout::FieldLayout},
+    },
+    prelude::*,
+};
+
+/// Distance between footsteps in radii of the agent.
+const STRIDE_RADII: f32 = 3.0;
+
+pub(super) struct FootstepsPlugin;
+
+impl Plugin for FootstepsPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Footsteps);
+        app.add_systems(Update, step.run_if(resource_exists::<SurfaceField>));
+    }
+}
+
+/// Distance travelled on the ground since the last footstep.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct Footsteps(f32);
+
+#[allow(clippy::type_complexity)]
+fn step(
+    mut commands: Commands,
+    mut units: Query<
+        (Entity, &Agent, &GlobalTransform, &LinearVelocity, Option<&mut Footsteps>),
+        (With<Moving>, Without<Airborne>),
+    >,
+    surfaces: Res<SurfaceField>,
+    layout: Res<FieldLayout>,
+    time: Res<Time>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for (entity, agent, transform, velocity, footsteps) in &mut units {
+        let Some(mut footsteps) = footsteps else {
+            commands.entity(entity).insert(Footsteps::default());
+            continue;
+        };
+        let stride = agent.radius() * STRIDE_RADII;
+        footsteps.0 += velocity.xz().length() * time.delta_seconds();
+        if footsteps.0 < stride {
+            continue;
+        }
+        footsteps.0 %= stride;
+
+        let position = transform.translation();
+        let surface = surfaces.surface_at(&layout, position.xz());
+        sounds.send(PlaySound { sound: Sound::Footstep(surface), position: Some(position) });
+    }
+}
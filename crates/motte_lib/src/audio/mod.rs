@@ -0,0 +1,270 @@
+//! Audio
+//!
+//! Positional sounds are attenuated by their distance on the ground to where the camera looks instead of to the
+//! camera itself, which is high above the ground & about as far from everything on screen. Each kind of [`Sound`]
+//! plays at most [`Sound::max_voices`] at once, the nearest sounds of a frame get the free voices.
+use std::mem::{discriminant, Discriminant};
+
+use bevy::audio::{SpatialScale, Volume};
+use bevy_asset_loader::asset_collection::AssetCollection;
+
+use self::music::MusicManifest;
+use crate::{
+    events::GameEvent,
+    navigation::flow_field::fields::surface::Surface,
+    player::camera::MainCamera,
+    prelude::*,
+    settings::{AudioSettings, Settings, SettingsChanged},
+};
+
+mod footsteps;
+mod music;
+
+/// Scale from world units to audio units, the camera is far away from the action.
+const SPATIAL_SCALE: SpatialScale = SpatialScale::new(0.05);
+/// Sounds within this distance on the ground of the camera's focus play at full volume.
+const ATTENUATION_NEAR: f32 = 12.0;
+/// Sounds fade out until this distance on the ground of the camera's focus & aren't played further away.
+const ATTENUATION_FAR: f32 = 48.0;
+
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(AudioAssets, AudioChannel, Sound, PlaySound, Voice);
+        app.add_event::<PlaySound>();
+        app.add_plugins((footsteps::FootstepsPlugin, music::MusicPlugin));
+        app.add_systems(Update, (listener, volume.run_if(on_event::<SettingsChanged>())));
+        app.add_systems(PostUpdate, (game_events, play).chain());
+    }
+}
+
+#[derive(AssetCollection, Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct AudioAssets {
+    #[asset(path = "audio/attack.wav")]
+    pub attack: Handle<AudioSource>,
+
+    #[asset(path = "audio/death.wav")]
+    pub death: Handle<AudioSource>,
+
+    #[asset(path = "audio/spell_cast.wav")]
+    pub spell_cast: Handle<AudioSource>,
+
+    #[asset(path = "audio/ui_click.wav")]
+    pub ui_click: Handle<AudioSource>,
+
+    #[asset(path = "audio/footstep_grass.wav")]
+    pub footstep_grass: Handle<AudioSource>,
+
+    #[asset(path = "audio/footstep_road.wav")]
+    pub footstep_road: Handle<AudioSource>,
+
+    #[asset(path = "audio/footstep_water.wav")]
+    pub footstep_water: Handle<AudioSource>,
+
+    #[asset(path = "audio/music.manifest.ron")]
+    pub music: Handle<MusicManifest>,
+}
+
+/// Volume channel of an audio entity, see [`AudioSettings`].
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+#[allow(unused)]
+pub enum AudioChannel {
+    Music,
+    Effects,
+    Ui,
+}
+
+impl AudioChannel {
+    pub fn volume(self, settings: &AudioSettings) -> f32 {
+        settings.master
+            * match self {
+                AudioChannel::Music => settings.music,
+                AudioChannel::Effects => settings.effects,
+                AudioChannel::Ui => settings.ui,
+            }
+    }
+}
+
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(unused)]
+pub enum Sound {
+    Attack,
+    Death,
+    SpellCast,
+    UiClick,
+    Footstep(Surface),
+}
+
+impl Sound {
+    /// Nothing is heard stepping on [`Surface::Void`].
+    pub fn handle(self, assets: &AudioAssets) -> Option<Handle<AudioSource>> {
+        match self {
+            Sound::Attack => Some(assets.attack.clone()),
+            Sound::Death => Some(assets.death.clone()),
+            Sound::SpellCast => Some(assets.spell_cast.clone()),
+            Sound::UiClick => Some(assets.ui_click.clone()),
+            Sound::Footstep(Surface::Grass) => Some(assets.footstep_grass.clone()),
+            Sound::Footstep(Surface::Road) => Some(assets.footstep_road.clone()),
+            Sound::Footstep(Surface::Water) => Some(assets.footstep_water.clone()),
+            Sound::Footstep(Surface::Void) => None,
+        }
+    }
+
+    pub fn channel(self) -> AudioChannel {
+        match self {
+            Sound::Attack | Sound::Death | Sound::SpellCast | Sound::Footstep(_) => AudioChannel::Effects,
+            Sound::UiClick => AudioChannel::Ui,
+        }
+    }
+
+    /// Sounds of this kind that can play at once, footsteps on every [`Surface`] share their voices.
+    pub fn max_voices(self) -> usize {
+        match self {
+            Sound::Footstep(_) => 8,
+            Sound::Attack => 6,
+            Sound::Death | Sound::SpellCast => 4,
+            Sound::UiClick => 2,
+        }
+    }
+
+    fn kind(self) -> Discriminant<Sound> {
+        discriminant(&self)
+    }
+}
+
+/// Plays a one-shot [`Sound`], spatialized if a position is given.
+#[derive(Event, Reflect, Clone, Copy, Debug)]
+pub struct PlaySound {
+    pub sound: Sound,
+    pub position: Option<Vec3>,
+}
+
+/// A playing sound & its attenuation.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Voice {
+    pub sound: Sound,
+    pub gain: f32,
+}
+
+/// Volume of a sound `distance` away on the ground from the camera's focus.
+fn attenuation(distance: f32) -> f32 {
+    let fade = 1.0 - ((distance - ATTENUATION_NEAR) / (ATTENUATION_FAR - ATTENUATION_NEAR)).clamp01();
+    fade * fade
+}
+
+/// Where the camera looks at the ground.
+fn focus(camera: &GlobalTransform) -> Vec3 {
+    let forward = camera.forward();
+    if forward.y < 0.0 {
+        camera.translation() + forward * (-camera.translation().y / forward.y)
+    } else {
+        camera.translation()
+    }
+}
+
+fn listener(mut commands: Commands, cameras: Query<Entity, (With<MainCamera>, Without<SpatialListener>)>) {
+    for entity in &cameras {
+        commands.entity(entity).insert(SpatialListener::default());
+    }
+}
+
+fn game_events(
+    mut game_events: EventReader<GameEvent>,
+    mut sounds: EventWriter<PlaySound>,
+    transforms: Query<&GlobalTransform>,
+) {
+    for event in game_events.read() {
+        let (sound, entity) = match *event {
+            GameEvent::Attacked { attacker, .. } => (Sound::Attack, attacker),
+            GameEvent::Died { entity } => (Sound::Death, entity),
+            GameEvent::SpellCast { caster, .. } => (Sound::SpellCast, caster),
+            _ => continue,
+        };
+        let position = transforms.get(entity).ok().map(GlobalTransform::translation);
+        sounds.send(PlaySound { sound, position });
+    }
+}
+
+fn play(
+    mut commands: Commands,
+    mut events: EventReader<PlaySound>,
+    assets: Option<Res<AudioAssets>>,
+    settings: Res<Settings>,
+    voices: Query<&Voice>,
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+) {
+    let Some(assets) = assets else {
+        events.clear();
+        return;
+    };
+    if events.is_empty() {
+        return;
+    }
+
+    let focus = cameras.get_single().ok().map(focus);
+    let mut sounds = events
+        .read()
+        .map(|&PlaySound { sound, position }| {
+            let gain = match (position, focus) {
+                (Some(position), Some(focus)) => attenuation(position.xz().distance(focus.xz())),
+                _ => 1.0,
+            };
+            (sound, position, gain)
+        })
+        .filter(|&(.., gain)| gain > 0.0)
+        .collect_vec();
+    // The loudest sounds get the free voices.
+    sounds.sort_by(|(.., a), (.., b)| b.total_cmp(a));
+
+    let mut playing = HashMap::<Discriminant<Sound>, usize>::new();
+    for voice in &voices {
+        *playing.entry(voice.sound.kind()).or_default() += 1;
+    }
+
+    for (sound, position, gain) in sounds {
+        let voices = playing.entry(sound.kind()).or_default();
+        if *voices >= sound.max_voices() {
+            continue;
+        }
+        let Some(source) = sound.handle(&assets) else {
+            continue;
+        };
+        *voices += 1;
+
+        let channel = sound.channel();
+        let mut playback = PlaybackSettings::DESPAWN.with_volume(Volume::new(channel.volume(&settings.audio) * gain));
+        if position.is_some() {
+            playback = playback.with_spatial(true).with_spatial_scale(SPATIAL_SCALE);
+        }
+
+        let mut entity = commands.spawn((
+            Name::new(format!("sound {sound:?}")),
+            channel,
+            Voice { sound, gain },
+            AudioBundle { source, settings: playback },
+        ));
+
+        if let Some(position) = position {
+            entity.insert(SpatialBundle::from_transform(Transform::from_translation(position)));
+        }
+    }
+}
+
+fn volume(
+    settings: Res<Settings>,
+    sinks: Query<(&AudioChannel, Option<&Voice>, Option<&AudioSink>, Option<&SpatialAudioSink>)>,
+) {
+    for (channel, voice, sink, spatial_sink) in &sinks {
+        let volume = channel.volume(&settings.audio) * voice.map_or(1.0, |voice| voice.gain);
+        if let Some(sink) = sink {
+            sink.set_volume(volume);
+        }
+        if let Some(sink) = spatial_sink {
+            sink.set_volume(volume);
+        }
+    }
+}
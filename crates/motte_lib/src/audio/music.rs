@@ -0,0 +1,234 @@
+//! Adaptive music, an exploration & a combat stem loop together & crossfade by the threat on the player's units: the
+//! number of enemies that attacked one of them recently & are still near one. Stingers play over the stems when a
+//! wave spawns & when the game is won or lost. Everything is configured by the [`MusicManifest`] in
+//! `assets/audio/music.manifest.ron`, stems & stingers left out of the manifest are silent.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::audio::Volume;
+use bevy_common_assets::ron::RonAssetPlugin;
+use serde::Deserialize;
+
+use super::{AudioAssets, AudioChannel};
+use crate::{
+    app_state::{AppState, InGameState},
+    cleanup::StateScoped,
+    economy::Team,
+    events::GameEvent,
+    navigation::agent::Agent,
+    objectives::{Objectives, Outcome},
+    prelude::*,
+    settings::{AudioSettings, Settings},
+};
+
+pub(super) struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(MusicManifest, MusicLayer);
+        app.add_plugins(RonAssetPlugin::<MusicManifest>::new(&["manifest.ron"]));
+        app.add_systems(OnEnter(AppState::InGame), start);
+        app.add_systems(OnEnter(InGameState::GameOver), game_over);
+        app.add_systems(
+            Update,
+            (threat, crossfade.after(super::volume), stingers)
+                .chain()
+                .run_if(in_state(AppState::InGame))
+                .run_if(resource_exists::<Music>),
+        );
+        app.add_systems(OnExit(AppState::InGame), |mut commands: Commands| commands.remove_resource::<Music>());
+    }
+}
+
+#[derive(Asset, Reflect, Deserialize, Clone, Debug)]
+pub struct MusicManifest {
+    #[serde(default)]
+    pub exploration: Option<String>,
+    #[serde(default)]
+    pub combat: Option<String>,
+    #[serde(default)]
+    pub wave_start: Option<String>,
+    #[serde(default)]
+    pub victory: Option<String>,
+    #[serde(default)]
+    pub defeat: Option<String>,
+    /// Seconds to fully crossfade between the stems.
+    pub crossfade: f32,
+    /// Aggroed enemies count towards the threat while within this distance of a unit of the player.
+    pub threat_radius: f32,
+    /// Seconds an enemy stays aggroed after attacking a unit of the player.
+    pub aggro_seconds: f32,
+    /// Threat at which only the combat stem is heard.
+    pub max_threat: f32,
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum MusicLayer {
+    Exploration,
+    Combat,
+}
+
+#[derive(Resource)]
+struct Music {
+    manifest: MusicManifest,
+    /// Seconds left of the aggro of each enemy.
+    aggro: HashMap<Entity, f32>,
+    threat: f32,
+    /// Crossfade from the exploration (0) to the combat (1) stem.
+    mix: f32,
+    /// Volume of both stems, faded out when the game is over.
+    fade: f32,
+    ended: bool,
+    wave_start: Option<Handle<AudioSource>>,
+    victory: Option<Handle<AudioSource>>,
+    defeat: Option<Handle<AudioSource>>,
+}
+
+fn start(
+    mut commands: Commands,
+    assets: Res<AudioAssets>,
+    manifests: Res<Assets<MusicManifest>>,
+    server: Res<AssetServer>,
+) {
+    let Some(manifest) = manifests.get(&assets.music) else {
+        warn!("music manifest isn't loaded");
+        return;
+    };
+
+    let stems = [(MusicLayer::Exploration, &manifest.exploration), (MusicLayer::Combat, &manifest.combat)];
+    for (layer, path) in stems.into_iter().filter_map(|(layer, path)| Some((layer, path.as_ref()?))) {
+        commands.spawn((
+            Name::new(format!("music {layer:?}")),
+            layer,
+            AudioChannel::Music,
+            StateScoped(AppState::InGame),
+            AudioBundle {
+                source: server.load(path.clone()),
+                settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+            },
+        ));
+    }
+
+    let load = |path: &Option<String>| path.clone().map(|path| server.load(path));
+    commands.insert_resource(Music {
+        aggro: HashMap::new(),
+        threat: 0.0,
+        mix: 0.0,
+        fade: 1.0,
+        ended: false,
+        wave_start: load(&manifest.wave_start),
+        victory: load(&manifest.victory),
+        defeat: load(&manifest.defeat),
+        manifest: manifest.clone(),
+    });
+}
+
+fn threat(
+    mut music: ResMut<Music>,
+    mut events: EventReader<GameEvent>,
+    units: Query<(&GlobalTransform, Option<&Team>), With<Agent>>,
+    time: Res<Time>,
+) {
+    let music = &mut *music;
+    let hostile = |entity: Entity| units.get(entity).is_ok_and(|(_, team)| team != Some(&Team::PLAYER));
+
+    let delta = time.delta_seconds();
+    music.aggro.retain(|_, seconds| {
+        *seconds -= delta;
+        *seconds > 0.0
+    });
+    for event in events.read() {
+        match *event {
+            GameEvent::Attacked { attacker, target } if hostile(attacker) && !hostile(target) => {
+                music.aggro.insert(attacker, music.manifest.aggro_seconds);
+            }
+            GameEvent::Died { entity } => {
+                music.aggro.remove(&entity);
+            }
+            _ => {}
+        }
+    }
+
+    let radius = music.manifest.threat_radius;
+    let players = units
+        .iter()
+        .filter(|(_, team)| *team == Some(&Team::PLAYER))
+        .map(|(transform, _)| transform.translation().xz())
+        .collect_vec();
+    let threat = music
+        .aggro
+        .keys()
+        .filter_map(|&enemy| units.get(enemy).ok())
+        .filter(|(transform, _)| {
+            let position = transform.translation().xz();
+            players.iter().any(|player| player.distance_squared(position) <= radius * radius)
+        })
+        .count();
+    music.threat = threat as f32;
+}
+
+/// Moves the mix towards the threat, the stems are faded with equal power so the loudness stays the same.
+fn crossfade(
+    mut music: ResMut<Music>,
+    layers: Query<(&MusicLayer, &AudioSink)>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let music = &mut *music;
+    let step = time.delta_seconds() / music.manifest.crossfade.max(f32::EPSILON);
+    let mix = (music.threat / music.manifest.max_threat.max(f32::EPSILON)).clamp01();
+    music.mix += (mix - music.mix).clamp(-step, step);
+    let fade = if music.ended { 0.0 } else { 1.0 };
+    music.fade += (fade - music.fade).clamp(-step, step);
+
+    let volume = AudioChannel::Music.volume(&settings.audio) * music.fade;
+    let angle = music.mix * FRAC_PI_2;
+    for (layer, sink) in &layers {
+        let gain = match layer {
+            MusicLayer::Exploration => angle.cos(),
+            MusicLayer::Combat => angle.sin(),
+        };
+        sink.set_volume(volume * gain);
+    }
+}
+
+fn stingers(mut commands: Commands, music: Res<Music>, mut events: EventReader<GameEvent>, settings: Res<Settings>) {
+    let waves = events.read().filter(|event| matches!(event, GameEvent::WaveSpawned { .. })).count();
+    if waves > 0 {
+        stinger(&mut commands, music.wave_start.clone(), &settings.audio);
+    }
+}
+
+fn game_over(
+    mut commands: Commands,
+    music: Option<ResMut<Music>>,
+    objectives: Option<Res<Objectives>>,
+    settings: Res<Settings>,
+) {
+    let Some(mut music) = music else {
+        return;
+    };
+    music.ended = true;
+    let source = match objectives.and_then(|objectives| objectives.outcome()) {
+        Some(Outcome::Victory) => music.victory.clone(),
+        Some(Outcome::Defeat) => music.defeat.clone(),
+        None => None,
+    };
+    stinger(&mut commands, source, &settings.audio);
+}
+
+fn stinger(commands: &mut Commands, source: Option<Handle<AudioSource>>, settings: &AudioSettings) {
+    let Some(source) = source else {
+        return;
+    };
+    commands.spawn((
+        Name::new("music stinger"),
+        AudioChannel::Music,
+        StateScoped(AppState::InGame),
+        AudioBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(AudioChannel::Music.volume(settings))),
+        },
+    ));
+}
@@ -0,0 +1,123 @@
+//! Every number of resolving a hit is computed here, so balance changes are made in one place. Hits are resolved
+//! every fixed tick & rolls use the [`GameRng`](crate::determinism::GameRng), so they're the same on every peer of a
+//! lockstep game & in replays of a seed.
+
+use crate::prelude::*;
+
+/// Damage multiplier of critical hits of units without a [`CritMultiplier`](super::CritMultiplier).
+pub const DEFAULT_CRIT_MULTIPLIER: f32 = 1.5;
+
+/// Hits land at least this often regardless of evasion.
+pub const MIN_HIT_CHANCE: f32 = 0.05;
+
+/// Stats of the attacker & target involved in a hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attack {
+    /// Damage before any multipliers.
+    pub base: f32,
+    /// Multiplier of the attacker's [`Affinity`](crate::spells::Affinity) for the element of the attack.
+    pub affinity: f32,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    pub evasion: f32,
+}
+
+impl Default for Attack {
+    fn default() -> Self {
+        Self { base: 0.0, affinity: 1.0, crit_chance: 0.0, crit_multiplier: DEFAULT_CRIT_MULTIPLIER, evasion: 0.0 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Miss,
+    Hit {
+        damage: f32,
+        critical: bool,
+        /// Crit multiplier of critical hits & 1 otherwise, on-hit effects scale by it too.
+        multiplier: f32,
+    },
+}
+
+/// Chance of a hit landing on a target with `evasion`.
+pub fn hit_chance(evasion: f32) -> f32 {
+    (1.0 - evasion).clamp(MIN_HIT_CHANCE, 1.0)
+}
+
+/// Rolls `chance` in [0..1], always consumes one number of `rng` so the sequence doesn't depend on the chance.
+pub fn roll(rng: &mut impl Rng, chance: f32) -> bool {
+    let value: f32 = rng.gen();
+    value < chance.clamp01()
+}
+
+/// Final damage of a hit, crits multiply after the affinity. Never negative.
+pub fn damage(base: f32, affinity: f32, critical: bool, crit_multiplier: f32) -> f32 {
+    let crit = if critical { crit_multiplier.max(1.0) } else { 1.0 };
+    (base * affinity.max(0.0) * crit).max(0.0)
+}
+
+/// Rolls whether the attack hits & crits, then composes its damage.
+pub fn resolve(rng: &mut impl Rng, attack: Attack) -> Outcome {
+    if !roll(rng, hit_chance(attack.evasion)) {
+        return Outcome::Miss;
+    }
+    let critical = roll(rng, attack.crit_chance);
+    let damage = damage(attack.base, attack.affinity, critical, attack.crit_multiplier);
+    let multiplier = if critical { attack.crit_multiplier.max(1.0) } else { 1.0 };
+    Outcome::Hit { damage, critical, multiplier }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::mock::StepRng;
+
+    use super::*;
+
+    /// Rolls of `0.0`, every chance above zero succeeds.
+    fn lucky() -> StepRng {
+        StepRng::new(0, 0)
+    }
+
+    /// Rolls just below `1.0`, only certain chances succeed.
+    fn unlucky() -> StepRng {
+        StepRng::new(u64::MAX, 0)
+    }
+
+    #[test]
+    fn crits_multiply_damage() {
+        let attack = Attack { base: 10.0, crit_chance: 0.5, crit_multiplier: 2.0, ..default() };
+        assert_eq!(resolve(&mut lucky(), attack), Outcome::Hit { damage: 20.0, critical: true, multiplier: 2.0 });
+
+        let attack = Attack { crit_chance: 0.0, ..attack };
+        assert_eq!(resolve(&mut lucky(), attack), Outcome::Hit { damage: 10.0, critical: false, multiplier: 1.0 });
+
+        // Multipliers below one don't make crits weaker than normal hits.
+        assert_eq!(damage(10.0, 1.0, true, 0.5), 10.0);
+    }
+
+    #[test]
+    fn evasion_misses() {
+        let attack = Attack { base: 10.0, evasion: 0.5, ..default() };
+        assert_eq!(resolve(&mut unlucky(), attack), Outcome::Miss);
+        assert!(matches!(resolve(&mut lucky(), attack), Outcome::Hit { .. }));
+
+        assert_eq!(hit_chance(1.0), MIN_HIT_CHANCE);
+        assert_eq!(hit_chance(-1.0), 1.0);
+        assert!(matches!(resolve(&mut lucky(), Attack { evasion: 1.0, ..attack }), Outcome::Hit { .. }));
+    }
+
+    #[test]
+    fn affinity_scales_damage_before_crits() {
+        let attack = Attack { base: 10.0, affinity: 1.5, crit_chance: 1.0, crit_multiplier: 2.0, ..default() };
+        assert_eq!(resolve(&mut lucky(), attack), Outcome::Hit { damage: 30.0, critical: true, multiplier: 2.0 });
+        assert_eq!(damage(10.0, -1.0, false, 1.0), 0.0);
+    }
+
+    #[test]
+    fn rolls_consume_one_number() {
+        let (mut a, mut b) = (StepRng::new(0, 1 << 40), StepRng::new(0, 1 << 40));
+        roll(&mut a, 0.0);
+        roll(&mut b, 1.0);
+        assert_eq!(a.next_u64(), b.next_u64());
+    }
+}
@@ -0,0 +1,225 @@
+//! Combat stats of units & the damage pipeline, the math of resolving a hit lives in [`formula`]. All [`Damage`] is
+//! applied by the same system so every source interacts with [`Shield`]s the same way: a shield absorbs damage before
+//! [`Health`] & sends [`ShieldBroken`] when a hit depletes it.
+
+use crate::{
+    events::GameEvent,
+    prelude::*,
+    simulation::GameplaySystems,
+    stats::{
+        pool::{Pool, PoolBundle},
+        stat::{Stat, StatPlugin},
+    },
+};
+
+pub mod formula;
+#[cfg(feature = "presentation")]
+pub mod text;
+
+/// [`Health`] of units spawned without a specific amount.
+pub const DEFAULT_HEALTH: f32 = 100.0;
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(ShieldDecay);
+        app.add_event::<Damage>().add_event::<ShieldBroken>();
+        app.add_plugins((
+            StatPlugin::<CritChance>::default(),
+            StatPlugin::<CritMultiplier>::default(),
+            StatPlugin::<Evasion>::default(),
+            StatPlugin::<Health>::default(),
+            StatPlugin::<Shield>::default(),
+        ));
+        #[cfg(feature = "net")]
+        {
+            use crate::net::AppNetExt;
+            app.replicate_pool::<Health>().replicate_pool::<Shield>();
+        }
+        app.add_systems(FixedUpdate, (decay, damage).chain().in_set(GameplaySystems::Damage));
+    }
+}
+
+/// Chance of a hit being critical, [0..1].
+#[derive(Stat, Component, Reflect)]
+#[reflect(Component)]
+#[stat(name = "crit_chance")]
+pub struct CritChance(f32);
+
+/// Damage multiplier of critical hits, units without one use [`formula::DEFAULT_CRIT_MULTIPLIER`].
+#[derive(Stat, Component, Reflect)]
+#[reflect(Component)]
+#[stat(name = "crit_multiplier")]
+pub struct CritMultiplier(f32);
+
+/// Chance of dodging a hit, [0..1].
+#[derive(Stat, Component, Reflect)]
+#[reflect(Component)]
+#[stat(name = "evasion")]
+pub struct Evasion(f32);
+
+/// Pool of hit points, the unit dies when it's depleted.
+#[derive(Stat, Component, Reflect)]
+#[reflect(Component)]
+#[stat(name = "health")]
+pub struct Health(f32);
+
+/// Pool absorbing damage before [`Health`], refreshed by spells & drained by [`ShieldDecay`].
+#[derive(Stat, Component, Reflect)]
+#[reflect(Component)]
+#[stat(name = "shield")]
+pub struct Shield(f32);
+
+/// [`Health`] & an empty [`Shield`] pool of a unit that can take [`Damage`].
+#[derive(Bundle)]
+pub struct CombatBundle {
+    health: PoolBundle<Health>,
+    shield: PoolBundle<Shield>,
+}
+
+impl CombatBundle {
+    pub fn new(health: f32) -> Self {
+        Self { health: Health::pool(health), shield: Shield::pool(0.0) }
+    }
+}
+
+impl Default for CombatBundle {
+    fn default() -> Self {
+        Self::new(DEFAULT_HEALTH)
+    }
+}
+
+/// [`Shield`] points lost per second.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct ShieldDecay(pub f32);
+
+/// Damage dealt to `target`, after [`formula::resolve`].
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Damage {
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: f32,
+    pub critical: bool,
+}
+
+/// A hit depleted the [`Shield`] of `entity`, decay doesn't break shields.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ShieldBroken {
+    pub entity: Entity,
+}
+
+fn decay(mut shields: Query<(Pool<Shield>, &ShieldDecay)>, time: Res<Time<Fixed>>) {
+    let delta = time.delta_seconds();
+    for (mut shield, decay) in &mut shields {
+        if shield.current() > 0.0 {
+            shield -= decay.0 * delta;
+        }
+    }
+}
+
+/// Applies damage to the [`Shield`] first & the rest to [`Health`].
+fn damage(
+    mut damages: EventReader<Damage>,
+    mut shields: Query<Pool<Shield>>,
+    mut healths: Query<Pool<Health>>,
+    mut broken: EventWriter<ShieldBroken>,
+    mut events: EventWriter<GameEvent>,
+) {
+    for &Damage { target, amount, .. } in damages.read() {
+        let mut rest = amount.max(0.0);
+        if let Ok(mut shield) = shields.get_mut(target)
+            && shield.current() > 0.0
+        {
+            rest = shield.absorb(rest);
+            if shield.current() <= 0.0 {
+                broken.send(ShieldBroken { entity: target });
+            }
+        }
+        if rest > 0.0
+            && let Ok(mut health) = healths.get_mut(target)
+            && health.current() > 0.0
+        {
+            health.absorb(rest);
+            if health.current() <= 0.0 {
+                events.send(GameEvent::Died { entity: target });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{event::ManualEventReader, system::RunSystemOnce};
+
+    use super::*;
+    use crate::stats::pool::Current;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Events<Damage>>();
+        world.init_resource::<Events<ShieldBroken>>();
+        world.init_resource::<Events<GameEvent>>();
+        world
+    }
+
+    /// Spawns a unit with its totals already resolved, as the stat systems would.
+    fn unit(world: &mut World, health: f32, shield: f32) -> Entity {
+        let mut unit = world.spawn((Health::pool(health), Shield::pool(shield)));
+        unit.insert((Health::new(health), Shield::new(shield)));
+        unit.id()
+    }
+
+    fn hit(world: &mut World, target: Entity, amount: f32) -> Vec<GameEvent> {
+        world.send_event(Damage { source: target, target, amount, critical: false });
+        world.run_system_once(damage);
+        let events = world.resource::<Events<GameEvent>>();
+        ManualEventReader::<GameEvent>::default().read(events).copied().collect()
+    }
+
+    #[test]
+    fn lethal_damage_dies() {
+        let mut world = world();
+        let target = unit(&mut world, DEFAULT_HEALTH, 0.0);
+
+        assert!(hit(&mut world, target, DEFAULT_HEALTH / 2.0).is_empty());
+        world.resource_mut::<Events<GameEvent>>().clear();
+
+        let events = hit(&mut world, target, DEFAULT_HEALTH);
+        assert!(matches!(events.as_slice(), [GameEvent::Died { entity }] if *entity == target));
+        assert_eq!(world.get::<Current<Health>>(target).unwrap().value(), 0.0);
+
+        // Dead units don't die again.
+        world.resource_mut::<Events<GameEvent>>().clear();
+        assert!(hit(&mut world, target, DEFAULT_HEALTH).is_empty());
+    }
+
+    #[test]
+    fn shield_absorbs_before_health() {
+        let mut world = world();
+        let target = unit(&mut world, DEFAULT_HEALTH, 30.0);
+
+        hit(&mut world, target, 50.0);
+        assert_eq!(world.get::<Current<Shield>>(target).unwrap().value(), 0.0);
+        assert_eq!(world.get::<Current<Health>>(target).unwrap().value(), DEFAULT_HEALTH - 20.0);
+        assert_eq!(world.resource::<Events<ShieldBroken>>().len(), 1);
+    }
+
+    #[test]
+    fn shield_decays_without_breaking() {
+        let mut world = world();
+        world.init_resource::<Time<Fixed>>();
+        let target = unit(&mut world, DEFAULT_HEALTH, 30.0);
+        world.entity_mut(target).insert(ShieldDecay(20.0));
+
+        world.resource_mut::<Time<Fixed>>().advance_by(Duration::from_secs(1));
+        world.run_system_once(decay);
+        assert_eq!(world.get::<Current<Shield>>(target).unwrap().value(), 10.0);
+
+        world.run_system_once(decay);
+        assert_eq!(world.get::<Current<Shield>>(target).unwrap().value(), 0.0);
+        assert_eq!(world.get::<Current<Health>>(target).unwrap().value(), DEFAULT_HEALTH);
+        assert!(world.resource::<Events<ShieldBroken>>().is_empty());
+    }
+}
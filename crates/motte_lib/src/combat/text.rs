@@ -0,0 +1,99 @@
+//! Numbers of the [`Damage`] dealt rising above the target & fading out, every hit shows one so they're taken from
+//! an [`EntityPool`].
+
+use bevy::window::PrimaryWindow;
+
+use super::Damage;
+use crate::{
+    app_state::AppState,
+    asset_management::FontAssets,
+    main_menu,
+    player::camera::MainCamera,
+    pool::{EntityCommandsReleaseExt, EntityPool, EntityPoolPlugin},
+    prelude::*,
+};
+
+/// Seconds a number is shown.
+const LIFETIME: f32 = 0.8;
+
+/// Units the number rises per second.
+const RISE: f32 = 2.0;
+
+/// Height above the target the number starts at.
+const OFFSET: f32 = 2.0;
+
+const FONT_SIZE: f32 = 16.0;
+
+const CRITICAL_FONT_SIZE: f32 = 24.0;
+
+pub struct CombatTextPlugin;
+
+impl Plugin for CombatTextPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(CombatText);
+        app.add_plugins(EntityPoolPlugin::<CombatText>::with_warm_up(32));
+        app.add_systems(Update, (spawn, float).chain().run_if(in_state(AppState::InGame)));
+    }
+}
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct CombatText {
+    /// World position the number rises from.
+    origin: Vec3,
+    /// Seconds since it was shown.
+    age: f32,
+}
+
+fn spawn(
+    mut commands: Commands,
+    mut damages: EventReader<Damage>,
+    mut pool: ResMut<EntityPool<CombatText>>,
+    targets: Query<&GlobalTransform>,
+    fonts: Res<FontAssets>,
+) {
+    for &Damage { target, amount, critical, .. } in damages.read() {
+        let Ok(transform) = targets.get(target) else {
+            continue;
+        };
+        let (font_size, color) = if critical { (CRITICAL_FONT_SIZE, Color::ORANGE) } else { (FONT_SIZE, Color::WHITE) };
+        let mut text = main_menu::text(&fonts, format!("{amount:.0}"), font_size);
+        text.text.sections[0].style.color = color;
+        text.style.position_type = PositionType::Absolute;
+        text.visibility = Visibility::Hidden;
+
+        let origin = transform.translation() + Vec3::Y * OFFSET;
+        pool.acquire(&mut commands, CombatText { origin, age: 0.0 }).insert(text);
+    }
+}
+
+/// Moves the numbers to the screen position of their rising world position & fades them out.
+fn float(
+    mut commands: Commands,
+    mut texts: Query<(Entity, &mut CombatText, &mut Style, &mut Text, &mut Visibility)>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    time: Res<Time>,
+) {
+    let (Ok((camera, camera_transform)), Ok(window)) = (camera.get_single(), windows.get_single()) else {
+        return;
+    };
+    for (entity, mut combat_text, mut style, mut text, mut visibility) in &mut texts {
+        combat_text.age += time.delta_seconds();
+        if combat_text.age >= LIFETIME {
+            commands.entity(entity).release();
+            continue;
+        }
+
+        let position = combat_text.origin + Vec3::Y * RISE * combat_text.age;
+        let Some(ndc) = camera.world_to_ndc(camera_transform, position).filter(|ndc| ndc.z >= 0.0) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let screen = (Vec2::new(ndc.x, -ndc.y) + 1.0) / 2.0 * Vec2::new(window.width(), window.height());
+        style.left = Val::Px(screen.x);
+        style.top = Val::Px(screen.y);
+        text.sections[0].style.color.set_a(1.0 - combat_text.age / LIFETIME);
+        *visibility = Visibility::Inherited;
+    }
+}
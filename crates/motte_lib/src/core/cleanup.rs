@@ -1,23 +1,34 @@
-use crate::{app_state::AppState, prelude::*};
+use bevy::{ecs::schedule::StateTransitionEvent, reflect::TypePath};
 
-#[derive(Component, Reflect)]
+use crate::prelude::*;
+
+/// Despawns the entity when exiting the given state, requires [`AppStateScopedExt::enable_state_scoped`] for `S`.
+#[derive(Component, Reflect, Clone, Debug, Deref, DerefMut, From)]
 #[reflect(Component)]
-#[component(storage = "SparseSet")]
-pub struct Cleanup<T>(#[reflect(ignore)] PhantomData<T>);
+pub struct StateScoped<S: States>(pub S);
 
-pub struct OnEnterState<const S: AppState>;
-pub struct OnExitState<const S: AppState>;
+pub trait AppStateScopedExt {
+    /// Despawn [`StateScoped<S>`] entities on state exit.
+    fn enable_state_scoped<S: States + FromReflect + TypePath>(&mut self) -> &mut Self;
+}
 
-// TODO: want to improve this API.
-// ex.
-// spawn(Cleanup.on_enter(AppState::InGame))
-// spawn(Cleanup.on_exit(AppState::InGame))
-// spawn(Cleanup.on_exit::<{ AppState::InGame }>
+impl AppStateScopedExt for App {
+    fn enable_state_scoped<S: States + FromReflect + TypePath>(&mut self) -> &mut Self {
+        self.register_type::<StateScoped<S>>();
+        self.add_systems(StateTransition, state_scoped::<S>.after(apply_state_transition::<S>))
+    }
+}
 
-pub fn cleanup<T: Component>(commands: ParallelCommands, entities: Query<Entity, With<Cleanup<T>>>) {
-    entities.par_iter().for_each(|e| {
-        commands.command_scope(|mut c| {
-            c.entity(e).despawn_recursive();
-        })
-    });
+fn state_scoped<S: States>(
+    mut commands: Commands,
+    mut transitions: EventReader<StateTransitionEvent<S>>,
+    entities: Query<(Entity, &StateScoped<S>)>,
+) {
+    for transition in transitions.read() {
+        for (entity, scoped) in &entities {
+            if **scoped == transition.before {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
 }
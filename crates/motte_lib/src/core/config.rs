@@ -0,0 +1,143 @@
+//! Gameplay tuning loaded from `assets/config/game.config.ron`.
+//! The asset is copied into the [`GameConfig`] resource whenever it's (re)loaded, enable the `hot_reload` feature
+//! to apply changes to the file while the game is running.
+
+use bevy_common_assets::ron::RonAssetPlugin;
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<GameConfig>::new(&["config.ron"]));
+        app_register_types!(res: GameConfig, NavigationConfig, AvoidanceConfig, FlowFieldConfig, CrowdConfig);
+        app.add_systems(PreUpdate, apply);
+    }
+}
+
+#[derive(Asset, Resource, Reflect, Deserialize, Default, Clone, Debug)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct GameConfig {
+    pub navigation: NavigationConfig,
+}
+
+#[derive(Reflect, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct NavigationConfig {
+    pub avoidance: AvoidanceConfig,
+    pub flow_field: FlowFieldConfig,
+    pub crowd: CrowdConfig,
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AvoidanceConfig {
+    pub time_horizon: f32,
+    pub obstacle_time_horizon: f32,
+    pub obstacle_margin: f32,
+    /// Max speed of the avoiding velocity relative to the desired velocity.
+    pub max_speed_multiplier: f32,
+    /// Added to the neighborhood radius when searching for nearby agents.
+    pub neighborhood_padding: f32,
+    /// Strength of the push between overlapping agents relative to their speed, only used by boids avoidance.
+    pub separation_weight: f32,
+    /// Agents closer to the camera's focus get
+    /// [`AvoidanceQuality::High`](crate::navigation::avoidance::AvoidanceQuality).
+    pub high_quality_distance: f32,
+    /// Agents further from the camera's focus get
+    /// [`AvoidanceQuality::Low`](crate::navigation::avoidance::AvoidanceQuality).
+    pub low_quality_distance: f32,
+}
+
+impl Default for AvoidanceConfig {
+    fn default() -> Self {
+        Self {
+            time_horizon: 3.0,
+            obstacle_time_horizon: 0.1,
+            obstacle_margin: 0.1,
+            max_speed_multiplier: 1.2,
+            neighborhood_padding: 0.0,
+            separation_weight: 1.0,
+            high_quality_distance: 30.0,
+            low_quality_distance: 60.0,
+        }
+    }
+}
+
+impl AvoidanceConfig {
+    #[inline]
+    pub fn options(&self) -> dodgy_2d::AvoidanceOptions {
+        dodgy_2d::AvoidanceOptions {
+            obstacle_margin: self.obstacle_margin,
+            time_horizon: self.time_horizon,
+            obstacle_time_horizon: self.obstacle_time_horizon,
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct FlowFieldConfig {
+    /// Seconds an unused flow field is kept in the cache.
+    pub cache_ttl: f32,
+    /// Microseconds spent rebuilding flow fields per tick, the remaining builds are deferred. `0` builds every dirty
+    /// field in the same tick.
+    pub build_budget: u32,
+    /// Cells the goal has to move since the last build to rebuild the field right away.
+    pub rebuild_distance: u32,
+    /// Seconds since the last build after which any movement of the goal rebuilds the field.
+    pub rebuild_interval: f32,
+    /// Cells to a goal beyond which agents follow a corridor instead of a field of the whole layout. `0` disables
+    /// corridors.
+    pub corridor_distance: u32,
+    /// Cells from the agent to the edge of the window a corridor's field is built within.
+    pub corridor_window: u32,
+}
+
+impl Default for FlowFieldConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl: 30.0,
+            build_budget: 4000,
+            rebuild_distance: 2,
+            rebuild_interval: 0.25,
+            corridor_distance: 96,
+            corridor_window: 24,
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CrowdConfig {
+    /// Seconds an agent has to stand still without a goal before it can fall asleep.
+    pub sleep_after: f32,
+    /// Agents don't fall asleep & are woken up within this distance of a moving agent.
+    pub wake_distance: f32,
+    /// Ticks between updates of the cell of a sleeping agent.
+    pub dormant_interval: u32,
+}
+
+impl Default for CrowdConfig {
+    fn default() -> Self {
+        Self { sleep_after: 2.0, wake_distance: 16.0, dormant_interval: 8 }
+    }
+}
+
+fn apply(
+    mut events: EventReader<AssetEvent<GameConfig>>,
+    assets: Res<Assets<GameConfig>>,
+    mut config: ResMut<GameConfig>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event
+            && let Some(loaded) = assets.get(*id)
+        {
+            info!("Applied game config");
+            *config = loaded.clone();
+        }
+    }
+}
@@ -1,32 +1,44 @@
+//! Pointer input of mice & touch screens as the same cursor: [`CursorPosition`], [`CursorClick`] & [`CursorDrag`]
+//! don't care which one produced them. A tap is a left click, dragging a finger is a left drag when [`TouchDrag`] is
+//! [`TouchDrag::Select`], otherwise it pans the camera. Pans & pinches are sent as [`TouchGesture`]s.
+
 use bevy::{
-    input::{mouse::MouseButtonInput, ButtonState},
+    input::{
+        mouse::MouseButtonInput,
+        touch::{TouchInput, TouchPhase},
+        ButtonState,
+    },
     window::PrimaryWindow,
 };
 
-use crate::{app_state::AppState, prelude::*};
+use crate::{app_state::InGameState, prelude::*};
 
 const DRAGGING_THRESHOLD: f32 = 0.02;
 
 pub struct CursorPlugin;
 impl Plugin for CursorPlugin {
     fn build(&self, app: &mut App) {
-        app_register_types!(CursorDrag, CursorClick, CursorDoubleClick);
+        app_register_types!(res: TouchDrag, CursorDrag, CursorClick, CursorDoubleClick, TouchGesture, Pointer);
 
         app.add_event::<CursorClick>();
         app.add_event::<CursorDoubleClick>();
         app.add_event::<CursorDrag>();
+        app.add_event::<TouchGesture>();
         app.insert_resource(CursorButtonState::default());
         app.insert_resource(CursorPosition::default());
+        app.init_resource::<TouchDrag>();
+        app.init_resource::<TouchState>();
         app.add_systems(
             Update,
             (
                 update_position,
+                update_touch,
                 update_dragging.run_if(resource_exists_and_changed::<CursorPosition>),
                 update_button_input,
                 double_click,
             )
                 .chain()
-                .run_if(in_state(AppState::InGame)),
+                .run_if(in_state(InGameState::Playing)),
         );
     }
 }
@@ -47,6 +59,47 @@ impl CursorPosition {
     pub fn ndc(&self) -> Vec2 {
         self.ndc
     }
+
+    fn set(&mut self, window: &Window, position: Vec2) {
+        self.position = position;
+        self.ndc = ndc(window, position);
+    }
+}
+
+/// Device that moved the cursor last.
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pointer {
+    #[default]
+    Mouse,
+    Touch,
+}
+
+/// What dragging a single finger does.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum TouchDrag {
+    #[default]
+    Pan,
+    /// Box selects like a left drag of the mouse.
+    Select,
+}
+
+/// Camera gestures of touch screens.
+#[derive(Event, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+pub enum TouchGesture {
+    /// A single finger dragged from `from_ndc` to `to_ndc` since the last gesture.
+    Pan { from_ndc: Vec2, to_ndc: Vec2 },
+    /// Two fingers moved apart by `scale` times their distance since the last gesture, below 1 when pinching in.
+    Pinch { scale: f32 },
+}
+
+/// Fingers on the screen, the first one moves the cursor until it's lifted.
+#[derive(Resource, Default)]
+struct TouchState {
+    primary: Option<u64>,
+    /// Position of each finger in logical pixels.
+    fingers: HashMap<u64, Vec2>,
 }
 
 #[derive(Resource, Default, Clone, Deref, DerefMut)]
@@ -61,8 +114,20 @@ pub enum DragState {
 #[derive(Event, Debug, Clone, PartialEq, Reflect)]
 #[reflect(Debug, PartialEq)]
 pub enum CursorDrag {
-    Moved { button: MouseButton, start_ndc: Vec2, current_ndc: Vec2 },
-    Released { button: MouseButton, start_ndc: Vec2, end_ndc: Vec2 },
+    Moved {
+        button: MouseButton,
+        start_ndc: Vec2,
+        current_ndc: Vec2,
+    },
+    Released {
+        button: MouseButton,
+        start_ndc: Vec2,
+        end_ndc: Vec2,
+    },
+    /// The drag ended without being released, e.g. a second finger touched the screen.
+    Cancelled {
+        button: MouseButton,
+    },
 }
 
 #[derive(Event, Debug, Clone, PartialEq, Reflect)]
@@ -70,6 +135,7 @@ pub enum CursorDrag {
 pub struct CursorClick {
     pub button: MouseButton,
     pub ndc: Vec2,
+    pub pointer: Pointer,
 }
 
 #[derive(Event, Debug, Clone, PartialEq, Reflect)]
@@ -79,16 +145,99 @@ pub struct CursorDoubleClick {
     pub ndc: Vec2,
 }
 
+/// Cursor & touch positions are in logical pixels, `y` of the ndc points down.
+fn ndc(window: &Window, position: Vec2) -> Vec2 {
+    2.0 * (position / Vec2::new(window.width(), window.height())) - 1.0
+}
+
 fn update_position(
     windows: Query<&Window, With<PrimaryWindow>>,
     mut cursor_pos: ResMut<CursorPosition>,
     mut cursor_moved_events: EventReader<CursorMoved>,
 ) {
     if let Some(last_mouse_position) = cursor_moved_events.read().last() {
-        cursor_pos.position = last_mouse_position.position;
         if let Ok(window) = windows.get(last_mouse_position.window) {
-            let window_size = Vec2::new(window.physical_width() as f32, window.physical_height() as f32);
-            cursor_pos.ndc = 2.0 * (last_mouse_position.position / window_size) - 1.0;
+            cursor_pos.set(window, last_mouse_position.position);
+        }
+    }
+}
+
+/// Moves the cursor with the primary finger & presses the left button while it's down, unless it's dragging with
+/// [`TouchDrag::Pan`] or a second finger is down. Fingers touching the UI don't reach the world.
+#[allow(clippy::too_many_arguments)]
+fn update_touch(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    interactions: Query<&Interaction>,
+    mode: Res<TouchDrag>,
+    mut touch: ResMut<TouchState>,
+    mut cursor_pos: ResMut<CursorPosition>,
+    mut cursor_button_state: ResMut<CursorButtonState>,
+    mut input_events: EventReader<TouchInput>,
+    mut drags: EventWriter<CursorDrag>,
+    mut clicks: EventWriter<CursorClick>,
+    mut gestures: EventWriter<TouchGesture>,
+) {
+    let Ok(window) = windows.get_single() else {
+        input_events.clear();
+        return;
+    };
+    let touch = &mut *touch;
+
+    for event in input_events.read() {
+        let previous = touch.fingers.get(&event.id).copied();
+        match event.phase {
+            TouchPhase::Started => {
+                touch.fingers.insert(event.id, event.position);
+                let over_ui = interactions.iter().any(|interaction| *interaction != Interaction::None);
+                match touch.fingers.len() {
+                    1 if !over_ui => {
+                        touch.primary = Some(event.id);
+                        cursor_pos.set(window, event.position);
+                        press(&mut cursor_button_state, MouseButton::Left, cursor_pos.ndc);
+                    }
+                    // Over the UI, or a second finger to pinch with which cancels the press of the first one.
+                    _ => cancel(&mut cursor_button_state, MouseButton::Left, &mut drags),
+                }
+            }
+            TouchPhase::Moved => {
+                touch.fingers.insert(event.id, event.position);
+                if touch.fingers.len() == 2 {
+                    let other = touch.fingers.iter().find(|(&id, _)| id != event.id).map(|(_, &position)| position);
+                    if let (Some(previous), Some(other)) = (previous, other) {
+                        let scale = other.distance(event.position) / other.distance(previous).max(f32::EPSILON);
+                        gestures.send(TouchGesture::Pinch { scale });
+                    }
+                } else if touch.primary == Some(event.id) {
+                    let to_ndc = ndc(window, event.position);
+                    let from_ndc = match cursor_button_state.get(&MouseButton::Left) {
+                        // Until the finger moves past the dragging threshold it may still be a tap.
+                        Some(DragState::Pressed { ndc }) if ndc.distance(to_ndc) >= DRAGGING_THRESHOLD => Some(*ndc),
+                        Some(_) => None,
+                        None => Some(cursor_pos.ndc),
+                    };
+                    cursor_pos.set(window, event.position);
+                    if let (TouchDrag::Pan, Some(from_ndc)) = (*mode, from_ndc) {
+                        cursor_button_state.remove(&MouseButton::Left);
+                        gestures.send(TouchGesture::Pan { from_ndc, to_ndc });
+                    }
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Canceled => {
+                touch.fingers.remove(&event.id);
+                if touch.primary == Some(event.id) {
+                    touch.primary = None;
+                    match event.phase {
+                        TouchPhase::Ended => release(
+                            &mut cursor_button_state,
+                            MouseButton::Left,
+                            Pointer::Touch,
+                            &mut drags,
+                            &mut clicks,
+                        ),
+                        _ => cancel(&mut cursor_button_state, MouseButton::Left, &mut drags),
+                    }
+                }
+            }
         }
     }
 }
@@ -125,24 +274,44 @@ fn update_button_input(
     for event in input_events.read() {
         match event.state {
             ButtonState::Released => {
-                if let Some((_, drag_state)) = cursor_button_state.0.remove_entry(&event.button) {
-                    match drag_state {
-                        DragState::Pressed { ndc } => {
-                            click.send(CursorClick { button: event.button, ndc });
-                        }
-                        DragState::Dragging { start_ndc, current_ndc } => {
-                            drags.send(CursorDrag::Released { button: event.button, start_ndc, end_ndc: current_ndc });
-                        }
-                    }
-                }
+                release(&mut cursor_button_state, event.button, Pointer::Mouse, &mut drags, &mut click);
+            }
+            ButtonState::Pressed => press(&mut cursor_button_state, event.button, cursor_pos.ndc),
+        }
+    }
+}
+
+fn press(cursor_button_state: &mut CursorButtonState, button: MouseButton, ndc: Vec2) {
+    cursor_button_state.0.insert(button, DragState::Pressed { ndc });
+}
+
+/// Clicks if `button` was pressed without dragging, otherwise ends the drag.
+fn release(
+    cursor_button_state: &mut CursorButtonState,
+    button: MouseButton,
+    pointer: Pointer,
+    drags: &mut EventWriter<CursorDrag>,
+    click: &mut EventWriter<CursorClick>,
+) {
+    if let Some((_, drag_state)) = cursor_button_state.0.remove_entry(&button) {
+        match drag_state {
+            DragState::Pressed { ndc } => {
+                click.send(CursorClick { button, ndc, pointer });
             }
-            ButtonState::Pressed => {
-                cursor_button_state.0.insert(event.button, DragState::Pressed { ndc: cursor_pos.ndc });
+            DragState::Dragging { start_ndc, current_ndc } => {
+                drags.send(CursorDrag::Released { button, start_ndc, end_ndc: current_ndc });
             }
         }
     }
 }
 
+/// Releases `button` without clicking or ending the drag.
+fn cancel(cursor_button_state: &mut CursorButtonState, button: MouseButton, drags: &mut EventWriter<CursorDrag>) {
+    if let Some(DragState::Dragging { .. }) = cursor_button_state.0.remove(&button) {
+        drags.send(CursorDrag::Cancelled { button });
+    }
+}
+
 fn double_click(
     mut clicks: EventReader<CursorClick>,
     mut double_clicks: EventWriter<CursorDoubleClick>,
@@ -1,11 +1,18 @@
-use crate::prelude::*;
+use std::time::Duration;
+
+use crate::{navigation::flow_field::layout::FieldLayout, prelude::*};
 
 pub struct DespawnPlugin;
 
 impl Plugin for DespawnPlugin {
     fn build(&self, app: &mut App) {
-        app_register_types!(Despawn, DespawnInProgress);
-        app.configure_sets(Last, (DespawnSystem::Timer, DespawnSystem::Despawn).chain());
+        app_register_types!(Despawn, DespawnInProgress, DespawnAfter, DespawnOutOfBounds, DespawnWhenFar);
+        app.configure_sets(Last, (DespawnSystem::Conditions, DespawnSystem::Timer, DespawnSystem::Despawn).chain());
+        app.add_systems(
+            Last,
+            (despawn_after, despawn_out_of_bounds.run_if(resource_exists::<FieldLayout>), despawn_when_far)
+                .in_set(DespawnSystem::Conditions),
+        );
         app.add_systems(
             Last,
             (despawn_timer.in_set(DespawnSystem::Timer), apply_deferred, despawn.in_set(DespawnSystem::Timer)).chain(),
@@ -15,6 +22,7 @@ impl Plugin for DespawnPlugin {
 
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 pub(crate) enum DespawnSystem {
+    Conditions,
     Timer,
     Despawn,
 }
@@ -35,6 +43,56 @@ pub enum Despawn {
 #[derive(Component, Default, Reflect)]
 pub struct DespawnInProgress;
 
+/// Despawn after the duration has elapsed.
+#[derive(Component, Default, Reflect, Deref, DerefMut, From)]
+#[reflect(Component)]
+pub struct DespawnAfter(pub Duration);
+
+/// Despawn when outside of the [`FieldLayout`] bounds, expanded by the margin.
+#[derive(Component, Default, Reflect, Deref, DerefMut, From)]
+#[reflect(Component)]
+pub struct DespawnOutOfBounds(pub f32);
+
+/// Despawn when further than `distance` away from `from`.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct DespawnWhenFar {
+    pub from: Vec3,
+    pub distance: f32,
+}
+
+fn despawn_after(mut commands: Commands, mut despawns: Query<(Entity, &mut DespawnAfter)>, time: Res<Time>) {
+    for (entity, mut despawn) in &mut despawns {
+        **despawn = despawn.saturating_sub(time.delta());
+        if despawn.is_zero() {
+            commands.entity(entity).remove::<DespawnAfter>().insert(Despawn::Immediate);
+        }
+    }
+}
+
+fn despawn_out_of_bounds(
+    mut commands: Commands,
+    despawns: Query<(Entity, &DespawnOutOfBounds, &GlobalTransform)>,
+    layout: Res<FieldLayout>,
+) {
+    let ((min_x, min_y), (max_x, max_y)) = layout.aabb();
+    let (min, max) = (Vec2::new(min_x, min_y), Vec2::new(max_x, max_y));
+    for (entity, margin, global_transform) in &despawns {
+        let position = global_transform.translation().xz();
+        if position.cmplt(min - **margin).any() || position.cmpgt(max + **margin).any() {
+            commands.entity(entity).remove::<DespawnOutOfBounds>().insert(Despawn::Immediate);
+        }
+    }
+}
+
+fn despawn_when_far(mut commands: Commands, despawns: Query<(Entity, &DespawnWhenFar, &GlobalTransform)>) {
+    for (entity, despawn, global_transform) in &despawns {
+        if global_transform.translation().distance_squared(despawn.from) > despawn.distance * despawn.distance {
+            commands.entity(entity).remove::<DespawnWhenFar>().insert(Despawn::Immediate);
+        }
+    }
+}
+
 fn despawn_timer(mut commands: Commands, mut despawns: Query<(Entity, &mut Despawn)>, time: Res<Time>) {
     for (entity, mut despawn) in &mut despawns {
         let despawn = match *despawn {
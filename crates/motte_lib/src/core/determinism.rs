@@ -0,0 +1,163 @@
+//! Groundwork for a deterministic simulation (lockstep multiplayer & replays).
+//!
+//! All randomness of the simulation comes from [`GameRng`], seeded with `--seed` or randomly. Every game starts from
+//! the seed again & subsystems can [`fork`](GameRng::fork) their own stream so they don't shift each other's numbers.
+//! The [`GameSeed`] is written to save files & sent to lockstep peers.
+//!
+//! With the `determinism` feature enabled:
+//! - Navigation iterates queries sequentially in a stable order, see [`QueryExt`](crate::utils::trait_ext::QueryExt).
+//! - [`GameRng`] is seeded with a fixed [`SEED`].
+//! - A [`StateHash`] of all rigid bodies is computed after every physics step.
+//!
+//! TODO: physics isn't cross-platform deterministic yet, `bevy_xpbd_3d/enhanced-determinism` can't be combined with
+//! the `simd` feature.
+
+use rand::rngs::StdRng;
+
+use crate::{app_state::AppState, launch::LaunchOptions, prelude::*, save::AppSaveExt};
+
+/// Seed used by [`GameRng`] when the `determinism` feature is enabled.
+#[cfg(feature = "determinism")]
+pub const SEED: u64 = 0x6D6F_7474_65;
+
+pub struct DeterminismPlugin;
+
+impl Plugin for DeterminismPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = app.world.get_resource::<LaunchOptions>().and_then(|launch| launch.seed);
+        let rng = seed.map_or_else(GameRng::default, GameRng::from_seed);
+        info!("Game rng seed: {}", rng.seed());
+        app.insert_resource(GameSeed(rng.seed())).insert_resource(rng);
+        app_register_types!(GameSeed);
+        app.register_save_resource::<GameSeed>();
+        app.add_systems(PreUpdate, reseed.run_if(resource_changed::<GameSeed>));
+        // Also when entering the game, in case the seed was received while loading the map e.g. from a lockstep host.
+        app.add_systems(OnEnter(AppState::InGame), reseed);
+        app.add_systems(OnExit(AppState::InGame), restart);
+
+        #[cfg(feature = "determinism")]
+        {
+            use bevy::diagnostic::{Diagnostic, RegisterDiagnostic};
+            use bevy_xpbd_3d::{PhysicsSchedule, PhysicsStepSet};
+
+            app_register_types!(res: StateHash);
+            app.register_diagnostic(Diagnostic::new(StateHash::DIAGNOSTIC).with_max_history_length(1));
+            app.add_systems(
+                PhysicsSchedule,
+                state_hash.after(PhysicsStepSet::SpatialQuery).after(crate::physics::ccd::sweep),
+            );
+        }
+    }
+}
+
+/// Seeded random number generator, use instead of [`thread_rng`] for anything affecting the simulation.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng {
+    seed: u64,
+    #[deref]
+    rng: StdRng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        #[cfg(feature = "determinism")]
+        let seed = SEED;
+        #[cfg(not(feature = "determinism"))]
+        let seed = thread_rng().next_u64();
+        Self::from_seed(seed)
+    }
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self { seed, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Independent generator for `subsystem`, only depends on the seed & the name of the subsystem. Hashed with
+    /// FNV-1a instead of the std hasher, whose algorithm may change between Rust releases.
+    pub fn fork(&self, subsystem: &str) -> GameRng {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let bytes = self.seed.to_le_bytes().into_iter().chain(subsystem.bytes());
+        Self::from_seed(bytes.fold(OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME)))
+    }
+}
+
+/// Seed of the [`GameRng`], replacing it reseeds the generator e.g. when loading a save.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq, Deref)]
+#[reflect(Resource)]
+pub struct GameSeed(pub u64);
+
+pub(crate) fn reseed(seed: Res<GameSeed>, mut rng: ResMut<GameRng>) {
+    if rng.seed() != **seed {
+        info!("Game rng seed: {}", **seed);
+        *rng = GameRng::from_seed(**seed);
+    }
+}
+
+/// Starts the next game from the seed again.
+fn restart(mut rng: ResMut<GameRng>) {
+    *rng = GameRng::from_seed(rng.seed());
+}
+
+/// Hash of the physics state after the last physics step, peers running the same simulation should produce the
+/// same hash for the same tick.
+#[cfg(feature = "determinism")]
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct StateHash {
+    pub tick: u64,
+    pub hash: u64,
+}
+
+#[cfg(feature = "determinism")]
+impl StateHash {
+    /// Lower 32 bits of the hash, as diagnostics are stored as [`f64`].
+    pub const DIAGNOSTIC: bevy::diagnostic::DiagnosticPath =
+        bevy::diagnostic::DiagnosticPath::const_new("simulation/state_hash");
+}
+
+#[cfg(feature = "determinism")]
+fn state_hash(
+    mut state_hash: ResMut<StateHash>,
+    mut diagnostics: bevy::diagnostic::Diagnostics,
+    bodies: Query<(Entity, &Position, &Rotation, &LinearVelocity, &AngularVelocity)>,
+) {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    let mut bodies = bodies.iter().collect_vec();
+    bodies.sort_unstable_by_key(|(entity, ..)| *entity);
+
+    let mut hasher = DefaultHasher::new();
+    for (entity, position, rotation, linear_velocity, angular_velocity) in bodies {
+        entity.hash(&mut hasher);
+        position.to_array().map(f32::to_bits).hash(&mut hasher);
+        rotation.to_array().map(f32::to_bits).hash(&mut hasher);
+        linear_velocity.to_array().map(f32::to_bits).hash(&mut hasher);
+        angular_velocity.to_array().map(f32::to_bits).hash(&mut hasher);
+    }
+
+    state_hash.tick += 1;
+    state_hash.hash = hasher.finish();
+
+    let hash = state_hash.hash;
+    diagnostics.add_measurement(&StateHash::DIAGNOSTIC, || (hash & u32::MAX as u64) as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forks_are_stable() {
+        let rng = GameRng::from_seed(42);
+        assert_eq!(rng.fork("obstacles").seed(), 0x2731_de08_65f3_ae77);
+        assert_eq!(rng.fork("waves").seed(), 0x215d_ffaa_c18f_dccf);
+        assert_eq!(rng.fork("obstacles").next_u64(), rng.fork("obstacles").next_u64());
+    }
+}
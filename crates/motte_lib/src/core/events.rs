@@ -0,0 +1,140 @@
+//! Append-only log of notable gameplay events (orders, attacks, deaths, spells, waves).
+//! Send a [`GameEvent`] to record it, read it back through the [`GameEventLog`] resource.
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::core::FrameCount;
+
+use crate::{app_state::AppState, navigation::flow_field::pathing::Goal, prelude::*};
+
+pub struct GameEventsPlugin;
+
+impl Plugin for GameEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(GameEvent, GameEventEntry, res: GameEventLog);
+        app.add_event::<GameEvent>();
+        app.add_systems(OnEnter(AppState::InGame), clear);
+        app.add_systems(Last, (orders, record).chain().run_if(in_state(AppState::InGame)));
+    }
+}
+
+#[derive(Event, Reflect, Clone, Copy, Debug, PartialEq)]
+#[allow(unused)]
+pub enum GameEvent {
+    OrderIssued { entity: Entity, goal: Goal },
+    Attacked { attacker: Entity, target: Entity },
+    Died { entity: Entity },
+    SpellCast { caster: Entity, target: Option<Entity> },
+    WaveSpawned { wave: u32, count: u32 },
+}
+
+impl GameEvent {
+    /// Returns true if `entity` is the subject or target of the event.
+    pub fn involves(&self, entity: Entity) -> bool {
+        match *self {
+            GameEvent::OrderIssued { entity: subject, goal } => subject == entity || goal == Goal::Entity(entity),
+            GameEvent::Attacked { attacker, target } => attacker == entity || target == entity,
+            GameEvent::Died { entity: subject } => subject == entity,
+            GameEvent::SpellCast { caster, target } => caster == entity || target == Some(entity),
+            GameEvent::WaveSpawned { .. } => false,
+        }
+    }
+}
+
+#[derive(Reflect, Clone, Copy, Debug)]
+pub struct GameEventEntry {
+    pub frame: u32,
+    pub elapsed: Duration,
+    pub event: GameEvent,
+}
+
+/// Ring-buffer of the last [`GameEventLog::capacity`] recorded [`GameEvent`]s.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct GameEventLog {
+    entries: VecDeque<GameEventEntry>,
+    capacity: usize,
+    total: u64,
+}
+
+impl Default for GameEventLog {
+    fn default() -> Self {
+        Self::with_capacity(4096)
+    }
+}
+
+#[allow(unused)]
+impl GameEventLog {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { entries: VecDeque::with_capacity(capacity), capacity, total: 0 }
+    }
+
+    pub fn push(&mut self, entry: GameEventEntry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        self.total += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.total = 0;
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Amount of events recorded, including the ones no longer retained.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Retained entries, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &GameEventEntry> {
+        self.entries.iter()
+    }
+
+    /// Entries recorded at or after `elapsed`.
+    pub fn since(&self, elapsed: Duration) -> impl Iterator<Item = &GameEventEntry> {
+        let start = self.entries.partition_point(|entry| entry.elapsed < elapsed);
+        self.entries.range(start..)
+    }
+
+    /// Entries where `entity` is the subject or target.
+    pub fn involving(&self, entity: Entity) -> impl Iterator<Item = &GameEventEntry> {
+        self.entries.iter().filter(move |entry| entry.event.involves(entity))
+    }
+}
+
+fn clear(mut log: ResMut<GameEventLog>) {
+    log.clear();
+}
+
+fn orders(mut events: EventWriter<GameEvent>, goals: Query<(Entity, &Goal), Changed<Goal>>) {
+    for (entity, &goal) in &goals {
+        if goal != Goal::None {
+            events.send(GameEvent::OrderIssued { entity, goal });
+        }
+    }
+}
+
+fn record(
+    mut log: ResMut<GameEventLog>,
+    mut events: EventReader<GameEvent>,
+    frame_count: Res<FrameCount>,
+    time: Res<Time>,
+) {
+    for &event in events.read() {
+        log.push(GameEventEntry { frame: frame_count.0, elapsed: time.elapsed(), event });
+    }
+}
@@ -1,27 +1,63 @@
 use std::borrow::Cow;
 
 use crate::{
-    app_state::AppState,
-    cleanup::{Cleanup, OnEnterState, OnExitState},
+    app_state::{AppState, InGameState},
+    cleanup::AppStateScopedExt,
     prelude::*,
 };
 
 pub mod active_duration;
+#[cfg(feature = "presentation")]
 pub mod camera;
 pub mod cleanup;
+pub mod config;
+#[cfg(feature = "presentation")]
 pub mod cursor;
 pub mod despawn;
+pub mod determinism;
+pub mod events;
+pub mod owner;
+pub mod pool;
+pub mod prefab;
 pub mod previous;
+pub mod save;
+pub mod settings;
+pub mod simulation;
+pub mod timer;
+pub mod timings;
 
+/// Core plugins the simulation depends on.
 pub struct CorePlugin;
 
 impl Plugin for CorePlugin {
     fn build(&self, app: &mut App) {
-        app_register_types!(Owner);
+        // Sent by the save plugin of the presentation, the simulation only reacts to it.
+        app.add_event::<save::Loaded>();
+        app.add_plugins((
+            despawn::DespawnPlugin,
+            determinism::DeterminismPlugin,
+            prefab::PrefabPlugin,
+            events::GameEventsPlugin,
+            owner::OwnerPlugin,
+            config::ConfigPlugin,
+            simulation::SimulationPlugin,
+            timings::SystemTimingsPlugin,
+        ));
+        app.enable_state_scoped::<AppState>();
+        app.enable_state_scoped::<InGameState>();
+    }
+}
+
+/// Input, camera, saves & settings of the player.
+#[cfg(feature = "presentation")]
+pub struct CorePresentationPlugin;
+
+#[cfg(feature = "presentation")]
+impl Plugin for CorePresentationPlugin {
+    fn build(&self, app: &mut App) {
         app.add_plugins(bevy_mod_picking::DefaultPickingPlugins);
-        app.add_plugins((despawn::DespawnPlugin, cursor::CursorPlugin, camera::CameraPlugin::in_schedule(Last)));
-        app.add_systems(OnEnter(AppState::InGame), cleanup::cleanup::<Cleanup<OnEnterState<{ AppState::InGame }>>>);
-        app.add_systems(OnExit(AppState::InGame), cleanup::cleanup::<Cleanup<OnExitState<{ AppState::InGame }>>>);
+        app.add_plugins((cursor::CursorPlugin, camera::CameraPlugin::in_schedule(Last)));
+        app.add_plugins((save::SavePlugin, settings::SettingsPlugin));
     }
 }
 
@@ -52,9 +88,6 @@ pub trait NameTags {
 
 impl NameTags for Name {}
 
-#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Deref, DerefMut, From)]
-pub struct Owner(pub Entity);
-
 /// Generic component to mark component [`T`] as dirty.
 #[derive(Component, Default, Deref, DerefMut, From, Reflect)]
 #[component(storage = "SparseSet")]
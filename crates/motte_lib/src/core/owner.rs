@@ -0,0 +1,106 @@
+//! Ownership between entities outside of the transform hierarchy, e.g. a caster owning its projectiles & summons.
+//! Iterate owned entities with [`Query::iter_many`] over [`Owned`].
+
+use crate::{despawn::DespawnSystem, prelude::*};
+
+pub struct OwnerPlugin;
+
+impl Plugin for OwnerPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Owner, Owned, DespawnWithOwner);
+        app.add_systems(Last, (validate, owned).chain().before(DespawnSystem::Conditions));
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Deref, DerefMut, From)]
+pub struct Owner(pub Entity);
+
+/// Entities owned by this entity, maintained from [`Owner`].
+#[derive(Component, Reflect, Default, Debug, Deref)]
+#[reflect(Component)]
+pub struct Owned(SmallVec<[Entity; 8]>);
+
+/// Despawn this entity when its [`Owner`] no longer exists, instead of removing the [`Owner`].
+#[derive(Component, Reflect, Default, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct DespawnWithOwner;
+
+/// Removes dangling [`Owner`]s, or despawns the entity right away if marked with [`DespawnWithOwner`].
+fn validate(
+    mut commands: Commands,
+    owners: Query<(Entity, &Owner, Has<DespawnWithOwner>)>,
+    changed: Query<(), Changed<Owner>>,
+    mut removed: RemovedComponents<Owned>,
+) {
+    // Owned is removed when an owner is despawned, only check all owners then.
+    let owner_removed = removed.read().count() > 0;
+    if !owner_removed && changed.is_empty() {
+        return;
+    }
+
+    for (entity, owner, despawn) in &owners {
+        if commands.get_entity(**owner).is_some() {
+            continue;
+        }
+        if despawn {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            commands.entity(entity).remove::<Owner>();
+        }
+    }
+}
+
+fn owned(
+    mut commands: Commands,
+    changed_owners: Query<(Entity, &Owner), Changed<Owner>>,
+    owners: Query<&Owner>,
+    mut owned: Query<(Entity, &mut Owned)>,
+    mut removed: RemovedComponents<Owner>,
+) {
+    let any_removed = removed.read().count() > 0;
+    if any_removed || !changed_owners.is_empty() {
+        for (entity, mut owned) in &mut owned {
+            if owned.iter().any(|&e| owners.get(e).map_or(true, |owner| **owner != entity)) {
+                owned.0.retain(|e| owners.get(*e).is_ok_and(|owner| **owner == entity));
+            }
+        }
+    }
+
+    let mut added: HashMap<Entity, SmallVec<[Entity; 8]>> = HashMap::default();
+    for (entity, owner) in &changed_owners {
+        match owned.get_mut(**owner) {
+            Ok((_, mut owned)) if !owned.contains(&entity) => owned.0.push(entity),
+            Ok(_) => {}
+            Err(_) => added.entry(**owner).or_default().push(entity),
+        }
+    }
+
+    for (owner, entities) in added {
+        if let Some(mut commands) = commands.get_entity(owner) {
+            commands.insert(Owned(entities));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orphans_are_handled_on_the_same_tick() {
+        let mut world = World::new();
+        let mut schedule = Schedule::default();
+        schedule.add_systems((validate, owned).chain());
+
+        let owner = world.spawn_empty().id();
+        let despawned = world.spawn((Owner(owner), DespawnWithOwner)).id();
+        let kept = world.spawn(Owner(owner)).id();
+        schedule.run(&mut world);
+        assert_eq!(world.get::<Owned>(owner).unwrap().as_slice(), [despawned, kept]);
+
+        world.despawn(owner);
+        schedule.run(&mut world);
+        assert!(world.get_entity(despawned).is_none());
+        assert!(!world.entity(kept).contains::<Owner>());
+    }
+}
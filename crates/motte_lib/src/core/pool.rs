@@ -0,0 +1,186 @@
+//! Entity pooling for short-lived entities (projectiles, combat text, vfx).
+//!
+//! Released entities are hidden and have their pooled component `T` removed, so regular `T` queries skip them.
+//! All reflected components with [`ReflectDefault`] are reset to their default value on release.
+
+use std::marker::PhantomData;
+
+use bevy::{ecs::system::EntityCommands, reflect::TypeRegistry};
+
+use crate::{app_state::AppState, cleanup::StateScoped, despawn::DespawnSystem, prelude::*};
+
+pub struct EntityPoolPlugin<T: Component> {
+    warm_up: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Component> EntityPoolPlugin<T> {
+    /// Amount of entities to spawn up-front when entering [`AppState::InGame`].
+    pub fn with_warm_up(warm_up: usize) -> Self {
+        Self { warm_up, _marker: PhantomData }
+    }
+}
+
+impl<T: Component> Default for EntityPoolPlugin<T> {
+    fn default() -> Self {
+        Self::with_warm_up(0)
+    }
+}
+
+impl<T: Component + TypePath> Plugin for EntityPoolPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Release, Pooled<T>);
+        app.insert_resource(EntityPool::<T> {
+            free: Vec::with_capacity(self.warm_up),
+            warm_up: self.warm_up,
+            ..default()
+        });
+        app.add_systems(OnEnter(AppState::InGame), warm_up::<T>);
+        app.add_systems(OnExit(AppState::InGame), clear::<T>);
+        app.add_systems(Last, release::<T>.before(DespawnSystem::Timer).run_if(in_state(AppState::InGame)));
+    }
+}
+
+/// Marks an entity as owned by [`EntityPool<T>`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Pooled<T: Component>(#[reflect(ignore)] PhantomData<T>);
+
+impl<T: Component> Default for Pooled<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Returns a pooled entity to its pool at the end of the frame.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+#[component(storage = "SparseSet")]
+pub struct Release;
+
+#[derive(Bundle)]
+struct PooledBundle<T: Component> {
+    pooled: Pooled<T>,
+    spatial: SpatialBundle,
+    scoped: StateScoped<AppState>,
+}
+
+impl<T: Component> Default for PooledBundle<T> {
+    fn default() -> Self {
+        Self {
+            pooled: Pooled::default(),
+            spatial: SpatialBundle { visibility: Visibility::Hidden, ..default() },
+            scoped: StateScoped(AppState::InGame),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct EntityPool<T: Component> {
+    free: Vec<Entity>,
+    active: usize,
+    warm_up: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Component> Default for EntityPool<T> {
+    fn default() -> Self {
+        Self { free: Vec::new(), active: 0, warm_up: 0, _marker: PhantomData }
+    }
+}
+
+#[allow(unused)]
+impl<T: Component> EntityPool<T> {
+    /// Takes a free entity from the pool, or spawns a new one if the pool is empty, and inserts `value`.
+    pub fn acquire<'a>(&mut self, commands: &'a mut Commands, value: T) -> EntityCommands<'a> {
+        self.active += 1;
+        while let Some(entity) = self.free.pop() {
+            // Entity might have been despawned while in the pool.
+            if commands.get_entity(entity).is_some() {
+                let mut entity = commands.entity(entity);
+                entity.insert((value, Visibility::Inherited));
+                return entity;
+            }
+        }
+        let mut entity = commands.spawn(PooledBundle::<T>::default());
+        entity.insert((value, Visibility::Inherited));
+        entity
+    }
+
+    pub fn free(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+}
+
+#[allow(unused)]
+pub trait EntityCommandsReleaseExt {
+    /// Returns the entity to its [`EntityPool`], see [`Release`].
+    fn release(&mut self) -> &mut Self;
+}
+
+impl EntityCommandsReleaseExt for EntityCommands<'_> {
+    #[inline]
+    fn release(&mut self) -> &mut Self {
+        self.insert(Release)
+    }
+}
+
+fn warm_up<T: Component>(mut commands: Commands, mut pool: ResMut<EntityPool<T>>) {
+    let missing = pool.warm_up.saturating_sub(pool.free.len());
+    for _ in 0..missing {
+        let entity = commands.spawn(PooledBundle::<T>::default()).id();
+        pool.free.push(entity);
+    }
+}
+
+fn clear<T: Component>(mut pool: ResMut<EntityPool<T>>) {
+    pool.free.clear();
+    pool.active = 0;
+}
+
+fn release<T: Component>(world: &mut World) {
+    let released: Vec<Entity> =
+        world.query_filtered::<Entity, (With<Release>, With<Pooled<T>>)>().iter(world).collect();
+
+    if released.is_empty() {
+        return;
+    }
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    for &entity in &released {
+        reset(world, entity, &registry);
+        world.entity_mut(entity).remove::<(T, Release)>().insert(Visibility::Hidden);
+    }
+
+    let mut pool = world.resource_mut::<EntityPool<T>>();
+    pool.active = pool.active.saturating_sub(released.len());
+    pool.free.extend(released);
+}
+
+/// Resets all components of `entity` that are registered with both [`ReflectComponent`] & [`ReflectDefault`].
+fn reset(world: &mut World, entity: Entity, registry: &TypeRegistry) {
+    let type_ids = world
+        .entity(entity)
+        .archetype()
+        .components()
+        .filter_map(|id| world.components().get_info(id).and_then(|info| info.type_id()))
+        .collect_vec();
+
+    for type_id in type_ids {
+        let Some(registration) = registry.get(type_id) else {
+            continue;
+        };
+        let (Some(reflect_component), Some(reflect_default)) =
+            (registration.data::<ReflectComponent>(), registration.data::<ReflectDefault>())
+        else {
+            continue;
+        };
+        reflect_component.apply(&mut world.entity_mut(entity), reflect_default.default().as_reflect());
+    }
+}
@@ -0,0 +1,243 @@
+//! Prefabs defined as RON files in `assets/prefabs`, a list of reflected components & children:
+//! ```ron
+//! (
+//!     components: {
+//!         "motte_lib::navigation::obstacle::Obstacle": Empty,
+//!     },
+//!     children: [],
+//! )
+//! ```
+//! Spawned by name with [`PrefabCommandsExt::spawn_prefab`], components inserted afterwards override the prefab.
+
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    ecs::system::{EntityCommand, EntityCommands},
+    reflect::{
+        serde::{TypeRegistrationDeserializer, TypedReflectDeserializer},
+        TypeRegistry, TypeRegistryArc,
+    },
+    utils::BoxedFuture,
+};
+use serde::{
+    de::{DeserializeSeed, Error as _, MapAccess, SeqAccess, Visitor},
+    Deserializer,
+};
+
+use crate::prelude::*;
+
+pub const PREFAB_EXTENSION: &str = "prefab.ron";
+
+pub struct PrefabPlugin;
+
+impl Plugin for PrefabPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Prefab>().init_asset_loader::<PrefabLoader>();
+    }
+}
+
+#[derive(Asset, TypePath)]
+pub struct Prefab {
+    /// File name without extension.
+    name: String,
+    root: PrefabNode,
+}
+
+#[derive(Default)]
+struct PrefabNode {
+    components: Vec<Box<dyn Reflect>>,
+    children: Vec<PrefabNode>,
+}
+
+impl PrefabNode {
+    fn write(&self, world: &mut World, entity: Entity, registry: &TypeRegistry) {
+        for component in &self.components {
+            let Some(reflect_component) = component
+                .get_represented_type_info()
+                .and_then(|info| registry.get(info.type_id()))
+                .and_then(|registration| registration.data::<ReflectComponent>())
+            else {
+                warn!("Prefab component {} is not registered as a component", component.reflect_type_path());
+                continue;
+            };
+            reflect_component.apply_or_insert(&mut world.entity_mut(entity), &**component, registry);
+        }
+
+        for child in &self.children {
+            let child_entity = world.spawn(SpatialBundle::default()).id();
+            child.write(world, child_entity, registry);
+            world.entity_mut(entity).add_child(child_entity);
+        }
+    }
+}
+
+pub trait PrefabCommandsExt {
+    /// Spawns the prefab with the given name.
+    fn spawn_prefab(&mut self, name: impl Into<String>) -> EntityCommands<'_>;
+}
+
+impl PrefabCommandsExt for Commands<'_, '_> {
+    fn spawn_prefab(&mut self, name: impl Into<String>) -> EntityCommands<'_> {
+        let mut entity = self.spawn(SpatialBundle::default());
+        entity.add(SpawnPrefab(name.into()));
+        entity
+    }
+}
+
+struct SpawnPrefab(String);
+
+impl EntityCommand for SpawnPrefab {
+    fn apply(self, entity: Entity, world: &mut World) {
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        world.resource_scope(|world, prefabs: Mut<Assets<Prefab>>| {
+            let Some((_, prefab)) = prefabs.iter().find(|(_, prefab)| prefab.name == self.0) else {
+                error!("Prefab '{}' not found", self.0);
+                return;
+            };
+            prefab.root.write(world, entity, &registry);
+        });
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum PrefabError {
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("deserialization: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+struct PrefabLoader {
+    registry: TypeRegistryArc,
+}
+
+impl FromWorld for PrefabLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self { registry: world.resource::<AppTypeRegistry>().0.clone() }
+    }
+}
+
+impl AssetLoader for PrefabLoader {
+    type Asset = Prefab;
+    type Settings = ();
+    type Error = PrefabError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Prefab, PrefabError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let registry = self.registry.read();
+            let mut deserializer = ron::de::Deserializer::from_bytes(&bytes)?;
+            let root = PrefabNodeDeserializer { registry: &registry }
+                .deserialize(&mut deserializer)
+                .map_err(|err| deserializer.span_error(err))?;
+
+            let file_name = load_context.path().file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            let name = file_name.strip_suffix(PREFAB_EXTENSION).unwrap_or(file_name).trim_end_matches('.').to_owned();
+
+            Ok(Prefab { name, root })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[PREFAB_EXTENSION]
+    }
+}
+
+struct PrefabNodeDeserializer<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for PrefabNodeDeserializer<'a> {
+    type Value = PrefabNode;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("Prefab", &["components", "children"], self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for PrefabNodeDeserializer<'a> {
+    type Value = PrefabNode;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("prefab struct")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut node = PrefabNode::default();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "components" => {
+                    node.components = map.next_value_seed(ComponentsDeserializer { registry: self.registry })?
+                }
+                "children" => node.children = map.next_value_seed(ChildrenDeserializer { registry: self.registry })?,
+                other => return Err(A::Error::unknown_field(other, &["components", "children"])),
+            }
+        }
+        Ok(node)
+    }
+}
+
+struct ComponentsDeserializer<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for ComponentsDeserializer<'a> {
+    type Value = Vec<Box<dyn Reflect>>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for ComponentsDeserializer<'a> {
+    type Value = Vec<Box<dyn Reflect>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("map of reflected components")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut components = Vec::new();
+        while let Some(registration) = map.next_key_seed(TypeRegistrationDeserializer::new(self.registry))? {
+            components.push(map.next_value_seed(TypedReflectDeserializer::new(registration, self.registry))?);
+        }
+        Ok(components)
+    }
+}
+
+struct ChildrenDeserializer<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for ChildrenDeserializer<'a> {
+    type Value = Vec<PrefabNode>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for ChildrenDeserializer<'a> {
+    type Value = Vec<PrefabNode>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("list of prefabs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut children = Vec::new();
+        while let Some(child) = seq.next_element_seed(PrefabNodeDeserializer { registry: self.registry })? {
+            children.push(child);
+        }
+        Ok(children)
+    }
+}
@@ -1,49 +1,93 @@
 use std::marker::PhantomData;
 
-use crate::prelude::*;
+use bevy::ecs::query::QueryFilter;
 
-pub struct PreviousValuePlugin<T: Component + Clone> {
+use crate::{prelude::*, stats::stat::Stat};
+
+/// Keeps a [`Previous<T>`] of every entity with a `T` matching `F`, updated in the given schedule when `T` changed.
+/// Added once per `T` by whatever needs it.
+pub struct PreviousPlugin<T: Component + Clone, F: QueryFilter = ()> {
     schedule: Interned<dyn ScheduleLabel>,
-    _marker: PhantomData<T>,
+    _marker: PhantomData<fn() -> (T, F)>,
 }
 
-impl<T: Component + Clone> PreviousValuePlugin<T> {
+impl<T: Component + Clone, F: QueryFilter> PreviousPlugin<T, F> {
     pub fn in_schedule(schedule: impl ScheduleLabel) -> Self {
         Self { schedule: schedule.intern(), _marker: PhantomData }
     }
 }
 
-impl<T: Component + Clone> Default for PreviousValuePlugin<T> {
+impl<T: Component + Clone, F: QueryFilter> Default for PreviousPlugin<T, F> {
     fn default() -> Self {
-        Self::in_schedule(PostUpdate)
+        Self::in_schedule(Last)
     }
 }
 
-impl<T: Component + Clone> Plugin for PreviousValuePlugin<T> {
+impl<T: Component + Clone, F: QueryFilter + 'static> Plugin for PreviousPlugin<T, F> {
     fn build(&self, app: &mut App) {
-        app.add_systems(self.schedule, propagate_previous_changed::<T>);
+        app.add_systems(self.schedule, propagate_previous_changed::<T, F>);
     }
 }
 
-#[derive(Component, Default, Deref, DerefMut, Reflect, From)]
-pub struct PreviousValue<T: Component + Clone>(T);
+/// Value of `T` when its [`PreviousPlugin`] last ran. By default that's the value of the last frame for systems
+/// running before [`Last`], it's only written to when `T` changed.
+#[derive(Component, Default, Deref, Reflect, From)]
+pub struct Previous<T: Component + Clone>(T);
 
-impl<T: Component + Clone> PreviousValue<T> {
-    #[allow(unused)]
+impl<T: Component + Clone> Previous<T> {
     pub fn get(&self) -> &T {
         &self.0
     }
+
+    /// Change from the previous to the `current` value.
+    pub fn delta(&self, current: &T) -> T::Output
+    where
+        T: Delta,
+    {
+        current.delta(&self.0)
+    }
+}
+
+/// Difference between two values of a component, see [`Previous::delta`].
+pub trait Delta {
+    type Output;
+
+    fn delta(&self, previous: &Self) -> Self::Output;
+}
+
+impl<S: Stat> Delta for S {
+    type Output = f32;
+
+    fn delta(&self, previous: &Self) -> f32 {
+        self.value() - previous.value()
+    }
+}
+
+impl Delta for Position {
+    type Output = Vector;
+
+    fn delta(&self, previous: &Self) -> Vector {
+        self.0 - previous.0
+    }
+}
+
+impl Delta for Transform {
+    type Output = Vec3;
+
+    fn delta(&self, previous: &Self) -> Vec3 {
+        self.translation - previous.translation
+    }
 }
 
-pub(crate) fn propagate_previous_changed<T: Component + Clone>(
+pub(crate) fn propagate_previous_changed<T: Component + Clone, F: QueryFilter + 'static>(
     mut commands: Commands,
-    mut values: Query<(Entity, Option<&mut PreviousValue<T>>, &T), Changed<T>>,
+    mut values: Query<(Entity, Option<&mut Previous<T>>, &T), (Changed<T>, F)>,
 ) {
-    for (entity, mut previous_value, current_value) in values.iter_mut() {
-        if let Some(previous_value) = &mut previous_value {
-            previous_value.0 = current_value.clone();
+    for (entity, previous, current) in values.iter_mut() {
+        if let Some(mut previous) = previous {
+            previous.0 = current.clone();
         } else {
-            commands.entity(entity).insert(PreviousValue(current_value.clone()));
+            commands.entity(entity).insert(Previous(current.clone()));
         }
     }
 }
@@ -0,0 +1,309 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    ecs::entity::EntityHashMap,
+    scene::{serde::SceneDeserializer, DynamicSceneBuilder, SceneFilter, SceneSpawnError},
+};
+use serde::de::DeserializeSeed;
+
+use crate::{
+    app_state::{AppState, InGameState},
+    prelude::*,
+    settings::Settings,
+    Semver, VERSION,
+};
+
+const SAVE_DIRECTORY: &str = "saves";
+const SAVE_EXTENSION: &str = "save.ron";
+const QUICK_SAVE_NAME: &str = "quicksave";
+
+/// First line of every save file, followed by the [`Semver`] the file was written with.
+const HEADER: &str = "// motte save ";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Save, SaveGame, LoadGame, Loaded);
+
+        app.add_event::<SaveGame>().add_event::<LoadGame>().add_event::<Loaded>();
+        app.world.get_resource_or_insert_with(SaveFilter::default);
+        app.world.get_resource_or_insert_with(SaveResourceFilter::default);
+
+        app.register_save::<Save>().register_save::<Name>().register_save::<Transform>();
+
+        app.add_systems(Update, quick_save.run_if(in_state(InGameState::Playing)));
+        app.add_systems(Last, (save, load).chain().run_if(in_state(AppState::InGame)));
+    }
+}
+
+/// Marks an entity to be included in save files.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Save;
+
+/// Writes all [`Save`] entities to the save file with the given name.
+#[derive(Event, Reflect, Clone, Debug, From)]
+pub struct SaveGame(pub String);
+
+/// Replaces all [`Save`] entities with the contents of the save file with the given name.
+#[derive(Event, Reflect, Clone, Debug, From)]
+pub struct LoadGame(pub String);
+
+/// Sent after a save file has been written to the world.
+#[derive(Event, Reflect, Clone, Copy, Debug)]
+pub struct Loaded;
+
+/// Components written to save files, see [`AppSaveExt::register_save`].
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct SaveFilter(SceneFilter);
+
+/// Resources written to save files, see [`AppSaveExt::register_save_resource`].
+#[derive(Resource, Deref, DerefMut)]
+pub struct SaveResourceFilter(SceneFilter);
+
+impl Default for SaveResourceFilter {
+    fn default() -> Self {
+        Self(SceneFilter::deny_all())
+    }
+}
+
+pub trait AppSaveExt {
+    /// Registers component `T` to be written to & read from save files.
+    fn register_save<T>(&mut self) -> &mut Self
+    where
+        T: Component + Reflect + FromReflect + TypePath + GetTypeRegistration;
+
+    /// Registers resource `T` to be written to & read from save files.
+    fn register_save_resource<T>(&mut self) -> &mut Self
+    where
+        T: Resource + Reflect + FromReflect + TypePath + GetTypeRegistration;
+}
+
+impl AppSaveExt for App {
+    fn register_save<T>(&mut self) -> &mut Self
+    where
+        T: Component + Reflect + FromReflect + TypePath + GetTypeRegistration,
+    {
+        self.register_type::<T>().register_type_data::<T, ReflectComponent>();
+        let mut filter = self.world.get_resource_or_insert_with(SaveFilter::default);
+        **filter = std::mem::take(&mut **filter).allow::<T>();
+        self
+    }
+
+    fn register_save_resource<T>(&mut self) -> &mut Self
+    where
+        T: Resource + Reflect + FromReflect + TypePath + GetTypeRegistration,
+    {
+        self.register_type::<T>().register_type_data::<T, ReflectResource>();
+        let mut filter = self.world.get_resource_or_insert_with(SaveResourceFilter::default);
+        **filter = std::mem::take(&mut **filter).allow::<T>();
+        self
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SaveError {
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("deserialization: {0}")]
+    SpannedRon(#[from] ron::de::SpannedError),
+    #[error("spawn: {0}")]
+    Spawn(#[from] SceneSpawnError),
+    #[error("missing or invalid save header")]
+    InvalidHeader,
+    #[error("save version {found} is incompatible with {expected}")]
+    IncompatibleVersion { found: Semver, expected: Semver },
+}
+
+#[inline]
+fn path(directory: &Path, name: &str) -> PathBuf {
+    directory.join(format!("{name}.{SAVE_EXTENSION}"))
+}
+
+fn quick_save(
+    input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut save: EventWriter<SaveGame>,
+    mut load: EventWriter<LoadGame>,
+) {
+    if input.just_pressed(settings.keybinds.quick_save) {
+        save.send(SaveGame(QUICK_SAVE_NAME.into()));
+    }
+    if input.just_pressed(settings.keybinds.quick_load) {
+        load.send(LoadGame(QUICK_SAVE_NAME.into()));
+    }
+}
+
+fn save(world: &mut World) {
+    let requests: Vec<SaveGame> = world.resource_mut::<Events<SaveGame>>().drain().collect();
+    for SaveGame(name) in requests {
+        match write(world, Path::new(SAVE_DIRECTORY), &name) {
+            Ok(path) => info!("Saved game to {path:?}"),
+            Err(err) => error!("Failed to save game '{name}': {err}"),
+        }
+    }
+}
+
+fn load(world: &mut World) {
+    let requests: Vec<LoadGame> = world.resource_mut::<Events<LoadGame>>().drain().collect();
+    // Only the last request matters, as each load replaces the previous one.
+    let Some(LoadGame(name)) = requests.into_iter().last() else {
+        return;
+    };
+
+    let directory = Path::new(SAVE_DIRECTORY);
+    match read(world, directory, &name) {
+        Ok(()) => {
+            info!("Loaded game from {:?}", path(directory, &name));
+            world.send_event(Loaded);
+        }
+        Err(err) => error!("Failed to load game '{name}': {err}"),
+    }
+}
+
+fn write(world: &mut World, directory: &Path, name: &str) -> Result<PathBuf, SaveError> {
+    let entities: Vec<Entity> = world.query_filtered::<Entity, With<Save>>().iter(world).collect();
+    let filter = world.resource::<SaveFilter>().0.clone();
+    let resource_filter = world.resource::<SaveResourceFilter>().0.clone();
+    let scene = DynamicSceneBuilder::from_world(world)
+        .with_filter(filter)
+        .with_resource_filter(resource_filter)
+        .extract_entities(entities.into_iter())
+        .extract_resources()
+        .build();
+    let serialized = scene.serialize_ron(world.resource::<AppTypeRegistry>())?;
+
+    let path = path(directory, name);
+    fs::create_dir_all(directory)?;
+    fs::write(&path, format!("{HEADER}{}\n{serialized}", *VERSION))?;
+
+    Ok(path)
+}
+
+fn read(world: &mut World, directory: &Path, name: &str) -> Result<(), SaveError> {
+    let contents = fs::read_to_string(path(directory, name))?;
+    let (header, body) = contents.split_once('\n').ok_or(SaveError::InvalidHeader)?;
+    let found: Semver =
+        header.strip_prefix(HEADER).and_then(|v| v.trim().parse().ok()).ok_or(SaveError::InvalidHeader)?;
+
+    if !found.is_compatible(&VERSION) {
+        return Err(SaveError::IncompatibleVersion { found, expected: *VERSION });
+    }
+
+    let scene = {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let mut deserializer = ron::de::Deserializer::from_str(body)?;
+        SceneDeserializer { type_registry: &registry }.deserialize(&mut deserializer)?
+    };
+
+    let existing: Vec<Entity> = world.query_filtered::<Entity, With<Save>>().iter(world).collect();
+    for entity in existing {
+        world.entity_mut(entity).despawn_recursive();
+    }
+
+    let mut entity_map = EntityHashMap::default();
+    scene.write_to_world(world, &mut entity_map)?;
+
+    // Saved entities only contain the registered components, make sure they are still part of the transform
+    // hierarchy. Other components (meshes, colliders, etc.) are expected to be added by their setup systems.
+    for &entity in entity_map.values() {
+        let mut entity = world.entity_mut(entity);
+        if entity.contains::<Transform>() && !entity.contains::<GlobalTransform>() {
+            entity.insert(GlobalTransform::default());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut app = App::new();
+        app.add_plugins(SavePlugin);
+        let world = &mut app.world;
+        let unit = world.spawn((Save, Name::new("unit"), Transform::from_xyz(1.0, 2.0, 3.0))).id();
+        world.spawn((Name::new("scenery"), Transform::default()));
+
+        let (directory, name) = (std::env::temp_dir(), "motte test round trip");
+        write(world, &directory, name).unwrap();
+        world.entity_mut(unit).insert(Transform::from_xyz(9.0, 9.0, 9.0));
+        world.spawn((Save, Name::new("spawned after saving")));
+        let result = read(world, &directory, name);
+        fs::remove_file(path(&directory, name)).unwrap();
+        result.unwrap();
+
+        let saved = world
+            .query_filtered::<(&Name, &Transform, Has<GlobalTransform>), With<Save>>()
+            .iter(world)
+            .map(|(name, transform, global)| (name.as_str().to_owned(), transform.translation, global))
+            .collect_vec();
+        assert_eq!(saved, [("unit".to_owned(), Vec3::new(1.0, 2.0, 3.0), true)]);
+        // Entities without the marker are left alone.
+        assert_eq!(world.query::<&Name>().iter(world).count(), 2);
+    }
+
+    #[test]
+    fn stockpile_round_trip() {
+        use crate::economy::{ResourceKind, Stockpile, Team};
+
+        let mut app = App::new();
+        app.add_plugins(SavePlugin);
+        app_register_types!(res: Stockpile, Team);
+        app.register_save_resource::<Stockpile>();
+        let world = &mut app.world;
+        world.resource_mut::<Stockpile>().add(Team::PLAYER, ResourceKind::Gold, 30);
+        world.resource_mut::<Stockpile>().add(Team::HOSTILE, ResourceKind::Wood, 5);
+
+        let (directory, name) = (std::env::temp_dir(), "motte test stockpile round trip");
+        write(world, &directory, name).unwrap();
+        world.resource_mut::<Stockpile>().add(Team::PLAYER, ResourceKind::Gold, 70);
+        let result = read(world, &directory, name);
+        fs::remove_file(path(&directory, name)).unwrap();
+        result.unwrap();
+
+        let stockpile = world.resource::<Stockpile>();
+        assert_eq!(stockpile.get(Team::PLAYER, ResourceKind::Gold), 30);
+        assert_eq!(stockpile.get(Team::HOSTILE, ResourceKind::Wood), 5);
+        assert_eq!(stockpile.get(Team::HOSTILE, ResourceKind::Gold), 0);
+    }
+
+    #[test]
+    fn research_round_trip() {
+        use crate::{
+            economy::Team,
+            tech::{Research, Researching, TeamResearch},
+        };
+
+        let mut app = App::new();
+        app.add_plugins(SavePlugin);
+        app_register_types!(res: Research, TeamResearch, Researching, Team);
+        app.register_save_resource::<Research>();
+        let world = &mut app.world;
+        let mut research = world.resource_mut::<Research>();
+        let player = research.get_mut(Team::PLAYER);
+        player.researched.push("masonry".into());
+        player.current = Some(Researching { tech: "forging".into(), elapsed: 2.5 });
+
+        let (directory, name) = (std::env::temp_dir(), "motte test research round trip");
+        write(world, &directory, name).unwrap();
+        world.resource_mut::<Research>().get_mut(Team::PLAYER).current = None;
+        let result = read(world, &directory, name);
+        fs::remove_file(path(&directory, name)).unwrap();
+        result.unwrap();
+
+        let player = world.resource::<Research>().get(Team::PLAYER).unwrap();
+        assert_eq!(player.researched, ["masonry"]);
+        let current = player.current.as_ref().unwrap();
+        assert_eq!((current.tech.as_str(), current.elapsed), ("forging", 2.5));
+    }
+}
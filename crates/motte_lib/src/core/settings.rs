@@ -0,0 +1,400 @@
+//! User settings, persisted as RON in the platform config directory.
+//! Loaded in [`PreStartup`] & written whenever the [`Settings`] resource changes, subsystems can listen to
+//! [`SettingsChanged`] to apply changes live.
+
+use std::{any::TypeId, fs, path::PathBuf};
+
+use bevy::{
+    reflect::{
+        serde::{TypedReflectDeserializer, TypedReflectSerializer},
+        TypeRegistry,
+    },
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use serde::de::DeserializeSeed;
+
+use crate::{launch::LaunchOptions, prelude::*};
+
+const SETTINGS_DIRECTORY: &str = "motte";
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(
+            res: Settings,
+            GraphicsSettings,
+            WindowSettings,
+            DisplayMode,
+            Quality,
+            UnitShadows,
+            AudioSettings,
+            Keybinds,
+            CameraSettings,
+            SettingsChanged
+        );
+        app.add_event::<SettingsChanged>();
+        app.add_systems(PreStartup, load);
+        app.add_systems(PreUpdate, changed.run_if(resource_changed::<Settings>));
+        app.add_systems(Update, apply_graphics.run_if(on_event::<SettingsChanged>()));
+        #[cfg(feature = "presentation")]
+        app.add_systems(Update, apply_window);
+        app.add_systems(Last, save.run_if(resource_changed::<Settings>));
+    }
+}
+
+#[derive(Resource, Reflect, Default, Clone, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct Settings {
+    pub graphics: GraphicsSettings,
+    pub audio: AudioSettings,
+    pub keybinds: Keybinds,
+    pub camera: CameraSettings,
+}
+
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct GraphicsSettings {
+    pub vsync: bool,
+    /// Asset quality, applied on the next start.
+    pub quality: Quality,
+    pub unit_shadows: UnitShadows,
+    pub window: WindowSettings,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            quality: Quality::default(),
+            unit_shadows: UnitShadows::default(),
+            window: WindowSettings::default(),
+        }
+    }
+}
+
+/// Resolutions cycled through by the settings screen.
+pub const RESOLUTIONS: [UVec2; 5] = [
+    UVec2::new(1280, 720),
+    UVec2::new(1600, 900),
+    UVec2::new(1920, 1080),
+    UVec2::new(2560, 1440),
+    UVec2::new(3840, 2160),
+];
+
+/// Mode, monitor & size of the primary window, overridden by the `--fullscreen` & `--resolution` launch options.
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct WindowSettings {
+    pub mode: DisplayMode,
+    /// Index of the monitor, falls back to the first monitor if it isn't connected.
+    pub monitor: usize,
+    /// Logical size of the window, also picks the video mode in [`DisplayMode::Exclusive`].
+    pub resolution: UVec2,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self { mode: DisplayMode::default(), monitor: 0, resolution: RESOLUTIONS[0] }
+    }
+}
+
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// Fullscreen at the resolution of the monitor.
+    Borderless,
+    /// Fullscreen with the video mode of the monitor closest to the resolution.
+    Exclusive,
+}
+
+impl DisplayMode {
+    pub fn window_mode(self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Exclusive => WindowMode::SizedFullscreen,
+        }
+    }
+}
+
+/// How units cast shadows, see [`BlobShadow`](crate::graphics::blob_shadow::BlobShadow).
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnitShadows {
+    /// Projected blobs under the units, they don't cast shadow-mapped shadows.
+    #[default]
+    Blob,
+    /// Shadow-mapped by the directional light like the rest of the scene.
+    ShadowMap,
+}
+
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quality {
+    #[cfg_attr(target_arch = "wasm32", default)]
+    Low,
+    Medium,
+    #[cfg_attr(not(target_arch = "wasm32"), default)]
+    High,
+}
+
+impl Quality {
+    /// Suffix of asset variants for this quality, [`Quality::High`] uses the original assets.
+    pub fn suffix(self) -> Option<&'static str> {
+        match self {
+            Quality::Low => Some("low"),
+            Quality::Medium => Some("medium"),
+            Quality::High => None,
+        }
+    }
+
+    /// This quality followed by all higher qualities.
+    pub fn fallbacks(self) -> impl Iterator<Item = Quality> {
+        [Quality::Low, Quality::Medium, Quality::High].into_iter().filter(move |quality| *quality >= self)
+    }
+}
+
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+    pub ui: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self { master: 1.0, music: 0.5, effects: 1.0, ui: 1.0 }
+    }
+}
+
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct Keybinds {
+    pub camera_yaw_left: KeyCode,
+    pub camera_yaw_right: KeyCode,
+    pub camera_pitch_up: KeyCode,
+    pub camera_pitch_down: KeyCode,
+    pub camera_reset: KeyCode,
+    pub quick_save: KeyCode,
+    pub quick_load: KeyCode,
+    pub pause: KeyCode,
+    pub build: KeyCode,
+    pub rotate_building_left: KeyCode,
+    pub rotate_building_right: KeyCode,
+    /// Casts the spell in the matching slot of the selected unit's spell book.
+    pub abilities: [KeyCode; 4],
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        Self {
+            camera_yaw_left: KeyCode::KeyQ,
+            camera_yaw_right: KeyCode::KeyE,
+            camera_pitch_up: KeyCode::KeyW,
+            camera_pitch_down: KeyCode::KeyS,
+            camera_reset: KeyCode::KeyR,
+            quick_save: KeyCode::F9,
+            quick_load: KeyCode::F10,
+            pause: KeyCode::Escape,
+            build: KeyCode::KeyB,
+            rotate_building_left: KeyCode::KeyZ,
+            rotate_building_right: KeyCode::KeyX,
+            abilities: [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4],
+        }
+    }
+}
+
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct CameraSettings {
+    /// Degrees rotated per yaw input.
+    pub yaw_step: f32,
+    /// Degrees rotated per pitch input.
+    pub pitch_step: f32,
+    /// Zoom per scroll wheel line.
+    pub zoom_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self { yaw_step: 90.0, pitch_step: 5.0, zoom_speed: 1.0 }
+    }
+}
+
+/// Sent when [`Settings`] has changed (including after being loaded).
+#[derive(Event, Reflect, Clone, Copy, Debug)]
+pub struct SettingsChanged;
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("deserialization: {0}")]
+    SpannedRon(#[from] ron::de::SpannedError),
+    #[error("no config directory found")]
+    NoConfigDirectory,
+}
+
+/// Platform config directory, e.g. `~/.config/motte` on Linux.
+pub(crate) fn directory() -> Option<PathBuf> {
+    use std::env::var_os;
+
+    #[cfg(target_os = "windows")]
+    let base = var_os("APPDATA").map(PathBuf::from);
+    #[cfg(target_os = "macos")]
+    let base = var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"));
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    base.map(|base| base.join(SETTINGS_DIRECTORY))
+}
+
+fn path() -> Result<PathBuf, SettingsError> {
+    directory().map(|directory| directory.join(SETTINGS_FILE)).ok_or(SettingsError::NoConfigDirectory)
+}
+
+fn load(world: &mut World) {
+    let path = match path() {
+        Ok(path) => path,
+        Err(err) => {
+            warn!("Failed to load settings: {err}");
+            return;
+        }
+    };
+
+    if !path.exists() {
+        return;
+    }
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    match read(&path, &registry.read()) {
+        Ok(settings) => {
+            info!("Loaded settings from {path:?}");
+            world.insert_resource(settings);
+        }
+        Err(err) => error!("Failed to load settings from {path:?}: {err}"),
+    }
+}
+
+fn save(settings: Res<Settings>, registry: Res<AppTypeRegistry>) {
+    // Only changes are saved, not the defaults or loaded settings inserted before the first frame.
+    if settings.is_added() {
+        return;
+    }
+    if let Err(err) = write(&settings, &registry.read()) {
+        error!("Failed to save settings: {err}");
+    }
+}
+
+fn read(path: &PathBuf, registry: &TypeRegistry) -> Result<Settings, SettingsError> {
+    let contents = fs::read_to_string(path)?;
+    let registration = registry.get(TypeId::of::<Settings>()).expect("Settings should be registered");
+    let mut deserializer = ron::de::Deserializer::from_str(&contents)?;
+    let reflected = TypedReflectDeserializer::new(registration, registry)
+        .deserialize(&mut deserializer)
+        .map_err(|err| deserializer.span_error(err))?;
+
+    // Apply onto the defaults, so settings missing from the file keep their default value.
+    let mut settings = Settings::default();
+    settings.apply(&*reflected);
+    Ok(settings)
+}
+
+fn write(settings: &Settings, registry: &TypeRegistry) -> Result<(), SettingsError> {
+    let path = path()?;
+    let serialized = ron::ser::to_string_pretty(
+        &TypedReflectSerializer::new(settings, registry),
+        ron::ser::PrettyConfig::default(),
+    )?;
+    fs::create_dir_all(path.parent().expect("settings path should have a parent"))?;
+    fs::write(path, serialized)?;
+    Ok(())
+}
+
+fn changed(mut events: EventWriter<SettingsChanged>) {
+    events.send(SettingsChanged);
+}
+
+fn apply_graphics(
+    settings: Res<Settings>,
+    launch: Option<Res<LaunchOptions>>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window.get_single_mut() else {
+        return;
+    };
+
+    let vsync = if settings.graphics.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync };
+    let present_mode = launch.and_then(|launch| launch.present_mode).unwrap_or(vsync);
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+}
+
+/// Applies the [`WindowSettings`] that changed since they were last applied, so resizing or moving the window by
+/// hand sticks until the settings change it. The render textures of the pixelate cameras follow the resize of the
+/// window. Fullscreen modes stay on the monitor the window is on, so when moving to another monitor the window is
+/// first windowed & moved, the mode is applied the frame after.
+#[cfg(feature = "presentation")]
+fn apply_window(
+    mut changed: EventReader<SettingsChanged>,
+    mut settings: ResMut<Settings>,
+    launch: Option<Res<LaunchOptions>>,
+    mut window: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    winit: Option<NonSend<bevy::winit::WinitWindows>>,
+    mut applied: Local<Option<WindowSettings>>,
+    mut pending: Local<Option<WindowMode>>,
+) {
+    use bevy::window::{MonitorSelection, WindowPosition};
+
+    let Ok((entity, mut window)) = window.get_single_mut() else {
+        return;
+    };
+
+    if changed.read().count() == 0 {
+        if let Some(mode) = pending.take() {
+            window.mode = mode;
+        }
+        return;
+    }
+
+    let monitors =
+        winit.as_ref().and_then(|winit| winit.get_window(entity)).map(|winit| winit.available_monitors().count());
+    if monitors.is_some_and(|monitors| settings.graphics.window.monitor >= monitors) {
+        warn!("Monitor {} isn't connected, using the first monitor", settings.graphics.window.monitor);
+        settings.graphics.window.monitor = 0;
+    }
+
+    let mut target = settings.graphics.window.clone();
+    if let Some(launch) = launch.as_deref() {
+        if launch.fullscreen {
+            target.mode = DisplayMode::Borderless;
+        }
+        if let Some(resolution) = launch.resolution {
+            target.resolution = resolution.as_uvec2();
+        }
+    }
+    let previous = applied.replace(target.clone());
+    let resized = previous.as_ref().map_or(true, |previous| previous.resolution != target.resolution);
+    let moved = previous.as_ref().map_or(true, |previous| previous.monitor != target.monitor);
+
+    if resized {
+        window.resolution.set(target.resolution.x as f32, target.resolution.y as f32);
+    }
+
+    let mode = target.mode.window_mode();
+    *pending = None;
+    if moved {
+        window.position = WindowPosition::Centered(MonitorSelection::Index(target.monitor));
+        if window.mode != WindowMode::Windowed {
+            window.mode = WindowMode::Windowed;
+            *pending = Some(mode).filter(|mode| *mode != WindowMode::Windowed);
+            return;
+        }
+    }
+    if window.mode != mode {
+        window.mode = mode;
+    }
+}
@@ -0,0 +1,77 @@
+//! Tick rate of the simulation. Navigation, movement & the [`GameplaySystems`] run in [`FixedUpdate`] & physics steps
+//! at the same rate, lower it for large battles where a 30 Hz simulation is enough.
+
+use crate::{app_state::simulating, movement::MovementSystems, prelude::*};
+
+ordered_sets! {
+    /// Rules of the game advanced every tick once the units moved, so every peer resolves them on the same tick.
+    pub enum GameplaySystems: FixedUpdate {
+        /// Resolves spell hits into damage & on-hit effects.
+        Hits.after(MovementSystems::State),
+        /// Applies the damage of the tick to shields & health.
+        Damage,
+        /// Scenario triggers & their actions.
+        Scenario,
+        /// Victory & defeat, after everything else that happened this tick.
+        Objectives,
+    }
+    .run_if(simulating)
+}
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(res: SimulationConfig);
+        // Applied right away so the timesteps are correct for anything reading them while building the app.
+        let config = *app.world.resource::<SimulationConfig>();
+        config.apply(&mut app.world);
+        GameplaySystems::configure(app);
+        app.add_systems(First, apply.run_if(resource_changed::<SimulationConfig>));
+    }
+}
+
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct SimulationConfig {
+    /// Fixed updates & physics steps per second.
+    pub tick_rate: f64,
+    /// Physics substeps per step, more substeps make stacking & joints more stable.
+    pub substeps: u32,
+}
+
+impl SimulationConfig {
+    pub const DEFAULT: Self = Self { tick_rate: 60.0, substeps: 12 };
+
+    pub fn timestep(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.tick_rate.max(1.0))
+    }
+
+    fn apply(&self, world: &mut World) {
+        let timestep = self.timestep();
+        if let Some(mut time) = world.get_resource_mut::<Time<Fixed>>() {
+            time.set_timestep(timestep);
+        }
+        if let Some(mut time) = world.get_resource_mut::<Time<Physics>>() {
+            match time.timestep_mode_mut() {
+                TimestepMode::Fixed { delta, .. } | TimestepMode::FixedOnce { delta } => *delta = timestep,
+                TimestepMode::Variable { max_delta } => *max_delta = timestep,
+            }
+        }
+        if let Some(mut substeps) = world.get_resource_mut::<SubstepCount>() {
+            substeps.0 = self.substeps.max(1);
+        }
+    }
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+fn apply(world: &mut World) {
+    let config = *world.resource::<SimulationConfig>();
+    config.apply(world);
+    info!("Simulation running at {} Hz with {} substeps", config.tick_rate, config.substeps);
+}
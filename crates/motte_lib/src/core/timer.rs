@@ -0,0 +1,251 @@
+use crate::{app_state::simulating, prelude::*};
+
+/// Ticks the [`Cooldown<T>`] & [`DelayedAction<T>`] components while simulating & sends their [`Ready<T>`] &
+/// [`Expired<T>`] events, `T` tells the timers of different abilities on the same entity apart.
+pub struct TimerPlugin<T>(PhantomData<T>);
+
+impl<T> Default for TimerPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: TypePath + Send + Sync + 'static> Plugin for TimerPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Cooldown<T>, DelayedAction<T>);
+        app.add_event::<Ready<T>>().add_event::<Expired<T>>();
+        app.add_systems(FixedUpdate, (cooldowns::<T>, delayed_actions::<T>).run_if(simulating));
+    }
+}
+
+/// Cooldown of `T`, ready once its duration passed since it was started. Can also be kept & ticked by hand, e.g. one
+/// per slot of a [`SpellBook`](crate::spells::SpellBook).
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct Cooldown<T: TypePath + Send + Sync> {
+    duration: f32,
+    remaining: f32,
+    #[reflect(ignore)]
+    _m: PhantomData<T>,
+}
+
+impl<T: TypePath + Send + Sync> Default for Cooldown<T> {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl<T: TypePath + Send + Sync> Cooldown<T> {
+    /// Cooldown of `duration` seconds, ready until it's started.
+    pub fn new(duration: f32) -> Self {
+        Self { duration, remaining: 0.0, _m: PhantomData }
+    }
+
+    pub fn start(&mut self) {
+        self.remaining = self.duration;
+    }
+
+    /// Makes it ready right away.
+    pub fn reset(&mut self) {
+        self.remaining = 0.0;
+    }
+
+    /// Starts the cooldown with a new duration.
+    pub fn start_with(&mut self, duration: f32) {
+        self.duration = duration;
+        self.start();
+    }
+
+    pub fn ready(&self) -> bool {
+        self.remaining <= 0.0
+    }
+
+    /// Seconds left until it's ready.
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+
+    /// Fraction of the duration left, 0 when it's ready.
+    pub fn fraction(&self) -> f32 {
+        if self.duration > 0.0 {
+            self.remaining / self.duration
+        } else {
+            0.0
+        }
+    }
+
+    /// Advances the cooldown by `delta` seconds, returns true if it became ready.
+    pub fn tick(&mut self, delta: f32) -> bool {
+        if self.ready() {
+            return false;
+        }
+        self.remaining = (self.remaining - delta).max(0.0);
+        self.ready()
+    }
+
+    /// Advances a repeating cooldown by `delta` seconds, returns true if it became ready & starts it again. The time
+    /// past ready is carried over, so the repeats don't drift.
+    pub fn repeat(&mut self, delta: f32) -> bool {
+        self.remaining -= delta;
+        let ready = self.remaining <= 0.0;
+        if ready {
+            self.remaining = (self.remaining + self.duration).max(0.0);
+        }
+        ready
+    }
+}
+
+/// Sends [`Expired<T>`] once `delay` seconds passed & removes itself.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+#[component(storage = "SparseSet")]
+pub struct DelayedAction<T: TypePath + Send + Sync> {
+    remaining: f32,
+    #[reflect(ignore)]
+    _m: PhantomData<T>,
+}
+
+impl<T: TypePath + Send + Sync> Default for DelayedAction<T> {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl<T: TypePath + Send + Sync> DelayedAction<T> {
+    pub fn new(delay: f32) -> Self {
+        Self { remaining: delay, _m: PhantomData }
+    }
+
+    /// Seconds left until it expires.
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+}
+
+/// The [`Cooldown<T>`] of `entity` became ready.
+#[derive(Event)]
+pub struct Ready<T: Send + Sync + 'static> {
+    pub entity: Entity,
+    _m: PhantomData<T>,
+}
+
+/// The [`DelayedAction<T>`] of `entity` expired & was removed.
+#[derive(Event)]
+pub struct Expired<T: Send + Sync + 'static> {
+    pub entity: Entity,
+    _m: PhantomData<T>,
+}
+
+fn cooldowns<T: TypePath + Send + Sync + 'static>(
+    mut cooldowns: Query<(Entity, &mut Cooldown<T>)>,
+    mut ready: EventWriter<Ready<T>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut cooldown) in &mut cooldowns {
+        if !cooldown.ready() && cooldown.tick(delta) {
+            ready.send(Ready { entity, _m: PhantomData });
+        }
+    }
+}
+
+fn delayed_actions<T: TypePath + Send + Sync + 'static>(
+    mut commands: Commands,
+    mut actions: Query<(Entity, &mut DelayedAction<T>)>,
+    mut expired: EventWriter<Expired<T>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut action) in &mut actions {
+        action.remaining -= delta;
+        if action.remaining <= 0.0 {
+            commands.entity(entity).remove::<DelayedAction<T>>();
+            expired.send(Expired { entity, _m: PhantomData });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Test = ();
+
+    #[test]
+    fn cooldown() {
+        let mut cooldown = Cooldown::<Test>::new(1.0);
+        assert!(cooldown.ready());
+        assert!(!cooldown.tick(0.5), "ticking a ready cooldown doesn't make it ready again");
+
+        cooldown.start();
+        assert!(!cooldown.ready());
+        assert_eq!(cooldown.fraction(), 1.0);
+        assert!(!cooldown.tick(0.75));
+        assert_eq!(cooldown.remaining(), 0.25);
+        assert!(cooldown.tick(0.5));
+        assert!(cooldown.ready());
+        assert_eq!(cooldown.remaining(), 0.0);
+
+        cooldown.start_with(2.0);
+        assert_eq!(cooldown.remaining(), 2.0);
+        cooldown.reset();
+        assert!(cooldown.ready());
+        assert_eq!(cooldown.fraction(), 0.0);
+    }
+
+    #[test]
+    fn repeat_carries_over() {
+        let mut cooldown = Cooldown::<Test>::new(1.0);
+        cooldown.start();
+        assert!(!cooldown.repeat(0.75));
+        assert!(cooldown.repeat(0.5));
+        assert_eq!(cooldown.remaining(), 0.75);
+        assert!(cooldown.repeat(0.75));
+        assert_eq!(cooldown.remaining(), 1.0);
+    }
+
+    fn world() -> (World, Schedule) {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<Ready<Test>>>();
+        world.init_resource::<Events<Expired<Test>>>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems((cooldowns::<Test>, delayed_actions::<Test>));
+        (world, schedule)
+    }
+
+    fn advance(world: &mut World, schedule: &mut Schedule, seconds: f32) {
+        world.resource_mut::<Time>().advance_by(Duration::from_secs_f32(seconds));
+        schedule.run(world);
+    }
+
+    #[test]
+    fn ready_once() {
+        let (mut world, mut schedule) = world();
+        let mut cooldown = Cooldown::<Test>::new(1.0);
+        cooldown.start();
+        let entity = world.spawn(cooldown).id();
+
+        advance(&mut world, &mut schedule, 0.5);
+        assert!(world.resource::<Events<Ready<Test>>>().is_empty());
+        advance(&mut world, &mut schedule, 0.5);
+        advance(&mut world, &mut schedule, 0.5);
+        let ready = world.resource_mut::<Events<Ready<Test>>>().drain().map(|ready| ready.entity).collect_vec();
+        assert_eq!(ready, [entity]);
+        assert!(world.get::<Cooldown<Test>>(entity).unwrap().ready());
+    }
+
+    #[test]
+    fn expires_once() {
+        let (mut world, mut schedule) = world();
+        let entity = world.spawn(DelayedAction::<Test>::new(1.0)).id();
+
+        advance(&mut world, &mut schedule, 0.5);
+        assert_eq!(world.get::<DelayedAction<Test>>(entity).unwrap().remaining(), 0.5);
+        advance(&mut world, &mut schedule, 0.5);
+        advance(&mut world, &mut schedule, 0.5);
+        let expired = world.resource_mut::<Events<Expired<Test>>>().drain().map(|expired| expired.entity).collect_vec();
+        assert_eq!(expired, [entity]);
+        assert!(!world.entity(entity).contains::<DelayedAction<Test>>());
+    }
+}
@@ -0,0 +1,59 @@
+//! Wall time of the navigation system sets, recorded as [`Diagnostic`]s in milliseconds for the perf ui & headless
+//! reports. The amount of work they did is recorded as counters next to it, e.g. [`DIRTY_FIELDS`].
+
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+
+use crate::{
+    app_state::simulating,
+    navigation::{flow_field::FlowFieldSystems, NavigationSystems},
+    prelude::*,
+};
+
+pub static SPLAT: DiagnosticPath = DiagnosticPath::const_new("navigation/splat");
+pub static BUILD: DiagnosticPath = DiagnosticPath::const_new("navigation/build");
+pub static PATHING: DiagnosticPath = DiagnosticPath::const_new("navigation/pathing");
+pub static AVOIDANCE: DiagnosticPath = DiagnosticPath::const_new("navigation/avoidance");
+
+/// Flow fields waiting to be rebuilt at the start of a tick.
+pub static DIRTY_FIELDS: DiagnosticPath = DiagnosticPath::const_new("navigation/dirty_fields");
+/// Neighbors avoided by all agents in a tick.
+pub static AVOIDANCE_NEIGHBORS: DiagnosticPath = DiagnosticPath::const_new("navigation/avoidance_neighbors");
+
+/// Every recorded timing with a display label.
+#[cfg(any(feature = "dev_tools", feature = "headless"))]
+pub static ALL: [(&str, &DiagnosticPath); 4] =
+    [("Splat", &SPLAT), ("Build", &BUILD), ("Pathing", &PATHING), ("Avoidance", &AVOIDANCE)];
+
+pub struct SystemTimingsPlugin;
+
+impl Plugin for SystemTimingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimingStarts>();
+        add_system_timing(app, &SPLAT, FlowFieldSystems::DetectChanges, FlowFieldSystems::Splat);
+        add_system_timing(app, &BUILD, FlowFieldSystems::Splat, FlowFieldSystems::Build);
+        add_system_timing(app, &PATHING, FlowFieldSystems::Build, FlowFieldSystems::Pathing);
+        add_system_timing(app, &AVOIDANCE, NavigationSystems::Velocity, NavigationSystems::Avoidance);
+        app.register_diagnostic(Diagnostic::new(DIRTY_FIELDS.clone()));
+        app.register_diagnostic(Diagnostic::new(AVOIDANCE_NEIGHBORS.clone()));
+    }
+}
+
+#[derive(Resource, Default, Deref, DerefMut)]
+struct TimingStarts(HashMap<&'static str, Instant>);
+
+/// Measures the wall time from after `after` until `set` has finished, sets run in the chained order of the
+/// navigation schedule so nothing but `set` should run in between.
+fn add_system_timing(app: &mut App, path: &'static DiagnosticPath, after: impl SystemSet, set: impl SystemSet + Clone) {
+    app.register_diagnostic(Diagnostic::new(path.clone()).with_suffix("ms"));
+
+    let begin = move |mut starts: ResMut<TimingStarts>| {
+        starts.insert(path.as_str(), Instant::now());
+    };
+    let end = move |mut starts: ResMut<TimingStarts>, mut diagnostics: Diagnostics| {
+        if let Some(start) = starts.remove(path.as_str()) {
+            diagnostics.add_measurement(path, || start.elapsed().as_secs_f64() * 1000.0);
+        }
+    };
+
+    app.add_systems(FixedUpdate, (begin.after(after).before(set.clone()), end.after(set)).run_if(simulating));
+}
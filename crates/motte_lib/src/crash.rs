@@ -0,0 +1,193 @@
+//! Writes a crash report when the game panics, with the last log lines, system info & a summary of the game's state,
+//! to `crashes/` in the config directory (e.g. `~/.config/motte/crashes` on Linux) & tells the player where to find it.
+//!
+//! [`install`] sets the panic hook, [`capture_logs`] has to be passed to [`LogPlugin::update_subscriber`] for the log
+//! lines & [`CrashReportPlugin`] keeps the summary up to date.
+//!
+//! [`LogPlugin::update_subscriber`]: bevy::log::LogPlugin::update_subscriber
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, TryLockError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    ecs::entity::Entities,
+    log::{
+        tracing_subscriber::{layer::Context as LayerContext, prelude::*, Layer},
+        BoxedSubscriber,
+    },
+    render::renderer::RenderAdapterInfo,
+    utils::tracing::{
+        field::{Field, Visit},
+        Event, Subscriber,
+    },
+};
+
+use crate::{app_state::AppState, navigation::agent::Agent, prelude::*, settings, GIT_VERSION, VERSION};
+
+/// Log lines kept for the report.
+const LOG_LINES: usize = 200;
+const DIRECTORY: &str = "crashes";
+
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static SUMMARY: Mutex<Summary> = Mutex::new(Summary::new());
+
+pub struct CrashReportPlugin;
+
+impl Plugin for CrashReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, summarize);
+    }
+}
+
+/// State of the game when it crashed, updated every frame.
+#[derive(Debug)]
+struct Summary {
+    state: Option<AppState>,
+    frame: u64,
+    entities: u32,
+    agents: usize,
+    adapter: Option<String>,
+}
+
+impl Summary {
+    const fn new() -> Self {
+        Self { state: None, frame: 0, entities: 0, agents: 0, adapter: None }
+    }
+}
+
+fn summarize(
+    state: Option<Res<State<AppState>>>,
+    entities: &Entities,
+    agents: Query<(), With<Agent>>,
+    adapter: Option<Res<RenderAdapterInfo>>,
+) {
+    let mut summary = lock(&SUMMARY);
+    summary.state = state.map(|state| state.get().clone());
+    summary.frame += 1;
+    summary.entities = entities.len();
+    summary.agents = agents.iter().len();
+    if summary.adapter.is_none() {
+        summary.adapter = adapter.map(|adapter| format!("{} ({:?})", adapter.name, adapter.backend));
+    }
+}
+
+/// Sets the panic hook, the previous hook still runs first. `dialog` shows a message box with the report's location.
+pub fn install(dialog: bool) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        let path = match write(info) {
+            Ok(path) => path,
+            Err(err) => {
+                eprintln!("failed to write crash report: {err:?}");
+                return;
+            }
+        };
+        eprintln!("crash report written to {}", path.display());
+        if dialog {
+            let text = format!(
+                "Sorry, the game crashed.\n\nA crash report was written to {}, please attach it when reporting the \
+                 issue.",
+                path.display()
+            );
+            let title = format!("{} crashed", env!("CARGO_PKG_NAME"));
+            let alert = native_dialog::MessageDialog::new()
+                .set_type(native_dialog::MessageType::Error)
+                .set_title(&title)
+                .set_text(&text)
+                .show_alert();
+            if let Err(err) = alert {
+                eprintln!("failed to show crash dialog: {err}");
+            }
+        }
+    }));
+}
+
+fn write(info: &dyn std::fmt::Display) -> AnyResult<PathBuf> {
+    let directory = settings::directory().context("no config directory found")?.join(DIRECTORY);
+    std::fs::create_dir_all(&directory)?;
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let path = directory.join(format!("crash-{time}.txt"));
+    std::fs::write(&path, report(info))?;
+    Ok(path)
+}
+
+fn report(info: &dyn std::fmt::Display) -> String {
+    let mut report = String::new();
+    let thread = std::thread::current();
+    let _ = writeln!(report, "{info}");
+    let _ = writeln!(report, "thread: {}", thread.name().unwrap_or("<unnamed>"));
+    let _ = writeln!(report, "\n# version\n{} ({GIT_VERSION})", *VERSION);
+    let _ = writeln!(report, "\n# system");
+    let _ = writeln!(report, "os: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let cpus = std::thread::available_parallelism().map_or(0, |cpus| cpus.get());
+    let _ = writeln!(report, "cpus: {cpus}");
+    if let Some(summary) = try_lock(&SUMMARY) {
+        let _ = writeln!(report, "adapter: {}", summary.adapter.as_deref().unwrap_or("none"));
+        let _ = writeln!(report, "\n# game");
+        let _ = writeln!(report, "state: {:?}", summary.state);
+        let _ = writeln!(report, "frame: {}", summary.frame);
+        let _ = writeln!(report, "entities: {}", summary.entities);
+        let _ = writeln!(report, "agents: {}", summary.agents);
+    }
+    if let Some(log) = try_lock(&LOG) {
+        let _ = writeln!(report, "\n# log");
+        for line in log.iter() {
+            let _ = writeln!(report, "{line}");
+        }
+    }
+    report
+}
+
+/// Locks ignoring poisoning, the hook may run after a thread panicked while holding the lock.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Like [`lock`] but gives up if the lock is held, e.g. by the panicking thread itself.
+fn try_lock<T>(mutex: &Mutex<T>) -> Option<MutexGuard<'_, T>> {
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// Keeps the last log lines for the crash report.
+pub fn capture_logs(subscriber: BoxedSubscriber) -> BoxedSubscriber {
+    Box::new(subscriber.with(LogBuffer))
+}
+
+struct LogBuffer;
+
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!("{} {}:", metadata.level(), metadata.target());
+        event.record(&mut LineVisitor(&mut line));
+        // Skip instead of blocking when the panic hook holds the lock.
+        let Ok(mut log) = LOG.try_lock() else {
+            return;
+        };
+        if log.len() == LOG_LINES {
+            log.pop_front();
+        }
+        log.push_back(line);
+    }
+}
+
+struct LineVisitor<'a>(&'a mut String);
+
+impl Visit for LineVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let _ = match field.name() {
+            "message" => write!(self.0, " {value:?}"),
+            name => write!(self.0, " {name}={value:?}"),
+        };
+    }
+}
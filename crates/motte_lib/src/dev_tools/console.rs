@@ -0,0 +1,389 @@
+//! Developer console, toggle with [`key_codes::TOGGLE_CONSOLE`]. Commands are one-shot systems registered with
+//! [`ConsoleAppExt::add_console_command`] that take the arguments after the command name & return the output line.
+//! `Tab` completes the current word, `Up`/`Down` browse the history.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use bevy::{
+    ecs::system::SystemId,
+    input::common_conditions::input_toggle_active,
+    reflect::{ReflectMut, Struct},
+    window::PrimaryWindow,
+};
+use bevy_egui::{egui, EguiContext};
+
+use super::{
+    heatmap::{HeatmapLayer, HeatmapMode},
+    key_codes, snapshot, spawn_menu, AgentDebugLayer, DebugLayers,
+};
+use crate::{
+    determinism::GameRng,
+    movement::motor::JumpHeight,
+    navigation::{
+        agent::{Agent, Speed},
+        flow_field::{layout::FieldLayout, pathing::Goal},
+    },
+    prelude::*,
+    stats::{modifier::Flat, stat::Stat},
+    utils::math::random_point_in_square,
+};
+
+const MAX_OUTPUT_LINES: usize = 256;
+
+pub type ConsoleResult = Result<String, String>;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>();
+        app.add_systems(Update, console_ui.run_if(input_toggle_active(false, key_codes::TOGGLE_CONSOLE)));
+
+        app.add_console_command("help", "help", &[], help)
+            .add_console_command("spawn", "spawn unit [count]", &[&["unit"]], spawn)
+            .add_console_command("set", "set stat <name> <value>", &[&["stat"]], set)
+            .add_console_command("toggle", "toggle gizmo <layer> [small|medium|large|huge]", &[&["gizmo"]], toggle)
+            .add_console_command("goal", "goal all (x,y)", &[&["all"]], goal)
+            .add_console_command(
+                "snapshot",
+                "snapshot [small|medium|large|huge]",
+                &[&["small", "medium", "large", "huge"]],
+                snapshot::snapshot,
+            );
+
+        app.add_console_stat::<Speed>().add_console_stat::<JumpHeight>();
+
+        let layers = DebugLayers::default();
+        let layers: Vec<_> = (0..layers.field_len())
+            .filter_map(|i| layers.name_at(i)?.strip_prefix("debug_").map(str::to_owned))
+            .collect();
+        app.world.resource_mut::<Console>().add_completions("toggle", 1, layers);
+    }
+}
+
+pub trait ConsoleAppExt {
+    /// Registers a console command, `completions` are the candidates for each argument.
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        completions: &[&[&str]],
+        system: impl IntoSystem<Vec<String>, ConsoleResult, M> + 'static,
+    ) -> &mut Self;
+
+    /// Allows setting the base value of stat `S` with `set stat <name> <value>`.
+    fn add_console_stat<S: Stat + Component>(&mut self) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        completions: &[&[&str]],
+        system: impl IntoSystem<Vec<String>, ConsoleResult, M> + 'static,
+    ) -> &mut Self {
+        let system = self.world.register_system(system);
+        let completions =
+            completions.iter().map(|candidates| candidates.iter().map(|c| (*c).to_owned()).collect()).collect();
+        self.world.resource_mut::<Console>().commands.insert(name, ConsoleCommand { usage, system, completions });
+        self
+    }
+
+    fn add_console_stat<S: Stat + Component>(&mut self) -> &mut Self {
+        let name = S::name();
+        let mut console = self.world.resource_mut::<Console>();
+        console.stats.insert(name.clone(), set_stat::<S>);
+        console.add_completions("set", 1, [name]);
+        self
+    }
+}
+
+struct ConsoleCommand {
+    usage: &'static str,
+    system: SystemId<Vec<String>, ConsoleResult>,
+    completions: Vec<Vec<String>>,
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    commands: BTreeMap<&'static str, ConsoleCommand>,
+    stats: BTreeMap<String, fn(&mut World, f32) -> usize>,
+    output: VecDeque<String>,
+    history: Vec<String>,
+}
+
+impl Console {
+    fn add_completions(&mut self, name: &str, argument: usize, candidates: impl IntoIterator<Item = String>) {
+        let Some(command) = self.commands.get_mut(name) else {
+            return;
+        };
+        if command.completions.len() <= argument {
+            command.completions.resize_with(argument + 1, Vec::new);
+        }
+        command.completions[argument].extend(candidates);
+    }
+
+    fn print(&mut self, line: impl Into<String>) {
+        if self.output.len() == MAX_OUTPUT_LINES {
+            self.output.pop_front();
+        }
+        self.output.push_back(line.into());
+    }
+
+    /// Candidates for the last word of `input`.
+    fn complete(&self, input: &str) -> Vec<String> {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let (index, prefix) = match input.ends_with(char::is_whitespace) {
+            true => (words.len(), ""),
+            false => (words.len().saturating_sub(1), words.last().copied().unwrap_or_default()),
+        };
+
+        let candidates: Box<dyn Iterator<Item = &str>> = if index == 0 {
+            Box::new(self.commands.keys().copied())
+        } else {
+            let Some(command) = words.first().and_then(|name| self.commands.get(name)) else {
+                return Vec::new();
+            };
+            let Some(argument) = command.completions.get(index - 1) else {
+                return Vec::new();
+            };
+            Box::new(argument.iter().map(String::as_str))
+        };
+
+        candidates.filter(|candidate| candidate.starts_with(prefix)).map(str::to_owned).collect()
+    }
+}
+
+/// Runs the command line, the first word is the command name.
+fn execute(world: &mut World, line: &str) {
+    let mut words = line.split_whitespace().map(str::to_owned);
+    let Some(name) = words.next() else {
+        return;
+    };
+
+    let mut console = world.resource_mut::<Console>();
+    console.print(format!("> {line}"));
+    if console.history.last().map(String::as_str) != Some(line) {
+        console.history.push(line.to_owned());
+    }
+
+    let Some(system) = console.commands.get(name.as_str()).map(|command| command.system) else {
+        console.print(format!("unknown command '{name}', see 'help'"));
+        return;
+    };
+
+    let output = match world.run_system_with_input(system, words.collect()) {
+        Ok(Ok(output)) => output,
+        Ok(Err(err)) => format!("error: {err}"),
+        Err(err) => format!("error: {err:?}"),
+    };
+    if !output.is_empty() {
+        world.resource_mut::<Console>().print(output);
+    }
+}
+
+#[derive(Default)]
+struct ConsoleState {
+    input: String,
+    /// Position in [`Console::history`] while browsing, from the end.
+    history_index: Option<usize>,
+    /// Candidates shown when the completion is ambiguous.
+    hint: Option<String>,
+}
+
+fn console_ui(world: &mut World, mut state: Local<ConsoleState>) {
+    let mut egui_context = world.query_filtered::<&mut EguiContext, With<PrimaryWindow>>().single(world).clone();
+    let mut submitted = None;
+
+    egui::Window::new("Console").default_width(600.0).anchor(egui::Align2::CENTER_TOP, [0.0, 16.0]).show(
+        egui_context.get_mut(),
+        |ui| {
+            let console = world.resource::<Console>();
+
+            egui::ScrollArea::vertical().max_height(240.0).stick_to_bottom(true).show(ui, |ui| {
+                for line in &console.output {
+                    ui.monospace(line);
+                }
+            });
+
+            ui.separator();
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut state.input)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY)
+                    .lock_focus(true),
+            );
+            response.request_focus();
+            // The toggle key is typed into the input when opening the console.
+            state.input.retain(|c| c != '`' && c != '~');
+
+            let (enter, tab, up, down) = ui.input(|input| {
+                (
+                    input.key_pressed(egui::Key::Enter),
+                    input.key_pressed(egui::Key::Tab),
+                    input.key_pressed(egui::Key::ArrowUp),
+                    input.key_pressed(egui::Key::ArrowDown),
+                )
+            });
+
+            if response.changed() || enter {
+                state.hint = None;
+            }
+
+            if enter {
+                submitted = Some(std::mem::take(&mut state.input));
+                state.history_index = None;
+            } else if tab {
+                let candidates = console.complete(&state.input);
+                state.hint = None;
+                match candidates.as_slice() {
+                    [] => {}
+                    [candidate] => {
+                        let keep = state.input.trim_end_matches(|c: char| !c.is_whitespace()).len();
+                        state.input.truncate(keep);
+                        state.input.push_str(candidate);
+                        state.input.push(' ');
+                    }
+                    candidates => state.hint = Some(candidates.join("  ")),
+                }
+            } else if up || down {
+                let len = console.history.len();
+                let index = match (state.history_index, up) {
+                    (None, true) if len > 0 => Some(0),
+                    (Some(index), true) => Some((index + 1).min(len.saturating_sub(1))),
+                    (Some(0), false) => None,
+                    (Some(index), false) => Some(index - 1),
+                    _ => None,
+                };
+                state.history_index = index;
+                state.input = index.map_or_else(String::new, |index| console.history[len - 1 - index].clone());
+            }
+
+            if let Some(hint) = &state.hint {
+                ui.weak(hint);
+            }
+        },
+    );
+
+    if let Some(line) = submitted {
+        execute(world, line.trim());
+    }
+}
+
+fn help(In(_): In<Vec<String>>, console: Res<Console>) -> ConsoleResult {
+    Ok(console.commands.values().map(|command| command.usage).collect::<Vec<_>>().join("\n"))
+}
+
+fn spawn(
+    In(args): In<Vec<String>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<GameRng>,
+    layout: Res<FieldLayout>,
+) -> ConsoleResult {
+    if args.first().map(String::as_str) != Some("unit") {
+        return Err("usage: spawn unit [count]".into());
+    }
+    let count: u32 = args.get(1).map_or(Ok(1), |count| count.parse()).map_err(|err| format!("invalid count: {err}"))?;
+
+    let agent = Agent::Medium;
+    let mesh = meshes.add(spawn_menu::agent_mesh(agent));
+    let material = materials.add(Color::RED);
+    let extent = layout.width().min(layout.height()) as f32 / 2.0 - agent.radius();
+
+    for _ in 0..count {
+        let position = random_point_in_square(&mut **rng, extent);
+        spawn_menu::spawn_agent(&mut commands, agent, position, mesh.clone(), material.clone());
+    }
+
+    Ok(format!("spawned {count} units"))
+}
+
+fn set(In(args): In<Vec<String>>, world: &mut World) -> ConsoleResult {
+    let [kind, name, value] = args.as_slice() else {
+        return Err("usage: set stat <name> <value>".into());
+    };
+    if kind != "stat" {
+        return Err(format!("can't set '{kind}'"));
+    }
+    let value: f32 = value.parse().map_err(|err| format!("invalid value: {err}"))?;
+    let Some(set_stat) = world.resource::<Console>().stats.get(name).copied() else {
+        return Err(format!("unknown stat '{name}'"));
+    };
+    let count = set_stat(world, value);
+    Ok(format!("set {name} to {value} for {count} entities"))
+}
+
+fn set_stat<S: Stat + Component>(world: &mut World, value: f32) -> usize {
+    let mut stats = world.query_filtered::<&mut Flat<S>, With<S>>();
+    stats.iter_mut(world).map(|mut base| *base.0.value_mut() = value).count()
+}
+
+fn toggle(In(args): In<Vec<String>>, mut layers: ResMut<DebugLayers>) -> ConsoleResult {
+    let (Some("gizmo"), Some(layer)) = (args.first().map(String::as_str), args.get(1)) else {
+        return Err("usage: toggle gizmo <layer> [small|medium|large|huge]".into());
+    };
+    let (agent, agent_layer) = match args.get(2).map(String::as_str) {
+        None | Some("medium") => (Agent::Medium, AgentDebugLayer::Medium),
+        Some("small") => (Agent::Small, AgentDebugLayer::Small),
+        Some("large") => (Agent::Large, AgentDebugLayer::Large),
+        Some("huge") => (Agent::Huge, AgentDebugLayer::Huge),
+        Some(size) => return Err(format!("unknown agent size '{size}'")),
+    };
+
+    let ReflectMut::Struct(layers) = layers.reflect_mut() else {
+        unreachable!("debug layers is a struct");
+    };
+    let Some(field) = layers.field_mut(&format!("debug_{layer}")) else {
+        return Err(format!("unknown gizmo layer '{layer}'"));
+    };
+
+    if let Some(enabled) = field.downcast_mut::<bool>() {
+        *enabled = !*enabled;
+        Ok(format!("{layer}: {enabled}"))
+    } else if let Some(current) = field.downcast_mut::<AgentDebugLayer>() {
+        *current = if matches!(current, AgentDebugLayer::Disabled) { agent_layer } else { AgentDebugLayer::Disabled };
+        Ok(format!("{layer}: {current:?}"))
+    } else if let Some(heatmap) = field.downcast_mut::<HeatmapLayer>() {
+        heatmap.agent = agent;
+        heatmap.mode =
+            if heatmap.mode == HeatmapMode::Disabled { HeatmapMode::Integration } else { HeatmapMode::Disabled };
+        Ok(format!("{layer}: {:?} {:?}", heatmap.mode, heatmap.agent))
+    } else {
+        Err(format!("can't toggle '{layer}'"))
+    }
+}
+
+fn goal(
+    In(args): In<Vec<String>>,
+    mut commands: Commands,
+    agents: Query<Entity, With<Agent>>,
+    layout: Res<FieldLayout>,
+) -> ConsoleResult {
+    let usage = || "usage: goal all (x,y)".to_owned();
+    if args.first().map(String::as_str) != Some("all") {
+        return Err(usage());
+    }
+    // Allow spaces in the position, e.g. `(10, 20)`.
+    let position = args[1..].concat();
+    let (x, y) = position.trim_matches(|c| c == '(' || c == ')').split_once(',').ok_or_else(usage)?;
+    let position = Vec2::new(
+        x.parse().map_err(|err| format!("invalid x: {err}"))?,
+        y.parse().map_err(|err| format!("invalid y: {err}"))?,
+    );
+
+    let cell = layout.cell(position);
+    if !layout.valid(cell) {
+        return Err(format!("{position} is outside of the field"));
+    }
+
+    let mut count = 0;
+    for entity in &agents {
+        commands.entity(entity).insert(Goal::Cell(cell));
+        count += 1;
+    }
+    Ok(format!("{count} agents moving to {position}"))
+}
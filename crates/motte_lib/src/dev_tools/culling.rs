@@ -0,0 +1,74 @@
+//! Culling for the [`DebugLayers`] gizmos, so enabling a per-cell layer on a large map stays interactive. Gizmos
+//! outside the main camera's frustum or too far from the point it looks at are skipped & every layer stops drawing
+//! once it used up its line budget.
+
+use bevy::{
+    ecs::system::SystemParam,
+    render::primitives::{Frustum, Sphere},
+};
+
+use super::DebugLayers;
+use crate::{player::camera::MainCamera, prelude::*};
+
+#[derive(Reflect, Debug)]
+pub struct GizmoCullingSettings {
+    pub enabled: bool,
+    /// Max distance on the ground from the point the camera looks at.
+    pub max_distance: f32,
+    /// Max lines drawn per layer each frame.
+    pub budget: usize,
+}
+
+impl Default for GizmoCullingSettings {
+    fn default() -> Self {
+        Self { enabled: true, max_distance: 80.0, budget: 20_000 }
+    }
+}
+
+#[derive(SystemParam)]
+pub struct GizmoCulling<'w, 's> {
+    layers: Res<'w, DebugLayers>,
+    cameras: Query<'w, 's, (&'static Frustum, &'static GlobalTransform), With<MainCamera>>,
+}
+
+impl GizmoCulling<'_, '_> {
+    /// Starts culling a layer, each layer has its own budget.
+    pub fn layer(&self) -> GizmoCuller {
+        let settings = &self.layers.gizmo_culling;
+        let view = self.cameras.get_single().ok().filter(|_| settings.enabled).map(|(frustum, transform)| {
+            let (origin, direction) = (transform.translation(), transform.forward());
+            (frustum.clone(), math::plane_intersection(origin, direction, Vec3::ZERO, Vec3::Y))
+        });
+        GizmoCuller {
+            view,
+            max_distance_squared: settings.max_distance * settings.max_distance,
+            remaining: if settings.enabled { settings.budget } else { usize::MAX },
+        }
+    }
+}
+
+pub struct GizmoCuller {
+    /// Frustum & the point on the ground the camera looks at, `None` draws everything.
+    view: Option<(Frustum, Vec3)>,
+    max_distance_squared: f32,
+    remaining: usize,
+}
+
+impl GizmoCuller {
+    /// Returns true if a gizmo made of `lines` lines around `position` should be drawn & takes it from the budget.
+    pub fn draw(&mut self, position: Vec3, lines: usize) -> bool {
+        if self.remaining < lines {
+            return false;
+        }
+        if let Some((frustum, focus)) = &self.view {
+            if position.xz().distance_squared(focus.xz()) > self.max_distance_squared {
+                return false;
+            }
+            if !frustum.intersects_sphere(&Sphere { center: position.into(), radius: 1.0 }, false) {
+                return false;
+            }
+        }
+        self.remaining -= lines;
+        true
+    }
+}
@@ -0,0 +1,237 @@
+//! Side panel section for editing the obstacles & spawn points of the current map. While editing they can be
+//! picked & moved with the transform gizmo, the [`ObstacleField`] follows as they move. Saving writes them back to
+//! the file of the [`SelectedMap`], random obstacles are saved as the obstacles they generated.
+//!
+//! [`ObstacleField`]: crate::navigation::flow_field::fields::obstacle::ObstacleField
+
+use std::fs;
+
+use bevy::{asset::io::file::FileAssetReader, ecs::system::CommandQueue, reflect::serde::TypedReflectSerializer};
+use bevy_egui::egui;
+use bevy_mod_picking::prelude::{PickSelection, PickableBundle};
+use bevy_transform_gizmo::GizmoTransformable;
+
+use crate::{
+    app_state::AppState,
+    cleanup::StateScoped,
+    in_game::map::{MapDef, SelectedMap, SpawnPointDef},
+    navigation::{
+        flow_field::fields::obstacle::DirtyObstacleField,
+        obstacle::{ObstacleDef, ObstacleShape},
+    },
+    objectives::Target,
+    physics::layers,
+    prefab::PrefabCommandsExt,
+    prelude::*,
+    scenario::SpawnPoint,
+};
+
+pub(super) struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(res: MapEditor, SpawnMarker);
+        app.add_systems(Update, (editable, markers).run_if(in_state(AppState::InGame)));
+        app.add_systems(OnExit(AppState::InGame), |mut editor: ResMut<MapEditor>| editor.editing = false);
+    }
+}
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub(super) struct MapEditor {
+    editing: bool,
+    /// Group of new spawn points.
+    group: String,
+    /// Result of the last save.
+    status: Option<String>,
+}
+
+impl Default for MapEditor {
+    fn default() -> Self {
+        Self { editing: false, group: "waves".into(), status: None }
+    }
+}
+
+/// Mesh making a [`SpawnPoint`] visible & pickable while editing.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct SpawnMarker;
+
+pub(super) fn ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut editor = world.resource_mut::<MapEditor>();
+    ui.checkbox(&mut editor.editing, "Edit map");
+    if !editor.editing {
+        ui.weak("obstacles & spawn points can be moved with the gizmo while editing");
+        return;
+    }
+    ui.separator();
+
+    let mut commands = Vec::<fn(&mut World)>::new();
+    ui.horizontal(|ui| {
+        if ui.button("Add cuboid").clicked() {
+            commands.push(|world| add_obstacle(world, ObstacleShape::Cuboid { half_size: 2.0 }));
+        }
+        if ui.button("Add capsule").clicked() {
+            commands.push(|world| add_obstacle(world, ObstacleShape::Capsule { radius: 2.0, height: 4.0 }));
+        }
+    });
+    let mut editor = world.resource_mut::<MapEditor>();
+    ui.horizontal(|ui| {
+        if ui.button("Add spawn point").clicked() {
+            commands.push(add_spawn_point);
+        }
+        ui.text_edit_singleline(&mut editor.group);
+    });
+    ui.horizontal(|ui| {
+        if ui.button("Delete selected").clicked() {
+            commands.push(delete_selected);
+        }
+        if ui.button("Save map").clicked() {
+            commands.push(|world| {
+                let status = match save(world) {
+                    Ok(path) => format!("saved to {}", path.display()),
+                    Err(err) => format!("failed to save: {err}"),
+                };
+                world.resource_mut::<MapEditor>().status = Some(status);
+            });
+        }
+    });
+    if let Some(status) = &editor.status {
+        ui.weak(status);
+    }
+
+    for command in commands {
+        command(world);
+    }
+}
+
+fn add_obstacle(world: &mut World, shape: ObstacleShape) {
+    let mesh = world.resource_mut::<Assets<Mesh>>().add(shape.mesh());
+    let material = world.resource_mut::<Assets<StandardMaterial>>().add(Color::BEIGE);
+    let mut queue = CommandQueue::default();
+    Commands::new(&mut queue, world).spawn_prefab("obstacle").insert((
+        Name::unit("obstacle"),
+        PbrBundle { mesh, material, ..default() },
+        shape.collider(),
+        shape,
+        layers::terrain(),
+        StateScoped(AppState::InGame),
+    ));
+    queue.apply(world);
+    world.send_event(DirtyObstacleField);
+}
+
+fn add_spawn_point(world: &mut World) {
+    let group = world.resource::<MapEditor>().group.clone();
+    world.spawn((
+        Name::new(format!("spawn point {group}")),
+        TransformBundle::default(),
+        SpawnPoint(group),
+        StateScoped(AppState::InGame),
+    ));
+}
+
+fn delete_selected(world: &mut World) {
+    let selected: Vec<_> = world
+        .query_filtered::<(Entity, &PickSelection), (With<GizmoTransformable>, Without<Target>)>()
+        .iter(world)
+        .filter(|(_, selection)| selection.is_selected)
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in selected {
+        world.entity_mut(entity).despawn_recursive();
+    }
+    world.send_event(DirtyObstacleField);
+}
+
+/// Makes obstacles & spawn points pickable by the transform gizmo while editing.
+fn editable(
+    mut commands: Commands,
+    editor: Res<MapEditor>,
+    uneditable: Query<Entity, (Or<(With<ObstacleShape>, With<Target>, With<SpawnPoint>)>, Without<GizmoTransformable>)>,
+    editable: Query<Entity, With<GizmoTransformable>>,
+) {
+    if editor.editing {
+        for entity in &uneditable {
+            commands.entity(entity).insert((PickableBundle::default(), GizmoTransformable));
+        }
+    } else if editor.is_changed() {
+        for entity in &editable {
+            commands.entity(entity).remove::<(PickableBundle, GizmoTransformable)>();
+        }
+    }
+}
+
+/// Shows spawn points as spheres while editing.
+fn markers(
+    mut commands: Commands,
+    editor: Res<MapEditor>,
+    spawn_points: Query<(Entity, Option<&Children>), With<SpawnPoint>>,
+    markers: Query<Entity, With<SpawnMarker>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut assets: Local<Option<(Handle<Mesh>, Handle<StandardMaterial>)>>,
+) {
+    if !editor.editing {
+        if editor.is_changed() {
+            for marker in &markers {
+                commands.entity(marker).despawn_recursive();
+            }
+        }
+        return;
+    }
+
+    let (mesh, material) =
+        assets.get_or_insert_with(|| (meshes.add(Sphere::new(0.75)), materials.add(Color::FUCHSIA))).clone();
+    for (entity, children) in &spawn_points {
+        if children.is_some_and(|children| markers.iter_many(children).next().is_some()) {
+            continue;
+        }
+        commands.entity(entity).insert(VisibilityBundle::default()).with_children(|parent| {
+            parent.spawn((
+                Name::new("spawn marker"),
+                PbrBundle { mesh: mesh.clone(), material: material.clone(), ..default() },
+                SpawnMarker,
+            ));
+        });
+    }
+}
+
+/// Writes the map with the current obstacles, spawn points & target to the file it was loaded from.
+fn save(world: &mut World) -> AnyResult<std::path::PathBuf> {
+    let selected = world.resource::<SelectedMap>().0.clone();
+    let path = selected.path().context("map has no path")?.path().to_owned();
+    let mut def = world.resource::<Assets<MapDef>>().get(&selected).context("map isn't loaded")?.clone();
+
+    let mut obstacles = world.query::<(&ObstacleShape, &GlobalTransform)>();
+    def.obstacles = obstacles
+        .iter(world)
+        .map(|(&shape, transform)| ObstacleDef { position: transform.translation().xz(), shape })
+        .collect();
+    def.random_obstacles = None;
+
+    // Spawn points authored in the map's scenes are saved with the scene.
+    let mut spawn_points = world.query_filtered::<(&SpawnPoint, &GlobalTransform), Without<Parent>>();
+    def.spawn_points = spawn_points
+        .iter(world)
+        .map(|(SpawnPoint(group), transform)| SpawnPointDef {
+            group: group.clone(),
+            position: transform.translation().xz(),
+        })
+        .collect();
+
+    let mut targets = world.query_filtered::<&GlobalTransform, With<Target>>();
+    if let Some(target) = targets.iter(world).next() {
+        def.target = target.translation().xz();
+    }
+
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let serialized =
+        ron::ser::to_string_pretty(&TypedReflectSerializer::new(&def, &registry), ron::ser::PrettyConfig::default())?;
+    // Resolved like the asset server does, relative to the executable or the manifest instead of the working
+    // directory.
+    let file = FileAssetReader::new(AssetPlugin::default().file_path).root_path().join(&path);
+    fs::write(&file, serialized)?;
+    info!("Saved map to {file:?}");
+    Ok(file)
+}
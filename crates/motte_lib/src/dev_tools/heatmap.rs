@@ -0,0 +1,208 @@
+//! Flow field overlay drawn as a translucent texture on the ground, unlike the gizmo layers it stays cheap for
+//! full-size fields. The texture is only rewritten when the displayed [`FlowField`] is rebuilt.
+
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+
+use super::DebugLayers;
+use crate::{
+    app_state::AppState,
+    cleanup::StateScoped,
+    graphics::pixelate::SortBias,
+    navigation::{
+        agent::{for_each_agent, Agent},
+        flow_field::{
+            fields::flow::FlowField,
+            layout::{FieldLayout, CELL_SIZE_F32},
+        },
+    },
+    prelude::*,
+};
+
+pub(super) struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(HeatmapLayer, HeatmapMode, ColorMap);
+
+        app.add_systems(
+            Update,
+            (
+                overlay,
+                for_each_agent!(|AGENT| write::<AGENT>.run_if(|d: Res<DebugLayers>| d.debug_heatmap.agent == AGENT)),
+            )
+                .chain()
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+#[derive(Reflect, Debug)]
+pub struct HeatmapLayer {
+    pub mode: HeatmapMode,
+    pub agent: Agent,
+    pub color_map: ColorMap,
+    pub opacity: f32,
+}
+
+impl Default for HeatmapLayer {
+    fn default() -> Self {
+        Self { mode: HeatmapMode::Disabled, agent: Agent::Medium, color_map: ColorMap::Viridis, opacity: 0.6 }
+    }
+}
+
+#[derive(Default, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeatmapMode {
+    #[default]
+    Disabled,
+    /// Integration cost, blocked & occupied cells are drawn dark.
+    Integration,
+    /// Flow direction as hue, repulsion is drawn darker.
+    Flow,
+}
+
+#[derive(Default, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMap {
+    #[default]
+    Viridis,
+    Inferno,
+    Grayscale,
+}
+
+impl ColorMap {
+    fn sample(self, t: f32) -> [u8; 4] {
+        const VIRIDIS: [[u8; 3]; 5] = [[68, 1, 84], [59, 82, 139], [33, 145, 140], [94, 201, 98], [253, 231, 37]];
+        const INFERNO: [[u8; 3]; 5] = [[0, 0, 4], [87, 16, 110], [188, 55, 84], [249, 142, 9], [252, 255, 164]];
+        const GRAYSCALE: [[u8; 3]; 2] = [[0, 0, 0], [255, 255, 255]];
+
+        let stops: &[[u8; 3]] = match self {
+            Self::Viridis => &VIRIDIS,
+            Self::Inferno => &INFERNO,
+            Self::Grayscale => &GRAYSCALE,
+        };
+
+        let t = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let (i, fract) = (t as usize, t.fract());
+        let (a, b) = (stops[i], stops[(i + 1).min(stops.len() - 1)]);
+        let lerp = |c: usize| (a[c] as f32 + (b[c] as f32 - a[c] as f32) * fract) as u8;
+        [lerp(0), lerp(1), lerp(2), u8::MAX]
+    }
+}
+
+const BLOCKED_COLOR: [u8; 4] = [16, 16, 16, 200];
+
+/// Ground quad displaying the heatmap texture.
+#[derive(Component)]
+struct HeatmapOverlay {
+    image: Handle<Image>,
+}
+
+fn overlay(
+    mut commands: Commands,
+    layers: Res<DebugLayers>,
+    layout: Res<FieldLayout>,
+    overlays: Query<Entity, With<HeatmapOverlay>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let layer = &layers.debug_heatmap;
+    let enabled = layer.mode != HeatmapMode::Disabled;
+    if !layers.is_changed() && !layout.is_changed() && enabled != overlays.is_empty() {
+        return;
+    }
+
+    for entity in &overlays {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    if !enabled || layout.len() == 0 {
+        return;
+    }
+
+    let mut image = Image::new_fill(
+        Extent3d { width: layout.width() as u32, height: layout.height() as u32, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    let image = images.add(image);
+
+    // Plane uvs map `u` to +x & `v` to +z, same as cell x & y, so cell indices are pixel indices.
+    let size = Vec2::new(layout.width() as f32, layout.height() as f32) * CELL_SIZE_F32;
+    commands.spawn((
+        Name::new("HeatmapOverlay"),
+        PbrBundle {
+            mesh: meshes.add(Plane3d::default().mesh().size(size.x, size.y)),
+            material: materials.add(StandardMaterial {
+                base_color: Color::WHITE.with_a(layer.opacity),
+                base_color_texture: Some(image.clone()),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
+            transform: (layout.center().x0y() + Vec3::Y * 0.05).into_transform(),
+            ..default()
+        },
+        NotShadowCaster,
+        NotShadowReceiver,
+        // Drawn below other ground-projected transparents.
+        SortBias(-1.0),
+        HeatmapOverlay { image },
+        StateScoped(AppState::InGame),
+    ));
+}
+
+fn write<const AGENT: Agent>(
+    layers: Res<DebugLayers>,
+    overlays: Query<Ref<HeatmapOverlay>>,
+    flow_fields: Query<Ref<FlowField<AGENT>>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Ok(overlay) = overlays.get_single() else {
+        return;
+    };
+    // Display the most recently rebuilt field, or any field when the overlay was just (re)created.
+    let Some(flow_field) = flow_fields.iter().filter(|field| overlay.is_added() || field.is_changed()).last() else {
+        return;
+    };
+    let Some(image) = images.get_mut(&overlay.image) else {
+        return;
+    };
+    if image.data.len() != flow_field.len() * 4 {
+        // Layout changed & the field hasn't been rebuilt yet.
+        return;
+    }
+
+    let layer = &layers.debug_heatmap;
+    let pixels = image.data.chunks_exact_mut(4);
+    match layer.mode {
+        HeatmapMode::Disabled => {}
+        HeatmapMode::Integration => {
+            for (pixel, cost) in pixels.zip(flow_field.normalized_costs()) {
+                let color = cost.map_or(BLOCKED_COLOR, |cost| layer.color_map.sample(cost));
+                pixel.copy_from_slice(&color);
+            }
+        }
+        HeatmapMode::Flow => {
+            for (pixel, flow) in pixels.zip(flow_field.iter()) {
+                let direction = flow.direction();
+                let color = if direction.as_direction2d().is_some() {
+                    let lightness = if flow.is_repulse() { 0.3 } else { 0.6 };
+                    Color::hsl(direction as u8 as f32 * 45.0, 0.8, lightness).as_rgba_u8()
+                } else {
+                    [0, 0, 0, 0]
+                };
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Side panel section for editing the active [`FieldLayout`]. Applying replaces the layout & rebuilds everything
+//! derived from it: the obstacle & surface fields, every flow field, footprints & the [`CellIndex`] of all entities.
+
+use bevy_egui::egui;
+
+use crate::{
+    navigation::{
+        agent::{for_each_agent, Agent},
+        flow_field::{
+            fields::{
+                flow::FlowField,
+                obstacle::{DirtyObstacleField, ObstacleField},
+                surface::{SurfaceField, SurfaceRegion},
+            },
+            layout::{FieldLayout, CELL_SIZE},
+            CellIndex,
+        },
+        obstacle::Obstacle,
+    },
+    prelude::*,
+};
+
+/// Layout being edited, `None` until the panel is first shown or after applying.
+#[derive(Resource, Default)]
+pub(super) struct LayoutEditor(Option<LayoutEdit>);
+
+struct LayoutEdit {
+    width: u8,
+    height: u8,
+    centered: bool,
+    offset: Vec2,
+}
+
+impl From<&FieldLayout> for LayoutEdit {
+    fn from(layout: &FieldLayout) -> Self {
+        Self { width: layout.width(), height: layout.height(), centered: layout.is_centered(), offset: layout.offset() }
+    }
+}
+
+impl LayoutEdit {
+    fn layout(&self) -> FieldLayout {
+        let layout = FieldLayout::new(self.width, self.height);
+        if self.centered {
+            layout
+        } else {
+            layout.with_offset(self.offset)
+        }
+    }
+}
+
+pub(super) fn ui(world: &mut World, ui: &mut egui::Ui) {
+    let current = *world.resource::<FieldLayout>();
+    let mut editor = world.get_resource_or_insert_with(LayoutEditor::default);
+    let edit = editor.0.get_or_insert_with(|| LayoutEdit::from(&current));
+
+    ui.weak(format!("active: {}x{} cells, {} total", current.width(), current.height(), current.len()));
+    ui.separator();
+
+    egui::Grid::new("layout_panel").num_columns(2).show(ui, |ui| {
+        ui.label("Width");
+        ui.add(egui::DragValue::new(&mut edit.width).clamp_range(8..=u8::MAX));
+        ui.end_row();
+
+        ui.label("Height");
+        ui.add(egui::DragValue::new(&mut edit.height).clamp_range(8..=u8::MAX));
+        ui.end_row();
+
+        ui.label("Cell size");
+        ui.weak(format!("{CELL_SIZE} (fixed)"));
+        ui.end_row();
+
+        ui.label("Centered");
+        ui.checkbox(&mut edit.centered, "");
+        ui.end_row();
+
+        ui.label("Origin");
+        ui.add_enabled_ui(!edit.centered, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut edit.offset.x).speed(0.5).prefix("x "));
+                ui.add(egui::DragValue::new(&mut edit.offset.y).speed(0.5).prefix("z "));
+            });
+        });
+        ui.end_row();
+    });
+
+    let layout = edit.layout();
+    let (apply, reset) = ui.horizontal(|ui| (ui.button("Apply").clicked(), ui.button("Reset").clicked())).inner;
+    if apply || reset {
+        editor.0 = None;
+    }
+    if apply {
+        rebuild(world, layout);
+    }
+}
+
+fn rebuild(world: &mut World, layout: FieldLayout) {
+    world.insert_resource(ObstacleField::from_layout(&layout));
+    world.insert_resource(SurfaceField::from_layout(&layout));
+    world.insert_resource(layout);
+
+    for_each_agent!(|AGENT| rebuild_flow_fields::<AGENT>(world, &layout));
+
+    // Refreshes cell indices & agent footprints.
+    let mut cell_indices = world.query::<(&mut GlobalTransform, &mut CellIndex)>();
+    for (mut transform, mut cell_index) in cell_indices.iter_mut(world) {
+        transform.set_changed();
+        cell_index.set_changed();
+    }
+    // Refreshes obstacle footprints.
+    let mut obstacles = world.query::<&mut Obstacle>();
+    for mut obstacle in obstacles.iter_mut(world) {
+        obstacle.set_changed();
+    }
+    // Refreshes surfaces.
+    let mut regions = world.query::<&mut SurfaceRegion>();
+    for mut region in regions.iter_mut(world) {
+        region.set_changed();
+    }
+
+    world.send_event(DirtyObstacleField);
+}
+
+fn rebuild_flow_fields<const AGENT: Agent>(world: &mut World, layout: &FieldLayout) {
+    let flow_fields = world.query_filtered::<Entity, With<FlowField<AGENT>>>().iter(world).collect_vec();
+    for entity in flow_fields {
+        world
+            .entity_mut(entity)
+            .insert((FlowField::<AGENT>::from_layout(layout), Dirty::<FlowField<AGENT>>::default()));
+    }
+}
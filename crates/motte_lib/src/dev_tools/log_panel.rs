@@ -0,0 +1,194 @@
+//! Log events captured into a ring buffer & shown in the side panel, filtered by level, target & message so runtime
+//! diagnostics can be read without a terminal. [`capture_logs`] has to be passed to [`LogPlugin::update_subscriber`],
+//! events below the level of the [`LogPlugin`] aren't captured.
+//!
+//! [`LogPlugin`]: bevy::log::LogPlugin
+//! [`LogPlugin::update_subscriber`]: bevy::log::LogPlugin::update_subscriber
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    sync::{Mutex, MutexGuard},
+};
+
+use bevy::{
+    log::{
+        tracing_subscriber::{layer::Context as LayerContext, prelude::*, Layer},
+        BoxedSubscriber, Level,
+    },
+    utils::tracing::{
+        field::{Field, Visit},
+        Event, Subscriber,
+    },
+};
+use bevy_egui::egui;
+
+use crate::prelude::*;
+
+/// Entries kept in the panel, older ones are dropped.
+const CAPACITY: usize = 2000;
+const LEVELS: [Level; 5] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG, Level::TRACE];
+
+/// Events captured since the panel last took them, written from any thread.
+static PENDING: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+pub(super) struct LogPanelPlugin;
+
+impl Plugin for LogPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LogPanel>();
+        app.add_systems(First, collect);
+    }
+}
+
+/// Captures log events for the log panel of the dev tools.
+pub fn capture_logs(subscriber: BoxedSubscriber) -> BoxedSubscriber {
+    Box::new(subscriber.with(LogCapture))
+}
+
+#[derive(Clone, Debug)]
+struct LogEntry {
+    level: Level,
+    target: String,
+    message: String,
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:<5} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+#[derive(Resource)]
+struct LogPanel {
+    entries: VecDeque<LogEntry>,
+    /// Most verbose level shown.
+    level: Level,
+    /// Only entries with a target containing this are shown, e.g. `flow_field`.
+    target: String,
+    search: String,
+}
+
+impl Default for LogPanel {
+    fn default() -> Self {
+        Self { entries: VecDeque::new(), level: Level::TRACE, target: String::new(), search: String::new() }
+    }
+}
+
+impl LogPanel {
+    fn visible(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter().filter(|entry| {
+            entry.level <= self.level
+                && entry.target.contains(self.target.as_str())
+                && entry.message.contains(self.search.as_str())
+        })
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn push(entries: &mut VecDeque<LogEntry>, entry: LogEntry) {
+    if entries.len() == CAPACITY {
+        entries.pop_front();
+    }
+    entries.push_back(entry);
+}
+
+/// Moves the pending entries into the panel, nothing may be logged while the lock is held.
+fn collect(mut panel: ResMut<LogPanel>) {
+    let pending = std::mem::take(&mut *lock(&PENDING));
+    if pending.is_empty() {
+        return;
+    }
+    for entry in pending {
+        push(&mut panel.entries, entry);
+    }
+}
+
+struct LogCapture;
+
+impl<S: Subscriber> Layer<S> for LogCapture {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let metadata = event.metadata();
+        let mut message = String::new();
+        event.record(&mut MessageVisitor(&mut message));
+        let entry = LogEntry { level: *metadata.level(), target: metadata.target().to_owned(), message };
+        push(&mut lock(&PENDING), entry);
+    }
+}
+
+struct MessageVisitor<'a>(&'a mut String);
+
+impl Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let separator = if self.0.is_empty() { "" } else { " " };
+        let _ = match field.name() {
+            "message" => write!(self.0, "{separator}{value:?}"),
+            name => write!(self.0, "{separator}{name}={value:?}"),
+        };
+    }
+}
+
+fn level_color(level: Level) -> egui::Color32 {
+    match level {
+        Level::ERROR => egui::Color32::LIGHT_RED,
+        Level::WARN => egui::Color32::YELLOW,
+        Level::INFO => egui::Color32::LIGHT_GRAY,
+        _ => egui::Color32::GRAY,
+    }
+}
+
+pub(super) fn ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut panel = world.resource_mut::<LogPanel>();
+    let panel = &mut *panel;
+
+    egui::Grid::new("log_panel").num_columns(2).show(ui, |ui| {
+        ui.label("Level");
+        egui::ComboBox::from_id_source("log_level").selected_text(panel.level.as_str()).show_ui(ui, |ui| {
+            for level in LEVELS {
+                ui.selectable_value(&mut panel.level, level, level.as_str());
+            }
+        });
+        ui.end_row();
+
+        ui.label("Target");
+        ui.text_edit_singleline(&mut panel.target);
+        ui.end_row();
+
+        ui.label("Search");
+        ui.text_edit_singleline(&mut panel.search);
+        ui.end_row();
+    });
+
+    let visible = panel.visible().count();
+    ui.horizontal(|ui| {
+        if ui.button("Copy").clicked() {
+            let text = panel.visible().join("\n");
+            ui.output_mut(|output| output.copied_text = text);
+        }
+        if ui.button("Clear").clicked() {
+            panel.entries.clear();
+        }
+        ui.weak(format!("{visible} of {} entries", panel.entries.len()));
+    });
+    ui.separator();
+
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    egui::ScrollArea::vertical().id_source("log").stick_to_bottom(true).show_rows(
+        ui,
+        row_height,
+        visible,
+        |ui, rows| {
+            for entry in panel.visible().skip(rows.start).take(rows.len()) {
+                ui.add(
+                    egui::Label::new(
+                        egui::RichText::new(entry.to_string()).monospace().color(level_color(entry.level)),
+                    )
+                    .wrap(false),
+                );
+            }
+        },
+    );
+}
@@ -1,22 +1,42 @@
 use bevy_egui::EguiPlugin;
 use bevy_inspector_egui::DefaultInspectorConfigPlugin;
 
-use crate::{app_state::AppState, asset_management::FontAssets, navigation::agent::Agent, prelude::*};
-
+use crate::{
+    app_state::AppState, asset_management::FontAssets, navigation::agent::Agent, player::camera::UiCamera, prelude::*,
+};
+
+mod console;
+mod culling;
+mod editor;
+mod heatmap;
+mod layout_panel;
+mod log_panel;
+mod perf_graphs;
 mod perf_ui;
 mod side_panel;
+mod snapshot;
+mod spawn_menu;
+mod spikes;
+mod stats_panel;
+mod trace;
+mod world_inspector;
+
+pub(crate) use culling::GizmoCulling;
+pub use log_panel::capture_logs;
 
 mod key_codes {
     use bevy::input::keyboard::KeyCode;
     pub const TOGGLE_SIDE_PANEL: KeyCode = KeyCode::F1;
     pub const TOGGLE_PERF_PANEL: KeyCode = KeyCode::F2;
+    pub const TOGGLE_CONSOLE: KeyCode = KeyCode::Backquote;
+    pub const TOGGLE_WORLD_INSPECTOR: KeyCode = KeyCode::F3;
 }
 
 pub struct DevToolsPlugin;
 
 impl Plugin for DevToolsPlugin {
     fn build(&self, app: &mut App) {
-        app_register_types!(AgentDebugLayer);
+        app_register_types!(res: DebugLayers, AgentDebugLayer, culling::GizmoCullingSettings);
 
         app.add_plugins((
             bevy::diagnostic::FrameTimeDiagnosticsPlugin,
@@ -33,75 +53,55 @@ impl Plugin for DevToolsPlugin {
 
         app.add_plugins((PhysicsDebugPlugin::default(), bevy_transform_gizmo::TransformGizmoPlugin::default()));
 
-        app.add_plugins((perf_ui::PerfUiPlugin, side_panel::SidePanelPlugin));
+        app.add_plugins((
+            perf_ui::PerfUiPlugin,
+            side_panel::SidePanelPlugin,
+            console::ConsolePlugin,
+            editor::EditorPlugin,
+            heatmap::HeatmapPlugin,
+            log_panel::LogPanelPlugin,
+            spawn_menu::SpawnMenuPlugin,
+            trace::TracePlugin,
+            world_inspector::WorldInspectorPlugin,
+            spikes::SpikeWatchdogPlugin,
+            stats_panel::StatsPanelPlugin,
+        ));
 
         app.insert_gizmo_group(PhysicsGizmos { aabb_color: Some(Color::WHITE), ..default() }, GizmoConfig::default());
-        app.init_resource::<DebugLayers>();
 
         app.add_systems(OnExit(AppState::Loading), semver_ui);
-        app.add_systems(
-            Update,
-            (
-                toggle_debug_physics,
-                crate::navigation::flow_field::footprint::gizmos.run_if(|d: Res<DebugLayers>| d.debug_footprints),
-                crate::navigation::flow_field::layout::gizmos.run_if(|d: Res<DebugLayers>| d.debug_field_layout),
-                crate::navigation::flow_field::gizmos_cell_index.run_if(|d: Res<DebugLayers>| d.debug_cell_index),
-                crate::navigation::agent::gizmos.run_if(|d: Res<DebugLayers>| d.debug_agents),
-                crate::navigation::obstacle::gizmos.run_if(|d: Res<DebugLayers>| d.debug_obstacles),
-                crate::navigation::avoidance::gizmos.run_if(|d: Res<DebugLayers>| d.debug_avoidance),
-                // TODO: annoying setup, maybe use a macro to generate this :P ?
-                crate::navigation::flow_field::fields::obstacle::gizmos::<{ Agent::Huge }>
-                    .run_if(|d: Res<DebugLayers>| d.debug_obstacle_field.enabled_for(Agent::Huge)),
-                crate::navigation::flow_field::fields::obstacle::gizmos::<{ Agent::Large }>
-                    .run_if(|d: Res<DebugLayers>| d.debug_obstacle_field.enabled_for(Agent::Large)),
-                crate::navigation::flow_field::fields::obstacle::gizmos::<{ Agent::Medium }>
-                    .run_if(|d: Res<DebugLayers>| d.debug_obstacle_field.enabled_for(Agent::Medium)),
-                crate::navigation::flow_field::fields::obstacle::gizmos::<{ Agent::Small }>
-                    .run_if(|d: Res<DebugLayers>| d.debug_obstacle_field.enabled_for(Agent::Small)),
-                crate::navigation::flow_field::fields::flow::gizmos::<{ Agent::Huge }>
-                    .run_if(|d: Res<DebugLayers>| d.debug_flow_field.enabled_for(Agent::Huge)),
-                crate::navigation::flow_field::fields::flow::gizmos::<{ Agent::Large }>
-                    .run_if(|d: Res<DebugLayers>| d.debug_flow_field.enabled_for(Agent::Large)),
-                crate::navigation::flow_field::fields::flow::gizmos::<{ Agent::Medium }>
-                    .run_if(|d: Res<DebugLayers>| d.debug_flow_field.enabled_for(Agent::Medium)),
-                crate::navigation::flow_field::fields::flow::gizmos::<{ Agent::Small }>
-                    .run_if(|d: Res<DebugLayers>| d.debug_flow_field.enabled_for(Agent::Small)),
-            )
-                .run_if(in_state(AppState::InGame)),
-        );
+        app.add_systems(Update, (toggle_debug_physics, DebugLayers::gizmos()).run_if(in_state(AppState::InGame)));
     }
 }
 
-#[derive(Resource, Reflect)]
+/// Debug views toggled from the side panel & the console, a new gizmo view only needs a `debug_` field with
+/// `#[debug_layer(gizmos = path)]`.
+#[derive(Resource, Reflect, DebugLayer, Default)]
 pub struct DebugLayers {
+    #[debug_layer(gizmos = crate::navigation::flow_field::gizmos_cell_index)]
     debug_cell_index: bool,
+    #[debug_layer(gizmos = crate::navigation::agent::gizmos)]
     debug_agents: bool,
+    #[debug_layer(gizmos = crate::navigation::obstacle::gizmos)]
     debug_obstacles: bool,
+    #[debug_layer(gizmos = crate::navigation::avoidance::gizmos)]
     debug_avoidance: bool,
+    #[debug_layer(gizmos = crate::navigation::crowd::gizmos)]
+    debug_dormant: bool,
+    #[debug_layer(gizmos = crate::navigation::flow_field::footprint::gizmos)]
     debug_footprints: bool,
+    #[debug_layer(gizmos = crate::navigation::flow_field::fields::obstacle::gizmos, per_agent)]
     debug_obstacle_field: AgentDebugLayer,
+    #[debug_layer(gizmos = crate::navigation::flow_field::fields::flow::gizmos, per_agent)]
     debug_flow_field: AgentDebugLayer,
+    debug_heatmap: heatmap::HeatmapLayer,
+    #[debug_layer(gizmos = crate::navigation::flow_field::layout::gizmos)]
     debug_field_layout: bool,
     debug_physics: bool,
+    gizmo_culling: culling::GizmoCullingSettings,
 }
 
-impl Default for DebugLayers {
-    fn default() -> Self {
-        Self {
-            debug_cell_index: false,
-            debug_agents: false,
-            debug_avoidance: false,
-            debug_obstacles: false,
-            debug_footprints: false,
-            debug_obstacle_field: AgentDebugLayer::Disabled,
-            debug_flow_field: AgentDebugLayer::Disabled,
-            debug_field_layout: false,
-            debug_physics: false,
-        }
-    }
-}
-
-#[derive(Default, Reflect)]
+#[derive(Default, Reflect, Debug)]
 pub enum AgentDebugLayer {
     #[default]
     Disabled,
@@ -134,8 +134,8 @@ fn toggle_debug_physics(debug_layers: Res<DebugLayers>, mut store: ResMut<GizmoC
 #[derive(Component)]
 struct SemverUi;
 
-fn semver_ui(mut commands: Commands, assets: Res<FontAssets>) {
-    commands
+fn semver_ui(mut commands: Commands, assets: Res<FontAssets>, ui_camera: Query<Entity, With<UiCamera>>) {
+    let semver = commands
         .spawn((
             Name::ui("semver"),
             NodeBundle {
@@ -156,5 +156,10 @@ fn semver_ui(mut commands: Commands, assets: Res<FontAssets>) {
                 crate::version(),
                 TextStyle { font: assets.commit_mono_400.clone(), font_size: 16.0, color: Color::WHITE },
             )]),));
-        });
+        })
+        .id();
+
+    if let Ok(ui_camera) = ui_camera.get_single() {
+        commands.entity(semver).insert(TargetCamera(ui_camera));
+    }
 }
@@ -0,0 +1,168 @@
+//! Scrolling graphs of a few diagnostics over the last [`HISTORY`] seconds, shown with the perf ui so spikes can be
+//! correlated with what happened in the game, spawned waves are marked on every graph. Pausing freezes the history,
+//! hovering a graph then inspects the values & [`GameEvent`]s of that frame.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticPath, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+    },
+    window::PrimaryWindow,
+};
+use bevy_egui::{egui, EguiContext};
+use iyes_perf_ui::PerfUiRoot;
+
+use crate::{app_state::AppState, events::GameEvent, prelude::*, timings};
+
+/// Length of the history in seconds.
+const HISTORY: f32 = 10.0;
+const GRAPH_SIZE: egui::Vec2 = egui::vec2(280.0, 32.0);
+/// Events listed when inspecting a frame, the rest are only counted.
+const MAX_INSPECTED_EVENTS: usize = 8;
+
+const GRAPH_COUNT: usize = 4;
+static GRAPHS: [(&str, &DiagnosticPath); GRAPH_COUNT] = [
+    ("Frame time (ms)", &FrameTimeDiagnosticsPlugin::FRAME_TIME),
+    ("Entities", &EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+    ("Dirty fields", &timings::DIRTY_FIELDS),
+    ("Avoidance neighbors", &timings::AVOIDANCE_NEIGHBORS),
+];
+
+pub(super) struct PerfGraphsPlugin;
+
+impl Plugin for PerfGraphsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PerfHistory>();
+        app.add_systems(Update, (sample, graphs_ui.run_if(in_state(AppState::InGame))).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+struct PerfHistory {
+    samples: VecDeque<Sample>,
+    paused: bool,
+    /// Time of the sample hovered last frame while paused.
+    inspected: Option<f32>,
+}
+
+impl PerfHistory {
+    fn end(&self) -> f32 {
+        self.samples.back().map_or(0.0, |sample| sample.elapsed)
+    }
+
+    fn nearest(&self, elapsed: f32) -> Option<&Sample> {
+        self.samples.iter().min_by(|a, b| (a.elapsed - elapsed).abs().total_cmp(&(b.elapsed - elapsed).abs()))
+    }
+}
+
+struct Sample {
+    /// Real time of the frame in seconds.
+    elapsed: f32,
+    values: [Option<f64>; GRAPH_COUNT],
+    events: Vec<GameEvent>,
+}
+
+impl Sample {
+    fn wave(&self) -> bool {
+        self.events.iter().any(|event| matches!(event, GameEvent::WaveSpawned { .. }))
+    }
+}
+
+fn sample(
+    mut history: ResMut<PerfHistory>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut events: EventReader<GameEvent>,
+    time: Res<Time<Real>>,
+) {
+    if history.paused {
+        events.clear();
+        return;
+    }
+
+    let elapsed = time.elapsed_seconds();
+    history.samples.push_back(Sample {
+        elapsed,
+        values: GRAPHS.map(|(_, path)| diagnostics.get(path).and_then(Diagnostic::value)),
+        events: events.read().copied().collect(),
+    });
+    while history.samples.front().is_some_and(|sample| elapsed - sample.elapsed > HISTORY) {
+        history.samples.pop_front();
+    }
+}
+
+fn graphs_ui(
+    mut egui_context: Query<&mut EguiContext, With<PrimaryWindow>>,
+    mut history: ResMut<PerfHistory>,
+    perf_ui: Query<&Visibility, With<PerfUiRoot>>,
+) {
+    let (Ok(mut egui_context), Ok(visibility)) = (egui_context.get_single_mut(), perf_ui.get_single()) else {
+        return;
+    };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    let history = &mut *history;
+    egui::Window::new("Perf graphs").resizable(false).anchor(egui::Align2::LEFT_BOTTOM, [16.0, -16.0]).show(
+        egui_context.get_mut(),
+        |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut history.paused, "Pause");
+                if history.paused {
+                    ui.weak("hover a graph to inspect a frame");
+                }
+            });
+
+            let mut hovered = None;
+            for index in 0..GRAPH_COUNT {
+                hovered = graph(ui, history, index).or(hovered);
+            }
+            history.inspected = hovered.filter(|_| history.paused);
+
+            if let Some(sample) = history.inspected.and_then(|elapsed| history.nearest(elapsed)) {
+                ui.separator();
+                ui.monospace(format!("{:.2}s ago", history.end() - sample.elapsed));
+                for ((label, _), value) in GRAPHS.iter().zip(sample.values) {
+                    ui.monospace(format!("{label}: {}", value.map_or("-".to_owned(), |value| format!("{value:.2}"))));
+                }
+                for event in sample.events.iter().take(MAX_INSPECTED_EVENTS) {
+                    ui.monospace(format!("{event:?}"));
+                }
+                if sample.events.len() > MAX_INSPECTED_EVENTS {
+                    ui.weak(format!("{} more events", sample.events.len() - MAX_INSPECTED_EVENTS));
+                }
+            }
+        },
+    );
+}
+
+/// Draws the history of `GRAPHS[index]` scaled to its max, returns the time under the pointer if it's hovered.
+fn graph(ui: &mut egui::Ui, history: &PerfHistory, index: usize) -> Option<f32> {
+    let (label, _) = GRAPHS[index];
+    let values = history.samples.iter().filter_map(|sample| Some((sample.elapsed, sample.values[index]?)));
+    let max = values.clone().map(|(_, value)| value).fold(0.0, f64::max);
+    let current = history.samples.back().and_then(|sample| sample.values[index]);
+    ui.monospace(format!(
+        "{label}: {} (max {max:.1})",
+        current.map_or("-".to_owned(), |current| format!("{current:.1}"))
+    ));
+
+    let (response, painter) = ui.allocate_painter(GRAPH_SIZE, egui::Sense::hover());
+    let rect = response.rect;
+    let end = history.end();
+    let x = |elapsed: f32| rect.right() - (end - elapsed) / HISTORY * rect.width();
+    let y = |value: f64| rect.bottom() - (value / max.max(f64::EPSILON)) as f32 * rect.height();
+
+    painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(160));
+    for sample in history.samples.iter().filter(|sample| sample.wave()) {
+        painter.vline(x(sample.elapsed), rect.y_range(), egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE));
+    }
+    let points = values.map(|(elapsed, value)| egui::pos2(x(elapsed), y(value))).collect_vec();
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, egui::Color32::YELLOW)));
+    if let Some(inspected) = history.inspected {
+        painter.vline(x(inspected), rect.y_range(), egui::Stroke::new(1.0, egui::Color32::WHITE));
+    }
+
+    response.hover_pos().map(|pos| end - (rect.right() - pos.x) / rect.width() * HISTORY)
+}
@@ -1,4 +1,5 @@
 use bevy::{
+    diagnostic::{DiagnosticPath, DiagnosticsStore},
     ecs::system::{
         lifetimeless::{SQuery, SRes},
         SystemParam,
@@ -6,18 +7,19 @@ use bevy::{
     input::common_conditions::input_just_pressed,
     render::renderer::RenderAdapterInfo,
 };
-use iyes_perf_ui::prelude::*;
+use iyes_perf_ui::{prelude::*, utils::ColorGradient};
 
 use super::key_codes;
-use crate::{app_state::AppState, asset_management::FontAssets, graphics::pixelate, prelude::*};
+use crate::{app_state::AppState, asset_management::FontAssets, graphics::pixelate, prelude::*, timings};
 
 pub struct PerfUiPlugin;
 
 impl Plugin for PerfUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(iyes_perf_ui::PerfUiPlugin);
+        app.add_plugins((iyes_perf_ui::PerfUiPlugin, super::perf_graphs::PerfGraphsPlugin));
         app.add_perf_ui_entry_type::<PerfUiEntryRenderAdapter>();
         app.add_perf_ui_entry_type::<PerfUiEntryRenderResolution>();
+        app.add_perf_ui_entry_type::<PerfUiEntrySystemTiming>();
         app.add_systems(OnExit(AppState::Loading), perf_ui);
         app.add_systems(
             Update,
@@ -99,10 +101,82 @@ impl PerfUiEntry for PerfUiEntryRenderResolution {
     }
 }
 
+/// Wall time of a system set in milliseconds, shown as current, average & worst over the diagnostic history.
+#[derive(Component)]
+pub struct PerfUiEntrySystemTiming {
+    pub label: &'static str,
+    pub path: &'static DiagnosticPath,
+    pub color_gradient: ColorGradient,
+    pub sort_key: i32,
+}
+
+impl PerfUiEntrySystemTiming {
+    /// Width of the timing bar, full at [`PerfUiEntrySystemTiming::BUDGET_MS`].
+    const BAR_WIDTH: usize = 10;
+    const BUDGET_MS: f64 = 2.0;
+
+    fn new(label: &'static str, path: &'static DiagnosticPath, sort_key: i32) -> Self {
+        Self { label, path, color_gradient: ColorGradient::new_preset_gyr(0.25, 1.0, 2.0).unwrap(), sort_key }
+    }
+
+    fn bar(value: f64) -> usize {
+        ((value / Self::BUDGET_MS * Self::BAR_WIDTH as f64).ceil() as usize).min(Self::BAR_WIDTH)
+    }
+}
+
+impl PerfUiEntry for PerfUiEntrySystemTiming {
+    type Value = (f64, f64, f64);
+    type SystemParam = SRes<DiagnosticsStore>;
+
+    fn label(&self) -> &str {
+        self.label
+    }
+
+    fn sort_key(&self) -> i32 {
+        self.sort_key
+    }
+
+    fn width_hint(&self) -> usize {
+        // `cur avg max [bar]`
+        3 * 6 + 2 + Self::BAR_WIDTH
+    }
+
+    fn update_value(&self, diagnostics: &mut <Self::SystemParam as SystemParam>::Item<'_, '_>) -> Option<Self::Value> {
+        let diagnostic = diagnostics.get(self.path)?;
+        let worst = diagnostic.values().copied().fold(0.0, f64::max);
+        Some((diagnostic.value()?, diagnostic.average()?, worst))
+    }
+
+    fn format_value(&self, &(current, average, worst): &Self::Value) -> String {
+        // `#` up to the current timing, `|` marks the worst.
+        let (current_bar, worst_bar) = (Self::bar(current), Self::bar(worst));
+        let bar: String = (1..=Self::BAR_WIDTH)
+            .map(|i| match i {
+                i if i <= current_bar => '#',
+                i if i == worst_bar => '|',
+                _ => '.',
+            })
+            .collect();
+        format!("{current:5.2} {average:5.2} {worst:5.2} [{bar}]")
+    }
+
+    fn value_color(&self, &(_, average, _): &Self::Value) -> Option<Color> {
+        self.color_gradient.get_color_for_value(average as f32)
+    }
+
+    fn value_highlight(&self, &(_, _, worst): &Self::Value) -> bool {
+        worst > Self::BUDGET_MS
+    }
+}
+
 mod sort_keys {
     pub const RENDER_ADAPTER: i32 = 1000;
     pub const WINDOW_RESOLUTION: i32 = 1001;
     pub const RENDER_RESOLUTION: i32 = 1002;
+    pub const TIMING_SPLAT: i32 = 1100;
+    pub const TIMING_BUILD: i32 = 1101;
+    pub const TIMING_PATHING: i32 = 1102;
+    pub const TIMING_AVOIDANCE: i32 = 1103;
 }
 
 fn perf_ui(mut commands: Commands, assets: Res<FontAssets>) {
@@ -129,6 +203,12 @@ fn perf_ui(mut commands: Commands, assets: Res<FontAssets>) {
             },
             PerfUiEntryRenderResolution { sort_key: sort_keys::RENDER_RESOLUTION },
         ),
+        (
+            PerfUiEntrySystemTiming::new("Splat (ms)", &timings::SPLAT, sort_keys::TIMING_SPLAT),
+            PerfUiEntrySystemTiming::new("Build (ms)", &timings::BUILD, sort_keys::TIMING_BUILD),
+            PerfUiEntrySystemTiming::new("Pathing (ms)", &timings::PATHING, sort_keys::TIMING_PATHING),
+            PerfUiEntrySystemTiming::new("Avoidance (ms)", &timings::AVOIDANCE, sort_keys::TIMING_AVOIDANCE),
+        ),
     ));
 }
 
@@ -3,12 +3,13 @@ use bevy_egui::{egui, EguiContext};
 use bevy_inspector_egui::bevy_inspector::hierarchy::SelectedEntities;
 
 use super::key_codes;
-use crate::{app_state::AppState, prelude::*};
+use crate::{app_state::AppState, navigation::avoidance::AvoidanceBackend, prelude::*, simulation::SimulationConfig};
 
 pub struct SidePanelPlugin;
 
 impl Plugin for SidePanelPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<InspectorSelection>();
         app.add_systems(
             Update,
             side_panel_ui
@@ -18,6 +19,10 @@ impl Plugin for SidePanelPlugin {
     }
 }
 
+/// Entities selected in the hierarchy.
+#[derive(Resource, Default, Deref, PartialEq, Eq)]
+pub(super) struct InspectorSelection(Vec<Entity>);
+
 #[derive(Default, PartialEq, Eq)]
 pub(super) enum Panel {
     #[default]
@@ -25,6 +30,12 @@ pub(super) enum Panel {
     Resources,
     Assets,
     DebugLayers,
+    Spawn,
+    Stats,
+    Log,
+    Trace,
+    Layout,
+    Editor,
 }
 
 pub(super) fn side_panel_ui(
@@ -43,6 +54,12 @@ pub(super) fn side_panel_ui(
                 ui.selectable_value(&mut *active_panel, Panel::Resources, "Resource");
                 ui.selectable_value(&mut *active_panel, Panel::Assets, "Assets");
                 ui.selectable_value(&mut *active_panel, Panel::DebugLayers, "Debug Layers");
+                ui.selectable_value(&mut *active_panel, Panel::Spawn, "Spawn");
+                ui.selectable_value(&mut *active_panel, Panel::Stats, "Stats");
+                ui.selectable_value(&mut *active_panel, Panel::Log, "Log");
+                ui.selectable_value(&mut *active_panel, Panel::Trace, "Trace");
+                ui.selectable_value(&mut *active_panel, Panel::Layout, "Layout");
+                ui.selectable_value(&mut *active_panel, Panel::Editor, "Editor");
             });
 
             ui.separator();
@@ -72,6 +89,30 @@ pub(super) fn side_panel_ui(
                         }
                         Panel::DebugLayers => {
                             bevy_inspector_egui::bevy_inspector::ui_for_resource::<DebugLayers>(world, ui);
+                            ui.separator();
+                            ui.label("Avoidance");
+                            bevy_inspector_egui::bevy_inspector::ui_for_resource::<AvoidanceBackend>(world, ui);
+                            ui.separator();
+                            ui.label("Simulation");
+                            bevy_inspector_egui::bevy_inspector::ui_for_resource::<SimulationConfig>(world, ui);
+                        }
+                        Panel::Spawn => {
+                            super::spawn_menu::ui(world, ui);
+                        }
+                        Panel::Stats => {
+                            super::stats_panel::ui(world, ui);
+                        }
+                        Panel::Log => {
+                            super::log_panel::ui(world, ui);
+                        }
+                        Panel::Trace => {
+                            super::trace::ui(world, ui);
+                        }
+                        Panel::Layout => {
+                            super::layout_panel::ui(world, ui);
+                        }
+                        Panel::Editor => {
+                            super::editor::ui(world, ui);
                         }
                     };
                     ui.set_min_width(available_size.x);
@@ -94,4 +135,6 @@ pub(super) fn side_panel_ui(
             }
         },
     );
+
+    world.resource_mut::<InspectorSelection>().set_if_neq(InspectorSelection(selected_entities.as_slice().to_vec()));
 }
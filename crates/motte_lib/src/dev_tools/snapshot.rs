@@ -0,0 +1,114 @@
+//! Field snapshots written by the `snapshot` console command, so problematic maps can be attached to bug reports &
+//! diffed between versions. Every [`FlowField`] of the chosen agent size is written to [`SNAPSHOT_DIRECTORY`] as a PNG
+//! with one pixel per cell & one layer per channel:
+//!
+//! - red: [`ObstacleField`], `255` if traversable by the agent size, `128` if occupied by an agent & else `0`.
+//! - green: integration cost normalized to `1..=255`, `0` for blocked or occupied cells.
+//! - blue: flow direction as `16 + 32 * direction`, `+8` when repulsing & `0` without a direction.
+
+use std::{fs, path::Path};
+
+use bevy::{
+    core::FrameCount,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use super::console::ConsoleResult;
+use crate::{
+    navigation::{
+        agent::Agent,
+        flow_field::fields::{
+            flow::FlowField,
+            obstacle::{ObstacleField, Occupant},
+        },
+    },
+    prelude::*,
+    settings,
+};
+
+/// Relative to the [config directory](settings::directory).
+const SNAPSHOT_DIRECTORY: &str = "snapshots";
+
+pub(super) fn snapshot(In(args): In<Vec<String>>, world: &mut World) -> ConsoleResult {
+    let agent = match args.first().map(String::as_str) {
+        None | Some("medium") => Agent::Medium,
+        Some("small") => Agent::Small,
+        Some("large") => Agent::Large,
+        Some("huge") => Agent::Huge,
+        Some(size) => return Err(format!("unknown agent size '{size}'")),
+    };
+    if !world.contains_resource::<ObstacleField>() {
+        return Err("no obstacle field".into());
+    }
+
+    let directory = settings::directory().ok_or("no config directory found")?.join(SNAPSHOT_DIRECTORY);
+    fs::create_dir_all(&directory).map_err(|err| format!("failed to create {}: {err}", directory.display()))?;
+    let count = match agent {
+        Agent::Small => write::<{ Agent::Small }>(world, &directory)?,
+        Agent::Medium => write::<{ Agent::Medium }>(world, &directory)?,
+        Agent::Large => write::<{ Agent::Large }>(world, &directory)?,
+        Agent::Huge => write::<{ Agent::Huge }>(world, &directory)?,
+    };
+    Ok(format!("wrote {count} {agent} field snapshots to {}", directory.display()))
+}
+
+/// Writes a snapshot of each [`FlowField`] of `AGENT` to `directory`, returns the number of files written.
+fn write<const AGENT: Agent>(world: &mut World, directory: &Path) -> Result<usize, String> {
+    let frame = world.resource::<FrameCount>().0;
+    let mut flow_fields = world.query::<(Entity, &FlowField<AGENT>)>();
+    let obstacle_field = world.resource::<ObstacleField>();
+
+    let mut count = 0;
+    for (entity, flow_field) in flow_fields.iter(world) {
+        if flow_field.len() != obstacle_field.len() {
+            // Layout changed & the field hasn't been rebuilt yet.
+            continue;
+        }
+
+        let pixels = (0..obstacle_field.len())
+            .zip(flow_field.normalized_costs())
+            .zip(flow_field.iter())
+            .flat_map(|((index, cost), flow)| {
+                let cell = obstacle_field.cell_no_check(index);
+                let obstacle = if obstacle_field.traversable(cell, AGENT) {
+                    u8::MAX
+                } else if obstacle_field.occupant(cell) == Occupant::Agent {
+                    128
+                } else {
+                    0
+                };
+                let integration = cost.map_or(0, |cost| 1 + (cost * 254.0) as u8);
+                let direction = flow.direction();
+                let flow = match direction.as_direction2d() {
+                    Some(_) => 16 + 32 * direction as u8 + if flow.is_repulse() { 8 } else { 0 },
+                    None => 0,
+                };
+                [obstacle, integration, flow, u8::MAX]
+            })
+            .collect();
+
+        let image = Image::new(
+            Extent3d {
+                width: obstacle_field.width() as u32,
+                height: obstacle_field.height() as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            pixels,
+            TextureFormat::Rgba8Unorm,
+            RenderAssetUsages::default(),
+        );
+
+        let path = directory.join(format!("{frame}-{AGENT}-{}.png", entity.index()));
+        image
+            .try_into_dynamic()
+            .map_err(|err| format!("failed to convert snapshot: {err:?}"))?
+            .save(&path)
+            .map_err(|err| format!("failed to write {}: {err}", path.display()))?;
+        count += 1;
+    }
+    Ok(count)
+}
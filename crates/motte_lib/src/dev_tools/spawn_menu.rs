@@ -0,0 +1,259 @@
+//! Side panel section for spawning agents, obstacles & a goal at the cursor. The selected tool places its content
+//! on every left click in the world until it's deselected.
+
+use bevy::{ecs::system::EntityCommands, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContext};
+
+use crate::{
+    app_state::AppState,
+    asset_management::SpellDefAssets,
+    cleanup::StateScoped,
+    combat::CombatBundle,
+    cursor::{CursorClick, CursorPosition},
+    determinism::GameRng,
+    graphics::pixelate,
+    movement::motor::CharacterMotor,
+    navigation::{
+        agent::{Agent, Speed, TargetReachedCondition},
+        flow_field::{pathing::Goal, CellIndex},
+        obstacle::ObstacleShape,
+    },
+    physics::layers,
+    player::camera::MainCamera,
+    prefab::PrefabCommandsExt,
+    prelude::*,
+    save::Save,
+    spells::SpellBook,
+    utils::math::random_point_in_square,
+};
+
+pub(super) struct SpawnMenuPlugin;
+
+impl Plugin for SpawnMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(res: SpawnMenu, SpawnTool, GoalMarker);
+        app.add_systems(
+            Update,
+            place.run_if(|menu: Res<SpawnMenu>| menu.tool.is_some()).run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub(super) struct SpawnMenu {
+    tool: Option<SpawnTool>,
+    agent: Agent,
+    count: u32,
+    /// Agents are scattered within this distance of the cursor.
+    spread: f32,
+    obstacle: ObstacleShape,
+}
+
+impl Default for SpawnMenu {
+    fn default() -> Self {
+        Self {
+            tool: None,
+            agent: Agent::Medium,
+            count: 10,
+            spread: 5.0,
+            obstacle: ObstacleShape::Cuboid { half_size: 2.0 },
+        }
+    }
+}
+
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+enum SpawnTool {
+    Agents,
+    Obstacle,
+    /// Moves the goal marker & points every agent at it.
+    Goal,
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct GoalMarker;
+
+pub(super) fn ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut menu = world.resource_mut::<SpawnMenu>();
+
+    ui.horizontal(|ui| {
+        ui.selectable_value(&mut menu.tool, None, "None");
+        ui.selectable_value(&mut menu.tool, Some(SpawnTool::Agents), "Agents");
+        ui.selectable_value(&mut menu.tool, Some(SpawnTool::Obstacle), "Obstacle");
+        ui.selectable_value(&mut menu.tool, Some(SpawnTool::Goal), "Goal");
+    });
+    ui.weak("left click in the world to place");
+    ui.separator();
+
+    egui::Grid::new("spawn_menu").num_columns(2).show(ui, |ui| {
+        ui.label("Agent size");
+        ui.horizontal(|ui| {
+            for agent in Agent::ALL.into_iter().rev() {
+                ui.selectable_value(&mut menu.agent, agent, agent.to_string());
+            }
+        });
+        ui.end_row();
+
+        ui.label("Count");
+        ui.add(egui::DragValue::new(&mut menu.count).clamp_range(1..=1000));
+        ui.end_row();
+
+        ui.label("Spread");
+        ui.add(egui::DragValue::new(&mut menu.spread).clamp_range(0.0..=100.0).speed(0.1));
+        ui.end_row();
+
+        ui.label("Obstacle");
+        ui.horizontal(|ui| {
+            let cuboid = matches!(menu.obstacle, ObstacleShape::Cuboid { .. });
+            if ui.selectable_label(cuboid, "Cuboid").clicked() && !cuboid {
+                menu.obstacle = ObstacleShape::Cuboid { half_size: 2.0 };
+            }
+            if ui.selectable_label(!cuboid, "Capsule").clicked() && cuboid {
+                menu.obstacle = ObstacleShape::Capsule { radius: 2.0, height: 4.0 };
+            }
+        });
+        ui.end_row();
+
+        match &mut menu.obstacle {
+            ObstacleShape::Cuboid { half_size } => {
+                ui.label("Half size");
+                ui.add(egui::DragValue::new(half_size).clamp_range(0.5..=20.0).speed(0.1));
+                ui.end_row();
+            }
+            ObstacleShape::Capsule { radius, height } => {
+                ui.label("Radius");
+                ui.add(egui::DragValue::new(radius).clamp_range(0.5..=20.0).speed(0.1));
+                ui.end_row();
+                ui.label("Height");
+                ui.add(egui::DragValue::new(height).clamp_range(0.5..=20.0).speed(0.1));
+                ui.end_row();
+            }
+        }
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn place(
+    mut commands: Commands,
+    menu: Res<SpawnMenu>,
+    mut clicks: EventReader<CursorClick>,
+    cursor: Res<CursorPosition>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut egui_contexts: Query<&mut EguiContext, With<PrimaryWindow>>,
+    mut goals: Query<(Entity, &mut Transform), With<GoalMarker>>,
+    agents: Query<Entity, With<Agent>>,
+    spells: Res<SpellDefAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<GameRng>,
+) {
+    let Some(tool) = menu.tool else {
+        return;
+    };
+    let (Ok((camera, camera_transform)), Ok(mut egui_context)) = (cameras.get_single(), egui_contexts.get_single_mut())
+    else {
+        return;
+    };
+    let over_ui = egui_context.get_mut().is_pointer_over_area();
+
+    for click in clicks.read() {
+        if !matches!(click.button, MouseButton::Left) || over_ui {
+            continue;
+        }
+
+        let (origin, direction) = math::world_space_ray_from_ndc(cursor.ndc(), camera, camera_transform);
+        let position = math::plane_intersection(origin, direction, Vec3::ZERO, Vec3::Y).xz();
+
+        match tool {
+            SpawnTool::Agents => {
+                let agent = menu.agent;
+                let mesh = meshes.add(agent_mesh(agent));
+                let material = materials.add(Color::RED);
+                // New agents follow the goal marker if one was placed.
+                let goal = goals.get_single().ok().map(|(goal, _)| goal);
+                for _ in 0..menu.count {
+                    let position = position + random_point_in_square(&mut **rng, menu.spread);
+                    let mut agent = spawn_agent(&mut commands, agent, position, mesh.clone(), material.clone());
+                    agent.insert(SpellBook::new(spells.spells.iter().cloned()));
+                    if let Some(goal) = goal {
+                        agent.insert(Goal::Entity(goal));
+                    }
+                }
+            }
+            SpawnTool::Obstacle => {
+                let shape = menu.obstacle;
+                commands.spawn_prefab("obstacle").insert((
+                    Name::unit("obstacle"),
+                    PbrBundle {
+                        mesh: meshes.add(shape.mesh()),
+                        material: materials.add(Color::BEIGE),
+                        transform: position.x0y().into_transform(),
+                        ..default()
+                    },
+                    shape.collider(),
+                    shape,
+                    layers::terrain(),
+                    StateScoped(AppState::InGame),
+                ));
+            }
+            SpawnTool::Goal => {
+                let translation = position.x0y() + Vec3::Y;
+                let goal = match goals.get_single_mut() {
+                    Ok((goal, mut transform)) => {
+                        transform.translation = translation;
+                        goal
+                    }
+                    Err(_) => commands
+                        .spawn((
+                            Name::unit("goal"),
+                            PbrBundle {
+                                mesh: meshes.add(Sphere::new(1.0)),
+                                material: materials.add(Color::GREEN),
+                                transform: translation.into_transform(),
+                                ..default()
+                            },
+                            GoalMarker,
+                            StateScoped(AppState::InGame),
+                        ))
+                        .id(),
+                };
+                for entity in &agents {
+                    commands.entity(entity).insert(Goal::Entity(goal));
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn agent_mesh(agent: Agent) -> Mesh {
+    Mesh::from(Cylinder { radius: agent.radius(), half_height: agent.height() / 2.0 })
+}
+
+/// Spawns an agent standing on the ground at `position`.
+pub(super) fn spawn_agent<'a>(
+    commands: &'a mut Commands,
+    agent: Agent,
+    position: Vec2,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+) -> EntityCommands<'a> {
+    commands.spawn((
+        Name::unit("agent"),
+        PbrBundle {
+            mesh,
+            material,
+            transform: Vec3::new(position.x, agent.height() / 2.0, position.y).into_transform(),
+            ..default()
+        },
+        CharacterMotor::cylinder(agent.height(), agent.radius()),
+        pixelate::Snap::translation(),
+        agent,
+        Speed::base(100.0),
+        CombatBundle::default(),
+        Save,
+        CellIndex::default(),
+        TargetReachedCondition::Distance(1.0),
+        StateScoped(AppState::InGame),
+    ))
+}
@@ -0,0 +1,134 @@
+//! Watchdog for frame hitches. Every frame slower than [`SpikeWatchdog::budget_ms`] appends a report with the
+//! navigation [`timings`] of that frame, entity counts & the flow fields rebuilt to [`SPIKE_LOG_PATH`] as a JSON line,
+//! so intermittent spikes can be looked at after the fact.
+
+use std::{fs, io::Write};
+
+use bevy::{core::FrameCount, diagnostic::DiagnosticsStore, ecs::entity::Entities};
+use serde::Serialize;
+
+use crate::{
+    app_state::AppState,
+    navigation::{
+        agent::{for_each_agent, Agent},
+        flow_field::fields::flow::FlowField,
+        obstacle::Obstacle,
+    },
+    prelude::*,
+    settings, timings,
+};
+
+/// Relative to the [config directory](settings::directory).
+const SPIKE_LOG_PATH: &str = "logs/spikes.jsonl";
+
+pub(super) struct SpikeWatchdogPlugin;
+
+impl Plugin for SpikeWatchdogPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(res: SpikeWatchdog);
+        app.init_resource::<RebuiltFlowFields>();
+        app.add_systems(
+            Last,
+            (for_each_agent!(|AGENT| count_rebuilt::<AGENT>), watchdog)
+                .chain()
+                .run_if(|watchdog: Res<SpikeWatchdog>| watchdog.enabled)
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct SpikeWatchdog {
+    pub enabled: bool,
+    /// Frames taking longer than this are logged.
+    pub budget_ms: f32,
+}
+
+impl Default for SpikeWatchdog {
+    fn default() -> Self {
+        Self { enabled: true, budget_ms: 50.0 }
+    }
+}
+
+/// Flow fields rebuilt this frame, dirty flow fields have their [`Dirty`] marker removed once built.
+#[derive(Resource, Default, Deref, DerefMut)]
+struct RebuiltFlowFields(usize);
+
+#[derive(Serialize, Debug)]
+struct SpikeReport<'a> {
+    frame: u32,
+    elapsed_secs: f64,
+    frame_ms: f64,
+    budget_ms: f32,
+    /// Milliseconds spent in each timed system set this frame, slowest first.
+    timings: Vec<(&'a str, f64)>,
+    entities: u32,
+    agents: usize,
+    obstacles: usize,
+    rebuilt_flow_fields: usize,
+}
+
+fn count_rebuilt<const AGENT: Agent>(
+    mut rebuilt: ResMut<RebuiltFlowFields>,
+    mut removed: RemovedComponents<Dirty<FlowField<AGENT>>>,
+) {
+    **rebuilt += removed.read().count();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn watchdog(
+    watchdog: Res<SpikeWatchdog>,
+    mut rebuilt: ResMut<RebuiltFlowFields>,
+    time: Res<Time<Real>>,
+    frame_count: Res<FrameCount>,
+    diagnostics: Res<DiagnosticsStore>,
+    entities: &Entities,
+    agents: Query<(), With<Agent>>,
+    obstacles: Query<(), With<Obstacle>>,
+) {
+    let rebuilt_flow_fields = std::mem::take(&mut **rebuilt);
+    let frame_ms = time.delta_seconds_f64() * 1000.0;
+    if frame_ms <= watchdog.budget_ms as f64 {
+        return;
+    }
+
+    let frame_start = time.last_update();
+    let mut timings = timings::ALL
+        .iter()
+        .filter_map(|(label, path)| {
+            let measurements = diagnostics.get(path)?.measurements();
+            let ms: f64 =
+                measurements.filter(|m| frame_start.map_or(true, |start| m.time >= start)).map(|m| m.value).sum();
+            Some((*label, ms))
+        })
+        .collect_vec();
+    timings.sort_unstable_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    let report = SpikeReport {
+        frame: frame_count.0,
+        elapsed_secs: time.elapsed_seconds_f64(),
+        frame_ms,
+        budget_ms: watchdog.budget_ms,
+        timings,
+        entities: entities.len(),
+        agents: agents.iter().len(),
+        obstacles: obstacles.iter().len(),
+        rebuilt_flow_fields,
+    };
+
+    warn!("Frame {} took {frame_ms:.1}ms, over the {}ms budget", report.frame, report.budget_ms);
+    if let Err(err) = append(&report) {
+        error!("Failed to write spike report to {SPIKE_LOG_PATH}: {err}");
+    }
+}
+
+fn append(report: &SpikeReport) -> AnyResult<()> {
+    let path = settings::directory().context("no config directory found")?.join(SPIKE_LOG_PATH);
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(report)?)?;
+    Ok(())
+}
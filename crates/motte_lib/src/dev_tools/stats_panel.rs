@@ -0,0 +1,119 @@
+//! Stats of the entity selected in the hierarchy, or the first selected unit, with the modifiers that make up each
+//! value. Base values can be edited in place & any stat can be pinned to an override, which is reapplied every frame
+//! after the stats are recomputed until it's unpinned.
+
+use bevy_egui::egui;
+
+use super::side_panel::InspectorSelection;
+use crate::{
+    app_state::AppState,
+    player::selection::Selected,
+    prelude::*,
+    stats::{
+        stat::{ModifierOp, StatRegistry},
+        StatSystem,
+    },
+};
+
+pub(super) struct StatsPanelPlugin;
+
+impl Plugin for StatsPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StatOverrides>();
+        app.add_systems(
+            PostUpdate,
+            apply_overrides.after(StatSystem::Cleanup).run_if(|overrides: Res<StatOverrides>| !overrides.is_empty()),
+        );
+        app.add_systems(OnExit(AppState::InGame), |mut overrides: ResMut<StatOverrides>| overrides.clear());
+    }
+}
+
+/// Pinned values by entity & stat name, with the setter of the stat.
+#[derive(Resource, Default, Deref, DerefMut)]
+struct StatOverrides(HashMap<(Entity, String), (f32, fn(&mut World, Entity, f32))>);
+
+fn apply_overrides(world: &mut World) {
+    let overrides = world
+        .resource::<StatOverrides>()
+        .iter()
+        .map(|(&(entity, _), &(value, set))| (entity, value, set))
+        .collect_vec();
+    for (entity, value, set) in overrides {
+        set(world, entity, value);
+    }
+}
+
+fn selected(world: &mut World) -> Option<Entity> {
+    if let &[entity] = world.resource::<InspectorSelection>().as_slice() {
+        return Some(entity);
+    }
+    world.query_filtered::<Entity, With<Selected>>().iter(world).next()
+}
+
+pub(super) fn ui(world: &mut World, ui: &mut egui::Ui) {
+    let Some(entity) = selected(world) else {
+        ui.weak("select an entity in the hierarchy or a unit");
+        return;
+    };
+    let name = |world: &World, entity: Entity| {
+        world.get::<Name>(entity).map_or_else(|| format!("{entity:?}"), |name| name.to_string())
+    };
+    ui.weak(format!("stats of {}", name(world, entity)));
+    ui.separator();
+
+    let registered = world.resource::<StatRegistry>().iter().cloned().collect_vec();
+    let mut any = false;
+    for stat in registered {
+        let Some(breakdown) = (stat.inspect)(world, entity) else {
+            continue;
+        };
+        any = true;
+
+        let key = (entity, stat.name.clone());
+        let pinned = world.resource::<StatOverrides>().get(&key).map(|&(value, _)| value);
+        let title = format!("{}: {:.2}{}", stat.name, breakdown.value, if pinned.is_some() { " (pinned)" } else { "" });
+        egui::CollapsingHeader::new(title).id_source(&stat.name).default_open(true).show(ui, |ui| {
+            egui::Grid::new(&stat.name).num_columns(3).show(ui, |ui| {
+                if let Some(mut base) = breakdown.base {
+                    ui.label("base");
+                    ui.label("");
+                    if ui.add(egui::DragValue::new(&mut base).speed(0.1)).changed() {
+                        (stat.set_base)(world, entity, base);
+                    }
+                    ui.end_row();
+                }
+
+                for modifier in &breakdown.modifiers {
+                    ui.label(name(world, modifier.source));
+                    ui.monospace(match modifier.op {
+                        ModifierOp::Flat => "+",
+                        ModifierOp::Mult => "x",
+                    });
+                    ui.monospace(format!("{:.2}", modifier.value));
+                    ui.end_row();
+                }
+
+                let (mut enabled, mut value) = (pinned.is_some(), pinned.unwrap_or(breakdown.value));
+                ui.label("override");
+                ui.checkbox(&mut enabled, "");
+                ui.add_enabled(enabled, egui::DragValue::new(&mut value).speed(0.1));
+                ui.end_row();
+
+                let mut overrides = world.resource_mut::<StatOverrides>();
+                match (enabled, pinned) {
+                    (true, pinned) if pinned != Some(value) => {
+                        overrides.insert(key, (value, stat.set_value));
+                    }
+                    (false, Some(_)) => {
+                        overrides.remove(&key);
+                    }
+                    _ => {}
+                }
+            });
+        });
+    }
+
+    if !any {
+        ui.weak("no stats");
+    }
+}
@@ -0,0 +1,181 @@
+//! Records the navigation state of the agents selected in the side panel into a ring buffer & draws it as trails.
+//! Recording can be paused to scrub back through the samples, e.g. to find where agents start oscillating.
+
+use std::collections::VecDeque;
+
+use bevy_egui::egui;
+
+use super::side_panel::InspectorSelection;
+use crate::{
+    app_state::{simulating, AppState},
+    navigation::{
+        agent::{Agent, Blocking, DesiredVelocity},
+        NavigationSystems,
+    },
+    prelude::*,
+};
+
+pub(super) struct TracePlugin;
+
+impl Plugin for TracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TraceRecorder>();
+        app.add_systems(
+            FixedUpdate,
+            (
+                record_desired.after(NavigationSystems::Velocity).before(NavigationSystems::Avoidance),
+                record_avoidance.after(NavigationSystems::Steering),
+            )
+                .run_if(|recorder: Res<TraceRecorder>| recorder.recording)
+                .run_if(simulating),
+        );
+        app.add_systems(
+            Update,
+            gizmos
+                .run_if(|recorder: Res<TraceRecorder>| !recorder.traces.is_empty())
+                .run_if(in_state(AppState::InGame)),
+        );
+        app.add_systems(OnExit(AppState::InGame), clear);
+    }
+}
+
+#[derive(Resource)]
+pub(super) struct TraceRecorder {
+    recording: bool,
+    /// Samples kept per agent, one per fixed update.
+    capacity: usize,
+    /// Samples back from the newest that are shown while paused.
+    playback: usize,
+    traces: HashMap<Entity, VecDeque<TraceSample>>,
+}
+
+impl Default for TraceRecorder {
+    fn default() -> Self {
+        Self { recording: true, capacity: 600, playback: 0, traces: HashMap::default() }
+    }
+}
+
+impl TraceRecorder {
+    fn len(&self) -> usize {
+        self.traces.values().map(VecDeque::len).max().unwrap_or_default()
+    }
+
+    /// Samples up to the playback position.
+    fn visible<'a>(&self, trace: &'a VecDeque<TraceSample>) -> impl Iterator<Item = &'a TraceSample> {
+        let end = if self.recording { trace.len() } else { trace.len().saturating_sub(self.playback) };
+        trace.range(..end)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TraceSample {
+    position: Vec2,
+    /// [`DesiredVelocity`] before avoidance.
+    desired: Vec2,
+    /// [`DesiredVelocity`] after blending in avoidance.
+    avoided: Vec2,
+    blocking: bool,
+}
+
+fn record_desired(
+    mut recorder: ResMut<TraceRecorder>,
+    selection: Res<InspectorSelection>,
+    agents: Query<(&GlobalTransform, &DesiredVelocity, Has<Blocking>), With<Agent>>,
+) {
+    let capacity = recorder.capacity.max(1);
+    for &entity in selection.iter() {
+        let Ok((transform, desired_velocity, blocking)) = agents.get(entity) else {
+            continue;
+        };
+        let trace = recorder.traces.entry(entity).or_default();
+        while trace.len() >= capacity {
+            trace.pop_front();
+        }
+        trace.push_back(TraceSample {
+            position: transform.translation().xz(),
+            desired: **desired_velocity,
+            avoided: **desired_velocity,
+            blocking,
+        });
+    }
+}
+
+fn record_avoidance(
+    mut recorder: ResMut<TraceRecorder>,
+    selection: Res<InspectorSelection>,
+    agents: Query<&DesiredVelocity, With<Agent>>,
+) {
+    for &entity in selection.iter() {
+        let (Ok(desired_velocity), Some(sample)) =
+            (agents.get(entity), recorder.traces.get_mut(&entity).and_then(VecDeque::back_mut))
+        else {
+            continue;
+        };
+        sample.avoided = **desired_velocity;
+    }
+}
+
+fn clear(mut recorder: ResMut<TraceRecorder>) {
+    recorder.traces.clear();
+    recorder.playback = 0;
+}
+
+pub(super) fn ui(world: &mut World, ui: &mut egui::Ui) {
+    let selected = world.resource::<InspectorSelection>().len();
+    let mut recorder = world.resource_mut::<TraceRecorder>();
+    let len = recorder.len();
+
+    ui.horizontal(|ui| {
+        let label = if recorder.recording { "Pause" } else { "Record" };
+        if ui.button(label).clicked() {
+            recorder.recording = !recorder.recording;
+            recorder.playback = 0;
+        }
+        if ui.button("Clear").clicked() {
+            recorder.traces.clear();
+            recorder.playback = 0;
+        }
+    });
+    ui.weak(format!("recording {selected} selected agents, {} traces", recorder.traces.len()));
+    ui.separator();
+
+    egui::Grid::new("trace").num_columns(2).show(ui, |ui| {
+        ui.label("Capacity");
+        ui.add(egui::DragValue::new(&mut recorder.capacity).clamp_range(1..=10_000));
+        ui.end_row();
+
+        ui.label("Playback");
+        ui.add_enabled(
+            !recorder.recording,
+            egui::Slider::new(&mut recorder.playback, 0..=len.saturating_sub(1)).text("samples back"),
+        );
+        ui.end_row();
+    });
+}
+
+/// Trails go from green to red the more avoidance deviates from the desired velocity, blocking samples are gray.
+/// The sample at the playback position shows the desired (yellow) & avoided (purple) velocity.
+fn gizmos(mut gizmos: Gizmos, recorder: Res<TraceRecorder>) {
+    for trace in recorder.traces.values() {
+        let mut previous: Option<&TraceSample> = None;
+        for sample in recorder.visible(trace) {
+            if let Some(previous) = previous {
+                let color = if sample.blocking {
+                    Color::GRAY
+                } else {
+                    let deviation = sample.desired.angle_between(sample.avoided).abs() / PI;
+                    let deviation = if deviation.is_nan() { 0.0 } else { deviation };
+                    Color::GREEN * (1.0 - deviation) + Color::RED * deviation
+                };
+                gizmos.line(previous.position.x0y().y_pad(), sample.position.x0y().y_pad(), color);
+            }
+            previous = Some(sample);
+        }
+
+        if let Some(sample) = previous {
+            let position = sample.position.x0y().y_pad();
+            gizmos.arrow(position, position + sample.desired.x0y(), Color::YELLOW);
+            gizmos.arrow(position, position + sample.avoided.x0y(), Color::PURPLE);
+        }
+    }
+}
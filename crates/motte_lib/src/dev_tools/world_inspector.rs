@@ -0,0 +1,159 @@
+//! Inspects agents by clicking them in the world instead of finding them in the hierarchy. Hovering an agent shows
+//! its name, cell & goal, clicking it opens a window with its navigation state & components.
+
+use bevy::{input::common_conditions::input_toggle_active, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContext};
+
+use super::key_codes;
+use crate::{
+    app_state::AppState,
+    cursor::{CursorClick, CursorPosition},
+    navigation::{
+        agent::Agent,
+        flow_field::{cache::FlowFieldCache, fields::flow::Flow, pathing::Goal, CellIndex},
+    },
+    player::camera::MainCamera,
+    prelude::*,
+};
+
+pub(super) struct WorldInspectorPlugin;
+
+impl Plugin for WorldInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldInspector>();
+        app.add_systems(
+            Update,
+            (pick, ui)
+                .chain()
+                .run_if(input_toggle_active(false, key_codes::TOGGLE_WORLD_INSPECTOR))
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct WorldInspector {
+    hovered: Option<Entity>,
+    selected: Option<Entity>,
+}
+
+fn pick(
+    mut inspector: ResMut<WorldInspector>,
+    mut clicks: EventReader<CursorClick>,
+    cursor: Res<CursorPosition>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut egui_contexts: Query<&mut EguiContext, With<PrimaryWindow>>,
+    agents: Query<(Entity, &Agent, &GlobalTransform)>,
+) {
+    let (Ok((camera, camera_transform)), Ok(mut egui_context)) = (cameras.get_single(), egui_contexts.get_single_mut())
+    else {
+        return;
+    };
+    if egui_context.get_mut().is_pointer_over_area() {
+        inspector.hovered = None;
+        clicks.clear();
+        return;
+    }
+
+    let (origin, direction) = math::world_space_ray_from_ndc(cursor.ndc(), camera, camera_transform);
+    // Hit test each agent at its center height, the closest one to the camera wins.
+    inspector.hovered = agents
+        .iter()
+        .filter_map(|(entity, agent, transform)| {
+            let center = transform.translation();
+            let hit = math::plane_intersection(origin, direction, center, Vec3::Y);
+            (hit.xz().distance(center.xz()) <= agent.radius()).then(|| (entity, hit.distance_squared(origin)))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity);
+
+    for click in clicks.read() {
+        if matches!(click.button, MouseButton::Left) {
+            inspector.selected = inspector.hovered;
+        }
+    }
+}
+
+fn ui(world: &mut World) {
+    let mut egui_context = world.query_filtered::<&mut EguiContext, With<PrimaryWindow>>().single(world).clone();
+    let ctx = egui_context.get_mut();
+    let WorldInspector { hovered, selected } = *world.resource::<WorldInspector>();
+
+    if let Some(entity) = hovered.filter(|&entity| world.get_entity(entity).is_some()) {
+        egui::show_tooltip_at_pointer(ctx, egui::Id::new("world_inspector_hover"), |ui| {
+            let entity_ref = world.entity(entity);
+            ui.strong(entity_ref.get::<Name>().map_or_else(|| format!("{entity:?}"), ToString::to_string));
+            ui.label(format!("cell: {:?}", entity_ref.get::<CellIndex>().copied().unwrap_or_default()));
+            ui.label(format!("goal: {:?}", entity_ref.get::<Goal>().copied().unwrap_or_default()));
+        });
+    }
+
+    let Some(entity) = selected.filter(|&entity| world.get_entity(entity).is_some()) else {
+        world.resource_mut::<WorldInspector>().selected = None;
+        return;
+    };
+
+    let title = world.get::<Name>(entity).map_or_else(|| format!("{entity:?}"), ToString::to_string);
+    let mut open = true;
+    egui::Window::new(title)
+        .id(egui::Id::new("world_inspector"))
+        .open(&mut open)
+        .default_width(320.0)
+        .default_height(480.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                navigation_ui(world, entity, ui);
+                ui.separator();
+                bevy_inspector_egui::bevy_inspector::ui_for_entity(world, entity, ui);
+            });
+        });
+
+    if !open {
+        world.resource_mut::<WorldInspector>().selected = None;
+    }
+}
+
+fn navigation_ui(world: &World, entity: Entity, ui: &mut egui::Ui) {
+    let entity_ref = world.entity(entity);
+    let Some(&agent) = entity_ref.get::<Agent>() else {
+        return;
+    };
+    let goal = entity_ref.get::<Goal>().copied().unwrap_or_default();
+    let entry = match agent {
+        Agent::Small => cache_entry::<{ Agent::Small }>(world, &goal),
+        Agent::Medium => cache_entry::<{ Agent::Medium }>(world, &goal),
+        Agent::Large => cache_entry::<{ Agent::Large }>(world, &goal),
+        Agent::Huge => cache_entry::<{ Agent::Huge }>(world, &goal),
+    };
+
+    egui::Grid::new("world_inspector_navigation").num_columns(2).show(ui, |ui| {
+        ui.label("Agent");
+        ui.label(agent.to_string());
+        ui.end_row();
+
+        ui.label("Cell");
+        ui.label(format!("{:?}", entity_ref.get::<CellIndex>().copied().unwrap_or_default()));
+        ui.end_row();
+
+        ui.label("Goal");
+        ui.label(format!("{goal:?}"));
+        ui.end_row();
+
+        ui.label("Flow");
+        ui.label(entity_ref.get::<Flow>().map_or_else(|| "-".into(), |flow| format!("{flow:?}")));
+        ui.end_row();
+
+        ui.label("Flow field");
+        match entry {
+            Some((flow_field, remaining)) => ui.label(format!("{flow_field:?}, expires in {remaining:.1}s")),
+            None => ui.weak("not cached"),
+        };
+        ui.end_row();
+    });
+}
+
+/// Flow field entity & seconds until it expires from the [`FlowFieldCache`].
+fn cache_entry<const AGENT: Agent>(world: &World, goal: &Goal) -> Option<(Entity, f32)> {
+    let (entity, timer) = world.get_resource::<FlowFieldCache<AGENT>>()?.get(goal)?;
+    Some((*entity, timer.remaining_secs()))
+}
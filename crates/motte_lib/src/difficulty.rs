@@ -0,0 +1,103 @@
+//! [`Difficulty`] of a game, picked on the map select screen. Hostile units get multiplicative stat modifiers through
+//! the same [`TechBonus`] entities as researched techs & scenario waves are scaled by [`Difficulty::wave_size`]. The
+//! difficulty is written to save files & sent to lockstep peers so they simulate the same game.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    economy::Team,
+    prelude::*,
+    save::AppSaveExt,
+    tech::{TechBonus, TechEffect, TechModifier, TechStat},
+};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(res: Difficulty, DifficultyBonus);
+        app.register_save_resource::<Difficulty>();
+        app.add_systems(OnEnter(AppState::InGame), apply);
+        app.add_systems(Update, apply.run_if(in_state(AppState::InGame).and_then(resource_changed::<Difficulty>)));
+    }
+}
+
+#[derive(Resource, Reflect, Serialize, Deserialize, Display, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn next(self) -> Self {
+        Self::ALL[(Self::ALL.iter().position(|&other| other == self).unwrap() + 1) % Self::ALL.len()]
+    }
+
+    /// Multiplier of the speed of hostile units.
+    pub fn speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.85,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.15,
+        }
+    }
+
+    /// Multiplier of the health of hostile units.
+    pub fn health(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Multiplier of the spell damage of hostile units, see [`Affinity`](crate::spells::Affinity).
+    pub fn damage(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    /// Multiplier of the number of units in a wave.
+    pub fn wave_size(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    fn effects(self) -> impl Iterator<Item = TechEffect> {
+        let effect = |stat, value| TechEffect { stat, modifier: TechModifier::Mult(value), agent: None };
+        [effect(TechStat::Speed, self.speed()), effect(TechStat::Health, self.health())].into_iter().chain(
+            [TechStat::FireAffinity, TechStat::FrostAffinity, TechStat::ArcaneAffinity]
+                .into_iter()
+                .map(move |stat| effect(stat, self.damage())),
+        )
+    }
+}
+
+/// [`TechBonus`] of the current [`Difficulty`].
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct DifficultyBonus;
+
+/// Replaces the bonuses of hostile units when a game starts or the difficulty changes, e.g. by loading a save.
+fn apply(mut commands: Commands, difficulty: Res<Difficulty>, bonuses: Query<Entity, With<DifficultyBonus>>) {
+    for bonus in &bonuses {
+        commands.entity(bonus).despawn_recursive();
+    }
+    info!("Difficulty: {}", *difficulty);
+    for effect in difficulty.effects() {
+        let bonus = effect.spawn(&mut commands, Team::HOSTILE, format!("{} difficulty", *difficulty));
+        commands.entity(bonus).insert(DifficultyBonus);
+    }
+}
@@ -0,0 +1,51 @@
+//! Counters of the player's [`Stockpile`] in the top left corner.
+
+use super::{ResourceKind, Stockpile, Team};
+use crate::{app_state::AppState, asset_management::FontAssets, cleanup::StateScoped, main_menu, prelude::*};
+
+pub struct EconomyHudPlugin;
+
+impl Plugin for EconomyHudPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(StockpileCounter);
+        app.add_systems(OnEnter(AppState::InGame), spawn);
+        app.add_systems(Update, update.run_if(in_state(AppState::InGame).and_then(resource_changed::<Stockpile>)));
+    }
+}
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct StockpileCounter(ResourceKind);
+
+fn spawn(mut commands: Commands, fonts: Res<FontAssets>, stockpile: Res<Stockpile>) {
+    commands
+        .spawn((
+            Name::ui("stockpile"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    left: Val::Px(8.0),
+                    column_gap: Val::Px(16.0),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|builder| {
+            for kind in ResourceKind::ALL {
+                builder.spawn((main_menu::text(&fonts, counter(&stockpile, kind), 16.0), StockpileCounter(kind)));
+            }
+        });
+}
+
+fn update(stockpile: Res<Stockpile>, mut counters: Query<(&StockpileCounter, &mut Text)>) {
+    for (StockpileCounter(kind), mut text) in &mut counters {
+        text.sections[0].value = counter(&stockpile, *kind);
+    }
+}
+
+fn counter(stockpile: &Stockpile, kind: ResourceKind) -> String {
+    format!("{kind:?}: {}", stockpile.get(Team::PLAYER, kind))
+}
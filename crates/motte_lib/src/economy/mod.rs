@@ -0,0 +1,397 @@
+//! Resource gathering. [`Worker`]s walk to the nearest [`ResourceNode`] using a [`Goal`], gather until they carry
+//! their capacity, then return to the nearest [`Depot`] of their [`Team`] & add what they carry to its [`Stockpile`].
+//! Nodes are despawned once depleted. The map's nodes, depots & workers are spawned from an [`EconomyDef`].
+
+use bevy::ecs::{
+    entity::{EntityMapper, MapEntities},
+    reflect::ReflectMapEntities,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::{simulating, AppState},
+    despawn::Despawn,
+    navigation::{agent::Agent, flow_field::pathing::Goal},
+    prelude::*,
+    save::AppSaveExt,
+    stats::stat::StatPlugin,
+};
+
+#[cfg(feature = "presentation")]
+pub mod hud;
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(res: Stockpile, ResourceKind, GatherRate, EconomyDef, ResourceNodeDef);
+        app.register_save_resource::<Stockpile>();
+        app.register_save::<Team>()
+            .register_save::<Depot>()
+            .register_save::<ResourceNode>()
+            .register_save::<Worker>()
+            .register_save::<Carrying>()
+            .register_save::<WorkerTask>();
+        app.add_plugins(StatPlugin::<GatherRate>::default());
+        app.add_systems(FixedUpdate, (assign, work, deplete).chain().run_if(simulating));
+        app.add_systems(OnExit(AppState::InGame), |mut stockpile: ResMut<Stockpile>| *stockpile = default());
+    }
+}
+
+/// Team a unit, building or [`Stockpile`] belongs to. Hashed by reflection too, as the key of saved maps.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+#[reflect(Component, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Team(pub u8);
+
+impl Team {
+    /// Team of the local player.
+    pub const PLAYER: Team = Team(0);
+    /// Team of the waves spawned by a map's scenario.
+    pub const HOSTILE: Team = Team(1);
+}
+
+#[derive(Reflect, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    #[default]
+    Wood,
+    Stone,
+    Gold,
+}
+
+impl ResourceKind {
+    pub const ALL: [ResourceKind; 3] = [ResourceKind::Wood, ResourceKind::Stone, ResourceKind::Gold];
+
+    fn color(self) -> Color {
+        match self {
+            ResourceKind::Wood => Color::rgb(0.45, 0.3, 0.15),
+            ResourceKind::Stone => Color::GRAY,
+            ResourceKind::Gold => Color::GOLD,
+        }
+    }
+}
+
+/// Resources gathered by each team.
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct Stockpile(HashMap<Team, [u32; ResourceKind::ALL.len()]>);
+
+impl Stockpile {
+    pub fn get(&self, team: Team, kind: ResourceKind) -> u32 {
+        self.0.get(&team).map_or(0, |amounts| amounts[kind as usize])
+    }
+
+    pub fn add(&mut self, team: Team, kind: ResourceKind, amount: u32) {
+        let amounts = self.0.entry(team).or_default();
+        amounts[kind as usize] = amounts[kind as usize].saturating_add(amount);
+    }
+
+    /// Takes `amount` of `kind` from `team` if it has enough.
+    pub fn spend(&mut self, team: Team, kind: ResourceKind, amount: u32) -> bool {
+        let Some(amounts) = self.0.get_mut(&team).filter(|amounts| amounts[kind as usize] >= amount) else {
+            return false;
+        };
+        amounts[kind as usize] -= amount;
+        true
+    }
+}
+
+/// Gatherable resources, despawned when depleted.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct ResourceNode {
+    pub kind: ResourceKind,
+    pub amount: u32,
+}
+
+/// Where the [`Worker`]s of its [`Team`] deliver resources.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Depot;
+
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Worker {
+    /// Most resources carried at once.
+    pub capacity: u32,
+    /// Distance from the edge of the worker to the center of a node or depot at which it can gather or deliver.
+    pub reach: f32,
+}
+
+impl Default for Worker {
+    fn default() -> Self {
+        Self { capacity: 10, reach: 2.5 }
+    }
+}
+
+/// Resources gathered per second by a [`Worker`].
+#[derive(Stat, Component, Reflect)]
+pub struct GatherRate(f32);
+
+/// Resources carried by a [`Worker`].
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Carrying {
+    pub kind: ResourceKind,
+    pub amount: u32,
+}
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, MapEntities)]
+pub enum WorkerTask {
+    #[default]
+    Idle,
+    /// Walking to or gathering from a node, `progress` is the fraction of a resource gathered so far.
+    Gather {
+        node: Entity,
+        progress: f32,
+    },
+    Deliver {
+        depot: Entity,
+    },
+}
+
+impl MapEntities for WorkerTask {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        match self {
+            WorkerTask::Idle => {}
+            WorkerTask::Gather { node, .. } => *node = entity_mapper.map_entity(*node),
+            WorkerTask::Deliver { depot } => *depot = entity_mapper.map_entity(*depot),
+        }
+    }
+}
+
+/// Nearest entity to `position` of `candidates`.
+fn nearest(position: Vec2, candidates: impl Iterator<Item = (Entity, Vec2)>) -> Option<Entity> {
+    candidates
+        .map(|(entity, candidate)| (entity, candidate.distance_squared(position)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+/// Sends idle workers to the nearest node, or the nearest depot of their team if they carry anything.
+fn assign(
+    mut workers: Query<(&mut WorkerTask, &mut Goal, &Carrying, &Team, &GlobalTransform)>,
+    nodes: Query<(Entity, &ResourceNode, &GlobalTransform)>,
+    depots: Query<(Entity, &Team, &GlobalTransform), With<Depot>>,
+) {
+    let _span = info_span!("economy::assign").entered();
+    for (mut task, mut goal, carrying, team, transform) in &mut workers {
+        if *task != WorkerTask::Idle {
+            continue;
+        }
+        let position = transform.translation().xz();
+        let next = if carrying.amount > 0 {
+            let depots = depots.iter().filter(|(_, depot_team, _)| *depot_team == team);
+            nearest(position, depots.map(|(entity, _, transform)| (entity, transform.translation().xz())))
+                .map(|depot| WorkerTask::Deliver { depot })
+        } else {
+            let nodes = nodes.iter().filter(|(_, node, _)| node.amount > 0);
+            nearest(position, nodes.map(|(entity, _, transform)| (entity, transform.translation().xz())))
+                .map(|node| WorkerTask::Gather { node, progress: 0.0 })
+        };
+        let Some(next) = next else {
+            continue;
+        };
+        *task = next;
+        *goal = match next {
+            WorkerTask::Gather { node, .. } => Goal::Entity(node),
+            WorkerTask::Deliver { depot } => Goal::Entity(depot),
+            WorkerTask::Idle => Goal::None,
+        };
+    }
+}
+
+/// Gathers from nodes & delivers to depots the workers reached.
+fn work(
+    mut workers: Query<(
+        &mut WorkerTask,
+        &mut Goal,
+        &mut Carrying,
+        &Worker,
+        &GatherRate,
+        &Agent,
+        &Team,
+        &GlobalTransform,
+    )>,
+    mut nodes: Query<(&mut ResourceNode, &GlobalTransform)>,
+    depots: Query<&GlobalTransform, With<Depot>>,
+    mut stockpile: ResMut<Stockpile>,
+    time: Res<Time>,
+) {
+    let _span = info_span!("economy::work").entered();
+    for (mut task, mut goal, mut carrying, worker, rate, agent, team, transform) in &mut workers {
+        let position = transform.translation().xz();
+        let in_reach =
+            |target: &GlobalTransform| target.translation().xz().distance(position) <= agent.radius() + worker.reach;
+
+        match *task {
+            WorkerTask::Idle => {}
+            WorkerTask::Gather { node, progress } => {
+                let Ok((mut resource, node_transform)) = nodes.get_mut(node) else {
+                    *task = WorkerTask::Idle;
+                    continue;
+                };
+                if !in_reach(node_transform) {
+                    continue;
+                }
+                // Switching resources drops what's carried.
+                if carrying.kind != resource.kind {
+                    *carrying = Carrying { kind: resource.kind, amount: 0 };
+                }
+
+                let progress = progress + rate.value() * time.delta_seconds();
+                let gathered =
+                    (progress as u32).min(resource.amount).min(worker.capacity.saturating_sub(carrying.amount));
+                resource.amount -= gathered;
+                carrying.amount += gathered;
+
+                if carrying.amount >= worker.capacity || resource.amount == 0 {
+                    *task = WorkerTask::Idle;
+                    *goal = Goal::None;
+                } else {
+                    *task = WorkerTask::Gather { node, progress: progress.fract() };
+                }
+            }
+            WorkerTask::Deliver { depot } => {
+                let Ok(depot_transform) = depots.get(depot) else {
+                    *task = WorkerTask::Idle;
+                    continue;
+                };
+                if !in_reach(depot_transform) {
+                    continue;
+                }
+                stockpile.add(*team, carrying.kind, carrying.amount);
+                carrying.amount = 0;
+                *task = WorkerTask::Idle;
+                *goal = Goal::None;
+            }
+        }
+    }
+}
+
+fn deplete(mut commands: Commands, nodes: Query<(Entity, &ResourceNode), Changed<ResourceNode>>) {
+    for (entity, node) in &nodes {
+        if node.amount == 0 {
+            commands.entity(entity).insert(Despawn::Immediate);
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Copy, Debug)]
+pub struct ResourceNodeDef {
+    pub position: Vec2,
+    #[serde(default)]
+    pub kind: ResourceKind,
+    pub amount: u32,
+}
+
+/// Resource nodes & the depots & workers of the player's [`Team`] on a map.
+#[derive(Reflect, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct EconomyDef {
+    pub nodes: Vec<ResourceNodeDef>,
+    pub depots: Vec<Vec2>,
+    /// Workers spawned around each depot.
+    pub workers: u32,
+}
+
+#[cfg(feature = "presentation")]
+impl EconomyDef {
+    /// Spawns the nodes, depots & workers, returns all spawned entities.
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Vec<Entity> {
+        use crate::{
+            combat::CombatBundle,
+            navigation::{
+                agent::{Speed, TargetReachedCondition},
+                flow_field::CellIndex,
+            },
+            physics::layers,
+            prefab::PrefabCommandsExt,
+            save::Save,
+        };
+
+        let mut entities = Vec::new();
+
+        for (i, def) in self.nodes.iter().enumerate() {
+            entities.push(
+                commands
+                    .spawn_prefab("obstacle")
+                    .insert((
+                        Name::unit(format!("{:?} node {i}", def.kind)),
+                        PbrBundle {
+                            mesh: meshes.add(Sphere::new(1.0)),
+                            material: materials.add(def.kind.color()),
+                            transform: (def.position.x0y() + Vec3::Y).into_transform(),
+                            ..default()
+                        },
+                        Collider::from(Sphere::new(1.0)),
+                        layers::terrain(),
+                        ResourceNode { kind: def.kind, amount: def.amount },
+                        Save,
+                    ))
+                    .id(),
+            );
+        }
+
+        let agent = Agent::Small;
+        let worker_mesh = meshes.add(Cylinder { radius: agent.radius(), half_height: agent.height() / 2.0 });
+        let worker_material = materials.add(Color::CYAN);
+        for (i, position) in self.depots.iter().enumerate() {
+            entities.push(
+                commands
+                    .spawn_prefab("obstacle")
+                    .insert((
+                        Name::unit(format!("depot {i}")),
+                        PbrBundle {
+                            mesh: meshes.add(Cuboid::new(3.0, 2.0, 3.0)),
+                            material: materials.add(Color::ORANGE),
+                            transform: (position.x0y() + Vec3::Y).into_transform(),
+                            ..default()
+                        },
+                        Collider::cuboid(3.0, 2.0, 3.0),
+                        layers::terrain(),
+                        Depot,
+                        Team::PLAYER,
+                        Save,
+                    ))
+                    .id(),
+            );
+
+            for j in 0..self.workers {
+                let angle = j as f32 / self.workers as f32 * std::f32::consts::TAU;
+                let offset = Vec2::from_angle(angle) * 4.0;
+                entities.push(
+                    commands
+                        .spawn((
+                            Name::unit(format!("worker {i}-{j}")),
+                            PbrBundle {
+                                mesh: worker_mesh.clone(),
+                                material: worker_material.clone(),
+                                transform: ((*position + offset).x0y() + Vec3::Y * agent.height() / 2.0)
+                                    .into_transform(),
+                                ..default()
+                            },
+                            CharacterMotor::cylinder(agent.height(), agent.radius()),
+                            agent,
+                            Speed::base(80.0),
+                            CombatBundle::default(),
+                            Save,
+                            CellIndex::default(),
+                            TargetReachedCondition::Distance(1.0),
+                            Goal::None,
+                            (Worker::default(), WorkerTask::Idle, Carrying::default(), GatherRate::base(2.0)),
+                            Team::PLAYER,
+                        ))
+                        .id(),
+                );
+            }
+        }
+
+        entities
+    }
+}
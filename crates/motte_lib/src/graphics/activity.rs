@@ -0,0 +1,67 @@
+//! Throttles purely cosmetic work for units the player can't see, e.g. projecting [`BlobShadow`]s. Every [`Agent`]
+//! gets an [`Activity`] from whether it or one of its children was visible to any camera last frame, as computed for
+//! the render world. Cosmetic systems skip off-screen units on most frames by checking [`Throttle::update`], the
+//! frames are staggered by entity so the work is spread out instead of spiking every few frames.
+//!
+//! [`BlobShadow`]: super::blob_shadow::BlobShadow
+
+use bevy::{core::FrameCount, ecs::system::SystemParam, render::view::VisibilitySystems};
+
+use crate::{navigation::agent::Agent, prelude::*};
+
+/// Off-screen units are updated every this many frames.
+const OFF_SCREEN_INTERVAL: u32 = 8;
+
+pub struct ActivityPlugin;
+
+impl Plugin for ActivityPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Activity);
+        app.add_systems(Update, attach);
+        app.add_systems(PostUpdate, classify.after(VisibilitySystems::CheckVisibility));
+    }
+}
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum Activity {
+    #[default]
+    OnScreen,
+    OffScreen,
+}
+
+/// Whether cosmetic systems should update an entity this frame.
+#[derive(SystemParam)]
+pub struct Throttle<'w, 's> {
+    frame: Res<'w, FrameCount>,
+    activities: Query<'w, 's, &'static Activity>,
+}
+
+impl Throttle<'_, '_> {
+    /// Entities without an [`Activity`] are always updated.
+    pub fn update(&self, entity: Entity) -> bool {
+        match self.activities.get(entity) {
+            Ok(Activity::OffScreen) => (entity.index().wrapping_add(self.frame.0)) % OFF_SCREEN_INTERVAL == 0,
+            _ => true,
+        }
+    }
+}
+
+fn attach(mut commands: Commands, agents: Query<Entity, Added<Agent>>) {
+    for entity in &agents {
+        commands.entity(entity).insert(Activity::default());
+    }
+}
+
+fn classify(
+    mut activities: Query<(&mut Activity, Option<&ViewVisibility>, Option<&Children>)>,
+    visibilities: Query<&ViewVisibility>,
+) {
+    for (mut activity, visibility, children) in &mut activities {
+        let visible = visibility.is_some_and(|visibility| visibility.get())
+            || children.is_some_and(|children| {
+                children.iter().any(|&child| visibilities.get(child).is_ok_and(|visibility| visibility.get()))
+            });
+        activity.set_if_neq(if visible { Activity::OnScreen } else { Activity::OffScreen });
+    }
+}
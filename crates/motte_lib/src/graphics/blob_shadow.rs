@@ -0,0 +1,161 @@
+//! Cheap projected shadows under units, the directional light's shadow map is expensive & noisy at pixel-art
+//! resolutions. Every [`Agent`] gets a [`BlobShadow`] scaled by its radius, which shrinks & fades out as the agent is
+//! lifted off the ground. [`UnitShadows::ShadowMap`] switches units back to shadow-mapped shadows.
+
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    transform::TransformSystem,
+};
+
+use crate::{
+    graphics::{activity::Throttle, materials::blob_shadow::BlobShadowMaterial},
+    movement::motor::Airborne,
+    navigation::agent::Agent,
+    physics::{layers::CollisionLayer, queries::PhysicsQueries},
+    prelude::*,
+    settings::{Settings, UnitShadows},
+};
+
+/// Opacity levels a [`BlobShadow`] fades through, each is its own material so shadows at the same level batch.
+const FADE_STEPS: usize = 4;
+
+/// Height above the ground the shadow is drawn at, keeps it from z-fighting with the floor.
+const LIFT: f32 = 0.02;
+
+pub struct BlobShadowPlugin;
+
+impl Plugin for BlobShadowPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(BlobShadow, UnitShadowHidden);
+        app.init_resource::<BlobShadowAssets>();
+        app.add_systems(Update, (attach, casters));
+        app.add_systems(PostUpdate, project.after(PhysicsSet::Sync).before(TransformSystem::TransformPropagate));
+    }
+}
+
+/// Shadow under the [`Agent`] it's a child of, spawned automatically.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct BlobShadow {
+    /// Radius of the shadow relative to the agent's.
+    pub scale: f32,
+    /// Height above the ground at which the shadow has faded out.
+    pub max_height: f32,
+}
+
+impl Default for BlobShadow {
+    fn default() -> Self {
+        Self { scale: 1.2, max_height: 6.0 }
+    }
+}
+
+#[derive(Resource)]
+struct BlobShadowAssets {
+    mesh: Handle<Mesh>,
+    /// From opaque to most faded.
+    materials: [Handle<BlobShadowMaterial>; FADE_STEPS],
+}
+
+impl FromWorld for BlobShadowAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Plane3d::default().mesh().size(2.0, 2.0));
+        let mut materials = world.resource_mut::<Assets<BlobShadowMaterial>>();
+        let base = BlobShadowMaterial::default();
+        let materials = std::array::from_fn(|step| {
+            let opacity = 1.0 - step as f32 / FADE_STEPS as f32;
+            materials.add(BlobShadowMaterial { color: base.color.with_a(base.color.a() * opacity) })
+        });
+        Self { mesh, materials }
+    }
+}
+
+fn attach(mut commands: Commands, agents: Query<Entity, Added<Agent>>, assets: Res<BlobShadowAssets>) {
+    for entity in &agents {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                Name::new("blob shadow"),
+                MaterialMeshBundle { mesh: assets.mesh.clone(), material: assets.materials[0].clone(), ..default() },
+                BlobShadow::default(),
+                NotShadowCaster,
+                NotShadowReceiver,
+            ));
+        });
+    }
+}
+
+/// Places the shadows on the ground below their agent, only airborne agents are raycast. Shadows of off-screen agents
+/// are throttled.
+fn project(
+    settings: Res<Settings>,
+    assets: Res<BlobShadowAssets>,
+    agents: Query<(&Agent, &GlobalTransform, Has<Airborne>)>,
+    mut shadows: Query<(&BlobShadow, &Parent, &mut Transform, &mut Handle<BlobShadowMaterial>, &mut Visibility)>,
+    queries: PhysicsQueries,
+    throttle: Throttle,
+) {
+    let enabled = settings.graphics.unit_shadows == UnitShadows::Blob;
+
+    for (shadow, parent, mut transform, mut material, mut visibility) in &mut shadows {
+        if !throttle.update(parent.get()) {
+            continue;
+        }
+        let Ok((agent, agent_transform, airborne)) = agents.get(parent.get()) else {
+            continue;
+        };
+        let half_height = agent.height() / 2.0;
+        let center = agent_transform.translation();
+        let height = if airborne {
+            queries
+                .raycast(center, Vec3::NEG_Y, half_height + shadow.max_height, CollisionLayer::Terrain)
+                .map(|hit| (hit.distance - half_height).max(0.0))
+        } else {
+            Some(0.0)
+        };
+
+        let Some(height) = height.filter(|_| enabled) else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+
+        let fade = (height / shadow.max_height).clamp(0.0, 1.0);
+        let step = ((fade * FADE_STEPS as f32) as usize).min(FADE_STEPS - 1);
+        material.set_if_neq(assets.materials[step].clone());
+
+        // Flat on the ground regardless of how the agent is rotated.
+        let radius = agent.radius() * shadow.scale * (1.0 - fade * 0.5);
+        let ground = center - Vec3::Y * (half_height + height - LIFT);
+        let world = Transform::from_translation(ground).with_scale(Vec3::splat(radius));
+        transform.set_if_neq(GlobalTransform::from(world).reparented_to(agent_transform));
+    }
+}
+
+/// Shadow-mapped shadow of an agent's mesh turned off by the [`UnitShadows`] setting, meshes that don't cast shadows
+/// on their own keep them off when it's turned back on.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct UnitShadowHidden;
+
+/// Toggles shadow-mapped shadows of the meshes of agents by the [`UnitShadows`] setting, including meshes of scenes
+/// spawned under them later.
+fn casters(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    meshes: Query<(Entity, Has<NotShadowCaster>, Has<UnitShadowHidden>), (With<Handle<Mesh>>, Without<BlobShadow>)>,
+    added: Query<(Entity, Has<NotShadowCaster>, Has<UnitShadowHidden>), (Added<Handle<Mesh>>, Without<BlobShadow>)>,
+    agents: Query<(), With<Agent>>,
+    parents: Query<&Parent>,
+) {
+    let entities: Vec<_> = if settings.is_changed() { meshes.iter().collect() } else { added.iter().collect() };
+    let cast = settings.graphics.unit_shadows == UnitShadows::ShadowMap;
+    for (entity, not_caster, hidden) in entities {
+        if !agents.contains(entity) && !parents.iter_ancestors(entity).any(|ancestor| agents.contains(ancestor)) {
+            continue;
+        }
+        if cast && hidden {
+            commands.entity(entity).remove::<(NotShadowCaster, UnitShadowHidden)>();
+        } else if !cast && !not_caster {
+            commands.entity(entity).insert((NotShadowCaster, UnitShadowHidden));
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Grass, pebbles & other small props scattered over the ground plane by a density mask. All instances of a
+//! [`DetailLayer`] share a mesh & [`DetailMaterial`] so they're drawn as a single instanced batch. They sway in the
+//! [`Wind`] & fade out with the distance to the point the [`MainCamera`] looks at, without touching the instances on
+//! the CPU after they're spawned. The [`DetailBudget`] caps how many are spawned & thins them out as units spawn.
+
+use bevy::{
+    pbr::NotShadowCaster,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+    transform::TransformSystem,
+};
+use rand::rngs::StdRng;
+use serde::Deserialize;
+
+use crate::{
+    graphics::materials::detail::{DetailExtension, DetailMaterial},
+    navigation::agent::Agent,
+    player::camera::MainCamera,
+    prelude::*,
+};
+
+pub struct DetailPlugin;
+
+impl Plugin for DetailPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(res: Wind, res: DetailBudget, Details, DetailScatter, DetailLayer, DetailKind);
+        app.add_systems(PostUpdate, sync.after(TransformSystem::TransformPropagate));
+    }
+}
+
+/// Wind blowing over the map, bends the [`DetailLayer`]s.
+#[derive(Resource, Reflect, Clone, Copy, Debug)]
+#[reflect(Resource)]
+pub struct Wind {
+    pub direction: Vec2,
+    /// Units the tip of a unit tall instance bends at the peak of a gust.
+    pub strength: f32,
+    /// Gusts per second, in radians.
+    pub frequency: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self { direction: Vec2::new(1.0, 0.3).normalize(), strength: 0.25, frequency: 1.5 }
+    }
+}
+
+/// Most detail instances spawned, every unit on the map takes `per_unit` of it so details don't compete with large
+/// crowds. Never drops below a quarter of `instances`.
+#[derive(Resource, Reflect, Clone, Copy, Debug)]
+#[reflect(Resource)]
+pub struct DetailBudget {
+    pub instances: u32,
+    pub per_unit: u32,
+}
+
+impl Default for DetailBudget {
+    fn default() -> Self {
+        Self { instances: 8000, per_unit: 4 }
+    }
+}
+
+impl DetailBudget {
+    /// Instances drawn with `units` on the map.
+    pub fn available(&self, units: usize) -> u32 {
+        self.instances.saturating_sub(units as u32 * self.per_unit).max(self.instances / 4)
+    }
+}
+
+/// Root of the instances spawned by a [`DetailScatter`].
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct Details {
+    /// Instances across all layers.
+    pub instances: u32,
+    /// Material of each layer.
+    pub materials: Vec<Handle<DetailMaterial>>,
+}
+
+/// Kind of mesh instanced by a [`DetailLayer`].
+#[derive(Reflect, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DetailKind {
+    /// Tuft of three crossed blades, one unit tall.
+    #[default]
+    Grass,
+    /// Flattened rock, one unit wide.
+    Pebble,
+}
+
+impl DetailKind {
+    fn mesh(self) -> Mesh {
+        match self {
+            DetailKind::Grass => grass_tuft(),
+            DetailKind::Pebble => Sphere::new(0.5).mesh().ico(1).expect("subdivisions should be in range"),
+        }
+    }
+
+    fn scale(self, scale: f32) -> Vec3 {
+        match self {
+            DetailKind::Grass => Vec3::splat(scale),
+            DetailKind::Pebble => Vec3::new(scale, scale * 0.5, scale),
+        }
+    }
+
+    fn sway(self) -> f32 {
+        match self {
+            DetailKind::Grass => 1.0,
+            DetailKind::Pebble => 0.0,
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct DetailLayer {
+    pub kind: DetailKind,
+    /// Instances per square unit where the density mask is white.
+    pub density: f32,
+    pub color: Color,
+    /// Range of the random uniform scale.
+    pub scale: (f32, f32),
+}
+
+impl Default for DetailLayer {
+    fn default() -> Self {
+        Self { kind: DetailKind::Grass, density: 0.5, color: Color::rgb(0.35, 0.55, 0.2), scale: (0.6, 1.0) }
+    }
+}
+
+/// Details scattered over the ground plane of a map. The red channel of the density mask scales the density of all
+/// layers, it spans the whole plane. Without a mask the layers are scattered everywhere.
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DetailScatter {
+    pub density_mask: Option<String>,
+    pub layers: Vec<DetailLayer>,
+    /// Distances from the camera focus where instances start & finish fading out.
+    pub fade: Vec2,
+    /// Seed of the placement, the same map always looks the same.
+    pub seed: u64,
+}
+
+impl Default for DetailScatter {
+    fn default() -> Self {
+        Self { density_mask: None, layers: vec![DetailLayer::default()], fade: Vec2::new(40.0, 60.0), seed: 0 }
+    }
+}
+
+impl DetailScatter {
+    /// Spawns the layers over a plane of `size` centered on the origin, returns the [`Details`] root.
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<DetailMaterial>,
+        mask: Option<&Image>,
+        size: Vec2,
+        budget: &DetailBudget,
+    ) -> Entity {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let area = size.x * size.y;
+
+        // Candidates are thinned uniformly so the layers keep their ratios within the budget.
+        let candidates: f32 = self.layers.iter().map(|layer| layer.density * area).sum();
+        let thinning = (budget.instances as f32 / candidates.max(1.0)).min(1.0);
+
+        let mut instances = 0;
+        let mut layer_materials = Vec::with_capacity(self.layers.len());
+        let root = commands.spawn((Name::unit("details"), SpatialBundle::default())).id();
+
+        for layer in &self.layers {
+            let mesh = meshes.add(layer.kind.mesh());
+            let material = materials.add(DetailMaterial {
+                base: StandardMaterial {
+                    base_color: layer.color,
+                    perceptual_roughness: 1.0,
+                    double_sided: true,
+                    cull_mode: None,
+                    ..default()
+                },
+                extension: DetailExtension { fade: self.fade, sway: layer.kind.sway(), ..default() },
+            });
+            layer_materials.push(material.clone());
+
+            let count = (layer.density * area) as u32;
+            let bundles: Vec<_> = (0..count)
+                .filter_map(|_| {
+                    let position = Vec2::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5)) * size;
+                    let weight = mask.map_or(1.0, |mask| sample(mask, position / size + 0.5)) * thinning;
+                    if rng.gen::<f32>() >= weight {
+                        return None;
+                    }
+                    let (min, max) = layer.scale;
+                    let scale = if max > min { rng.gen_range(min..max) } else { min };
+                    let transform = Transform {
+                        translation: position.x0y(),
+                        rotation: Quat::from_rotation_y(rng.gen_range(0.0..std::f32::consts::TAU)),
+                        scale: layer.kind.scale(scale),
+                    };
+                    Some((
+                        MaterialMeshBundle { mesh: mesh.clone(), material: material.clone(), transform, ..default() },
+                        NotShadowCaster,
+                    ))
+                })
+                .collect();
+
+            instances += bundles.len() as u32;
+            commands.entity(root).with_children(|parent| {
+                for bundle in bundles {
+                    parent.spawn(bundle);
+                }
+            });
+        }
+
+        commands.entity(root).insert(Details { instances, materials: layer_materials });
+        root
+    }
+}
+
+/// Red channel of `image` at `uv`, assumes one byte per channel.
+fn sample(image: &Image, uv: Vec2) -> f32 {
+    let size = image.size();
+    if size.x == 0 || size.y == 0 {
+        return 0.0;
+    }
+    let texel = (uv.clamp(Vec2::ZERO, Vec2::ONE) * (size - 1).as_vec2()).round().as_uvec2();
+    let stride = image.data.len() / (size.x * size.y) as usize;
+    let index = (texel.y * size.x + texel.x) as usize * stride;
+    image.data.get(index).map_or(0.0, |&red| red as f32 / 255.0)
+}
+
+/// Three crossed blades, tapering from the ground to their tips at one unit.
+fn grass_tuft() -> Mesh {
+    const BLADES: usize = 3;
+    const WIDTH: f32 = 0.12;
+
+    let mut positions = Vec::with_capacity(BLADES * 3);
+    let mut uvs = Vec::with_capacity(BLADES * 3);
+    for i in 0..BLADES {
+        let angle = i as f32 * std::f32::consts::PI / BLADES as f32;
+        let side = Vec3::new(angle.cos(), 0.0, angle.sin()) * WIDTH;
+        let lean = Vec3::new(-angle.sin(), 0.0, angle.cos()) * 0.15;
+        positions.extend([(-side).to_array(), side.to_array(), (Vec3::Y + lean).to_array()]);
+        uvs.extend([[0.0, 1.0], [1.0, 1.0], [0.5, 0.0]]);
+    }
+
+    // Pointing up lights the tufts like the ground they stand on.
+    let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+    let indices = (0..positions.len() as u32).collect();
+
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Pushes the [`Wind`], camera focus & remaining budget to the [`DetailMaterial`]s.
+fn sync(
+    wind: Res<Wind>,
+    budget: Res<DetailBudget>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    units: Query<(), With<Agent>>,
+    details: Query<&Details>,
+    mut materials: ResMut<Assets<DetailMaterial>>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let (origin, direction) = math::world_space_ray_from_ndc(Vec2::ZERO, camera, camera_transform);
+    let focus = math::plane_intersection(origin, direction, Vec3::ZERO, Vec3::Y).xz();
+    let wind = wind.direction.normalize_or_zero().extend(wind.strength).extend(wind.frequency);
+
+    let instances: u32 = details.iter().map(|details| details.instances).sum();
+    let density = (budget.available(units.iter().len()) as f32 / instances.max(1) as f32).min(1.0);
+
+    for handle in details.iter().flat_map(|details| &details.materials) {
+        // Only touch changed materials, every change re-uploads the bind group.
+        let Some(material) = materials.get(handle) else {
+            continue;
+        };
+        let extension = &material.extension;
+        if extension.focus == focus && extension.wind == wind && extension.density == density {
+            continue;
+        }
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+        material.extension.focus = focus;
+        material.extension.wind = wind;
+        material.extension.density = density;
+    }
+}
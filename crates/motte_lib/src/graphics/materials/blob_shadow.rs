@@ -0,0 +1,27 @@
+use bevy::render::render_resource::*;
+
+use crate::prelude::*;
+
+/// Disc darkening the ground under a unit, spans the uv square of its mesh. The alpha of `color` is the opacity at
+/// the center of the disc.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct BlobShadowMaterial {
+    #[uniform(0)]
+    pub color: Color,
+}
+
+impl Default for BlobShadowMaterial {
+    fn default() -> Self {
+        Self { color: Color::rgba(0.0, 0.0, 0.0, 0.45) }
+    }
+}
+
+impl Material for BlobShadowMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/blob_shadow.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+}
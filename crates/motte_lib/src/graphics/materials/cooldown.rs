@@ -0,0 +1,25 @@
+use bevy::render::render_resource::*;
+
+use crate::prelude::*;
+
+/// Radial sweep darkening the part of a UI node that's still on cooldown, clockwise from the top.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct CooldownMaterial {
+    #[uniform(0)]
+    pub color: Color,
+    /// Fraction of the cooldown left, nothing is drawn at 0.
+    #[uniform(0)]
+    pub fraction: f32,
+}
+
+impl Default for CooldownMaterial {
+    fn default() -> Self {
+        Self { color: Color::rgba(0.0, 0.0, 0.0, 0.6), fraction: 0.0 }
+    }
+}
+
+impl UiMaterial for CooldownMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/cooldown.wgsl".into()
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension},
+    render::render_resource::*,
+};
+
+use crate::prelude::*;
+
+pub type DetailMaterial = ExtendedMaterial<StandardMaterial, DetailExtension>;
+
+/// Small props scattered in large numbers, e.g. grass & pebbles. Instances sway in the `wind`, scaled by the height of
+/// the vertex & `sway`, & shrink into the ground between `fade.x` & `fade.y` units from `focus`. Only a random
+/// `density` fraction of the instances is drawn, the rest collapse to a point. Has no prepass & casts no shadows, the
+/// vertex displacement would differ between the passes.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct DetailExtension {
+    /// Direction in xy, strength in z & frequency in w.
+    #[uniform(100)]
+    pub wind: Vec4,
+    /// Point on the ground the fade is measured from.
+    #[uniform(100)]
+    pub focus: Vec2,
+    #[uniform(100)]
+    pub fade: Vec2,
+    #[uniform(100)]
+    pub sway: f32,
+    #[uniform(100)]
+    pub density: f32,
+}
+
+impl Default for DetailExtension {
+    fn default() -> Self {
+        Self { wind: Vec4::ZERO, focus: Vec2::ZERO, fade: Vec2::new(40.0, 60.0), sway: 1.0, density: 1.0 }
+    }
+}
+
+impl MaterialExtension for DetailExtension {
+    fn vertex_shader() -> ShaderRef {
+        "shaders/detail.wgsl".into()
+    }
+}
@@ -1,9 +1,21 @@
 use bevy::asset::load_internal_asset;
 
-use self::cel::{CelExtension, CelMaterial};
+use self::{
+    blob_shadow::BlobShadowMaterial,
+    cel::{CelExtension, CelMaterial},
+    cooldown::CooldownMaterial,
+    detail::DetailMaterial,
+    terrain::TerrainMaterial,
+    water::{WaterMaterial, WaterPlane},
+};
 use crate::prelude::*;
 
+pub mod blob_shadow;
 pub mod cel;
+pub mod cooldown;
+pub mod detail;
+pub mod terrain;
+pub mod water;
 
 // TODO: move into a "shader" plugin
 const COLORS_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(5569923404675166368);
@@ -18,7 +30,23 @@ impl Plugin for MaterialsPlugin {
         load_internal_asset!(app, COLORS_SHADER_HANDLE, "../../../../../assets/shaders/colors.wgsl", Shader::from_wgsl);
         load_internal_asset!(app, EDGES_SHADER_HANDLE, "../../../../../assets/shaders/edges.wgsl", Shader::from_wgsl);
 
-        app.add_plugins(MaterialPlugin::<CelMaterial>::default()).register_asset_reflect::<CelMaterial>();
+        app.add_plugins((
+            MaterialPlugin::<CelMaterial>::default(),
+            MaterialPlugin::<WaterMaterial>::default(),
+            MaterialPlugin::<TerrainMaterial>::default(),
+            MaterialPlugin::<DetailMaterial> { prepass_enabled: false, shadows_enabled: false, ..default() },
+            MaterialPlugin::<BlobShadowMaterial>::default(),
+            UiMaterialPlugin::<CooldownMaterial>::default(),
+        ));
+        app_register_types!(
+            asset: CelMaterial,
+            asset: WaterMaterial,
+            asset: TerrainMaterial,
+            asset: DetailMaterial,
+            asset: BlobShadowMaterial,
+            asset: CooldownMaterial,
+            WaterPlane
+        );
 
         app.add_systems(PostUpdate, replace_shaders);
     }
@@ -0,0 +1,84 @@
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension},
+    render::{
+        render_resource::*,
+        texture::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
+    },
+};
+
+use crate::prelude::*;
+
+pub type TerrainMaterial = ExtendedMaterial<StandardMaterial, TerrainExtension>;
+
+/// Most layers blended by a [`TerrainExtension`].
+pub const MAX_TERRAIN_LAYERS: usize = 4;
+
+/// Ground with up to [`MAX_TERRAIN_LAYERS`] tiling albedo layers, weighted by the rgba channels of `splat_map`. Layers
+/// are projected from above in world space, surfaces steeper than `slope` blend projections along all axes instead
+/// (triplanar) so textures don't stretch on cliffs & ramps. Without a splat map all layers are weighted equally.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct TerrainExtension {
+    /// World units covered by one repeat of each layer.
+    #[uniform(100)]
+    pub tiling: Vec4,
+    /// Normal y below which layers are projected triplanar.
+    #[uniform(100)]
+    pub slope: f32,
+    #[texture(101)]
+    #[sampler(102)]
+    pub splat_map: Option<Handle<Image>>,
+    #[texture(103)]
+    #[sampler(104)]
+    pub layer_0: Option<Handle<Image>>,
+    #[texture(105)]
+    #[sampler(106)]
+    pub layer_1: Option<Handle<Image>>,
+    #[texture(107)]
+    #[sampler(108)]
+    pub layer_2: Option<Handle<Image>>,
+    #[texture(109)]
+    #[sampler(110)]
+    pub layer_3: Option<Handle<Image>>,
+}
+
+impl TerrainExtension {
+    /// Creates a terrain from at most [`MAX_TERRAIN_LAYERS`] layers, missing layers repeat the first one.
+    pub fn new(layers: &[Handle<Image>], splat_map: Option<Handle<Image>>, tiling: f32) -> Self {
+        debug_assert!(!layers.is_empty() && layers.len() <= MAX_TERRAIN_LAYERS, "expected 1 to 4 terrain layers");
+        let layer = |i: usize| layers.get(i).or(layers.first()).cloned();
+        Self {
+            tiling: Vec4::splat(tiling),
+            slope: 0.7,
+            splat_map,
+            layer_0: layer(0),
+            layer_1: layer(1),
+            layer_2: layer(2),
+            layer_3: layer(3),
+        }
+    }
+}
+
+impl MaterialExtension for TerrainExtension {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/terrain.wgsl".into()
+    }
+}
+
+/// Sets the sampler of a terrain layer to repeat in every direction.
+pub fn repeat(image: &mut Image) {
+    match &mut image.sampler {
+        ImageSampler::Default => {
+            image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+                address_mode_u: ImageAddressMode::Repeat,
+                address_mode_v: ImageAddressMode::Repeat,
+                address_mode_w: ImageAddressMode::Repeat,
+                ..default()
+            });
+        }
+        ImageSampler::Descriptor(sampler_descriptor) => {
+            sampler_descriptor.address_mode_u = ImageAddressMode::Repeat;
+            sampler_descriptor.address_mode_v = ImageAddressMode::Repeat;
+            sampler_descriptor.address_mode_w = ImageAddressMode::Repeat;
+        }
+    }
+}
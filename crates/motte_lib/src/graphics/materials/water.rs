@@ -0,0 +1,142 @@
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension, NotShadowCaster},
+    render::{render_asset::RenderAssets, render_resource::*},
+};
+use serde::Deserialize;
+
+use crate::{
+    movement::motor::WaterVolume,
+    navigation::flow_field::fields::surface::{Surface, SurfaceRegion},
+    physics::triggers::TriggerVolume,
+    prelude::*,
+};
+
+pub type WaterMaterial = ExtendedMaterial<StandardMaterial, WaterExtension>;
+
+/// Stylized water with procedural scrolling normals, a color fading from `shallow_color` to `deep_color` over
+/// `depth_fade` units of water & foam where the water is shallower than `foam_width`. Lit like the
+/// [`CelExtension`](super::cel::CelExtension). The depth is read from the depth prepass, cameras without one see
+/// `deep_color` & no foam.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+#[uniform(100, WaterUniform)]
+pub struct WaterExtension {
+    pub shallow_color: Color,
+    pub deep_color: Color,
+    pub foam_color: Color,
+    /// Scroll direction & speed of the normals in world units per second.
+    pub scroll: Vec2,
+    /// Waves per world unit.
+    pub wave_scale: f32,
+    pub wave_strength: f32,
+    pub depth_fade: f32,
+    pub foam_width: f32,
+    pub lit: f32,
+    pub shadow: f32,
+    pub cut_off: f32,
+}
+
+impl Default for WaterExtension {
+    fn default() -> Self {
+        Self {
+            shallow_color: Color::rgba(0.3, 0.75, 0.8, 0.6),
+            deep_color: Color::rgba(0.05, 0.2, 0.45, 0.9),
+            foam_color: Color::rgba(0.9, 0.95, 1.0, 1.0),
+            scroll: Vec2::new(0.4, 0.25),
+            wave_scale: 0.6,
+            wave_strength: 0.15,
+            depth_fade: 2.0,
+            foam_width: 0.2,
+            lit: 1.0,
+            shadow: 0.5,
+            cut_off: 0.5,
+        }
+    }
+}
+
+impl MaterialExtension for WaterExtension {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/water.wgsl".into()
+    }
+}
+
+#[derive(Clone, Default, ShaderType)]
+pub struct WaterUniform {
+    shallow_color: Vec4,
+    deep_color: Vec4,
+    foam_color: Vec4,
+    scroll: Vec2,
+    wave_scale: f32,
+    wave_strength: f32,
+    depth_fade: f32,
+    foam_width: f32,
+    lit: f32,
+    shadow: f32,
+    cut_off: f32,
+}
+
+impl AsBindGroupShaderType<WaterUniform> for WaterExtension {
+    fn as_bind_group_shader_type(&self, _: &RenderAssets<Image>) -> WaterUniform {
+        WaterUniform {
+            shallow_color: self.shallow_color.as_linear_rgba_f32().into(),
+            deep_color: self.deep_color.as_linear_rgba_f32().into(),
+            foam_color: self.foam_color.as_linear_rgba_f32().into(),
+            scroll: self.scroll,
+            wave_scale: self.wave_scale,
+            wave_strength: self.wave_strength,
+            depth_fade: self.depth_fade.max(f32::EPSILON),
+            foam_width: self.foam_width,
+            lit: self.lit,
+            shadow: self.shadow,
+            cut_off: self.cut_off,
+        }
+    }
+}
+
+/// Rectangular body of water on the ground, e.g. a river segment or a lake. Spawned with [`WaterPlane::spawn`] as a
+/// [`WaterMaterial`] plane inside a [`WaterVolume`] reaching `depth` units below & above it. The cells below it are
+/// [`Surface::Water`].
+#[derive(Reflect, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct WaterPlane {
+    pub position: Vec2,
+    pub size: Vec2,
+    pub depth: f32,
+}
+
+impl Default for WaterPlane {
+    fn default() -> Self {
+        Self { position: Vec2::ZERO, size: Vec2::splat(10.0), depth: 1.0 }
+    }
+}
+
+impl WaterPlane {
+    /// Height of the surface above the ground, keeps it from z-fighting with the floor.
+    const LEVEL: f32 = 0.05;
+
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<WaterMaterial>,
+    ) -> Entity {
+        commands
+            .spawn((
+                Name::unit("water"),
+                MaterialMeshBundle {
+                    mesh: meshes.add(Plane3d::default().mesh().size(self.size.x, self.size.y)),
+                    material: materials.add(WaterMaterial {
+                        base: StandardMaterial { alpha_mode: AlphaMode::Blend, perceptual_roughness: 0.2, ..default() },
+                        extension: WaterExtension::default(),
+                    }),
+                    transform: (self.position.x0y() + Vec3::Y * Self::LEVEL).into_transform(),
+                    ..default()
+                },
+                Collider::cuboid(self.size.x, self.depth * 2.0, self.size.y),
+                TriggerVolume::default(),
+                WaterVolume::default(),
+                SurfaceRegion(Surface::Water),
+                NotShadowCaster,
+            ))
+            .id()
+    }
+}
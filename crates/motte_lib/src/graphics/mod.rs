@@ -1,11 +1,22 @@
 use bevy::prelude::{App, Plugin};
 
+pub mod activity;
+pub mod blob_shadow;
+pub mod detail;
 pub mod materials;
 pub mod pixelate;
+pub mod tween;
 
 pub struct GraphicsPlugin;
 impl Plugin for GraphicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((pixelate::PixelatePlugin, materials::MaterialsPlugin));
+        app.add_plugins((
+            pixelate::PixelatePlugin,
+            materials::MaterialsPlugin,
+            detail::DetailPlugin,
+            activity::ActivityPlugin,
+            blob_shadow::BlobShadowPlugin,
+            tween::TweenPlugin,
+        ));
     }
 }
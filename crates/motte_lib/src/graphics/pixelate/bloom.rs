@@ -0,0 +1,69 @@
+use bevy::{
+    core_pipeline::bloom::{BloomPrefilterSettings, BloomSettings},
+    prelude::*,
+};
+
+use super::camera::{Pixelate, RenderResolution};
+
+/// Enables HDR & bloom on a [`Pixelate`] camera, e.g. for spell VFX & emissive materials. Bloom is part of the
+/// camera's own render graph, so it's resolved in the low-res render texture before the blit & bloomed texels are
+/// upscaled like any other.
+///
+/// The spread is clamped to roughly `max_radius` texels of the render texture, which keeps blooms chunky instead of
+/// smearing them across the screen at low resolutions.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct PixelateBloom {
+    pub intensity: f32,
+    /// Brightness above which texels bloom, values above `1.0` only bloom emissive & HDR colors.
+    pub threshold: f32,
+    /// Largest spread of a bloom in texels of the render texture.
+    pub max_radius: u32,
+}
+
+impl Default for PixelateBloom {
+    fn default() -> Self {
+        Self { intensity: 0.15, threshold: 1.0, max_radius: 8 }
+    }
+}
+
+impl PixelateBloom {
+    /// [`BloomSettings`] for a render texture of `resolution`.
+    fn settings(&self, resolution: UVec2) -> BloomSettings {
+        // The bloom mip chain spans the smallest dimension of the view, the high pass frequency weights how far
+        // into the chain blooms spread.
+        let spread = 2.0 * self.max_radius as f32 / resolution.min_element().max(1) as f32;
+        BloomSettings {
+            intensity: self.intensity,
+            high_pass_frequency: spread.clamp(0.0, 1.0),
+            prefilter_settings: BloomPrefilterSettings { threshold: self.threshold, threshold_softness: 0.2 },
+            ..BloomSettings::NATURAL
+        }
+    }
+}
+
+/// Keeps the [`BloomSettings`] & HDR of [`Pixelate`] cameras in sync with their [`PixelateBloom`].
+pub(super) fn bloom(
+    mut commands: Commands,
+    mut cameras: Query<
+        (Entity, &mut Camera, &PixelateBloom, &RenderResolution),
+        (With<Pixelate>, Or<(Changed<PixelateBloom>, Changed<RenderResolution>)>),
+    >,
+    mut removed: RemovedComponents<PixelateBloom>,
+    mut disabled: Query<&mut Camera, (With<Pixelate>, Without<PixelateBloom>)>,
+) {
+    for (entity, mut camera, bloom, resolution) in &mut cameras {
+        if !camera.hdr {
+            camera.hdr = true;
+        }
+        commands.entity(entity).insert(bloom.settings(resolution.value()));
+    }
+
+    for entity in removed.read() {
+        let Ok(mut camera) = disabled.get_mut(entity) else {
+            continue;
+        };
+        camera.hdr = false;
+        commands.entity(entity).remove::<BloomSettings>();
+    }
+}
@@ -7,7 +7,7 @@ use bevy::{
         render_resource::{Extent3d, ShaderType},
         texture::ImageSampler,
     },
-    window::{PrimaryWindow, WindowResized},
+    window::{PrimaryWindow, WindowResized, WindowScaleFactorChanged},
 };
 
 use super::{constants, snap::Snap};
@@ -200,12 +200,44 @@ impl RenderResolution {
     }
 }
 
-/// If added to a [`bevy::prelude::Camera2d`] & it's value is a valid entity with a [`Pixelate`]
-/// component, then the render texture from that entity will be blitted to render target.
+/// If added to a [`bevy::prelude::Camera2d`] & `target` is a valid entity with a [`Pixelate`]
+/// component, then the render texture from that entity will be blitted to render target using `filter`.
 #[derive(Component, Reflect, Clone, Copy, Debug, Deref, DerefMut, Default, ExtractComponent)]
 #[extract_component_filter((With<Camera2d>, With<Camera>))]
 #[reflect(Component)]
-pub struct Blitter(pub Option<Entity>);
+pub struct Blitter {
+    #[deref]
+    pub target: Option<Entity>,
+    pub filter: UpscaleFilter,
+}
+
+impl Blitter {
+    pub fn new(target: impl Into<Option<Entity>>) -> Self {
+        Self { target: target.into(), filter: UpscaleFilter::default() }
+    }
+
+    pub fn with_filter(mut self, filter: UpscaleFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+/// Filter used when upscaling the render texture to the screen. Backends that can't filter the render texture
+/// always point sample it.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UpscaleFilter {
+    /// Point sampled, the pixel grid stays crisp but pixels are unevenly sized at non-integer scale factors.
+    Nearest,
+    /// Bilinear, blurs the pixel grid.
+    Linear,
+    /// Point sampled within texels & blended over one screen pixel at texel edges, i.e. pixel-art anti-aliasing.
+    #[default]
+    SharpBilinear,
+}
+
+impl UpscaleFilter {
+    pub const ALL: [Self; 3] = [Self::Nearest, Self::Linear, Self::SharpBilinear];
+}
 
 /// Scale bias applied when blitting the texture to the screen camera for smooth sub-pixel movement.
 /// This is derived from the [`SnapOffset`] generated when snapping the [`Pixelate`] camera.
@@ -301,6 +333,7 @@ pub(super) fn render_texture(
     )>,
     windows: Query<(Entity, &Window), With<PrimaryWindow>>,
     mut window_resized_events: EventReader<WindowResized>,
+    mut scale_factor_events: EventReader<WindowScaleFactorChanged>,
     mut images: ResMut<Assets<Image>>,
 ) {
     let Ok((window_entity, window)) = windows.get_single() else {
@@ -308,7 +341,10 @@ pub(super) fn render_texture(
         return;
     };
 
-    let window_changed = window_resized_events.read().any(|e| e.window == window_entity);
+    // Moving the window to a monitor with another scale factor changes its physical size without a resize.
+    let window_changed = window_resized_events.read().filter(|e| e.window == window_entity).count()
+        + scale_factor_events.read().filter(|e| e.window == window_entity).count()
+        > 0;
 
     let window_resolution = UVec2::new(window.physical_width(), window.physical_height());
 
@@ -358,14 +394,14 @@ pub(super) fn render_texture(
         } else {
             use bevy::render::{
                 camera::RenderTarget,
-                render_resource::{TextureDescriptor, TextureDimension, TextureFormat, TextureUsages},
+                render_resource::{TextureDescriptor, TextureDimension, TextureUsages},
             };
             let mut image = Image {
                 texture_descriptor: TextureDescriptor {
                     label: None,
                     size,
                     dimension: TextureDimension::D2,
-                    format: TextureFormat::Rgba8UnormSrgb,
+                    format: constants::RENDER_TEXTURE_FORMAT,
                     mip_level_count: 1,
                     sample_count: 1,
                     usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT,
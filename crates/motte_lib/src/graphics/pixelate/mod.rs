@@ -10,22 +10,30 @@ use bevy::{
     },
 };
 
+mod bloom;
 mod camera;
 mod node;
 mod pipeline;
 mod snap;
+mod sorting;
+mod transition;
+mod zoom;
 
 use bevy_xpbd_3d::PhysicsSet;
+pub use bloom::PixelateBloom;
 pub use camera::*;
 use node::PixelateNode;
 use pipeline::PixelatePipeline;
 pub use snap::{Snap, SnappedTransform};
+pub use sorting::{OrderedTransparency, SortBias};
+pub use zoom::{DynamicPixelsPerUnit, PixelsPerUnitBucket};
 
 pub(crate) mod constants {
-    use bevy::prelude::UVec2;
+    use bevy::{prelude::UVec2, render::render_resource::TextureFormat};
     pub const MIN_SCALE_FACTOR: f32 = 1.0;
     pub const MIN_PIXELS_PER_UNIT: u8 = 1;
     pub const MIN_RENDER_TEXTURE_SIZE: UVec2 = UVec2::splat(1);
+    pub const RENDER_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
 }
 
 /// Set for the systems related to snapping transforms & cameras.
@@ -57,8 +65,12 @@ impl Plugin for PixelatePlugin {
             .register_type::<ScaleBias>()
             .register_type::<RenderTexture>()
             .register_type::<Blitter>()
+            .register_type::<UpscaleFilter>()
             .register_type::<Snap>()
-            .register_type::<SnappedTransform>();
+            .register_type::<SnappedTransform>()
+            .register_type::<DynamicPixelsPerUnit>()
+            .register_type::<PixelsPerUnitBucket>()
+            .register_type::<PixelateBloom>();
 
         use bevy::{render::camera::CameraUpdateSystem, transform::TransformSystem};
 
@@ -83,12 +95,22 @@ impl Plugin for PixelatePlugin {
             UniformComponentPlugin::<ScaleBias>::default(),
         ));
 
+        app.add_plugins((transition::ScreenTransitionPlugin, sorting::TransparencySortingPlugin));
+
         app.insert_resource(Msaa::Off);
         app.init_resource::<MainSnapTransformsCamera>();
 
         app.add_systems(
             Update,
-            (camera::setup, camera::orthographic_fixed_height, apply_deferred, camera::render_texture).chain(),
+            (
+                camera::setup,
+                camera::orthographic_fixed_height,
+                apply_deferred,
+                zoom::pixels_per_unit,
+                camera::render_texture,
+                bloom::bloom,
+            )
+                .chain(),
         );
 
         app.add_systems(First, (snap::revert.run_if(snap_transforms_camera_active)).in_set(SnapSystems::Revert));
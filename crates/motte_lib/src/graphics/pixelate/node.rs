@@ -29,7 +29,7 @@ impl ViewNode for PixelateNode {
         &self,
         _: &mut RenderGraphContext,
         render_context: &mut RenderContext,
-        (target, render_texture, scale_bias_index, _): QueryItem<Self::ViewQuery>,
+        (target, render_texture, scale_bias_index, blitter): QueryItem<Self::ViewQuery>,
         world: &World,
     ) -> Result<(), NodeRunError> {
         let pixelate_pipeline = world.resource::<PixelatePipeline>();
@@ -38,7 +38,7 @@ impl ViewNode for PixelateNode {
 
         let (Some(scale_bias_uniforms), Some(pipeline), Some(image_handle)) = (
             scale_bias_uniforms.binding(),
-            pipeline_cache.get_render_pipeline(pixelate_pipeline.pipeline_id),
+            pipeline_cache.get_render_pipeline(pixelate_pipeline.pipeline_id(blitter.filter)),
             render_texture.handle(),
         ) else {
             return Ok(());
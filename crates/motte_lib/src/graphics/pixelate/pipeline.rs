@@ -1,4 +1,5 @@
 use bevy::{
+    log::debug,
     prelude::{FromWorld, Resource, World},
     render::{
         render_resource::{
@@ -7,16 +8,22 @@ use bevy::{
             RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages, ShaderType,
             TextureFormat, TextureSampleType, TextureViewDimension, VertexState,
         },
-        renderer::RenderDevice,
+        renderer::{RenderAdapter, RenderAdapterInfo, RenderDevice},
         texture::BevyDefault,
     },
 };
+use wgpu::TextureFormatFeatureFlags;
 
-use super::{camera::ScaleBias, SHADER_HANDLE};
+use super::{
+    camera::{ScaleBias, UpscaleFilter},
+    constants::RENDER_TEXTURE_FORMAT,
+    SHADER_HANDLE,
+};
 
 #[derive(Resource)]
 pub(super) struct PixelatePipeline {
-    pub pipeline_id: CachedRenderPipelineId,
+    /// Pipeline of each [`UpscaleFilter`], indexed by its discriminant.
+    pub pipeline_ids: [CachedRenderPipelineId; UpscaleFilter::ALL.len()],
     pub sampler: Sampler,
     pub layout: BindGroupLayout,
 }
@@ -25,6 +32,13 @@ impl FromWorld for PixelatePipeline {
     fn from_world(world: &mut World) -> Self {
         let render_device = world.resource::<RenderDevice>();
 
+        // Smooth upscaling blends neighbouring texels with a bilinear filter, backends that can't filter the render
+        // texture (e.g. some GL drivers) point sample it instead.
+        let features = world.resource::<RenderAdapter>().get_texture_format_features(RENDER_TEXTURE_FORMAT);
+        let filterable = features.flags.contains(TextureFormatFeatureFlags::FILTERABLE);
+        let backend = world.resource::<RenderAdapterInfo>().backend;
+        debug!("pixelate on {backend:?}, smooth upscaling: {filterable}");
+
         let layout = render_device.create_bind_group_layout(
             "pixelate_texture_bind_group_layout",
             &[
@@ -33,17 +47,21 @@ impl FromWorld for PixelatePipeline {
                     binding: 0,
                     visibility: ShaderStages::FRAGMENT,
                     ty: BindingType::Texture {
-                        sample_type: TextureSampleType::Float { filterable: true },
+                        sample_type: TextureSampleType::Float { filterable },
                         view_dimension: TextureViewDimension::D2,
                         multisampled: false,
                     },
                     count: None,
                 },
-                // linear (bilinear) sampler
+                // linear (bilinear) sampler, nearest if not filterable
                 BindGroupLayoutEntry {
                     binding: 1,
                     visibility: ShaderStages::FRAGMENT,
-                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    ty: BindingType::Sampler(if filterable {
+                        SamplerBindingType::Filtering
+                    } else {
+                        SamplerBindingType::NonFiltering
+                    }),
                     count: None,
                 },
                 // scale bias
@@ -60,39 +78,55 @@ impl FromWorld for PixelatePipeline {
             ],
         );
 
+        let filter = if filterable { FilterMode::Linear } else { FilterMode::Nearest };
         let sampler = render_device.create_sampler(&SamplerDescriptor {
             label: None,
-            mag_filter: FilterMode::Linear,
-            min_filter: FilterMode::Linear,
+            mag_filter: filter,
+            min_filter: filter,
             ..SamplerDescriptor::default()
         });
 
-        let pipeline_id = world.resource_mut::<PipelineCache>().queue_render_pipeline(RenderPipelineDescriptor {
-            label: Some("pixelate_pipeline".into()),
-            layout: vec![layout.clone()],
-            vertex: VertexState {
-                shader: SHADER_HANDLE,
-                shader_defs: vec![],
-                entry_point: "vertex".into(),
-                buffers: Vec::new(),
-            },
-            fragment: FragmentState {
-                shader: SHADER_HANDLE,
-                shader_defs: vec![],
-                entry_point: "fragment".into(),
-                targets: vec![Some(ColorTargetState {
-                    format: TextureFormat::bevy_default(),
-                    blend: None,
-                    write_mask: ColorWrites::ALL,
-                })],
-            }
-            .into(),
-            primitive: PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: MultisampleState::default(),
-            push_constant_ranges: vec![],
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline_ids = UpscaleFilter::ALL.map(|filter| {
+            let shader_defs = match filter {
+                UpscaleFilter::Nearest => vec!["NEAREST_UPSCALE".into()],
+                UpscaleFilter::SharpBilinear if filterable => vec!["SMOOTH_UPSCALE".into()],
+                _ => vec![],
+            };
+            pipeline_cache.queue_render_pipeline(RenderPipelineDescriptor {
+                label: Some(format!("pixelate_pipeline_{filter:?}").to_lowercase().into()),
+                layout: vec![layout.clone()],
+                vertex: VertexState {
+                    shader: SHADER_HANDLE,
+                    shader_defs: vec![],
+                    entry_point: "vertex".into(),
+                    buffers: Vec::new(),
+                },
+                fragment: FragmentState {
+                    shader: SHADER_HANDLE,
+                    shader_defs,
+                    entry_point: "fragment".into(),
+                    targets: vec![Some(ColorTargetState {
+                        format: TextureFormat::bevy_default(),
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                }
+                .into(),
+                primitive: PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: MultisampleState::default(),
+                push_constant_ranges: vec![],
+            })
         });
 
-        Self { pipeline_id, layout, sampler }
+        Self { pipeline_ids, layout, sampler }
+    }
+}
+
+impl PixelatePipeline {
+    #[inline]
+    pub fn pipeline_id(&self, filter: UpscaleFilter) -> CachedRenderPipelineId {
+        self.pipeline_ids[filter as usize]
     }
 }
@@ -26,6 +26,12 @@ fn vertex(@builtin(vertex_index) vertex_index: u32) -> FullscreenVertexOutput {
 
 @fragment
 fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+#ifdef NEAREST_UPSCALE
+    // point sample the texel below the uv, independent of the sampler's filter mode
+    let resolution = vec2<i32>(textureDimensions(screen_texture));
+    let texel = clamp(vec2<i32>(floor(in.uv * vec2<f32>(resolution))), vec2<i32>(0), resolution - vec2<i32>(1));
+    return textureLoad(screen_texture, texel, 0);
+#else ifdef SMOOTH_UPSCALE
     let resolution = vec2<f32>(textureDimensions(screen_texture));
     let texel_size = 1.0 / resolution;
     // smooth pixel upscaling, see: https://www.youtube.com/watch?v=d6tp43wZqps
@@ -39,4 +45,8 @@ fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     let uv = (floor(tx) + vec2<f32>(0.5) + tx_offset) * texel_size;
 
     return textureSampleGrad(screen_texture, linear_sampler, uv, dpdx(in.uv), dpdy(in.uv));
+#else
+    // bilinear, the sampler is nearest when the texture isn't filterable
+    return textureSample(screen_texture, linear_sampler, in.uv);
+#endif
 }
\ No newline at end of file
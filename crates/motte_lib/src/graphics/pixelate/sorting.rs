@@ -0,0 +1,61 @@
+use bevy::{
+    core_pipeline::core_3d::Transparent3d,
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_phase::{sort_phase_system, RenderPhase},
+        Render, RenderApp, RenderSet,
+    },
+    utils::FloatOrd,
+};
+
+/// Orders the transparent phase of a [`Pixelate`](super::Pixelate) camera deterministically. The view distance of
+/// each transparent is quantized to `bucket` world units before [`SortBias`] is added & ties are drawn in entity
+/// order, so transparents close to each other, e.g. ground-projected decals, don't swap draw order as the snapped
+/// camera moves.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent)]
+#[extract_component_filter(With<Camera3d>)]
+#[reflect(Component)]
+pub struct OrderedTransparency {
+    pub bucket: f32,
+}
+
+impl Default for OrderedTransparency {
+    fn default() -> Self {
+        Self { bucket: 0.5 }
+    }
+}
+
+/// Offset added to the view distance of a transparent mesh when sorting it for a camera with
+/// [`OrderedTransparency`], higher values are drawn later i.e. on top.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, Deref, DerefMut, ExtractComponent)]
+#[reflect(Component)]
+pub struct SortBias(pub f32);
+
+pub(super) struct TransparencySortingPlugin;
+
+impl Plugin for TransparencySortingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<OrderedTransparency>().register_type::<SortBias>();
+        app.add_plugins((
+            ExtractComponentPlugin::<OrderedTransparency>::default(),
+            ExtractComponentPlugin::<SortBias>::default(),
+        ));
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.add_systems(Render, sort.in_set(RenderSet::PhaseSort).after(sort_phase_system::<Transparent3d>));
+    }
+}
+
+/// Re-sorts the transparent phase of views with [`OrderedTransparency`], the sort is stable.
+fn sort(mut views: Query<(&mut RenderPhase<Transparent3d>, &OrderedTransparency)>, biases: Query<&SortBias>) {
+    for (mut phase, ordering) in &mut views {
+        let bucket = ordering.bucket.max(f32::EPSILON);
+        phase.items.sort_by_cached_key(|item| {
+            let bias = biases.get(item.entity).map_or(0.0, |bias| bias.0);
+            (FloatOrd((item.distance / bucket).round() * bucket + bias), item.entity)
+        });
+    }
+}
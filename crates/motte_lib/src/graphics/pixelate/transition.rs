@@ -0,0 +1,307 @@
+//! Fade & pixel-dissolve transitions between [`AppState`]s, rendered as a fullscreen pass on the [`Blitter`] camera
+//! after the pixelated texture is blitted. The screen is covered when the transition starts and revealed over
+//! [`ScreenTransition::duration`] by a [`Tween`] of its coverage, with real time as virtual time might be paused during
+//! transitions.
+
+use std::time::Duration;
+
+use bevy::{
+    asset::load_internal_asset,
+    core_pipeline::{
+        core_2d::graph::{Core2d, Node2d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin, UniformComponentPlugin,
+        },
+        render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner},
+        render_resource::{
+            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntry, BindingType, BufferBindingType,
+            CachedRenderPipelineId, ColorTargetState, ColorWrites, FragmentState, MultisampleState, Operations,
+            PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor,
+            Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages, ShaderType, TextureFormat, TextureSampleType,
+            TextureViewDimension,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::BevyDefault,
+        view::ViewTarget,
+        RenderApp,
+    },
+};
+
+use super::{Blitter, PixelateRenderLabel};
+use crate::{
+    app_state::AppState,
+    graphics::tween::{self, Ease, Tween, TweenTarget},
+};
+
+const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(2715381960358720934);
+
+#[derive(RenderLabel, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct TransitionRenderLabel;
+
+pub(super) struct ScreenTransitionPlugin;
+
+impl Plugin for ScreenTransitionPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, SHADER_HANDLE, "transition.wgsl", Shader::from_wgsl);
+
+        app.register_type::<ScreenTransition>().register_type::<TransitionKind>();
+
+        app.add_plugins((
+            ExtractComponentPlugin::<ScreenTransition>::default(),
+            UniformComponentPlugin::<TransitionUniform>::default(),
+        ));
+
+        app.add_systems(Update, setup);
+        app.add_systems(StateTransition, start.after(apply_state_transition::<AppState>));
+        app.add_systems(Last, tween::tween::<f32, ScreenTransition, (), Real>);
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<TransitionNode>>(Core2d, TransitionRenderLabel)
+            .add_render_graph_edges(
+                Core2d,
+                (PixelateRenderLabel, TransitionRenderLabel, Node2d::EndMainPassPostProcessing),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<TransitionPipeline>();
+    }
+}
+
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TransitionKind {
+    #[default]
+    Fade,
+    /// Blocks of [`ScreenTransition::pixel_size`] pixels appear in random order.
+    Dissolve,
+}
+
+/// Transition state of a [`Blitter`] camera, added automatically.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct ScreenTransition {
+    pub kind: TransitionKind,
+    pub color: Color,
+    pub duration: Duration,
+    pub pixel_size: f32,
+    /// Amount the screen is covered, from `1.0` when started to `0.0` when finished.
+    coverage: f32,
+}
+
+impl Default for ScreenTransition {
+    fn default() -> Self {
+        Self {
+            kind: TransitionKind::Fade,
+            color: Color::BLACK,
+            duration: Duration::from_millis(400),
+            pixel_size: 8.0,
+            // Starts finished, nothing is rendered until a transition is started.
+            coverage: 0.0,
+        }
+    }
+}
+
+impl ScreenTransition {
+    /// Covers the screen, returns the tween revealing it.
+    pub fn start(&mut self, kind: TransitionKind) -> Tween<f32> {
+        self.kind = kind;
+        self.coverage = 1.0;
+        Tween::to(0.0, self.duration.as_secs_f32(), Ease::SmootherStep)
+    }
+
+    /// Amount the screen is covered, from `1.0` when started to `0.0` when finished.
+    pub fn progress(&self) -> f32 {
+        self.coverage
+    }
+}
+
+impl TweenTarget<f32> for ScreenTransition {
+    fn get(&self) -> f32 {
+        self.coverage
+    }
+
+    fn set(&mut self, value: f32) {
+        self.coverage = value;
+    }
+}
+
+impl ExtractComponent for ScreenTransition {
+    type QueryData = &'static Self;
+    type QueryFilter = (With<Blitter>, With<Camera2d>);
+    type Out = TransitionUniform;
+
+    fn extract_component(transition: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        let progress = transition.progress();
+        (progress > 0.0).then(|| TransitionUniform {
+            color: transition.color.as_linear_rgba_f32().into(),
+            progress,
+            kind: transition.kind as u32,
+            pixel_size: transition.pixel_size.max(1.0),
+        })
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, ShaderType)]
+pub struct TransitionUniform {
+    color: Vec4,
+    progress: f32,
+    kind: u32,
+    pixel_size: f32,
+}
+
+fn setup(mut commands: Commands, blitters: Query<Entity, (With<Blitter>, Without<ScreenTransition>)>) {
+    for entity in &blitters {
+        commands.entity(entity).insert(ScreenTransition::default());
+    }
+}
+
+fn start(
+    mut commands: Commands,
+    mut transitions: EventReader<StateTransitionEvent<AppState>>,
+    mut screens: Query<(Entity, &mut ScreenTransition)>,
+) {
+    for transition in transitions.read() {
+        let kind = if transition.before == AppState::InGame || transition.after == AppState::InGame {
+            TransitionKind::Dissolve
+        } else {
+            TransitionKind::Fade
+        };
+        for (entity, mut screen) in &mut screens {
+            commands.entity(entity).insert(screen.start(kind));
+        }
+    }
+}
+
+#[derive(Default)]
+struct TransitionNode;
+
+impl ViewNode for TransitionNode {
+    type ViewQuery = (&'static ViewTarget, &'static DynamicUniformIndex<TransitionUniform>);
+
+    fn run(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (target, uniform_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let transition_pipeline = world.resource::<TransitionPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let uniforms = world.resource::<ComponentUniforms<TransitionUniform>>();
+
+        let (Some(uniforms), Some(pipeline)) =
+            (uniforms.binding(), pipeline_cache.get_render_pipeline(transition_pipeline.pipeline_id))
+        else {
+            return Ok(());
+        };
+
+        let post_process = target.post_process_write();
+
+        let bind_group = render_context.render_device().create_bind_group(
+            None,
+            &transition_pipeline.layout,
+            &BindGroupEntries::sequential((post_process.source, &transition_pipeline.sampler, uniforms)),
+        );
+
+        let mut render_pass = render_context.command_encoder().begin_render_pass(&RenderPassDescriptor {
+            label: Some("screen_transition_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            ..Default::default()
+        });
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[uniform_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct TransitionPipeline {
+    pipeline_id: CachedRenderPipelineId,
+    sampler: Sampler,
+    layout: BindGroupLayout,
+}
+
+impl FromWorld for TransitionPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "screen_transition_bind_group_layout",
+            &[
+                // screen texture
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+                // transition settings
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: Some(TransitionUniform::min_size()),
+                    },
+                    count: None,
+                },
+            ],
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        let pipeline_id = world.resource_mut::<PipelineCache>().queue_render_pipeline(RenderPipelineDescriptor {
+            label: Some("screen_transition_pipeline".into()),
+            layout: vec![layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: FragmentState {
+                shader: SHADER_HANDLE,
+                shader_defs: vec![],
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: TextureFormat::bevy_default(),
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }
+            .into(),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+        });
+
+        Self { pipeline_id, layout, sampler }
+    }
+}
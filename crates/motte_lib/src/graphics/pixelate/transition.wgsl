@@ -0,0 +1,39 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0)
+var screen_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var screen_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> transition: ScreenTransition;
+
+struct ScreenTransition {
+    color: vec4<f32>,
+    progress: f32,
+    kind: u32,
+    pixel_size: f32,
+}
+
+const KIND_FADE: u32 = 0u;
+const KIND_DISSOLVE: u32 = 1u;
+
+/// Cheap 2d hash, see: https://www.shadertoy.com/view/4djSRW
+fn hash(p: vec2<f32>) -> f32 {
+    var p3 = fract(vec3<f32>(p.xyx) * 0.1031);
+    p3 += dot(p3, p3.yzx + 33.33);
+    return fract((p3.x + p3.y) * p3.z);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let screen = textureSample(screen_texture, screen_sampler, in.uv);
+
+    var amount = transition.progress;
+    if transition.kind == KIND_DISSOLVE {
+        // each block of pixels flips to the transition color once progress passes its threshold
+        let block = floor(in.position.xy / transition.pixel_size);
+        amount = step(hash(block), transition.progress);
+    }
+
+    return vec4<f32>(mix(screen.rgb, transition.color.rgb, amount * transition.color.a), screen.a);
+}
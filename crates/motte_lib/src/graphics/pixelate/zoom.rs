@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+
+use super::camera::{OrthographicFixedVertical, Pixelate};
+
+/// A single zoom bucket for [`DynamicPixelsPerUnit`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct PixelsPerUnitBucket {
+    /// Upper bound (inclusive) of the orthographic scale for this bucket.
+    pub max_scale: f32,
+    pub pixels_per_unit: u8,
+}
+
+impl PixelsPerUnitBucket {
+    pub const fn new(max_scale: f32, pixels_per_unit: u8) -> Self {
+        Self { max_scale, pixels_per_unit }
+    }
+}
+
+/// Maps the orthographic scale (zoom) of a [`Pixelate::PixelsPerUnit`] camera to a pixels per unit value, so
+/// zoomed out views don't waste resolution and zoomed in views gain detail. Only supported for cameras with an
+/// [`OrthographicProjection`] & [`bevy::render::camera::ScalingMode::FixedVertical`] scaling mode.
+///
+/// A bucket is only left once the scale moves past its bounds by more than `hysteresis`, which prevents the render
+/// texture from being resized back & forth while a smoothed zoom settles around a bucket edge.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct DynamicPixelsPerUnit {
+    /// Buckets sorted by ascending `max_scale`, scales above the last bucket use the last bucket.
+    buckets: Vec<PixelsPerUnitBucket>,
+    /// Margin (in orthographic scale units) the scale has to move past a bucket edge before switching.
+    hysteresis: f32,
+}
+
+impl Default for DynamicPixelsPerUnit {
+    fn default() -> Self {
+        Self::new(
+            [
+                PixelsPerUnitBucket::new(8.0, 16),
+                PixelsPerUnitBucket::new(20.0, 10),
+                PixelsPerUnitBucket::new(40.0, 7),
+                PixelsPerUnitBucket::new(60.0, 5),
+                PixelsPerUnitBucket::new(f32::MAX, 4),
+            ],
+            2.0,
+        )
+    }
+}
+
+impl DynamicPixelsPerUnit {
+    pub fn new(buckets: impl IntoIterator<Item = PixelsPerUnitBucket>, hysteresis: f32) -> Self {
+        let mut buckets: Vec<_> = buckets.into_iter().collect();
+        buckets.sort_by(|a, b| a.max_scale.total_cmp(&b.max_scale));
+        debug_assert!(!buckets.is_empty(), "DynamicPixelsPerUnit requires at least one bucket.");
+        Self { buckets, hysteresis: hysteresis.max(0.0) }
+    }
+
+    /// Index of the bucket containing `scale`, without hysteresis.
+    #[inline]
+    fn bucket(&self, scale: f32) -> usize {
+        self.buckets.iter().position(|b| scale <= b.max_scale).unwrap_or(self.buckets.len().saturating_sub(1))
+    }
+
+    /// Scale range covered by the bucket at `index`.
+    #[inline]
+    fn range(&self, index: usize) -> (f32, f32) {
+        let min = if index == 0 { f32::MIN } else { self.buckets[index - 1].max_scale };
+        (min, self.buckets[index].max_scale)
+    }
+
+    /// Pixels per unit for `scale`, staying at `current` while within the hysteresis margin of its bucket.
+    pub fn pixels_per_unit(&self, scale: f32, current: u8) -> Option<u8> {
+        let target = self.buckets.get(self.bucket(scale))?;
+
+        if let Some(index) = self.buckets.iter().position(|b| b.pixels_per_unit == current) {
+            let (min, max) = self.range(index);
+            if scale > min - self.hysteresis && scale <= max + self.hysteresis {
+                return Some(current);
+            }
+        }
+
+        Some(target.pixels_per_unit)
+    }
+}
+
+/// Updates [`Pixelate::PixelsPerUnit`] from the current zoom of cameras with [`DynamicPixelsPerUnit`]. Only
+/// writes to [`Pixelate`] when the value changes, as any change resizes the render texture.
+pub(super) fn pixels_per_unit(
+    mut cameras: Query<
+        (&mut Pixelate, &DynamicPixelsPerUnit, &OrthographicFixedVertical),
+        Or<(Changed<OrthographicFixedVertical>, Changed<DynamicPixelsPerUnit>)>,
+    >,
+) {
+    for (mut pixelate, dynamic, orthographic_fixed_vertical) in &mut cameras {
+        let Pixelate::PixelsPerUnit(current) = *pixelate else {
+            continue;
+        };
+
+        let Some(pixels_per_unit) = dynamic.pixels_per_unit(orthographic_fixed_vertical.scale, current) else {
+            continue;
+        };
+
+        if pixels_per_unit != current {
+            *pixelate = Pixelate::PixelsPerUnit(pixels_per_unit);
+        }
+    }
+}
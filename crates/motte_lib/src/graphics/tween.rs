@@ -0,0 +1,226 @@
+//! Tweens animating a value of an entity towards targets over time, e.g. fading a node with a [`Tween<Color>`] or
+//! scaling a mesh in with a [`Tween<Transform>`]. A tween starts from the entity's current value, runs its steps in
+//! order, removes itself when done & sends [`TweenCompleted`]. Tweens advance with virtual time unless their target is
+//! animated by a [`tween`] system of another clock, e.g. [`Real`] time for screen transitions.
+
+use bevy::ecs::query::QueryFilter;
+
+use crate::prelude::*;
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Ease);
+        app.add_event::<TweenCompleted>();
+        app.add_systems(
+            Update,
+            (
+                tween::<Transform, Transform, (), ()>,
+                tween::<Color, Text, (), ()>,
+                tween::<Color, BackgroundColor, Without<Text>, ()>,
+            ),
+        );
+    }
+}
+
+/// Easing curve of a tween step, maps the progress of the step to the progress of its value.
+#[derive(Reflect, Clone, Copy, Default, Debug, PartialEq)]
+pub enum Ease {
+    #[default]
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    OutCubic,
+    SmootherStep,
+    /// Overshoots the target slightly before settling, for things popping in.
+    OutBack,
+}
+
+impl Ease {
+    /// Eased value of `t` in [0..1].
+    pub fn sample(self, t: f32) -> f32 {
+        let t = t.clamp01();
+        match self {
+            Ease::Linear => t,
+            Ease::InQuad => t * t,
+            Ease::OutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Ease::InOutQuad => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Ease::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Ease::SmootherStep => t * t * t * (t * (t * 6.0 - 15.0) + 10.0),
+            Ease::OutBack => {
+                const OVERSHOOT: f32 = 1.70158;
+                1.0 + (OVERSHOOT + 1.0) * (t - 1.0).powi(3) + OVERSHOOT * (t - 1.0).powi(2)
+            }
+        }
+    }
+}
+
+/// Value that can be interpolated by a [`Tween`].
+pub trait Tweenable: Clone + Send + Sync + 'static {
+    fn interpolate(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        self.lerp(*to, t)
+    }
+}
+
+impl Tweenable for Vec3 {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        self.lerp(*to, t)
+    }
+}
+
+impl Tweenable for Transform {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        Transform {
+            translation: self.translation.lerp(to.translation, t),
+            rotation: self.rotation.slerp(to.rotation, t),
+            scale: self.scale.lerp(to.scale, t),
+        }
+    }
+}
+
+impl Tweenable for Color {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        let [r, g, b, a] = Vec4::from(self.as_rgba_f32()).lerp(Vec4::from(to.as_rgba_f32()), t).to_array();
+        Color::rgba(r, g, b, a)
+    }
+}
+
+/// Component holding a value of type `T` a [`Tween<T>`] animates.
+pub trait TweenTarget<T: Tweenable>: Component {
+    fn get(&self) -> T;
+    fn set(&mut self, value: T);
+}
+
+impl TweenTarget<Transform> for Transform {
+    fn get(&self) -> Transform {
+        *self
+    }
+
+    fn set(&mut self, value: Transform) {
+        *self = value;
+    }
+}
+
+/// Color of all sections of the text.
+impl TweenTarget<Color> for Text {
+    fn get(&self) -> Color {
+        self.sections.first().map_or(Color::WHITE, |section| section.style.color)
+    }
+
+    fn set(&mut self, value: Color) {
+        for section in &mut self.sections {
+            section.style.color = value;
+        }
+    }
+}
+
+impl TweenTarget<Color> for BackgroundColor {
+    fn get(&self) -> Color {
+        self.0
+    }
+
+    fn set(&mut self, value: Color) {
+        self.0 = value;
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TweenStep<T> {
+    to: Option<T>,
+    duration: f32,
+    ease: Ease,
+}
+
+/// Animates the `T` of the entity through its steps, see the [module docs](self). A [`Tween<Color>`] animates the
+/// color of [`Text`] or otherwise the [`BackgroundColor`] of a node.
+#[derive(Component, Clone, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Tween<T: Tweenable> {
+    steps: SmallVec<[TweenStep<T>; 2]>,
+    step: usize,
+    elapsed: f32,
+    /// Value at the start of the current step, read from the entity when the step starts.
+    from: Option<T>,
+}
+
+impl<T: Tweenable> Tween<T> {
+    pub fn to(to: T, duration: f32, ease: Ease) -> Self {
+        Self { steps: SmallVec::new(), step: 0, elapsed: 0.0, from: None }.then(to, duration, ease)
+    }
+
+    /// Waits `duration` seconds before the first step.
+    pub fn delayed(duration: f32) -> Self {
+        Self { steps: SmallVec::new(), step: 0, elapsed: 0.0, from: None }.wait(duration)
+    }
+
+    /// Moves on to `to` after the previous steps.
+    pub fn then(mut self, to: T, duration: f32, ease: Ease) -> Self {
+        self.steps.push(TweenStep { to: Some(to), duration, ease });
+        self
+    }
+
+    /// Holds the value for `duration` seconds after the previous steps.
+    pub fn wait(mut self, duration: f32) -> Self {
+        self.steps.push(TweenStep { to: None, duration, ease: Ease::Linear });
+        self
+    }
+
+    /// Advances the tween by `delta` seconds from the `current` value, returns the new value if it changed & whether
+    /// the tween is done. Used directly for targets spanning several components.
+    pub fn advance(&mut self, current: T, mut delta: f32) -> (Option<T>, bool) {
+        let mut value = None;
+        while let Some(step) = self.steps.get(self.step) {
+            let from = self.from.get_or_insert_with(|| value.clone().unwrap_or_else(|| current.clone()));
+            self.elapsed += delta;
+            let progress = self.elapsed / step.duration.max(f32::EPSILON);
+            if let Some(to) = &step.to {
+                value = Some(from.interpolate(to, step.ease.sample(progress)));
+            }
+            if progress < 1.0 {
+                break;
+            }
+            delta = self.elapsed - step.duration;
+            self.elapsed = 0.0;
+            self.from = None;
+            self.step += 1;
+        }
+        (value, self.step >= self.steps.len())
+    }
+}
+
+/// A [`Tween`] of `entity` finished all its steps & was removed.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TweenCompleted {
+    pub entity: Entity,
+}
+
+/// Animates the `C` of entities with a [`Tween<T>`] with the time of `Clock`.
+pub(crate) fn tween<T: Tweenable, C: TweenTarget<T>, F: QueryFilter, Clock: Default + Send + Sync + 'static>(
+    mut commands: Commands,
+    mut tweens: Query<(Entity, &mut Tween<T>, &mut C), F>,
+    mut completed: EventWriter<TweenCompleted>,
+    time: Res<Time<Clock>>,
+) {
+    for (entity, mut tween, mut target) in &mut tweens {
+        let (value, done) = tween.advance(target.get(), time.delta_seconds());
+        if let Some(value) = value {
+            target.set(value);
+        }
+        if done {
+            commands.entity(entity).remove::<Tween<T>>();
+            completed.send(TweenCompleted { entity });
+        }
+    }
+}
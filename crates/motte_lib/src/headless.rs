@@ -0,0 +1,218 @@
+//! Runs the simulation without rendering or a window for benchmarks & CI. A [`Scenario`] is simulated for a fixed
+//! number of ticks, afterwards the navigation [`timings`] & the final [`StateHash`] are printed.
+//!
+//! `motte --headless [--ticks <n>] [--scenario <file.ron>] [--seed <n>]`
+
+use std::time::Duration;
+
+use bevy::{
+    app::PluginsState,
+    diagnostic::{Diagnostic, DiagnosticsStore, RegisterDiagnostic},
+    scene::ScenePlugin,
+    time::TimeUpdateStrategy,
+};
+use serde::Deserialize;
+
+use crate::{
+    app_state::AppState,
+    cleanup::StateScoped,
+    combat::CombatBundle,
+    determinism::{GameRng, StateHash},
+    launch::LaunchOptions,
+    movement::motor::CharacterMotor,
+    navigation::{
+        agent::{Agent, Speed, TargetReachedCondition},
+        flow_field::{
+            fields::{obstacle::ObstacleField, surface::SurfaceField},
+            layout::{FieldLayout, CELL_SIZE_F32},
+            pathing::Goal,
+            CellIndex,
+        },
+        obstacle::{ObstacleDef, RandomObstacles},
+    },
+    physics::layers,
+    prelude::*,
+    save::Save,
+    timings,
+    utils::math::random_point_in_square,
+};
+
+const DEFAULT_TICKS: u32 = 1000;
+
+/// Agents spawned in a square around `spawn` that all walk to `goal`, read from a `.ron` file.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Scenario {
+    /// Size of the [`FieldLayout`] in cells.
+    pub size: (u8, u8),
+    pub agent: Agent,
+    pub agents: u32,
+    pub spawn: Vec2,
+    /// Agents are scattered within this distance of `spawn`.
+    pub spread: f32,
+    pub goal: Vec2,
+    pub obstacles: Option<RandomObstacles>,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            size: (150, 150),
+            agent: Agent::Medium,
+            agents: 500,
+            spawn: Vec2::new(-50.0, -50.0),
+            spread: 20.0,
+            goal: Vec2::new(50.0, 50.0),
+            obstacles: Some(RandomObstacles { count: 20, extent: 40.0 }),
+        }
+    }
+}
+
+impl Scenario {
+    /// Reads a scenario from a `.ron` file.
+    pub fn load(path: &str) -> AnyResult<Self> {
+        let ron = std::fs::read_to_string(path).with_context(|| format!("failed to read '{path}'"))?;
+        ron::from_str(&ron).with_context(|| format!("failed to parse '{path}'"))
+    }
+}
+
+/// [`SimulationPlugins`](crate::SimulationPlugins) simulating a [`Scenario`], expects [`MinimalPlugins`] to be added.
+pub struct HeadlessPlugin {
+    pub scenario: Scenario,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((TransformPlugin, HierarchyPlugin, AssetPlugin::default(), ScenePlugin));
+        // Required by the async colliders of `bevy_xpbd_3d`.
+        app.init_asset::<Mesh>();
+
+        app.add_plugins(crate::SimulationPlugins);
+
+        let (width, height) = self.scenario.size;
+        let layout = FieldLayout::new(width, height);
+        app.insert_resource(ObstacleField::from_layout(&layout));
+        app.insert_resource(SurfaceField::from_layout(&layout));
+        app.insert_resource(layout);
+        app.insert_resource(self.scenario.clone());
+
+        app.init_resource::<FixedTicks>();
+        app.add_systems(FixedLast, |mut ticks: ResMut<FixedTicks>| **ticks += 1);
+        app.add_systems(OnEnter(AppState::InGame), setup);
+
+        // Skip the menus, the in game sub states are only entered on a transition so the initial state can't be used.
+        app.world.resource_mut::<NextState<AppState>>().set(AppState::InGame);
+    }
+}
+
+/// Fixed updates run since startup.
+#[derive(Resource, Default, Deref, DerefMut)]
+struct FixedTicks(u32);
+
+fn setup(mut commands: Commands, scenario: Res<Scenario>, layout: Res<FieldLayout>, mut rng: ResMut<GameRng>) {
+    let size = Vec2::new(layout.width() as f32, layout.height() as f32) * CELL_SIZE_F32;
+    commands.spawn((
+        Name::unit("plane"),
+        TransformBundle::default(),
+        Collider::cuboid(size.x, 0.1, size.y),
+        RigidBody::Static,
+        StateScoped(AppState::InGame),
+    ));
+
+    let mut obstacle_rng = rng.fork("obstacles");
+    let obstacles =
+        scenario.obstacles.map(|random| random.generate(&mut *obstacle_rng).collect_vec()).unwrap_or_default();
+    for (i, ObstacleDef { position, shape }) in obstacles.into_iter().enumerate() {
+        commands.spawn((
+            Name::unit(format!("obstacle {i}")),
+            TransformBundle::from_transform(position.x0y().into_transform()),
+            crate::navigation::obstacle::Obstacle::default(),
+            crate::navigation::flow_field::footprint::Footprint::default(),
+            CellIndex::default(),
+            RigidBody::Static,
+            shape.collider(),
+            layers::terrain(),
+            StateScoped(AppState::InGame),
+        ));
+    }
+
+    let agent = scenario.agent;
+    let goal = layout.cell(scenario.goal);
+    for _ in 0..scenario.agents {
+        let position = scenario.spawn + random_point_in_square(&mut **rng, scenario.spread);
+        commands.spawn((
+            Name::unit("agent"),
+            TransformBundle::from_transform(Vec3::new(position.x, agent.height() / 2.0, position.y).into_transform()),
+            CharacterMotor::cylinder(agent.height(), agent.radius()),
+            agent,
+            Speed::base(100.0),
+            CombatBundle::default(),
+            Save,
+            CellIndex::default(),
+            TargetReachedCondition::Distance(1.0),
+            Goal::Cell(goal),
+            StateScoped(AppState::InGame),
+        ));
+    }
+}
+
+/// Runs the headless simulation with the given command line arguments & prints a report.
+pub fn run(mut args: impl Iterator<Item = String>) -> AnyResult<()> {
+    let mut ticks = DEFAULT_TICKS;
+    let mut scenario = Scenario::default();
+    let mut launch = LaunchOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => {}
+            "--ticks" => ticks = args.next().context("missing value for --ticks")?.parse()?,
+            "--scenario" => scenario = Scenario::load(&args.next().context("missing value for --scenario")?)?,
+            "--seed" => launch.seed = Some(args.next().context("missing value for --seed")?.parse()?),
+            _ => bail!("unknown argument '{arg}'"),
+        }
+    }
+
+    let mut app = App::new();
+    app.insert_resource(launch);
+    app.add_plugins((MinimalPlugins, HeadlessPlugin { scenario }));
+
+    // Advance exactly one fixed timestep per update, independent of how long the update took.
+    let timestep = app.world.resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+    // Keep a measurement of every tick.
+    for (_, path) in &timings::ALL {
+        app.register_diagnostic(
+            Diagnostic::new((*path).clone()).with_suffix("ms").with_max_history_length(ticks as usize),
+        );
+    }
+
+    while app.plugins_state() == PluginsState::Adding {
+        bevy::tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    let start = Instant::now();
+    while **app.world.resource::<FixedTicks>() < ticks {
+        app.update();
+    }
+    report(&app.world, ticks, start.elapsed());
+    Ok(())
+}
+
+fn report(world: &World, ticks: u32, elapsed: Duration) {
+    println!("{ticks} ticks in {:.2}s ({:.1} ticks/s)", elapsed.as_secs_f64(), ticks as f64 / elapsed.as_secs_f64());
+
+    let diagnostics = world.resource::<DiagnosticsStore>();
+    println!("{:<12}{:>10}{:>10}{:>10}", "system", "avg ms", "max ms", "samples");
+    for (label, path) in &timings::ALL {
+        let Some(diagnostic) = diagnostics.get(path) else {
+            continue;
+        };
+        let max = diagnostic.values().copied().fold(0.0, f64::max);
+        let average = diagnostic.average().unwrap_or_default();
+        println!("{label:<12}{average:>10.3}{max:>10.3}{:>10}", diagnostic.history_len());
+    }
+
+    let state_hash = world.resource::<StateHash>();
+    println!("state hash {:016x} after {} physics steps", state_hash.hash, state_hash.tick);
+}
@@ -0,0 +1,236 @@
+//! Maps are defined by `assets/maps/*.map.ron` files, see [`MapDef`]. A map is picked in [`AppState::MapSelect`],
+//! its assets are loaded in [`AppState::LoadingMap`] & unloaded again when leaving [`AppState::InGame`]. The map of
+//! [`LaunchOptions::map`] is started right away instead.
+
+use bevy_common_assets::ron::RonAssetPlugin;
+use serde::Deserialize;
+
+use crate::{
+    app_state::AppState,
+    asset_management::{FontAssets, MapAssets, MapDefAssets},
+    difficulty::Difficulty,
+    economy::EconomyDef,
+    graphics::{detail::DetailScatter, materials::water::WaterPlane},
+    launch::LaunchOptions,
+    main_menu::{self, MenuAction},
+    navigation::{
+        flow_field::{
+            fields::{obstacle::ObstacleField, surface::SurfaceField},
+            layout::FieldLayout,
+        },
+        obstacle::{ObstacleDef, RandomObstacles},
+    },
+    objectives::ObjectivesDef,
+    prelude::*,
+    scenario::ScenarioDef,
+};
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(MapDef, TerrainDef, LightingDef, SpawnPointDef, SelectedMap, MapButton, DifficultyButton);
+        app.add_plugins(RonAssetPlugin::<MapDef>::new(&["map.ron"]));
+        app.add_systems(OnEnter(AppState::MainMenu), launch.run_if(resource_exists::<LaunchOptions>));
+        app.add_systems(OnEnter(AppState::MapSelect), menu);
+        app.add_systems(Update, (select, difficulty).run_if(in_state(AppState::MapSelect)));
+        app.add_systems(OnEnter(AppState::InGame), layout);
+        app.add_systems(OnExit(AppState::InGame), unload);
+    }
+}
+
+#[derive(Asset, Reflect, Deserialize, Clone, Debug)]
+pub struct MapDef {
+    pub name: String,
+    /// Size of the [`FieldLayout`] in cells.
+    pub size: (u8, u8),
+    /// Texture of the ground plane, the first layer of the [`TerrainDef`] if there's one.
+    pub floor: String,
+    #[serde(default)]
+    pub terrain: Option<TerrainDef>,
+    /// Scenes spawned at the origin, e.g. `glb/ramp.glb#Scene0`.
+    #[serde(default)]
+    pub scenes: Vec<String>,
+    #[serde(default)]
+    pub target: Vec2,
+    #[serde(default)]
+    pub lighting: LightingDef,
+    #[serde(default)]
+    pub spawn_points: Vec<SpawnPointDef>,
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleDef>,
+    #[serde(default)]
+    pub random_obstacles: Option<RandomObstacles>,
+    #[serde(default)]
+    pub water: Vec<WaterPlane>,
+    /// Grass & pebbles scattered over the ground plane.
+    #[serde(default)]
+    pub details: Option<DetailScatter>,
+    /// Resource nodes, depots & workers.
+    #[serde(default)]
+    pub economy: Option<EconomyDef>,
+    /// Win & lose conditions, lost when the target is destroyed if unset.
+    #[serde(default)]
+    pub objectives: ObjectivesDef,
+    /// Triggers of the map's encounters, e.g. spawning waves.
+    #[serde(default)]
+    pub scenario: ScenarioDef,
+    /// Script of the map's encounter logic, e.g. `scripts/outpost.lua`. Requires the `scripting` feature.
+    #[serde(default)]
+    pub script: Option<String>,
+}
+
+/// Layers of the ground plane blended over [`MapDef::floor`] by a splat map, see
+/// [`TerrainExtension`](crate::graphics::materials::terrain::TerrainExtension).
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct TerrainDef {
+    /// Weights of the floor & each layer in its rgba channels, spans the whole map.
+    pub splat_map: String,
+    /// At most 3 textures, blended by the green, blue & alpha channels of the splat map.
+    #[serde(default)]
+    pub layers: Vec<String>,
+}
+
+/// The sun of a map.
+#[derive(Reflect, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct LightingDef {
+    pub illuminance: f32,
+    pub color: Color,
+    /// Position the sun shines from towards the origin.
+    pub sun: Vec3,
+    pub shadows: bool,
+}
+
+impl Default for LightingDef {
+    fn default() -> Self {
+        Self { illuminance: 5000.0, color: Color::WHITE, sun: Vec3::new(30.0, 100.0, 30.0), shadows: false }
+    }
+}
+
+impl LightingDef {
+    pub fn bundle(&self) -> DirectionalLightBundle {
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: self.illuminance,
+                color: self.color,
+                shadows_enabled: self.shadows,
+                ..default()
+            },
+            transform: Transform::from_translation(self.sun).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        }
+    }
+}
+
+/// [`SpawnPoint`](crate::scenario::SpawnPoint) placed on the ground of a map, in addition to the ones authored in its
+/// scenes.
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct SpawnPointDef {
+    pub group: String,
+    pub position: Vec2,
+}
+
+/// The map to load or currently loaded.
+#[derive(Resource, Reflect, Default, Clone, Debug, Deref)]
+#[reflect(Resource)]
+pub struct SelectedMap(pub Handle<MapDef>);
+
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+struct MapButton(Handle<MapDef>);
+
+/// Cycles the [`Difficulty`] of the next game.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct DifficultyButton;
+
+fn menu(
+    mut commands: Commands,
+    maps: Res<MapDefAssets>,
+    defs: Res<Assets<MapDef>>,
+    fonts: Res<FontAssets>,
+    difficulty: Res<Difficulty>,
+) {
+    main_menu::screen(&mut commands, "map select", AppState::MapSelect).with_children(|builder| {
+        builder.spawn(main_menu::text(&fonts, "Select map", 32.0));
+        for handle in &maps.maps {
+            let Some(def) = defs.get(handle) else {
+                continue;
+            };
+            main_menu::button(builder, &fonts, &def.name, MapButton(handle.clone()));
+        }
+        main_menu::button(builder, &fonts, format!("Difficulty: {}", *difficulty), DifficultyButton);
+        main_menu::button(builder, &fonts, "Back", MenuAction::MainMenu);
+    });
+}
+
+fn select(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &MapButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (_, button) in buttons.iter().filter(|(interaction, _)| **interaction == Interaction::Pressed) {
+        commands.insert_resource(SelectedMap(button.0.clone()));
+        next_state.set(AppState::LoadingMap);
+    }
+}
+
+fn difficulty(
+    buttons: Query<(&Interaction, &Children), (With<DifficultyButton>, Changed<Interaction>)>,
+    mut texts: Query<&mut Text>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    for (_, children) in buttons.iter().filter(|(interaction, _)| **interaction == Interaction::Pressed) {
+        *difficulty = difficulty.next();
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = format!("Difficulty: {}", *difficulty);
+        }
+    }
+}
+
+/// Starts the map of the launch options once, matched by its name or file name e.g. `outpost.map.ron`.
+fn launch(
+    mut commands: Commands,
+    mut launch: ResMut<LaunchOptions>,
+    maps: Res<MapDefAssets>,
+    defs: Res<Assets<MapDef>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(name) = launch.map.take() else {
+        return;
+    };
+    let Some(handle) = find(&name, &maps, &defs) else {
+        warn!("no map named '{name}'");
+        return;
+    };
+    commands.insert_resource(SelectedMap(handle.clone()));
+    next_state.set(AppState::LoadingMap);
+}
+
+/// Returns the map matching `name` by its name or file name, e.g. `outpost.map.ron`.
+pub fn find<'a>(name: &str, maps: &'a MapDefAssets, defs: &Assets<MapDef>) -> Option<&'a Handle<MapDef>> {
+    let file_name = |handle: &Handle<MapDef>| {
+        let path = handle.path()?.path().file_name()?.to_str()?;
+        Some(path.strip_suffix(".map.ron").unwrap_or(path).to_owned())
+    };
+    maps.maps.iter().find(|handle| {
+        defs.get(*handle).is_some_and(|def| def.name.eq_ignore_ascii_case(name))
+            || file_name(handle).is_some_and(|file_name| file_name == name.trim_end_matches(".map.ron"))
+    })
+}
+
+/// Sizes the fields to the selected map, [`setup`](super::setup) reads them so it runs after.
+pub(super) fn layout(mut commands: Commands, selected: Res<SelectedMap>, defs: Res<Assets<MapDef>>) {
+    let def = defs.get(&**selected).expect("selected map should be loaded");
+    let layout = FieldLayout::new(def.size.0, def.size.1);
+    commands.insert_resource(ObstacleField::from_layout(&layout));
+    commands.insert_resource(SurfaceField::from_layout(&layout));
+    commands.insert_resource(layout);
+}
+
+/// Drops the handles to the map's assets, entities using them are despawned through [`StateScoped`].
+fn unload(mut commands: Commands) {
+    commands.remove_resource::<MapAssets>();
+}
@@ -1,192 +1,211 @@
-use bevy::render::{
-    mesh::VertexAttributeValues,
-    texture::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
+use self::{
+    cleanup::StateScoped,
+    cursor::{CursorClick, CursorPosition, Pointer},
+    determinism::{self, GameRng},
+    map::{MapDef, SelectedMap, SpawnPointDef},
+    prefab::PrefabCommandsExt,
 };
-
-use self::cursor::{CursorClick, CursorPosition};
 use crate::{
     app_state::AppState,
-    asset_management::{GlbAssets, ImageAssets},
-    graphics::pixelate,
-    movement::motor::CharacterMotor,
+    asset_management::{GlbAssets, MapAssets},
+    graphics::{
+        detail::DetailBudget,
+        materials::{
+            detail::DetailMaterial,
+            terrain::{self, TerrainExtension, TerrainMaterial, MAX_TERRAIN_LAYERS},
+            water::WaterMaterial,
+        },
+        pixelate,
+    },
     navigation::{
-        agent::{Agent, Speed, TargetReachedCondition},
         flow_field::{
-            fields::obstacle::ObstacleField, footprint::Footprint, layout::FieldLayout, pathing::Goal, CellIndex,
+            fields::{obstacle::ObstacleField, surface::SurfaceField},
+            layout::{FieldLayout, CELL_SIZE_F32},
+            CellIndex,
         },
-        obstacle::Obstacle,
+        obstacle::ObstacleDef,
     },
-    physics::CollisionLayer,
-    player::camera::MainCamera,
+    objectives::{Objectives, Target},
+    physics::{
+        layers::{self, CollisionLayer},
+        queries::PhysicsQueries,
+    },
+    player::{camera::MainCamera, hotbar::Targeting},
     prelude::*,
-    utils::math::random_point_in_square,
+    scenario::{Scenario, SpawnPoint},
 };
 
+pub mod map;
+pub mod placement;
+mod screens;
+
 pub struct InGamePlugin;
 
 impl Plugin for InGamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::InGame), setup);
-        app.add_systems(Update, click);
-
+        app.add_plugins((map::MapPlugin, placement::PlacementPlugin, screens::ScreensPlugin));
+        // Clients show the units simulated by their server instead.
+        let rules = rules.after(setup);
+        #[cfg(feature = "net")]
+        let rules = rules.run_if(crate::net::simulated);
+        app.add_systems(OnEnter(AppState::InGame), (setup.after(map::layout).after(determinism::reseed), rules));
+        // Right click cancels a placement or targeting instead.
+        app.add_systems(
+            Update,
+            click.run_if(not(resource_exists::<placement::Placement>)).run_if(not(resource_exists::<Targeting>)),
+        );
+
+        // Replaced by the layout of the selected map when entering the game.
         const DEFAULT_SIZE: (u8, u8) = (150, 150);
 
         let layout = FieldLayout::new(DEFAULT_SIZE.0, DEFAULT_SIZE.1);
         let obstacles = ObstacleField::from_layout(&layout);
+        let surfaces = SurfaceField::from_layout(&layout);
 
         app.insert_resource(layout);
         app.insert_resource(obstacles);
+        app.insert_resource(surfaces);
     }
 }
 
-#[derive(Component)]
-pub struct Target;
-
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    image_assets: Res<ImageAssets>,
+    mut water_materials: ResMut<Assets<WaterMaterial>>,
+    mut terrain_materials: ResMut<Assets<TerrainMaterial>>,
+    mut detail_materials: ResMut<Assets<DetailMaterial>>,
+    detail_budget: Res<DetailBudget>,
+    map_assets: Res<MapAssets>,
+    selected: Res<SelectedMap>,
+    defs: Res<Assets<MapDef>>,
     _glb_assets: Res<GlbAssets>,
     mut asset_image: ResMut<Assets<Image>>,
+    rng: Res<GameRng>,
 ) {
-    commands.spawn((
-        Name::light("sun"),
-        DirectionalLightBundle {
-            directional_light: DirectionalLight { illuminance: 5000.0, color: Color::WHITE, ..default() },
-            transform: Transform::from_xyz(30., 100., 30.).looking_at(Vec3::ZERO, Vec3::Y),
-            ..default()
-        },
-    ));
+    let map = defs.get(&**selected).expect("selected map should be loaded");
+    commands.spawn((Name::light("sun"), map.lighting.bundle(), StateScoped(AppState::InGame)));
+
+    for SpawnPointDef { group, position } in &map.spawn_points {
+        commands.spawn((
+            Name::new(format!("spawn point {group}")),
+            TransformBundle::from_transform(position.x0y().into_transform()),
+            SpawnPoint(group.clone()),
+            StateScoped(AppState::InGame),
+        ));
+    }
+
+    for (i, scene) in map_assets.scenes.iter().enumerate() {
+        commands.spawn((
+            Name::unit(format!("scene {i}")),
+            SceneBundle { scene: scene.clone(), ..default() },
+            StateScoped(AppState::InGame),
+        ));
+    }
 
     // Plane
-    let plane_size = 150.0;
-    let _half_plane_size = plane_size / 2.0;
-
-    let mut mesh_plane = Mesh::from(Plane3d::default().mesh().size(plane_size, plane_size));
-    match mesh_plane.attribute_mut(Mesh::ATTRIBUTE_UV_0).unwrap() {
-        VertexAttributeValues::Float32x2(uvs) => {
-            for uv in uvs {
-                uv[0] *= 16.0; // Make the UV 4x larger, so 4x4 = 16 images
-                uv[1] *= 16.0;
-            }
-        }
-        _ => panic!(),
-    };
-
-    let panel = image_assets.proto_dark.clone();
-    let panel_image = asset_image.get_mut(&panel).unwrap(); // Assuming image is already loaded
-    match &mut panel_image.sampler {
-        ImageSampler::Default => {
-            panel_image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
-                address_mode_u: ImageAddressMode::Repeat,
-                address_mode_v: ImageAddressMode::Repeat,
-                address_mode_w: ImageAddressMode::Repeat,
-                ..default()
-            });
+    let plane_size = Vec2::new(map.size.0 as f32, map.size.1 as f32) * CELL_SIZE_F32;
+
+    let mesh_plane = Mesh::from(Plane3d::default().mesh().size(plane_size.x, plane_size.y));
+
+    let terrain_layers: Vec<_> = std::iter::once(&map_assets.floor)
+        .chain(&map_assets.terrain_layers)
+        .take(MAX_TERRAIN_LAYERS)
+        .cloned()
+        .collect();
+    for layer in &terrain_layers {
+        if let Some(image) = asset_image.get_mut(layer) {
+            terrain::repeat(image);
         }
-        ImageSampler::Descriptor(sampler_descriptor) => {
-            sampler_descriptor.address_mode_u = ImageAddressMode::Repeat;
-            sampler_descriptor.address_mode_v = ImageAddressMode::Repeat;
-            sampler_descriptor.address_mode_w = ImageAddressMode::Repeat;
-        }
-    };
+    }
+    // Each layer repeats 16 times across the plane.
+    let tiling = plane_size.max_element() / 16.0;
+    let ground = TerrainExtension::new(&terrain_layers, map_assets.splat_map.first().cloned(), tiling);
 
     commands.spawn((
         Name::unit("plane"),
-        PbrBundle {
+        MaterialMeshBundle {
             mesh: meshes.add(mesh_plane),
-            material: materials.add(StandardMaterial { base_color_texture: Some(panel), unlit: true, ..default() }),
+            material: terrain_materials
+                .add(TerrainMaterial { base: StandardMaterial { unlit: true, ..default() }, extension: ground }),
             transform: Transform::IDENTITY,
             ..default()
         },
-        Collider::cuboid(plane_size, 0.1, plane_size),
+        Collider::cuboid(plane_size.x, 0.1, plane_size.y),
+        layers::terrain(),
         pixelate::Snap::translation(),
         RigidBody::Static,
+        StateScoped(AppState::InGame),
     ));
 
-    let target = commands
-        .spawn((
-            Name::unit("target"),
-            // SceneBundle {
-            //     scene: glb_assets.crystal.clone(),
-            //     transform: (Vec3::ZERO + Vec3::NEG_Y * 2.5).into_transform(),
-            //     ..Default::default()
-            // },
-            PbrBundle {
-                mesh: meshes.add(Mesh::from(Sphere::new(3.0))),
-                material: materials.add(Color::GREEN),
-                transform: (Vec3::ZERO + Vec3::Y * 3.0).into_transform(),
-                ..default()
-            },
-            pixelate::Snap::translation(),
-            Collider::from(Sphere::new(3.0)),
-            RigidBody::Static,
-            Footprint::default(),
-            Obstacle::default(),
-            CellIndex::default(),
-            Target,
-        ))
-        .id();
-
-    for i in 0..5 {
-        let translation = random_point_in_square(70.0);
-        let radius = thread_rng().gen_range(2.0..3.0);
-        let height = thread_rng().gen_range(2.0..6.0);
-        let shape = thread_rng().gen_range(0..2) >= 1;
+    if let Some(details) = &map.details {
+        let mask = map_assets.density_mask.first().and_then(|mask| asset_image.get(mask));
+        let details =
+            details.spawn(&mut commands, &mut meshes, &mut detail_materials, mask, plane_size, &detail_budget);
+        commands.entity(details).insert(StateScoped(AppState::InGame));
+    }
 
-        commands.spawn((
+    commands.spawn_prefab("target").insert((
+        Name::unit("target"),
+        // SceneBundle {
+        //     scene: glb_assets.crystal.clone(),
+        //     transform: (Vec3::ZERO + Vec3::NEG_Y * 2.5).into_transform(),
+        //     ..Default::default()
+        // },
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(Sphere::new(3.0))),
+            material: materials.add(Color::GREEN),
+            transform: (map.target.x0y() + Vec3::Y * 3.0).into_transform(),
+            ..default()
+        },
+        Collider::from(Sphere::new(3.0)),
+        StateScoped(AppState::InGame),
+    ));
+
+    for water in &map.water {
+        let water = water.spawn(&mut commands, &mut meshes, &mut water_materials);
+        commands.entity(water).insert(StateScoped(AppState::InGame));
+    }
+
+    let mut obstacles = map.obstacles.clone();
+    if let Some(random) = map.random_obstacles {
+        obstacles.extend(random.generate(&mut *rng.fork("obstacles")));
+    }
+
+    for (i, ObstacleDef { position, shape }) in obstacles.into_iter().enumerate() {
+        commands.spawn_prefab("obstacle").insert((
             Name::unit(format!("obstacle {i}")),
             PbrBundle {
-                mesh: meshes.add(if shape {
-                    Mesh::from(Capsule3d::new(radius, height))
-                } else {
-                    Mesh::from(Cuboid { half_size: Vec3::ONE * height })
-                }),
+                mesh: meshes.add(shape.mesh()),
                 material: materials.add(Color::BEIGE),
-                transform: Vec3::new(translation.x, 0.0, translation.y).into_transform(),
+                transform: position.x0y().into_transform(),
                 ..default()
             },
-            Footprint::default(),
-            if shape {
-                Collider::from(Capsule3d::new(radius, height))
-            } else {
-                Collider::from(Cuboid { half_size: Vec3::ONE * height })
-            },
-            pixelate::Snap::translation(),
-            CollisionLayers::new([CollisionLayer::Terrain], [CollisionLayer::Terrain, CollisionLayer::Units]),
-            RigidBody::Static,
-            LinearVelocity::ZERO,
-            Obstacle::default(),
-            CellIndex::default(),
+            shape.collider(),
+            shape,
+            layers::terrain(),
+            StateScoped(AppState::InGame),
         ));
     }
-    // TODO: agents are now broken??
-    // for i in 0..1 {
-    //     let agent = Agent::Medium; // Agent::ALL[thread_rng().gen_range(0..Agent::ALL.len())];
-    //     let translation = random_point_in_square(50.0);
-    //     let transform = Vec3::new(translation.x, 1.0, translation.y).into_transform();
-    //     let agent = commands
-    //         .spawn((
-    //             Name::unit(format!("agent {i}")),
-    //             PbrBundle {
-    //                 mesh: meshes
-    //                     .add(Mesh::from(Cylinder { radius: agent.radius(), half_height: agent.height() / 2.0 })),
-    //                 material: materials.add(Color::RED),
-    //                 transform,
-    //                 ..default()
-    //             },
-    //             CharacterMotor::cylinder(agent.height(), agent.radius()),
-    //             pixelate::Snap::translation(),
-    //             agent,
-    //             Speed::base(100.0),
-    //             CellIndex::default(),
-    //             TargetReachedCondition::Distance(1.0),
-    //         ))
-    //         .id();
-
-    //     commands.entity(agent).insert(Goal::Entity(target));
-    // }
+}
+
+/// Objectives, scenario & economy of the map, which are simulated.
+fn rules(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    selected: Res<SelectedMap>,
+    defs: Res<Assets<MapDef>>,
+) {
+    let map = defs.get(&**selected).expect("selected map should be loaded");
+    commands.insert_resource(Objectives::new(map.objectives.clone()));
+    commands.insert_resource(Scenario::new(map.scenario.clone()));
+
+    if let Some(economy) = &map.economy {
+        for entity in economy.spawn(&mut commands, &mut meshes, &mut materials) {
+            commands.entity(entity).insert(StateScoped(AppState::InGame));
+        }
+    }
 }
 
 fn click(
@@ -194,16 +213,23 @@ fn click(
     mut event_reader: EventReader<CursorClick>,
     mut fields: Query<(&mut Transform, &mut CellIndex), With<Target>>,
     main_cam: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    queries: PhysicsQueries,
     _field_layout: Res<FieldLayout>,
 ) {
     for cursor_click in event_reader.read() {
-        if !matches!(cursor_click.button, MouseButton::Right) {
+        let (camera, camera_transform) = main_cam.get_single().expect("there should be a main camera");
+        let (origin, direction) = math::world_space_ray_from_ndc(cursor.ndc(), camera, camera_transform);
+        // Tapping the ground orders like a right click, tapping a unit selects it instead.
+        let tapped = cursor_click.pointer == Pointer::Touch
+            && cursor_click.button == MouseButton::Left
+            && queries.raycast(origin, direction, f32::MAX, CollisionLayer::Units).is_none();
+        if cursor_click.button != MouseButton::Right && !tapped {
             continue;
         }
         for (mut transform, _cell_index) in &mut fields {
-            let (camera, camera_transform) = main_cam.get_single().expect("there should be a main camera");
-            let (origin, direction) = math::world_space_ray_from_ndc(cursor.ndc(), camera, camera_transform);
-            let position = math::plane_intersection(origin, direction, Vec3::ZERO, Vec3::Y);
+            let position = queries
+                .ground_point(origin, direction)
+                .unwrap_or_else(|| math::plane_intersection(origin, direction, Vec3::ZERO, Vec3::Y));
             transform.translation = position + Vec3::Y * 3.0;
         }
     }
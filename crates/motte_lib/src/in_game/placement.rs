@@ -0,0 +1,333 @@
+//! Placing buildings on the field. While a [`Placement`] exists its [`BuildingDef`] is previewed as a ghost snapped to
+//! the [`FieldLayout`] cells under the cursor, tinted by whether it can be placed there: its cells have to be free &
+//! it may not seal off a region of the field. Left click places it as an obstacle, right click or the build key
+//! cancels.
+
+use std::f32::consts::FRAC_PI_2;
+
+use crate::{
+    app_state::{AppState, InGameState},
+    cleanup::StateScoped,
+    cursor::{CursorClick, CursorPosition},
+    graphics::materials::cel::{CelExtension, CelMaterial},
+    navigation::{
+        agent::Agent,
+        flow_field::{
+            fields::{
+                obstacle::{DirtyObstacleField, ObstacleField, Occupant},
+                Cell,
+            },
+            layout::{FieldLayout, CELL_SIZE_F32},
+        },
+    },
+    physics::{layers, queries::PhysicsQueries},
+    player::camera::MainCamera,
+    prefab::PrefabCommandsExt,
+    prelude::*,
+    settings::Settings,
+};
+
+/// Gap between the collider of a building & the borders of its cells, keeps the obstacle's footprint from spilling
+/// into the neighboring cells.
+const COLLIDER_INSET: f32 = 0.1;
+
+pub struct PlacementPlugin;
+
+impl Plugin for PlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(BuildingDef, Building);
+        app.add_systems(
+            Update,
+            (toggle, (rotate, preview, confirm).chain().run_if(resource_exists::<Placement>))
+                .chain()
+                .run_if(in_state(InGameState::Playing)),
+        );
+        app.add_systems(OnExit(AppState::InGame), |mut commands: Commands| commands.remove_resource::<Placement>());
+    }
+}
+
+#[derive(Reflect, Clone, Debug)]
+pub struct BuildingDef {
+    pub name: String,
+    /// Cells covered along x & z before rotating.
+    pub size: (u8, u8),
+    pub height: f32,
+    pub color: Color,
+}
+
+impl Default for BuildingDef {
+    fn default() -> Self {
+        Self { name: "building".into(), size: (4, 2), height: 3.0, color: Color::BEIGE }
+    }
+}
+
+/// A placed building.
+#[derive(Component, Reflect, Default, Clone, Debug)]
+#[reflect(Component)]
+pub struct Building(pub String);
+
+/// Building being placed, insert to start placing it.
+#[derive(Resource)]
+pub struct Placement {
+    pub building: BuildingDef,
+    /// Quarter turns around the y axis.
+    pub rotation: u8,
+    ghost: Option<Ghost>,
+    /// Anchor cell & rotation of the last validation & whether it was valid.
+    validated: Option<(Cell, u8, bool)>,
+}
+
+impl Placement {
+    /// Placement of `building`, `None` if it doesn't cover any cells.
+    pub fn new(building: BuildingDef) -> Option<Self> {
+        let (x, z) = building.size;
+        (x > 0 && z > 0).then(|| Self { building, rotation: 0, ghost: None, validated: None })
+    }
+
+    /// Cells covered along x & z after rotating.
+    fn size(&self) -> (u8, u8) {
+        let (x, z) = self.building.size;
+        if self.rotation % 2 == 0 {
+            (x, z)
+        } else {
+            (z, x)
+        }
+    }
+
+    /// Cells covered when anchored at `anchor`, `None` if any are outside of the field.
+    fn cells(&self, anchor: Cell, layout: &FieldLayout) -> Option<SmallVec<[Cell; 16]>> {
+        let (width, depth) = self.size();
+        let min_x = anchor.x().checked_sub((width.max(1) - 1) / 2)?;
+        let min_y = anchor.y().checked_sub((depth.max(1) - 1) / 2)?;
+        let cells: SmallVec<_> = (0..width)
+            .flat_map(|x| (0..depth).map(move |y| (min_x.checked_add(x), min_y.checked_add(y))))
+            .map(|(x, y)| Some(Cell::new(x?, y?)))
+            .collect::<Option<_>>()?;
+        cells.iter().all(|&cell| layout.valid(cell)).then_some(cells)
+    }
+
+    /// Transform of the building covering `cells`, `None` if there are none.
+    fn transform(&self, cells: &[Cell], layout: &FieldLayout) -> Option<Transform> {
+        let (first, last) = (cells.first()?, cells.last()?);
+        let center = (layout.position(*first) + layout.position(*last)) / 2.0;
+        let transform = Transform::from_translation(center.x0y() + Vec3::Y * self.building.height / 2.0)
+            .with_rotation(Quat::from_rotation_y(self.rotation as f32 * FRAC_PI_2));
+        Some(transform)
+    }
+}
+
+struct Ghost {
+    entity: Entity,
+    valid: Handle<CelMaterial>,
+    invalid: Handle<CelMaterial>,
+}
+
+fn toggle(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    placement: Option<Res<Placement>>,
+) {
+    if !input.just_pressed(settings.keybinds.build) {
+        return;
+    }
+    match placement {
+        Some(placement) => cancel(&mut commands, &placement),
+        None => match Placement::new(BuildingDef::default()) {
+            Some(placement) => commands.insert_resource(placement),
+            None => warn!("Buildings have to cover at least one cell"),
+        },
+    }
+}
+
+fn cancel(commands: &mut Commands, placement: &Placement) {
+    if let Some(ghost) = &placement.ghost {
+        commands.entity(ghost.entity).despawn_recursive();
+    }
+    commands.remove_resource::<Placement>();
+}
+
+fn rotate(mut placement: ResMut<Placement>, input: Res<ButtonInput<KeyCode>>, settings: Res<Settings>) {
+    if input.just_pressed(settings.keybinds.rotate_building_left) {
+        placement.rotation = (placement.rotation + 3) % 4;
+    }
+    if input.just_pressed(settings.keybinds.rotate_building_right) {
+        placement.rotation = (placement.rotation + 1) % 4;
+    }
+}
+
+/// Moves the ghost to the cells under the cursor, revalidates when it moved, rotated or the field changed.
+fn preview(
+    mut commands: Commands,
+    mut placement: ResMut<Placement>,
+    mut ghosts: Query<(&mut Transform, &mut Handle<CelMaterial>, &mut Visibility)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CelMaterial>>,
+    cursor: Res<CursorPosition>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    queries: PhysicsQueries,
+    layout: Res<FieldLayout>,
+    obstacle_field: Res<ObstacleField>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+
+    if placement.ghost.is_none() {
+        let (x, z) = placement.building.size;
+        let mut material = |color: Color| {
+            materials.add(CelMaterial {
+                base: StandardMaterial { base_color: color.with_a(0.5), alpha_mode: AlphaMode::Blend, ..default() },
+                extension: CelExtension::default(),
+            })
+        };
+        let (valid, invalid) = (material(Color::GREEN), material(Color::RED));
+        let mesh = Cuboid::new(x as f32 * CELL_SIZE_F32, placement.building.height, z as f32 * CELL_SIZE_F32);
+        let entity = commands
+            .spawn((
+                Name::unit("placement ghost"),
+                MaterialMeshBundle {
+                    mesh: meshes.add(mesh),
+                    material: invalid.clone(),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                StateScoped(AppState::InGame),
+            ))
+            .id();
+        placement.ghost = Some(Ghost { entity, valid, invalid });
+        return;
+    }
+
+    let (origin, direction) = math::world_space_ray_from_ndc(cursor.ndc(), camera, camera_transform);
+    let point = queries
+        .ground_point(origin, direction)
+        .unwrap_or_else(|| math::plane_intersection(origin, direction, Vec3::ZERO, Vec3::Y));
+    let anchor = layout.cell(point.xz());
+
+    let Some(ghost) = &placement.ghost else {
+        return;
+    };
+    let Ok((mut transform, mut material, mut visibility)) = ghosts.get_mut(ghost.entity) else {
+        return;
+    };
+    let placed =
+        placement.cells(anchor, &layout).and_then(|cells| Some((placement.transform(&cells, &layout)?, cells)));
+    let Some((target, cells)) = placed else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    visibility.set_if_neq(Visibility::Inherited);
+    transform.set_if_neq(target);
+
+    let unchanged =
+        placement.validated.is_some_and(|(cell, rotation, _)| cell == anchor && rotation == placement.rotation);
+    let valid = match placement.validated {
+        Some((_, _, valid)) if unchanged && !obstacle_field.is_changed() => valid,
+        _ => free(&obstacle_field, &cells) && !seals(&obstacle_field, &layout, &cells),
+    };
+    material.set_if_neq(if valid { ghost.valid.clone() } else { ghost.invalid.clone() });
+
+    let rotation = placement.rotation;
+    placement.validated = Some((anchor, rotation, valid));
+}
+
+/// Places the building where it was last validated on left click, cancels on right click.
+fn confirm(
+    mut commands: Commands,
+    mut clicks: EventReader<CursorClick>,
+    mut dirty: EventWriter<DirtyObstacleField>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    placement: Res<Placement>,
+    layout: Res<FieldLayout>,
+) {
+    for click in clicks.read() {
+        match click.button {
+            MouseButton::Right => {
+                cancel(&mut commands, &placement);
+                return;
+            }
+            MouseButton::Left => {}
+            _ => continue,
+        }
+
+        let Some((anchor, _, true)) = placement.validated else {
+            continue;
+        };
+        let Some(transform) = placement.cells(anchor, &layout).and_then(|cells| placement.transform(&cells, &layout))
+        else {
+            continue;
+        };
+
+        let building = &placement.building;
+        let (x, z) = (building.size.0 as f32 * CELL_SIZE_F32, building.size.1 as f32 * CELL_SIZE_F32);
+        commands.spawn_prefab("obstacle").insert((
+            Name::unit(building.name.clone()),
+            PbrBundle {
+                mesh: meshes.add(Cuboid::new(x, building.height, z)),
+                material: materials.add(building.color),
+                transform,
+                ..default()
+            },
+            Collider::cuboid(x - COLLIDER_INSET * 2.0, building.height, z - COLLIDER_INSET * 2.0),
+            layers::terrain(),
+            Building(building.name.clone()),
+            StateScoped(AppState::InGame),
+        ));
+        dirty.send(DirtyObstacleField);
+        cancel(&mut commands, &placement);
+        return;
+    }
+}
+
+/// Whether no obstacle or agent occupies any of `cells`.
+fn free(obstacle_field: &ObstacleField, cells: &[Cell]) -> bool {
+    cells
+        .iter()
+        .all(|&cell| obstacle_field.occupant(cell) == Occupant::Empty && obstacle_field.traversable(cell, Agent::Small))
+}
+
+/// Whether blocking `cells` disconnects traversable cells that are connected through them. Compares the cells
+/// reachable from a neighbor of the footprint with & without passing through it.
+fn seals(obstacle_field: &ObstacleField, layout: &FieldLayout, cells: &[Cell]) -> bool {
+    // Agents move out of the way, only obstacles split the field.
+    let passable =
+        |cell: Cell| obstacle_field.traversable(cell, Agent::Small) || obstacle_field.occupant(cell) == Occupant::Agent;
+    let Some(start) = cells
+        .iter()
+        .flat_map(|cell| cell.adjacent())
+        .find(|&neighbor| layout.valid(neighbor) && !cells.contains(&neighbor) && passable(neighbor))
+    else {
+        return false;
+    };
+
+    let through = flood(layout, start, passable);
+    let around = flood(layout, start, |cell| passable(cell) && !cells.contains(&cell));
+    around + cells.len() < through
+}
+
+/// Number of cells reachable from `start` through adjacent `passable` cells.
+fn flood(layout: &FieldLayout, start: Cell, passable: impl Fn(Cell) -> bool) -> usize {
+    let mut visited = vec![false; layout.len()];
+    let mut stack = vec![start];
+    if let Some(index) = layout.index(start) {
+        visited[index] = true;
+    }
+
+    let mut count = 0;
+    while let Some(cell) = stack.pop() {
+        count += 1;
+        for neighbor in cell.adjacent() {
+            let Some(index) = layout.index(neighbor) else {
+                continue;
+            };
+            if visited[index] || !passable(neighbor) {
+                continue;
+            }
+            visited[index] = true;
+            stack.push(neighbor);
+        }
+    }
+    count
+}
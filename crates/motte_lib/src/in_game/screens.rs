@@ -0,0 +1,114 @@
+//! Pause & game over screens, shown for the matching [`InGameState`].
+
+use crate::{
+    app_state::{simulating, InGameState},
+    asset_management::FontAssets,
+    events::{GameEvent, GameEventLog},
+    main_menu::{self, MenuAction},
+    objectives::{Objectives, Outcome},
+    prelude::*,
+    settings::Settings,
+};
+
+pub struct ScreensPlugin;
+
+impl Plugin for ScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Resume);
+        app.add_systems(OnEnter(InGameState::Paused), pause_screen);
+        app.add_systems(OnEnter(InGameState::GameOver), game_over_screen);
+        app.add_systems(
+            Update,
+            (
+                toggle_pause.run_if(in_state(InGameState::Playing).or_else(in_state(InGameState::Paused))),
+                resume.run_if(in_state(InGameState::Paused)),
+                physics_time.run_if(state_changed::<InGameState>),
+            ),
+        );
+    }
+}
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct Resume;
+
+fn pause_screen(mut commands: Commands, fonts: Res<FontAssets>) {
+    main_menu::screen(&mut commands, "pause", InGameState::Paused)
+        .insert(BackgroundColor(Color::BLACK.with_a(0.6)))
+        .with_children(|builder| {
+            builder.spawn(main_menu::text(&fonts, "Paused", 32.0));
+            main_menu::button(builder, &fonts, "Resume", Resume);
+            main_menu::button(builder, &fonts, "Main menu", MenuAction::MainMenu);
+        });
+}
+
+fn game_over_screen(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    objectives: Option<Res<Objectives>>,
+    log: Res<GameEventLog>,
+) {
+    let objectives = objectives.as_deref();
+    let title = match objectives.and_then(Objectives::outcome) {
+        Some(Outcome::Victory) => "Victory",
+        Some(Outcome::Defeat) => "Defeat",
+        None => "Game over",
+    };
+    main_menu::screen(&mut commands, "game over", InGameState::GameOver)
+        .insert(BackgroundColor(Color::BLACK.with_a(0.6)))
+        .with_children(|builder| {
+            builder.spawn(main_menu::text(&fonts, title, 32.0));
+            for line in summary(&log, objectives) {
+                builder.spawn(main_menu::text(&fonts, line, 16.0));
+            }
+            main_menu::button(builder, &fonts, "Main menu", MenuAction::MainMenu);
+        });
+}
+
+/// Lines summarizing the game from the [`GameEventLog`], the log is cleared when a game starts.
+fn summary(log: &GameEventLog, objectives: Option<&Objectives>) -> Vec<String> {
+    let count = |matches: fn(&GameEvent) -> bool| log.iter().filter(|entry| matches(&entry.event)).count();
+    let mut lines = Vec::new();
+    if let Some(objectives) = objectives {
+        let seconds = objectives.elapsed() as u32;
+        lines.push(format!("Time: {}:{:02}", seconds / 60, seconds % 60));
+        for (condition, met) in objectives.victory() {
+            lines.push(format!("[{}] {condition}", if met { "x" } else { " " }));
+        }
+    }
+    lines.push(format!("Deaths: {}", count(|event| matches!(event, GameEvent::Died { .. }))));
+    lines.push(format!("Spells cast: {}", count(|event| matches!(event, GameEvent::SpellCast { .. }))));
+    lines.push(format!("Waves: {}", count(|event| matches!(event, GameEvent::WaveSpawned { .. }))));
+    lines
+}
+
+fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    state: Res<State<InGameState>>,
+    mut next_state: ResMut<NextState<InGameState>>,
+) {
+    if keys.just_pressed(settings.keybinds.pause) {
+        next_state.set(match **state {
+            InGameState::Paused => InGameState::Playing,
+            _ => InGameState::Paused,
+        });
+    }
+}
+
+fn resume(
+    buttons: Query<&Interaction, (With<Resume>, Changed<Interaction>)>,
+    mut next_state: ResMut<NextState<InGameState>>,
+) {
+    if buttons.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        next_state.set(InGameState::Playing);
+    }
+}
+
+fn physics_time(state: Res<State<InGameState>>, mut time: ResMut<Time<Physics>>) {
+    if simulating(state) {
+        time.unpause();
+    } else {
+        time.pause();
+    }
+}
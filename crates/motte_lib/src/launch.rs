@@ -0,0 +1,190 @@
+//! Options of the game launcher, read from `MOTTE_*` environment variables & the command line, arguments take
+//! precedence. Options that are also [`Settings`](crate::settings::Settings) override them for this run only, they
+//! aren't saved.
+//!
+//! `motte [--resolution <width>x<height>] [--fullscreen] [--present-mode <vsync|no-vsync|fifo|immediate|mailbox>]
+//! [--backend <vulkan|dx12|metal|gl|all>[,...]] [--map <name>] [--log <level|filter>] [--mods <dir>] [--seed <n>]`
+//!
+//! e.g. `MOTTE_RESOLUTION=1920x1080 MOTTE_FULLSCREEN=1 motte --map outpost --backend dx12,vulkan`. `--headless` or
+//! `MOTTE_HEADLESS=1` runs [`crate::headless`] instead, which takes its own arguments. Arguments of plugins that read
+//! them on their own, e.g. `--host` & `--connect` of the net plugin, are skipped.
+
+use std::path::PathBuf;
+
+use bevy::{log::Level, render::settings::Backends, window::PresentMode};
+
+use crate::prelude::*;
+
+const OPTIONS: [&str; 8] = ["resolution", "fullscreen", "present-mode", "backend", "map", "log", "mods", "seed"];
+/// Options without a value on the command line.
+const FLAGS: [&str; 1] = ["fullscreen"];
+/// Options read by plugins from [`std::env::args`] themselves, skipped together with their value if they have one.
+const PLUGIN_OPTIONS: [&str; 4] = ["host", "connect", "lockstep", "input-delay"];
+
+/// Renderer backends tried in order, see [`LaunchOptions::negotiate_backend`].
+pub const DEFAULT_BACKENDS: [Backends; 4] = [Backends::VULKAN, Backends::DX12, Backends::METAL, Backends::GL];
+
+#[derive(Resource, Clone, Debug)]
+pub struct LaunchOptions {
+    pub resolution: Option<Vec2>,
+    pub fullscreen: bool,
+    pub present_mode: Option<PresentMode>,
+    /// Renderer backends in order of preference.
+    pub backends: Vec<Backends>,
+    /// Name or file name of a map to start right after loading, skipping the menu.
+    pub map: Option<String>,
+    pub log: Option<LogFilter>,
+    pub mods: Option<PathBuf>,
+    /// Seed of the [`GameRng`](crate::determinism::GameRng) to reproduce a game.
+    pub seed: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub enum LogFilter {
+    Level(Level),
+    /// Directives as in `RUST_LOG`, e.g. `motte_lib=debug`.
+    Filter(String),
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        Self {
+            resolution: None,
+            fullscreen: false,
+            present_mode: None,
+            backends: DEFAULT_BACKENDS.to_vec(),
+            map: None,
+            log: None,
+            mods: None,
+            seed: None,
+        }
+    }
+}
+
+impl LaunchOptions {
+    /// Reads the options from the environment, then from `args` (without the executable).
+    pub fn parse(args: impl IntoIterator<Item = String>) -> AnyResult<Self> {
+        let mut options = Self::default();
+        for name in OPTIONS {
+            let var = env_var(name);
+            if let Ok(value) = std::env::var(&var) {
+                options.set(name, &value).with_context(|| format!("invalid value for {var}"))?;
+            }
+        }
+
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            if arg.strip_prefix("--").is_some_and(|name| PLUGIN_OPTIONS.contains(&name)) {
+                args.next_if(|value| !value.starts_with("--"));
+                continue;
+            }
+            let name = arg
+                .strip_prefix("--")
+                .filter(|name| OPTIONS.contains(name))
+                .with_context(|| format!("unknown argument '{arg}'"))?;
+            let value = match FLAGS.contains(&name) {
+                true => "true".to_owned(),
+                false => args.next().with_context(|| format!("missing value for {arg}"))?,
+            };
+            options.set(name, &value).with_context(|| format!("invalid value for {arg}"))?;
+        }
+        Ok(options)
+    }
+
+    /// Returns true if the game should run [`crate::headless`].
+    pub fn headless(args: &[String]) -> bool {
+        args.iter().any(|arg| arg == "--headless")
+            || std::env::var(env_var("headless")).is_ok_and(|value| parse_bool(&value).unwrap_or(false))
+    }
+
+    /// Returns the first of [`LaunchOptions::backends`] with an adapter, forcing a backend that isn't available
+    /// fails to create the renderer.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn negotiate_backend(&self) -> Option<Backends> {
+        self.backends.iter().copied().find(|&backends| {
+            let instance = wgpu::Instance::new(wgpu::InstanceDescriptor { backends, ..default() });
+            !instance.enumerate_adapters(backends).is_empty()
+        })
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> AnyResult<()> {
+        match name {
+            "resolution" => {
+                let (width, height) = value.split_once('x').context("expected <width>x<height>")?;
+                self.resolution = Some(Vec2::new(width.trim().parse()?, height.trim().parse()?));
+            }
+            "fullscreen" => self.fullscreen = parse_bool(value)?,
+            "present-mode" => {
+                self.present_mode = Some(match value {
+                    "vsync" => PresentMode::AutoVsync,
+                    "no-vsync" => PresentMode::AutoNoVsync,
+                    "fifo" => PresentMode::Fifo,
+                    "immediate" => PresentMode::Immediate,
+                    "mailbox" => PresentMode::Mailbox,
+                    _ => bail!("expected vsync, no-vsync, fifo, immediate or mailbox"),
+                })
+            }
+            "backend" => {
+                self.backends = value
+                    .split(',')
+                    .map(|backend| match backend.trim() {
+                        "vulkan" => Ok(Backends::VULKAN),
+                        "dx12" => Ok(Backends::DX12),
+                        "metal" => Ok(Backends::METAL),
+                        "gl" => Ok(Backends::GL),
+                        "all" => Ok(Backends::all()),
+                        _ => bail!("expected vulkan, dx12, metal, gl or all"),
+                    })
+                    .try_collect()?;
+            }
+            "map" => self.map = Some(value.to_owned()),
+            "log" => {
+                self.log = Some(match value.parse() {
+                    Ok(level) => LogFilter::Level(level),
+                    Err(_) => LogFilter::Filter(value.to_owned()),
+                })
+            }
+            "mods" => self.mods = Some(value.into()),
+            "seed" => self.seed = Some(value.parse()?),
+            _ => unreachable!("unknown option {name}"),
+        }
+        Ok(())
+    }
+}
+
+fn env_var(name: &str) -> String {
+    format!("MOTTE_{}", name.to_uppercase().replace('-', "_"))
+}
+
+fn parse_bool(value: &str) -> AnyResult<bool> {
+    match value {
+        "1" | "true" | "yes" => Ok(true),
+        "0" | "false" | "no" => Ok(false),
+        _ => bail!("expected true or false"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> AnyResult<LaunchOptions> {
+        LaunchOptions::parse(args.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn plugin_options_are_skipped() {
+        let options = parse("--host 0.0.0.0:5000 --lockstep").unwrap();
+        assert_eq!(options.map, None);
+
+        let options = parse("--lockstep --map outpost --host --fullscreen").unwrap();
+        assert_eq!(options.map.as_deref(), Some("outpost"));
+        assert!(options.fullscreen);
+    }
+
+    #[test]
+    fn unknown_arguments_fail() {
+        assert!(parse("--nope").is_err());
+        assert!(parse("outpost").is_err());
+    }
+}
@@ -15,42 +15,117 @@
 #![feature(const_mut_refs)]
 
 mod app_state;
+#[cfg(feature = "presentation")]
 mod asset_management;
+#[cfg(feature = "presentation")]
+mod audio;
+mod combat;
 mod core;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod crash;
 #[cfg(feature = "dev_tools")]
 mod dev_tools;
+mod difficulty;
+mod economy;
+#[cfg(feature = "presentation")]
 mod graphics;
+#[cfg(feature = "headless")]
+pub mod headless;
+#[cfg(feature = "presentation")]
 mod in_game;
+pub mod launch;
+#[cfg(feature = "presentation")]
+mod main_menu;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod modding;
 mod movement;
+#[cfg(feature = "bench")]
+pub mod navigation;
+#[cfg(not(feature = "bench"))]
 mod navigation;
+#[cfg(feature = "net")]
+pub mod net;
+mod objectives;
 mod physics;
+#[cfg(feature = "presentation")]
 mod player;
 mod prelude;
+mod scenario;
+#[cfg(feature = "scripting")]
+mod scripting;
 mod spells;
 mod stats;
+mod tech;
 mod utils;
 
+use bevy::app::PluginGroupBuilder;
 use prelude::*;
 
+/// Captures log events for the log panel of the dev tools, pass to [`LogPlugin::update_subscriber`].
+///
+/// [`LogPlugin::update_subscriber`]: bevy::log::LogPlugin::update_subscriber
+#[cfg(feature = "dev_tools")]
+pub use dev_tools::capture_logs as capture_dev_logs;
+
+#[cfg(feature = "presentation")]
 pub struct Plugin;
+#[cfg(feature = "presentation")]
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        use crate::app_state::AppState;
-        app_register_types!(AppState);
-        app.init_state::<AppState>();
-        app.add_plugins((
-            #[cfg(feature = "dev_tools")]
-            dev_tools::DevToolsPlugin,
-            asset_management::AssetManagementPlugin,
-            physics::PhysicsPlugin,
-            graphics::GraphicsPlugin,
-            player::PlayerPlugin,
-            core::CorePlugin,
-            stats::StatsPlugin,
-            in_game::InGamePlugin,
-            navigation::NavigationPlugin,
-            movement::MovementPlugin,
-        ));
+        app.add_plugins((SimulationPlugins, PresentationPlugins));
+    }
+}
+
+/// Plugins of the game simulation, enough to run it without a window e.g. as a dedicated server. Expects the
+/// transform, hierarchy, asset & scene plugins of bevy to be added.
+pub struct SimulationPlugins;
+impl PluginGroup for SimulationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>()
+            .add(app_state::AppStatePlugin)
+            .add(physics::PhysicsPlugin)
+            .add(core::CorePlugin)
+            .add(stats::StatsPlugin)
+            .add(navigation::NavigationPlugin)
+            .add(movement::MovementPlugin)
+            .add(spells::SpellsPlugin)
+            .add(combat::CombatPlugin)
+            .add(economy::EconomyPlugin)
+            .add(tech::TechPlugin)
+            .add(objectives::ObjectivesPlugin)
+            .add(difficulty::DifficultyPlugin)
+            .add(scenario::ScenarioPlugin);
+        #[cfg(feature = "scripting")]
+        let group = group.add(scripting::ScriptingPlugin);
+        #[cfg(feature = "net")]
+        let group = group.add(net::NetPlugin);
+        group
+    }
+}
+
+/// Rendering, audio, input & ui of the game on top of [`SimulationPlugins`].
+#[cfg(feature = "presentation")]
+pub struct PresentationPlugins;
+#[cfg(feature = "presentation")]
+impl PluginGroup for PresentationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>();
+        #[cfg(feature = "dev_tools")]
+        let group = group.add(dev_tools::DevToolsPlugin);
+        #[cfg(not(feature = "determinism"))]
+        let group = group.add(navigation::avoidance::AvoidanceQualityPlugin);
+        group
+            .add(asset_management::AssetManagementPlugin)
+            .add(audio::AudioPlugin)
+            .add(graphics::GraphicsPlugin)
+            .add(physics::interpolation::InterpolationPlugin)
+            .add(player::PlayerPlugin)
+            .add(core::CorePresentationPlugin)
+            .add(in_game::InGamePlugin)
+            .add(combat::text::CombatTextPlugin)
+            .add(economy::hud::EconomyHudPlugin)
+            .add(scenario::hud::ScenarioHudPlugin)
+            .add(main_menu::MainMenuPlugin)
     }
 }
 
@@ -61,6 +136,26 @@ pub struct Semver {
     pub patch: u16,
 }
 
+impl Semver {
+    /// Returns true if data written with `other` can be read with `self`, versions before `1.0.0` also require
+    /// a matching minor version.
+    pub fn is_compatible(&self, other: &Semver) -> bool {
+        self.major == other.major && (self.major > 0 || self.minor == other.minor)
+    }
+}
+
+impl std::str::FromStr for Semver {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+        let mut next = || -> AnyResult<u16> { Ok(parts.next().context("missing version part")?.parse()?) };
+        let semver = Semver { major: next()?, minor: next()?, patch: next()? };
+        ensure!(parts.next().is_none(), "too many version parts");
+        Ok(semver)
+    }
+}
+
 impl std::fmt::Display for Semver {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
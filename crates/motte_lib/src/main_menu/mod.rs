@@ -0,0 +1,235 @@
+//! Main menu, entered once loading is done. Home of the settings screen & entry point to map selection.
+
+use bevy::{app::AppExit, ecs::system::EntityCommands};
+
+use crate::{
+    app_state::{AppState, AppSubStateExt},
+    asset_management::FontAssets,
+    audio::{PlaySound, Sound},
+    cleanup::{AppStateScopedExt, StateScoped},
+    prelude::*,
+    settings::{DisplayMode, Quality, Settings, RESOLUTIONS},
+};
+
+const BUTTON_COLOR: Color = Color::DARK_GRAY;
+const BUTTON_HOVERED_COLOR: Color = Color::GRAY;
+
+pub struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(MenuScreen, MenuAction, SettingLabel);
+        app.add_sub_state(AppState::MainMenu, MenuScreen::Main);
+        app.enable_state_scoped::<MenuScreen>();
+        app.add_systems(OnEnter(MenuScreen::Main), main);
+        app.add_systems(OnEnter(MenuScreen::Settings), settings);
+        app.add_systems(Update, (buttons, actions));
+        app.add_systems(Update, labels.run_if(in_state(MenuScreen::Settings).and_then(resource_changed::<Settings>)));
+    }
+}
+
+/// Screen shown while in [`AppState::MainMenu`].
+#[derive(States, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MenuScreen {
+    #[default]
+    Disabled,
+    Main,
+    Settings,
+}
+
+/// Action performed when the button is pressed.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component)]
+pub enum MenuAction {
+    Play,
+    MainMenu,
+    Screen(MenuScreen),
+    Quit,
+    ToggleVsync,
+    CycleQuality,
+    CycleDisplayMode,
+    NextMonitor,
+    CycleResolution,
+    MasterVolume(f32),
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+enum SettingLabel {
+    Vsync,
+    Quality,
+    DisplayMode,
+    Monitor,
+    Resolution,
+    MasterVolume,
+}
+
+impl SettingLabel {
+    fn text(self, settings: &Settings) -> String {
+        match self {
+            SettingLabel::Vsync => format!("Vsync: {}", if settings.graphics.vsync { "on" } else { "off" }),
+            SettingLabel::Quality => format!("Quality: {:?} (restart to apply)", settings.graphics.quality),
+            SettingLabel::DisplayMode => format!("Display: {:?}", settings.graphics.window.mode),
+            SettingLabel::Monitor => format!("Monitor: {}", settings.graphics.window.monitor + 1),
+            SettingLabel::Resolution => {
+                let resolution = settings.graphics.window.resolution;
+                format!("Resolution: {}x{}", resolution.x, resolution.y)
+            }
+            SettingLabel::MasterVolume => format!("Volume: {:.0}%", settings.audio.master * 100.0),
+        }
+    }
+}
+
+/// Fullscreen centered column, despawned when leaving `scope`.
+pub(crate) fn screen<'a, S: States>(commands: &'a mut Commands, name: &'static str, scope: S) -> EntityCommands<'a> {
+    commands.spawn((
+        Name::ui(name),
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::BLACK),
+            ..default()
+        },
+        StateScoped(scope),
+    ))
+}
+
+pub(crate) fn text(fonts: &FontAssets, value: impl Into<String>, font_size: f32) -> TextBundle {
+    TextBundle::from_section(value, TextStyle { font: fonts.commit_mono_400.clone(), font_size, color: Color::WHITE })
+}
+
+/// Spawns a text button, add a [`MenuAction`] or handle [`Interaction`] yourself.
+pub(crate) fn button<'a>(
+    builder: &'a mut ChildBuilder<'_>,
+    fonts: &FontAssets,
+    label: impl Into<String>,
+    bundle: impl Bundle,
+) -> EntityCommands<'a> {
+    let label = label.into();
+    let mut button = button_node(builder, &label, bundle);
+    button.with_children(|builder| {
+        builder.spawn(text(fonts, label, 20.0));
+    });
+    button
+}
+
+/// Spawns an empty button, see [`button`].
+pub(crate) fn button_node<'a>(
+    builder: &'a mut ChildBuilder<'_>,
+    name: &str,
+    bundle: impl Bundle,
+) -> EntityCommands<'a> {
+    builder.spawn((
+        Name::ui(format!("button {name}")),
+        ButtonBundle {
+            style: Style { padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)), ..default() },
+            background_color: BackgroundColor(BUTTON_COLOR),
+            ..default()
+        },
+        bundle,
+    ))
+}
+
+fn main(mut commands: Commands, fonts: Res<FontAssets>) {
+    screen(&mut commands, "main menu", MenuScreen::Main).with_children(|builder| {
+        builder.spawn(text(&fonts, "motte", 48.0));
+        button(builder, &fonts, "Play", MenuAction::Play);
+        button(builder, &fonts, "Settings", MenuAction::Screen(MenuScreen::Settings));
+        #[cfg(not(target_arch = "wasm32"))]
+        button(builder, &fonts, "Quit", MenuAction::Quit);
+    });
+}
+
+fn settings(mut commands: Commands, fonts: Res<FontAssets>, settings: Res<Settings>) {
+    screen(&mut commands, "settings", MenuScreen::Settings).with_children(|builder| {
+        builder.spawn(text(&fonts, "Settings", 32.0));
+        for (label, action) in [
+            (SettingLabel::Vsync, MenuAction::ToggleVsync),
+            (SettingLabel::Quality, MenuAction::CycleQuality),
+            (SettingLabel::DisplayMode, MenuAction::CycleDisplayMode),
+            (SettingLabel::Monitor, MenuAction::NextMonitor),
+            (SettingLabel::Resolution, MenuAction::CycleResolution),
+            (SettingLabel::MasterVolume, MenuAction::MasterVolume(0.1)),
+        ] {
+            button_node(builder, &format!("{label:?}"), action).with_children(|builder| {
+                builder.spawn((text(&fonts, label.text(&settings), 20.0), label));
+            });
+        }
+        button(builder, &fonts, "Volume -", MenuAction::MasterVolume(-0.1));
+        button(builder, &fonts, "Back", MenuAction::Screen(MenuScreen::Main));
+    });
+}
+
+fn buttons(
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), (With<Button>, Changed<Interaction>)>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for (interaction, mut background) in &mut buttons {
+        *background = BackgroundColor(match interaction {
+            Interaction::Pressed => {
+                sounds.send(PlaySound { sound: Sound::UiClick, position: None });
+                BUTTON_HOVERED_COLOR
+            }
+            Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        });
+    }
+}
+
+fn actions(
+    buttons: Query<(&Interaction, &MenuAction), Changed<Interaction>>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut screen: ResMut<NextState<MenuScreen>>,
+    mut settings: ResMut<Settings>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (_, action) in buttons.iter().filter(|(interaction, _)| **interaction == Interaction::Pressed) {
+        match *action {
+            MenuAction::Play => app_state.set(AppState::MapSelect),
+            MenuAction::MainMenu => app_state.set(AppState::MainMenu),
+            MenuAction::Screen(next) => screen.set(next),
+            MenuAction::Quit => {
+                exit.send(AppExit);
+            }
+            MenuAction::ToggleVsync => settings.graphics.vsync = !settings.graphics.vsync,
+            MenuAction::CycleQuality => {
+                settings.graphics.quality = match settings.graphics.quality {
+                    Quality::Low => Quality::Medium,
+                    Quality::Medium => Quality::High,
+                    Quality::High => Quality::Low,
+                }
+            }
+            MenuAction::CycleDisplayMode => {
+                settings.graphics.window.mode = match settings.graphics.window.mode {
+                    DisplayMode::Windowed => DisplayMode::Borderless,
+                    DisplayMode::Borderless => DisplayMode::Exclusive,
+                    DisplayMode::Exclusive => DisplayMode::Windowed,
+                }
+            }
+            // Wraps around to the first monitor when applied, past the last connected monitor.
+            MenuAction::NextMonitor => settings.graphics.window.monitor += 1,
+            MenuAction::CycleResolution => {
+                let current =
+                    RESOLUTIONS.iter().position(|resolution| *resolution == settings.graphics.window.resolution);
+                settings.graphics.window.resolution =
+                    RESOLUTIONS[current.map_or(0, |current| (current + 1) % RESOLUTIONS.len())];
+            }
+            MenuAction::MasterVolume(delta) => {
+                settings.audio.master = (settings.audio.master + delta).clamp(0.0, 1.0);
+            }
+        }
+    }
+}
+
+fn labels(settings: Res<Settings>, mut labels: Query<(&SettingLabel, &mut Text)>) {
+    for (label, mut text) in &mut labels {
+        text.sections[0].value = label.text(&settings);
+    }
+}
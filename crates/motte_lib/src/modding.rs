@@ -0,0 +1,335 @@
+//! Mods extend or override the game's assets without rebuilding it. A mod is a directory in `mods/` next to the
+//! executable, or in the directory of `--mods <dir>`, with a `mod.ron` manifest & files laid out like `assets/`:
+//! ```text
+//! mods/frost_pack/
+//!   mod.ron
+//!   spells/blizzard.spell.ron   <- added to the spells
+//!   images/proto_dark.png       <- replaces the texture
+//! ```
+//! ```ron
+//! (name: "frost_pack", version: "0.1.0", after: ["base_tweaks"], requires: [])
+//! ```
+//! A file of a mod replaces the file with the same path of the game or of mods loaded before it, directories are
+//! merged so new maps, spells & prefabs are picked up by their collections. Mods load after the mods they list in
+//! `after` or `requires`, otherwise ordered by name, a mod is skipped if one it requires is missing.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    asset::io::{file::FileAssetReader, AssetReader, AssetReaderError, AssetSource, AssetSourceId, PathStream, Reader},
+    tasks::futures_lite::{stream, StreamExt},
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{prelude::*, Semver, VERSION};
+
+pub const MANIFEST: &str = "mod.ron";
+
+/// Replaces the default asset source with `base` layered with the mods in `dir`, must be added before
+/// [`bevy::asset::AssetPlugin`].
+pub struct ModdingPlugin<F> {
+    pub dir: PathBuf,
+    pub base: F,
+}
+
+impl<F> ModdingPlugin<F>
+where
+    F: Fn() -> Box<dyn AssetReader> + Clone + Send + Sync + 'static,
+{
+    pub fn new(dir: impl Into<PathBuf>, base: F) -> Self {
+        Self { dir: dir.into(), base }
+    }
+}
+
+/// `mods/` next to the executable.
+pub fn default_directory() -> PathBuf {
+    FileAssetReader::get_base_path().join("mods")
+}
+
+impl<F> Plugin for ModdingPlugin<F>
+where
+    F: Fn() -> Box<dyn AssetReader> + Clone + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app_register_types!(ModManifest, res: Mods);
+        // Absolute so the manifests & the assets of the mods are read from the same directory, relative paths would
+        // be resolved against the working directory by one & the executable by the other.
+        let dir = self.dir.canonicalize().unwrap_or_else(|_| self.dir.clone());
+        let mods = match scan(&dir) {
+            Ok(manifests) => resolve(manifests),
+            Err(err) => {
+                warn!("failed to read mods in {}: {err:?}", dir.display());
+                vec![]
+            }
+        };
+        for (manifest, path) in &mods {
+            info!("loading mod {} {} from {}", manifest.name, manifest.version, path.display());
+        }
+
+        let base = self.base.clone();
+        let layers = mods.iter().map(|(_, path)| path.clone()).collect_vec();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build().with_reader(move || {
+                let mods = layers.iter().map(FileAssetReader::new).collect();
+                Box::new(ModAssetReader { base: base(), mods })
+            }),
+        );
+        app.insert_resource(Mods(mods.into_iter().map(|(manifest, _)| manifest).collect()));
+    }
+}
+
+/// The `mod.ron` of a mod.
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct ModManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Version of the game the mod was made for, the mod is skipped if it isn't compatible.
+    #[serde(default)]
+    pub game_version: Option<String>,
+    /// Mods to load before this one if present.
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Mods that have to be present, loaded before this one.
+    #[serde(default)]
+    pub requires: Vec<String>,
+}
+
+/// Mods in load order.
+#[derive(Resource, Reflect, Default, Clone, Debug, Deref)]
+#[reflect(Resource)]
+pub struct Mods(pub Vec<ModManifest>);
+
+/// Reads the manifests of the mods in `dir`, invalid mods are skipped.
+fn scan(dir: &Path) -> AnyResult<Vec<(ModManifest, PathBuf)>> {
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut mods = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let manifest = path.join(MANIFEST);
+        if !manifest.is_file() {
+            continue;
+        }
+        let parsed = std::fs::read_to_string(&manifest)
+            .context("failed to read manifest")
+            .and_then(|source| ron::from_str::<ModManifest>(&source).context("failed to parse manifest"));
+        match parsed {
+            Ok(manifest) => mods.push((manifest, path)),
+            Err(err) => warn!("skipping mod {}: {err:?}", path.display()),
+        }
+    }
+    Ok(mods)
+}
+
+/// Drops invalid mods & sorts the rest into load order.
+fn resolve(mut mods: Vec<(ModManifest, PathBuf)>) -> Vec<(ModManifest, PathBuf)> {
+    // Sorted by name first so the order doesn't depend on the file system, it has to match between peers.
+    mods.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+    mods.dedup_by(|(a, path), (b, _)| {
+        let duplicate = a.name == b.name;
+        if duplicate {
+            warn!("skipping mod {}, a mod named {} already exists", path.display(), a.name);
+        }
+        duplicate
+    });
+    mods.retain(|(manifest, _)| {
+        let Some(version) = &manifest.game_version else {
+            return true;
+        };
+        let compatible = version.parse::<Semver>().is_ok_and(|version| VERSION.is_compatible(&version));
+        if !compatible {
+            warn!("skipping mod {}, made for game version {version}", manifest.name);
+        }
+        compatible
+    });
+    // Requirements can be dropped in turn, repeat until nothing changes.
+    loop {
+        let names: HashSet<String> = mods.iter().map(|(manifest, _)| manifest.name.clone()).collect();
+        let count = mods.len();
+        mods.retain(|(manifest, _)| {
+            let missing = manifest.requires.iter().find(|name| !names.contains(*name));
+            if let Some(missing) = missing {
+                warn!("skipping mod {}, requires missing mod {missing}", manifest.name);
+            }
+            missing.is_none()
+        });
+        if mods.len() == count {
+            break;
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(mods.len());
+    while !mods.is_empty() {
+        let ready = mods.iter().position(|(manifest, _)| {
+            let mut dependencies = manifest.after.iter().chain(&manifest.requires);
+            !dependencies.any(|name| mods.iter().any(|(other, _)| other.name == *name))
+        });
+        let Some(ready) = ready else {
+            let names = mods.iter().map(|(manifest, _)| manifest.name.as_str()).join(", ");
+            warn!("skipping mods with cyclic load order: {names}");
+            break;
+        };
+        ordered.push(mods.remove(ready));
+    }
+    ordered
+}
+
+/// Reads assets from the mods, later mods first, falling back to `base`.
+struct ModAssetReader {
+    base: Box<dyn AssetReader>,
+    mods: Vec<FileAssetReader>,
+}
+
+impl ModAssetReader {
+    fn layers(&self) -> impl Iterator<Item = &dyn AssetReader> {
+        self.mods.iter().rev().map(|reader| reader as &dyn AssetReader).chain([&*self.base])
+    }
+}
+
+impl AssetReader for ModAssetReader {
+    fn read<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            for layer in self.layers() {
+                match layer.read(path).await {
+                    Err(AssetReaderError::NotFound(_)) => continue,
+                    result => return result,
+                }
+            }
+            Err(AssetReaderError::NotFound(path.to_owned()))
+        })
+    }
+
+    fn read_meta<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            for layer in self.layers() {
+                match layer.read_meta(path).await {
+                    Err(AssetReaderError::NotFound(_)) => continue,
+                    result => return result,
+                }
+            }
+            Err(AssetReaderError::NotFound(path.to_owned()))
+        })
+    }
+
+    fn read_directory<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        Box::pin(async move {
+            let mut paths = None::<Vec<PathBuf>>;
+            for layer in self.layers() {
+                match layer.read_directory(path).await {
+                    Ok(entries) => paths.get_or_insert_with(Vec::new).extend(entries.collect::<Vec<_>>().await),
+                    Err(AssetReaderError::NotFound(_)) => continue,
+                    Err(err) => return Err(err),
+                }
+            }
+            let paths = paths.ok_or_else(|| AssetReaderError::NotFound(path.to_owned()))?;
+            let paths: Box<PathStream> = Box::new(stream::iter(paths.into_iter().unique()));
+            Ok(paths)
+        })
+    }
+
+    fn is_directory<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        Box::pin(async move {
+            let mut found = false;
+            for layer in self.layers() {
+                match layer.is_directory(path).await {
+                    Ok(true) => return Ok(true),
+                    Ok(false) => found = true,
+                    Err(AssetReaderError::NotFound(_)) => continue,
+                    Err(err) => return Err(err),
+                }
+            }
+            if found {
+                Ok(false)
+            } else {
+                Err(AssetReaderError::NotFound(path.to_owned()))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::tasks::{block_on, futures_lite::AsyncReadExt};
+
+    use super::*;
+
+    fn manifest(name: &str, after: &[&str], requires: &[&str]) -> (ModManifest, PathBuf) {
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        let manifest = ModManifest {
+            name: name.to_owned(),
+            version: "0.1.0".to_owned(),
+            description: String::new(),
+            game_version: None,
+            after: names(after),
+            requires: names(requires),
+        };
+        (manifest, PathBuf::from(name))
+    }
+
+    fn names(mods: &[(ModManifest, PathBuf)]) -> Vec<&str> {
+        mods.iter().map(|(manifest, _)| manifest.name.as_str()).collect()
+    }
+
+    #[test]
+    fn mods_load_after_their_dependencies() {
+        let mods = resolve(vec![
+            manifest("c", &[], &[]),
+            manifest("a", &["c"], &[]),
+            manifest("b", &[], &["a"]),
+            manifest("d", &["missing"], &[]),
+        ]);
+        assert_eq!(names(&mods), ["c", "a", "b", "d"]);
+    }
+
+    #[test]
+    fn mods_with_missing_requirements_are_skipped() {
+        let mods = resolve(vec![manifest("a", &[], &["missing"]), manifest("b", &[], &["a"]), manifest("c", &[], &[])]);
+        assert_eq!(names(&mods), ["c"]);
+    }
+
+    #[test]
+    fn cyclic_mods_are_skipped() {
+        let mods = resolve(vec![manifest("a", &["b"], &[]), manifest("b", &["a"], &[]), manifest("c", &[], &[])]);
+        assert_eq!(names(&mods), ["c"]);
+    }
+
+    fn read(reader: &ModAssetReader, path: &str) -> Option<String> {
+        block_on(async {
+            let mut bytes = vec![];
+            reader.read(Path::new(path)).await.ok()?.read_to_end(&mut bytes).await.ok()?;
+            String::from_utf8(bytes).ok()
+        })
+    }
+
+    #[test]
+    fn later_mods_override_earlier_mods_and_the_base() {
+        let root = std::env::temp_dir().join(format!("motte_mods_{}", std::process::id()));
+        let files = [
+            ("base", "a.txt", "base"),
+            ("base", "b.txt", "base"),
+            ("one", "a.txt", "one"),
+            ("one", "c.txt", "one"),
+            ("two", "a.txt", "two"),
+        ];
+        for (layer, file, contents) in files {
+            std::fs::create_dir_all(root.join(layer)).unwrap();
+            std::fs::write(root.join(layer).join(file), contents).unwrap();
+        }
+
+        let reader = ModAssetReader {
+            base: Box::new(FileAssetReader::new(root.join("base"))),
+            mods: vec![FileAssetReader::new(root.join("one")), FileAssetReader::new(root.join("two"))],
+        };
+        assert_eq!(read(&reader, "a.txt").as_deref(), Some("two"));
+        assert_eq!(read(&reader, "b.txt").as_deref(), Some("base"));
+        assert_eq!(read(&reader, "c.txt").as_deref(), Some("one"));
+        assert_eq!(read(&reader, "d.txt"), None);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,27 +1,30 @@
 use bevy_xpbd_3d::{SubstepSchedule, SubstepSet};
 
-use self::motor::{DampingFactor, Jump, JumpHeight, MaxSlopeAngle, Movement};
+use self::motor::{DampingFactor, Jump, JumpHeight, MaxSlopeAngle, Movement, WaterVolume};
 use crate::{
     active_duration::{active_duration, ActiveDuration},
-    app_state::AppState,
+    app_state::simulating,
     movement::motor::{Airborne, Grounded, Moving, Stationary},
     prelude::*,
     stats::stat::StatPlugin,
+    timer::TimerPlugin,
 };
 
 pub mod motor;
 
-#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum MovementSystems {
-    Setup,
-    Motor,
-    State,
+ordered_sets! {
+    pub enum MovementSystems: FixedUpdate {
+        Setup,
+        Motor.before(PhysicsSet::Prepare),
+        State,
+    }
+    .run_if(simulating)
 }
 
 pub struct MovementPlugin;
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
-        app_register_types!(Movement, DampingFactor, MaxSlopeAngle, Jump, JumpHeight);
+        app_register_types!(Movement, DampingFactor, MaxSlopeAngle, Jump, JumpHeight, WaterVolume);
         app_register_types!(
             Stationary,
             Airborne,
@@ -33,18 +36,14 @@ impl Plugin for MovementPlugin {
             ActiveDuration<Moving>
         );
 
-        app.add_plugins(StatPlugin::<JumpHeight>::default());
+        app.add_plugins((StatPlugin::<JumpHeight>::default(), TimerPlugin::<Jump>::default()));
 
-        app.configure_sets(
-            FixedUpdate,
-            (MovementSystems::Setup, MovementSystems::Motor.before(PhysicsSet::Prepare), MovementSystems::State)
-                .chain()
-                .run_if(in_state(AppState::InGame)),
-        );
+        MovementSystems::configure(app);
 
         app.add_systems(
             FixedUpdate,
-            (motor::jumping, (motor::gravity, motor::movement, motor::damping).chain()).in_set(MovementSystems::Motor),
+            (motor::jumping, (motor::gravity, motor::movement, motor::damping, motor::water).chain())
+                .in_set(MovementSystems::Motor),
         );
 
         app.add_systems(SubstepSchedule, motor::collisions.in_set(SubstepSet::SolveUserConstraints));
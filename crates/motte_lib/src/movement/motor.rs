@@ -1,4 +1,12 @@
-use crate::{physics::CollisionLayer, prelude::*};
+use crate::{
+    navigation::flow_field::{
+        fields::surface::{Surface, SurfaceField},
+        layout::FieldLayout,
+    },
+    physics::{layers, triggers::TriggerVolume},
+    prelude::*,
+    timer::Cooldown,
+};
 
 #[derive(Component, Debug, Clone, Default, PartialEq, Reflect)]
 #[reflect(Component)]
@@ -19,14 +27,7 @@ impl CharacterMotor {
             damping: DampingFactor(0.9),
             max_slope_angle: MaxSlopeAngle(PI * 0.45),
             ground_caster: ShapeCaster::new(caster_shape, Vector::ZERO, Quaternion::default(), Direction3d::NEG_Y),
-            collision_layers: CollisionLayers::new(
-                [CollisionLayer::Units],
-                [
-                    CollisionLayer::Player, // ,CollisionLayer::Units
-                    CollisionLayer::Terrain,
-                    CollisionLayer::Sensor,
-                ],
-            ),
+            collision_layers: layers::unit_collider(),
             character_motor: default(),
         }
     }
@@ -81,11 +82,37 @@ pub struct Stationary;
 #[component(storage = "SparseSet")]
 pub struct Moving;
 
-pub(super) fn movement(time: Res<Time>, mut motors: Query<(&mut Movement, &mut LinearVelocity), With<CharacterMotor>>) {
+/// Slows down character motors inside the [`TriggerVolume`] of the entity, e.g. rivers & lakes.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct WaterVolume {
+    /// Damping of the horizontal velocity of motors inside, applied on top of their [`DampingFactor`].
+    pub damping: f32,
+}
+
+impl Default for WaterVolume {
+    fn default() -> Self {
+        Self { damping: 0.8 }
+    }
+}
+
+/// Accelerates motors by their [`Movement`], scaled by the [`Surface::speed_multiplier`] of the ground they're on.
+pub(super) fn movement(
+    time: Res<Time>,
+    surfaces: Option<Res<SurfaceField>>,
+    layout: Option<Res<FieldLayout>>,
+    mut motors: Query<(&mut Movement, &mut LinearVelocity, &Position), With<CharacterMotor>>,
+) {
     let delta_time: f32 = time.delta_seconds();
-    motors.par_iter_mut().for_each(|(mut movement, mut linvel)| {
-        linvel.x += movement.x * delta_time;
-        linvel.z += movement.y * delta_time;
+    let surfaces = surfaces.as_deref().zip(layout.as_deref());
+    motors.par_iter_mut().for_each(|(mut movement, mut linvel, position)| {
+        // Motors off the field aren't stuck on its void.
+        let multiplier = surfaces
+            .map(|(surfaces, layout)| surfaces.surface_at(layout, position.xz()))
+            .filter(|&surface| surface != Surface::Void)
+            .map_or(1.0, Surface::speed_multiplier);
+        linvel.x += movement.x * multiplier * delta_time;
+        linvel.z += movement.y * multiplier * delta_time;
         movement.reset();
     });
 }
@@ -97,6 +124,21 @@ pub(super) fn damping(mut motors: Query<(&DampingFactor, &mut LinearVelocity)>)
     });
 }
 
+pub(super) fn water(
+    volumes: Query<(&WaterVolume, &TriggerVolume)>,
+    mut motors: Query<&mut LinearVelocity, With<CharacterMotor>>,
+) {
+    for (water, volume) in &volumes {
+        for entity in volume.occupants() {
+            let Ok(mut linvel) = motors.get_mut(entity) else {
+                continue;
+            };
+            linvel.x *= water.damping;
+            linvel.z *= water.damping;
+        }
+    }
+}
+
 pub(super) fn gravity(
     time: Res<Time>,
     gravity: Res<Gravity>,
@@ -170,13 +212,21 @@ pub(super) fn collisions(
     }
 }
 
+/// Jumps of motors with a [`Cooldown<Jump>`] are ignored until it's ready.
 pub(super) fn jumping(
-    mut motors: Query<(&mut Jump, &JumpHeight, &mut LinearVelocity, Has<Grounded>), With<CharacterMotor>>,
+    mut motors: Query<
+        (&mut Jump, &JumpHeight, &mut LinearVelocity, Option<&mut Cooldown<Jump>>, Has<Grounded>),
+        With<CharacterMotor>,
+    >,
 ) {
-    motors.par_iter_mut().for_each(|(mut jump, jump_height, mut linvel, is_grounded)| {
+    motors.par_iter_mut().for_each(|(mut jump, jump_height, mut linvel, cooldown, is_grounded)| {
         if **jump {
-            if is_grounded {
+            let ready = cooldown.as_ref().map_or(true, |cooldown| cooldown.ready());
+            if is_grounded && ready {
                 linvel.y = jump_height.0;
+                if let Some(mut cooldown) = cooldown {
+                    cooldown.start();
+                }
             }
             jump.reset();
         }
@@ -1,10 +1,32 @@
 use std::marker::ConstParamTy;
 
-use super::flow_field::{footprint::Footprint, layout::CELL_SIZE, pathing::Goal};
-use crate::{movement::motor::Movement, prelude::*};
+use serde::Deserialize;
+
+use super::{
+    crowd::{Dormant, Idle},
+    flow_field::{
+        footprint::{Footprint, FootprintOrigin},
+        layout::CELL_SIZE,
+        pathing::Goal,
+    },
+};
+use crate::{config::GameConfig, movement::motor::Movement, prelude::*};
 
 #[derive(
-    Component, Default, Debug, ConstParamTy, Display, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect,
+    Component,
+    Default,
+    Debug,
+    ConstParamTy,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Reflect,
+    Deserialize,
 )]
 #[reflect(Component)]
 #[repr(u8)]
@@ -39,6 +61,34 @@ impl Agent {
     }
 }
 
+/// Expands `$body` once for every [`Agent`] size with `$agent` as a const of that size, largest-to-smallest like
+/// [`Agent::ALL`], into a tuple. Used to register systems & plugins that are generic over the agent size, e.g.
+/// `app.add_systems(Update, for_each_agent!(|AGENT| gizmos::<AGENT>))`.
+macro_rules! for_each_agent {
+    (|$agent:ident| $body:expr) => {
+        (
+            {
+                const $agent: $crate::navigation::agent::Agent = $crate::navigation::agent::Agent::Huge;
+                $body
+            },
+            {
+                const $agent: $crate::navigation::agent::Agent = $crate::navigation::agent::Agent::Large;
+                $body
+            },
+            {
+                const $agent: $crate::navigation::agent::Agent = $crate::navigation::agent::Agent::Medium;
+                $body
+            },
+            {
+                const $agent: $crate::navigation::agent::Agent = $crate::navigation::agent::Agent::Small;
+                $body
+            },
+        )
+    };
+}
+
+pub(crate) use for_each_agent;
+
 #[derive(Component, Default, Reflect)]
 pub struct AgentType<const AGENT: Agent>;
 
@@ -48,6 +98,39 @@ pub struct DesiredDirection(pub Option<Direction2d>);
 #[derive(Component, Debug, Clone, Copy, Deref, DerefMut, Default, Reflect)]
 pub struct DesiredVelocity(Vec2);
 
+/// Contributions to the [`DesiredVelocity`] of an agent this tick, blended by [`SteeringWeights`] in [`blend`].
+/// Directions are relative to the agent's speed.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct Steering {
+    /// Direction of the flow field.
+    pub flow: Vec2,
+    /// Correction of the flow velocity by RVO2 avoidance.
+    pub avoidance: Vec2,
+    /// Push away from overlapping neighbors by boids avoidance.
+    pub separation: Vec2,
+    /// Speed factor in `0.0..=1.0`, slowing down when close to the target.
+    pub arrival: f32,
+}
+
+/// How much each [`Steering`] contribution affects the [`DesiredVelocity`] of an agent, agents without the component
+/// use the default weights.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct SteeringWeights {
+    pub flow: f32,
+    pub avoidance: f32,
+    pub separation: f32,
+    /// `0.0` keeps full speed until the target is reached.
+    pub arrival: f32,
+}
+
+impl Default for SteeringWeights {
+    fn default() -> Self {
+        Self { flow: 1.0, avoidance: 1.0, separation: 1.0, arrival: 0.0 }
+    }
+}
+
 #[derive(Component, Default, Reflect)]
 #[component(storage = "SparseSet")]
 pub struct Blocking;
@@ -80,31 +163,66 @@ impl TargetReachedCondition {
 }
 
 pub(super) fn setup(mut commands: Commands, agents: Query<Entity, Added<Agent>>) {
+    let _span = info_span!("navigation::agent::setup").entered();
     for entity in &agents {
-        commands.entity(entity).insert((DesiredVelocity::default(), DesiredDirection(None), TargetDistance(0.0)));
+        commands.entity(entity).insert((
+            DesiredVelocity::default(),
+            DesiredDirection(None),
+            TargetDistance(0.0),
+            FootprintOrigin::default(),
+            Steering::default(),
+            Idle::default(),
+        ));
     }
 }
 
-type MovingAgents = (With<Agent>, Without<TargetReached>);
+type MovingAgents = (With<Agent>, Without<TargetReached>, Without<Dormant>);
 
 #[inline]
 pub(super) fn desired_velocity(
-    mut agents: Query<(Option<&DesiredDirection>, &Speed, &mut DesiredVelocity), MovingAgents>,
+    mut agents: Query<
+        (&Agent, Option<&DesiredDirection>, &TargetDistance, &Speed, &mut DesiredVelocity, &mut Steering),
+        MovingAgents,
+    >,
 ) {
-    agents.par_iter_mut().for_each(|(desired_direction, speed, mut desired_velocity)| {
-        if let Some(desired_direction) = desired_direction
-            && let Some(dir) = **desired_direction
-        {
-            **desired_velocity = dir.xy() * speed.value(); // (desired_velocity.lerp(velocity,
-                                                           // KSI)).clamp_length_max(speed.value());
-        } else {
-            desired_velocity.reset();
-        }
+    let _span = info_span!("navigation::agent::desired_velocity").entered();
+    /// Distance from the target in agent radii where agents start slowing down.
+    const ARRIVAL_RADII: f32 = 4.0;
+
+    agents.stable_par_iter_mut().for_each(
+        |(agent, desired_direction, target_distance, speed, mut desired_velocity, mut steering)| {
+            let flow = desired_direction.and_then(|direction| **direction).map_or(Vec2::ZERO, |dir| dir.xy());
+            // Avoidance & separation are kept, agents with a lower `AvoidanceQuality` skip updating them on some ticks.
+            steering.flow = flow;
+            steering.arrival = (**target_distance / (agent.radius() * ARRIVAL_RADII)).clamp(0.0, 1.0);
+            // The preferred velocity avoidance works from.
+            **desired_velocity = flow * speed.value();
+        },
+    );
+}
+
+/// Combines the [`Steering`] contributions into the [`DesiredVelocity`].
+pub(super) fn blend(
+    mut agents: Query<(&Steering, Option<&SteeringWeights>, &Speed, &mut DesiredVelocity), MovingAgents>,
+    config: Res<GameConfig>,
+) {
+    let _span = info_span!("navigation::agent::blend").entered();
+    let max_speed_multiplier = config.navigation.avoidance.max_speed_multiplier;
+
+    agents.stable_par_iter_mut().for_each(|(steering, weights, speed, mut desired_velocity)| {
+        let weights = weights.copied().unwrap_or_default();
+        let speed = speed.value();
+        let direction = steering.flow * weights.flow
+            + steering.avoidance * weights.avoidance
+            + steering.separation * weights.separation;
+        let arrival = 1.0 - weights.arrival * (1.0 - steering.arrival);
+        **desired_velocity = (direction * speed * arrival).clamp_length_max(max_speed_multiplier * speed);
     });
 }
 
 pub(super) fn apply_velocity(mut agents: Query<(&DesiredVelocity, &mut Movement), MovingAgents>) {
-    agents.par_iter_mut().for_each(|(desired_velocity, mut movement)| {
+    let _span = info_span!("navigation::agent::apply_velocity").entered();
+    agents.stable_par_iter_mut().for_each(|(desired_velocity, mut movement)| {
         if desired_velocity.is_approx_zero() {
             return;
         }
@@ -127,7 +245,8 @@ pub(super) fn target_reached(
         With<Agent>,
     >,
 ) {
-    agents.par_iter_mut().for_each(
+    let _span = info_span!("navigation::agent::target_reached").entered();
+    agents.stable_par_iter_mut().for_each(
         |(
             entity,
             agent,
@@ -156,13 +275,14 @@ pub(super) fn blocking(
     blocking: Query<Entity, (With<Agent>, Or<(Without<Goal>, With<TargetReached>)>, Without<Blocking>)>,
     pathing: Query<Entity, (With<Agent>, With<Goal>, Without<TargetReached>, With<Blocking>)>,
 ) {
-    blocking.par_iter().for_each(|entity| {
+    let _span = info_span!("navigation::agent::blocking").entered();
+    blocking.stable_par_iter().for_each(|entity| {
         commands.command_scope(|mut c| {
             c.entity(entity).insert((Footprint::default(), Blocking));
         });
     });
 
-    pathing.par_iter().for_each(|entity| {
+    pathing.stable_par_iter().for_each(|entity| {
         commands.command_scope(|mut c| {
             c.entity(entity).remove::<Footprint>().remove::<Blocking>();
         });
@@ -174,7 +294,8 @@ pub(super) fn agent_type<const AGENT: Agent>(
     agents: Query<(Entity, &Agent), (Changed<Agent>, Without<AgentType<AGENT>>)>,
     mut removed: RemovedComponents<Agent>,
 ) {
-    agents.par_iter().for_each(|(entity, agent)| {
+    let _span = info_span!("navigation::agent::agent_type", agent = %AGENT).entered();
+    agents.stable_par_iter().for_each(|(entity, agent)| {
         commands.command_scope(|mut c| {
             if *agent == AGENT {
                 c.entity(entity).insert(AgentType::<AGENT>);
@@ -192,9 +313,13 @@ pub(super) fn agent_type<const AGENT: Agent>(
 }
 
 #[cfg(feature = "dev_tools")]
-pub(crate) fn gizmos(mut gizmos: Gizmos, agents: Query<(&Agent, &GlobalTransform)>) {
+pub(crate) fn gizmos(mut gizmos: Gizmos, culling: GizmoCulling, agents: Query<(&Agent, &GlobalTransform)>) {
+    let mut culler = culling.layer();
     for (agent, transform) in &agents {
         let position = transform.translation();
+        if !culler.draw(position, 65) {
+            continue;
+        }
         gizmos.circle(position.x0z().y_pad(), Direction3d::Y, agent.radius(), Color::YELLOW);
         gizmos.line(position.x0z().y_pad(), position.x0z() + agent.height() * Vec3::Y, Color::YELLOW);
         gizmos.circle(position.x0z() + agent.height() * Vec3::Y, Direction3d::Y, agent.radius(), Color::YELLOW);
@@ -1,4 +1,6 @@
-//! Local Avoidance, currently using RVO2, implemented by https://lib.rs/crates/dodgy_2d
+//! Local Avoidance, the [`AvoidanceBackend`] resource selects between RVO2, implemented by
+//! https://lib.rs/crates/dodgy_2d, & a boids style separation. It can be switched while running to compare them on the
+//! same crowd.
 //! NOTE: Doesn't work exactly how I want it to, but it's a start.
 //! In the future I want to further explore the following:
 //! - https://www.jdxdev.com/blog/2021/03/19/boids-for-rts/
@@ -7,15 +9,35 @@
 //! - https://cell-devs-02.sce.carleton.ca/publications/2019/Hes19a/hesham-centroidalparticledynamicsanexplicitmodel_compressed.pdf
 //! - https://onlinelibrary.wiley.com/doi/full/10.1111/cgf.14737
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
+use bevy::diagnostic::Diagnostics;
 use bevy_spatial::{kdtree::KDTree3, SpatialAccess};
 
 use super::{
-    agent::{Agent, Blocking, DesiredVelocity, TargetDistance},
+    agent::{Agent, Blocking, DesiredVelocity, Speed, Steering, TargetDistance},
+    crowd::Dormant,
     flow_field::layout::FieldBorders,
 };
-use crate::{navigation::obstacle::Obstacle, prelude::*};
+use crate::{
+    config::GameConfig,
+    navigation::obstacle::{Obstacle, TemporaryObstacle},
+    prelude::*,
+    timings,
+};
+
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum AvoidanceBackend {
+    Disabled,
+    #[default]
+    Rvo2,
+    /// Agents are pushed apart by their neighbors, weighted by [`crate::config::AvoidanceConfig::separation_weight`].
+    Boids,
+}
 
 #[derive(Component, Debug, Deref, DerefMut, Clone)]
 pub(crate) struct DodgyAgent(Cow<'static, dodgy_2d::Agent>);
@@ -33,15 +55,144 @@ impl Default for DodgyAgent {
 #[derive(Component, Debug, Deref, DerefMut, Clone, Default)]
 pub(crate) struct DodgyObstacle(Option<Cow<'static, dodgy_2d::Obstacle>>);
 
+/// How much effort avoidance spends on an agent, assigned by [`AvoidanceQualityPlugin`] from the distance to the camera
+/// & whether the agent is selected. Agents without the component use [`AvoidanceQuality::High`].
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum AvoidanceQuality {
+    #[default]
+    High,
+    Medium,
+    Low,
+}
+
+impl AvoidanceQuality {
+    /// Max neighbors avoided, the closest are kept.
+    #[inline]
+    pub const fn max_neighbors(self) -> usize {
+        match self {
+            Self::High => usize::MAX,
+            Self::Medium => 8,
+            Self::Low => 4,
+        }
+    }
+
+    /// Scale of the avoidance time horizons.
+    #[inline]
+    pub const fn time_horizon_scale(self) -> f32 {
+        match self {
+            Self::High => 1.0,
+            Self::Medium => 0.75,
+            Self::Low => 0.5,
+        }
+    }
+
+    /// Ticks between avoidance updates, the previous avoidance is kept in between.
+    #[inline]
+    pub const fn interval(self) -> u32 {
+        match self {
+            Self::High => 1,
+            Self::Medium => 2,
+            Self::Low => 4,
+        }
+    }
+
+    /// Returns true if an agent is updated on `tick`, agents are spread over the ticks by their entity index.
+    #[inline]
+    pub const fn updates(self, entity: Entity, tick: u32) -> bool {
+        tick.wrapping_add(entity.index()) % self.interval() == 0
+    }
+}
+
+/// Assigns [`AvoidanceQuality`] to agents, not added with the `determinism` feature as the camera differs between
+/// peers.
+#[cfg(feature = "presentation")]
+pub struct AvoidanceQualityPlugin;
+
+#[cfg(feature = "presentation")]
+impl Plugin for AvoidanceQualityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            assign_quality.before(super::NavigationSystems::Avoidance).run_if(crate::app_state::simulating),
+        );
+    }
+}
+
+#[cfg(feature = "presentation")]
+fn assign_quality(
+    mut agents: Query<
+        (Entity, &GlobalTransform, Option<&mut AvoidanceQuality>, Option<&bevy_mod_picking::selection::PickSelection>),
+        With<Agent>,
+    >,
+    camera: Query<&GlobalTransform, With<crate::player::camera::MainCamera>>,
+    config: Res<GameConfig>,
+    commands: ParallelCommands,
+) {
+    let _span = info_span!("navigation::avoidance::assign_quality").entered();
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+    // Where the camera looks at the ground.
+    let forward = camera.forward();
+    let focus = if forward.y < 0.0 {
+        camera.translation() + forward * (-camera.translation().y / forward.y)
+    } else {
+        camera.translation()
+    };
+    let config = &config.navigation.avoidance;
+
+    agents.stable_par_iter_mut().for_each(|(entity, global_transform, quality, selection)| {
+        let distance = global_transform.translation().xz().distance(focus.xz());
+        let value =
+            if selection.is_some_and(|selection| selection.is_selected) || distance < config.high_quality_distance {
+                AvoidanceQuality::High
+            } else if distance < config.low_quality_distance {
+                AvoidanceQuality::Medium
+            } else {
+                AvoidanceQuality::Low
+            };
+
+        match quality {
+            Some(mut quality) => {
+                quality.set_if_neq(value);
+            }
+            None => commands.command_scope(|mut c| {
+                c.entity(entity).insert(value);
+            }),
+        }
+    });
+}
+
+/// Clears the contributions of the previous backend.
+pub(super) fn reset(mut agents: Query<&mut Steering>) {
+    let _span = info_span!("navigation::avoidance::reset").entered();
+    agents.stable_par_iter_mut().for_each(|mut steering| {
+        steering.avoidance = Vec2::ZERO;
+        steering.separation = Vec2::ZERO;
+    });
+}
+
 pub(super) fn rvo2(
-    mut agents: Query<(Entity, &Agent, &DodgyAgent, &mut DesiredVelocity)>,
+    mut agents: Query<
+        (Entity, &Agent, &DodgyAgent, &DesiredVelocity, &Speed, &mut Steering, Option<&AvoidanceQuality>),
+        Without<Dormant>,
+    >,
     other_agents: Query<&DodgyAgent, Without<Blocking>>,
     agents_kd_tree: Res<KDTree3<Agent>>,
     obstacles: Query<&DodgyObstacle>,
     field_borders: Res<FieldBorders>,
+    config: Res<GameConfig>,
     time: Res<Time>,
+    mut tick: Local<u32>,
+    mut diagnostics: Diagnostics,
 ) {
+    let _span = info_span!("navigation::avoidance::rvo2").entered();
+    *tick = tick.wrapping_add(1);
+    let tick = *tick;
     let delta_time = time.delta_seconds();
+    let config = &config.navigation.avoidance;
+    let avoidance_options = config.options();
 
     // TODO: only get nearby obstacles.
     let mut obstacles: Vec<Cow<'static, dodgy_2d::Obstacle>> =
@@ -49,58 +200,146 @@ pub(super) fn rvo2(
 
     obstacles.push(Cow::Owned(dodgy_2d::Obstacle::Open { vertices: (**field_borders).into() }));
 
-    agents.par_iter_mut().for_each(|(entity, agent, dodgy_agent, mut desired_velocity)| {
-        const fn neighborhood(agent: &Agent) -> f32 {
-            agent.radius() + Agent::LARGEST.radius()
+    let total = AtomicU32::new(0);
+    agents.stable_par_iter_mut().for_each(
+        |(entity, agent, dodgy_agent, desired_velocity, speed, mut steering, quality)| {
+            let quality = quality.copied().unwrap_or_default();
+            if !quality.updates(entity, tick) {
+                return;
+            }
+
+            const fn neighborhood(agent: &Agent) -> f32 {
+                agent.radius() + Agent::LARGEST.radius()
+            }
+
+            let neighborhood = neighborhood(agent) + config.neighborhood_padding;
+            let position = dodgy_agent.0.position;
+            #[allow(unused_mut)]
+            let mut nearby = agents_kd_tree.within_distance(position.x0y(), neighborhood);
+
+            // The avoidance result depends on the order of the neighbors.
+            #[cfg(feature = "determinism")]
+            nearby.sort_unstable_by_key(|(_, other)| *other);
+
+            let mut neighbors: SmallVec<[Cow<'static, dodgy_2d::Agent>; 16]> = nearby
+                .iter()
+                .filter_map(|(_, other)| {
+                    other.filter(|&other| other != entity).and_then(|other| other_agents.get(other).ok())
+                })
+                .filter(|other| other.0.position.distance(position) <= (agent.radius() + other.0.radius))
+                .map(|other| other.0.clone())
+                .collect();
+            let max_neighbors = quality.max_neighbors();
+            if neighbors.len() > max_neighbors {
+                let distance = |other: &Cow<'static, dodgy_2d::Agent>| other.position.distance_squared(position);
+                neighbors.select_nth_unstable_by(max_neighbors, |a, b| distance(a).total_cmp(&distance(b)));
+                neighbors.truncate(max_neighbors);
+            }
+            total.fetch_add(neighbors.len() as u32, Ordering::Relaxed);
+
+            let scale = quality.time_horizon_scale();
+            let options = dodgy_2d::AvoidanceOptions {
+                time_horizon: avoidance_options.time_horizon * scale,
+                obstacle_time_horizon: avoidance_options.obstacle_time_horizon * scale,
+                ..avoidance_options
+            };
+            let avoiding_velocity = dodgy_agent.compute_avoiding_velocity(
+                &neighbors,
+                &obstacles,
+                **desired_velocity,
+                config.max_speed_multiplier * desired_velocity.length(),
+                delta_time,
+                &options,
+            );
+            let speed = speed.value();
+            if speed > 0.0 {
+                steering.avoidance = (avoiding_velocity - **desired_velocity) / speed;
+            }
+        },
+    );
+    diagnostics.add_measurement(&timings::AVOIDANCE_NEIGHBORS, || total.into_inner() as f64);
+}
+
+pub(super) fn boids(
+    mut agents: Query<
+        (Entity, &Agent, &GlobalTransform, &mut Steering, Option<&AvoidanceQuality>),
+        (Without<Blocking>, Without<Dormant>),
+    >,
+    other_agents: Query<(&Agent, &GlobalTransform)>,
+    agents_kd_tree: Res<KDTree3<Agent>>,
+    config: Res<GameConfig>,
+    mut tick: Local<u32>,
+    mut diagnostics: Diagnostics,
+) {
+    let _span = info_span!("navigation::avoidance::boids").entered();
+    *tick = tick.wrapping_add(1);
+    let tick = *tick;
+    let config = &config.navigation.avoidance;
+    let total = AtomicU32::new(0);
+
+    agents.stable_par_iter_mut().for_each(|(entity, agent, global_transform, mut steering, quality)| {
+        let quality = quality.copied().unwrap_or_default();
+        if !quality.updates(entity, tick) {
+            return;
         }
 
-        let neighborhood = neighborhood(agent);
-        let position = dodgy_agent.0.position;
-        let neighbors: SmallVec<[Cow<'static, dodgy_2d::Agent>; 16]> = agents_kd_tree
-            .within_distance(position.x0y(), neighborhood)
+        let neighborhood = agent.radius() + Agent::LARGEST.radius() + config.neighborhood_padding;
+        let position = global_transform.translation().xz();
+
+        #[allow(unused_mut)]
+        let mut nearby = agents_kd_tree.within_distance(position.x0y(), neighborhood);
+
+        // Floating point sums depend on the order of the neighbors.
+        #[cfg(feature = "determinism")]
+        nearby.sort_unstable_by_key(|(_, other)| *other);
+
+        // Pushes away from each neighbor in range, with its distance to keep the nearest.
+        let mut pushes: SmallVec<[(f32, Vec2); 16]> = nearby
             .iter()
             .filter_map(|(_, other)| {
                 other.filter(|&other| other != entity).and_then(|other| other_agents.get(other).ok())
             })
-            .filter(|other| other.0.position.distance(position) <= (agent.radius() + other.0.radius))
-            .map(|other| other.0.clone())
+            .filter_map(|(other, other_transform)| {
+                let offset = position - other_transform.translation().xz();
+                let range = agent.radius() + other.radius() + config.neighborhood_padding;
+                let distance = offset.length();
+                (distance < range).then(|| (distance, offset.normalize_or_zero() * (1.0 - distance / range)))
+            })
             .collect();
+        let max_neighbors = quality.max_neighbors();
+        if pushes.len() > max_neighbors {
+            pushes.select_nth_unstable_by(max_neighbors, |(a, _), (b, _)| a.total_cmp(b));
+            pushes.truncate(max_neighbors);
+        }
+        total.fetch_add(pushes.len() as u32, Ordering::Relaxed);
+        let separation: Vec2 = pushes.iter().map(|&(_, push)| push).sum();
 
-        const AVOIDANCE_OPTIONS: dodgy_2d::AvoidanceOptions =
-            dodgy_2d::AvoidanceOptions { obstacle_margin: 0.1, time_horizon: 3.0, obstacle_time_horizon: 0.1 };
-
-        const MAX_SPEED_MULTIPLIER: f32 = 1.2;
-
-        **desired_velocity = dodgy_agent.compute_avoiding_velocity(
-            &neighbors,
-            &obstacles,
-            **desired_velocity,
-            MAX_SPEED_MULTIPLIER * desired_velocity.length(),
-            delta_time,
-            &AVOIDANCE_OPTIONS,
-        );
+        steering.separation = separation * config.separation_weight;
     });
+    diagnostics.add_measurement(&timings::AVOIDANCE_NEIGHBORS, || total.into_inner() as f64);
 }
 
 pub(super) fn setup(
     commands: ParallelCommands,
     agents: Query<Entity, (With<Agent>, Without<DodgyAgent>)>,
     blocking: Query<Entity, (With<Agent>, With<Blocking>, With<DodgyAgent>, Without<DodgyObstacle>)>,
-    obstacles: Query<Entity, (With<Obstacle>, Without<DodgyObstacle>)>,
+    // Temporary obstacles only raise the cost of the flow field.
+    obstacles: Query<Entity, (With<Obstacle>, Without<TemporaryObstacle>, Without<DodgyObstacle>)>,
 ) {
-    agents.par_iter().for_each(|entity| {
+    let _span = info_span!("navigation::avoidance::setup").entered();
+    agents.stable_par_iter().for_each(|entity| {
         commands.command_scope(|mut c| {
             c.entity(entity).insert(DodgyAgent::default());
         })
     });
 
-    blocking.par_iter().for_each(|entity| {
+    blocking.stable_par_iter().for_each(|entity| {
         commands.command_scope(|mut c| {
             c.entity(entity).insert(DodgyObstacle::default());
         })
     });
 
-    obstacles.par_iter().for_each(|entity| {
+    obstacles.stable_par_iter().for_each(|entity| {
         commands.command_scope(|mut c| {
             c.entity(entity).insert(DodgyObstacle::default());
         })
@@ -113,10 +352,11 @@ type DodgyAgentNeedsSync =
 pub(super) fn sync_agents(
     mut agents: Query<
         (&mut DodgyAgent, &Agent, &GlobalTransform, &LinearVelocity, Has<Blocking>, &TargetDistance),
-        DodgyAgentNeedsSync,
+        (DodgyAgentNeedsSync, Without<Dormant>),
     >,
 ) {
-    agents.par_iter_mut().for_each(
+    let _span = info_span!("navigation::avoidance::sync_agents").entered();
+    agents.stable_par_iter_mut().for_each(
         |(mut dodgy_agent, agent, global_transform, velocity, is_blocking, target_distance)| {
             let dodgy_agent = dodgy_agent.0.to_mut();
             dodgy_agent.position = global_transform.translation().xz();
@@ -141,7 +381,8 @@ pub(super) fn sync_agents(
 type DodgyObstacleNeedsSync = Or<(Added<DodgyObstacle>, Changed<Obstacle>, Changed<ColliderAabb>)>;
 
 pub(super) fn sync_obstacles(mut obstacles: Query<(&mut DodgyObstacle, &Obstacle), DodgyObstacleNeedsSync>) {
-    obstacles.par_iter_mut().for_each(|(mut dodgy_obstacle, obstacle)| {
+    let _span = info_span!("navigation::avoidance::sync_obstacles").entered();
+    obstacles.stable_par_iter_mut().for_each(|(mut dodgy_obstacle, obstacle)| {
         if let Some(obstacle) = obstacle.try_into_dodgy() {
             **dodgy_obstacle = Some(Cow::Owned(obstacle));
         } else {
@@ -156,7 +397,8 @@ type DodgyBlockingAgentNeedsSync =
 pub(super) fn sync_blocking(
     mut blocking: Query<(&mut DodgyObstacle, &GlobalTransform, &Agent), DodgyBlockingAgentNeedsSync>,
 ) {
-    blocking.par_iter_mut().for_each(|(mut dodgy_obstacle, global_transform, agent)| {
+    let _span = info_span!("navigation::avoidance::sync_blocking").entered();
+    blocking.stable_par_iter_mut().for_each(|(mut dodgy_obstacle, global_transform, agent)| {
         const SUBDIVISIONS: usize = 8;
         const fn circle_footprint(agent: &Agent, position: Vec2) -> [Vec2; SUBDIVISIONS] {
             use parry2d::na::SimdComplexField;
@@ -185,6 +427,7 @@ pub(super) fn cleanup(
     mut removed_obstacle: RemovedComponents<Obstacle>,
     mut removed_blocking: RemovedComponents<Blocking>,
 ) {
+    let _span = info_span!("navigation::avoidance::cleanup").entered();
     for entity in &mut removed_agents.read() {
         if let Some(mut commands) = commands.get_entity(entity) {
             commands.remove::<DodgyAgent>();
@@ -205,9 +448,13 @@ pub(super) fn cleanup(
 }
 
 #[cfg(feature = "dev_tools")]
-pub(crate) fn gizmos(mut gizmos: Gizmos, agents: Query<(&Agent, &DodgyAgent)>) {
+pub(crate) fn gizmos(mut gizmos: Gizmos, culling: GizmoCulling, agents: Query<(&Agent, &DodgyAgent)>) {
+    let mut culler = culling.layer();
     for (agent, dodgy_agent) in &agents {
         let position = dodgy_agent.0.position;
+        if !culler.draw(position.x0y(), 32) {
+            continue;
+        }
         gizmos.circle(position.x0y().y_pad(), Direction3d::Y, dodgy_agent.radius + 0.1, Color::PURPLE);
     }
 }
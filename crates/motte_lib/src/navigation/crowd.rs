@@ -0,0 +1,154 @@
+//! Puts idle agents far from any activity to sleep, see [`CrowdConfig`](crate::config::CrowdConfig).
+//!
+//! [`Dormant`] agents skip avoidance, flow sampling & footprint updates, and their [`CellIndex`] is only refreshed
+//! every [`CrowdConfig::dormant_interval`](crate::config::CrowdConfig::dormant_interval) ticks. They wake up when a
+//! moving agent comes near, when they're given a new [`Goal`] or when they're attacked or targeted by a spell.
+
+use bevy_spatial::{kdtree::KDTree3, SpatialAccess};
+
+use super::{
+    agent::{Agent, TargetReached},
+    flow_field::{layout::FieldLayout, pathing::Goal, CellIndex},
+};
+use crate::{
+    config::GameConfig,
+    events::GameEvent,
+    movement::motor::{Moving, Stationary},
+    prelude::*,
+};
+
+/// Marker for sleeping agents.
+#[derive(Component, Default, Reflect)]
+#[component(storage = "SparseSet")]
+pub struct Dormant;
+
+/// How long an agent has been stationary without a goal to reach.
+#[derive(Component, Clone, Copy, Deref, Default, Reflect)]
+pub struct Idle(Duration);
+
+pub(super) fn sleep(
+    commands: ParallelCommands,
+    mut agents: Query<
+        (Entity, &mut Idle, &GlobalTransform, Option<&Goal>, Has<TargetReached>, Has<Stationary>),
+        (With<Agent>, Without<Dormant>),
+    >,
+    active: Query<(), (With<Agent>, With<Moving>, Without<Dormant>)>,
+    agents_kd_tree: Res<KDTree3<Agent>>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let _span = info_span!("navigation::crowd::sleep").entered();
+    let config = &config.navigation.crowd;
+    let sleep_after = Duration::from_secs_f32(config.sleep_after);
+    let delta = time.delta();
+
+    agents.stable_par_iter_mut().for_each(|(entity, mut idle, global_transform, goal, target_reached, stationary)| {
+        let pathing = goal.is_some_and(|goal| *goal != Goal::None) && !target_reached;
+        if pathing || !stationary {
+            if idle.0 != Duration::ZERO {
+                idle.0 = Duration::ZERO;
+            }
+            return;
+        }
+
+        idle.0 = idle.0.saturating_add(delta);
+        if idle.0 < sleep_after {
+            return;
+        }
+
+        let nearby = agents_kd_tree.within_distance(global_transform.translation(), config.wake_distance);
+        if nearby.iter().any(|(_, other)| other.is_some_and(|other| active.contains(other))) {
+            return;
+        }
+
+        commands.command_scope(|mut c| {
+            c.entity(entity).insert(Dormant);
+        });
+    });
+}
+
+pub(super) fn wake(
+    commands: ParallelCommands,
+    dormant: Query<(), With<Dormant>>,
+    ordered: Query<Entity, (With<Dormant>, Changed<Goal>)>,
+    active: Query<&GlobalTransform, (With<Agent>, With<Moving>, Without<Dormant>)>,
+    agents_kd_tree: Res<KDTree3<Agent>>,
+    mut events: EventReader<GameEvent>,
+    config: Res<GameConfig>,
+) {
+    let _span = info_span!("navigation::crowd::wake").entered();
+    if dormant.is_empty() {
+        events.clear();
+        return;
+    }
+
+    let wake = |entity: Entity| {
+        commands.command_scope(|mut c| {
+            c.entity(entity).remove::<Dormant>().insert(Idle::default());
+        });
+    };
+
+    ordered.iter().for_each(wake);
+
+    for event in events.read() {
+        let target = match *event {
+            GameEvent::Attacked { target, .. } => Some(target),
+            GameEvent::SpellCast { target, .. } => target,
+            _ => None,
+        };
+        if let Some(target) = target
+            && dormant.contains(target)
+        {
+            wake(target);
+        }
+    }
+
+    let wake_distance = config.navigation.crowd.wake_distance;
+    active.stable_par_iter().for_each(|global_transform| {
+        for (_, other) in agents_kd_tree.within_distance(global_transform.translation(), wake_distance) {
+            if let Some(other) = other
+                && dormant.contains(other)
+            {
+                wake(other);
+            }
+        }
+    });
+}
+
+/// Refreshes the [`CellIndex`] of [`Dormant`] agents, spread over ticks by entity.
+pub(super) fn cell_index(
+    mut agents: Query<(Entity, &mut CellIndex, &GlobalTransform), With<Dormant>>,
+    layout: Res<FieldLayout>,
+    config: Res<GameConfig>,
+    mut tick: Local<u32>,
+) {
+    let _span = info_span!("navigation::crowd::cell_index").entered();
+    *tick = tick.wrapping_add(1);
+    let tick = *tick;
+    let interval = config.navigation.crowd.dormant_interval.max(1);
+
+    agents.stable_par_iter_mut().for_each(|(entity, mut cell_index, global)| {
+        if tick.wrapping_add(entity.index()) % interval != 0 {
+            return;
+        }
+        let cell = layout.cell(global.translation().xz());
+        let value = layout.index(cell).map(|index| CellIndex::Valid(cell, index)).unwrap_or(CellIndex::Invalid);
+        cell_index.set_if_neq(value);
+    });
+}
+
+#[cfg(feature = "dev_tools")]
+pub(crate) fn gizmos(
+    mut gizmos: Gizmos,
+    culling: GizmoCulling,
+    agents: Query<(&Agent, &GlobalTransform), With<Dormant>>,
+) {
+    let mut culler = culling.layer();
+    for (agent, transform) in &agents {
+        let position = transform.translation();
+        if !culler.draw(position, 65) {
+            continue;
+        }
+        gizmos.circle(position.x0z() + agent.height() * Vec3::Y, Direction3d::Y, agent.radius() * 0.5, Color::GRAY);
+    }
+}
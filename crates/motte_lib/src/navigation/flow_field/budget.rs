@@ -0,0 +1,138 @@
+//! Caps the time spent rebuilding dirty [`FlowField`]s per tick across all agent sizes, see
+//! [`FlowFieldConfig::build_budget`](crate::config::FlowFieldConfig::build_budget).
+//!
+//! Every tick the dirty fields of each size are collected, ordered by priority & the ones that fit in the budget are
+//! built, the rest stay dirty until a later tick. Fields that were never built come first, then the priority grows
+//! with the distance the goal moved since the last build, the number of agents using the field & the number of ticks
+//! the build has been deferred.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use bevy::diagnostic::Diagnostics;
+
+use super::{cache::FlowFieldCache, fields::flow::FlowField, pathing::Goal, CellIndex};
+use crate::{
+    config::GameConfig,
+    navigation::agent::{Agent, AgentType},
+    prelude::*,
+    timings,
+};
+
+/// Priority added per cell the goal moved since the last build.
+const MOVED_WEIGHT: u32 = 4;
+/// Priority added per tick the build has been deferred.
+const DEFERRED_WEIGHT: u32 = 8;
+/// Build time of a single field in microseconds until one has been measured.
+const INITIAL_ESTIMATE: f32 = 500.0;
+
+#[derive(Resource)]
+pub struct BuildScheduler {
+    candidates: Vec<Candidate>,
+    scheduled: HashSet<Entity>,
+    /// Moving average of the build time of a single field in microseconds.
+    estimate: f32,
+    spent: AtomicU64,
+    built: AtomicU32,
+}
+
+impl Default for BuildScheduler {
+    fn default() -> Self {
+        Self {
+            candidates: Vec::new(),
+            scheduled: HashSet::new(),
+            estimate: INITIAL_ESTIMATE,
+            spent: AtomicU64::new(0),
+            built: AtomicU32::new(0),
+        }
+    }
+}
+
+impl BuildScheduler {
+    /// Returns true if the field of `entity` should be built this tick.
+    #[inline]
+    pub fn scheduled(&self, entity: Entity) -> bool {
+        self.scheduled.contains(&entity)
+    }
+
+    /// Records the time a scheduled build took, can be called from parallel builds.
+    #[inline]
+    pub fn record(&self, elapsed: Duration) {
+        self.spent.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        self.built.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+struct Candidate {
+    entity: Entity,
+    unbuilt: bool,
+    priority: u32,
+}
+
+/// Collects the dirty fields of `AGENT`, with the number of agents using each.
+pub(in crate::navigation) fn collect<const AGENT: Agent>(
+    mut scheduler: ResMut<BuildScheduler>,
+    flow_fields: Query<(Entity, &FlowField<AGENT>, &CellIndex), With<Dirty<FlowField<AGENT>>>>,
+    agents: Query<&Goal, With<AgentType<AGENT>>>,
+    cache: Res<FlowFieldCache<AGENT>>,
+) {
+    let _span = info_span!("navigation::flow_field::budget::collect", agent = %AGENT).entered();
+    if flow_fields.is_empty() {
+        return;
+    }
+
+    let mut users = HashMap::<Entity, u32>::new();
+    for goal in &agents {
+        if let Some((entity, _)) = cache.get(goal) {
+            *users.entry(*entity).or_default() += 1;
+        }
+    }
+
+    for (entity, flow_field, cell_index) in &flow_fields {
+        let moved = match (flow_field.built_at(), cell_index) {
+            (Some(built), CellIndex::Valid(cell, _)) => built.chebyshev(*cell),
+            _ => 0,
+        };
+        let priority = users.get(&entity).copied().unwrap_or_default()
+            + moved.saturating_mul(MOVED_WEIGHT)
+            + flow_field.deferred().saturating_mul(DEFERRED_WEIGHT);
+        scheduler.candidates.push(Candidate { entity, unbuilt: flow_field.built_at().is_none(), priority });
+    }
+}
+
+/// Schedules the collected fields by priority until the estimated build time exceeds the budget, the field with the
+/// highest priority is always built.
+pub(in crate::navigation) fn plan(
+    mut scheduler: ResMut<BuildScheduler>,
+    config: Res<GameConfig>,
+    mut diagnostics: Diagnostics,
+) {
+    let _span = info_span!("navigation::flow_field::budget::plan").entered();
+    let scheduler = &mut *scheduler;
+    let spent = std::mem::take(scheduler.spent.get_mut());
+    let built = std::mem::take(scheduler.built.get_mut());
+    // Peers have to defer the same builds, so the estimate isn't updated from the wall time.
+    #[cfg(not(feature = "determinism"))]
+    if built > 0 {
+        let average = spent as f32 / built as f32;
+        scheduler.estimate += (average - scheduler.estimate) * 0.25;
+    }
+    #[cfg(feature = "determinism")]
+    let _ = (spent, built);
+
+    scheduler.scheduled.clear();
+    let candidates = &mut scheduler.candidates;
+    diagnostics.add_measurement(&timings::DIRTY_FIELDS, || candidates.len() as f64);
+    candidates.sort_unstable_by_key(|candidate| {
+        (std::cmp::Reverse((candidate.unbuilt, candidate.priority)), candidate.entity)
+    });
+
+    let budget = config.navigation.flow_field.build_budget as f32;
+    let mut estimated = 0.0;
+    for candidate in candidates.drain(..) {
+        if budget > 0.0 && !scheduler.scheduled.is_empty() && estimated + scheduler.estimate > budget {
+            break;
+        }
+        estimated += scheduler.estimate;
+        scheduler.scheduled.insert(candidate.entity);
+    }
+}
@@ -1,11 +1,16 @@
-use super::{fields::flow::FlowField, layout::FieldLayout, pathing::Goal, CellIndex};
+use super::{
+    corridor::{self, CorridorWindow},
+    fields::flow::FlowField,
+    layout::FieldLayout,
+    pathing::Goal,
+    CellIndex,
+};
 use crate::{
+    config::GameConfig,
     navigation::agent::{Agent, AgentType},
     prelude::*,
 };
 
-pub const CACHE_TTL_SEC: f32 = 30.0;
-
 #[derive(Resource, Default, Deref, DerefMut, Reflect)]
 pub struct FlowFieldCache<const AGENT: Agent>(HashMap<Goal, (Entity, Timer)>);
 
@@ -18,15 +23,21 @@ pub(super) enum Cached {
 
 pub(super) fn spawn<const AGENT: Agent>(
     mut commands: Commands,
-    agents: Query<&Goal, (Or<(Changed<Goal>, Changed<AgentType<AGENT>>)>, With<AgentType<AGENT>>)>,
+    agents: Query<(&Goal, &CellIndex), (Or<(Changed<Goal>, Changed<AgentType<AGENT>>)>, With<AgentType<AGENT>>)>,
     layout: Res<FieldLayout>,
     mut cache: ResMut<FlowFieldCache<AGENT>>,
+    config: Res<GameConfig>,
 ) {
-    for goal in &agents {
+    let _span = info_span!("navigation::flow_field::cache::spawn", agent = %AGENT).entered();
+    let cache_ttl = config.navigation.flow_field.cache_ttl;
+    for (goal, cell_index) in &agents {
         match cache.get_mut(goal) {
             Some((_, timer)) => {
                 timer.reset();
             }
+            // Distant agents use a corridor instead, see [`corridor::plan`].
+            None if let Goal::Cell(cell) = goal
+                && corridor::distant(cell_index, *cell, &config.navigation.flow_field) => {}
             None if let Goal::Cell(cell) = goal => {
                 let flow_field = commands
                     .spawn((
@@ -39,7 +50,7 @@ pub(super) fn spawn<const AGENT: Agent>(
                     ))
                     .id();
 
-                cache.insert_unique_unchecked(*goal, (flow_field, Timer::from_seconds(CACHE_TTL_SEC, TimerMode::Once)));
+                cache.insert_unique_unchecked(*goal, (flow_field, Timer::from_seconds(cache_ttl, TimerMode::Once)));
             }
             None if let Goal::Entity(entity) = goal => {
                 commands.entity(*entity).insert((
@@ -49,7 +60,7 @@ pub(super) fn spawn<const AGENT: Agent>(
                     Dirty::<FlowField<AGENT>>::default(),
                 ));
 
-                cache.insert_unique_unchecked(*goal, (*entity, Timer::from_seconds(CACHE_TTL_SEC, TimerMode::Once)));
+                cache.insert_unique_unchecked(*goal, (*entity, Timer::from_seconds(cache_ttl, TimerMode::Once)));
             }
             _ => {}
         }
@@ -59,13 +70,16 @@ pub(super) fn spawn<const AGENT: Agent>(
 pub(super) fn insert<const AGENT: Agent>(
     mut commands: Commands,
     mut cache: ResMut<FlowFieldCache<AGENT>>,
-    flow_fields: Query<Entity, (Added<FlowField<AGENT>>, Without<Cached>, Without<Disabled<FlowField<AGENT>>>)>,
+    flow_fields: Query<
+        Entity,
+        (Added<FlowField<AGENT>>, Without<Cached>, Without<CorridorWindow>, Without<Disabled<FlowField<AGENT>>>),
+    >,
+    config: Res<GameConfig>,
 ) {
+    let _span = info_span!("navigation::flow_field::cache::insert", agent = %AGENT).entered();
+    let cache_ttl = config.navigation.flow_field.cache_ttl;
     for entity in &flow_fields {
-        cache.insert_unique_unchecked(
-            Goal::Entity(entity),
-            (entity, Timer::from_seconds(CACHE_TTL_SEC, TimerMode::Once)),
-        );
+        cache.insert_unique_unchecked(Goal::Entity(entity), (entity, Timer::from_seconds(cache_ttl, TimerMode::Once)));
         commands.entity(entity).insert(Cached::Unmanaged);
     }
 }
@@ -75,6 +89,7 @@ pub(super) fn tick<const AGENT: Agent>(
     mut cache: ResMut<FlowFieldCache<AGENT>>,
     time: Res<Time>,
 ) {
+    let _span = info_span!("navigation::flow_field::cache::tick", agent = %AGENT).entered();
     for (_, (entity, _)) in cache.0.extract_if(|_, (_, timer)| timer.tick(time.delta()).just_finished()) {
         commands.entity(entity).insert(Disabled::<FlowField<AGENT>>::default());
     }
@@ -84,6 +99,7 @@ pub(super) fn despawn<const AGENT: Agent>(
     mut commands: Commands,
     flow_fields: Query<(Entity, &Cached), (With<FlowField<AGENT>>, With<Disabled<FlowField<AGENT>>>)>,
 ) {
+    let _span = info_span!("navigation::flow_field::cache::despawn", agent = %AGENT).entered();
     for (entity, cached) in &flow_fields {
         match cached {
             Cached::Managed => commands.entity(entity).despawn_recursive(),
@@ -0,0 +1,289 @@
+//! Corridors for agents with a [`Goal::Cell`] further than
+//! [`FlowFieldConfig::corridor_distance`](crate::config::FlowFieldConfig::corridor_distance) cells away.
+//!
+//! Instead of building a flow field of the whole layout, a coarse path is planned with A* on the [`ObstacleField`]
+//! downsampled to blocks of [`BLOCK_SIZE`] cells. The flow field of the corridor is owned by the agent, sized to a
+//! window around it & built towards the furthest cell of the path inside the window. The window slides forward as
+//! the agent progresses.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use super::{
+    cache::FlowFieldCache,
+    fields::{flow::FlowField, obstacle::ObstacleField, Cell, Field, Scalar},
+    layout::FieldLayout,
+    pathing::Goal,
+    CellIndex,
+};
+use crate::{
+    config::{FlowFieldConfig, GameConfig},
+    navigation::agent::{Agent, AgentType},
+    prelude::*,
+};
+
+/// Cells per side of a block of the downsampled obstacle field.
+const BLOCK_SIZE: Scalar = 8;
+/// Cost of moving to an adjacent block, diagonal moves cost [`DIAGONAL_COST`].
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+#[derive(Component, Reflect)]
+pub struct Corridor<const AGENT: Agent> {
+    /// Coarse path to the goal, the last cell is the goal.
+    path: Vec<Cell>,
+    /// Index of the cell of the path the window flows towards.
+    waypoint: usize,
+    /// Entity of the [`FlowField`] built within the window.
+    field: Entity,
+}
+
+impl<const AGENT: Agent> Corridor<AGENT> {
+    #[inline]
+    pub fn field(&self) -> Entity {
+        self.field
+    }
+}
+
+/// Inclusive bounds of the [`FlowField`] of a [`Corridor`].
+#[derive(Component, Reflect)]
+pub struct CorridorWindow {
+    owner: Entity,
+    center: Cell,
+    min: Cell,
+    max: Cell,
+}
+
+impl CorridorWindow {
+    #[inline]
+    pub fn bounds(&self) -> (Cell, Cell) {
+        (self.min, self.max)
+    }
+
+    #[inline]
+    fn contains(&self, cell: Cell) -> bool {
+        (self.min.x()..=self.max.x()).contains(&cell.x()) && (self.min.y()..=self.max.y()).contains(&cell.y())
+    }
+
+    #[inline]
+    fn clamp(&self, cell: Cell) -> Cell {
+        Cell::new(cell.x().clamp(self.min.x(), self.max.x()), cell.y().clamp(self.min.y(), self.max.y()))
+    }
+}
+
+/// Returns true if an agent at `cell_index` should use a [`Corridor`] to reach `goal`.
+#[inline]
+pub(super) fn distant(cell_index: &CellIndex, goal: Cell, config: &FlowFieldConfig) -> bool {
+    config.corridor_distance > 0
+        && matches!(cell_index, CellIndex::Valid(cell, _) if cell.chebyshev(goal) > config.corridor_distance)
+}
+
+/// Plans a corridor for agents given a distant goal that has no cached field.
+pub(super) fn plan<const AGENT: Agent>(
+    mut commands: Commands,
+    mut agents: Query<
+        (Entity, &Goal, &CellIndex, Option<&mut Corridor<AGENT>>),
+        (Or<(Changed<Goal>, Changed<AgentType<AGENT>>)>, With<AgentType<AGENT>>),
+    >,
+    stale: Query<Entity, (With<Corridor<AGENT>>, Without<AgentType<AGENT>>)>,
+    cache: Res<FlowFieldCache<AGENT>>,
+    obstacle_field: Option<Res<ObstacleField>>,
+    config: Res<GameConfig>,
+) {
+    let _span = info_span!("navigation::flow_field::corridor::plan", agent = %AGENT).entered();
+    for entity in &stale {
+        commands.entity(entity).remove::<Corridor<AGENT>>();
+    }
+
+    let config = &config.navigation.flow_field;
+    for (entity, goal, cell_index, corridor) in &mut agents {
+        let (Goal::Cell(target), CellIndex::Valid(cell, _)) = (goal, cell_index) else {
+            if corridor.is_some() {
+                commands.entity(entity).remove::<Corridor<AGENT>>();
+            }
+            continue;
+        };
+
+        if cache.contains_key(goal) || !distant(cell_index, *target, config) {
+            if corridor.is_some() {
+                commands.entity(entity).remove::<Corridor<AGENT>>();
+            }
+            continue;
+        }
+
+        let path = match &obstacle_field {
+            Some(obstacle_field) => coarse_path::<AGENT>(obstacle_field, *cell, *target),
+            None => vec![*target],
+        };
+
+        match corridor {
+            Some(mut corridor) => {
+                corridor.path = path;
+                corridor.waypoint = 0;
+            }
+            None => {
+                let field = commands
+                    .spawn((
+                        Name::new(format!("Corridor {:?}", entity)),
+                        FlowField::<AGENT>::default(),
+                        SpatialBundle::default(),
+                        CellIndex::default(),
+                        CorridorWindow { owner: entity, center: *cell, min: *cell, max: *cell },
+                    ))
+                    .id();
+                commands.entity(entity).insert(Corridor::<AGENT> { path, waypoint: 0, field });
+            }
+        }
+    }
+}
+
+/// Slides the window of a corridor when the agent leaves its center or gets close to the waypoint.
+pub(super) fn advance<const AGENT: Agent>(
+    mut commands: Commands,
+    mut agents: Query<(&mut Corridor<AGENT>, &CellIndex), Without<CorridorWindow>>,
+    mut windows: Query<(&mut CorridorWindow, &mut CellIndex, &mut Transform), With<FlowField<AGENT>>>,
+    layout: Res<FieldLayout>,
+    config: Res<GameConfig>,
+) {
+    let _span = info_span!("navigation::flow_field::corridor::advance", agent = %AGENT).entered();
+    let radius = config.navigation.flow_field.corridor_window.min(Scalar::MAX as u32) as Scalar;
+
+    for (mut corridor, cell_index) in &mut agents {
+        let CellIndex::Valid(cell, _) = *cell_index else {
+            continue;
+        };
+        let Ok((mut window, mut window_cell, mut transform)) = windows.get_mut(corridor.field) else {
+            continue;
+        };
+
+        let last = corridor.waypoint + 1 >= corridor.path.len();
+        let reached = !last && cell.chebyshev(corridor.path[corridor.waypoint]) <= radius as u32 / 2;
+        if !corridor.is_changed() && !reached && cell.chebyshev(window.center) <= radius as u32 / 2 {
+            continue;
+        }
+
+        window.center = cell;
+        window.min = Cell::new(cell.x().saturating_sub(radius), cell.y().saturating_sub(radius));
+        window.max = Cell::new(
+            cell.x().saturating_add(radius).min(layout.width().saturating_sub(1)),
+            cell.y().saturating_add(radius).min(layout.height().saturating_sub(1)),
+        );
+
+        let waypoint = (corridor.waypoint..corridor.path.len())
+            .rev()
+            .find(|&index| window.contains(corridor.path[index]))
+            .unwrap_or(corridor.waypoint);
+        corridor.bypass_change_detection().waypoint = waypoint;
+
+        // Flows towards the edge of the window when the agent was pushed off the path.
+        let goal = window.clamp(corridor.path[waypoint]);
+        window_cell.set_if_neq(layout.index(goal).map_or(CellIndex::Invalid, |index| CellIndex::Valid(goal, index)));
+        transform.translation = layout.position(goal).x0y();
+        commands.entity(corridor.field).insert(Dirty::<FlowField<AGENT>>::default());
+    }
+}
+
+/// Despawns the fields of corridors that were removed or replanned.
+pub(super) fn cleanup<const AGENT: Agent>(
+    mut commands: Commands,
+    windows: Query<(Entity, &CorridorWindow), With<FlowField<AGENT>>>,
+    corridors: Query<&Corridor<AGENT>>,
+) {
+    let _span = info_span!("navigation::flow_field::corridor::cleanup", agent = %AGENT).entered();
+    for (entity, window) in &windows {
+        if corridors.get(window.owner).map_or(true, |corridor| corridor.field != entity) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Plans a path of block centers from `from` to `to` with A* on the obstacle field downsampled to blocks of
+/// [`BLOCK_SIZE`] cells. Blocks without any cell traversable by `AGENT` are avoided & blocks cost more the less of
+/// their cells are traversable. Returns a straight path to `to` if there's no path.
+fn coarse_path<const AGENT: Agent>(obstacle_field: &ObstacleField, from: Cell, to: Cell) -> Vec<Cell> {
+    let (width, height) = (obstacle_field.width(), obstacle_field.height());
+    let block = |cell: Cell| Cell::new(cell.x() / BLOCK_SIZE, cell.y() / BLOCK_SIZE);
+    let center = |block: Cell| {
+        Cell::new(
+            (block.x() * BLOCK_SIZE).saturating_add(BLOCK_SIZE / 2).min(width.saturating_sub(1)),
+            (block.y() * BLOCK_SIZE).saturating_add(BLOCK_SIZE / 2).min(height.saturating_sub(1)),
+        )
+    };
+
+    let (start, goal) = (block(from), block(to));
+    if start == goal || !obstacle_field.valid(to) {
+        return vec![to];
+    }
+
+    let (blocks_width, blocks_height) = (width.div_ceil(BLOCK_SIZE), height.div_ceil(BLOCK_SIZE));
+    let len = blocks_width as usize * blocks_height as usize;
+
+    // Penalty of crossing each block, `None` if none of its cells are traversable.
+    let mut penalty: Field<Option<u32>> = Field::new(blocks_width, blocks_height, vec![None; len]);
+    for index in 0..len {
+        let block = penalty.cell_no_check(index);
+        let (x, y) = (block.x() * BLOCK_SIZE, block.y() * BLOCK_SIZE);
+        let cells = (y..y.saturating_add(BLOCK_SIZE).min(height))
+            .flat_map(|y| (x..x.saturating_add(BLOCK_SIZE).min(width)).map(move |x| Cell::new(x, y)));
+        let (total, traversable) = cells.fold((0, 0), |(total, traversable), cell| {
+            (total + 1, traversable + obstacle_field.traversable(cell, AGENT) as u32)
+        });
+        if traversable > 0 {
+            penalty[index] = Some(STRAIGHT_COST * (total - traversable) / total);
+        }
+    }
+    penalty[start] = penalty[start].or(Some(0));
+    penalty[goal] = penalty[goal].or(Some(0));
+
+    let heuristic = |block: Cell| {
+        let (dx, dy) = (block.x().abs_diff(goal.x()) as u32, block.y().abs_diff(goal.y()) as u32);
+        STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+    };
+
+    let mut costs: Field<u32> = Field::new(blocks_width, blocks_height, vec![u32::MAX; len]);
+    let mut previous: Field<Option<Cell>> = Field::new(blocks_width, blocks_height, vec![None; len]);
+    let mut open = BinaryHeap::new();
+    costs[start] = 0;
+    open.push(Reverse((heuristic(start), start)));
+
+    while let Some(Reverse((_, current))) = open.pop() {
+        if current == goal {
+            break;
+        }
+
+        let passable = |block: Cell| penalty.valid(block) && penalty[block].is_some();
+        for neighbor in penalty.neighbors(current) {
+            let Some(block_penalty) = penalty[neighbor] else {
+                continue;
+            };
+            let diagonal = neighbor.x() != current.x() && neighbor.y() != current.y();
+            if diagonal
+                && !(passable(Cell::new(neighbor.x(), current.y())) && passable(Cell::new(current.x(), neighbor.y())))
+            {
+                continue;
+            }
+
+            let step = if diagonal { DIAGONAL_COST } else { STRAIGHT_COST };
+            let cost = costs[current] + step + block_penalty;
+            if cost < costs[neighbor] {
+                costs[neighbor] = cost;
+                previous[neighbor] = Some(current);
+                open.push(Reverse((cost + heuristic(neighbor), neighbor)));
+            }
+        }
+    }
+
+    if previous[goal].is_none() {
+        return vec![to];
+    }
+
+    let mut path = vec![to];
+    let mut current = previous[goal];
+    while let Some(block) = current
+        && block != start
+    {
+        path.push(center(block));
+        current = previous[block];
+    }
+    path.reverse();
+    path
+}
@@ -2,12 +2,15 @@ use std::{cmp::Reverse, collections::BinaryHeap};
 
 use super::{
     obstacle::{ObstacleField, Occupant},
-    Cell, Direction, Field,
+    BitField, Cell, Direction, Field, NibbleField,
 };
 use crate::{
+    config::GameConfig,
     navigation::{
         agent::Agent,
         flow_field::{
+            budget::BuildScheduler,
+            corridor::CorridorWindow,
             footprint::{ExpandedFootprint, Footprint},
             layout::FieldLayout,
             CellIndex,
@@ -18,42 +21,150 @@ use crate::{
 
 #[derive(Component, Default, Reflect)]
 pub struct FlowField<const AGENT: Agent> {
-    flow: Field<Flow>,
+    /// [`Direction`] of the [`Flow`] of each cell.
+    directions: NibbleField,
+    /// Whether the [`Flow`] of each cell is [`Flow::Repulse`].
+    repulse: BitField,
     #[reflect(ignore)]
-    integration: Field<IntegrationCost>,
+    integration: IntegrationField,
     #[reflect(ignore)]
     heap: Heap,
+    /// Cell of the layout at the first index of the field, fields built within a window only cover the window.
+    origin: Cell,
+    /// Cell of the goal at the last build.
+    built_at: Option<Cell>,
+    /// Ticks the build has been deferred by the [`BuildScheduler`].
+    deferred: u32,
+    /// Elapsed fixed time at the last build.
+    built_time: Duration,
+    /// The goal moved less than the rebuild hysteresis since the last build.
+    pending: bool,
 }
 
 impl<const AGENT: Agent> FlowField<AGENT> {
     pub fn from_layout(layout: &FieldLayout) -> Self {
-        let len: usize = layout.len();
+        let (width, height) = (layout.width(), layout.height());
         Self {
-            flow: Field::new(layout.width(), layout.height(), vec![Flow::default(); len]),
-            integration: Field::new(layout.width(), layout.height(), vec![IntegrationCost::default(); len]),
-            heap: Heap::new(layout.width(), layout.height()),
+            directions: NibbleField::new(width, height, Direction::None as u8),
+            repulse: BitField::new(width, height, false),
+            integration: IntegrationField::new(width, height),
+            heap: Heap::new(width, height),
+            origin: Cell::ZERO,
+            built_at: None,
+            deferred: 0,
+            built_time: Duration::ZERO,
+            pending: false,
         }
     }
 
     #[inline]
-    pub fn build(&mut self, goals: impl Iterator<Item = Cell>, obstacle_field: &ObstacleField) {
-        debug_assert!(self.len() == obstacle_field.len());
+    pub fn built_at(&self) -> Option<Cell> {
+        self.built_at
+    }
+
+    #[inline]
+    pub fn deferred(&self) -> u32 {
+        self.deferred
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.directions.len()
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.directions.is_empty()
+    }
+
+    #[inline]
+    pub const fn origin(&self) -> Cell {
+        self.origin
+    }
+
+    #[inline]
+    pub fn valid(&self, cell: Cell) -> bool {
+        cell.x() >= self.origin.x() && cell.y() >= self.origin.y() && self.directions.valid(self.local(cell))
+    }
+
+    /// Returns the [`Flow`] of a [Cell]. Does not check if the cell is valid for the field.
+    #[inline]
+    pub fn flow(&self, cell: Cell) -> Flow {
+        let cell = self.local(cell);
+        Flow::from_parts(Direction::from_u8(self.directions.get(cell)), self.repulse.get(cell))
+    }
+
+    /// Returns the [Cell] of the layout at an index of the field.
+    #[inline]
+    pub const fn cell(&self, index: usize) -> Cell {
+        let cell = Cell::from_index(index, self.directions.width());
+        Cell::new(cell.x() + self.origin.x(), cell.y() + self.origin.y())
+    }
+
+    /// Returns the [Cell] of the field at a [Cell] of the layout. Does not check if the cell is within the field.
+    #[inline]
+    const fn local(&self, cell: Cell) -> Cell {
+        Cell::new(cell.x().wrapping_sub(self.origin.x()), cell.y().wrapping_sub(self.origin.y()))
+    }
 
-        let (flow, integration, heap) = (&mut self.flow, &mut self.integration, &mut self.heap);
-        for (cost, flow) in integration.iter_mut().zip(flow.iter_mut()) {
-            *cost = IntegrationCost::default();
-            *flow = Flow::default();
+    /// Resizes the field to the inclusive `(min, max)` bounds, the storage is only reallocated if the size changed.
+    fn fit(&mut self, (min, max): (Cell, Cell)) {
+        let (width, height) = (max.x() - min.x() + 1, max.y() - min.y() + 1);
+        self.origin = min;
+        if (self.directions.width(), self.directions.height()) != (width, height) {
+            self.directions = NibbleField::new(width, height, Direction::None as u8);
+            self.repulse = BitField::new(width, height, false);
+            self.integration = IntegrationField::new(width, height);
+            self.heap = Heap::new(width, height);
         }
+    }
+
+    /// Iterates the [`Flow`] of each cell in index order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Flow> + '_ {
+        self.directions
+            .iter()
+            .zip(self.repulse.iter())
+            .map(|(direction, repulse)| Flow::from_parts(Direction::from_u8(direction), repulse))
+    }
 
+    #[inline]
+    pub fn build(&mut self, goals: impl Iterator<Item = Cell>, obstacle_field: &ObstacleField) {
+        let max = Cell::new(obstacle_field.width().saturating_sub(1), obstacle_field.height().saturating_sub(1));
+        self.build_within(goals, obstacle_field, (Cell::ZERO, max));
+    }
+
+    /// Builds the flow of the cells within the inclusive `(min, max)` bounds. The field is resized to the bounds, so
+    /// only the cells within are reset & filled.
+    pub fn build_within(
+        &mut self,
+        goals: impl Iterator<Item = Cell>,
+        obstacle_field: &ObstacleField,
+        (min, max): (Cell, Cell),
+    ) {
+        if obstacle_field.is_empty() {
+            return;
+        }
+        debug_assert!(min.x() <= max.x() && min.y() <= max.y() && obstacle_field.valid(max));
+        self.fit((min, max));
+        let within = |cell: Cell| (min.x()..=max.x()).contains(&cell.x()) && (min.y()..=max.y()).contains(&cell.y());
+        // Storage is indexed relative to the bounds, the traversal works with cells of the layout.
+        let local = |cell: Cell| Cell::new(cell.x() - min.x(), cell.y() - min.y());
+        let global = |cell: Cell| Cell::new(cell.x() + min.x(), cell.y() + min.y());
+
+        let (directions, repulse, integration, heap) =
+            (&mut self.directions, &mut self.repulse, &mut self.integration, &mut self.heap);
+        directions.fill(Direction::None as u8);
+        repulse.fill(false);
+        integration.fill(IntegrationCost::default());
         heap.clear();
 
         for goal in goals.into_iter() {
-            if !flow.valid(goal) {
+            if !within(goal) {
                 continue;
             }
-            heap.push(goal, IntegrationCost::Goal);
-            integration[goal] = IntegrationCost::Goal;
-            flow[goal] = Flow::default();
+            heap.push(local(goal), IntegrationCost::Goal);
+            integration.set(local(goal), IntegrationCost::Goal);
         }
 
         let is_traversable = |cell: Cell| obstacle_field.traversable(cell, AGENT);
@@ -78,13 +189,16 @@ impl<const AGENT: Agent> FlowField<AGENT> {
         // FIXME: bug if goal is surrounded by agents, but some traversable cells in between, the flow will cant be
         // traversed.
         while let Some((cell, _)) = heap.pop() {
+            let cell = global(cell);
             let mut process = |neighbor: Cell| {
-                let current: IntegrationCost = integration[cell];
+                let current: IntegrationCost = integration.get(local(cell));
                 let cost = if is_traversable(neighbor) {
                     // Traversable
                     let distance = cell.manhattan(neighbor) as u8;
-                    IntegrationCost::Traversable(current.cost().saturating_add(distance))
-                } else if integration[neighbor] == IntegrationCost::Goal {
+                    let soft =
+                        obstacle_field.soft_cost(neighbor).saturating_add(obstacle_field.wall_cost(neighbor, AGENT));
+                    IntegrationCost::Traversable(current.cost().saturating_add(distance).saturating_add(soft))
+                } else if integration.get(local(neighbor)) == IntegrationCost::Goal {
                     // Goal
                     IntegrationCost::Goal
                 } else {
@@ -98,96 +212,161 @@ impl<const AGENT: Agent> FlowField<AGENT> {
                     }
                 };
 
-                if current.valid_traversal(cost) && cost < integration[neighbor] {
-                    integration[neighbor] = cost;
-                    if !heap.contains(neighbor) {
-                        heap.push(neighbor, cost);
+                if current.valid_traversal(cost) && cost < integration.get(local(neighbor)) {
+                    integration.set(local(neighbor), cost);
+                    if !heap.contains(local(neighbor)) {
+                        heap.push(local(neighbor), cost);
                     }
                 }
             };
 
-            for neighbor in obstacle_field.adjacent(cell) {
+            for neighbor in obstacle_field.adjacent(cell).filter(|&n| within(n)) {
                 process(neighbor);
             }
 
-            for neighbor in
-                obstacle_field.diagonal(cell).filter(|&n| is_diagonal_move_traversable(cell, cell.direction(n)))
+            for neighbor in obstacle_field
+                .diagonal(cell)
+                .filter(|&n| within(n) && is_diagonal_move_traversable(cell, cell.direction(n)))
             {
                 process(neighbor);
             }
         }
 
-        let width = integration.width();
-        for i in 0..integration.len() {
-            let cell = Cell::from_index(i, width);
-            let cost = integration[i];
-            if let Some(min) = integration
+        for cell in (min.y()..=max.y()).flat_map(|y| (min.x()..=max.x()).map(move |x| Cell::new(x, y))) {
+            let cost = integration.get(local(cell));
+            if let Some(min) = obstacle_field
                 .adjacent(cell)
-                .chain(integration.diagonal(cell).filter(|&n| is_diagonal_move_traversable(cell, cell.direction(n))))
-                .filter(|&n| cost.valid_flow_candidate(integration[n]))
-                .min_by(|a, b| integration[*a].cmp(&integration[*b]))
+                .chain(obstacle_field.diagonal(cell).filter(|&n| is_diagonal_move_traversable(cell, cell.direction(n))))
+                .filter(|&n| within(n) && cost.valid_flow_candidate(integration.get(local(n))))
+                .min_by_key(|&n| integration.get(local(n)))
             {
-                flow[cell] = match cost {
-                    IntegrationCost::Blocked(_, _) | IntegrationCost::Occupied(_, _) => {
-                        Flow::Repulse(cell.direction(min))
-                    }
-                    IntegrationCost::Goal | IntegrationCost::Traversable(_) => Flow::Toward(cell.direction(min)),
-                }
+                let blocked = matches!(cost, IntegrationCost::Blocked(_, _) | IntegrationCost::Occupied(_, _));
+                directions.set(local(cell), cell.direction(min) as u8);
+                repulse.set(local(cell), blocked);
             }
         }
     }
 }
 
-impl<const AGENT: Agent> std::ops::Deref for FlowField<AGENT> {
-    type Target = Field<Flow>;
-    fn deref(&self) -> &Self::Target {
-        &self.flow
+#[cfg(feature = "dev_tools")]
+impl<const AGENT: Agent> FlowField<AGENT> {
+    /// Integration cost of each cell normalized to `0.0..=1.0`, `None` for blocked or occupied cells.
+    pub(crate) fn normalized_costs(&self) -> impl Iterator<Item = Option<f32>> + '_ {
+        let traversable =
+            |cost: &IntegrationCost| matches!(cost, IntegrationCost::Goal | IntegrationCost::Traversable(_));
+        let max = self.integration.iter().filter(|cost| traversable(cost)).map(|cost| cost.cost()).max();
+        let max = max.unwrap_or_default().max(1) as f32;
+        self.integration.iter().map(move |cost| traversable(&cost).then(|| cost.cost() as f32 / max))
+    }
+}
+
+/// [`IntegrationCost`] of each cell stored as struct-of-arrays, so scans only touch the bytes they compare.
+#[derive(Clone, Default)]
+struct IntegrationField {
+    kind: Field<CostKind>,
+    depth: Field<u8>,
+    cost: Field<u8>,
+}
+
+impl IntegrationField {
+    #[inline]
+    fn new(width: super::Scalar, height: super::Scalar) -> Self {
+        let len = width as usize * height as usize;
+        let (kind, depth, cost) = IntegrationCost::default().parts();
+        Self {
+            kind: Field::new(width, height, vec![kind; len]),
+            depth: Field::new(width, height, vec![depth; len]),
+            cost: Field::new(width, height, vec![cost; len]),
+        }
+    }
+
+    #[inline]
+    fn get(&self, cell: Cell) -> IntegrationCost {
+        self.at(self.kind.index_no_check(cell))
+    }
+
+    #[inline]
+    fn at(&self, index: usize) -> IntegrationCost {
+        let (depth, cost) = (self.depth[index], self.cost[index]);
+        match self.kind[index] {
+            CostKind::Blocked => IntegrationCost::Blocked(depth, cost),
+            CostKind::Occupied => IntegrationCost::Occupied(depth, cost),
+            CostKind::Traversable => IntegrationCost::Traversable(cost),
+            CostKind::Goal => IntegrationCost::Goal,
+        }
+    }
+
+    #[inline]
+    fn set(&mut self, cell: Cell, value: IntegrationCost) {
+        let index = self.kind.index_no_check(cell);
+        let (kind, depth, cost) = value.parts();
+        self.kind[index] = kind;
+        self.depth[index] = depth;
+        self.cost[index] = cost;
+    }
+
+    #[inline]
+    fn fill(&mut self, value: IntegrationCost) {
+        let (kind, depth, cost) = value.parts();
+        self.kind.fill(kind);
+        self.depth.fill(depth);
+        self.cost.fill(cost);
+    }
+
+    #[inline]
+    fn iter(&self) -> impl Iterator<Item = IntegrationCost> + '_ {
+        (0..self.kind.len()).map(|index| self.at(index))
     }
 }
 
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[repr(u8)]
+enum CostKind {
+    #[default]
+    Blocked,
+    Occupied,
+    Traversable,
+    Goal,
+}
+
 #[derive(Clone, Default)]
 struct Heap {
     heap: BinaryHeap<Reverse<(IntegrationCost, Cell)>>,
-    contains: Field<bool>,
+    contains: BitField,
 }
 
 impl Heap {
     #[inline]
     fn new(width: super::Scalar, height: super::Scalar) -> Self {
-        Self {
-            heap: BinaryHeap::new(),
-            contains: Field::new(width, height, vec![false; width as usize * height as usize]),
-        }
+        Self { heap: BinaryHeap::new(), contains: BitField::new(width, height, false) }
     }
 
     #[inline]
     fn push(&mut self, cell: Cell, cost: IntegrationCost) {
         self.heap.push(Reverse((cost, cell)));
-        self.contains[cell] = true;
+        self.contains.set(cell, true);
     }
 
     #[inline]
     fn pop(&mut self) -> Option<(Cell, IntegrationCost)> {
         let Reverse((cost, cell)) = self.heap.pop()?;
-        self.contains[cell] = false;
+        self.contains.set(cell, false);
         Some((cell, cost))
     }
 
     #[inline]
     fn contains(&self, cell: Cell) -> bool {
-        self.contains[cell]
+        self.contains.get(cell)
     }
 
     #[inline]
     fn clear(&mut self) {
         self.heap.clear();
-        for cell in self.contains.iter_mut() {
-            *cell = false;
-        }
+        self.contains.fill(false);
     }
 }
 
-#[derive(Component, Clone, Copy, Default, Reflect)]
+#[derive(Component, Clone, Copy, Default, Debug, Reflect)]
 #[repr(u8)]
 pub enum Flow {
     // A direction towards the goal.
@@ -199,6 +378,16 @@ pub enum Flow {
 }
 
 impl Flow {
+    /// Creates a [`Flow`] from its direction & whether it repulses, [`Flow::None`] without a direction.
+    #[inline]
+    pub const fn from_parts(direction: Direction, repulse: bool) -> Self {
+        match (direction, repulse) {
+            (Direction::None, _) => Self::None,
+            (direction, true) => Self::Repulse(direction),
+            (direction, false) => Self::Toward(direction),
+        }
+    }
+
     #[inline]
     pub const fn direction(self) -> super::Direction {
         match self {
@@ -239,6 +428,18 @@ impl Default for IntegrationCost {
 }
 
 impl IntegrationCost {
+    /// Splits the cost into its kind, depth & cost as stored in [`IntegrationField`].
+    #[inline]
+    const fn parts(self) -> (CostKind, u8, u8) {
+        use IntegrationCost::*;
+        match self {
+            Blocked(d, c) => (CostKind::Blocked, d, c),
+            Occupied(d, c) => (CostKind::Occupied, d, c),
+            Traversable(c) => (CostKind::Traversable, 0, c),
+            Goal => (CostKind::Goal, 0, 0),
+        }
+    }
+
     #[inline]
     pub const fn cost(&self) -> u8 {
         use IntegrationCost::*;
@@ -342,19 +543,41 @@ impl Ord for IntegrationCost {
 pub(in crate::navigation) fn build<const AGENT: Agent>(
     commands: ParallelCommands,
     mut flow_fields: Query<
-        (Entity, &mut FlowField<AGENT>, &CellIndex, Option<&ExpandedFootprint<AGENT>>),
+        (Entity, &mut FlowField<AGENT>, &CellIndex, Option<&ExpandedFootprint<AGENT>>, Option<&CorridorWindow>),
         With<Dirty<FlowField<AGENT>>>,
     >,
     obstacle_field: Res<ObstacleField>,
+    scheduler: Res<BuildScheduler>,
+    time: Res<Time>,
 ) {
-    flow_fields.par_iter_mut().for_each(|(entity, mut flow_field, cell_index, footprint)| {
+    let _span = info_span!("navigation::flow_field::flow::build", agent = %AGENT).entered();
+    flow_fields.stable_par_iter_mut().for_each(|(entity, mut flow_field, cell_index, footprint, window)| {
+        if !scheduler.scheduled(entity) {
+            // Only rebuilds should count as a change of the field.
+            flow_field.bypass_change_detection().deferred += 1;
+            return;
+        }
+        let _span = info_span!("navigation::flow_field::flow::build_field", agent = %AGENT, ?entity).entered();
+
         let goals = match footprint {
             Some(ExpandedFootprint::Cells(cells)) => cells.iter().cloned().collect_vec(),
             None if let CellIndex::Valid(cell, _) = cell_index => vec![*cell],
             _ => return,
         };
 
-        flow_field.build(goals.into_iter(), &obstacle_field);
+        let start = Instant::now();
+        match window {
+            Some(window) => flow_field.build_within(goals.into_iter(), &obstacle_field, window.bounds()),
+            None => flow_field.build(goals.into_iter(), &obstacle_field),
+        }
+        scheduler.record(start.elapsed());
+        flow_field.built_at = match cell_index {
+            CellIndex::Valid(cell, _) => Some(*cell),
+            CellIndex::Invalid => None,
+        };
+        flow_field.deferred = 0;
+        flow_field.built_time = time.elapsed();
+        flow_field.pending = false;
 
         commands.command_scope(|mut c| {
             c.entity(entity).remove::<Dirty<FlowField<AGENT>>>();
@@ -362,19 +585,39 @@ pub(in crate::navigation) fn build<const AGENT: Agent>(
     });
 }
 
+/// Marks fields whose goal moved as dirty, unless it moved less than
+/// [`FlowFieldConfig::rebuild_distance`](crate::config::FlowFieldConfig::rebuild_distance) cells within
+/// [`FlowFieldConfig::rebuild_interval`](crate::config::FlowFieldConfig::rebuild_interval) of the last build. Those
+/// are kept pending & rebuilt once either is exceeded.
 pub(in crate::navigation) fn moved<const AGENT: Agent>(
     commands: ParallelCommands,
-    flow_fields: Query<
-        Entity,
-        (
-            Or<(Changed<CellIndex>, Changed<Footprint>)>,
-            With<FlowField<AGENT>>,
-            Without<Dirty<FlowField<AGENT>>>,
-            Without<Disabled<FlowField<AGENT>>>,
-        ),
+    mut flow_fields: Query<
+        (Entity, &mut FlowField<AGENT>, Ref<CellIndex>, Option<Ref<Footprint>>),
+        (Without<Dirty<FlowField<AGENT>>>, Without<Disabled<FlowField<AGENT>>>),
     >,
+    time: Res<Time>,
+    config: Res<GameConfig>,
 ) {
-    flow_fields.par_iter().for_each(|entity| {
+    let _span = info_span!("navigation::flow_field::flow::moved", agent = %AGENT).entered();
+    let config = &config.navigation.flow_field;
+    let elapsed = time.elapsed();
+
+    flow_fields.stable_par_iter_mut().for_each(|(entity, mut flow_field, cell_index, footprint)| {
+        let moved = cell_index.is_changed() || footprint.is_some_and(|footprint| footprint.is_changed());
+        if !moved && !flow_field.pending {
+            return;
+        }
+
+        let distance = match (flow_field.built_at, *cell_index) {
+            (Some(built), CellIndex::Valid(cell, _)) => built.chebyshev(cell),
+            _ => u32::MAX,
+        };
+        let since_build = elapsed.saturating_sub(flow_field.built_time).as_secs_f32();
+        if distance < config.rebuild_distance && since_build < config.rebuild_interval {
+            flow_field.bypass_change_detection().pending = true;
+            return;
+        }
+
         commands.command_scope(|mut c| {
             c.entity(entity).insert(Dirty::<FlowField<AGENT>>::default());
         })
@@ -388,7 +631,8 @@ pub(in crate::navigation) fn changed<const AGENT: Agent>(
         (With<FlowField<AGENT>>, Without<Dirty<FlowField<AGENT>>>, Without<Disabled<FlowField<AGENT>>>),
     >,
 ) {
-    flow_fields.par_iter().for_each(|entity| {
+    let _span = info_span!("navigation::flow_field::flow::changed", agent = %AGENT).entered();
+    flow_fields.stable_par_iter().for_each(|entity| {
         commands.command_scope(|mut c| {
             c.entity(entity).insert(Dirty::<FlowField<AGENT>>::default());
         })
@@ -398,18 +642,22 @@ pub(in crate::navigation) fn changed<const AGENT: Agent>(
 #[cfg(feature = "dev_tools")]
 pub(crate) fn gizmos<const AGENT: Agent>(
     mut gizmos: Gizmos,
+    culling: GizmoCulling,
     layout: Res<FieldLayout>,
     flow_fields: Query<&FlowField<AGENT>>,
 ) {
     use crate::navigation::flow_field::layout::HALF_CELL_SIZE;
 
+    let mut culler = culling.layer();
     for flow_field in &flow_fields {
-        for (cell, &flow) in flow_field.iter().enumerate().map(|(i, cost)| (layout.cell_from_index(i), cost)) {
-            let position = layout.position(cell).x0y();
-            if let Some(direction) = flow.direction().as_direction2d() {
+        for (index, flow) in flow_field.iter().enumerate() {
+            let position = layout.position(flow_field.cell(index)).x0y();
+            if let Some(direction) = flow.direction().as_direction2d()
+                && culler.draw(position, 3)
+            {
                 let start = position;
                 let end = start + direction.x0y() * HALF_CELL_SIZE;
-                let color = match flow_field.integration[cell] {
+                let color = match flow_field.integration.at(index) {
                     IntegrationCost::Blocked(_, _) => Color::RED,
                     IntegrationCost::Occupied(_, _) => Color::ORANGE,
                     IntegrationCost::Traversable(_) => Color::GRAY,
@@ -2,6 +2,7 @@ use std::ops::{Deref, DerefMut, Index, IndexMut};
 
 pub mod flow;
 pub mod obstacle;
+pub mod surface;
 
 use crate::prelude::*;
 
@@ -225,6 +226,29 @@ pub enum Direction {
 }
 
 impl Direction {
+    /// Every direction ordered by their discriminant.
+    pub const ALL: [Self; 9] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+        Self::None,
+    ];
+
+    /// Returns the direction of a discriminant, [`Direction::None`] if out of range.
+    #[inline]
+    pub const fn from_u8(value: u8) -> Self {
+        if (value as usize) < Self::ALL.len() {
+            Self::ALL[value as usize]
+        } else {
+            Self::None
+        }
+    }
+
     #[inline]
     pub fn from_vec(vec: Vec2) -> Self {
         let normalized = vec.normalize_or_zero();
@@ -410,6 +434,15 @@ impl<T> Field<T> {
         )
     }
 
+    /// Sets every cell to `value`.
+    #[inline]
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        self.data.fill(value);
+    }
+
     #[inline]
     pub fn resize(&mut self, width: Scalar, height: Scalar)
     where
@@ -466,3 +499,141 @@ impl<T> IndexMut<usize> for Field<T> {
         &mut self.data[index]
     }
 }
+
+/// A 2-dimensional field of bits, packed 64 cells per word.
+#[derive(Default, Clone, Reflect)]
+pub struct BitField {
+    width: Scalar,
+    height: Scalar,
+    words: Vec<u64>,
+}
+
+impl BitField {
+    /// Creates a new [BitField] with the given dimensions and every bit set to `value`.
+    pub fn new(width: Scalar, height: Scalar, value: bool) -> Self {
+        let len = width as usize * height as usize;
+        Self { width, height, words: vec![Self::word(value); len.div_ceil(u64::BITS as usize)] }
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bit of a [Cell]. Does not check if the cell is valid for the field.
+    #[inline]
+    pub fn get(&self, cell: Cell) -> bool {
+        let index = cell.index(self.width);
+        debug_assert!(index < self.len());
+        (self.words[index / u64::BITS as usize] >> (index % u64::BITS as usize)) & 1 == 1
+    }
+
+    /// Sets the bit of a [Cell]. Does not check if the cell is valid for the field.
+    #[inline]
+    pub fn set(&mut self, cell: Cell, value: bool) {
+        let index = cell.index(self.width);
+        debug_assert!(index < self.len());
+        let (word, bit) = (&mut self.words[index / u64::BITS as usize], index % u64::BITS as usize);
+        *word = (*word & !(1 << bit)) | ((value as u64) << bit);
+    }
+
+    /// Sets every bit to `value`.
+    #[inline]
+    pub fn fill(&mut self, value: bool) {
+        self.words.fill(Self::word(value));
+    }
+
+    /// Iterates the bits in index order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.words.iter().flat_map(|&word| (0..u64::BITS).map(move |bit| (word >> bit) & 1 == 1)).take(self.len())
+    }
+
+    /// A word with every bit set to `value`.
+    #[inline]
+    const fn word(value: bool) -> u64 {
+        (value as u64).wrapping_neg()
+    }
+}
+
+/// A 2-dimensional field of 4 bit values, packed 2 cells per byte.
+#[derive(Default, Clone, Reflect)]
+pub struct NibbleField {
+    width: Scalar,
+    height: Scalar,
+    bytes: Vec<u8>,
+}
+
+impl NibbleField {
+    /// Creates a new [NibbleField] with the given dimensions and every value set to `value`.
+    pub fn new(width: Scalar, height: Scalar, value: u8) -> Self {
+        let len = width as usize * height as usize;
+        Self { width, height, bytes: vec![Self::byte(value); len.div_ceil(2)] }
+    }
+
+    #[inline]
+    pub const fn width(&self) -> Scalar {
+        self.width
+    }
+
+    #[inline]
+    pub const fn height(&self) -> Scalar {
+        self.height
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub const fn valid(&self, cell: Cell) -> bool {
+        cell.x() < self.width && cell.y() < self.height
+    }
+
+    /// Returns the value of a [Cell]. Does not check if the cell is valid for the field.
+    #[inline]
+    pub fn get(&self, cell: Cell) -> u8 {
+        let index = cell.index(self.width);
+        debug_assert!(index < self.len());
+        (self.bytes[index / 2] >> ((index % 2) * 4)) & 0xF
+    }
+
+    /// Sets the value of a [Cell], only the lower 4 bits of `value` are kept. Does not check if the cell is valid for
+    /// the field.
+    #[inline]
+    pub fn set(&mut self, cell: Cell, value: u8) {
+        let index = cell.index(self.width);
+        debug_assert!(index < self.len());
+        let (byte, shift) = (&mut self.bytes[index / 2], (index % 2) * 4);
+        *byte = (*byte & !(0xF << shift)) | ((value & 0xF) << shift);
+    }
+
+    /// Sets every value to `value`.
+    #[inline]
+    pub fn fill(&mut self, value: u8) {
+        self.bytes.fill(Self::byte(value));
+    }
+
+    /// Iterates the values in index order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.bytes.iter().flat_map(|&byte| [byte & 0xF, byte >> 4]).take(self.len())
+    }
+
+    /// A byte with both values set to `value`.
+    #[inline]
+    const fn byte(value: u8) -> u8 {
+        (value & 0xF) * 0x11
+    }
+}
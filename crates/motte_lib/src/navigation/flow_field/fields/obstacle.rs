@@ -2,63 +2,185 @@ use crate::{
     navigation::{
         agent::{Agent, Blocking},
         flow_field::{
-            fields::{Cell, Field},
+            fields::{BitField, Cell, Direction, Field, NibbleField},
             footprint::{ExpandedFootprint, Footprint},
             layout::{FieldBounds, FieldLayout},
         },
-        obstacle::Obstacle,
+        obstacle::{Obstacle, TemporaryObstacle},
     },
     prelude::*,
 };
 
+/// Highest soft cost of a cell, added to the cost of traversing it.
+pub const SOFT_COST_MAX: u8 = 8;
+
+/// Highest clearance stored, cells further away from any blocked cell have this clearance.
+pub const CLEARANCE_MAX: u8 = 15;
+
+/// Cells with a clearance below this cost more to traverse, so flow keeps away from walls.
+pub const WALL_BIAS_CLEARANCE: u8 = 3;
+
+/// Neighbors visited before a cell when scanning rows from the first cell, & after when scanning from the last.
+const FORWARD: [Direction; 4] = [Direction::West, Direction::NorthWest, Direction::North, Direction::NorthEast];
+const BACKWARD: [Direction; 4] = [Direction::East, Direction::SouthEast, Direction::South, Direction::SouthWest];
+
+/// Traversability of each cell packed as one bit per cell for each clearance class, i.e. agent size, & whether it's
+/// occupied by an obstacle or an agent.
 #[derive(Resource, Clone, Reflect)]
 pub struct ObstacleField {
-    cost: Field<Cost>,
-    occupant: Field<Occupant>,
+    /// Dimensions of the field, holds no data.
+    shape: Field<()>,
+    /// Cells traversable by each agent size, indexed by [`clearance`].
+    traversable: [BitField; Agent::ALL.len()],
+    obstacle: BitField,
+    agent: BitField,
+    /// Cost added to traversing each cell, splatted by [`TemporaryObstacle`]s.
+    soft: Field<u8>,
+    /// Cells to the nearest cell not traversable by each agent size, indexed by [`clearance`]. Blocked cells have a
+    /// clearance of 0 & their neighbors 1, diagonals count as a single cell.
+    clearance: [NibbleField; Agent::ALL.len()],
 }
 
 impl ObstacleField {
     pub fn from_layout(layout: &FieldLayout) -> Self {
-        let len: usize = layout.len();
+        let (width, height) = (layout.width(), layout.height());
         Self {
-            cost: Field::new(layout.width(), layout.height(), vec![default(); len]),
-            occupant: Field::new(layout.width(), layout.height(), vec![default(); len]),
+            shape: Field::new(width, height, vec![(); layout.len()]),
+            traversable: std::array::from_fn(|_| BitField::new(width, height, true)),
+            obstacle: BitField::new(width, height, false),
+            agent: BitField::new(width, height, false),
+            soft: Field::new(width, height, vec![0; layout.len()]),
+            clearance: std::array::from_fn(|_| NibbleField::new(width, height, CLEARANCE_MAX)),
         }
     }
 
     #[inline]
     pub fn splat(&mut self, cells: &[Cell], cost: Cost, occupant: Occupant) {
+        let largest = match cost {
+            Cost::Blocked => None,
+            Cost::Traversable(agent) => Some(clearance(agent)),
+        };
+        let (obstacle, agent) = (occupant == Occupant::Obstacle, occupant == Occupant::Agent);
         for &cell in cells {
             if !self.valid(cell) {
                 continue;
             }
-            self.cost[cell] = cost;
-            self.occupant[cell] = occupant;
+            for (class, traversable) in self.traversable.iter_mut().enumerate() {
+                traversable.set(cell, largest.is_some_and(|largest| class <= largest));
+            }
+            self.obstacle.set(cell, obstacle);
+            self.agent.set(cell, agent);
+        }
+    }
+
+    /// Raises the soft cost of `cells` to `cost`, overlapping splats keep the highest.
+    #[inline]
+    pub fn splat_soft(&mut self, cells: &[Cell], cost: u8) {
+        for &cell in cells {
+            if let Some(index) = self.soft.index(cell) {
+                self.soft[index] = self.soft[index].max(cost.min(SOFT_COST_MAX));
+            }
         }
     }
 
+    #[inline]
+    pub fn soft_cost(&self, cell: Cell) -> u8 {
+        self.soft[cell]
+    }
+
     #[inline]
     pub fn traversable(&self, cell: Cell, agent_radius: Agent) -> bool {
-        self.cost[cell].traversable(agent_radius)
+        self.traversable[clearance(agent_radius)].get(cell)
+    }
+
+    /// Cells from `cell` to the nearest cell `agent_radius` can't traverse, up to [`CLEARANCE_MAX`]. Only up to date
+    /// after the splats, e.g. to validate spawn points or detect chokepoints as cells of low clearance.
+    #[inline]
+    pub fn clearance_at(&self, cell: Cell, agent_radius: Agent) -> u8 {
+        self.clearance[clearance(agent_radius)].get(cell)
+    }
+
+    /// Cost added to traversing `cell` for being near a cell `agent_radius` can't traverse.
+    #[inline]
+    pub fn wall_cost(&self, cell: Cell, agent_radius: Agent) -> u8 {
+        WALL_BIAS_CLEARANCE.saturating_sub(self.clearance_at(cell, agent_radius))
+    }
+
+    /// Recomputes the clearance of every cell from the traversable cells, a chessboard distance transform in two
+    /// passes over the rows.
+    pub fn update_clearance(&mut self) {
+        let shape = &self.shape;
+        let (width, height) = (shape.width(), shape.height());
+        for (traversable, clearance) in self.traversable.iter().zip(&mut self.clearance) {
+            for cell in (0..height).flat_map(|y| (0..width).map(move |x| Cell::new(x, y))) {
+                if traversable.get(cell) {
+                    clearance.set(cell, CLEARANCE_MAX);
+                    relax(shape, clearance, cell, &FORWARD);
+                } else {
+                    clearance.set(cell, 0);
+                }
+            }
+            for cell in (0..height).rev().flat_map(|y| (0..width).rev().map(move |x| Cell::new(x, y))) {
+                relax(shape, clearance, cell, &BACKWARD);
+            }
+        }
     }
 
     pub fn occupant(&self, cell: Cell) -> Occupant {
-        self.occupant[cell]
+        if self.obstacle.get(cell) {
+            Occupant::Obstacle
+        } else if self.agent.get(cell) {
+            Occupant::Agent
+        } else {
+            Occupant::Empty
+        }
+    }
+
+    /// Returns the [`Cost`] of a cell, the largest agent size it's traversable by.
+    pub fn cost(&self, cell: Cell) -> Cost {
+        Agent::ALL.into_iter().find(|&agent| self.traversable(cell, agent)).map_or(Cost::Blocked, Cost::Traversable)
     }
 
     #[inline]
     pub fn clear(&mut self) {
-        for i in 0..self.len() {
-            self.cost[i] = Cost::default();
-            self.occupant[i] = Occupant::Empty;
+        for traversable in &mut self.traversable {
+            traversable.fill(true);
+        }
+        self.obstacle.fill(false);
+        self.agent.fill(false);
+        self.soft.fill(0);
+        for clearance in &mut self.clearance {
+            clearance.fill(CLEARANCE_MAX);
         }
     }
 }
 
 impl std::ops::Deref for ObstacleField {
-    type Target = Field<Cost>;
+    type Target = Field<()>;
     fn deref(&self) -> &Self::Target {
-        &self.cost
+        &self.shape
+    }
+}
+
+/// Lowers the clearance of `cell` to one more than its lowest neighbor in `directions`.
+#[inline]
+fn relax(shape: &Field<()>, clearance: &mut NibbleField, cell: Cell, directions: &[Direction]) {
+    let nearest = directions
+        .iter()
+        .filter_map(|&direction| shape.neighbor(cell, direction))
+        .map(|neighbor| clearance.get(neighbor).saturating_add(1))
+        .fold(clearance.get(cell), u8::min);
+    clearance.set(cell, nearest);
+}
+
+/// Index of the clearance class of `agent`, smallest first.
+#[inline]
+const fn clearance(agent: Agent) -> usize {
+    match agent {
+        Agent::Small => 0,
+        Agent::Medium => 1,
+        Agent::Large => 2,
+        Agent::Huge => 3,
     }
 }
 
@@ -93,10 +215,12 @@ impl Cost {
 #[derive(Event, Reflect)]
 pub struct DirtyObstacleField;
 
-pub type ObstacleFilter = Or<((With<Obstacle>, With<Footprint>), (With<Agent>, With<Blocking>, With<Footprint>))>;
+pub type ObstacleFilter =
+    Or<((With<Obstacle>, With<Footprint>, Without<TemporaryObstacle>), (With<Agent>, With<Blocking>, With<Footprint>))>;
 
 #[inline]
 pub(in crate::navigation) fn clear(mut obstacle_field: ResMut<ObstacleField>) {
+    let _span = info_span!("navigation::flow_field::obstacle::clear").entered();
     obstacle_field.clear();
 }
 
@@ -106,6 +230,7 @@ pub(in crate::navigation) fn splat<const AGENT: Agent>(
     obstacles: Query<(&ExpandedFootprint<AGENT>, Has<Agent>), ObstacleFilter>,
     bounds: Res<FieldBounds<AGENT>>,
 ) {
+    let _span = info_span!("navigation::flow_field::obstacle::splat", agent = %AGENT).entered();
     for (expanded_footprint, is_agent) in &obstacles {
         if let ExpandedFootprint::Cells(cells) = expanded_footprint {
             obstacle_field.splat(
@@ -118,6 +243,25 @@ pub(in crate::navigation) fn splat<const AGENT: Agent>(
     obstacle_field.splat(&bounds, expanded_traversable(AGENT), Occupant::Obstacle);
 }
 
+#[inline]
+pub(in crate::navigation) fn update_clearance(mut obstacle_field: ResMut<ObstacleField>) {
+    let _span = info_span!("navigation::flow_field::obstacle::update_clearance").entered();
+    obstacle_field.update_clearance();
+}
+
+#[inline]
+pub(in crate::navigation) fn splat_temporary(
+    mut obstacle_field: ResMut<ObstacleField>,
+    obstacles: Query<(&Footprint, &TemporaryObstacle)>,
+) {
+    let _span = info_span!("navigation::flow_field::obstacle::splat_temporary").entered();
+    for (footprint, temporary) in &obstacles {
+        if let Some(cells) = footprint.cells() {
+            obstacle_field.splat_soft(cells, temporary.cost());
+        }
+    }
+}
+
 /// Cost of cells that exist in [`ExpandedFootprint<{ `agent` }>`].
 #[inline]
 const fn expanded_traversable(agent: Agent) -> Cost {
@@ -134,6 +278,7 @@ pub(in crate::navigation) fn changes<const AGENT: Agent>(
     mut event: EventWriter<DirtyObstacleField>,
     removed: RemovedComponents<ExpandedFootprint<AGENT>>,
 ) {
+    let _span = info_span!("navigation::flow_field::obstacle::changes", agent = %AGENT).entered();
     if !obstacles.is_empty() || !removed.is_empty() {
         event.send(DirtyObstacleField);
     }
@@ -142,19 +287,69 @@ pub(in crate::navigation) fn changes<const AGENT: Agent>(
 #[cfg(feature = "dev_tools")]
 pub(crate) fn gizmos<const AGENT: Agent>(
     mut gizmos: Gizmos,
+    culling: GizmoCulling,
     layout: Res<FieldLayout>,
     obstacle_field: Res<ObstacleField>,
 ) {
     use crate::navigation::flow_field::layout::CELL_SIZE_F32;
 
-    for (cell, cost) in obstacle_field.iter().enumerate().map(|(i, cost)| (layout.cell_from_index(i), cost)) {
+    let mut culler = culling.layer();
+    for cell in (0..obstacle_field.len()).map(|i| layout.cell_from_index(i)) {
         let position = layout.position(cell).x0y();
-        let color = match cost {
+        let color = match obstacle_field.cost(cell) {
             Cost::Blocked => Color::RED,
-            Cost::Traversable(radius) if radius == &Agent::LARGEST => Color::NONE,
-            Cost::Traversable(radius) if *radius < AGENT => Color::RED,
+            _ if obstacle_field.soft_cost(cell) > 0 => Color::ORANGE,
+            Cost::Traversable(radius) if radius == Agent::LARGEST => Color::NONE,
+            Cost::Traversable(radius) if radius < AGENT => Color::RED,
             _ => Color::NONE,
         };
+        if color == Color::NONE || !culler.draw(position, 4) {
+            continue;
+        }
         gizmos.rect(position.y_pad(), Quat::from_rotation_x(PI / 2.), Vec2::ONE / 1.5 * CELL_SIZE_F32, color);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(width: u8, height: u8, blocked: &[Cell]) -> ObstacleField {
+        let mut field = ObstacleField::from_layout(&FieldLayout::new(width, height));
+        field.splat(blocked, Cost::Blocked, Occupant::Obstacle);
+        field.update_clearance();
+        field
+    }
+
+    fn cells(width: u8, height: u8) -> impl Iterator<Item = Cell> {
+        (0..height).flat_map(move |y| (0..width).map(move |x| Cell::new(x, y)))
+    }
+
+    #[test]
+    fn open_field_has_max_clearance() {
+        let field = field(8, 8, &[]);
+        for cell in cells(8, 8) {
+            assert_eq!(field.clearance_at(cell, Agent::Small), CLEARANCE_MAX, "{cell:?}");
+        }
+    }
+
+    #[test]
+    fn clearance_is_chessboard_distance_to_walls() {
+        let wall = Cell::new(4, 3);
+        let field = field(9, 9, &[wall]);
+        for cell in cells(9, 9) {
+            let distance = cell.x().abs_diff(wall.x()).max(cell.y().abs_diff(wall.y()));
+            assert_eq!(field.clearance_at(cell, Agent::Small), distance, "{cell:?}");
+        }
+        assert_eq!(field.wall_cost(Cell::new(5, 4), Agent::Small), WALL_BIAS_CLEARANCE - 1);
+        assert_eq!(field.wall_cost(Cell::new(8, 8), Agent::Small), 0);
+    }
+
+    #[test]
+    fn clearance_is_capped() {
+        let field = field(32, 1, &[Cell::new(0, 0)]);
+        assert_eq!(field.clearance_at(Cell::new(10, 0), Agent::Small), 10);
+        assert_eq!(field.clearance_at(Cell::new(CLEARANCE_MAX, 0), Agent::Small), CLEARANCE_MAX);
+        assert_eq!(field.clearance_at(Cell::new(31, 0), Agent::Small), CLEARANCE_MAX);
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::render::primitives::Aabb;
+use serde::Deserialize;
+
+use super::{Cell, Field};
+use crate::{navigation::flow_field::layout::FieldLayout, prelude::*};
+
+/// Type of the ground of a cell, read by e.g. footstep audio, movement speed modifiers & spell interactions.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, Reflect, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum Surface {
+    #[default]
+    Grass,
+    Road,
+    Water,
+    /// Outside of the walkable ground, e.g. gaps & cliffs.
+    Void,
+}
+
+impl Surface {
+    /// Multiplier of the movement speed of agents on the surface.
+    #[inline]
+    pub const fn speed_multiplier(self) -> f32 {
+        match self {
+            Surface::Grass => 1.0,
+            Surface::Road => 1.2,
+            Surface::Water => 0.6,
+            Surface::Void => 0.0,
+        }
+    }
+}
+
+/// [`Surface`] of each cell, parallel to the [`ObstacleField`](super::obstacle::ObstacleField).
+#[derive(Resource, Clone, Reflect, Deref)]
+pub struct SurfaceField(Field<Surface>);
+
+impl SurfaceField {
+    pub fn from_layout(layout: &FieldLayout) -> Self {
+        Self(Field::new(layout.width(), layout.height(), vec![Surface::default(); layout.len()]))
+    }
+
+    /// Returns the [`Surface`] of a cell, [`Surface::Void`] outside of the field.
+    #[inline]
+    pub fn surface(&self, cell: Cell) -> Surface {
+        self.0.get(cell).unwrap_or(Surface::Void)
+    }
+
+    /// Returns the [`Surface`] at a position on the xz-plane.
+    #[inline]
+    pub fn surface_at(&self, layout: &FieldLayout, position: Vec2) -> Surface {
+        self.surface(layout.cell(position))
+    }
+
+    /// Cells within `radius` of a position on the xz-plane with their [`Surface`].
+    pub fn within<'a>(
+        &'a self,
+        layout: &FieldLayout,
+        position: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Cell, Surface)> + 'a {
+        let (min, max) = (layout.cell(position - radius), layout.cell(position + radius));
+        let center = layout.cell(position);
+        let radius_sqrt = radius * radius;
+        (min.y()..=max.y())
+            .flat_map(move |y| (min.x()..=max.x()).map(move |x| Cell::new(x, y)))
+            .filter(move |&cell| self.0.valid(cell) && center.euclidean_sqrt(cell) <= radius_sqrt)
+            .map(|cell| (cell, self.0[cell]))
+    }
+
+    #[inline]
+    pub fn splat(&mut self, cells: impl Iterator<Item = Cell>, surface: Surface) {
+        for cell in cells {
+            if let Some(index) = self.0.index(cell) {
+                self.0[index] = surface;
+            }
+        }
+    }
+}
+
+/// Sets the [`Surface`] of the cells below the bounds of a mesh, e.g. from the `surface` glTF extra.
+#[derive(Component, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct SurfaceRegion(pub Surface);
+
+pub(in crate::navigation) fn splat(
+    mut surface_field: ResMut<SurfaceField>,
+    regions: Query<(&SurfaceRegion, &Aabb, &GlobalTransform), Or<(Changed<SurfaceRegion>, Changed<Aabb>)>>,
+    layout: Res<FieldLayout>,
+) {
+    let _span = info_span!("navigation::flow_field::surface::splat").entered();
+    for (region, aabb, global_transform) in &regions {
+        let (min, max) = (Vec3::from(aabb.min()), Vec3::from(aabb.max()));
+        let corners = [min, Vec3::new(max.x, min.y, min.z), Vec3::new(min.x, min.y, max.z), max]
+            .map(|corner| global_transform.transform_point(corner).xz());
+        let min = layout.cell(corners.into_iter().reduce(Vec2::min).unwrap_or_default());
+        let max = layout.cell(corners.into_iter().reduce(Vec2::max).unwrap_or_default());
+        let cells = (min.y()..=max.y()).flat_map(|y| (min.x()..=max.x()).map(move |x| Cell::new(x, y)));
+        surface_field.splat(cells, region.0);
+    }
+}
@@ -4,13 +4,14 @@ use super::{
     CellIndex,
 };
 use crate::{
-    navigation::{agent::Agent, flow_field::fields, obstacle::Obstacle},
+    movement::motor::Stationary,
+    navigation::{agent::Agent, crowd::Dormant, flow_field::fields, obstacle::Obstacle},
     prelude::*,
     utils::math::point_in_poly2d,
 };
 
 /// Footprint of an entity on the field.
-#[derive(Component, Default, Reflect)]
+#[derive(Component, Default, PartialEq, Reflect)]
 #[reflect(Component)]
 pub enum Footprint {
     #[default]
@@ -53,46 +54,82 @@ impl Footprint {
     }
 }
 
+/// Center cell & position an agent's [`Footprint`] was last derived at.
+#[derive(Component, Default, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct FootprintOrigin {
+    cell: Option<Cell>,
+    position: Vec2,
+}
+
+/// Distance an agent has to move past the position its footprint was derived at before crossing into a neighboring
+/// cell updates it, so agents moving along a cell border don't update their footprint every tick. Agents coming to
+/// rest in another cell update it regardless.
+const FOOTPRINT_HYSTERESIS: f32 = HALF_CELL_SIZE * 0.5;
+
 pub(super) fn agents(
     mut agents: Query<
-        (&mut Footprint, &Agent, &CellIndex, &GlobalTransform),
-        Or<(Changed<CellIndex>, Added<Footprint>)>,
+        (&mut Footprint, &mut FootprintOrigin, &Agent, &CellIndex, &GlobalTransform, Option<Ref<Stationary>>),
+        (
+            Or<(Changed<CellIndex>, Added<Stationary>, Added<Footprint>, Added<FootprintOrigin>)>,
+            // Sleeping agents keep their footprint.
+            Without<Dormant>,
+        ),
     >,
     layout: Res<FieldLayout>,
 ) {
-    agents.par_iter_mut().for_each(|(mut footprint, agent, cell_index, global_transform)| match cell_index {
-        CellIndex::Invalid => {
-            if !footprint.is_empty() {
-                *footprint = Footprint::Empty;
+    let _span = info_span!("navigation::flow_field::footprint::agents").entered();
+    agents.stable_par_iter_mut().for_each(|(mut footprint, mut origin, agent, cell_index, transform, stationary)| {
+        match cell_index {
+            CellIndex::Invalid => {
+                if !footprint.is_empty() {
+                    *footprint = Footprint::Empty;
+                }
+                origin.cell = None;
             }
-        }
-        CellIndex::Valid(center, _) => {
-            let layout: FieldLayout = *layout;
-            let agent_radius: f32 = agent.radius();
-            const fn radius_sqrt(agent: &Agent) -> f32 {
-                agent.radius() * agent.radius()
-            }
-            let agent_position = global_transform.translation().xz();
-
-            const BORDER_PADDING: f32 = HALF_CELL_SIZE * 0.5;
-            const BORDER_PADDING_SQRT: f32 = BORDER_PADDING * BORDER_PADDING;
-
-            let min_cell = layout.cell(Vec2::new(
-                agent_position.x - (agent_radius + BORDER_PADDING),
-                agent_position.y - (agent_radius + BORDER_PADDING),
-            ));
-            let max_cell = layout.cell(Vec2::new(
-                agent_position.x + (agent_radius + BORDER_PADDING),
-                agent_position.y + (agent_radius + BORDER_PADDING),
-            ));
-
-            *footprint = Footprint::Cells(
-                (min_cell.x()..=max_cell.x())
+            CellIndex::Valid(center, _) => {
+                let layout: FieldLayout = *layout;
+                let agent_radius: f32 = agent.radius();
+                const fn radius_sqrt(agent: &Agent) -> f32 {
+                    agent.radius() * agent.radius()
+                }
+                let agent_position = transform.translation().xz();
+
+                // Keep the previous cells when back in the cell they were derived at or barely past its border while
+                // still moving.
+                let rested = stationary.is_some_and(|stationary| stationary.is_added());
+                if let Some(cell) = origin.cell
+                    && !footprint.is_empty()
+                    && (cell == *center
+                        || (!rested
+                            && cell.chebyshev(*center) <= 1
+                            && agent_position.distance_squared(origin.position)
+                                < FOOTPRINT_HYSTERESIS * FOOTPRINT_HYSTERESIS))
+                {
+                    return;
+                }
+
+                const BORDER_PADDING: f32 = HALF_CELL_SIZE * 0.5;
+                const BORDER_PADDING_SQRT: f32 = BORDER_PADDING * BORDER_PADDING;
+
+                let min_cell = layout.cell(Vec2::new(
+                    agent_position.x - (agent_radius + BORDER_PADDING),
+                    agent_position.y - (agent_radius + BORDER_PADDING),
+                ));
+                let max_cell = layout.cell(Vec2::new(
+                    agent_position.x + (agent_radius + BORDER_PADDING),
+                    agent_position.y + (agent_radius + BORDER_PADDING),
+                ));
+
+                let cells = (min_cell.x()..=max_cell.x())
                     .step_by(CELL_SIZE.into())
                     .flat_map(|x| (min_cell.y()..=max_cell.y()).step_by(CELL_SIZE.into()).map(move |y| Cell::new(x, y)))
                     .filter(|&cell| center.euclidean_sqrt(cell) <= radius_sqrt(agent) + BORDER_PADDING_SQRT)
-                    .collect(),
-            );
+                    .collect();
+
+                footprint.set_if_neq(Footprint::Cells(cells));
+                *origin = FootprintOrigin { cell: Some(*center), position: agent_position };
+            }
         }
     });
 }
@@ -101,7 +138,8 @@ pub(super) fn obstacles(
     mut obstacles: Query<(&mut Footprint, &Obstacle, &ColliderAabb), (Changed<Obstacle>, Without<Agent>)>,
     layout: Res<FieldLayout>,
 ) {
-    obstacles.par_iter_mut().for_each(|(mut footprint, obstacle, aabb)| {
+    let _span = info_span!("navigation::flow_field::footprint::obstacles").entered();
+    obstacles.stable_par_iter_mut().for_each(|(mut footprint, obstacle, aabb)| {
         let Obstacle::Shape(shape) = obstacle else {
             if !footprint.is_empty() {
                 *footprint = Footprint::Empty;
@@ -113,18 +151,18 @@ pub(super) fn obstacles(
         let min_cell = layout.cell(aabb.min.xz() + BORDER_PADDING);
         let max_cell = layout.cell(aabb.max.xz() + BORDER_PADDING);
 
-        *footprint = Footprint::Cells(
+        footprint.set_if_neq(Footprint::Cells(
             (min_cell.x()..=max_cell.x())
                 .step_by(CELL_SIZE.into())
                 .flat_map(|x| (min_cell.y()..=max_cell.y()).step_by(CELL_SIZE.into()).map(move |y| Cell::new(x, y)))
                 .filter(|&cell| layout.index(cell).is_some() && point_in_poly2d(layout.position(cell), shape))
                 .collect(),
-        );
+        ));
     });
 }
 
 /// A [`Footprint`] expanded to size how given [`Agent`] views it when on the field.
-#[derive(Component, Default, Reflect)]
+#[derive(Component, Default, PartialEq, Reflect)]
 #[reflect(Component)]
 pub enum ExpandedFootprint<const AGENT: Agent> {
     #[default]
@@ -137,7 +175,8 @@ pub(super) fn setup<const AGENT: Agent>(
     agents: Query<Entity, (With<Footprint>, Without<ExpandedFootprint<AGENT>>)>,
     mut removed: RemovedComponents<ExpandedFootprint<AGENT>>,
 ) {
-    agents.par_iter().for_each(|entity| {
+    let _span = info_span!("navigation::flow_field::footprint::setup", agent = %AGENT).entered();
+    agents.stable_par_iter().for_each(|entity| {
         commands.command_scope(|mut c| {
             c.entity(entity).insert(ExpandedFootprint::<AGENT>::default());
         })
@@ -158,30 +197,41 @@ pub(super) fn expand<const AGENT: Agent>(
         Or<(Changed<Footprint>, Added<Footprint>, Added<ExpandedFootprint<AGENT>>)>,
     >,
 ) {
+    let _span = info_span!("navigation::flow_field::footprint::expand", agent = %AGENT).entered();
     let expansion = AGENT.radius().floor() as u32;
 
-    footprints.par_iter_mut().for_each(|(footprint, mut expanded_footprint)| {
+    footprints.stable_par_iter_mut().for_each(|(footprint, mut expanded_footprint)| {
         if expansion == 0 {
             let Footprint::Cells(cells) = footprint else {
-                *expanded_footprint = ExpandedFootprint::Empty;
+                expanded_footprint.set_if_neq(ExpandedFootprint::Empty);
                 return;
             };
-            *expanded_footprint = ExpandedFootprint::Cells(cells.clone());
+            expanded_footprint.set_if_neq(ExpandedFootprint::Cells(cells.clone()));
             return;
         }
 
         let Some(cells) = footprint.expand(expansion) else {
-            *expanded_footprint = ExpandedFootprint::Empty;
+            expanded_footprint.set_if_neq(ExpandedFootprint::Empty);
             return;
         };
-        *expanded_footprint = ExpandedFootprint::Cells(cells.collect());
+        // Neighboring cells expand into mostly the same cells, splat each only once.
+        let mut cells: SmallVec<[Cell; 16]> = cells.collect();
+        cells.sort_unstable();
+        cells.dedup();
+        expanded_footprint.set_if_neq(ExpandedFootprint::Cells(cells));
     })
 }
 
 #[cfg(feature = "dev_tools")]
-pub(crate) fn gizmos(mut gizmos: Gizmos, footprints: Query<&Footprint>, layout: Res<FieldLayout>) {
+pub(crate) fn gizmos(
+    mut gizmos: Gizmos,
+    culling: GizmoCulling,
+    footprints: Query<&Footprint>,
+    layout: Res<FieldLayout>,
+) {
     use super::layout::CELL_SIZE_F32;
 
+    let mut culler = culling.layer();
     for footprint in &footprints {
         let Footprint::Cells(cells) = footprint else {
             continue;
@@ -189,6 +239,9 @@ pub(crate) fn gizmos(mut gizmos: Gizmos, footprints: Query<&Footprint>, layout:
 
         for cell in cells {
             let position = layout.position(*cell);
+            if !culler.draw(position.x0y(), 4) {
+                continue;
+            }
             gizmos.rect(position.x0y().y_pad(), Quat::from_rotation_x(PI / 2.), Vec2::ONE * CELL_SIZE_F32, Color::CYAN);
         }
     }
@@ -27,6 +27,17 @@ impl FieldLayout {
         layout
     }
 
+    /// Moves the layout so cell `(0, 0)` is centered on `offset` instead of centering the layout on the origin.
+    pub const fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    #[inline]
+    pub fn is_centered(&self) -> bool {
+        self.offset == centered_offset(self.width, self.height)
+    }
+
     #[inline]
     pub const fn width(&self) -> fields::Scalar {
         self.width
@@ -49,7 +60,8 @@ impl FieldLayout {
 
     #[inline]
     pub const fn center(&self) -> Vec2 {
-        Vec2::ZERO
+        let (min, max) = self.aabb();
+        Vec2::new((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0)
     }
 
     #[inline]
@@ -106,9 +118,9 @@ impl FieldLayout {
     }
 
     #[inline]
-    pub fn aabb(&self) -> ((f32, f32), (f32, f32)) {
-        let min = ((-(self.width() as f32) / 2.0) * CELL_SIZE_F32, (-(self.height() as f32) / 2.0) * CELL_SIZE_F32);
-        let max = ((self.width() as f32 / 2.0) * CELL_SIZE_F32, (self.height() as f32 / 2.0) * CELL_SIZE_F32);
+    pub const fn aabb(&self) -> ((f32, f32), (f32, f32)) {
+        let min = (self.offset.x - HALF_CELL_SIZE, self.offset.y - HALF_CELL_SIZE);
+        let max = (min.0 + self.width() as f32 * CELL_SIZE_F32, min.1 + self.height() as f32 * CELL_SIZE_F32);
         (min, max)
     }
 }
@@ -117,6 +129,7 @@ impl FieldLayout {
 pub struct FieldBorders([Vec2; 4]);
 
 pub(super) fn field_borders(layout: Res<FieldLayout>, mut field_borders: ResMut<FieldBorders>) {
+    let _span = info_span!("navigation::flow_field::layout::field_borders").entered();
     if layout.is_changed() || layout.len() != 0 && field_borders.0.is_empty() {
         let (min, max) = layout.aabb();
         **field_borders =
@@ -128,6 +141,7 @@ pub(super) fn field_borders(layout: Res<FieldLayout>, mut field_borders: ResMut<
 pub struct FieldBounds<const AGENT: Agent>(Vec<Cell>);
 
 pub(super) fn field_bounds<const AGENT: Agent>(layout: Res<FieldLayout>, mut field_bounds: ResMut<FieldBounds<AGENT>>) {
+    let _span = info_span!("navigation::flow_field::layout::field_bounds", agent = %AGENT).entered();
     if layout.is_changed() || layout.len() != 0 && field_bounds.0.is_empty() {
         let bounds = Agent::ALL.iter().filter(|a| a.radius() <= AGENT.radius()).flat_map(|a| layout.bounds(*a));
         **field_bounds = bounds.collect();
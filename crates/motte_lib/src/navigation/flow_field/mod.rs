@@ -1,13 +1,21 @@
-use self::{fields::Cell, footprint::Footprint, layout::FieldLayout};
+use self::{
+    fields::Cell,
+    footprint::{Footprint, FootprintOrigin},
+    layout::FieldLayout,
+};
 use crate::{
-    app_state::AppState,
+    app_state::simulating,
     navigation::{
-        agent::Agent,
+        agent::{for_each_agent, Agent},
+        crowd::Dormant,
         flow_field::{
+            budget::BuildScheduler,
             cache::FlowFieldCache,
+            corridor::{Corridor, CorridorWindow},
             fields::{
                 flow::FlowField,
                 obstacle::{DirtyObstacleField, ObstacleField},
+                surface::{Surface, SurfaceField, SurfaceRegion},
             },
             footprint::ExpandedFootprint,
             layout::{FieldBorders, FieldBounds},
@@ -16,51 +24,46 @@ use crate::{
     prelude::*,
 };
 
+pub mod budget;
 pub mod cache;
+pub mod corridor;
 pub mod fields;
 pub mod footprint;
 pub mod layout;
 pub mod pathing;
 
-#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum FlowFieldSystems {
-    Setup,
-    Maintain,
-    DetectChanges,
-    Splat,
-    Build,
-    Pathing,
-    Cleanup,
+ordered_sets! {
+    pub enum FlowFieldSystems: FixedUpdate {
+        Setup,
+        Maintain,
+        DetectChanges,
+        Splat.run_if(on_event::<DirtyObstacleField>().or_else(on_event::<save::Loaded>())),
+        Build,
+        Pathing,
+        Cleanup,
+    }
+    .run_if(simulating)
 }
 
 pub struct FlowFieldPlugin;
 
 impl Plugin for FlowFieldPlugin {
     fn build(&self, app: &mut App) {
-        app_register_types!(CellIndex, Footprint, DirtyObstacleField);
+        app_register_types!(CellIndex, Footprint, FootprintOrigin, DirtyObstacleField, CorridorWindow);
+        app_register_types!(Surface, SurfaceRegion, SurfaceField);
 
-        app.configure_sets(
-            FixedUpdate,
-            (
-                FlowFieldSystems::Setup,
-                FlowFieldSystems::Maintain,
-                FlowFieldSystems::DetectChanges,
-                FlowFieldSystems::Splat.run_if(on_event::<DirtyObstacleField>()),
-                FlowFieldSystems::Build,
-                FlowFieldSystems::Pathing,
-                FlowFieldSystems::Cleanup,
-            )
-                .chain()
-                .run_if(in_state(AppState::InGame)),
-        );
+        FlowFieldSystems::configure(app);
 
         app.insert_resource(FieldBorders::default());
+        app.init_resource::<BuildScheduler>();
         app.add_event::<DirtyObstacleField>();
 
         app.add_systems(
             FixedUpdate,
-            (cell_index, layout::field_borders, (footprint::agents, footprint::obstacles))
-                .chain()
+            (
+                (cell_index, layout::field_borders, (footprint::agents, footprint::obstacles)).chain(),
+                fields::surface::splat.run_if(resource_exists::<SurfaceField>),
+            )
                 .in_set(FlowFieldSystems::Maintain),
         );
 
@@ -68,16 +71,16 @@ impl Plugin for FlowFieldPlugin {
             FixedUpdate,
             (
                 fields::obstacle::clear,
+                fields::obstacle::splat_temporary,
                 // Would like to put this into [`FlowFieldAgentPlugin`], but not sure how to ensure the order.
                 // The order is important, should be 'splat' from largest to smallest.
-                fields::obstacle::splat::<{ Agent::Huge }>,
-                fields::obstacle::splat::<{ Agent::Large }>,
-                fields::obstacle::splat::<{ Agent::Medium }>,
-                fields::obstacle::splat::<{ Agent::Small }>,
+                for_each_agent!(|AGENT| fields::obstacle::splat::<AGENT>).chain(),
+                fields::obstacle::update_clearance,
             )
                 .chain()
                 .in_set(FlowFieldSystems::Splat),
         );
+        app.add_systems(FixedUpdate, budget::plan.in_set(FlowFieldSystems::Build));
     }
 }
 
@@ -85,14 +88,26 @@ pub struct FlowFieldAgentPlugin<const AGENT: Agent>;
 
 impl<const AGENT: Agent> Plugin for FlowFieldAgentPlugin<AGENT> {
     fn build(&self, app: &mut App) {
-        app_register_types!(FlowField<AGENT>, FlowFieldCache<AGENT>, FieldBounds<AGENT>, ExpandedFootprint<AGENT>);
+        app_register_types!(
+            FlowField<AGENT>,
+            FlowFieldCache<AGENT>,
+            FieldBounds<AGENT>,
+            ExpandedFootprint<AGENT>,
+            Corridor<AGENT>
+        );
 
         app.insert_resource(FlowFieldCache::<AGENT>::default());
         app.insert_resource(FieldBounds::<AGENT>::default());
 
         app.add_systems(
             FixedUpdate,
-            (cache::spawn::<AGENT>, cache::insert::<AGENT>, footprint::setup::<AGENT>).in_set(FlowFieldSystems::Setup),
+            (
+                cache::spawn::<AGENT>,
+                cache::insert::<AGENT>,
+                corridor::plan::<AGENT>.after(cache::spawn::<AGENT>),
+                footprint::setup::<AGENT>,
+            )
+                .in_set(FlowFieldSystems::Setup),
         );
         app.add_systems(
             FixedUpdate,
@@ -101,6 +116,7 @@ impl<const AGENT: Agent> Plugin for FlowFieldAgentPlugin<AGENT> {
                 cache::despawn::<AGENT>,
                 layout::field_bounds::<AGENT>,
                 pathing::maintain,
+                corridor::advance::<AGENT>.after(cell_index),
                 footprint::expand::<AGENT>
                     .after(footprint::agents)
                     .after(footprint::obstacles)
@@ -120,16 +136,20 @@ impl<const AGENT: Agent> Plugin for FlowFieldAgentPlugin<AGENT> {
                     fields::flow::changed::<AGENT>.run_if(resource_exists_and_changed::<ObstacleField>),
                 ),
                 apply_deferred,
-                fields::flow::build::<AGENT>.in_set(FlowFieldSystems::Build),
+                budget::collect::<AGENT>.in_set(FlowFieldSystems::Build).before(budget::plan),
+                fields::flow::build::<AGENT>.in_set(FlowFieldSystems::Build).after(budget::plan),
                 pathing::direction::<AGENT>.in_set(FlowFieldSystems::Pathing),
             )
                 .chain(),
         );
-        app.add_systems(FixedUpdate, (cache::despawn::<AGENT>).in_set(FlowFieldSystems::Cleanup));
+        app.add_systems(
+            FixedUpdate,
+            (cache::despawn::<AGENT>, corridor::cleanup::<AGENT>).in_set(FlowFieldSystems::Cleanup),
+        );
     }
 }
 
-#[derive(Component, Default, PartialEq, Eq, Reflect)]
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, Debug, Reflect)]
 #[reflect(Component)]
 pub enum CellIndex {
     #[default]
@@ -138,10 +158,14 @@ pub enum CellIndex {
 }
 
 pub fn cell_index(
-    mut transforms: Query<(&mut CellIndex, &GlobalTransform), Or<(Changed<GlobalTransform>, Added<CellIndex>)>>,
+    mut transforms: Query<
+        (&mut CellIndex, &GlobalTransform),
+        (Or<(Changed<GlobalTransform>, Added<CellIndex>)>, Without<Dormant>),
+    >,
     layout: Res<FieldLayout>,
 ) {
-    transforms.par_iter_mut().for_each(|(mut cell_index, global)| {
+    let _span = info_span!("navigation::flow_field::cell_index").entered();
+    transforms.stable_par_iter_mut().for_each(|(mut cell_index, global)| {
         let cell = layout.cell(global.translation().xz());
         let index = layout.index(cell);
         let value = index.map(|index| CellIndex::Valid(cell, index)).unwrap_or(CellIndex::Invalid);
@@ -153,15 +177,24 @@ pub fn cell_index(
 }
 
 #[cfg(feature = "dev_tools")]
-pub(crate) fn gizmos_cell_index(mut gizmos: Gizmos, agents: Query<&CellIndex>, layout: Res<FieldLayout>) {
+pub(crate) fn gizmos_cell_index(
+    mut gizmos: Gizmos,
+    culling: GizmoCulling,
+    agents: Query<&CellIndex>,
+    layout: Res<FieldLayout>,
+) {
     use self::layout::CELL_SIZE_F32;
 
+    let mut culler = culling.layer();
     for cell_index in &agents {
         let CellIndex::Valid(cell, _) = cell_index else {
             continue;
         };
 
         let position = layout.position(*cell);
+        if !culler.draw(position.x0y(), 4) {
+            continue;
+        }
         gizmos.rect(
             position.x0y().y_pad(),
             Quat::from_rotation_x(PI / 2.),
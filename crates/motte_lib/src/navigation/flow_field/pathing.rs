@@ -1,5 +1,11 @@
+use bevy::ecs::{
+    entity::{EntityMapper, MapEntities},
+    reflect::ReflectMapEntities,
+};
+
 use super::{
     cache::FlowFieldCache,
+    corridor::Corridor,
     fields::{
         flow::{Flow, FlowField},
         Cell,
@@ -9,12 +15,15 @@ use super::{
     CellIndex,
 };
 use crate::{
-    navigation::agent::{Agent, AgentType, DesiredDirection, TargetDistance},
+    navigation::{
+        agent::{Agent, AgentType, DesiredDirection, TargetDistance},
+        crowd::Dormant,
+    },
     prelude::*,
 };
 
 #[derive(Component, Clone, Copy, Default, PartialEq, Eq, Ord, PartialOrd, Hash, Debug, From, Reflect)]
-#[reflect(Component)]
+#[reflect(Component, MapEntities)]
 pub enum Goal {
     #[default]
     None,
@@ -22,18 +31,27 @@ pub enum Goal {
     Cell(Cell),
 }
 
+impl MapEntities for Goal {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        if let Goal::Entity(entity) = self {
+            *entity = entity_mapper.map_entity(*entity);
+        }
+    }
+}
+
 pub(super) fn direction<const AGENT: Agent>(
     mut agents: Query<
-        (Entity, &Goal, &mut Flow, &mut DesiredDirection, &mut TargetDistance, &CellIndex),
-        With<AgentType<AGENT>>,
+        (Entity, &Goal, &mut Flow, &mut DesiredDirection, &mut TargetDistance, &CellIndex, Option<&Corridor<AGENT>>),
+        (With<AgentType<AGENT>>, Without<Dormant>),
     >,
     layout: Res<FieldLayout>,
     flow_field_cache: Res<FlowFieldCache<AGENT>>,
     flow_fields: Query<(&FlowField<AGENT>, Option<Ref<Footprint>>), Without<Disabled<FlowField<AGENT>>>>,
     transforms: Query<Ref<GlobalTransform>>,
 ) {
-    agents.par_iter_mut().for_each(
-        |(entity, goal, mut flow, mut desired_direction, mut target_distance, cell_index)| {
+    let _span = info_span!("navigation::flow_field::pathing::direction", agent = %AGENT).entered();
+    agents.stable_par_iter_mut().for_each(
+        |(entity, goal, mut flow, mut desired_direction, mut target_distance, cell_index, corridor)| {
             if matches!(goal, Goal::None) {
                 *flow = Flow::None;
                 **desired_direction = None;
@@ -41,39 +59,51 @@ pub(super) fn direction<const AGENT: Agent>(
                 return;
             }
 
-            let CellIndex::Valid(cell, index) = cell_index else {
+            let CellIndex::Valid(cell, _) = cell_index else {
                 *flow = Flow::None;
                 **desired_direction = None;
                 **target_distance = 0.0;
                 return;
             };
 
-            let entry = flow_field_cache.get(goal);
+            let field = match corridor {
+                Some(corridor) => corridor.field(),
+                None => {
+                    let entry = flow_field_cache.get(goal);
+
+                    if entry.is_none() {
+                        *flow = Flow::None;
+                        **desired_direction = None;
+                        **target_distance = 0.0;
+                        return;
+                    }
+
+                    let entry = entry.unwrap();
+
+                    unsafe {
+                        // SAFETY: it's fine :)
+                        // Pokes the cache timer to keep it alive.
+                        #[allow(invalid_reference_casting)]
+                        #[allow(clippy::mut_from_ref)]
+                        unsafe fn as_mut<T>(reference: &T) -> &mut T {
+                            let const_ptr = reference as *const T;
+                            let mut_ptr = const_ptr as *mut T;
+                            &mut *mut_ptr
+                        }
+                        let timer = as_mut(&entry.1);
+                        timer.reset();
+                    }
 
-            if entry.is_none() {
+                    entry.0
+                }
+            };
+
+            let Ok((flow_field, footprint)) = flow_fields.get(field) else {
                 *flow = Flow::None;
                 **desired_direction = None;
                 **target_distance = 0.0;
                 return;
-            }
-
-            let entry = entry.unwrap();
-
-            unsafe {
-                // SAFETY: it's fine :)
-                // Pokes the cache timer to keep it alive.
-                #[allow(invalid_reference_casting)]
-                #[allow(clippy::mut_from_ref)]
-                unsafe fn as_mut<T>(reference: &T) -> &mut T {
-                    let const_ptr = reference as *const T;
-                    let mut_ptr = const_ptr as *mut T;
-                    &mut *mut_ptr
-                }
-                let timer = as_mut(&entry.1);
-                timer.reset();
-            }
-
-            let (flow_field, footprint) = flow_fields.get(entry.0).unwrap();
+            };
 
             if flow_field.is_empty() {
                 *flow = Flow::None;
@@ -90,7 +120,7 @@ pub(super) fn direction<const AGENT: Agent>(
             }
 
             // direction
-            let flow_next = flow_field[*index];
+            let flow_next = flow_field.flow(*cell);
 
             // TODO: maybe move this blending logic to the agent.
             if flow_next.is_repulse() {
@@ -150,13 +180,14 @@ pub(super) fn maintain(
     without_flow: Query<Entity, (With<Goal>, Without<Flow>)>,
     without_goal: Query<Entity, (Without<Goal>, With<Flow>)>,
 ) {
-    without_flow.par_iter().for_each(|entity| {
+    let _span = info_span!("navigation::flow_field::pathing::maintain").entered();
+    without_flow.stable_par_iter().for_each(|entity| {
         commands.command_scope(|mut c| {
             c.entity(entity).insert(Flow::default());
         });
     });
 
-    without_goal.par_iter().for_each(|entity| {
+    without_goal.stable_par_iter().for_each(|entity| {
         commands.command_scope(|mut c| {
             c.entity(entity).remove::<Flow>();
         });
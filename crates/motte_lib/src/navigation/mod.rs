@@ -2,61 +2,75 @@ use bevy_spatial::AutomaticUpdate;
 
 use self::agent::Agent;
 use crate::{
-    app_state::AppState,
+    app_state::simulating,
     movement::MovementSystems,
     navigation::{
-        agent::{agent_type, AgentType, Blocking, DesiredDirection, DesiredVelocity, Speed, TargetDistance},
-        flow_field::{FlowFieldAgentPlugin, FlowFieldPlugin, FlowFieldSystems},
-        obstacle::Obstacle,
+        agent::{
+            agent_type, for_each_agent, AgentType, Blocking, DesiredDirection, DesiredVelocity, Speed, Steering,
+            SteeringWeights, TargetDistance,
+        },
+        avoidance::{AvoidanceBackend, AvoidanceQuality},
+        crowd::{Dormant, Idle},
+        flow_field::{pathing::Goal, FlowFieldAgentPlugin, FlowFieldPlugin, FlowFieldSystems},
+        obstacle::{Obstacle, ObstacleDef, ObstacleShape, RandomObstacles, TemporaryObstacle},
     },
     prelude::*,
+    save::AppSaveExt,
     stats::stat::StatPlugin,
 };
 
 pub mod agent;
 pub mod avoidance;
+pub mod crowd;
 pub mod flow_field;
 pub mod obstacle;
 
-#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum NavigationSystems {
-    Setup,
-    Maintain,
-    Velocity,
-    Avoidance,
-    ApplyVelocity,
-    Cleanup,
+ordered_sets! {
+    pub enum NavigationSystems: FixedUpdate {
+        Setup,
+        Maintain.before(FlowFieldSystems::Maintain),
+        Velocity.after(FlowFieldSystems::Pathing),
+        Avoidance.after(FlowFieldSystems::Pathing),
+        Steering,
+        ApplyVelocity.after(FlowFieldSystems::Pathing).before(MovementSystems::Motor),
+        Cleanup.after(MovementSystems::State),
+    }
+    .before(PhysicsSet::Prepare)
+    .run_if(simulating)
 }
 
 pub struct NavigationPlugin;
 
 impl Plugin for NavigationPlugin {
     fn build(&self, app: &mut App) {
-        app_register_types!(Agent, Obstacle, DesiredDirection, TargetDistance, DesiredVelocity, Blocking, Speed);
+        app_register_types!(
+            Agent,
+            Obstacle,
+            TemporaryObstacle,
+            ObstacleDef,
+            ObstacleShape,
+            RandomObstacles,
+            DesiredDirection,
+            TargetDistance,
+            DesiredVelocity,
+            Steering,
+            SteeringWeights,
+            Blocking,
+            Speed,
+            AvoidanceQuality,
+            Dormant,
+            Idle,
+            res: AvoidanceBackend
+        );
+        app.register_save::<Agent>().register_save::<Goal>().register_save::<Obstacle>();
 
         app.add_plugins(FlowFieldPlugin);
         app.add_plugins((AutomaticUpdate::<agent::Agent>::new(), AutomaticUpdate::<obstacle::Obstacle>::new()));
         app.add_plugins(StatPlugin::<Speed>::default());
 
-        app.add_plugins(AgentPlugin::<{ Agent::Small }>);
-        app.add_plugins(AgentPlugin::<{ Agent::Medium }>);
-        app.add_plugins(AgentPlugin::<{ Agent::Large }>);
-        app.add_plugins(AgentPlugin::<{ Agent::Huge }>);
+        app.add_plugins(for_each_agent!(|AGENT| AgentPlugin::<AGENT>));
 
-        app.configure_sets(
-            FixedUpdate,
-            (
-                NavigationSystems::Setup,
-                NavigationSystems::Maintain.before(FlowFieldSystems::Maintain),
-                NavigationSystems::Velocity.after(FlowFieldSystems::Pathing),
-                NavigationSystems::Avoidance.after(FlowFieldSystems::Pathing),
-                NavigationSystems::ApplyVelocity.after(FlowFieldSystems::Pathing).before(MovementSystems::Motor),
-                NavigationSystems::Cleanup.after(MovementSystems::State),
-            )
-                .chain()
-                .before(PhysicsSet::Prepare)
-                .run_if(in_state(AppState::InGame)),
-        );
+        NavigationSystems::configure(app);
 
         app.add_systems(FixedUpdate, (agent::setup, avoidance::setup).in_set(NavigationSystems::Setup));
         app.add_systems(
@@ -64,19 +78,30 @@ impl Plugin for NavigationPlugin {
             (
                 (
                     obstacle::obstacle,
+                    obstacle::temporary,
                     agent::blocking,
-                    avoidance::sync_agents,
-                    avoidance::sync_obstacles,
-                    avoidance::sync_blocking,
+                    (crowd::sleep, crowd::wake),
+                    avoidance::reset.run_if(resource_changed::<AvoidanceBackend>),
+                    (avoidance::sync_agents, avoidance::sync_obstacles, avoidance::sync_blocking)
+                        .run_if(resource_equals(AvoidanceBackend::Rvo2)),
                     apply_deferred,
                 )
                     .chain()
                     .in_set(NavigationSystems::Maintain),
-                (avoidance::rvo2).in_set(NavigationSystems::Avoidance),
+                (
+                    avoidance::rvo2.run_if(resource_equals(AvoidanceBackend::Rvo2)),
+                    avoidance::boids.run_if(resource_equals(AvoidanceBackend::Boids)),
+                )
+                    .in_set(NavigationSystems::Avoidance),
                 (agent::desired_velocity).in_set(NavigationSystems::Velocity),
+                (agent::blend).in_set(NavigationSystems::Steering),
                 (agent::apply_velocity).in_set(NavigationSystems::ApplyVelocity),
             ),
         );
+        app.add_systems(
+            FixedUpdate,
+            crowd::cell_index.in_set(FlowFieldSystems::Maintain).after(flow_field::cell_index),
+        );
         app.add_systems(FixedUpdate, (agent::target_reached, avoidance::cleanup).in_set(NavigationSystems::Cleanup));
     }
 }
@@ -3,11 +3,19 @@ use bevy_xpbd_3d::parry::{
     shape::TypedShape,
 };
 use parry2d::shape::ConvexPolygon;
+use serde::Deserialize;
 
 use super::flow_field::CellIndex;
 use crate::{
-    navigation::{agent::Agent, flow_field::layout::HALF_CELL_SIZE},
+    navigation::{
+        agent::Agent,
+        flow_field::{
+            fields::obstacle::{DirtyObstacleField, SOFT_COST_MAX},
+            layout::HALF_CELL_SIZE,
+        },
+    },
     prelude::*,
+    utils::math::random_point_in_square,
 };
 
 #[derive(Component, Clone, Default, Reflect)]
@@ -48,18 +56,143 @@ impl Obstacle {
     }
 }
 
+/// An [`Obstacle`] that raises the cost of traversing its footprint instead of blocking it, e.g. scorched ground,
+/// wreckage or spell walls. The cost fades out over the last `decay` seconds & the entity is despawned after `ttl`
+/// seconds.
+#[derive(Component, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct TemporaryObstacle {
+    pub ttl: f32,
+    pub decay: f32,
+    elapsed: f32,
+    /// Quantized cost splatted into the [`ObstacleField`](super::flow_field::fields::obstacle::ObstacleField).
+    cost: u8,
+}
+
+impl Default for TemporaryObstacle {
+    fn default() -> Self {
+        Self::new(10.0, 2.0)
+    }
+}
+
+impl TemporaryObstacle {
+    pub fn new(ttl: f32, decay: f32) -> Self {
+        Self { ttl, decay, elapsed: 0.0, cost: SOFT_COST_MAX }
+    }
+
+    #[inline]
+    pub fn cost(&self) -> u8 {
+        self.cost
+    }
+
+    /// Strength of the obstacle in `0.0..=1.0`.
+    #[inline]
+    fn strength(&self) -> f32 {
+        let remaining = (self.ttl - self.elapsed).max(0.0);
+        if self.decay <= 0.0 {
+            return if remaining > 0.0 { 1.0 } else { 0.0 };
+        }
+        (remaining / self.decay).min(1.0)
+    }
+}
+
+/// Obstacle placed on a map or in a headless scenario.
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct ObstacleDef {
+    pub position: Vec2,
+    pub shape: ObstacleShape,
+}
+
+/// Shape of an obstacle, also kept on obstacles spawned from a map so the map editor can save them.
+#[derive(Component, Reflect, Deserialize, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub enum ObstacleShape {
+    Cuboid { half_size: f32 },
+    Capsule { radius: f32, height: f32 },
+}
+
+impl ObstacleShape {
+    pub fn mesh(self) -> Mesh {
+        match self {
+            ObstacleShape::Cuboid { half_size } => Mesh::from(Cuboid { half_size: Vec3::splat(half_size) }),
+            ObstacleShape::Capsule { radius, height } => Mesh::from(Capsule3d::new(radius, height)),
+        }
+    }
+
+    pub fn collider(self) -> Collider {
+        match self {
+            ObstacleShape::Cuboid { half_size } => Collider::from(Cuboid { half_size: Vec3::splat(half_size) }),
+            ObstacleShape::Capsule { radius, height } => Collider::from(Capsule3d::new(radius, height)),
+        }
+    }
+}
+
+/// Obstacles placed randomly within `extent` of the origin using [`crate::determinism::GameRng`].
+#[derive(Reflect, Deserialize, Clone, Copy, Debug)]
+pub struct RandomObstacles {
+    pub count: u32,
+    pub extent: f32,
+}
+
+impl RandomObstacles {
+    pub fn generate(self, rng: &mut impl Rng) -> impl Iterator<Item = ObstacleDef> + '_ {
+        (0..self.count).map(move |_| {
+            let position = random_point_in_square(rng, self.extent);
+            let radius = rng.gen_range(2.0..3.0);
+            let height = rng.gen_range(2.0..6.0);
+            let shape = if rng.gen_range(0..2) >= 1 {
+                ObstacleShape::Capsule { radius, height }
+            } else {
+                ObstacleShape::Cuboid { half_size: height }
+            };
+            ObstacleDef { position, shape }
+        })
+    }
+}
+
+/// Fades out [`TemporaryObstacle`]s, the field is only splatted again when a quantized cost changed.
+pub(super) fn temporary(
+    mut commands: Commands,
+    mut obstacles: Query<(Entity, &mut TemporaryObstacle)>,
+    mut dirty: EventWriter<DirtyObstacleField>,
+    time: Res<Time>,
+) {
+    let _span = info_span!("navigation::obstacle::temporary").entered();
+    let delta = time.delta_seconds();
+    let mut changed = false;
+    for (entity, mut obstacle) in &mut obstacles {
+        obstacle.elapsed += delta;
+        if obstacle.elapsed >= obstacle.ttl {
+            commands.entity(entity).despawn_recursive();
+            changed = true;
+            continue;
+        }
+
+        let cost = (obstacle.strength() * SOFT_COST_MAX as f32).ceil() as u8;
+        if cost != obstacle.cost {
+            obstacle.cost = cost;
+            changed = true;
+        }
+    }
+
+    if changed {
+        dirty.send(DirtyObstacleField);
+    }
+}
+
 pub(super) fn obstacle(
     mut obstacles: Query<
         (&mut Obstacle, &Collider, &ColliderAabb, &GlobalTransform),
         Or<(Changed<CellIndex>, Changed<Collider>, Changed<ColliderAabb>, Added<Obstacle>)>,
     >,
 ) {
+    let _span = info_span!("navigation::obstacle::obstacle").entered();
     // TODO: sample height if/whenever we have a generated height-field.
     const FIELD_HEIGHT: f32 = 0.0;
     // TODO: we would need another solution to properly support varying agent heights, not a concern for now tho.
     const MAX_AGENT_HEIGHT: f32 = Agent::LARGEST.height() / 2.0;
 
-    obstacles.par_iter_mut().for_each(|(mut obstacle, collider, aabb, global_transform)| {
+    obstacles.stable_par_iter_mut().for_each(|(mut obstacle, collider, aabb, global_transform)| {
         if aabb.min.y > MAX_AGENT_HEIGHT || aabb.max.y < FIELD_HEIGHT {
             if !obstacle.is_empty() {
                 *obstacle = Obstacle::Empty;
@@ -74,6 +207,9 @@ pub(super) fn obstacle(
             TypedShape::Capsule(capsule) => capsule.to_outline(SUBDIVISIONS).into(),
             TypedShape::Cylinder(cylinder) => cylinder.to_outline(SUBDIVISIONS).into(),
             TypedShape::Cone(cone) => cone.to_outline(SUBDIVISIONS).into(),
+            // Only the points are used to build the convex hull below.
+            TypedShape::ConvexPolyhedron(polyhedron) => Some((polyhedron.points().to_vec(), Vec::new())),
+            TypedShape::TriMesh(trimesh) => Some((trimesh.vertices().to_vec(), Vec::new())),
             _ => {
                 error!("Failed to convert shape to outline polylines.");
                 None
@@ -116,7 +252,8 @@ pub(super) fn obstacle(
 }
 
 #[cfg(feature = "dev_tools")]
-pub(crate) fn gizmos(mut gizmos: Gizmos, obstacles: Query<&mut Obstacle>) {
+pub(crate) fn gizmos(mut gizmos: Gizmos, culling: GizmoCulling, obstacles: Query<&mut Obstacle>) {
+    let mut culler = culling.layer();
     for obstacle in obstacles.iter() {
         match obstacle {
             Obstacle::Empty => {}
@@ -125,6 +262,9 @@ pub(crate) fn gizmos(mut gizmos: Gizmos, obstacles: Query<&mut Obstacle>) {
                     continue;
                 };
                 for (start, end) in segments {
+                    if !culler.draw(start.x0y(), 1) {
+                        continue;
+                    }
                     gizmos.line(start.x0y(), end.x0y(), Color::RED);
                 }
             }
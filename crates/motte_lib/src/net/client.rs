@@ -0,0 +1,263 @@
+use std::{collections::VecDeque, net::SocketAddr};
+
+use super::{
+    gate_simulation, is_client,
+    protocol::{ClientMessage, NetEvent, NetGoal, PoolState, ServerMessage, Snapshot, PROTOCOL_VERSION},
+    simulated,
+    transport::Transport,
+    HostGame, NetId, NetRole, INTERPOLATION_DELAY,
+};
+use crate::{
+    app_state::AppState, cleanup::StateScoped, events::GameEvent, physics::velocity::EstimatedVelocity,
+    player::camera::MainCamera, prelude::*, utils::math,
+};
+
+/// Interval of the hello messages while connecting & of focus updates once connected.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Snapshots kept per remote unit.
+const MAX_SAMPLES: usize = 16;
+
+pub(super) struct ClientPlugin;
+
+impl Plugin for ClientPlugin {
+    fn build(&self, app: &mut App) {
+        let NetRole::Client { server } = *app.world.resource::<NetRole>() else {
+            return;
+        };
+        match Transport::bind_any(server) {
+            Ok(transport) => {
+                info!("Connecting to {server}");
+                app.insert_resource(Client::new(transport, server));
+            }
+            Err(err) => {
+                error!("{err:?}");
+                app.insert_resource(NetRole::Offline);
+                return;
+            }
+        }
+
+        app_register_types!(RemoteUnit);
+        // Units are only simulated by the server.
+        gate_simulation(app, || simulated);
+        // Connects outside of a game to receive the game of the server.
+        app.add_systems(PreUpdate, receive.run_if(is_client));
+        app.add_systems(Update, keepalive.run_if(is_client));
+        app.add_systems(Update, interpolate.run_if(in_state(AppState::InGame).and_then(is_client)));
+        app.add_systems(OnExit(AppState::InGame), disconnect.run_if(is_client));
+    }
+}
+
+#[derive(Resource)]
+pub struct Client {
+    transport: Transport,
+    server: SocketAddr,
+    entities: HashMap<NetId, Entity>,
+    connection: Option<Connection>,
+    keepalive: Timer,
+}
+
+impl Client {
+    fn new(transport: Transport, server: SocketAddr) -> Self {
+        Self {
+            transport,
+            server,
+            entities: HashMap::default(),
+            connection: None,
+            keepalive: Timer::new(KEEPALIVE_INTERVAL, TimerMode::Repeating),
+        }
+    }
+
+    /// Returns the local entity of a replicated entity.
+    pub fn entity(&self, id: NetId) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+}
+
+struct Connection {
+    /// Duration of a server tick in seconds.
+    timestep: f32,
+    latest: u32,
+    received: Instant,
+}
+
+impl Connection {
+    /// Estimates the current tick of the server from the latest snapshot.
+    fn server_tick(&self) -> f32 {
+        self.latest as f32 + self.received.elapsed().as_secs_f32() / self.timestep
+    }
+}
+
+/// A unit simulated by the server, its transform is interpolated between the received snapshots.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+pub struct RemoteUnit {
+    samples: VecDeque<(u32, Transform)>,
+    pub pools: Vec<PoolState>,
+    pub goal: Option<NetGoal>,
+}
+
+impl RemoteUnit {
+    fn push(&mut self, tick: u32, transform: Transform) {
+        // Snapshots can arrive out of order, keep the samples sorted by tick.
+        let index = self.samples.partition_point(|&(sample, _)| sample < tick);
+        if self.samples.get(index).is_some_and(|&(sample, _)| sample == tick) {
+            return;
+        }
+        self.samples.insert(index, (tick, transform));
+        if self.samples.len() > MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Returns the transform at `tick`, clamped to the oldest & newest sample.
+    pub fn sample(&self, tick: f32) -> Option<Transform> {
+        let after = self.samples.partition_point(|&(sample, _)| (sample as f32) < tick);
+        if after == 0 || after == self.samples.len() {
+            return self.samples.get(after.min(self.samples.len().saturating_sub(1))).map(|&(_, transform)| transform);
+        }
+        let (from_tick, from) = self.samples[after - 1];
+        let (to_tick, to) = self.samples[after];
+        let s = ((tick - from_tick as f32) / (to_tick - from_tick) as f32).clamp(0.0, 1.0);
+        Some(Transform {
+            translation: from.translation.lerp(to.translation, s),
+            rotation: from.rotation.slerp(to.rotation, s),
+            scale: from.scale,
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn receive(
+    mut commands: Commands,
+    mut client: ResMut<Client>,
+    game: Option<Res<HostGame>>,
+    state: Res<State<AppState>>,
+    mut remotes: Query<&mut RemoteUnit>,
+    mut events: EventWriter<GameEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut visuals: Local<Option<(Handle<Mesh>, Handle<StandardMaterial>)>>,
+) {
+    let messages = client.transport.receive::<ServerMessage>();
+    for (from, message) in messages {
+        if from != client.server {
+            continue;
+        }
+        let snapshot = match message {
+            ServerMessage::Welcome { tick_rate, game: host_game } => {
+                if client.connection.is_none() {
+                    info!("Connected to {from} running at {tick_rate} Hz");
+                }
+                let timestep = 1.0 / tick_rate.max(1.0) as f32;
+                client.connection = Some(Connection { timestep, latest: 0, received: Instant::now() });
+                if game.as_deref() != Some(&host_game) {
+                    commands.insert_resource(host_game);
+                }
+                continue;
+            }
+            // Units are spawned once the game of the server is loaded.
+            ServerMessage::Snapshot(_) if *state != AppState::InGame => continue,
+            ServerMessage::Snapshot(snapshot) => snapshot,
+        };
+        let Some(connection) = client.connection.as_mut() else {
+            continue;
+        };
+        let Snapshot { tick, entities, left, events: snapshot_events } = snapshot;
+        if tick > connection.latest {
+            connection.latest = tick;
+            connection.received = Instant::now();
+        }
+
+        for id in left {
+            if let Some(entity) = client.entities.remove(&id) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        for state in entities {
+            let transform = state.transform();
+            if let Some(mut remote) = client.entity(state.id).and_then(|entity| remotes.get_mut(entity).ok()) {
+                remote.push(tick, transform);
+                remote.pools = state.pools;
+                remote.goal = state.goal;
+                continue;
+            }
+
+            let (mesh, material) = visuals
+                .get_or_insert_with(|| (meshes.add(Capsule3d::new(0.5, 1.0)), materials.add(Color::ORANGE_RED)))
+                .clone();
+            let mut remote = RemoteUnit { pools: state.pools, goal: state.goal, ..default() };
+            remote.push(tick, transform);
+            let entity = commands
+                .spawn((
+                    Name::unit(format!("remote {}", state.id.0)),
+                    PbrBundle { mesh, material, transform, ..default() },
+                    state.id,
+                    remote,
+                    EstimatedVelocity::default(),
+                    StateScoped(AppState::InGame),
+                ))
+                .id();
+            client.entities.insert(state.id, entity);
+        }
+
+        for event in snapshot_events {
+            let event = match event {
+                NetEvent::OrderIssued { entity, goal } => {
+                    let Some(entity) = client.entity(entity) else {
+                        continue;
+                    };
+                    GameEvent::OrderIssued { entity, goal: NetGoal::into_goal(goal, |id| client.entity(id)) }
+                }
+                NetEvent::SpellCast { caster, target } => {
+                    let Some(caster) = client.entity(caster) else {
+                        continue;
+                    };
+                    GameEvent::SpellCast { caster, target: target.and_then(|target| client.entity(target)) }
+                }
+            };
+            events.send(event);
+        }
+    }
+}
+
+/// Says hello until the server answers, afterwards reports the focus of the camera for interest management, also
+/// before there's a camera so the server doesn't drop the client.
+fn keepalive(
+    time: Res<Time>,
+    mut client: ResMut<Client>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    if !client.keepalive.tick(time.delta()).just_finished() {
+        return;
+    }
+    let message = match client.connection {
+        None => ClientMessage::Hello { version: PROTOCOL_VERSION },
+        Some(_) => ClientMessage::Focus(cameras.get_single().ok().map(|(camera, camera_transform)| {
+            let (origin, direction) = math::world_space_ray_from_ndc(Vec2::ZERO, camera, camera_transform);
+            math::plane_intersection(origin, direction, Vec3::ZERO, Vec3::Y).xz().to_array()
+        })),
+    };
+    client.transport.send(client.server, &message);
+}
+
+fn interpolate(client: Res<Client>, mut remotes: Query<(&RemoteUnit, &mut Transform)>) {
+    let Some(connection) = &client.connection else {
+        return;
+    };
+    let tick = connection.server_tick() - INTERPOLATION_DELAY;
+    for (remote, mut transform) in &mut remotes {
+        if let Some(sample) = remote.sample(tick) {
+            *transform = sample;
+        }
+    }
+}
+
+fn disconnect(mut client: ResMut<Client>) {
+    if client.connection.take().is_some() {
+        client.transport.send(client.server, &ClientMessage::Bye);
+    }
+    // Remote units are despawned with the in game state.
+    client.entities.clear();
+}
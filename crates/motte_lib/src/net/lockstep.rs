@@ -0,0 +1,591 @@
+//! Lockstep mode, an alternative to replicating state. Only orders are exchanged & every peer simulates the same
+//! ticks with the same orders, which relies on the `determinism` feature.
+//!
+//! - Orders are changes to the [`Goal`] of a replicated unit made outside of the simulation. They're reverted & sent to
+//!   the host instead, to be applied [`input delay`](Lockstep::input_delay) ticks later on every peer.
+//! - Peers join from the main menu & load the [`HostGame`] once the host accepts them, so every peer sets up the same
+//!   map from the same seed.
+//! - The host (`--host`) merges the orders of all peers into a step per tick. A tick is only simulated once its step is
+//!   confirmed, so all peers wait for the slowest one.
+//! - Every [`HASH_INTERVAL`] ticks the peers report their [`StateHash`] & the host announces a desync on mismatch.
+//! - Peers that time out stop holding back the others. They rejoin with their previous id & catch up on the steps they
+//!   missed, peers joining late replay all steps since the start.
+//!
+//! `motte --lockstep --host [addr] [--input-delay <ticks>]` or `motte --lockstep --connect <addr>`
+
+use std::{collections::BTreeMap, net::SocketAddr};
+
+use bevy::time::TimeUpdateStrategy;
+
+use super::{
+    gate_simulation, is_client, is_server,
+    protocol::{HostMessage, NetEvent, NetGoal, PeerMessage, PROTOCOL_VERSION},
+    transport::Transport,
+    HostGame, NetId, NetRole, NetSystems, Replicated,
+};
+use crate::{
+    app_state::{AppState, InGameState},
+    determinism::StateHash,
+    events::GameEvent,
+    navigation::{agent::Agent, flow_field::pathing::Goal},
+    prelude::*,
+    simulation::SimulationConfig,
+};
+
+/// Ticks between issuing an order & applying it, unless set with `--input-delay`.
+pub const DEFAULT_INPUT_DELAY: u32 = 4;
+
+/// Ticks between state hash reports.
+pub const HASH_INTERVAL: u32 = 30;
+
+/// Peers that haven't been heard from for this long are dropped & reconnect.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+const JOIN_INTERVAL: Duration = Duration::from_millis(500);
+
+const MAX_STEPS_PER_MESSAGE: usize = 32;
+
+/// Peers with more confirmed steps than this queued simulate a tick every frame to catch up.
+const CATCH_UP_STEPS: usize = 8;
+
+/// Hashes kept by the host to compare with late reports.
+const MAX_HASHES: usize = 64;
+
+pub(super) struct LockstepPlugin;
+
+impl Plugin for LockstepPlugin {
+    fn build(&self, app: &mut App) {
+        let role = *app.world.resource::<NetRole>();
+        let transport = match role {
+            NetRole::Offline => return warn!("--lockstep requires --host or --connect"),
+            NetRole::Server { bind } => Transport::bind(bind),
+            NetRole::Client { server } => Transport::bind_any(server),
+        };
+        let transport = match transport {
+            Ok(transport) => transport,
+            Err(err) => {
+                error!("{err:?}");
+                app.insert_resource(NetRole::Offline);
+                return;
+            }
+        };
+        match role {
+            NetRole::Server { bind } => {
+                info!("Hosting lockstep session on {bind}");
+                app.insert_resource(Host::new(transport));
+            }
+            NetRole::Client { server } => {
+                info!("Joining lockstep session on {server}");
+                app.insert_resource(Peer::new(transport, server));
+            }
+            NetRole::Offline => unreachable!(),
+        }
+
+        app_register_types!(ConfirmedGoal);
+        app.insert_resource(Lockstep::new(input_delay(std::env::args()).unwrap_or(DEFAULT_INPUT_DELAY)));
+
+        // Peers join outside of a game, the host only accepts them once its game is set up.
+        let in_game = in_state(AppState::InGame);
+        app.add_systems(
+            PreUpdate,
+            (host_receive.run_if(is_server.and_then(in_game.clone())), peer_receive.run_if(is_client)),
+        );
+        app.add_systems(FixedUpdate, (assign_ids, step).chain().in_set(NetSystems::Receive));
+        app.add_systems(FixedUpdate, (confirm_goals, advance).chain().in_set(NetSystems::Send));
+        // Stalls the simulation on ticks without a confirmed step instead of advancing them.
+        for set in NetSystems::ALL {
+            app.configure_sets(FixedUpdate, set.run_if(confirmed));
+        }
+        gate_simulation(app, || confirmed);
+        app.add_systems(
+            Last,
+            (
+                capture_orders.run_if(in_game.clone()),
+                (host_send.run_if(is_server.and_then(in_game.clone())), peer_send.run_if(is_client)),
+                pace.run_if(in_game),
+            )
+                .chain(),
+        );
+        app.add_systems(OnExit(AppState::InGame), reset);
+    }
+}
+
+/// Run condition for the simulation, whether the step of the current tick is confirmed.
+fn confirmed(lockstep: Res<Lockstep>) -> bool {
+    !lockstep.is_waiting()
+}
+
+fn input_delay(mut args: impl Iterator<Item = String>) -> Option<u32> {
+    let arg = args.find(|arg| arg == "--input-delay").and_then(|_| args.next())?;
+    match arg.parse() {
+        Ok(delay) => Some(delay),
+        Err(err) => {
+            error!("invalid input delay '{arg}': {err}");
+            None
+        }
+    }
+}
+
+/// Progress of the lockstep simulation on this peer.
+#[derive(Resource)]
+pub struct Lockstep {
+    input_delay: u32,
+    /// Next tick to simulate.
+    tick: u32,
+    /// Confirmed steps that haven't been simulated yet.
+    steps: BTreeMap<u32, Vec<NetEvent>>,
+    /// First step that hasn't been confirmed.
+    received: u32,
+    /// Orders issued since the last tick.
+    pending: Vec<NetEvent>,
+    /// Orders of this peer that aren't confirmed yet, by tick.
+    outgoing: BTreeMap<u32, Vec<NetEvent>>,
+    /// State hashes to report, by tick.
+    hashes: Vec<(u32, u64)>,
+    next_id: u32,
+    accumulator: Duration,
+    last_frame: Option<Instant>,
+    desync: Option<u32>,
+}
+
+impl Lockstep {
+    fn new(input_delay: u32) -> Self {
+        Self {
+            input_delay,
+            tick: 0,
+            steps: BTreeMap::new(),
+            received: 0,
+            pending: Vec::new(),
+            outgoing: BTreeMap::new(),
+            hashes: Vec::new(),
+            next_id: 0,
+            accumulator: Duration::ZERO,
+            last_frame: None,
+            desync: None,
+        }
+    }
+
+    /// Returns true if the next tick can't be simulated until its step is confirmed.
+    pub fn is_waiting(&self) -> bool {
+        !self.steps.contains_key(&self.tick)
+    }
+
+    fn report_desync(&mut self, tick: u32) {
+        if self.desync.is_none() {
+            error!("Simulation desynced at tick {tick}");
+            self.desync = Some(tick);
+        }
+    }
+}
+
+/// [`Goal`] as of the last simulated tick, changes that don't match it were made outside of the simulation.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct ConfirmedGoal(pub Goal);
+
+#[derive(Resource)]
+struct Host {
+    transport: Transport,
+    /// Indexed by peer id, the host itself is peer `0`.
+    peers: Vec<PeerSlot>,
+    /// All confirmed steps since the start, for peers catching up.
+    history: Vec<Vec<NetEvent>>,
+    hashes: BTreeMap<u32, u64>,
+    desync: Option<u32>,
+}
+
+impl Host {
+    fn new(transport: Transport) -> Self {
+        Self { transport, peers: vec![PeerSlot::new(None)], history: Vec::new(), hashes: BTreeMap::new(), desync: None }
+    }
+
+    fn compare_hash(&mut self, peer: u8, tick: u32, hash: u64) {
+        let expected = *self.hashes.entry(tick).or_insert(hash);
+        if expected != hash {
+            warn!("Peer {peer} reported hash {hash:016x} at tick {tick}, expected {expected:016x}");
+            self.desync.get_or_insert(tick);
+        }
+        while self.hashes.len() > MAX_HASHES {
+            self.hashes.pop_first();
+        }
+    }
+}
+
+struct PeerSlot {
+    /// `None` for the host.
+    addr: Option<SocketAddr>,
+    connected: bool,
+    last_seen: Instant,
+    /// First tick the peer sent orders for, the host waits for its orders of every tick from then on.
+    expects_from: Option<u32>,
+    orders: BTreeMap<u32, Vec<NetEvent>>,
+    /// First step the peer hasn't received.
+    next: u32,
+}
+
+impl PeerSlot {
+    fn new(addr: Option<SocketAddr>) -> Self {
+        Self { addr, connected: true, last_seen: Instant::now(), expects_from: None, orders: BTreeMap::new(), next: 0 }
+    }
+
+    fn disconnect(&mut self) {
+        self.connected = false;
+        self.expects_from = None;
+        self.orders.clear();
+    }
+
+    /// Adds the orders of ticks that aren't confirmed yet.
+    fn add_orders(&mut self, confirmed: u32, orders: impl IntoIterator<Item = (u32, Vec<NetEvent>)>) {
+        for (tick, orders) in orders.into_iter().filter(|&(tick, _)| tick >= confirmed) {
+            let expects_from = self.expects_from.get_or_insert(tick);
+            *expects_from = (*expects_from).min(tick);
+            self.orders.insert(tick, orders);
+        }
+    }
+
+    /// Returns true if the orders for `tick` are known or not needed.
+    fn has_orders(&self, tick: u32) -> bool {
+        !self.connected || self.expects_from.map_or(true, |from| tick < from) || self.orders.contains_key(&tick)
+    }
+}
+
+#[derive(Resource)]
+struct Peer {
+    transport: Transport,
+    host: SocketAddr,
+    /// Id assigned by the host, kept to rejoin as the same peer.
+    id: Option<u8>,
+    accepted: bool,
+    last_heard: Option<Instant>,
+    last_join: Option<Instant>,
+}
+
+impl Peer {
+    fn new(transport: Transport, host: SocketAddr) -> Self {
+        Self { transport, host, id: None, accepted: false, last_heard: None, last_join: None }
+    }
+}
+
+fn host_receive(mut host: ResMut<Host>, lockstep: Res<Lockstep>, game: Res<HostGame>) {
+    let now = Instant::now();
+    let Host { transport, peers, history, .. } = &mut *host;
+    let confirmed = history.len() as u32;
+    let mut hashes = Vec::new();
+    for (from, message) in transport.receive::<PeerMessage>() {
+        match message {
+            PeerMessage::Join { version, .. } if version != PROTOCOL_VERSION => {
+                warn!("Peer {from} uses protocol version {version}, expected {PROTOCOL_VERSION}");
+            }
+            PeerMessage::Join { peer, .. } => {
+                let id = match peer.filter(|&id| id != 0 && (id as usize) < peers.len()) {
+                    Some(id) => id,
+                    None if peers.len() > u8::MAX as usize => {
+                        warn!("Refusing peer {from}, the session is full");
+                        continue;
+                    }
+                    None => {
+                        peers.push(PeerSlot::new(Some(from)));
+                        (peers.len() - 1) as u8
+                    }
+                };
+                let slot = &mut peers[id as usize];
+                if !slot.connected || slot.addr != Some(from) {
+                    info!("Peer {id} rejoined from {from}");
+                    slot.disconnect();
+                    slot.connected = true;
+                } else if peer.is_none() {
+                    info!("Peer {id} joined from {from}");
+                }
+                slot.addr = Some(from);
+                slot.last_seen = now;
+                let input_delay = lockstep.input_delay;
+                transport.send(from, &HostMessage::Accepted { peer: id, input_delay, game: game.clone() });
+            }
+            PeerMessage::Orders { peer, next, orders } => {
+                if let Some(slot) = slot(peers, peer, from) {
+                    slot.last_seen = now;
+                    slot.next = next;
+                    slot.add_orders(confirmed, orders);
+                }
+            }
+            PeerMessage::Hash { peer, tick, hash } => {
+                if slot(peers, peer, from).is_some() {
+                    hashes.push((peer, tick, hash));
+                }
+            }
+            PeerMessage::Leave { peer } => {
+                if let Some(slot) = slot(peers, peer, from) {
+                    info!("Peer {peer} left");
+                    slot.disconnect();
+                }
+            }
+        }
+    }
+
+    for (id, slot) in peers.iter_mut().enumerate().skip(1) {
+        if slot.connected && now.duration_since(slot.last_seen) > TIMEOUT {
+            info!("Peer {id} timed out");
+            slot.disconnect();
+        }
+    }
+    for (peer, tick, hash) in hashes {
+        host.compare_hash(peer, tick, hash);
+    }
+}
+
+/// Returns the slot of a connected remote peer if `from` is its address.
+fn slot(peers: &mut [PeerSlot], id: u8, from: SocketAddr) -> Option<&mut PeerSlot> {
+    peers.get_mut(id as usize).filter(|slot| id != 0 && slot.connected && slot.addr == Some(from))
+}
+
+fn host_send(mut host: ResMut<Host>, mut lockstep: ResMut<Lockstep>) {
+    let confirmed = host.history.len() as u32;
+    let outgoing = std::mem::take(&mut lockstep.outgoing);
+    host.peers[0].add_orders(confirmed, outgoing);
+    for (tick, hash) in std::mem::take(&mut lockstep.hashes) {
+        host.compare_hash(0, tick, hash);
+    }
+
+    // Orders of the host for a tick are issued `input_delay` ticks before it, don't confirm ticks it can still issue
+    // orders for.
+    let Host { transport, peers, history, desync, .. } = &mut *host;
+    while (history.len() as u32) < lockstep.tick + lockstep.input_delay {
+        let tick = history.len() as u32;
+        if !peers.iter().all(|slot| slot.has_orders(tick)) {
+            break;
+        }
+        let step = peers.iter_mut().flat_map(|slot| slot.orders.remove(&tick).unwrap_or_default()).collect_vec();
+        lockstep.steps.insert(tick, step.clone());
+        history.push(step);
+    }
+    lockstep.received = history.len() as u32;
+
+    let desync = desync.take();
+    for slot in peers.iter().filter(|slot| slot.connected) {
+        let Some(addr) = slot.addr else {
+            continue;
+        };
+        // Also sent without steps, as a keepalive.
+        let start = (slot.next as usize).min(history.len());
+        let steps = history[start..(start + MAX_STEPS_PER_MESSAGE).min(history.len())].to_vec();
+        transport.send(addr, &HostMessage::Steps { tick: start as u32, steps });
+        if let Some(tick) = desync {
+            transport.send(addr, &HostMessage::Desync { tick });
+        }
+    }
+    if let Some(tick) = desync {
+        lockstep.report_desync(tick);
+    }
+}
+
+fn peer_receive(
+    mut commands: Commands,
+    mut peer: ResMut<Peer>,
+    mut lockstep: ResMut<Lockstep>,
+    game: Option<Res<HostGame>>,
+) {
+    let now = Instant::now();
+    let messages = peer.transport.receive::<HostMessage>();
+    for (from, message) in messages {
+        if from != peer.host {
+            continue;
+        }
+        peer.last_heard = Some(now);
+        match message {
+            HostMessage::Accepted { peer: id, input_delay, game: host_game } => {
+                if !peer.accepted {
+                    info!("Joined lockstep session as peer {id} with an input delay of {input_delay} ticks");
+                }
+                peer.id = Some(id);
+                peer.accepted = true;
+                lockstep.input_delay = input_delay;
+                // Loaded by `join` before entering the game.
+                if game.as_deref() != Some(&host_game) {
+                    commands.insert_resource(host_game);
+                }
+            }
+            HostMessage::Steps { tick, steps } => {
+                // Steps are resent until acknowledged, only take the next ones in order.
+                for (tick, step) in (tick..).zip(steps) {
+                    if tick == lockstep.received {
+                        lockstep.steps.insert(tick, step);
+                        lockstep.received += 1;
+                    }
+                }
+            }
+            HostMessage::Desync { tick } => lockstep.report_desync(tick),
+        }
+    }
+
+    if peer.accepted && peer.last_heard.is_some_and(|last| now.duration_since(last) > TIMEOUT) {
+        warn!("Lost connection to {}, reconnecting", peer.host);
+        peer.accepted = false;
+    }
+}
+
+fn peer_send(mut peer: ResMut<Peer>, mut lockstep: ResMut<Lockstep>) {
+    let received = lockstep.received;
+    lockstep.outgoing = lockstep.outgoing.split_off(&received);
+
+    let now = Instant::now();
+    let Peer { transport, host, id, accepted, last_join, .. } = &mut *peer;
+    match (*accepted, *id) {
+        (true, Some(id)) => {
+            let orders = lockstep.outgoing.iter().map(|(&tick, orders)| (tick, orders.clone())).collect();
+            transport.send(*host, &PeerMessage::Orders { peer: id, next: received, orders });
+            for (tick, hash) in std::mem::take(&mut lockstep.hashes) {
+                transport.send(*host, &PeerMessage::Hash { peer: id, tick, hash });
+            }
+        }
+        _ if last_join.map_or(true, |last| now.duration_since(last) >= JOIN_INTERVAL) => {
+            *last_join = Some(now);
+            transport.send(*host, &PeerMessage::Join { version: PROTOCOL_VERSION, peer: *id });
+        }
+        _ => {}
+    }
+}
+
+/// Entities differ between peers, new agents are ordered by their position which is the same on all peers.
+fn assign_ids(
+    mut commands: Commands,
+    mut lockstep: ResMut<Lockstep>,
+    agents: Query<(Entity, &Transform, Option<&Goal>), (With<Agent>, Without<NetId>)>,
+) {
+    let agents = agents
+        .iter()
+        .sorted_by_key(|(_, transform, _)| {
+            let [x, y, z] = transform.translation.to_array().map(f32::to_bits);
+            (x, z, y)
+        })
+        .collect_vec();
+    for (entity, _, goal) in agents {
+        let id = NetId(lockstep.next_id);
+        lockstep.next_id += 1;
+        commands.entity(entity).insert((id, Replicated, ConfirmedGoal(goal.copied().unwrap_or_default())));
+    }
+}
+
+fn step(
+    mut commands: Commands,
+    mut lockstep: ResMut<Lockstep>,
+    units: Query<(Entity, &NetId)>,
+    mut events: EventWriter<GameEvent>,
+    state_hash: Res<StateHash>,
+) {
+    let tick = lockstep.tick;
+    // The step is removed once the tick is done, see `advance`.
+    let Some(orders) = lockstep.steps.get_mut(&tick).map(std::mem::take) else {
+        return error!("Simulating tick {tick} without a confirmed step");
+    };
+    if tick % HASH_INTERVAL == 0 {
+        lockstep.hashes.push((tick, state_hash.hash));
+    }
+
+    if orders.is_empty() {
+        return;
+    }
+    let entities = units.iter().map(|(entity, &id)| (id, entity)).collect::<HashMap<_, _>>();
+    let entity = |id| entities.get(&id).copied();
+    for order in orders {
+        match order {
+            NetEvent::OrderIssued { entity: id, goal } => {
+                let Some(unit) = entity(id) else {
+                    continue;
+                };
+                let goal = NetGoal::into_goal(goal, entity);
+                commands.entity(unit).insert((goal, ConfirmedGoal(goal)));
+            }
+            NetEvent::SpellCast { caster, target } => {
+                let Some(caster) = entity(caster) else {
+                    continue;
+                };
+                events.send(GameEvent::SpellCast { caster, target: target.and_then(entity) });
+            }
+        }
+    }
+}
+
+/// Ends the tick, orders issued since the last one are sent to be applied after the input delay.
+fn advance(mut lockstep: ResMut<Lockstep>) {
+    let tick = lockstep.tick;
+    lockstep.steps.remove(&tick);
+    let pending = std::mem::take(&mut lockstep.pending);
+    lockstep.outgoing.insert(tick + lockstep.input_delay, pending);
+    lockstep.tick += 1;
+}
+
+fn confirm_goals(mut goals: Query<(&Goal, &mut ConfirmedGoal), Changed<Goal>>) {
+    for (&goal, mut confirmed) in &mut goals {
+        confirmed.0 = goal;
+    }
+}
+
+/// Reverts goals changed outside of the simulation & queues them as orders.
+fn capture_orders(
+    mut lockstep: ResMut<Lockstep>,
+    mut units: Query<(&NetId, &mut Goal, &ConfirmedGoal), Changed<Goal>>,
+    ids: Query<&NetId>,
+) {
+    for (&id, mut goal, confirmed) in &mut units {
+        if *goal == confirmed.0 {
+            continue;
+        }
+        let order = NetGoal::from_goal(*goal, |entity| ids.get(entity).ok().copied());
+        if order.is_none() && *goal != Goal::None {
+            warn!("Ignoring order for {id:?}, only goals on replicated entities can be sent");
+        } else {
+            lockstep.pending.push(NetEvent::OrderIssued { entity: id, goal: order });
+        }
+        *goal.bypass_change_detection() = confirmed.0;
+    }
+}
+
+/// Lets the next frame run exactly one fixed tick & physics step if its step is confirmed, otherwise none.
+fn pace(
+    mut lockstep: ResMut<Lockstep>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    config: Res<SimulationConfig>,
+    state: Res<State<InGameState>>,
+) {
+    if !state.simulating() {
+        *strategy = TimeUpdateStrategy::Automatic;
+        lockstep.last_frame = None;
+        return;
+    }
+
+    let now = Instant::now();
+    let elapsed = lockstep.last_frame.replace(now).map_or(Duration::ZERO, |last| now.duration_since(last));
+    let timestep = config.timestep();
+    // Slow frames slow down the simulation instead of building up ticks.
+    lockstep.accumulator = (lockstep.accumulator + elapsed).min(timestep * 2);
+
+    let due = lockstep.accumulator >= timestep || lockstep.steps.len() > CATCH_UP_STEPS;
+    if due && !lockstep.is_waiting() {
+        lockstep.accumulator = lockstep.accumulator.saturating_sub(timestep);
+        *strategy = TimeUpdateStrategy::ManualDuration(timestep);
+    } else {
+        *strategy = TimeUpdateStrategy::ManualDuration(Duration::ZERO);
+    }
+}
+
+fn reset(
+    mut lockstep: ResMut<Lockstep>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    host: Option<ResMut<Host>>,
+    peer: Option<ResMut<Peer>>,
+) {
+    *strategy = TimeUpdateStrategy::Automatic;
+    *lockstep = Lockstep::new(lockstep.input_delay);
+    if let Some(mut host) = host {
+        host.peers.truncate(1);
+        host.peers[0] = PeerSlot::new(None);
+        host.history.clear();
+        host.hashes.clear();
+    }
+    if let Some(mut peer) = peer
+        && let (true, Some(id)) = (peer.accepted, peer.id)
+    {
+        peer.transport.send(peer.host, &PeerMessage::Leave { peer: id });
+        peer.accepted = false;
+    }
+}
@@ -0,0 +1,305 @@
+//! Replicates the authoritative simulation of a server to clients.
+//!
+//! The server assigns a [`NetId`] to every [`Replicated`] entity (all agents are replicated) & sends a [`Snapshot`]
+//! of their transforms, pools & orders every [`SEND_INTERVAL`] fixed ticks, together with the orders & spell casts
+//! since the last snapshot. Each client only receives entities within [`INTEREST_RADIUS`] of its camera. Clients
+//! spawn a [`RemoteUnit`] per replicated entity & render it [`INTERPOLATION_DELAY`] ticks in the past, interpolating
+//! between the two surrounding snapshots. Clients don't simulate themselves, they join the [`HostGame`] of the server
+//! once it answers & only load its map.
+//!
+//! `motte --host [addr]` or `motte --connect <addr>`, see [`lockstep`] for the lockstep mode.
+//!
+//! [`Snapshot`]: protocol::Snapshot
+//! [`RemoteUnit`]: client::RemoteUnit
+
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "presentation")]
+use self::client::{Client, ClientPlugin};
+use self::{protocol::PoolState, server::ServerPlugin};
+use crate::{
+    app_state::simulating,
+    difficulty::Difficulty,
+    movement::MovementSystems,
+    navigation::{agent::Agent, flow_field::FlowFieldSystems, NavigationSystems},
+    prelude::*,
+    simulation::GameplaySystems,
+    stats::pool::Current,
+};
+#[cfg(feature = "presentation")]
+use crate::{
+    app_state::AppState,
+    asset_management::MapDefAssets,
+    determinism::GameSeed,
+    in_game::map::{self, MapDef, SelectedMap},
+};
+
+#[cfg(feature = "presentation")]
+pub mod client;
+#[cfg(feature = "lockstep")]
+pub mod lockstep;
+pub mod protocol;
+pub mod server;
+mod transport;
+
+/// Port used when an address is given without one.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// A snapshot is sent every this many fixed ticks.
+pub const SEND_INTERVAL: u32 = 2;
+
+/// Clients receive entities within this distance of their camera.
+pub const INTEREST_RADIUS: f32 = 120.0;
+
+/// Remote units are rendered this many ticks behind the latest snapshot, so there is usually a newer snapshot to
+/// interpolate towards.
+pub const INTERPOLATION_DELAY: f32 = SEND_INTERVAL as f32 * 2.0;
+
+ordered_sets! {
+    pub enum NetSystems: FixedUpdate {
+        /// Applies messages from the server or clients before the simulation runs.
+        Receive.before(NavigationSystems::Setup),
+        /// Sends snapshots of the simulated state.
+        Send.after(NavigationSystems::Cleanup).after(MovementSystems::State).after(GameplaySystems::Objectives),
+    }
+    .run_if(simulating)
+}
+
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(NetId, Replicated);
+        app.init_resource::<NetRole>();
+        if let Some(role) = NetRole::from_args(std::env::args().skip(1)) {
+            app.insert_resource(role);
+        }
+        app.init_resource::<ReplicatedPools>();
+        NetSystems::configure(app);
+        #[cfg(feature = "presentation")]
+        {
+            app.add_systems(OnEnter(AppState::InGame), host.run_if(is_server));
+            app.add_systems(Update, join.run_if(is_client.and_then(in_state(AppState::MainMenu))));
+            app.add_systems(OnEnter(AppState::LoadingMap), hold.run_if(is_client));
+        }
+        if std::env::args().any(|arg| arg == "--lockstep") {
+            #[cfg(feature = "lockstep")]
+            app.add_plugins(lockstep::LockstepPlugin);
+            #[cfg(not(feature = "lockstep"))]
+            error!("--lockstep requires the `lockstep` feature");
+        } else {
+            app.add_plugins(ServerPlugin);
+            #[cfg(feature = "presentation")]
+            app.add_plugins(ClientPlugin);
+            app.add_systems(PreUpdate, replicate_agents.run_if(is_server));
+        }
+    }
+}
+
+/// Whether this app simulates & sends state, receives it or runs on its own.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NetRole {
+    #[default]
+    Offline,
+    Server {
+        bind: SocketAddr,
+    },
+    Client {
+        server: SocketAddr,
+    },
+}
+
+impl NetRole {
+    /// Parses `--host [addr]` & `--connect <addr>`, other arguments are ignored. `--host` binds to all interfaces
+    /// unless it's followed by an address instead of another flag.
+    pub fn from_args(args: impl Iterator<Item = String>) -> Option<Self> {
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            let role = match arg.as_str() {
+                "--host" => {
+                    let bind = args.next_if(|arg| !arg.starts_with("--"));
+                    Self::Server { bind: parse_addr(bind.as_deref().unwrap_or("0.0.0.0"))? }
+                }
+                "--connect" => Self::Client { server: parse_addr(args.next().as_deref()?)? },
+                _ => continue,
+            };
+            return Some(role);
+        }
+        None
+    }
+}
+
+fn parse_addr(addr: &str) -> Option<SocketAddr> {
+    let parsed = addr
+        .parse::<SocketAddr>()
+        .or_else(|_| addr.parse::<std::net::IpAddr>().map(|ip| SocketAddr::new(ip, DEFAULT_PORT)))
+        .or_else(|_| {
+            use std::net::ToSocketAddrs;
+            let addr = if addr.contains(':') { addr.to_owned() } else { format!("{addr}:{DEFAULT_PORT}") };
+            addr.to_socket_addrs()?.next().ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
+        });
+    match parsed {
+        Ok(addr) => Some(addr),
+        Err(err) => {
+            error!("invalid network address '{addr}': {err:?}");
+            None
+        }
+    }
+}
+
+pub fn is_server(role: Res<NetRole>) -> bool {
+    matches!(*role, NetRole::Server { .. })
+}
+
+pub fn is_client(role: Res<NetRole>) -> bool {
+    matches!(*role, NetRole::Client { .. })
+}
+
+/// Run condition for the simulation, clients outside of lockstep mode show the one of their server instead.
+#[cfg(feature = "presentation")]
+pub fn simulated(client: Option<Res<Client>>) -> bool {
+    client.is_none()
+}
+
+/// Adds a run condition to every set of the simulation.
+pub(crate) fn gate_simulation<M, C: Condition<M>>(app: &mut App, condition: impl Fn() -> C) {
+    fn gate<M, C: Condition<M>>(
+        app: &mut App,
+        sets: impl IntoIterator<Item = impl SystemSet>,
+        condition: &impl Fn() -> C,
+    ) {
+        for set in sets {
+            app.configure_sets(FixedUpdate, set.run_if(condition()));
+        }
+    }
+    gate(app, FlowFieldSystems::ALL, &condition);
+    gate(app, NavigationSystems::ALL, &condition);
+    gate(app, MovementSystems::ALL, &condition);
+    gate(app, GameplaySystems::ALL, &condition);
+}
+
+/// Map, difficulty & rng seed of the game of the host, clients & lockstep peers have to load the same game.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HostGame {
+    /// Name of the [`MapDef`].
+    pub map: String,
+    pub difficulty: Difficulty,
+    pub seed: u64,
+}
+
+#[cfg(feature = "presentation")]
+fn host(
+    mut commands: Commands,
+    selected: Res<SelectedMap>,
+    defs: Res<Assets<MapDef>>,
+    difficulty: Res<Difficulty>,
+    seed: Res<GameSeed>,
+) {
+    let map = defs.get(&**selected).expect("selected map should be loaded");
+    commands.insert_resource(HostGame { map: map.name.clone(), difficulty: *difficulty, seed: **seed });
+}
+
+/// Loads the game of the host once it's received.
+#[cfg(feature = "presentation")]
+fn join(
+    mut commands: Commands,
+    game: Option<Res<HostGame>>,
+    maps: Res<MapDefAssets>,
+    defs: Res<Assets<MapDef>>,
+    mut difficulty: ResMut<Difficulty>,
+    mut seed: ResMut<GameSeed>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(game) = game.filter(|game| game.is_changed()) else {
+        return;
+    };
+    let Some(handle) = map::find(&game.map, &maps, &defs) else {
+        return error!("The host plays map '{}' which isn't available", game.map);
+    };
+    info!("Joining the game of the host on map '{}'", game.map);
+    *difficulty = game.difficulty;
+    // Applied before the map is set up, the seed also determines e.g. its random obstacles.
+    *seed = GameSeed(game.seed);
+    commands.insert_resource(SelectedMap(handle.clone()));
+    next_state.set(AppState::LoadingMap);
+}
+
+/// Clients only load the game of the host, other maps wait in the main menu until it's received.
+#[cfg(feature = "presentation")]
+fn hold(
+    selected: Res<SelectedMap>,
+    defs: Res<Assets<MapDef>>,
+    game: Option<Res<HostGame>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let hosted = game.is_some_and(|game| defs.get(&**selected).is_some_and(|map| map.name == game.map));
+    if !hosted {
+        warn!("Waiting for the host to start a game");
+        next_state.set(AppState::MainMenu);
+    }
+}
+
+/// Identifies a replicated entity across the server & all clients.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[reflect(Component)]
+pub struct NetId(pub u32);
+
+/// Marks an entity to be replicated by the server.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct Replicated;
+
+fn replicate_agents(mut commands: Commands, agents: Query<Entity, (With<Agent>, Without<Replicated>)>) {
+    for entity in &agents {
+        commands.entity(entity).insert(Replicated);
+    }
+}
+
+/// Reads the pools of an entity that are included in its snapshot, see [`AppNetExt::replicate_pool`].
+#[derive(Resource, Default)]
+pub(crate) struct ReplicatedPools(Vec<fn(&World, Entity) -> Option<PoolState>>);
+
+impl ReplicatedPools {
+    pub(crate) fn read(&self, world: &World, entity: Entity) -> Vec<PoolState> {
+        self.0.iter().filter_map(|read| read(world, entity)).collect()
+    }
+}
+
+pub trait AppNetExt {
+    /// Includes pool `S` (e.g. health) of replicated entities in snapshots.
+    fn replicate_pool<S: Stat + Component>(&mut self) -> &mut Self;
+}
+
+impl AppNetExt for App {
+    fn replicate_pool<S: Stat + Component>(&mut self) -> &mut Self {
+        let mut pools = self.world.get_resource_or_insert_with(ReplicatedPools::default);
+        pools.0.push(|world, entity| {
+            let current = world.get::<Current<S>>(entity)?;
+            let total = world.get::<S>(entity)?;
+            Some(PoolState { name: S::name(), current: current.value(), total: total.value() })
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn role(args: &[&str]) -> Option<NetRole> {
+        NetRole::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn host_address_is_optional() {
+        let any = SocketAddr::new([0, 0, 0, 0].into(), DEFAULT_PORT);
+        assert_eq!(role(&["--host"]), Some(NetRole::Server { bind: any }));
+        assert_eq!(role(&["--host", "--lockstep"]), Some(NetRole::Server { bind: any }));
+        let local = SocketAddr::new([127, 0, 0, 1].into(), 4000);
+        assert_eq!(role(&["--lockstep", "--host", "127.0.0.1:4000"]), Some(NetRole::Server { bind: local }));
+        assert_eq!(role(&["--connect", "127.0.0.1:4000"]), Some(NetRole::Client { server: local }));
+        assert_eq!(role(&["--lockstep"]), None);
+    }
+}
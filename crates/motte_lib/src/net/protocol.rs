@@ -0,0 +1,142 @@
+//! Messages sent between the server & clients, encoded as json.
+
+use serde::{Deserialize, Serialize};
+
+use super::{HostGame, NetId};
+use crate::{
+    navigation::flow_field::{fields::Cell, pathing::Goal},
+    prelude::*,
+};
+
+/// Bumped on any change to the messages, clients with another version are ignored.
+pub const PROTOCOL_VERSION: u16 = 5;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ClientMessage {
+    Hello {
+        version: u16,
+    },
+    /// Center of the area the client is interested in on the ground plane, `None` until it has a camera. Also sent as
+    /// a keepalive.
+    Focus(Option<[f32; 2]>),
+    Bye,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ServerMessage {
+    Welcome { tick_rate: f64, game: HostGame },
+    Snapshot(Snapshot),
+}
+
+/// State of the simulation at `tick`. Large snapshots are split over several messages with the same tick.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Snapshot {
+    pub tick: u32,
+    pub entities: Vec<EntityState>,
+    /// Entities that were despawned or left the interest area of the client.
+    pub left: Vec<NetId>,
+    pub events: Vec<NetEvent>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EntityState {
+    pub id: NetId,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub pools: Vec<PoolState>,
+    pub goal: Option<NetGoal>,
+}
+
+impl EntityState {
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(Vec3::from(self.translation)).with_rotation(Quat::from_array(self.rotation))
+    }
+}
+
+#[derive(Serialize, Deserialize, Reflect, Clone, Debug, PartialEq)]
+pub struct PoolState {
+    pub name: String,
+    pub current: f32,
+    pub total: f32,
+}
+
+#[derive(Serialize, Deserialize, Reflect, Clone, Copy, Debug, PartialEq)]
+pub enum NetGoal {
+    Entity(NetId),
+    Cell(u8, u8),
+}
+
+impl NetGoal {
+    /// Returns `None` for [`Goal::None`] & goals on entities without a [`NetId`].
+    pub fn from_goal(goal: Goal, id: impl Fn(Entity) -> Option<NetId>) -> Option<Self> {
+        match goal {
+            Goal::Entity(entity) => id(entity).map(Self::Entity),
+            Goal::Cell(cell) => {
+                let (x, y) = *cell;
+                Some(Self::Cell(x, y))
+            }
+            Goal::None => None,
+        }
+    }
+
+    /// Returns [`Goal::None`] if the entity of the goal isn't known.
+    pub fn into_goal(goal: Option<Self>, entity: impl Fn(NetId) -> Option<Entity>) -> Goal {
+        match goal {
+            Some(Self::Entity(id)) => entity(id).map_or(Goal::None, Goal::Entity),
+            Some(Self::Cell(x, y)) => Goal::Cell(Cell::from((x, y))),
+            None => Goal::None,
+        }
+    }
+}
+
+/// Replicated subset of [`GameEvent`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum NetEvent {
+    OrderIssued { entity: NetId, goal: Option<NetGoal> },
+    SpellCast { caster: NetId, target: Option<NetId> },
+}
+
+/// Sent by lockstep peers to the host.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum PeerMessage {
+    /// Sent until accepted, `peer` is the id of a previous session to reconnect as.
+    Join {
+        version: u16,
+        peer: Option<u8>,
+    },
+    /// Orders of the peer for every tick that isn't confirmed yet, `next` is the first step it hasn't received.
+    Orders {
+        peer: u8,
+        next: u32,
+        orders: Vec<(u32, Vec<NetEvent>)>,
+    },
+    /// [`StateHash`](crate::determinism::StateHash) at the start of `tick`.
+    Hash {
+        peer: u8,
+        tick: u32,
+        hash: u64,
+    },
+    Leave {
+        peer: u8,
+    },
+}
+
+/// Sent by the lockstep host to its peers.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum HostMessage {
+    Accepted {
+        peer: u8,
+        input_delay: u32,
+        /// Peers have to simulate the same game.
+        game: HostGame,
+    },
+    /// Confirmed orders of all peers for consecutive ticks starting at `tick`.
+    Steps {
+        tick: u32,
+        steps: Vec<Vec<NetEvent>>,
+    },
+    /// Peers reported different hashes at `tick`.
+    Desync {
+        tick: u32,
+    },
+}
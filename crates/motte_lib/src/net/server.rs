@@ -0,0 +1,207 @@
+use std::net::SocketAddr;
+
+use super::{
+    is_server,
+    protocol::{ClientMessage, EntityState, NetEvent, NetGoal, ServerMessage, Snapshot, PROTOCOL_VERSION},
+    transport::Transport,
+    HostGame, NetId, NetRole, NetSystems, Replicated, ReplicatedPools, INTEREST_RADIUS, SEND_INTERVAL,
+};
+use crate::{
+    app_state::AppState, events::GameEvent, navigation::flow_field::pathing::Goal, prelude::*,
+    simulation::SimulationConfig,
+};
+
+/// Clients that haven't sent anything for this long are dropped.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Snapshots with more entities are split over several messages to keep them below the maximum datagram size.
+const ENTITIES_PER_MESSAGE: usize = 64;
+
+pub(super) struct ServerPlugin;
+
+impl Plugin for ServerPlugin {
+    fn build(&self, app: &mut App) {
+        let NetRole::Server { bind } = *app.world.resource::<NetRole>() else {
+            return;
+        };
+        match Transport::bind(bind) {
+            Ok(transport) => {
+                info!("Hosting on {bind}");
+                app.insert_resource(Server::new(transport));
+            }
+            Err(err) => {
+                error!("{err:?}");
+                app.insert_resource(NetRole::Offline);
+                return;
+            }
+        }
+
+        app.add_systems(PreUpdate, assign_ids.run_if(is_server));
+        app.add_systems(FixedUpdate, receive.in_set(NetSystems::Receive).run_if(is_server));
+        app.add_systems(FixedUpdate, send.in_set(NetSystems::Send).run_if(is_server));
+        app.add_systems(Last, queue_events.run_if(in_state(AppState::InGame).and_then(is_server)));
+    }
+}
+
+#[derive(Resource)]
+pub struct Server {
+    transport: Transport,
+    clients: HashMap<SocketAddr, ClientState>,
+    next_id: u32,
+    tick: u32,
+    /// Events since the last snapshot.
+    events: Vec<NetEvent>,
+}
+
+impl Server {
+    fn new(transport: Transport) -> Self {
+        Self { transport, clients: HashMap::default(), next_id: 0, tick: 0, events: Vec::new() }
+    }
+}
+
+struct ClientState {
+    /// Only entities near this position are sent, all entities are sent until the client reports its focus.
+    focus: Option<Vec2>,
+    /// Entities the client received, to tell it which ones left its interest area.
+    known: HashSet<NetId>,
+    last_seen: Instant,
+}
+
+impl ClientState {
+    fn new(now: Instant) -> Self {
+        Self { focus: None, known: HashSet::default(), last_seen: now }
+    }
+
+    fn interested_in(&self, state: &EntityState) -> bool {
+        let [x, _, z] = state.translation;
+        self.focus.map_or(true, |focus| focus.distance_squared(Vec2::new(x, z)) <= INTEREST_RADIUS * INTEREST_RADIUS)
+    }
+}
+
+fn assign_ids(mut commands: Commands, mut server: ResMut<Server>, replicated: Query<Entity, Added<Replicated>>) {
+    for entity in &replicated {
+        commands.entity(entity).insert(NetId(server.next_id));
+        server.next_id += 1;
+    }
+}
+
+fn receive(mut server: ResMut<Server>, config: Res<SimulationConfig>, game: Res<HostGame>) {
+    let now = Instant::now();
+    let Server { transport, clients, .. } = &mut *server;
+    for (from, message) in transport.receive::<ClientMessage>() {
+        match message {
+            ClientMessage::Hello { version } if version == PROTOCOL_VERSION => {
+                if clients.insert(from, ClientState::new(now)).is_none() {
+                    info!("Client {from} connected");
+                }
+                transport.send(from, &ServerMessage::Welcome { tick_rate: config.tick_rate, game: game.clone() });
+            }
+            ClientMessage::Hello { version } => {
+                warn!("Client {from} uses protocol version {version}, expected {PROTOCOL_VERSION}");
+            }
+            ClientMessage::Focus(focus) => {
+                if let Some(client) = clients.get_mut(&from) {
+                    client.focus = focus.map(Vec2::from);
+                    client.last_seen = now;
+                }
+            }
+            ClientMessage::Bye => {
+                if clients.remove(&from).is_some() {
+                    info!("Client {from} disconnected");
+                }
+            }
+        }
+    }
+
+    clients.retain(|addr, client| {
+        let alive = now.duration_since(client.last_seen) < CLIENT_TIMEOUT;
+        if !alive {
+            info!("Client {addr} timed out");
+        }
+        alive
+    });
+}
+
+fn queue_events(mut server: ResMut<Server>, mut events: EventReader<GameEvent>, ids: Query<&NetId>) {
+    let id = |entity| ids.get(entity).ok().copied();
+    for event in events.read() {
+        let event = match *event {
+            GameEvent::OrderIssued { entity, goal: order } => {
+                let Ok(&entity) = ids.get(entity) else {
+                    continue;
+                };
+                NetEvent::OrderIssued { entity, goal: NetGoal::from_goal(order, id) }
+            }
+            GameEvent::SpellCast { caster, target } => {
+                let Ok(&caster) = ids.get(caster) else {
+                    continue;
+                };
+                NetEvent::SpellCast { caster, target: target.and_then(id) }
+            }
+            _ => continue,
+        };
+        server.events.push(event);
+    }
+}
+
+fn send(world: &mut World) {
+    let tick = {
+        let mut server = world.resource_mut::<Server>();
+        server.tick += 1;
+        server.tick
+    };
+    if tick % SEND_INTERVAL != 0 {
+        return;
+    }
+
+    let mut query = world.query_filtered::<(Entity, &NetId, &Transform, Option<&Goal>), With<Replicated>>();
+    let states = {
+        let world = &*world;
+        let pools = world.resource::<ReplicatedPools>();
+        query
+            .iter(world)
+            .map(|(entity, &id, transform, goal)| EntityState {
+                id,
+                translation: transform.translation.to_array(),
+                rotation: transform.rotation.to_array(),
+                pools: pools.read(world, entity),
+                goal: goal.and_then(|&goal| NetGoal::from_goal(goal, |entity| world.get::<NetId>(entity).copied())),
+            })
+            .collect_vec()
+    };
+
+    let mut server = world.resource_mut::<Server>();
+    let events = std::mem::take(&mut server.events);
+    let Server { transport, clients, .. } = &mut *server;
+    for (&addr, client) in clients.iter_mut() {
+        let visible = states.iter().filter(|state| client.interested_in(state)).collect_vec();
+        let ids = visible.iter().map(|state| state.id).collect::<HashSet<_>>();
+        let mut left = client.known.difference(&ids).copied().collect_vec();
+        let involved = |id: &NetId| ids.contains(id);
+        let mut events = events
+            .iter()
+            .filter(|event| match event {
+                NetEvent::OrderIssued { entity, .. } => involved(entity),
+                NetEvent::SpellCast { caster, target } => involved(caster) || target.as_ref().is_some_and(involved),
+            })
+            .copied()
+            .collect_vec();
+        client.known = ids;
+
+        let mut chunks = visible.chunks(ENTITIES_PER_MESSAGE).peekable();
+        if chunks.peek().is_none() {
+            let snapshot = Snapshot { tick, entities: Vec::new(), left, events };
+            transport.send(addr, &ServerMessage::Snapshot(snapshot));
+            continue;
+        }
+        for chunk in chunks {
+            let snapshot = Snapshot {
+                tick,
+                entities: chunk.iter().map(|&state| state.clone()).collect(),
+                left: std::mem::take(&mut left),
+                events: std::mem::take(&mut events),
+            };
+            transport.send(addr, &ServerMessage::Snapshot(snapshot));
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::prelude::*;
+
+/// Non-blocking udp socket sending json encoded messages.
+pub(super) struct Transport {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+}
+
+impl Transport {
+    pub(super) fn bind(addr: SocketAddr) -> AnyResult<Self> {
+        let socket = UdpSocket::bind(addr).with_context(|| format!("failed to bind {addr}"))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket, buffer: vec![0; u16::MAX as usize] })
+    }
+
+    /// Binds any free port of the same address family as `remote`.
+    pub(super) fn bind_any(remote: SocketAddr) -> AnyResult<Self> {
+        match remote {
+            SocketAddr::V4(_) => Self::bind((Ipv4Addr::UNSPECIFIED, 0).into()),
+            SocketAddr::V6(_) => Self::bind((Ipv6Addr::UNSPECIFIED, 0).into()),
+        }
+    }
+
+    pub(super) fn send<T: Serialize>(&self, to: SocketAddr, message: &T) {
+        let bytes = match serde_json::to_vec(message) {
+            Ok(bytes) => bytes,
+            Err(err) => return error!("failed to encode message: {err}"),
+        };
+        if let Err(err) = self.socket.send_to(&bytes, to) {
+            warn!("failed to send {} bytes to {to}: {err}", bytes.len());
+        }
+    }
+
+    /// Returns all messages received since the last call, malformed messages are skipped.
+    pub(super) fn receive<T: DeserializeOwned>(&mut self) -> Vec<(SocketAddr, T)> {
+        let mut messages = Vec::new();
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((len, from)) => match serde_json::from_slice(&self.buffer[..len]) {
+                    Ok(message) => messages.push((from, message)),
+                    Err(err) => debug!("malformed message from {from}: {err}"),
+                },
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
+                // Sending to a closed port is reported on the next receive on some platforms.
+                Err(err) if err.kind() == std::io::ErrorKind::ConnectionReset => continue,
+                Err(err) => {
+                    warn!("failed to receive: {err}");
+                    break;
+                }
+            }
+        }
+        messages
+    }
+}
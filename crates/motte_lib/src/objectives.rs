@@ -0,0 +1,256 @@
+//! Win & lose conditions of a map, see [`ObjectivesDef`]. The [`Objectives`] of the current map are evaluated every
+//! fixed tick while simulating, the game is won once every victory condition is met & lost as soon as any defeat
+//! condition is. Either enters [`InGameState::GameOver`] with the [`Outcome`] recorded in [`Objectives::outcome`].
+
+use serde::Deserialize;
+
+use crate::{
+    app_state::{AppState, InGameState},
+    economy::Team,
+    events::GameEvent,
+    navigation::agent::Agent,
+    prelude::*,
+    save::AppSaveExt,
+    simulation::GameplaySystems,
+};
+
+pub struct ObjectivesPlugin;
+
+impl Plugin for ObjectivesPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(ObjectivesDef, Condition, ObjectiveTarget, Outcome);
+        app.register_save::<Target>();
+        app.add_systems(
+            FixedUpdate,
+            (progress, outcome).chain().in_set(GameplaySystems::Objectives).run_if(resource_exists::<Objectives>),
+        );
+        app.add_systems(OnExit(AppState::InGame), |mut commands: Commands| commands.remove_resource::<Objectives>());
+    }
+}
+
+/// Target of a map, attacked by the waves of its scenario.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Target;
+
+/// Win & lose conditions declared by a map, maps without any are lost when their [`Target`] is destroyed.
+#[derive(Reflect, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ObjectivesDef {
+    /// All have to be met to win, the game can't be won without any.
+    pub victory: Vec<Condition>,
+    /// Any has to be met to lose.
+    pub defeat: Vec<Condition>,
+}
+
+impl Default for ObjectivesDef {
+    fn default() -> Self {
+        Self { victory: Vec::new(), defeat: vec![Condition::Destroy(ObjectiveTarget::Target)] }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub enum Condition {
+    /// An entity matching the target died.
+    Destroy(ObjectiveTarget),
+    /// The given number of waves spawned & no hostile units are left.
+    SurviveWaves(u32),
+    /// Only units of the player were within `radius` of `center` for `seconds` in a row.
+    HoldZone { center: Vec2, radius: f32, seconds: f32 },
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Condition::Destroy(ObjectiveTarget::Target) => write!(f, "Destroy the target"),
+            Condition::Destroy(ObjectiveTarget::Named(name)) => write!(f, "Destroy {name}"),
+            Condition::SurviveWaves(count) => write!(f, "Survive {count} waves"),
+            Condition::HoldZone { seconds, .. } => write!(f, "Hold the zone for {seconds:.0} seconds"),
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub enum ObjectiveTarget {
+    /// The map's [`Target`].
+    Target,
+    /// Units spawned with [`Name::unit`] of this name.
+    Named(String),
+}
+
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Victory,
+    Defeat,
+}
+
+/// Progress of a [`Condition`].
+#[derive(Clone, Copy, Default, Debug)]
+struct ConditionState {
+    met: bool,
+    waves: u32,
+    /// Seconds the zone has been held.
+    held: f32,
+}
+
+/// [`ObjectivesDef`] of the current map & their progress, inserted when the map is spawned.
+#[derive(Resource, Debug)]
+pub struct Objectives {
+    def: ObjectivesDef,
+    victory: Vec<ConditionState>,
+    defeat: Vec<ConditionState>,
+    outcome: Option<Outcome>,
+    /// Seconds simulated since the map was spawned.
+    elapsed: f32,
+}
+
+impl Objectives {
+    pub fn new(def: ObjectivesDef) -> Self {
+        let victory = vec![ConditionState::default(); def.victory.len()];
+        let defeat = vec![ConditionState::default(); def.defeat.len()];
+        Self { def, victory, defeat, outcome: None, elapsed: 0.0 }
+    }
+
+    /// Replaces the conditions, their progress starts over.
+    pub fn set(&mut self, def: ObjectivesDef) {
+        *self = Self { elapsed: self.elapsed, ..Self::new(def) };
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Conditions to win & whether each has been met.
+    pub fn victory(&self) -> impl Iterator<Item = (&Condition, bool)> {
+        self.def.victory.iter().zip(self.victory.iter().map(|state| state.met))
+    }
+}
+
+fn progress(
+    mut objectives: ResMut<Objectives>,
+    mut events: EventReader<GameEvent>,
+    targets: Query<(), With<Target>>,
+    names: Query<&Name>,
+    agents: Query<(&GlobalTransform, Option<&Team>), With<Agent>>,
+    time: Res<Time<Fixed>>,
+) {
+    let events: SmallVec<[GameEvent; 8]> = events.read().copied().collect();
+    let destroyed = |target: &ObjectiveTarget| {
+        events.iter().any(|event| {
+            let GameEvent::Died { entity } = *event else {
+                return false;
+            };
+            match target {
+                ObjectiveTarget::Target => targets.contains(entity),
+                ObjectiveTarget::Named(name) => names.get(entity).is_ok_and(|other| *other == Name::unit(name.clone())),
+            }
+        })
+    };
+    let waves = events.iter().filter(|event| matches!(event, GameEvent::WaveSpawned { .. })).count() as u32;
+    let hostile = |team: Option<&Team>| team != Some(&Team::PLAYER);
+    let hostiles_left = agents.iter().any(|(_, team)| hostile(team));
+
+    objectives.elapsed += time.delta_seconds();
+    let Objectives { def, victory, defeat, .. } = &mut *objectives;
+    let conditions = def.victory.iter().zip(victory.iter_mut()).chain(def.defeat.iter().zip(defeat.iter_mut()));
+    for (condition, state) in conditions {
+        match condition {
+            Condition::Destroy(target) => state.met |= destroyed(target),
+            Condition::SurviveWaves(count) => {
+                state.waves += waves;
+                state.met |= state.waves >= *count && !hostiles_left;
+            }
+            &Condition::HoldZone { center, radius, seconds } => {
+                let (mut friendly, mut contested) = (false, false);
+                for (transform, team) in &agents {
+                    if transform.translation().xz().distance(center) > radius {
+                        continue;
+                    }
+                    if hostile(team) {
+                        contested = true;
+                    } else {
+                        friendly = true;
+                    }
+                }
+                state.held = if friendly && !contested { state.held + time.delta_seconds() } else { 0.0 };
+                state.met |= state.held >= seconds;
+            }
+        }
+    }
+}
+
+fn outcome(mut objectives: ResMut<Objectives>, mut next_state: ResMut<NextState<InGameState>>) {
+    let outcome = if objectives.defeat.iter().any(|state| state.met) {
+        Outcome::Defeat
+    } else if !objectives.victory.is_empty() && objectives.victory.iter().all(|state| state.met) {
+        Outcome::Victory
+    } else {
+        return;
+    };
+    info!("Game over: {outcome:?}");
+    objectives.outcome = Some(outcome);
+    next_state.set(InGameState::GameOver);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world(def: ObjectivesDef) -> (World, Schedule) {
+        let mut world = World::new();
+        world.init_resource::<Time<Fixed>>();
+        world.init_resource::<Events<GameEvent>>();
+        world.init_resource::<NextState<InGameState>>();
+        world.insert_resource(Objectives::new(def));
+        let mut schedule = Schedule::default();
+        schedule.add_systems((progress, outcome).chain());
+        (world, schedule)
+    }
+
+    fn result(world: &World) -> Option<Outcome> {
+        world.resource::<Objectives>().outcome()
+    }
+
+    fn game_over(world: &World) -> bool {
+        world.resource::<NextState<InGameState>>().0 == Some(InGameState::GameOver)
+    }
+
+    #[test]
+    fn victory_once_waves_are_survived() {
+        let def = ObjectivesDef { victory: vec![Condition::SurviveWaves(1)], defeat: Vec::new() };
+        let (mut world, mut schedule) = world(def);
+        let hostile = world.spawn((Agent::Medium, Team::HOSTILE, GlobalTransform::default())).id();
+
+        world.send_event(GameEvent::WaveSpawned { wave: 1, count: 1 });
+        schedule.run(&mut world);
+        assert_eq!(result(&world), None);
+        assert!(!game_over(&world));
+
+        // Dead units stop being agents.
+        world.entity_mut(hostile).remove::<Agent>();
+        world.send_event(GameEvent::Died { entity: hostile });
+        schedule.run(&mut world);
+        assert_eq!(result(&world), Some(Outcome::Victory));
+        assert!(game_over(&world));
+    }
+
+    #[test]
+    fn defeat_once_the_target_dies() {
+        let (mut world, mut schedule) = world(ObjectivesDef::default());
+        let target = world.spawn(Target).id();
+        let unit = world.spawn((Agent::Medium, Team::PLAYER, GlobalTransform::default())).id();
+
+        world.send_event(GameEvent::Died { entity: unit });
+        schedule.run(&mut world);
+        assert_eq!(result(&world), None);
+
+        world.send_event(GameEvent::Died { entity: target });
+        schedule.run(&mut world);
+        assert_eq!(result(&world), Some(Outcome::Defeat));
+        assert!(game_over(&world));
+    }
+}
@@ -0,0 +1,107 @@
+//! Swept hit detection for fast projectiles. A [`FastProjectile`] casts a sphere from its position at the previous
+//! physics step to the current one, so thin obstacles it would otherwise tunnel through still register a
+//! [`ProjectileHit`]. This is independent of the solver, the projectile doesn't need to collide with anything.
+
+use bevy_xpbd_3d::{PhysicsSchedule, PhysicsStepSet};
+
+use super::layers;
+use crate::prelude::*;
+
+pub struct CcdPlugin;
+
+impl Plugin for CcdPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(FastProjectile, ProjectileHit);
+        app.add_event::<ProjectileHit>();
+        app.add_systems(PhysicsSchedule, sweep.after(PhysicsStepSet::SpatialQuery));
+    }
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct FastProjectile {
+    pub radius: f32,
+    /// Layers the projectile hits.
+    pub filter: LayerMask,
+    /// Entity the projectile never hits, e.g. its caster.
+    pub ignore: Option<Entity>,
+    /// Position at the previous physics step.
+    previous: Option<Vec3>,
+}
+
+impl FastProjectile {
+    pub fn new(radius: f32) -> Self {
+        Self { radius, filter: layers::projectile().filters, ignore: None, previous: None }
+    }
+
+    pub fn with_ignore(mut self, entity: Entity) -> Self {
+        self.ignore = Some(entity);
+        self
+    }
+}
+
+/// Sent for the first obstacle a [`FastProjectile`] swept through during a physics step, `entity` is resolved to the
+/// rigid body of the collider that was hit.
+#[derive(Event, Reflect, Clone, Copy, Debug)]
+pub struct ProjectileHit {
+    pub projectile: Entity,
+    pub entity: Entity,
+    /// Center of the projectile at the time of impact.
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+pub(crate) fn sweep(
+    mut projectiles: Query<(Entity, &mut FastProjectile)>,
+    mut hits: EventWriter<ProjectileHit>,
+    // The spatial query reads the positions that projectiles are stopped at.
+    mut queries: ParamSet<(SpatialQuery, Query<&mut Position, With<FastProjectile>>)>,
+    parents: Query<&ColliderParent>,
+) {
+    let mut impacts = Vec::new();
+    for (entity, mut projectile) in &mut projectiles {
+        let Ok(position) = queries.p1().get(entity).map(|position| position.0) else {
+            continue;
+        };
+        let Some(previous) = projectile.previous.replace(position) else {
+            continue;
+        };
+        let travel = position - previous;
+        let Ok(direction) = Direction3d::new(travel) else {
+            continue;
+        };
+
+        let filter = SpatialQueryFilter::from_mask(projectile.filter)
+            .with_excluded_entities(std::iter::once(entity).chain(projectile.ignore));
+        let Some(hit) = queries.p0().cast_shape(
+            &Collider::sphere(projectile.radius),
+            previous,
+            Quat::IDENTITY,
+            direction,
+            travel.length(),
+            false,
+            filter,
+        ) else {
+            continue;
+        };
+
+        // Stops the projectile at the impact, it won't be swept past the obstacle next step.
+        let point = previous + *direction * hit.time_of_impact;
+        projectile.previous = Some(point);
+        impacts.push((entity, point));
+
+        hits.send(ProjectileHit {
+            projectile: entity,
+            entity: parents.get(hit.entity).map_or(hit.entity, ColliderParent::get),
+            point,
+            normal: -hit.normal2,
+        });
+    }
+
+    let mut positions = queries.p1();
+    for (entity, point) in impacts {
+        if let Ok(mut position) = positions.get_mut(entity) {
+            position.0 = point;
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Per-entity interpolation of rigid body visuals. Physics runs at a fixed timestep, a visual child with
+//! [`InterpolateVisual`] is offset from its rigid body so it's drawn between the previous & current physics pose while
+//! the body itself stays on the fixed-step pose. Rigid bodies with [`NoInterpolation`] opt all their visuals out.
+//!
+//! Translation isn't interpolated for visuals snapped by the [pixelate](crate::graphics::pixelate) camera, the pixel
+//! grid already hides sub-pixel steps & interpolating inside it only makes the snapped position jitter between pixels.
+
+use bevy_xpbd_3d::PhysicsSchedule;
+use bevy_xpbd_3d_interp::{plugin::XPBDInterpolationPlugin, InterpolationCopySet, InterpolationSet};
+
+use crate::{
+    graphics::pixelate::{snap_transforms_camera_active, MainSnapTransformsCamera, Snap},
+    prelude::*,
+};
+
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(InterpolateVisual, NoInterpolation, PreviousPose);
+        app.add_plugins(XPBDInterpolationPlugin);
+        app.add_systems(PhysicsSchedule, copy.in_set(InterpolationCopySet));
+        app.add_systems(PostUpdate, (setup, interpolate).chain().in_set(InterpolationSet::Interpolation));
+    }
+}
+
+/// Interpolates the [`Transform`] of a direct child of a [`RigidBody`], the transform it's spawned with is kept as the
+/// offset from the rigid body.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct InterpolateVisual {
+    pub translation: bool,
+    pub rotation: bool,
+    /// Local transform relative to the rigid body, `None` until set up.
+    local: Option<Transform>,
+}
+
+impl Default for InterpolateVisual {
+    fn default() -> Self {
+        Self { translation: true, rotation: true, local: None }
+    }
+}
+
+impl InterpolateVisual {
+    #[allow(unused)]
+    pub fn rotation_only() -> Self {
+        Self { translation: false, ..default() }
+    }
+}
+
+/// Opts the visuals of a [`RigidBody`] out of interpolation, they're drawn on the fixed-step pose.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
+pub struct NoInterpolation;
+
+/// Pose of a rigid body before the latest physics step.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct PreviousPose {
+    position: Vec3,
+    rotation: Quat,
+}
+
+fn setup(
+    mut commands: Commands,
+    mut visuals: Query<(&mut InterpolateVisual, &Transform, &Parent), Added<InterpolateVisual>>,
+    bodies: Query<(&Position, &Rotation), (With<RigidBody>, Without<PreviousPose>)>,
+) {
+    for (mut visual, transform, parent) in &mut visuals {
+        visual.local.get_or_insert(*transform);
+        if let Ok((position, rotation)) = bodies.get(parent.get()) {
+            commands.entity(parent.get()).insert(PreviousPose { position: position.0, rotation: rotation.0 });
+        }
+    }
+}
+
+fn copy(mut bodies: Query<(&mut PreviousPose, &Position, &Rotation)>) {
+    for (mut previous, position, rotation) in &mut bodies {
+        previous.position = position.0;
+        previous.rotation = rotation.0;
+    }
+}
+
+fn interpolate(
+    mut visuals: Query<(&mut Transform, &InterpolateVisual, &Parent, Option<&Snap>), Without<RigidBody>>,
+    bodies: Query<(&Position, &Rotation, &PreviousPose, Option<&Snap>, Has<NoInterpolation>)>,
+    snap_camera: Option<Res<MainSnapTransformsCamera>>,
+    time: Res<Time<Physics>>,
+) {
+    let alpha = match time.timestep_mode() {
+        TimestepMode::Fixed { delta, overstep, .. } if !delta.is_zero() => {
+            (overstep.as_secs_f32() / delta.as_secs_f32()).clamp(0.0, 1.0)
+        }
+        _ => 1.0,
+    };
+    let snapping = snap_transforms_camera_active(snap_camera);
+
+    for (mut transform, visual, parent, visual_snap) in &mut visuals {
+        let Some(local) = visual.local else {
+            continue;
+        };
+        let Ok((position, rotation, previous, body_snap, disabled)) = bodies.get(parent.get()) else {
+            continue;
+        };
+        if disabled {
+            transform.set_if_neq(local);
+            continue;
+        }
+
+        let snapped = snapping && visual_snap.or(body_snap).is_some_and(Snap::is_translation_snapped);
+        let inverse = rotation.0.inverse();
+        let mut offset = Transform::IDENTITY;
+        if visual.translation && !snapped {
+            offset.translation = inverse * (previous.position.lerp(position.0, alpha) - position.0);
+        }
+        if visual.rotation {
+            offset.rotation = inverse * previous.rotation.slerp(rotation.0, alpha);
+        }
+        transform.set_if_neq(offset * local);
+    }
+}
@@ -0,0 +1,76 @@
+//! Collision layers & the presets used when spawning colliders, which layers collide is defined by [`INTERACTIONS`].
+
+use crate::prelude::*;
+
+#[derive(PhysicsLayer, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CollisionLayer {
+    Player,
+    Units,
+    Terrain,
+    Sensor,
+    Projectile,
+    Corpse,
+}
+
+/// Layers each layer filters for. Two colliders only collide if both filter for the other's layer, so the resulting
+/// interactions are:
+///
+/// |            | Player | Units | Terrain | Sensor | Projectile | Corpse |
+/// |------------|:------:|:-----:|:-------:|:------:|:----------:|:------:|
+/// | Player     |        |   x   |    x    |        |            |        |
+/// | Units      |   x    |       |    x    |        |     x      |        |
+/// | Terrain    |   x    |   x   |    x    |        |     x      |   x    |
+/// | Sensor     |        |       |         |        |            |        |
+/// | Projectile |        |   x   |    x    |        |            |        |
+/// | Corpse     |        |       |    x    |        |            |        |
+///
+/// Units don't collide with each other, local avoidance keeps them apart. Corpses only rest on the terrain. Sensors
+/// don't filter for anything & are only found through spatial queries, unless they're a
+/// [`super::triggers::TriggerVolume`], see [`trigger`].
+pub(crate) const INTERACTIONS: [(CollisionLayer, &[CollisionLayer]); 6] = {
+    use CollisionLayer::*;
+    [
+        (Player, &[Units, Terrain]),
+        (Units, &[Player, Terrain, Sensor, Projectile]),
+        (Terrain, &[Player, Units, Terrain, Projectile, Corpse]),
+        (Sensor, &[]),
+        (Projectile, &[Units, Terrain]),
+        (Corpse, &[Terrain]),
+    ]
+};
+
+/// [`CollisionLayers`] of `layer` with its filters from [`INTERACTIONS`].
+pub(crate) fn layers(layer: CollisionLayer) -> CollisionLayers {
+    let (_, filters) =
+        INTERACTIONS.iter().find(|(other, _)| *other == layer).expect("every layer should have an interaction entry");
+    CollisionLayers::new(layer, filters.iter().fold(LayerMask::NONE, |mask, filter| mask | filter))
+}
+
+/// Agents & other character motors.
+pub(crate) fn unit_collider() -> CollisionLayers {
+    layers(CollisionLayer::Units)
+}
+
+/// Static level geometry & obstacles.
+pub(crate) fn terrain() -> CollisionLayers {
+    layers(CollisionLayer::Terrain)
+}
+
+pub(crate) fn sensor() -> CollisionLayers {
+    layers(CollisionLayer::Sensor)
+}
+
+/// Sensor filtering for `filter`, colliders on those layers only interact with it if they filter for
+/// [`CollisionLayer::Sensor`].
+pub(crate) fn trigger(filter: impl Into<LayerMask>) -> CollisionLayers {
+    CollisionLayers::new(CollisionLayer::Sensor, filter)
+}
+
+/// Ragdolls & corpses of dead units.
+pub(crate) fn corpse() -> CollisionLayers {
+    layers(CollisionLayer::Corpse)
+}
+
+pub(crate) fn projectile() -> CollisionLayers {
+    layers(CollisionLayer::Projectile)
+}
@@ -1,19 +1,20 @@
-use bevy_xpbd_3d_interp::plugin::XPBDInterpolationPlugin;
-
 use crate::prelude::*;
 
+pub mod ccd;
+#[cfg(feature = "presentation")]
+pub mod interpolation;
+pub mod layers;
+#[cfg(feature = "presentation")]
+pub mod queries;
+pub mod ragdoll;
+pub mod triggers;
+pub mod velocity;
+
 pub struct PhysicsPlugin;
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(PhysicsPlugins::default());
-        app.add_plugins(XPBDInterpolationPlugin);
+        app.add_plugins((ccd::CcdPlugin, triggers::TriggersPlugin, ragdoll::RagdollPlugin));
+        app.add_plugins(velocity::EstimatedVelocityPlugin);
     }
 }
-
-#[derive(PhysicsLayer)]
-pub(crate) enum CollisionLayer {
-    Player,
-    Units,
-    Terrain,
-    Sensor,
-}
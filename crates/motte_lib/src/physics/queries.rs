@@ -0,0 +1,50 @@
+//! Spatial queries for gameplay code, wraps [`SpatialQuery`] with helpers that work in terms of the game's
+//! [`CollisionLayer`]s & return the rigid body that was hit instead of the collider.
+
+use bevy::ecs::system::SystemParam;
+
+use super::layers::CollisionLayer;
+use crate::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    /// Rigid body of the collider that was hit, or the collider if it has none.
+    pub entity: Entity,
+    pub point: Vec3,
+    pub distance: f32,
+}
+
+#[derive(SystemParam)]
+pub struct PhysicsQueries<'w, 's> {
+    spatial: SpatialQuery<'w, 's>,
+    parents: Query<'w, 's, &'static ColliderParent>,
+}
+
+impl PhysicsQueries<'_, '_> {
+    /// First hit of a ray against colliders on any of the `layers`.
+    pub fn raycast(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        layers: impl Into<LayerMask>,
+    ) -> Option<Hit> {
+        let direction = Direction3d::new(direction).ok()?;
+        let hit =
+            self.spatial.cast_ray(origin, direction, max_distance, true, SpatialQueryFilter::from_mask(layers))?;
+        Some(Hit {
+            entity: self.body(hit.entity),
+            point: origin + *direction * hit.time_of_impact,
+            distance: hit.time_of_impact,
+        })
+    }
+
+    /// Where a ray hits the terrain, e.g. for the cursor position in the world.
+    pub fn ground_point(&self, origin: Vec3, direction: Vec3) -> Option<Vec3> {
+        self.raycast(origin, direction, f32::MAX, CollisionLayer::Terrain).map(|hit| hit.point)
+    }
+
+    fn body(&self, collider: Entity) -> Entity {
+        self.parents.get(collider).map_or(collider, ColliderParent::get)
+    }
+}
@@ -0,0 +1,106 @@
+//! Units turn into ragdolls when they die. The character motor & navigation components are swapped for a dynamic
+//! rigid body that tumbles over, once it comes to rest it's left as a static corpse for [`CORPSE_DURATION`].
+//!
+//! TODO: jointed multi-body ragdolls once units have skinned meshes, every unit is a single collider for now.
+
+use std::time::Duration;
+
+use super::layers;
+use crate::{
+    app_state::simulating,
+    despawn::DespawnAfter,
+    determinism::GameRng,
+    events::GameEvent,
+    movement::motor::{CharacterMotor, CharacterMotorBundle},
+    navigation::{
+        agent::{
+            Agent, Blocking, DesiredDirection, DesiredVelocity, Steering, TargetDistance, TargetReached,
+            TargetReachedCondition,
+        },
+        flow_field::{footprint::Footprint, pathing::Goal},
+    },
+    prelude::*,
+};
+
+/// Seconds a ragdoll may tumble before it's turned into a corpse, even if it hasn't come to rest.
+const SETTLE_TIMEOUT: f32 = 5.0;
+const CORPSE_DURATION: Duration = Duration::from_secs(10);
+const TUMBLE_IMPULSE: f32 = 2.0;
+
+pub struct RagdollPlugin;
+
+impl Plugin for RagdollPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Ragdoll, Corpse);
+        app.add_systems(Update, (died, settle.run_if(simulating)).chain());
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Ragdoll {
+    settle: Timer,
+}
+
+impl Default for Ragdoll {
+    fn default() -> Self {
+        Self { settle: Timer::from_seconds(SETTLE_TIMEOUT, TimerMode::Once) }
+    }
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Corpse;
+
+fn died(
+    mut commands: Commands,
+    mut events: EventReader<GameEvent>,
+    units: Query<&Collider, (With<CharacterMotor>, Without<Ragdoll>)>,
+    mut rng: ResMut<GameRng>,
+) {
+    for event in events.read() {
+        let GameEvent::Died { entity } = *event else {
+            continue;
+        };
+        let Ok(collider) = units.get(entity) else {
+            continue;
+        };
+
+        let tumble = Vec3::new(rng.gen_range(-1.0..1.0), 0.0, rng.gen_range(-1.0..1.0)).normalize_or_zero();
+        commands
+            .entity(entity)
+            .remove::<CharacterMotorBundle>()
+            .remove::<(
+                Agent,
+                Goal,
+                TargetReachedCondition,
+                TargetReached,
+                DesiredVelocity,
+                DesiredDirection,
+                Steering,
+                TargetDistance,
+                Blocking,
+            )>()
+            .insert((
+                // Clears the occupied cells from the obstacle field.
+                Footprint::Empty,
+                collider.clone(),
+                RigidBody::Dynamic,
+                layers::corpse(),
+                ExternalAngularImpulse::new(tumble * TUMBLE_IMPULSE),
+                Ragdoll::default(),
+            ));
+    }
+}
+
+fn settle(mut commands: Commands, mut ragdolls: Query<(Entity, &mut Ragdoll, Has<Sleeping>)>, time: Res<Time>) {
+    for (entity, mut ragdoll, sleeping) in &mut ragdolls {
+        if sleeping || ragdoll.settle.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Ragdoll>().insert((
+                RigidBody::Static,
+                Corpse,
+                DespawnAfter(CORPSE_DURATION),
+            ));
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Trigger volumes, sensors that send [`TriggerEnter`] & [`TriggerExit`] events & keep track of the entities inside
+//! them, for features like gravity volumes, water, auras or map scripting.
+
+use bevy::ecs::entity::{Entities, EntityHashSet};
+
+use super::layers::{self, CollisionLayer};
+use crate::prelude::*;
+
+pub struct TriggersPlugin;
+
+impl Plugin for TriggersPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(TriggerVolume, TriggerEnter, TriggerExit);
+        app.add_event::<TriggerEnter>().add_event::<TriggerExit>();
+        app.add_systems(PostUpdate, (setup, (enter, exit, despawned)).chain().after(PhysicsSet::StepSimulation));
+    }
+}
+
+/// Sensor that's triggered by colliders on any of the `filter` layers, requires a [`Collider`].
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct TriggerVolume {
+    pub filter: LayerMask,
+    /// Rigid bodies (or colliders without one) currently inside the volume.
+    #[reflect(ignore)]
+    occupants: EntityHashSet,
+}
+
+impl TriggerVolume {
+    pub fn new(filter: impl Into<LayerMask>) -> Self {
+        Self { filter: filter.into(), occupants: default() }
+    }
+
+    pub fn occupants(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.occupants.iter().copied()
+    }
+}
+
+impl Default for TriggerVolume {
+    fn default() -> Self {
+        Self::new([CollisionLayer::Player, CollisionLayer::Units])
+    }
+}
+
+#[derive(Event, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TriggerEnter {
+    pub trigger: Entity,
+    pub entity: Entity,
+}
+
+/// Also sent when an entity inside the trigger is despawned.
+#[derive(Event, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TriggerExit {
+    pub trigger: Entity,
+    pub entity: Entity,
+}
+
+fn setup(mut commands: Commands, triggers: Query<(Entity, &TriggerVolume), Changed<TriggerVolume>>) {
+    for (entity, trigger) in &triggers {
+        commands.entity(entity).insert((Sensor, layers::trigger(trigger.filter)));
+    }
+}
+
+/// Orders a collision pair as `(trigger, other)`, the other entity is resolved to its rigid body.
+fn resolve(
+    a: Entity,
+    b: Entity,
+    triggers: &Query<&mut TriggerVolume>,
+    parents: &Query<&ColliderParent>,
+) -> Option<(Entity, Entity)> {
+    let (trigger, other) = if triggers.contains(a) {
+        (a, b)
+    } else if triggers.contains(b) {
+        (b, a)
+    } else {
+        return None;
+    };
+    Some((trigger, parents.get(other).map_or(other, ColliderParent::get)))
+}
+
+fn enter(
+    mut started: EventReader<CollisionStarted>,
+    mut events: EventWriter<TriggerEnter>,
+    mut triggers: Query<&mut TriggerVolume>,
+    parents: Query<&ColliderParent>,
+) {
+    for &CollisionStarted(a, b) in started.read() {
+        let Some((trigger, entity)) = resolve(a, b, &triggers, &parents) else {
+            continue;
+        };
+        // A rigid body with multiple colliders only enters once.
+        if triggers.get_mut(trigger).is_ok_and(|mut volume| volume.bypass_change_detection().occupants.insert(entity)) {
+            events.send(TriggerEnter { trigger, entity });
+        }
+    }
+}
+
+fn exit(
+    mut ended: EventReader<CollisionEnded>,
+    mut events: EventWriter<TriggerExit>,
+    mut triggers: Query<&mut TriggerVolume>,
+    parents: Query<&ColliderParent>,
+) {
+    for &CollisionEnded(a, b) in ended.read() {
+        let Some((trigger, entity)) = resolve(a, b, &triggers, &parents) else {
+            continue;
+        };
+        if triggers.get_mut(trigger).is_ok_and(|mut volume| volume.bypass_change_detection().occupants.remove(&entity))
+        {
+            events.send(TriggerExit { trigger, entity });
+        }
+    }
+}
+
+/// Despawned entities don't end their collisions, remove them from the triggers they were in.
+fn despawned(
+    mut events: EventWriter<TriggerExit>,
+    mut triggers: Query<(Entity, &mut TriggerVolume)>,
+    entities: &Entities,
+) {
+    for (trigger, mut volume) in &mut triggers {
+        let volume = volume.bypass_change_detection();
+        volume.occupants.retain(|&entity| {
+            let alive = entities.contains(entity);
+            if !alive {
+                events.send(TriggerExit { trigger, entity });
+            }
+            alive
+        });
+    }
+}
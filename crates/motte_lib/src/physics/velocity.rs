@@ -0,0 +1,35 @@
+//! Velocity of entities moved without a physics body, e.g. units interpolated from the snapshots of a server, estimated
+//! from how far their [`Transform`] moved since the last frame.
+
+use crate::{
+    prelude::*,
+    previous::{Previous, PreviousPlugin},
+};
+
+pub struct EstimatedVelocityPlugin;
+
+impl Plugin for EstimatedVelocityPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(EstimatedVelocity);
+        app.add_plugins(PreviousPlugin::<Transform, With<EstimatedVelocity>>::default());
+        app.add_systems(PostUpdate, estimate.after(PhysicsSet::Sync));
+    }
+}
+
+/// Velocity of the entity over the last frame, add it to entities that need one.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, Deref)]
+#[reflect(Component)]
+pub struct EstimatedVelocity(Vec3);
+
+fn estimate(mut velocities: Query<(&mut EstimatedVelocity, &Transform, &Previous<Transform>)>, time: Res<Time>) {
+    let delta = time.delta_seconds();
+    if delta <= 0.0 {
+        return;
+    }
+    for (mut velocity, transform, previous) in &mut velocities {
+        let estimate = previous.delta(transform) / delta;
+        if velocity.0 != estimate {
+            velocity.0 = estimate;
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::camera::MainCamera;
+use crate::{
+    app_state::InGameState,
+    graphics::tween::{Ease, Tween, TweenCompleted, Tweenable},
+    prelude::*,
+};
+
+mod key_codes {
+    use bevy::input::keyboard::KeyCode;
+    pub const BOOKMARKS: [KeyCode; 4] = [KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8];
+    pub const SAVE_MODIFIERS: [KeyCode; 2] = [KeyCode::ControlLeft, KeyCode::ControlRight];
+}
+
+/// Duration of the fly-to animation when recalling a bookmark.
+const FLY_TO_DURATION_SEC: f32 = 0.6;
+
+pub struct BookmarksPlugin;
+
+impl Plugin for BookmarksPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(res: CameraBookmarks, CameraBookmark);
+
+        app.add_systems(Update, (bookmarks, fly_to).chain().run_if(in_state(InGameState::Playing)));
+    }
+}
+
+/// Saved camera locations, indexed by the order of the bookmark keys (F5-F8).
+#[derive(Resource, Default, Reflect, Deref, DerefMut)]
+#[reflect(Resource)]
+pub struct CameraBookmarks([Option<CameraBookmark>; 4]);
+
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct CameraBookmark {
+    /// Position the camera is focused on, restored as its [`camera::Follow::Position`].
+    pub position: Vec3,
+    pub zoom: f32,
+}
+
+impl Tweenable for CameraBookmark {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        CameraBookmark {
+            position: self.position.interpolate(&to.position, t),
+            zoom: self.zoom.interpolate(&to.zoom, t),
+        }
+    }
+}
+
+fn bookmarks(
+    mut commands: Commands,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    cameras: Query<(Entity, &Transform, &camera::Follow, &camera::Zoom, Option<&camera::Offset>), With<MainCamera>>,
+    followed: Query<&Transform, Without<MainCamera>>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    let Ok((entity, transform, follow, zoom, offset)) = cameras.get_single() else {
+        return;
+    };
+
+    // The camera itself is offset from what it's focused on.
+    let unfocused = || transform.translation - offset.map_or(Vec3::ZERO, |offset| offset.0);
+    let current = CameraBookmark {
+        position: match *follow {
+            camera::Follow::Position(position) => position,
+            camera::Follow::Entity(target) => {
+                followed.get(target).map_or_else(|_| unfocused(), |target| target.translation)
+            }
+            camera::Follow::None => unfocused(),
+        },
+        zoom: zoom.zoom(),
+    };
+
+    for (slot, &key) in key_codes::BOOKMARKS.iter().enumerate() {
+        if !input.just_pressed(key) {
+            continue;
+        }
+
+        if input.any_pressed(key_codes::SAVE_MODIFIERS) {
+            bookmarks[slot] = Some(current);
+            info!("Saved camera bookmark {} at {:?}", slot + 1, current.position);
+        } else if let Some(bookmark) = bookmarks[slot] {
+            // Flies from where the camera is focused, also when it was following an entity.
+            commands.entity(entity).insert((
+                camera::Follow::Position(current.position),
+                Tween::to(bookmark, FLY_TO_DURATION_SEC, Ease::SmootherStep),
+            ));
+        }
+    }
+}
+
+/// Animates the camera rig [`camera::Follow`] position & [`camera::Zoom`] towards a recalled bookmark. The camera is
+/// still moved through the rig, so pixelate snapping & sub-pixel smoothing apply as usual. Following anything else
+/// cancels the flight.
+fn fly_to(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &mut Tween<CameraBookmark>, &mut camera::Follow, &mut camera::Zoom), With<MainCamera>>,
+    mut completed: EventWriter<TweenCompleted>,
+    time: Res<Time>,
+) {
+    for (entity, mut tween, mut follow, mut zoom) in &mut cameras {
+        let camera::Follow::Position(position) = *follow else {
+            commands.entity(entity).remove::<Tween<CameraBookmark>>();
+            continue;
+        };
+
+        let (value, done) = tween.advance(CameraBookmark { position, zoom: zoom.zoom() }, time.delta_seconds());
+        if let Some(sample) = value {
+            *follow = camera::Follow::Position(sample.position);
+            zoom.set_zoom(sample.zoom);
+        }
+        if done {
+            commands.entity(entity).remove::<Tween<CameraBookmark>>();
+            completed.send(TweenCompleted { entity });
+        }
+    }
+}
@@ -2,9 +2,15 @@ use bevy::{
     core_pipeline::prepass::{DepthPrepass, NormalPrepass},
     input::mouse::MouseWheel,
     pbr::ShadowFilteringMethod,
+    render::view::RenderLayers,
+    ui::IsDefaultUiCamera,
 };
 
-use crate::{graphics::pixelate, prelude::*};
+use crate::{cursor::TouchGesture, graphics::pixelate, prelude::*, settings::Settings};
+
+const MAX_ZOOM: f32 = 100.0;
+const MIN_ZOOM: f32 = 1.0;
+
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
@@ -21,9 +27,18 @@ pub struct MainCamera;
 #[derive(Component)]
 pub struct UiWorldCamera;
 
+/// Blits the pixelated texture of the [`MainCamera`] to the window.
+#[derive(Component)]
+pub struct BlitCamera;
+
+/// Renders the UI at window resolution after the [`BlitCamera`], UI roots target it unless they have a
+/// [`TargetCamera`]. Only renders entities on [`UI_RENDER_LAYER`].
 #[derive(Component)]
 pub struct UiCamera;
 
+/// Render layer of the [`UiCamera`].
+pub const UI_RENDER_LAYER: u8 = 1;
+
 fn setup(mut commands: Commands, _asset_server: Res<AssetServer>) {
     let main_camera = commands
         .spawn((
@@ -39,13 +54,14 @@ fn setup(mut commands: Commands, _asset_server: Res<AssetServer>) {
             NormalPrepass,
             ShadowFilteringMethod::Hardware2x2,
             camera::RigTransform::default(),
+            camera::Follow::Position(Vec3::ZERO),
             camera::Zoom::with_zoom(80.0),
             camera::YawPitch::with_yaw_pitch(0.0, -55.0),
             camera::Smoothing::default().with_position(0.0).with_rotation(2.0).with_zoom(0.0),
-            pixelate::Pixelate::PixelsPerUnit(4),
-            pixelate::SnapTransforms::On,
-            pixelate::Snap::translation(),
-            pixelate::SubPixelSmoothing::On,
+            pixelate::PixelateBundle { pixelate: pixelate::Pixelate::PixelsPerUnit(4), ..default() },
+            pixelate::DynamicPixelsPerUnit::default(),
+            pixelate::PixelateBloom::default(),
+            pixelate::OrderedTransparency::default(),
             #[cfg(feature = "dev_tools")]
             bevy_transform_gizmo::GizmoPickSource::default(),
         ))
@@ -63,41 +79,73 @@ fn setup(mut commands: Commands, _asset_server: Res<AssetServer>) {
     //     RenderLayers::layer(2),
     // ));
 
+    commands.spawn((
+        BlitCamera,
+        Name::camera("blit_camera"),
+        Camera2dBundle { camera: Camera { order: 0, ..default() }, ..default() },
+        pixelate::Blitter::new(main_camera),
+    ));
+
     commands.spawn((
         UiCamera,
         Name::camera("ui_camera"),
-        Camera2dBundle { ..default() },
-        pixelate::Blitter(main_camera.into()),
+        Camera2dBundle { camera: Camera { order: 1, clear_color: ClearColorConfig::None, ..default() }, ..default() },
+        RenderLayers::layer(UI_RENDER_LAYER),
+        IsDefaultUiCamera,
     ));
 }
 
+/// Pans by the ground under a dragged finger & zooms by scrolling or pinching, pinches scale the zoom so the view
+/// follows the fingers.
+#[allow(clippy::type_complexity)]
 fn controls(
-    mut camera: Query<(&mut camera::YawPitch, &mut camera::Zoom), With<MainCamera>>,
+    mut camera: Query<
+        (&mut camera::YawPitch, &mut camera::Zoom, &mut camera::Follow, &Camera, &GlobalTransform),
+        With<MainCamera>,
+    >,
     mut scroll: EventReader<MouseWheel>,
+    mut gestures: EventReader<TouchGesture>,
     input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
 ) {
-    for (mut yaw_pitch, mut zoom) in &mut camera {
-        let yaw_input = if input.just_pressed(KeyCode::KeyQ) { 1.0 } else { 0.0 }
-            - if input.just_pressed(KeyCode::KeyE) { 1.0 } else { 0.0 };
+    let Settings { keybinds, camera: camera_settings, .. } = &*settings;
+    for (mut yaw_pitch, mut zoom, mut follow, camera, camera_transform) in &mut camera {
+        let yaw_input = if input.just_pressed(keybinds.camera_yaw_left) { 1.0 } else { 0.0 }
+            - if input.just_pressed(keybinds.camera_yaw_right) { 1.0 } else { 0.0 };
 
-        yaw_pitch.rotate_yaw(yaw_input * 90.0);
+        yaw_pitch.rotate_yaw(yaw_input * camera_settings.yaw_step);
 
-        let pitch_input = if input.just_pressed(KeyCode::KeyS) { 1.0 } else { 0.0 }
-            - if input.just_pressed(KeyCode::KeyW) { 1.0 } else { 0.0 };
+        let pitch_input = if input.just_pressed(keybinds.camera_pitch_down) { 1.0 } else { 0.0 }
+            - if input.just_pressed(keybinds.camera_pitch_up) { 1.0 } else { 0.0 };
 
-        yaw_pitch.rotate_pitch(pitch_input * 5.0);
+        yaw_pitch.rotate_pitch(pitch_input * camera_settings.pitch_step);
 
-        if input.just_pressed(KeyCode::KeyR) {
+        if input.just_pressed(keybinds.camera_reset) {
             yaw_pitch.pitch = -35.0;
             yaw_pitch.yaw = 180.0;
         }
 
-        const MAX_ZOOM: f32 = 100.0;
-        const MIN_ZOOM: f32 = 1.0;
-
         for event in scroll.read() {
             let zoom_scale = zoom.zoom();
-            zoom.set_zoom((zoom_scale - event.y).clamp(MIN_ZOOM, MAX_ZOOM));
+            zoom.set_zoom((zoom_scale - event.y * camera_settings.zoom_speed).clamp(MIN_ZOOM, MAX_ZOOM));
+        }
+
+        let ground = |ndc: Vec2| {
+            let (origin, direction) = math::world_space_ray_from_ndc(ndc, camera, camera_transform);
+            math::plane_intersection(origin, direction, Vec3::ZERO, Vec3::Y)
+        };
+        for gesture in gestures.read() {
+            match *gesture {
+                TouchGesture::Pan { from_ndc, to_ndc } => {
+                    if let camera::Follow::Position(position) = &mut *follow {
+                        *position += ground(from_ndc) - ground(to_ndc);
+                    }
+                }
+                TouchGesture::Pinch { scale } => {
+                    let zoom_scale = zoom.zoom();
+                    zoom.set_zoom((zoom_scale / scale.max(f32::EPSILON)).clamp(MIN_ZOOM, MAX_ZOOM));
+                }
+            }
         }
     }
 }
@@ -0,0 +1,251 @@
+//! Bar at the bottom of the screen with the spells of the [`Selected`] unit's [`SpellBook`]. Each slot shows the
+//! spell's icon, a radial overlay of its cooldown & its hotkey from [`Keybinds::abilities`]. Clicking a slot or
+//! pressing its hotkey enters [`Targeting`], the next left click casts the spell at the unit or ground under the cursor
+//! & right click cancels.
+//!
+//! [`Keybinds::abilities`]: crate::settings::Keybinds::abilities
+
+use super::{camera::MainCamera, selection::Selected};
+use crate::{
+    app_state::{AppState, InGameState},
+    asset_management::{icons::Icon, FontAssets},
+    cleanup::StateScoped,
+    cursor::{CursorClick, CursorPosition},
+    graphics::materials::cooldown::CooldownMaterial,
+    main_menu,
+    physics::{layers::CollisionLayer, queries::PhysicsQueries},
+    prelude::*,
+    settings::Settings,
+    spells::{CastSpell, SpellBook, SpellDef, Target},
+};
+
+/// Width & height of a slot in pixels.
+const SLOT_SIZE: f32 = 48.0;
+
+/// Icon of spells without one.
+const MISSING_ICON: &str = "missing";
+
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Hotbar, HotbarSlot);
+        app.add_systems(OnEnter(AppState::InGame), spawn);
+        app.add_systems(Update, (slots, activate, target, cooldowns).chain().run_if(in_state(InGameState::Playing)));
+        app.add_systems(OnExit(AppState::InGame), |mut commands: Commands| commands.remove_resource::<Targeting>());
+    }
+}
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct Hotbar;
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub(crate) struct HotbarSlot(usize);
+
+/// Spell waiting for a target, cast by the next left click in the world.
+#[derive(Resource, Clone, Debug)]
+pub struct Targeting {
+    pub caster: Entity,
+    pub spell: Handle<SpellDef>,
+}
+
+/// Whether the cursor is over a slot of the hotbar, clicks on it shouldn't reach the world.
+pub(crate) fn hovered(slots: &Query<&Interaction, With<HotbarSlot>>) -> bool {
+    slots.iter().any(|interaction| *interaction != Interaction::None)
+}
+
+fn spawn(mut commands: Commands) {
+    commands.spawn((
+        Name::ui("hotbar"),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(8.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        },
+        Hotbar,
+        StateScoped(AppState::InGame),
+    ));
+}
+
+/// Rebuilds the slots when another unit is selected or the spells of its book change.
+#[allow(clippy::too_many_arguments)]
+fn slots(
+    mut commands: Commands,
+    hotbars: Query<Entity, With<Hotbar>>,
+    selected: Query<(Entity, &SpellBook), With<Selected>>,
+    mut materials: ResMut<Assets<CooldownMaterial>>,
+    defs: Res<Assets<SpellDef>>,
+    fonts: Res<FontAssets>,
+    settings: Res<Settings>,
+    mut shown: Local<(Option<Entity>, Vec<AssetId<SpellDef>>)>,
+) {
+    let Ok(hotbar) = hotbars.get_single() else {
+        return;
+    };
+    let current = selected.get_single().ok();
+    let spells: Vec<_> = current.map_or_else(Vec::new, |(_, book)| book.spells().iter().map(Handle::id).collect());
+    if shown.0 == current.map(|(entity, _)| entity) && shown.1 == spells {
+        return;
+    }
+    *shown = (current.map(|(entity, _)| entity), spells);
+
+    let mut hotbar = commands.entity(hotbar);
+    hotbar.despawn_descendants();
+    let Some((_, book)) = current else {
+        return;
+    };
+
+    hotbar.with_children(|builder| {
+        for (slot, spell) in book.spells().iter().enumerate().take(settings.keybinds.abilities.len()) {
+            let icon = defs.get(spell).and_then(|def| def.icon.clone()).unwrap_or_else(|| MISSING_ICON.into());
+            let fill = Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            };
+            builder
+                .spawn((
+                    Name::ui(format!("hotbar slot {slot}")),
+                    ButtonBundle {
+                        style: Style { width: Val::Px(SLOT_SIZE), height: Val::Px(SLOT_SIZE), ..default() },
+                        background_color: BackgroundColor(Color::BLACK.with_a(0.6)),
+                        ..default()
+                    },
+                    HotbarSlot(slot),
+                ))
+                .with_children(|builder| {
+                    builder.spawn((AtlasImageBundle { style: fill.clone(), ..default() }, Icon::new(icon)));
+                    builder.spawn(MaterialNodeBundle {
+                        style: fill,
+                        material: materials.add(CooldownMaterial::default()),
+                        ..default()
+                    });
+                    builder.spawn(main_menu::text(&fonts, hotkey(settings.keybinds.abilities[slot]), 12.0).with_style(
+                        Style {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(2.0),
+                            left: Val::Px(4.0),
+                            ..default()
+                        },
+                    ));
+                });
+        }
+    });
+}
+
+/// Label of `key`, e.g. `1` for [`KeyCode::Digit1`].
+fn hotkey(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    name.strip_prefix("Digit").or_else(|| name.strip_prefix("Key")).unwrap_or(&name).to_owned()
+}
+
+/// Enters [`Targeting`] for the slot that was clicked or whose hotkey was pressed, if it's not on cooldown.
+fn activate(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    buttons: Query<(&HotbarSlot, &Interaction), Changed<Interaction>>,
+    selected: Query<(Entity, &SpellBook), With<Selected>>,
+) {
+    let Ok((caster, book)) = selected.get_single() else {
+        return;
+    };
+    let pressed = settings.keybinds.abilities.iter().position(|&key| input.just_pressed(key)).or_else(|| {
+        buttons.iter().find(|(_, interaction)| **interaction == Interaction::Pressed).map(|(slot, _)| slot.0)
+    });
+    let Some(slot) = pressed else {
+        return;
+    };
+    let Some(spell) = book.spells().get(slot).filter(|_| book.ready(slot)) else {
+        return;
+    };
+    commands.insert_resource(Targeting { caster, spell: spell.clone() });
+}
+
+/// Casts the [`Targeting`] spell on left click, cancels on right click.
+#[allow(clippy::too_many_arguments)]
+fn target(
+    mut commands: Commands,
+    mut clicks: EventReader<CursorClick>,
+    mut casts: EventWriter<CastSpell>,
+    targeting: Option<Res<Targeting>>,
+    casters: Query<(), With<SpellBook>>,
+    slots: Query<&Interaction, With<HotbarSlot>>,
+    cursor: Res<CursorPosition>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    queries: PhysicsQueries,
+) {
+    let (Some(targeting), Ok((camera, camera_transform))) = (targeting, cameras.get_single()) else {
+        clicks.clear();
+        return;
+    };
+    if !casters.contains(targeting.caster) {
+        commands.remove_resource::<Targeting>();
+        return;
+    }
+
+    for click in clicks.read() {
+        if hovered(&slots) {
+            continue;
+        }
+        match click.button {
+            MouseButton::Right => {
+                commands.remove_resource::<Targeting>();
+                return;
+            }
+            MouseButton::Left => {}
+            _ => continue,
+        }
+
+        let (origin, direction) = math::world_space_ray_from_ndc(cursor.ndc(), camera, camera_transform);
+        let target = match queries.raycast(origin, direction, f32::MAX, CollisionLayer::Units) {
+            Some(hit) => Target::Entity(hit.entity),
+            None => Target::Location(
+                queries
+                    .ground_point(origin, direction)
+                    .unwrap_or_else(|| math::plane_intersection(origin, direction, Vec3::ZERO, Vec3::Y)),
+            ),
+        };
+        casts.send(CastSpell { caster: targeting.caster, spell: targeting.spell.clone(), target });
+        commands.remove_resource::<Targeting>();
+        return;
+    }
+}
+
+/// Updates the cooldown overlays & highlights the slot being targeted.
+fn cooldowns(
+    mut slots: Query<(&HotbarSlot, &Children, &mut BackgroundColor)>,
+    overlays: Query<&Handle<CooldownMaterial>>,
+    mut materials: ResMut<Assets<CooldownMaterial>>,
+    selected: Query<&SpellBook, With<Selected>>,
+    targeting: Option<Res<Targeting>>,
+) {
+    let Ok(book) = selected.get_single() else {
+        return;
+    };
+    for (&HotbarSlot(slot), children, mut background) in &mut slots {
+        let targeted = targeting.as_ref().is_some_and(|targeting| Some(&targeting.spell) == book.spells().get(slot));
+        let color = if targeted { Color::GOLD.with_a(0.6) } else { Color::BLACK.with_a(0.6) };
+        if background.0 != color {
+            background.0 = color;
+        }
+
+        let fraction = book.cooldown(slot);
+        for overlay in overlays.iter_many(children) {
+            if materials.get(overlay).is_some_and(|material| material.fraction != fraction) {
+                if let Some(material) = materials.get_mut(overlay) {
+                    material.fraction = fraction;
+                }
+            }
+        }
+    }
+}
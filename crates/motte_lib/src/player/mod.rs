@@ -1,11 +1,23 @@
 use bevy::prelude::{App, Plugin};
 
+pub mod bookmarks;
 pub mod camera;
+pub mod hotbar;
+pub mod selection;
+pub mod stat_popups;
+pub mod touch;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(camera::CameraPlugin);
+        app.add_plugins((
+            camera::CameraPlugin,
+            bookmarks::BookmarksPlugin,
+            selection::SelectionPlugin,
+            hotbar::HotbarPlugin,
+            stat_popups::StatPopupsPlugin,
+            touch::TouchControlsPlugin,
+        ));
     }
 }
@@ -0,0 +1,164 @@
+//! Selecting a unit by left clicking it or the units of the player by left dragging a box around them, left clicking
+//! anything else clears the selection. Tapping the ground keeps the selection, as taps also give orders.
+
+use super::{
+    camera::MainCamera,
+    hotbar::{self, HotbarSlot, Targeting},
+};
+use crate::{
+    app_state::{AppState, InGameState},
+    cleanup::StateScoped,
+    cursor::{CursorClick, CursorDrag, CursorPosition, Pointer},
+    economy::Team,
+    in_game::placement::Placement,
+    navigation::agent::Agent,
+    physics::{layers::CollisionLayer, queries::PhysicsQueries},
+    prelude::*,
+};
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(Selected, SelectionBox);
+        app.add_systems(OnEnter(AppState::InGame), spawn_box);
+        app.add_systems(Update, (select, select_box).run_if(in_state(InGameState::Playing)));
+    }
+}
+
+/// A unit the player has selected.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+#[component(storage = "SparseSet")]
+pub struct Selected;
+
+#[allow(clippy::too_many_arguments)]
+fn select(
+    mut commands: Commands,
+    mut clicks: EventReader<CursorClick>,
+    cursor: Res<CursorPosition>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    selected: Query<Entity, With<Selected>>,
+    units: Query<(), With<Agent>>,
+    slots: Query<&Interaction, With<HotbarSlot>>,
+    queries: PhysicsQueries,
+    // Left clicks place buildings & cast spells instead.
+    placement: Option<Res<Placement>>,
+    targeting: Option<Res<Targeting>>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let busy = placement.is_some() || targeting.is_some() || hotbar::hovered(&slots);
+
+    for click in clicks.read() {
+        if click.button != MouseButton::Left || busy {
+            continue;
+        }
+
+        let (origin, direction) = math::world_space_ray_from_ndc(cursor.ndc(), camera, camera_transform);
+        let hit = queries
+            .raycast(origin, direction, f32::MAX, CollisionLayer::Units)
+            .map(|hit| hit.entity)
+            .filter(|&entity| units.contains(entity));
+
+        if hit.is_none() && click.pointer == Pointer::Touch {
+            continue;
+        }
+
+        for entity in &selected {
+            if Some(entity) != hit {
+                commands.entity(entity).remove::<Selected>();
+            }
+        }
+        if let Some(entity) = hit {
+            commands.entity(entity).insert(Selected);
+        }
+    }
+}
+
+/// Outline of the box being dragged.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct SelectionBox;
+
+fn spawn_box(mut commands: Commands) {
+    commands.spawn((
+        Name::ui("selection box"),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                border: UiRect::all(Val::Px(1.0)),
+                display: Display::None,
+                ..default()
+            },
+            border_color: BorderColor(Color::WHITE),
+            background_color: BackgroundColor(Color::WHITE.with_a(0.1)),
+            ..default()
+        },
+        SelectionBox,
+        StateScoped(AppState::InGame),
+    ));
+}
+
+/// Shows the box while left dragging & selects the units of the player inside it when released.
+#[allow(clippy::too_many_arguments)]
+fn select_box(
+    mut commands: Commands,
+    mut drags: EventReader<CursorDrag>,
+    mut boxes: Query<&mut Style, With<SelectionBox>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    selected: Query<Entity, With<Selected>>,
+    units: Query<(Entity, &GlobalTransform, &Team), With<Agent>>,
+    placement: Option<Res<Placement>>,
+    targeting: Option<Res<Targeting>>,
+) {
+    let (Ok(mut style), Ok((camera, camera_transform))) = (boxes.get_single_mut(), cameras.get_single()) else {
+        drags.clear();
+        return;
+    };
+
+    for drag in drags.read() {
+        match *drag {
+            CursorDrag::Moved { button: MouseButton::Left, start_ndc, current_ndc }
+                if placement.is_none() && targeting.is_none() =>
+            {
+                let (min, max) = (start_ndc.min(current_ndc), start_ndc.max(current_ndc));
+                // The ndc span 2 across the window.
+                let percent = |ndc: f32| Val::Percent(ndc * 50.0);
+                style.display = Display::Flex;
+                style.left = percent(min.x + 1.0);
+                style.top = percent(min.y + 1.0);
+                style.width = percent(max.x - min.x);
+                style.height = percent(max.y - min.y);
+            }
+            CursorDrag::Released { button: MouseButton::Left, start_ndc, end_ndc }
+                if style.display != Display::None =>
+            {
+                style.display = Display::None;
+                let rect = Rect::from_corners(start_ndc, end_ndc);
+                let inside = |transform: &GlobalTransform| {
+                    camera
+                        .world_to_ndc(camera_transform, transform.translation())
+                        .is_some_and(|ndc| rect.contains(Vec2::new(ndc.x, -ndc.y)))
+                };
+                let boxed = units
+                    .iter()
+                    .filter(|(_, transform, team)| **team == Team::PLAYER && inside(transform))
+                    .map(|(entity, ..)| entity)
+                    .collect_vec();
+
+                for entity in &selected {
+                    if !boxed.contains(&entity) {
+                        commands.entity(entity).remove::<Selected>();
+                    }
+                }
+                for entity in boxed {
+                    commands.entity(entity).insert(Selected);
+                }
+            }
+            CursorDrag::Cancelled { button: MouseButton::Left } => style.display = Display::None,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Floating text over the [`Selected`] unit when one of its stats changes, e.g. `+15 speed` when a tech is researched.
+//! The change is the difference to the unit's [`Previous`] stat of the last frame.
+
+use bevy::window::PrimaryWindow;
+
+use super::{camera::MainCamera, selection::Selected};
+use crate::{
+    app_state::{AppState, InGameState},
+    asset_management::FontAssets,
+    cleanup::StateScoped,
+    economy::GatherRate,
+    graphics::tween::{Ease, Tween, TweenCompleted},
+    main_menu,
+    movement::motor::JumpHeight,
+    navigation::agent::Speed,
+    prelude::*,
+    previous::{Previous, PreviousPlugin},
+    stats::{stat::Stat, StatSystem},
+};
+
+/// Seconds a popup is shown before it fades out.
+const SHOWN: f32 = 1.0;
+
+const FADE: f32 = 0.5;
+
+/// Changes smaller than this aren't shown, e.g. rounding errors of multiplicative modifiers.
+const MIN_CHANGE: f32 = 0.01;
+
+pub struct StatPopupsPlugin;
+
+impl Plugin for StatPopupsPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(StatPopup);
+        app.add_plugins((
+            PreviousPlugin::<Speed>::default(),
+            PreviousPlugin::<JumpHeight>::default(),
+            PreviousPlugin::<GatherRate>::default(),
+        ));
+        app.add_systems(
+            PostUpdate,
+            (popups::<Speed>, popups::<JumpHeight>, popups::<GatherRate>)
+                .after(StatSystem::Cleanup)
+                .run_if(in_state(InGameState::Playing)),
+        );
+        app.add_systems(Update, despawn.run_if(in_state(AppState::InGame)));
+    }
+}
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct StatPopup;
+
+fn popups<S: Stat + Component + Clone>(
+    mut commands: Commands,
+    stats: Query<(&S, &Previous<S>, &GlobalTransform), (With<Selected>, Changed<S>)>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    fonts: Res<FontAssets>,
+) {
+    let (Ok((camera, camera_transform)), Ok(window)) = (cameras.get_single(), windows.get_single()) else {
+        return;
+    };
+    for (stat, previous, transform) in &stats {
+        let change = previous.delta(stat);
+        if change.abs() < MIN_CHANGE {
+            continue;
+        }
+        // The camera renders to a pixelated texture, so place the popup by normalized device coordinates.
+        let Some(ndc) = camera.world_to_ndc(camera_transform, transform.translation()) else {
+            continue;
+        };
+        let color = if change > 0.0 { Color::LIME_GREEN } else { Color::TOMATO };
+        let mut text = main_menu::text(&fonts, format!("{change:+.2} {}", S::name()), 14.0).with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px((ndc.x + 1.0) / 2.0 * window.width()),
+            top: Val::Px((1.0 - ndc.y) / 2.0 * window.height()),
+            ..default()
+        });
+        text.text.sections[0].style.color = color;
+        commands.spawn((
+            Name::ui("stat popup"),
+            text,
+            Tween::delayed(SHOWN).then(color.with_a(0.0), FADE, Ease::InQuad),
+            StatPopup,
+            StateScoped(AppState::InGame),
+        ));
+    }
+}
+
+fn despawn(mut commands: Commands, mut completed: EventReader<TweenCompleted>, popups: Query<(), With<StatPopup>>) {
+    for &TweenCompleted { entity } in completed.read() {
+        if popups.contains(entity) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
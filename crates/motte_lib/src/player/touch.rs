@@ -0,0 +1,88 @@
+//! Button toggling what dragging a finger does between panning the camera & box selecting, shown once the screen is
+//! touched so mouse players don't see it.
+
+use crate::{
+    app_state::{AppState, InGameState},
+    asset_management::FontAssets,
+    cleanup::StateScoped,
+    cursor::TouchDrag,
+    main_menu,
+    prelude::*,
+};
+
+pub struct TouchControlsPlugin;
+
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(TouchDragToggle);
+        app.add_systems(OnEnter(AppState::InGame), spawn);
+        app.add_systems(Update, (show, toggle, label).chain().run_if(in_state(InGameState::Playing)));
+    }
+}
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct TouchDragToggle;
+
+fn caption(mode: TouchDrag) -> String {
+    match mode {
+        TouchDrag::Pan => "Drag: pan".into(),
+        TouchDrag::Select => "Drag: select".into(),
+    }
+}
+
+fn spawn(mut commands: Commands, fonts: Res<FontAssets>, mode: Res<TouchDrag>) {
+    commands
+        .spawn((
+            Name::ui("touch controls"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    right: Val::Px(8.0),
+                    display: Display::None,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|builder| {
+            main_menu::button(builder, &fonts, caption(*mode), TouchDragToggle);
+        });
+}
+
+/// Shows the controls on the first touch.
+fn show(touches: Res<Touches>, toggles: Query<&Parent, With<TouchDragToggle>>, mut styles: Query<&mut Style>) {
+    if touches.iter_just_pressed().next().is_none() {
+        return;
+    }
+    for parent in &toggles {
+        if let Ok(mut style) = styles.get_mut(parent.get()) {
+            if style.display == Display::None {
+                style.display = Display::Flex;
+            }
+        }
+    }
+}
+
+fn toggle(buttons: Query<&Interaction, (With<TouchDragToggle>, Changed<Interaction>)>, mut mode: ResMut<TouchDrag>) {
+    if buttons.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        *mode = match *mode {
+            TouchDrag::Pan => TouchDrag::Select,
+            TouchDrag::Select => TouchDrag::Pan,
+        };
+    }
+}
+
+fn label(mode: Res<TouchDrag>, toggles: Query<&Children, With<TouchDragToggle>>, mut texts: Query<&mut Text>) {
+    if !mode.is_changed() {
+        return;
+    }
+    for children in &toggles {
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = caption(*mode);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Banner at the top of the screen fading in the latest [`ScenarioMessage`] until it expires.
+
+use super::ScenarioMessage;
+use crate::{
+    app_state::AppState,
+    asset_management::FontAssets,
+    cleanup::StateScoped,
+    graphics::tween::{Ease, Tween},
+    main_menu,
+    prelude::*,
+};
+
+pub struct ScenarioHudPlugin;
+
+impl Plugin for ScenarioHudPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(MessageBanner, MessageBackdrop);
+        app.add_systems(OnEnter(AppState::InGame), spawn);
+        app.add_systems(Update, show.run_if(in_state(AppState::InGame)));
+    }
+}
+
+/// Seconds the banner takes to fade in & out.
+const FADE: f32 = 0.25;
+
+const BACKDROP: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct MessageBanner;
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct MessageBackdrop;
+
+fn spawn(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn((
+            Name::ui("scenario message"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(48.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|builder| {
+            builder
+                .spawn((NodeBundle { background_color: Color::NONE.into(), ..default() }, MessageBackdrop))
+                .with_children(|builder| {
+                    builder.spawn((main_menu::text(&fonts, "", 20.0), MessageBanner));
+                });
+        });
+}
+
+/// Fades the latest message in, holds it for its duration & fades it out again.
+fn show(
+    mut commands: Commands,
+    mut messages: EventReader<ScenarioMessage>,
+    mut banners: Query<(Entity, &mut Text), With<MessageBanner>>,
+    mut backdrops: Query<(Entity, &mut BackgroundColor), With<MessageBackdrop>>,
+) {
+    let Some(message) = messages.read().last() else {
+        return;
+    };
+    let (Ok((banner, mut text)), Ok((backdrop, mut background))) =
+        (banners.get_single_mut(), backdrops.get_single_mut())
+    else {
+        return;
+    };
+    text.sections[0].value.clone_from(&message.text);
+    // Fades in from hidden, also when the previous message is still shown.
+    text.sections[0].style.color.set_a(0.0);
+    background.0.set_a(0.0);
+
+    let fade = |color: Color| {
+        Tween::to(color, FADE, Ease::OutQuad).wait((message.seconds - 2.0 * FADE).max(0.0)).then(
+            color.with_a(0.0),
+            FADE,
+            Ease::InQuad,
+        )
+    };
+    commands.entity(banner).insert(fade(Color::WHITE));
+    commands.entity(backdrop).insert(fade(BACKDROP));
+}
@@ -0,0 +1,281 @@
+//! Scripted encounters of a map, declared by its [`ScenarioDef`]. A [`TriggerDef`] fires its [`TriggerAction`]s once
+//! its [`TriggerCondition`] is met, e.g. a wave is spawned when the player's units enter an area or a message is shown
+//! after a minute. Triggers fire once unless they `repeat`, together they're the basis of missions & tutorials.
+
+use serde::Deserialize;
+
+use crate::{
+    app_state::AppState,
+    cleanup::StateScoped,
+    combat::CombatBundle,
+    determinism::GameRng,
+    difficulty::Difficulty,
+    economy::Team,
+    events::GameEvent,
+    movement::motor::CharacterMotor,
+    navigation::{
+        agent::{Agent, Speed, TargetReachedCondition},
+        flow_field::{
+            fields::obstacle::{ObstacleField, CLEARANCE_MAX},
+            layout::{FieldLayout, CELL_SIZE_F32},
+            pathing::Goal,
+            CellIndex,
+        },
+    },
+    objectives::{ObjectiveTarget, Objectives, ObjectivesDef, Target},
+    prelude::*,
+    save::Save,
+    simulation::GameplaySystems,
+    tech::TechEffect,
+    timer::Cooldown,
+    utils::math::random_point_in_square,
+};
+
+#[cfg(feature = "presentation")]
+pub mod hud;
+
+/// Units of a wave are spawned within this distance of their spawn point.
+const WAVE_SPREAD: f32 = 4.0;
+
+pub struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(ScenarioDef, TriggerDef, TriggerCondition, TriggerAction, SpawnPoint);
+        app.add_event::<ScenarioMessage>();
+        app.add_systems(
+            FixedUpdate,
+            (triggers, actions).chain().in_set(GameplaySystems::Scenario).run_if(resource_exists::<Scenario>),
+        );
+        app.add_systems(OnExit(AppState::InGame), |mut commands: Commands| commands.remove_resource::<Scenario>());
+    }
+}
+
+/// Spawn location authored in a map scene, the group is e.g. a team or wave name.
+#[derive(Component, Reflect, Default, Clone, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct SpawnPoint(pub String);
+
+#[derive(Reflect, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct ScenarioDef {
+    pub triggers: Vec<TriggerDef>,
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub struct TriggerDef {
+    pub condition: TriggerCondition,
+    pub actions: Vec<TriggerAction>,
+    /// Fires every time the condition is met instead of only the first time.
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub enum TriggerCondition {
+    /// A unit of `team` entered the area while none of its units were in it.
+    AreaEntered { team: Team, center: Vec2, radius: f32 },
+    /// Seconds since the map was spawned, or since the trigger last fired if it repeats.
+    Elapsed(f32),
+    /// An entity matching the target died.
+    Died(ObjectiveTarget),
+}
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub enum TriggerAction {
+    /// Spawns `count` hostile units spread over the spawn points of `group`, they attack the map's [`Target`]. The
+    /// count is scaled by [`Difficulty::wave_size`].
+    SpawnWave {
+        group: String,
+        count: u32,
+        #[serde(default)]
+        agent: Agent,
+    },
+    /// Shows `text` to the player for `seconds`.
+    Message { text: String, seconds: f32 },
+    /// Modifies a stat of the team's units like a researched tech.
+    GrantModifier { team: Team, effect: TechEffect },
+    /// Replaces the map's objectives, their progress starts over.
+    SetObjectives(ObjectivesDef),
+}
+
+/// Message of a [`TriggerAction::Message`], shown by the [`hud`].
+#[derive(Event, Clone, Debug)]
+pub struct ScenarioMessage {
+    pub text: String,
+    pub seconds: f32,
+}
+
+#[derive(Clone, Default, Debug)]
+struct TriggerState {
+    fired: u32,
+    /// Whether units of the team were in the area last tick.
+    occupied: bool,
+    /// Time until an [`TriggerCondition::Elapsed`] trigger fires, started again when it repeats.
+    countdown: Cooldown<TriggerDef>,
+}
+
+/// [`ScenarioDef`] of the current map & the state of its triggers, inserted when the map is spawned.
+#[derive(Resource, Debug)]
+pub struct Scenario {
+    def: ScenarioDef,
+    triggers: Vec<TriggerState>,
+    /// Actions of the triggers fired this tick.
+    pending: Vec<TriggerAction>,
+    waves: u32,
+}
+
+impl Scenario {
+    pub fn new(def: ScenarioDef) -> Self {
+        let triggers = def
+            .triggers
+            .iter()
+            .map(|trigger| {
+                let mut countdown = Cooldown::default();
+                if let TriggerCondition::Elapsed(seconds) = trigger.condition {
+                    countdown.start_with(seconds);
+                }
+                TriggerState { countdown, ..default() }
+            })
+            .collect();
+        Self { def, triggers, pending: Vec::new(), waves: 0 }
+    }
+}
+
+fn triggers(
+    mut scenario: ResMut<Scenario>,
+    mut events: EventReader<GameEvent>,
+    targets: Query<(), With<Target>>,
+    names: Query<&Name>,
+    units: Query<(&GlobalTransform, &Team), With<Agent>>,
+    time: Res<Time<Fixed>>,
+) {
+    let died: SmallVec<[Entity; 8]> = events
+        .read()
+        .filter_map(|event| match *event {
+            GameEvent::Died { entity } => Some(entity),
+            _ => None,
+        })
+        .collect();
+
+    let delta = time.delta_seconds();
+    let Scenario { def, triggers, pending, .. } = &mut *scenario;
+    for (trigger, state) in def.triggers.iter().zip(triggers.iter_mut()) {
+        if state.fired > 0 && !trigger.repeat {
+            continue;
+        }
+        let met = match &trigger.condition {
+            &TriggerCondition::AreaEntered { team, center, radius } => {
+                let occupied = units.iter().any(|(transform, &other)| {
+                    other == team && transform.translation().xz().distance(center) <= radius
+                });
+                let entered = occupied && !state.occupied;
+                state.occupied = occupied;
+                entered
+            }
+            TriggerCondition::Elapsed(_) => state.countdown.repeat(delta),
+            TriggerCondition::Died(target) => died.iter().any(|&entity| match target {
+                ObjectiveTarget::Target => targets.contains(entity),
+                ObjectiveTarget::Named(name) => names.get(entity).is_ok_and(|other| *other == Name::unit(name.clone())),
+            }),
+        };
+        if met {
+            state.fired += 1;
+            pending.extend(trigger.actions.iter().cloned());
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn actions(
+    mut commands: Commands,
+    mut scenario: ResMut<Scenario>,
+    mut objectives: Option<ResMut<Objectives>>,
+    mut messages: EventWriter<ScenarioMessage>,
+    mut events: EventWriter<GameEvent>,
+    spawn_points: Query<(&SpawnPoint, &GlobalTransform)>,
+    targets: Query<Entity, With<Target>>,
+    mut rng: ResMut<GameRng>,
+    difficulty: Res<Difficulty>,
+    obstacle_field: Option<Res<ObstacleField>>,
+    layout: Option<Res<FieldLayout>>,
+    // Waves are invisible without the presentation plugins, e.g. in headless runs.
+    #[cfg(feature = "presentation")] mut meshes: Option<ResMut<Assets<Mesh>>>,
+    #[cfg(feature = "presentation")] mut materials: Option<ResMut<Assets<StandardMaterial>>>,
+) {
+    for action in std::mem::take(&mut scenario.pending) {
+        match action {
+            TriggerAction::SpawnWave { group, count, agent } => {
+                let mut points = spawn_points
+                    .iter()
+                    .filter(|(point, _)| point.0 == group)
+                    .map(|(_, transform)| transform.translation().xz())
+                    .collect_vec();
+                if points.is_empty() {
+                    warn!("No spawn points in group '{group}' for a wave");
+                    continue;
+                }
+
+                // Skip points without room for the spread of the wave, unless none have it.
+                if let (Some(obstacle_field), Some(layout)) = (&obstacle_field, &layout) {
+                    // Clearance is only tracked up to `CLEARANCE_MAX` cells, so a wider spread can't ask for more.
+                    let clearance = ((WAVE_SPREAD / CELL_SIZE_F32).ceil() as u8).min(CLEARANCE_MAX);
+                    let clear = |point: &Vec2| {
+                        let cell = layout.cell(*point);
+                        layout.valid(cell) && obstacle_field.clearance_at(cell, agent) >= clearance
+                    };
+                    if points.iter().any(clear) {
+                        points.retain(clear);
+                    } else {
+                        warn!("No spawn points in group '{group}' have room for a wave of {agent} agents");
+                    }
+                }
+
+                let count = (count as f32 * difficulty.wave_size()).round().max(1.0) as u32;
+                scenario.waves += 1;
+                let wave = scenario.waves;
+                let goal = targets.get_single().map_or(Goal::None, Goal::Entity);
+                #[cfg(feature = "presentation")]
+                let visuals = meshes.as_mut().zip(materials.as_mut()).map(|(meshes, materials)| {
+                    let mesh = meshes.add(Cylinder { radius: agent.radius(), half_height: agent.height() / 2.0 });
+                    (mesh, materials.add(Color::RED))
+                });
+                for i in 0..count {
+                    let position = points[i as usize % points.len()] + random_point_in_square(&mut **rng, WAVE_SPREAD);
+                    #[cfg_attr(not(feature = "presentation"), allow(unused_mut, unused_variables))]
+                    let mut unit = commands.spawn((
+                        Name::unit(format!("wave {wave} unit {i}")),
+                        SpatialBundle::from_transform(
+                            Vec3::new(position.x, agent.height() / 2.0, position.y).into_transform(),
+                        ),
+                        CharacterMotor::cylinder(agent.height(), agent.radius()),
+                        agent,
+                        Speed::base(100.0),
+                        CombatBundle::default(),
+                        Save,
+                        CellIndex::default(),
+                        TargetReachedCondition::Distance(1.0),
+                        goal,
+                        Team::HOSTILE,
+                        StateScoped(AppState::InGame),
+                    ));
+                    #[cfg(feature = "presentation")]
+                    if let Some((mesh, material)) = &visuals {
+                        unit.insert((mesh.clone(), material.clone()));
+                    }
+                }
+                events.send(GameEvent::WaveSpawned { wave, count });
+            }
+            TriggerAction::Message { text, seconds } => {
+                messages.send(ScenarioMessage { text, seconds });
+            }
+            TriggerAction::GrantModifier { team, effect } => {
+                effect.spawn(&mut commands, team, "scenario bonus".into());
+            }
+            TriggerAction::SetObjectives(def) => match objectives.as_mut() {
+                Some(objectives) => objectives.set(def),
+                None => commands.insert_resource(Objectives::new(def)),
+            },
+        }
+    }
+}
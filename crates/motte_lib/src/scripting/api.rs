@@ -0,0 +1,431 @@
+//! Functions available to scripts:
+//! - `vec3(x, y, z)`, a vector has `x`, `y` & `z`, supports `+`, `-`, `* number`, `length()` & `distance(other)`.
+//! - `units()` & `units_in_radius(center, radius)` return the entities of all agents & those near `center`.
+//! - `position(target)` returns the position of a unit or a position as is, `stat(entity, name)` a stat's value. Both
+//!   return `nil` for unknown entities or stats.
+//! - `set_stat(entity, name, value)` & `add_stat(entity, name, amount)` change the base value of a stat, it never goes
+//!   below 0.
+//! - `modify_stat(entity, name, multiplier, seconds)` multiplies a stat by `multiplier` for `seconds`, e.g. to slow a
+//!   unit. Modifiers stack & the multiplier is clamped to 0 or more.
+//! - `order(entity, target)` moves a unit to an entity or position, `stop(entity)` clears its order.
+//! - `cast(caster, spell, target)` casts a spell by name at an entity, a position or `nil`.
+//! - `spawn(prefab, position)` spawns a prefab by name.
+
+use bevy::ecs::system::{CommandQueue, EntityCommands};
+use mlua::{
+    FromLua, Function, HookTriggers, IntoLua, Lua, LuaOptions, MetaMethod, StdLib, UserData, UserDataFields,
+    UserDataMethods, Value, Variadic,
+};
+
+use crate::{
+    app_state::AppState,
+    cleanup::StateScoped,
+    navigation::{
+        agent::Agent,
+        flow_field::{layout::FieldLayout, pathing::Goal},
+    },
+    prefab::PrefabCommandsExt,
+    prelude::*,
+    spells::{CastSpell, SpellDef, Target},
+    stats::modifier::{Flat, Modifies, Mult},
+    timer::{DelayedAction, Expired},
+};
+
+/// Instructions a single handler may run before it's aborted.
+const MAX_INSTRUCTIONS: u32 = 1_000_000;
+
+/// Instructions between checks of [`MAX_INSTRUCTIONS`].
+const HOOK_INTERVAL: u32 = 1000;
+
+const MEMORY_LIMIT: usize = 32 * 1024 * 1024;
+
+/// Returns a lua state without access to the file system, the os or the raw globals & with the api registered as
+/// globals.
+pub(super) fn sandboxed() -> mlua::Result<Lua> {
+    let lua = Lua::new_with(StdLib::TABLE | StdLib::STRING | StdLib::MATH, LuaOptions::default())?;
+    lua.set_memory_limit(MEMORY_LIMIT)?;
+    lua.set_app_data(ScriptContext::default());
+    lua.set_hook(HookTriggers::new().every_nth_instruction(HOOK_INTERVAL), |lua, _| {
+        let mut context = lua.app_data_mut::<ScriptContext>().expect("script context should be set");
+        context.instructions += HOOK_INTERVAL;
+        if context.instructions > MAX_INSTRUCTIONS {
+            return Err(mlua::Error::runtime("instruction limit exceeded"));
+        }
+        Ok(())
+    });
+    register(&lua)?;
+    Ok(lua)
+}
+
+fn register(lua: &Lua) -> mlua::Result<()> {
+    let globals = lua.globals();
+    // Scripts reach the globals through the `__index` of their environment, these would let one change them for all.
+    let shared = ["_G", "rawset", "setmetatable", "getmetatable"];
+    for unsafe_global in ["dofile", "loadfile", "load", "collectgarbage"].into_iter().chain(shared) {
+        globals.raw_set(unsafe_global, Value::Nil)?;
+    }
+    globals.set(
+        "print",
+        lua.create_function(|lua, values: Variadic<Value<'_>>| {
+            let tostring: Function<'_> = lua.globals().get("tostring")?;
+            let text: Vec<String> = values.into_iter().map(|value| tostring.call(value)).try_collect()?;
+            info!("{}", text.join("\t"));
+            Ok(())
+        })?,
+    )?;
+    globals.set("vec3", lua.create_function(|_, (x, y, z): (f32, f32, f32)| Ok(LuaVec3(Vec3::new(x, y, z))))?)?;
+
+    globals.set(
+        "units",
+        lua.create_function(|lua, ()| Ok(context(lua).units.iter().map(|unit| LuaEntity(unit.entity)).collect_vec()))?,
+    )?;
+    globals.set(
+        "units_in_radius",
+        lua.create_function(|lua, (center, radius): (LuaVec3, f32)| {
+            let context = context(lua);
+            let units = context.units.iter().filter(|unit| unit.position.distance_squared(center.0) <= radius * radius);
+            Ok(units.map(|unit| LuaEntity(unit.entity)).collect_vec())
+        })?,
+    )?;
+    globals.set(
+        "position",
+        lua.create_function(|lua, target: Value<'_>| match target_from_lua(&target) {
+            Target::Entity(entity) => Ok(context(lua).unit(entity).map(|unit| LuaVec3(unit.position))),
+            Target::Location(position) => Ok(Some(LuaVec3(position))),
+            Target::None => Ok(None),
+        })?,
+    )?;
+    globals.set(
+        "stat",
+        lua.create_function(|lua, (entity, name): (LuaEntity, String)| {
+            let context = context(lua);
+            let stat = context.unit(entity.0).and_then(|unit| unit.stats.iter().find(|(stat, _)| *stat == name));
+            Ok(stat.map(|&(_, value)| value))
+        })?,
+    )?;
+
+    globals.set(
+        "set_stat",
+        lua.create_function(|lua, (entity, name, value): (LuaEntity, String, f32)| {
+            queue(lua, ScriptCommand::Stat { entity: entity.0, name, value, add: false })
+        })?,
+    )?;
+    globals.set(
+        "add_stat",
+        lua.create_function(|lua, (entity, name, value): (LuaEntity, String, f32)| {
+            queue(lua, ScriptCommand::Stat { entity: entity.0, name, value, add: true })
+        })?,
+    )?;
+    globals.set(
+        "modify_stat",
+        lua.create_function(|lua, (entity, name, multiplier, seconds): (LuaEntity, String, f32, f32)| {
+            queue(lua, ScriptCommand::Modify { entity: entity.0, name, multiplier, seconds })
+        })?,
+    )?;
+    globals.set(
+        "order",
+        lua.create_function(|lua, (entity, target): (LuaEntity, Value<'_>)| {
+            queue(lua, ScriptCommand::Order { entity: entity.0, target: target_from_lua(&target) })
+        })?,
+    )?;
+    globals.set(
+        "stop",
+        lua.create_function(|lua, entity: LuaEntity| {
+            queue(lua, ScriptCommand::Order { entity: entity.0, target: Target::None })
+        })?,
+    )?;
+    globals.set(
+        "cast",
+        lua.create_function(|lua, (caster, spell, target): (LuaEntity, String, Value<'_>)| {
+            queue(lua, ScriptCommand::Cast { caster: caster.0, spell, target: target_from_lua(&target) })
+        })?,
+    )?;
+    globals.set(
+        "spawn",
+        lua.create_function(|lua, (prefab, position): (String, LuaVec3)| {
+            queue(lua, ScriptCommand::Spawn { prefab, position: position.0 })
+        })?,
+    )?;
+    Ok(())
+}
+
+fn context(lua: &Lua) -> mlua::AppDataRef<'_, ScriptContext> {
+    lua.app_data_ref().expect("script context should be set")
+}
+
+fn queue(lua: &Lua, command: ScriptCommand) -> mlua::Result<()> {
+    lua.app_data_mut::<ScriptContext>().expect("script context should be set").commands.push(command);
+    Ok(())
+}
+
+/// Converts an entity, position or `nil` passed by a script.
+fn target_from_lua(value: &Value<'_>) -> Target {
+    let Value::UserData(data) = value else {
+        return Target::None;
+    };
+    if let Ok(entity) = data.borrow::<LuaEntity>() {
+        Target::Entity(entity.0)
+    } else if let Ok(position) = data.borrow::<LuaVec3>() {
+        Target::Location(position.0)
+    } else {
+        Target::None
+    }
+}
+
+/// Argument of a handler.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum Arg {
+    Entity(Entity),
+    Position(Vec3),
+    Number(f32),
+    Nil,
+}
+
+impl From<Target> for Arg {
+    fn from(target: Target) -> Self {
+        match target {
+            Target::Entity(entity) => Arg::Entity(entity),
+            Target::Location(position) => Arg::Position(position),
+            Target::None => Arg::Nil,
+        }
+    }
+}
+
+impl<'lua> IntoLua<'lua> for Arg {
+    fn into_lua(self, lua: &'lua Lua) -> mlua::Result<Value<'lua>> {
+        match self {
+            Arg::Entity(entity) => LuaEntity(entity).into_lua(lua),
+            Arg::Position(position) => LuaVec3(position).into_lua(lua),
+            Arg::Number(number) => number.into_lua(lua),
+            Arg::Nil => Ok(Value::Nil),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct LuaEntity(Entity);
+
+impl UserData for LuaEntity {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_meta_method(MetaMethod::Eq, |_, a, b: LuaEntity| Ok(a.0 == b.0));
+        methods.add_meta_method(MetaMethod::ToString, |_, entity, ()| Ok(format!("{:?}", entity.0)));
+    }
+}
+
+impl<'lua> FromLua<'lua> for LuaEntity {
+    fn from_lua(value: Value<'lua>, _: &'lua Lua) -> mlua::Result<Self> {
+        from_userdata(value, "Entity")
+    }
+}
+
+#[derive(Clone, Copy)]
+struct LuaVec3(Vec3);
+
+impl UserData for LuaVec3 {
+    fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("x", |_, v| Ok(v.0.x));
+        fields.add_field_method_get("y", |_, v| Ok(v.0.y));
+        fields.add_field_method_get("z", |_, v| Ok(v.0.z));
+        fields.add_field_method_set("x", |_, v, x| Ok(v.0.x = x));
+        fields.add_field_method_set("y", |_, v, y| Ok(v.0.y = y));
+        fields.add_field_method_set("z", |_, v, z| Ok(v.0.z = z));
+    }
+
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("length", |_, v, ()| Ok(v.0.length()));
+        methods.add_method("distance", |_, v, other: LuaVec3| Ok(v.0.distance(other.0)));
+        methods.add_meta_method(MetaMethod::Add, |_, a, b: LuaVec3| Ok(LuaVec3(a.0 + b.0)));
+        methods.add_meta_method(MetaMethod::Sub, |_, a, b: LuaVec3| Ok(LuaVec3(a.0 - b.0)));
+        methods.add_meta_method(MetaMethod::Mul, |_, v, s: f32| Ok(LuaVec3(v.0 * s)));
+        methods.add_meta_method(MetaMethod::Eq, |_, a, b: LuaVec3| Ok(a.0 == b.0));
+        methods.add_meta_method(MetaMethod::ToString, |_, v, ()| Ok(v.0.to_string()));
+    }
+}
+
+impl<'lua> FromLua<'lua> for LuaVec3 {
+    fn from_lua(value: Value<'lua>, _: &'lua Lua) -> mlua::Result<Self> {
+        from_userdata(value, "Vec3")
+    }
+}
+
+fn from_userdata<T: UserData + Copy + 'static>(value: Value<'_>, to: &'static str) -> mlua::Result<T> {
+    match value {
+        Value::UserData(data) => Ok(*data.borrow::<T>()?),
+        _ => Err(mlua::Error::FromLuaConversionError { from: value.type_name(), to, message: None }),
+    }
+}
+
+/// Snapshot read by the api & the commands queued by handlers, kept in the app data of the lua state.
+#[derive(Default)]
+pub(super) struct ScriptContext {
+    units: Vec<UnitView>,
+    commands: Vec<ScriptCommand>,
+    /// Instructions run by the current handler.
+    instructions: u32,
+}
+
+impl ScriptContext {
+    /// Takes a snapshot of all agents & their stats.
+    pub(super) fn snapshot(&mut self, world: &mut World) {
+        let stats =
+            world.resource::<ScriptStats>().0.iter().map(|(name, stat)| (name.clone(), stat.read)).collect_vec();
+        let mut agents = world.query_filtered::<(Entity, &GlobalTransform), With<Agent>>();
+        self.units = agents
+            .iter(world)
+            .map(|(entity, transform)| UnitView {
+                entity,
+                position: transform.translation(),
+                stats: stats.iter().filter_map(|(name, read)| Some((name.clone(), read(world, entity)?))).collect(),
+            })
+            .collect();
+    }
+
+    /// Resets the instruction limit before a handler runs.
+    pub(super) fn reset_instructions(&mut self) {
+        self.instructions = 0;
+    }
+
+    fn unit(&self, entity: Entity) -> Option<&UnitView> {
+        self.units.iter().find(|unit| unit.entity == entity)
+    }
+
+    /// Applies the commands queued by the handlers.
+    pub(super) fn apply(&mut self, world: &mut World) {
+        let mut queue = CommandQueue::default();
+        for command in self.commands.drain(..) {
+            match command {
+                ScriptCommand::Stat { entity, name, value, add } => {
+                    let Some(stat) = world.resource::<ScriptStats>().0.get(&name).copied() else {
+                        warn!("script changed unknown stat '{name}'");
+                        continue;
+                    };
+                    (stat.modify)(world, entity, &|base| (if add { base + value } else { value }).max(0.0));
+                }
+                ScriptCommand::Modify { entity, name, multiplier, seconds } => {
+                    let Some(stat) = world.resource::<ScriptStats>().0.get(&name).copied() else {
+                        warn!("script modified unknown stat '{name}'");
+                        continue;
+                    };
+                    if world.get_entity(entity).is_none() {
+                        continue;
+                    }
+                    let mut commands = Commands::new(&mut queue, world);
+                    let mut modifier = commands.spawn((
+                        Name::new(format!("script modifier of {name}")),
+                        Modifies::Single(entity),
+                        ScriptModifier,
+                        DelayedAction::<ScriptModifier>::new(seconds.max(0.0)),
+                        StateScoped(AppState::InGame),
+                    ));
+                    (stat.modifier)(&mut modifier, multiplier.max(0.0));
+                }
+                ScriptCommand::Order { entity, target } => {
+                    let goal = match target {
+                        Target::Entity(target) => Goal::Entity(target),
+                        Target::Location(position) => {
+                            let Some(layout) = world.get_resource::<FieldLayout>() else {
+                                continue;
+                            };
+                            let cell = layout.cell(position.xz());
+                            if !layout.valid(cell) {
+                                warn!("script ordered {entity:?} to {position} outside of the field");
+                                continue;
+                            }
+                            Goal::Cell(cell)
+                        }
+                        Target::None => Goal::None,
+                    };
+                    if let Some(mut entity) = world.get_entity_mut(entity) {
+                        entity.insert(goal);
+                    }
+                }
+                ScriptCommand::Cast { caster, spell, target } => {
+                    let defs = world.resource::<Assets<SpellDef>>();
+                    let Some(id) = defs.iter().find_map(|(id, def)| (def.name == spell).then_some(id)) else {
+                        warn!("script cast unknown spell '{spell}'");
+                        continue;
+                    };
+                    world.send_event(CastSpell { caster, spell: Handle::Weak(id), target });
+                }
+                ScriptCommand::Spawn { prefab, position } => {
+                    Commands::new(&mut queue, world)
+                        .spawn_prefab(prefab)
+                        .insert((Transform::from_translation(position), StateScoped(AppState::InGame)));
+                }
+            }
+        }
+        queue.apply(world);
+        self.units.clear();
+    }
+}
+
+struct UnitView {
+    entity: Entity,
+    position: Vec3,
+    stats: Vec<(String, f32)>,
+}
+
+enum ScriptCommand {
+    Stat { entity: Entity, name: String, value: f32, add: bool },
+    Modify { entity: Entity, name: String, multiplier: f32, seconds: f32 },
+    Order { entity: Entity, target: Target },
+    Cast { caster: Entity, spell: String, target: Target },
+    Spawn { prefab: String, position: Vec3 },
+}
+
+/// Stats scripts can read & change by name, see [`AppScriptExt::script_stat`].
+#[derive(Resource, Default)]
+pub(crate) struct ScriptStats(HashMap<String, ScriptStat>);
+
+#[derive(Clone, Copy)]
+struct ScriptStat {
+    read: fn(&World, Entity) -> Option<f32>,
+    /// Changes the base value, if the entity has the stat.
+    modify: fn(&mut World, Entity, &dyn Fn(f32) -> f32),
+    /// Inserts a multiplier of the stat into a modifier entity.
+    modifier: fn(&mut EntityCommands, f32),
+}
+
+/// Modifier spawned by `modify_stat`, despawned when its [`DelayedAction`] expires.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub(super) struct ScriptModifier;
+
+pub(super) fn expire(
+    mut commands: Commands,
+    mut expired: EventReader<Expired<ScriptModifier>>,
+    modifiers: Query<(), With<ScriptModifier>>,
+) {
+    for &Expired { entity, .. } in expired.read() {
+        if modifiers.contains(entity) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub trait AppScriptExt {
+    /// Allows scripts to read & change stat `S` by its [`Stat::name`].
+    fn script_stat<S: Stat + Component>(&mut self) -> &mut Self;
+}
+
+impl AppScriptExt for App {
+    fn script_stat<S: Stat + Component>(&mut self) -> &mut Self {
+        let mut stats = self.world.get_resource_or_insert_with(ScriptStats::default);
+        stats.0.insert(
+            S::name(),
+            ScriptStat {
+                read: |world, entity| world.get::<S>(entity).map(Stat::value),
+                modify: |world, entity, modify| {
+                    if let Some(mut base) = world.get_mut::<Flat<S>>(entity) {
+                        let value = base.0.value_mut();
+                        *value = modify(*value);
+                    }
+                },
+                modifier: |entity, multiplier| {
+                    entity.insert(Mult(S::new(multiplier)));
+                },
+            },
+        );
+        self
+    }
+}
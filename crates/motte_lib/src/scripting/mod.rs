@@ -0,0 +1,301 @@
+//! Spell effects & encounter logic authored as Lua scripts, referenced by the `script` of a [`SpellDef`] or
+//! [`MapDef`].
+//!
+//! A script runs once when loaded, afterwards it receives events through the handlers it defines. Globals of a
+//! script are kept between calls & aren't shared with other scripts:
+//! ```lua
+//! waves = 0
+//! function on_start() end
+//! function on_update(dt) end
+//! function on_cast(caster, target) end -- spells only, target is an entity, a position or nil
+//! function on_order(entity) end
+//! function on_attacked(attacker, target) end
+//! function on_died(entity) end
+//! function on_spell_cast(caster, target) end
+//! function on_wave(wave, count) end
+//! ```
+//! Scripts can't access the file system or os & the instructions per handler are limited. Handlers read a snapshot of
+//! the units taken before they run, their changes are applied afterwards, see [`api`] for the available functions.
+//!
+//! [`MapDef`]: crate::in_game::map::MapDef
+
+use std::sync::Mutex;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    ecs::event::ManualEventReader,
+    utils::BoxedFuture,
+};
+use mlua::{Function, Lua, RegistryKey, Table};
+
+pub use self::api::AppScriptExt;
+use self::api::{Arg, ScriptContext};
+use crate::{
+    app_state::{simulating, AppState},
+    events::GameEvent,
+    movement::motor::JumpHeight,
+    navigation::agent::Speed,
+    prelude::*,
+    spells::{Affinity, Arcane, CastSpell, CooldownReduction, Fire, Frost, SpellDef},
+    timer::TimerPlugin,
+};
+
+pub mod api;
+
+pub struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(api::ScriptModifier);
+        app.init_asset::<Script>().init_asset_loader::<ScriptLoader>();
+        app.add_plugins(TimerPlugin::<api::ScriptModifier>::default());
+        app.init_resource::<ScriptRuntime>();
+        app.script_stat::<Speed>().script_stat::<JumpHeight>();
+        app.script_stat::<Affinity<Fire>>().script_stat::<Affinity<Frost>>().script_stat::<Affinity<Arcane>>();
+        app.script_stat::<CooldownReduction<Fire>>()
+            .script_stat::<CooldownReduction<Frost>>()
+            .script_stat::<CooldownReduction<Arcane>>();
+        #[cfg(feature = "presentation")]
+        app.add_systems(OnEnter(AppState::InGame), load_map_script);
+        app.add_systems(Update, (load_spell_scripts, run).chain().run_if(simulating));
+        app.add_systems(FixedUpdate, api::expire.run_if(simulating));
+        app.add_systems(OnExit(AppState::InGame), unload);
+    }
+}
+
+/// A `.lua` script, checked for syntax errors when loaded.
+#[derive(Asset, TypePath)]
+pub struct Script {
+    name: String,
+    source: String,
+}
+
+#[derive(Error, Debug)]
+pub enum ScriptError {
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("utf-8: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+    #[error("lua: {0}")]
+    Lua(#[from] mlua::Error),
+}
+
+#[derive(Default)]
+struct ScriptLoader;
+
+impl AssetLoader for ScriptLoader {
+    type Asset = Script;
+    type Settings = ();
+    type Error = ScriptError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Script, ScriptError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let source = String::from_utf8(bytes)?;
+            let name = load_context.path().to_string_lossy().into_owned();
+            Lua::new().load(&source).set_name(format!("@{name}")).into_function()?;
+            Ok(Script { name, source })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lua"]
+    }
+}
+
+#[derive(Resource)]
+pub struct ScriptRuntime {
+    lua: Mutex<Lua>,
+    map: Option<ScriptInstance>,
+    spells: HashMap<AssetId<SpellDef>, ScriptInstance>,
+}
+
+impl Default for ScriptRuntime {
+    fn default() -> Self {
+        let lua = api::sandboxed().expect("lua state should be created");
+        Self { lua: Mutex::new(lua), map: None, spells: HashMap::default() }
+    }
+}
+
+/// A script of a map or spell, `env` holds its globals once it ran.
+struct ScriptInstance {
+    script: Handle<Script>,
+    env: Option<RegistryKey>,
+}
+
+impl ScriptInstance {
+    fn new(script: Handle<Script>) -> Self {
+        Self { script, env: None }
+    }
+
+    /// Runs the script in a new environment that falls back to the api for unknown globals.
+    fn start(&mut self, lua: &Lua, script: &Script) -> mlua::Result<()> {
+        let env = lua.create_table()?;
+        env.set_metatable(Some(lua.create_table_from([("__index", lua.globals())])?));
+        self.env = Some(lua.create_registry_value(env.clone())?);
+        lua.load(&script.source).set_name(format!("@{}", script.name)).set_environment(env).exec()
+    }
+
+    fn handler<'lua>(&self, lua: &'lua Lua, function: &str) -> mlua::Result<Option<Function<'lua>>> {
+        let Some(env) = &self.env else {
+            return Ok(None);
+        };
+        lua.registry_value::<Table<'_>>(env)?.raw_get(function)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum InstanceKey {
+    Map,
+    Spell(AssetId<SpellDef>),
+}
+
+/// A handler to call with its arguments.
+struct Call {
+    instance: InstanceKey,
+    function: &'static str,
+    args: Vec<Arg>,
+}
+
+#[cfg(feature = "presentation")]
+fn load_map_script(
+    mut runtime: ResMut<ScriptRuntime>,
+    selected: Option<Res<crate::in_game::map::SelectedMap>>,
+    defs: Option<Res<Assets<crate::in_game::map::MapDef>>>,
+    asset_server: Res<AssetServer>,
+) {
+    // Maps are only loaded with the presentation plugins, e.g. the headless scenario has none.
+    let def = selected.zip(defs).and_then(|(selected, defs)| defs.get(&**selected).cloned());
+    let script = def.as_ref().and_then(|def| def.script.as_ref());
+    runtime.map = script.map(|path| ScriptInstance::new(asset_server.load(path)));
+}
+
+/// Starts loading the scripts of spells as soon as they're loaded.
+fn load_spell_scripts(
+    mut runtime: ResMut<ScriptRuntime>,
+    mut events: EventReader<AssetEvent<SpellDef>>,
+    defs: Res<Assets<SpellDef>>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.read() {
+        match *event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                let Some(path) = defs.get(id).and_then(|def| def.script.as_ref()) else {
+                    runtime.spells.remove(&id);
+                    continue;
+                };
+                runtime.spells.insert(id, ScriptInstance::new(asset_server.load(path)));
+            }
+            AssetEvent::Removed { id } => {
+                runtime.spells.remove(&id);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn run(
+    world: &mut World,
+    mut game_events: Local<ManualEventReader<GameEvent>>,
+    mut casts: Local<ManualEventReader<CastSpell>>,
+) {
+    world.resource_scope(|world, mut runtime: Mut<ScriptRuntime>| {
+        let ScriptRuntime { lua, map, spells } = &mut *runtime;
+        let lua = lua.get_mut().unwrap();
+        let scripts = world.resource::<Assets<Script>>();
+        let keys = map.iter().map(|_| InstanceKey::Map).chain(spells.keys().map(|&id| InstanceKey::Spell(id)));
+        let keys = keys.collect_vec();
+        let instance = |key: InstanceKey| match key {
+            InstanceKey::Map => map.as_ref(),
+            InstanceKey::Spell(id) => spells.get(&id),
+        };
+
+        // Scripts run once loaded, `on_start` is called right after.
+        let mut calls = keys
+            .iter()
+            .filter(|&&key| {
+                instance(key).is_some_and(|instance| instance.env.is_none() && scripts.contains(&instance.script))
+            })
+            .map(|&instance| Call { instance, function: "on_start", args: vec![] })
+            .collect_vec();
+        for cast in casts.read(world.resource::<Events<CastSpell>>()) {
+            let args = vec![Arg::Entity(cast.caster), cast.target.into()];
+            calls.push(Call { instance: InstanceKey::Spell(cast.spell.id()), function: "on_cast", args });
+        }
+        for &event in game_events.read(world.resource::<Events<GameEvent>>()) {
+            let (function, args) = match event {
+                GameEvent::OrderIssued { entity, .. } => ("on_order", vec![Arg::Entity(entity)]),
+                GameEvent::Attacked { attacker, target } => {
+                    ("on_attacked", vec![Arg::Entity(attacker), Arg::Entity(target)])
+                }
+                GameEvent::Died { entity } => ("on_died", vec![Arg::Entity(entity)]),
+                GameEvent::SpellCast { caster, target } => {
+                    ("on_spell_cast", vec![Arg::Entity(caster), target.map_or(Arg::Nil, Arg::Entity)])
+                }
+                GameEvent::WaveSpawned { wave, count } => {
+                    ("on_wave", vec![Arg::Number(wave as f32), Arg::Number(count as f32)])
+                }
+            };
+            calls.extend(keys.iter().map(|&instance| Call { instance, function, args: args.clone() }));
+        }
+        let dt = world.resource::<Time>().delta_seconds();
+        calls.extend(keys.iter().map(|&instance| Call {
+            instance,
+            function: "on_update",
+            args: vec![Arg::Number(dt)],
+        }));
+
+        calls.retain(|call| {
+            instance(call.instance).is_some_and(|instance| {
+                call.function == "on_start"
+                    || instance.handler(lua, call.function).is_ok_and(|handler| handler.is_some())
+            })
+        });
+        if calls.is_empty() {
+            return;
+        }
+
+        lua.app_data_mut::<ScriptContext>().unwrap().snapshot(world);
+        let scripts = world.resource::<Assets<Script>>();
+        for Call { instance, function, args } in calls {
+            let instance = match instance {
+                InstanceKey::Map => map.as_mut(),
+                InstanceKey::Spell(id) => spells.get_mut(&id),
+            };
+            let Some(instance) = instance else {
+                continue;
+            };
+            let Some(script) = scripts.get(&instance.script) else {
+                continue;
+            };
+            lua.app_data_mut::<ScriptContext>().unwrap().reset_instructions();
+            let result = (|| {
+                if instance.env.is_none() {
+                    instance.start(lua, script)?;
+                }
+                match instance.handler(lua, function)? {
+                    Some(handler) => handler.call::<_, ()>(mlua::Variadic::from_iter(args)),
+                    None => Ok(()),
+                }
+            })();
+            if let Err(err) = result {
+                error!("{function} of '{}': {err}", script.name);
+            }
+        }
+        lua.app_data_mut::<ScriptContext>().unwrap().apply(world);
+    });
+}
+
+fn unload(mut runtime: ResMut<ScriptRuntime>) {
+    runtime.map = None;
+    // Spells keep their scripts but start over in the next game.
+    for instance in runtime.spells.values_mut() {
+        instance.env = None;
+    }
+}
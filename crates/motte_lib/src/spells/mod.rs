@@ -1,17 +1,234 @@
-//! Spells
-use crate::prelude::*;
+//! Spells, defined by `assets/spells/*.spell.ron` files, see [`SpellDef`].
+use bevy_common_assets::ron::RonAssetPlugin;
+use serde::Deserialize;
 
+use self::{
+    on_hit::{OnHitEffect, SpellHit},
+    projectile::{Projectile, SpellSource},
+};
+use crate::{
+    app_state::simulating,
+    events::GameEvent,
+    pool::{EntityPool, EntityPoolPlugin},
+    prelude::*,
+    stats::stat::StatPlugin,
+    timer::{Cooldown, TimerPlugin},
+};
+
+pub mod on_hit;
 mod projectile;
 
-// #[derive(Stat, Component, Reflect)]
-// pub struct Affinity<T: Reflect + TypePath> {
-//     #[stat(value)]
-//     value: f32,
-//     #[reflect(ignore)]
-//     _marker: PhantomData<T>,
-// }
+pub struct SpellsPlugin;
+
+impl Plugin for SpellsPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(SpellDef, School, SpellBook, DeliveryMethod, Target, Projectile);
+        app.add_plugins(RonAssetPlugin::<SpellDef>::new(&["spell.ron"]));
+        app.add_event::<CastSpell>();
+        app.add_plugins((
+            EntityPoolPlugin::<Projectile>::with_warm_up(64),
+            TimerPlugin::<Projectile>::default(),
+            on_hit::OnHitPlugin,
+        ));
+        app.add_plugins((
+            StatPlugin::<Affinity<Fire>>::default(),
+            StatPlugin::<Affinity<Frost>>::default(),
+            StatPlugin::<Affinity<Arcane>>::default(),
+            StatPlugin::<CooldownReduction<Fire>>::default(),
+            StatPlugin::<CooldownReduction<Frost>>::default(),
+            StatPlugin::<CooldownReduction<Arcane>>::default(),
+        ));
+        app.add_systems(
+            Update,
+            (
+                projectile::projectile_type::<{ Projectile::Beam }>,
+                projectile::projectile_type::<{ Projectile::Missile }>,
+                projectile::projectile_type::<{ Projectile::Area }>,
+            )
+                .run_if(simulating),
+        );
+        app.add_systems(
+            Update,
+            (projectile::missile, projectile::motion, projectile::hit, projectile::expire)
+                .chain()
+                .after(projectile::projectile_type::<{ Projectile::Missile }>)
+                .run_if(simulating),
+        );
+        app.add_systems(Update, (cooldowns, cast).chain().run_if(simulating));
+    }
+}
+
+#[derive(Asset, Reflect, Deserialize, Clone, Debug)]
+pub struct SpellDef {
+    pub name: String,
+    #[serde(default)]
+    pub delivery: DeliveryMethod,
+    /// Cooldown in seconds.
+    #[serde(default)]
+    pub cooldown: f32,
+    /// Damage of a hit before multipliers, see [`formula`](crate::combat::formula).
+    #[serde(default)]
+    pub damage: f32,
+    /// Element of the spell, hits are scaled by the caster's [`Affinity`] for it.
+    #[serde(default)]
+    pub school: Option<School>,
+    /// Id of the spell's icon in the [`IconAtlas`](crate::asset_management::icons::IconAtlas).
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Script of the spell's effects, e.g. `scripts/fireball.lua`. Requires the `scripting` feature.
+    #[serde(default)]
+    pub script: Option<String>,
+    /// Effects applied when the spell hits, see [`on_hit`].
+    #[serde(default)]
+    pub on_hit: Vec<OnHitEffect>,
+}
+
+/// Casts `spell` from `caster` at `target`.
+#[derive(Event, Clone, Debug)]
+pub struct CastSpell {
+    pub caster: Entity,
+    pub spell: Handle<SpellDef>,
+    pub target: Target,
+}
+
+/// Spells a unit can cast by slot & their cooldowns. Casting a spell of the book puts its slot on cooldown, casts of
+/// a slot on cooldown are ignored.
+#[derive(Component, Reflect, Default, Clone, Debug)]
+#[reflect(Component)]
+pub struct SpellBook {
+    spells: SmallVec<[Handle<SpellDef>; 4]>,
+    cooldowns: SmallVec<[Cooldown<SpellDef>; 4]>,
+}
+
+impl SpellBook {
+    pub fn new(spells: impl IntoIterator<Item = Handle<SpellDef>>) -> Self {
+        let spells: SmallVec<_> = spells.into_iter().collect();
+        let cooldowns = spells.iter().map(|_| Cooldown::default()).collect();
+        Self { spells, cooldowns }
+    }
+
+    pub fn spells(&self) -> &[Handle<SpellDef>] {
+        &self.spells
+    }
+
+    pub fn slot(&self, spell: &Handle<SpellDef>) -> Option<usize> {
+        self.spells.iter().position(|other| other == spell)
+    }
+
+    /// Fraction of the cooldown of `slot` left, 0 when it's ready.
+    pub fn cooldown(&self, slot: usize) -> f32 {
+        self.cooldowns.get(slot).map_or(0.0, Cooldown::fraction)
+    }
+
+    pub fn ready(&self, slot: usize) -> bool {
+        self.cooldowns.get(slot).map_or(true, Cooldown::ready)
+    }
+}
+
+fn cooldowns(mut books: Query<&mut SpellBook>, time: Res<Time>) {
+    let delta = time.delta_seconds();
+    for mut book in &mut books {
+        if book.cooldowns.iter().all(Cooldown::ready) {
+            continue;
+        }
+        for cooldown in &mut book.cooldowns {
+            cooldown.tick(delta);
+        }
+    }
+}
+
+fn cast(
+    mut commands: Commands,
+    mut casts: EventReader<CastSpell>,
+    mut events: EventWriter<GameEvent>,
+    mut hits: EventWriter<SpellHit>,
+    mut books: Query<&mut SpellBook>,
+    mut projectiles: ResMut<EntityPool<Projectile>>,
+    transforms: Query<&GlobalTransform>,
+    defs: Res<Assets<SpellDef>>,
+) {
+    for &CastSpell { caster, ref spell, target } in casts.read() {
+        let Some(def) = defs.get(spell) else {
+            warn!("{caster:?} cast a spell that isn't loaded");
+            continue;
+        };
+        if let Ok(mut book) = books.get_mut(caster)
+            && let Some(slot) = book.slot(spell)
+        {
+            if !book.ready(slot) {
+                continue;
+            }
+            book.cooldowns[slot].start_with(def.cooldown);
+        }
+        let entity = match target {
+            Target::Entity(entity) => Some(entity),
+            Target::Location(_) | Target::None => None,
+        };
+        events.send(GameEvent::SpellCast { caster, target: entity });
+        // Beams hit instantly, projectiles report their hits when they land.
+        match def.delivery {
+            DeliveryMethod::Beam => {
+                if let Some(target) = entity
+                    && let Ok(transform) = transforms.get(target)
+                {
+                    let point = transform.translation();
+                    hits.send(SpellHit { source: caster, target, point, spell: Some(spell.clone()) });
+                }
+            }
+            DeliveryMethod::Projectile => {
+                if !matches!(target, Target::None)
+                    && let Ok(transform) = transforms.get(caster)
+                {
+                    let source = SpellSource { caster, spell: spell.clone() };
+                    projectile::launch(&mut commands, &mut projectiles, source, transform.translation(), target);
+                }
+            }
+            DeliveryMethod::Area => {}
+        }
+    }
+}
+
+/// Marker for the element of a spell, spell schools are their elements for now.
+pub trait Element: TypePath + Send + Sync + 'static {}
+
+#[derive(Reflect)]
+pub struct Fire;
+impl Element for Fire {}
+
+#[derive(Reflect)]
+pub struct Frost;
+impl Element for Frost {}
+
+#[derive(Reflect)]
+pub struct Arcane;
+impl Element for Arcane {}
+
+/// [`Element`] of a [`SpellDef`].
+#[derive(Reflect, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum School {
+    Fire,
+    Frost,
+    Arcane,
+}
+
+/// Damage multiplier for spells of element `E`.
+#[derive(Stat, Component, Reflect)]
+#[reflect(Component)]
+#[stat(name = "affinity")]
+pub struct Affinity<E: Element> {
+    #[stat(value)]
+    value: f32,
+    #[reflect(ignore)]
+    _marker: PhantomData<E>,
+}
+
+/// Cooldown reduction for spells of school `E`.
+#[derive(Stat, Component, Reflect)]
+#[reflect(Component)]
+#[stat(name = "cooldown_reduction")]
+pub struct CooldownReduction<E: Element>(f32, #[reflect(ignore)] PhantomData<E>);
 
-#[derive(Component, Reflect, Default, Clone, Copy)]
+#[derive(Component, Reflect, Deserialize, Default, Clone, Copy, Debug)]
 #[reflect(Component)]
 pub enum DeliveryMethod {
     #[default]
@@ -20,7 +237,7 @@ pub enum DeliveryMethod {
     Area,
 }
 
-#[derive(Component, Reflect, Default, Clone, Copy)]
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
 #[reflect(Component)]
 pub enum Target {
     Location(Vec3),
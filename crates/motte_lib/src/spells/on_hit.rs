@@ -0,0 +1,211 @@
+//! Effects applied when a hit lands, configured by the `on_hit` list of a [`SpellDef`] or an [`OnHit`] component of
+//! the unit that hit, e.g. in its prefab, instead of bespoke systems. Effects compose: an area can apply a status to
+//! every enemy in it & a chain can knock back each enemy it jumps to. Hits are resolved by [`formula::resolve`] first,
+//! misses apply no effects & critical hits knock back further.
+
+use serde::Deserialize;
+
+use super::{Affinity, Arcane, Fire, Frost, School, SpellDef};
+use crate::{
+    combat::{
+        formula::{self, Attack, Outcome, DEFAULT_CRIT_MULTIPLIER},
+        CritChance, CritMultiplier, Damage, Evasion, Shield,
+    },
+    determinism::GameRng,
+    economy::Team,
+    events::GameEvent,
+    navigation::agent::Agent,
+    prelude::*,
+    simulation::GameplaySystems,
+    stats::pool::Pool,
+    tech::TechEffect,
+    timer::{DelayedAction, Expired, TimerPlugin},
+};
+
+pub(super) struct OnHitPlugin;
+
+impl Plugin for OnHitPlugin {
+    fn build(&self, app: &mut App) {
+        app_register_types!(OnHit, OnHitEffect, Status);
+        app.add_event::<SpellHit>();
+        app.add_plugins(TimerPlugin::<Status>::default());
+        app.add_systems(FixedUpdate, (apply, expire).in_set(GameplaySystems::Hits));
+    }
+}
+
+/// A hit of `source` on `target` was confirmed, e.g. its beam was cast at it or its missile reached it.
+#[derive(Event, Clone, Debug)]
+pub struct SpellHit {
+    pub source: Entity,
+    pub target: Entity,
+    pub point: Vec3,
+    /// Spell the hit was delivered by, if any.
+    pub spell: Option<Handle<SpellDef>>,
+}
+
+/// Effects of every hit of the unit, in addition to those of the spell that hit.
+#[derive(Component, Reflect, Deserialize, Default, Clone, Debug)]
+#[reflect(Component)]
+pub struct OnHit(pub Vec<OnHitEffect>);
+
+#[derive(Reflect, Deserialize, Clone, Debug)]
+pub enum OnHitEffect {
+    /// Modifies a stat of the target for `seconds`, e.g. slows it.
+    Status { effect: TechEffect, seconds: f32 },
+    /// Applies `effects` to every enemy within `radius` of the hit.
+    Area { radius: f32, effects: Vec<OnHitEffect> },
+    /// Applies `effects` to up to `jumps` more enemies, each the nearest within `range` of the previous one.
+    Chain { range: f32, jumps: u32, effects: Vec<OnHitEffect> },
+    /// Pushes the target away from the source, in units per second before crits.
+    Knockback(f32),
+    /// Adds to the [`Shield`] of the target up to its total, e.g. for a protective spell cast at an ally.
+    RefreshShield(f32),
+}
+
+/// Modifier of a [`OnHitEffect::Status`], despawned when its [`DelayedAction`] expires.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+struct Status;
+
+/// Units that can be hit & how effects are applied to them.
+struct Hits<'a, 'w, 's> {
+    commands: Commands<'w, 's>,
+    units: &'a [(Entity, Vec3, Option<Team>)],
+    knockbacks: Vec<(Entity, Vec3)>,
+    shields: Vec<(Entity, f32)>,
+}
+
+impl Hits<'_, '_, '_> {
+    fn position(&self, entity: Entity) -> Option<Vec3> {
+        self.units.iter().find(|(unit, ..)| *unit == entity).map(|&(_, position, _)| position)
+    }
+
+    /// Units that aren't `source` & aren't on its team.
+    fn enemies(&self, source: Entity) -> impl Iterator<Item = (Entity, Vec3)> + '_ {
+        let team = self.units.iter().find(|(unit, ..)| *unit == source).and_then(|&(.., team)| team);
+        self.units
+            .iter()
+            .filter(move |&&(unit, _, other)| unit != source && (team.is_none() || other != team))
+            .map(|&(unit, position, _)| (unit, position))
+    }
+
+    /// Applies `effects` of a hit, `power` is the multiplier of its [`Outcome::Hit`].
+    fn apply(&mut self, effects: &[OnHitEffect], source: Entity, target: Entity, point: Vec3, power: f32) {
+        for effect in effects {
+            match effect {
+                OnHitEffect::Status { effect, seconds } => {
+                    let status = effect.spawn_on(&mut self.commands, target, format!("status of {source:?}"));
+                    self.commands.entity(status).insert((Status, DelayedAction::<Status>::new(*seconds)));
+                }
+                OnHitEffect::Area { radius, effects } => {
+                    let inside = self
+                        .enemies(source)
+                        .filter(|(_, position)| position.distance_squared(point) <= radius * radius)
+                        .collect_vec();
+                    for (unit, position) in inside {
+                        self.apply(effects, source, unit, position, power);
+                    }
+                }
+                OnHitEffect::Chain { range, jumps, effects } => {
+                    let mut visited: SmallVec<[Entity; 8]> = smallvec::smallvec![target];
+                    let mut from = self.position(target).unwrap_or(point);
+                    for _ in 0..*jumps {
+                        let Some((unit, position)) = self
+                            .enemies(source)
+                            .filter(|(unit, position)| {
+                                !visited.contains(unit) && position.distance_squared(from) <= range * range
+                            })
+                            .min_by(|(_, a), (_, b)| a.distance_squared(from).total_cmp(&b.distance_squared(from)))
+                        else {
+                            break;
+                        };
+                        visited.push(unit);
+                        from = position;
+                        self.apply(effects, source, unit, position, power);
+                    }
+                }
+                &OnHitEffect::Knockback(strength) => {
+                    let origin = self.position(source).unwrap_or(point);
+                    let target_position = self.position(target).unwrap_or(point);
+                    let direction = (target_position - origin).xz().normalize_or_zero().x0y();
+                    self.knockbacks.push((target, direction * strength * power));
+                }
+                &OnHitEffect::RefreshShield(amount) => self.shields.push((target, amount)),
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply(
+    commands: Commands,
+    mut hits: EventReader<SpellHit>,
+    mut events: EventWriter<GameEvent>,
+    mut damages: EventWriter<Damage>,
+    defs: Res<Assets<SpellDef>>,
+    on_hits: Query<&OnHit>,
+    stats: Query<(Option<&CritChance>, Option<&CritMultiplier>, Option<&Evasion>)>,
+    affinities: Query<(Option<&Affinity<Fire>>, Option<&Affinity<Frost>>, Option<&Affinity<Arcane>>)>,
+    units: Query<(Entity, &GlobalTransform, Option<&Team>), With<Agent>>,
+    mut velocities: Query<&mut LinearVelocity>,
+    mut shields: Query<Pool<Shield>>,
+    mut rng: ResMut<GameRng>,
+) {
+    if hits.is_empty() {
+        return;
+    }
+    let units =
+        units.iter().map(|(entity, transform, team)| (entity, transform.translation(), team.copied())).collect_vec();
+    let mut context = Hits { commands, units: &units, knockbacks: Vec::new(), shields: Vec::new() };
+    for hit in hits.read() {
+        let spell = hit.spell.as_ref().and_then(|spell| defs.get(spell));
+        let (crit_chance, crit_multiplier, _) = stats.get(hit.source).unwrap_or_default();
+        let (.., evasion) = stats.get(hit.target).unwrap_or_default();
+        let (fire, frost, arcane) = affinities.get(hit.source).unwrap_or_default();
+        let affinity = match spell.and_then(|def| def.school) {
+            Some(School::Fire) => fire.map(Stat::value),
+            Some(School::Frost) => frost.map(Stat::value),
+            Some(School::Arcane) => arcane.map(Stat::value),
+            None => None,
+        };
+        let attack = Attack {
+            base: spell.map_or(0.0, |def| def.damage),
+            affinity: affinity.unwrap_or(1.0),
+            crit_chance: crit_chance.map_or(0.0, Stat::value),
+            crit_multiplier: crit_multiplier.map_or(DEFAULT_CRIT_MULTIPLIER, Stat::value),
+            evasion: evasion.map_or(0.0, Stat::value),
+        };
+        let Outcome::Hit { damage, critical, multiplier } = formula::resolve(&mut **rng, attack) else {
+            continue;
+        };
+        events.send(GameEvent::Attacked { attacker: hit.source, target: hit.target });
+        if damage > 0.0 {
+            damages.send(Damage { source: hit.source, target: hit.target, amount: damage, critical });
+        }
+
+        let effects = spell
+            .into_iter()
+            .flat_map(|def| &def.on_hit)
+            .chain(on_hits.get(hit.source).into_iter().flat_map(|on_hit| &on_hit.0));
+        let effects = effects.cloned().collect_vec();
+        context.apply(&effects, hit.source, hit.target, hit.point, multiplier);
+    }
+    for (entity, impulse) in context.knockbacks {
+        if let Ok(mut velocity) = velocities.get_mut(entity) {
+            velocity.0 += impulse;
+        }
+    }
+    for (entity, amount) in context.shields {
+        if let Ok(mut shield) = shields.get_mut(entity) {
+            shield += amount;
+        }
+    }
+}
+
+fn expire(mut commands: Commands, mut expired: EventReader<Expired<Status>>, statuses: Query<(), With<Status>>) {
+    for &Expired { entity, .. } in expired.read() {
+        if statuses.contains(entity) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -1,7 +1,22 @@
 //! Projectile
 use std::marker::ConstParamTy;
 
-use crate::prelude::*;
+use super::{on_hit::SpellHit, Size, Speed, SpellDef, Target};
+use crate::{
+    physics::ccd::{FastProjectile, ProjectileHit},
+    pool::{EntityCommandsReleaseExt, EntityPool},
+    prelude::*,
+    timer::{DelayedAction, Expired},
+};
+
+/// Radius of missiles without a [`Size`].
+const MISSILE_RADIUS: f32 = 0.25;
+
+/// Speed of missiles without a [`Speed`], in units per second.
+const MISSILE_SPEED: f32 = 20.0;
+
+/// Seconds until missiles that haven't hit anything are returned to the pool.
+const MISSILE_LIFETIME: f32 = 5.0;
 
 #[derive(
     Component, Default, Debug, ConstParamTy, Clone, Display, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect,
@@ -18,6 +33,13 @@ pub enum Projectile {
 #[derive(Component, Default, Reflect)]
 pub struct ProjectileType<const PROJECTILE: Projectile>;
 
+/// Caster & spell of a projectile, its hits are sent as [`SpellHit`]s.
+#[derive(Component, Reflect, Clone, Debug)]
+pub struct SpellSource {
+    pub caster: Entity,
+    pub spell: Handle<SpellDef>,
+}
+
 // Spell Origin
 #[derive(Component, Default, Reflect)]
 pub struct Origin(Vec3);
@@ -33,6 +55,22 @@ pub enum ProjectileMotion {
     Instant,
 }
 
+/// Takes a missile from the pool & launches it from `origin` at `target`.
+pub(super) fn launch(
+    commands: &mut Commands,
+    pool: &mut EntityPool<Projectile>,
+    source: SpellSource,
+    origin: Vec3,
+    target: Target,
+) {
+    pool.acquire(commands, Projectile::Missile).insert((
+        source,
+        target,
+        Transform::from_translation(origin),
+        DelayedAction::<Projectile>::new(MISSILE_LIFETIME),
+    ));
+}
+
 pub(super) fn projectile_type<const PROJECTILE: Projectile>(
     commands: ParallelCommands,
     projectiles: Query<(Entity, &Projectile), (Changed<Projectile>, Without<ProjectileType<PROJECTILE>>)>,
@@ -55,4 +93,80 @@ pub(super) fn projectile_type<const PROJECTILE: Projectile>(
     }
 }
 
-pub(super) fn motion() {}
+/// Missiles are fast enough to tunnel through thin obstacles, they're moved kinematically & hit through swept
+/// [`FastProjectile`] casts instead of the solver.
+pub(super) fn missile(
+    mut commands: Commands,
+    missiles: Query<(Entity, Option<&Size>, Option<&SpellSource>), Added<ProjectileType<{ Projectile::Missile }>>>,
+) {
+    for (entity, size, source) in &missiles {
+        let mut projectile = FastProjectile::new(size.map_or(MISSILE_RADIUS, Stat::value));
+        if let Some(source) = source {
+            projectile = projectile.with_ignore(source.caster);
+        }
+        commands.entity(entity).insert((RigidBody::Kinematic, LinearVelocity::ZERO, projectile));
+    }
+}
+
+pub(super) fn motion(
+    mut missiles: Query<
+        (&mut LinearVelocity, &Position, &Target, Option<&Speed>),
+        With<ProjectileType<{ Projectile::Missile }>>,
+    >,
+    targets: Query<&Position, Without<ProjectileType<{ Projectile::Missile }>>>,
+) {
+    for (mut velocity, position, target, speed) in &mut missiles {
+        let target = match *target {
+            Target::Location(location) => location,
+            Target::Entity(entity) => match targets.get(entity) {
+                Ok(target) => target.0,
+                Err(_) => continue,
+            },
+            Target::None => continue,
+        };
+        velocity.0 = (target - position.0).normalize_or_zero() * speed.map_or(MISSILE_SPEED, Stat::value);
+    }
+}
+
+pub(super) fn hit(
+    mut commands: Commands,
+    mut hits: EventReader<ProjectileHit>,
+    mut spell_hits: EventWriter<SpellHit>,
+    missiles: Query<Option<&SpellSource>, With<ProjectileType<{ Projectile::Missile }>>>,
+) {
+    for hit in hits.read() {
+        let Ok(source) = missiles.get(hit.projectile) else {
+            continue;
+        };
+        if let Some(source) = source {
+            spell_hits.send(SpellHit {
+                source: source.caster,
+                target: hit.entity,
+                point: hit.point,
+                spell: Some(source.spell.clone()),
+            });
+        }
+        stop(&mut commands, hit.projectile);
+    }
+}
+
+/// Returns missiles that didn't hit anything in time to the pool.
+pub(super) fn expire(
+    mut commands: Commands,
+    mut expired: EventReader<Expired<Projectile>>,
+    missiles: Query<(), With<ProjectileType<{ Projectile::Missile }>>>,
+) {
+    for &Expired { entity, .. } in expired.read() {
+        if missiles.contains(entity) {
+            stop(&mut commands, entity);
+        }
+    }
+}
+
+fn stop(commands: &mut Commands, missile: Entity) {
+    commands
+        .entity(missile)
+        .remove::<(FastProjectile, RigidBody, DelayedAction<Projectile>)>()
+        .insert(LinearVelocity::ZERO)
+        .release();
+}
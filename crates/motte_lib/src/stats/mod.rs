@@ -1,6 +1,6 @@
 use self::modifier::Modifies;
 use crate::{
-    core::previous::{propagate_previous_changed, PreviousValue},
+    core::previous::{propagate_previous_changed, Previous},
     prelude::*,
 };
 
@@ -14,15 +14,16 @@ pub mod modifier;
 pub mod pool;
 pub mod stat;
 
-#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
-pub(crate) enum StatSystem {
-    Dirty,
-    DirtyFlush,
-    Reset,
-    ResetFlush,
-    ModifierFlat,
-    ModifierMult,
-    Cleanup,
+ordered_sets! {
+    pub(crate) enum StatSystem: PostUpdate {
+        Dirty,
+        DirtyFlush,
+        Reset,
+        ResetFlush,
+        ModifierFlat,
+        ModifierMult,
+        Cleanup,
+    }
 }
 
 #[derive(Default)]
@@ -30,24 +31,12 @@ pub struct StatsPlugin;
 
 impl Plugin for StatsPlugin {
     fn build(&self, app: &mut App) {
-        app_register_types!(Modifies, PreviousValue<Modifies>);
-
-        app.configure_sets(
-            PostUpdate,
-            (
-                StatSystem::Dirty,
-                StatSystem::DirtyFlush,
-                StatSystem::Reset,
-                StatSystem::ResetFlush,
-                StatSystem::ModifierFlat,
-                StatSystem::ModifierMult,
-                StatSystem::Cleanup,
-            )
-                .chain(),
-        );
+        app_register_types!(Modifies, Previous<Modifies>);
+
+        StatSystem::configure(app);
 
         app.add_systems(PostUpdate, apply_deferred.in_set(StatSystem::DirtyFlush));
         app.add_systems(PostUpdate, apply_deferred.in_set(StatSystem::ResetFlush));
-        app.add_systems(PostUpdate, propagate_previous_changed::<Modifies>.in_set(StatSystem::Cleanup));
+        app.add_systems(PostUpdate, propagate_previous_changed::<Modifies, ()>.in_set(StatSystem::Cleanup));
     }
 }
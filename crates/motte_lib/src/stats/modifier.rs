@@ -7,7 +7,7 @@ use super::{
     stat::{DirtyStat, Stat},
     StatSystem,
 };
-use crate::{core::previous::PreviousValue, prelude::*};
+use crate::{core::previous::Previous, prelude::*};
 
 pub struct ModifierPlugin<M: Stat, S: ModifiableStats>(PhantomData<M>, PhantomData<S>)
 where
@@ -163,7 +163,7 @@ fn modifier_changed<M: Modifier<T>, T: Stat, S: Stat>(
 pub(super) fn modifier_target_changed<S: Stat>(
     mut commands: Commands,
     mut stats: Query<Entity, NonDirtyStatFilter<S>>,
-    modifiers: Query<(&Modifies, &PreviousValue<Modifies>), Changed<Modifies>>,
+    modifiers: Query<(&Modifies, &Previous<Modifies>), Changed<Modifies>>,
 ) where
     S: Component,
 {
@@ -187,6 +187,30 @@ pub(super) fn modifier_target_changed<S: Stat>(
     }
 }
 
+/// Modifiers `M` applied to the stat of `entity` & the entities they're on, resolved like [apply_modifier].
+#[cfg(feature = "dev_tools")]
+pub(super) fn sources<M: Modifier<S>, S: Stat>(world: &mut World, entity: Entity) -> Vec<(Entity, f32)>
+where
+    M: Component,
+{
+    let mut modifiers = world.query::<(Entity, &M, Option<&Parent>, Option<&Modifies>)>();
+    let mut modifier_parents = world.query::<&Modifies>();
+    let world = &*world;
+
+    modifiers
+        .iter(world)
+        .filter(|&(source, _, maybe_parent, maybe_target)| {
+            let modifier_target = maybe_target.or(maybe_parent.and_then(|p| modifier_parents.get(world, p.get()).ok()));
+            match modifier_target {
+                Some(Modifies::Single(target)) => *target == entity,
+                Some(Modifies::Many(targets)) => targets.contains(&entity),
+                None => source == entity || maybe_parent.is_some_and(|p| p.get() == entity),
+            }
+        })
+        .map(|(source, modifier, ..)| (source, <M as Modifier<S>>::value(modifier)))
+        .collect()
+}
+
 // perf: should only add dirty to the removed modifiers' target (but how we do that?).
 fn modifier_removed<M: Modifier<T>, T: Stat, S: Stat>(
     query: Query<Entity, NonDirtyStatFilter<S>>,
@@ -79,6 +79,15 @@ impl<'w, S: Stat + Component> PoolItem<'w, S> {
             Err(value) => self.current.0 = value,
         };
     }
+
+    /// Takes up to `amount` out of the pool & returns the part it couldn't absorb, so pools can be layered by passing
+    /// the rest on to the next one, e.g. a shield in front of health.
+    #[inline]
+    pub fn absorb(&mut self, amount: f32) -> f32 {
+        let absorbed = amount.clamp(0.0, self.current());
+        self.set_current(self.current() - absorbed);
+        amount - absorbed
+    }
 }
 
 impl<'w, S: Stat + Component> AddAssign<f32> for PoolItem<'w, S> {
@@ -195,7 +204,8 @@ pub(crate) fn pool_clamp(current: f32, max: f32) -> Result<f32, f32> {
 
 #[inline]
 pub(crate) fn pool_perc(current: f32, max: f32) -> f32 {
-    if current == 0.0 && max == 0.0 {
+    // Pools start out full, before their total is resolved.
+    if max <= 0.0 {
         return 1.0;
     }
 
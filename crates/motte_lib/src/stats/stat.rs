@@ -3,11 +3,12 @@ use std::marker::PhantomData;
 use bevy::reflect::TypePath;
 
 use super::{
-    modifier::ModifierPlugin,
+    modifier::{Flat, ModifierPlugin, Mult},
     pool::{self, Current, PoolBundle},
 };
 use crate::{
     prelude::*,
+    save::AppSaveExt,
     stats::{modifier, pool::DirtyCurrent, StatSystem},
 };
 
@@ -22,12 +23,15 @@ where
 
 impl<S: Stat> Plugin for StatPlugin<S>
 where
-    S: Component + GetTypeRegistration,
+    S: Component + GetTypeRegistration + FromReflect,
 {
     fn build(&self, app: &mut App) {
         app_register_types!(Current<S>, DirtyStat<S>, DirtyCurrent<S>, S);
+        app.register_save::<S>().register_save::<Flat<S>>().register_save::<Mult<S>>().register_save::<Current<S>>();
 
         app.add_plugins(ModifierPlugin::<S, S>::default());
+        #[cfg(feature = "dev_tools")]
+        app.world.get_resource_or_insert_with(StatRegistry::default).register::<S>();
         app.add_systems(
             PostUpdate,
             (dirty_on_added::<S>, modifier::modifier_target_changed::<S>).in_set(StatSystem::Dirty),
@@ -131,6 +135,11 @@ pub trait Stat: Reflect + TypePath + Default + Sync + Send + Sized + 'static {
         PoolBundle::new(value)
     }
 
+    /// Name of the [Stat] in tools like the console, set with `#[stat(name = "...")]`. Defaults to the short type path.
+    fn name() -> String {
+        Self::short_type_path().to_owned()
+    }
+
     /// Returns the value of the [Stat].
     fn value(&self) -> f32;
 
@@ -176,3 +185,93 @@ where
         commands.entity(entity).remove::<DirtyStat<S>>();
     }
 }
+
+/// Every [Stat] added with a [StatPlugin], so tools can list the stats of an entity without knowing their types.
+#[cfg(feature = "dev_tools")]
+#[derive(Resource, Default)]
+pub struct StatRegistry(Vec<RegisteredStat>);
+
+#[cfg(feature = "dev_tools")]
+impl StatRegistry {
+    fn register<S: Stat + Component>(&mut self) {
+        self.0.push(RegisteredStat {
+            name: S::name(),
+            inspect: inspect::<S>,
+            set_base: set_base::<S>,
+            set_value: set_value::<S>,
+        });
+        self.0.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RegisteredStat> {
+        self.0.iter()
+    }
+}
+
+#[cfg(feature = "dev_tools")]
+#[derive(Clone)]
+pub struct RegisteredStat {
+    pub name: String,
+    /// Returns the breakdown of the stat of an entity, if it has the stat.
+    pub inspect: fn(&mut World, Entity) -> Option<StatBreakdown>,
+    /// Sets the base value of the stat of an entity, the stat is recomputed from it.
+    pub set_base: fn(&mut World, Entity, f32),
+    /// Overwrites the computed value of the stat of an entity until it's recomputed.
+    pub set_value: fn(&mut World, Entity, f32),
+}
+
+#[cfg(feature = "dev_tools")]
+pub struct StatBreakdown {
+    pub value: f32,
+    /// Value of the [modifier::Flat] on the entity itself.
+    pub base: Option<f32>,
+    pub modifiers: Vec<ModifierSource>,
+}
+
+/// A modifier applied to a stat & the entity it's on.
+#[cfg(feature = "dev_tools")]
+pub struct ModifierSource {
+    pub source: Entity,
+    pub op: ModifierOp,
+    pub value: f32,
+}
+
+#[cfg(feature = "dev_tools")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModifierOp {
+    Flat,
+    Mult,
+}
+
+#[cfg(feature = "dev_tools")]
+fn inspect<S: Stat + Component>(world: &mut World, entity: Entity) -> Option<StatBreakdown> {
+    let value = world.get::<S>(entity)?.value();
+    let base = world.get::<Flat<S>>(entity).map(|base| base.0.value());
+    let mut modifiers = modifier::sources::<Flat<S>, S>(world, entity)
+        .into_iter()
+        .filter(|&(source, _)| source != entity)
+        .map(|(source, value)| ModifierSource { source, op: ModifierOp::Flat, value })
+        .collect_vec();
+    modifiers.extend(
+        modifier::sources::<Mult<S>, S>(world, entity)
+            .into_iter()
+            .map(|(source, value)| ModifierSource { source, op: ModifierOp::Mult, value }),
+    );
+    Some(StatBreakdown { value, base, modifiers })
+}
+
+#[cfg(feature = "dev_tools")]
+fn set_base<S: Stat + Component>(world: &mut World, entity: Entity, value: f32) {
+    if let Some(mut base) = world.get_mut::<Flat<S>>(entity) {
+        *base.0.value_mut() = value;
+    }
+}
+
+#[cfg(feature = "dev_tools")]
+fn set_value<S: Stat + Component>(world: &mut World, entity: Entity, value: f32) {
+    if let Some(mut stat) = world.get_mut::<S>(entity)
+        && stat.value() != value
+    {
+        *stat.value_mut() = value;
+    }
+}
@@ -0,0 +1,360 @@
+//! Researchable upgrades, defined by the [`TechTree`] in `assets/config/default.tech.ron`. Send a [`StartResearch`]
+//! to research a [`TechDef`], it's paid from the team's [`Stockpile`] & requires its prerequisites to be researched.
+//! Researched techs apply their [`TechEffect`]s as stat modifiers to every matching unit of the team, including units
+//! spawned later. Progress is reported through [`ResearchEvent`]s.
+
+use bevy_common_assets::ron::RonAssetPlugin;
+use serde::Deserialize;
+
+use crate::{
+    app_state::{simulating, AppState},
+    cleanup::StateScoped,
+    combat::Health,
+    economy::{GatherRate, ResourceKind, Stockpile, Team},
+    navigation::agent::{Agent, Speed},
+    prelude::*,
+    save::AppSaveExt,
+    spells::{Affinity, Arcane, Fire, Frost},
+    stats::{
+        modifier::{Flat, Modifies, Mult},
+        StatSystem,
+    },
+};
+
+pub struct TechPlugin;
+
+impl Plugin for TechPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<TechTree>::new(&["tech.ron"]));
+        app_register_types!(
+            res: TechTree,
+            TechDef,
+            TechEffect,
+            TechStat,
+            TechModifier,
+            res: Research,
+            TeamResearch,
+            Researching,
+            TechBonus
+        );
+        app.register_save_resource::<Research>();
+        app.add_event::<StartResearch>().add_event::<ResearchEvent>();
+        app.add_systems(PreUpdate, load);
+        app.add_systems(FixedUpdate, (start, progress).chain().run_if(simulating));
+        app.add_systems(
+            PostUpdate,
+            (
+                retarget::<Speed>,
+                retarget::<GatherRate>,
+                retarget::<Health>,
+                retarget::<Affinity<Fire>>,
+                retarget::<Affinity<Frost>>,
+                retarget::<Affinity<Arcane>>,
+            )
+                .before(StatSystem::Dirty),
+        );
+        app.add_systems(OnExit(AppState::InGame), |mut research: ResMut<Research>| *research = default());
+    }
+}
+
+/// Every researchable [`TechDef`].
+#[derive(Asset, Resource, Reflect, Deserialize, Default, Clone, Debug)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct TechTree {
+    pub techs: Vec<TechDef>,
+}
+
+impl TechTree {
+    pub fn get(&self, name: &str) -> Option<&TechDef> {
+        self.techs.iter().find(|tech| tech.name == name)
+    }
+
+    /// Techs not researched yet whose prerequisites are.
+    pub fn available<'a>(&'a self, researched: &'a TeamResearch) -> impl Iterator<Item = &'a TechDef> {
+        self.techs
+            .iter()
+            .filter(|tech| !researched.has(&tech.name) && tech.requires.iter().all(|name| researched.has(name)))
+    }
+}
+
+#[derive(Reflect, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct TechDef {
+    pub name: String,
+    pub description: String,
+    pub cost: Vec<(ResourceKind, u32)>,
+    /// Seconds it takes to research.
+    pub duration: f32,
+    /// Names of the techs that have to be researched first.
+    pub requires: Vec<String>,
+    pub effects: Vec<TechEffect>,
+}
+
+/// Modifies `stat` of the team's units, only units of the `agent` size if set.
+#[derive(Reflect, Deserialize, Clone, Copy, Debug)]
+pub struct TechEffect {
+    pub stat: TechStat,
+    pub modifier: TechModifier,
+    #[serde(default)]
+    pub agent: Option<Agent>,
+}
+
+impl TechEffect {
+    /// Spawns a [`TechBonus`] applying the effect to the units of `team`.
+    pub fn spawn(&self, commands: &mut Commands, team: Team, name: String) -> Entity {
+        let mut bonus = commands.spawn((
+            Name::new(name),
+            TechBonus { team, agent: self.agent },
+            Modifies::Many(default()),
+            StateScoped(AppState::InGame),
+        ));
+        self.insert(&mut bonus);
+        bonus.id()
+    }
+
+    /// Spawns a modifier applying the effect to `target` only, regardless of its team & size.
+    pub fn spawn_on(&self, commands: &mut Commands, target: Entity, name: String) -> Entity {
+        let mut modifier = commands.spawn((Name::new(name), Modifies::Single(target), StateScoped(AppState::InGame)));
+        self.insert(&mut modifier);
+        modifier.id()
+    }
+
+    fn insert(&self, entity: &mut EntityCommands) {
+        match self.stat {
+            TechStat::Speed => self.modifier.insert::<Speed>(entity),
+            TechStat::GatherRate => self.modifier.insert::<GatherRate>(entity),
+            TechStat::Health => self.modifier.insert::<Health>(entity),
+            TechStat::FireAffinity => self.modifier.insert::<Affinity<Fire>>(entity),
+            TechStat::FrostAffinity => self.modifier.insert::<Affinity<Frost>>(entity),
+            TechStat::ArcaneAffinity => self.modifier.insert::<Affinity<Arcane>>(entity),
+        }
+    }
+}
+
+/// Stats a [`TechEffect`] can modify.
+#[derive(Reflect, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TechStat {
+    Speed,
+    GatherRate,
+    Health,
+    FireAffinity,
+    FrostAffinity,
+    ArcaneAffinity,
+}
+
+#[derive(Reflect, Deserialize, Clone, Copy, Debug)]
+pub enum TechModifier {
+    /// Added to the base value.
+    Flat(f32),
+    /// Multiplies the value, e.g. `Mult(1.1)` for +10%.
+    Mult(f32),
+}
+
+impl TechModifier {
+    fn insert<S: Stat + Component>(self, entity: &mut EntityCommands) {
+        match self {
+            TechModifier::Flat(value) => entity.insert(Flat(S::new(value))),
+            TechModifier::Mult(value) => entity.insert(Mult(S::new(value))),
+        };
+    }
+}
+
+/// Researched & ongoing techs of each team.
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct Research(HashMap<Team, TeamResearch>);
+
+impl Research {
+    pub fn get(&self, team: Team) -> Option<&TeamResearch> {
+        self.0.get(&team)
+    }
+
+    /// Research of `team`, inserted if it hasn't researched anything yet.
+    pub fn get_mut(&mut self, team: Team) -> &mut TeamResearch {
+        self.0.entry(team).or_default()
+    }
+}
+
+#[derive(Reflect, Default, Clone, Debug)]
+pub struct TeamResearch {
+    /// In the order they were researched.
+    pub researched: Vec<String>,
+    pub current: Option<Researching>,
+}
+
+impl TeamResearch {
+    pub fn has(&self, tech: &str) -> bool {
+        self.researched.iter().any(|name| name == tech)
+    }
+}
+
+#[derive(Reflect, Clone, Debug)]
+pub struct Researching {
+    pub tech: String,
+    /// Seconds spent researching.
+    pub elapsed: f32,
+}
+
+/// Starts researching `tech` for `team`, a team researches one tech at a time.
+#[derive(Event, Clone, Debug)]
+pub struct StartResearch {
+    pub team: Team,
+    pub tech: String,
+}
+
+#[derive(Event, Clone, Debug)]
+pub enum ResearchEvent {
+    Started { team: Team, tech: String },
+    /// Sent every tick while researching, `progress` goes from 0 to 1.
+    Progress { team: Team, tech: String, progress: f32 },
+    Completed { team: Team, tech: String },
+    Rejected { team: Team, tech: String, reason: ResearchError },
+}
+
+#[derive(Error, Clone, Debug, PartialEq)]
+pub enum ResearchError {
+    #[error("unknown tech")]
+    Unknown,
+    #[error("already researched")]
+    Researched,
+    #[error("already researching {0}")]
+    Busy(String),
+    #[error("requires {0}")]
+    Requires(String),
+    #[error("not enough resources")]
+    Cost,
+}
+
+/// Stat modifier of a [`TechEffect`], e.g. of a researched tech, targets the matching units of its team.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct TechBonus {
+    pub team: Team,
+    pub agent: Option<Agent>,
+}
+
+impl TechBonus {
+    fn matches(&self, team: Team, agent: Option<Agent>) -> bool {
+        self.team == team && self.agent.map_or(true, |size| agent == Some(size))
+    }
+}
+
+fn load(mut events: EventReader<AssetEvent<TechTree>>, assets: Res<Assets<TechTree>>, mut tree: ResMut<TechTree>) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event
+            && let Some(loaded) = assets.get(*id)
+        {
+            for tech in &loaded.techs {
+                for name in tech.requires.iter().filter(|name| loaded.get(name).is_none()) {
+                    warn!("Tech '{}' requires unknown tech '{name}'", tech.name);
+                }
+            }
+            info!("Applied tech tree");
+            *tree = loaded.clone();
+        }
+    }
+}
+
+fn start(
+    mut requests: EventReader<StartResearch>,
+    mut events: EventWriter<ResearchEvent>,
+    mut research: ResMut<Research>,
+    mut stockpile: ResMut<Stockpile>,
+    tree: Res<TechTree>,
+) {
+    for StartResearch { team, tech } in requests.read() {
+        let (team, tech) = (*team, tech.clone());
+        let state = research.get_mut(team);
+        if let Err(reason) = validate(&tree, team, state, &stockpile, &tech) {
+            events.send(ResearchEvent::Rejected { team, tech, reason });
+            continue;
+        }
+
+        let def = tree.get(&tech).expect("validated tech should exist");
+        for &(kind, amount) in &def.cost {
+            stockpile.spend(team, kind, amount);
+        }
+        state.current = Some(Researching { tech: tech.clone(), elapsed: 0.0 });
+        events.send(ResearchEvent::Started { team, tech });
+    }
+}
+
+fn validate(
+    tree: &TechTree,
+    team: Team,
+    state: &TeamResearch,
+    stockpile: &Stockpile,
+    tech: &str,
+) -> Result<(), ResearchError> {
+    let def = tree.get(tech).ok_or(ResearchError::Unknown)?;
+    if state.has(tech) {
+        return Err(ResearchError::Researched);
+    }
+    if let Some(current) = &state.current {
+        return Err(ResearchError::Busy(current.tech.clone()));
+    }
+    if let Some(missing) = def.requires.iter().find(|name| !state.has(name)) {
+        return Err(ResearchError::Requires(missing.clone()));
+    }
+    if def.cost.iter().any(|&(kind, amount)| stockpile.get(team, kind) < amount) {
+        return Err(ResearchError::Cost);
+    }
+    Ok(())
+}
+
+/// Advances the current research of each team & spawns the [`TechBonus`]es of completed techs.
+fn progress(
+    mut commands: Commands,
+    mut research: ResMut<Research>,
+    mut events: EventWriter<ResearchEvent>,
+    tree: Res<TechTree>,
+    time: Res<Time>,
+) {
+    for (&team, state) in &mut research.0 {
+        let Some(current) = &mut state.current else {
+            continue;
+        };
+        let Some(def) = tree.get(&current.tech) else {
+            state.current = None;
+            continue;
+        };
+
+        current.elapsed += time.delta_seconds();
+        let progress = if def.duration > 0.0 { (current.elapsed / def.duration).min(1.0) } else { 1.0 };
+        events.send(ResearchEvent::Progress { team, tech: def.name.clone(), progress });
+        if progress < 1.0 {
+            continue;
+        }
+
+        for effect in &def.effects {
+            effect.spawn(&mut commands, team, format!("{} bonus", def.name));
+        }
+        state.researched.push(def.name.clone());
+        state.current = None;
+        events.send(ResearchEvent::Completed { team, tech: def.name.clone() });
+    }
+}
+
+/// Points the [`TechBonus`]es modifying `S` at the matching units when bonuses or units with `S` are added or
+/// removed.
+fn retarget<S: Stat + Component>(
+    mut bonuses: Query<(Ref<TechBonus>, &mut Modifies), Or<(With<Flat<S>>, With<Mult<S>>)>>,
+    units: Query<(Entity, &Team, Option<&Agent>), With<S>>,
+    added: Query<(), Added<S>>,
+    mut removed: RemovedComponents<S>,
+) {
+    let units_changed = !added.is_empty() | (removed.read().count() > 0);
+    for (bonus, mut modifies) in &mut bonuses {
+        if !units_changed && !bonus.is_added() {
+            continue;
+        }
+        *modifies = Modifies::Many(
+            units
+                .iter()
+                .filter(|&(_, &team, agent)| bonus.matches(team, agent.copied()))
+                .map(|(entity, ..)| entity)
+                .collect(),
+        );
+    }
+}
@@ -28,10 +28,9 @@ pub fn world_space_ray_from_ndc(ndc: Vec2, camera: &Camera, camera_transform: &G
 
 #[allow(unused)]
 #[inline]
-pub fn random_point_in_square(size: f32) -> Vec2 {
-    let x = random::<f32>() * size - size / 2.0;
-    let y = random::<f32>() * size - size / 2.0;
-    Vec2::new(x, y)
+pub fn random_point_in_square(rng: &mut impl Rng, size: f32) -> Vec2 {
+    let half_size = size / 2.0;
+    Vec2::new(rng.gen_range(-half_size..half_size), rng.gen_range(-half_size..half_size))
 }
 
 /// ref: https://github.com/Jondolf/barry/blob/main/src/utils/point_in_poly2d.rs
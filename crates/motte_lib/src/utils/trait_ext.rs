@@ -2,6 +2,7 @@
 
 use std::simd::{f32x4, num::SimdFloat};
 
+use bevy::ecs::query::{QueryFilter, QueryItem, ROQueryItem};
 use parry2d::na::SimdPartialOrd;
 
 use crate::prelude::*;
@@ -229,6 +230,51 @@ impl Zero for f32 {
     const ZERO: Self = 0.0;
 }
 
+pub(crate) trait QueryExt<'w, 's, D: QueryData, F: QueryFilter> {
+    /// Same as [`Query::par_iter`], but iterates sequentially with the `determinism` feature so that order
+    /// dependent side effects (e.g. [`ParallelCommands`]) are the same between runs.
+    fn stable_par_iter(&self) -> StableParIter<'_, 'w, 's, D, F>;
+
+    /// Same as [`Query::par_iter_mut`], see [`QueryExt::stable_par_iter`].
+    fn stable_par_iter_mut(&mut self) -> StableParIterMut<'_, 'w, 's, D, F>;
+}
+
+impl<'w, 's, D: QueryData, F: QueryFilter> QueryExt<'w, 's, D, F> for Query<'w, 's, D, F> {
+    #[inline]
+    fn stable_par_iter(&self) -> StableParIter<'_, 'w, 's, D, F> {
+        StableParIter(self)
+    }
+
+    #[inline]
+    fn stable_par_iter_mut(&mut self) -> StableParIterMut<'_, 'w, 's, D, F> {
+        StableParIterMut(self)
+    }
+}
+
+pub(crate) struct StableParIter<'a, 'w, 's, D: QueryData, F: QueryFilter>(&'a Query<'w, 's, D, F>);
+
+impl<'a, 'w, 's, D: QueryData, F: QueryFilter> StableParIter<'a, 'w, 's, D, F> {
+    #[inline]
+    pub fn for_each<FN: Fn(ROQueryItem<'a, D>) + Send + Sync + Clone>(self, func: FN) {
+        #[cfg(feature = "determinism")]
+        self.0.iter().for_each(func);
+        #[cfg(not(feature = "determinism"))]
+        self.0.par_iter().for_each(func);
+    }
+}
+
+pub(crate) struct StableParIterMut<'a, 'w, 's, D: QueryData, F: QueryFilter>(&'a mut Query<'w, 's, D, F>);
+
+impl<'a, 'w, 's, D: QueryData, F: QueryFilter> StableParIterMut<'a, 'w, 's, D, F> {
+    #[inline]
+    pub fn for_each<FN: Fn(QueryItem<'a, D>) + Send + Sync + Clone>(self, func: FN) {
+        #[cfg(feature = "determinism")]
+        self.0.iter_mut().for_each(func);
+        #[cfg(not(feature = "determinism"))]
+        self.0.par_iter_mut().for_each(func);
+    }
+}
+
 // Base trait for types that can be wrapped in a [`NotZero`](struct.NotZero.html).
 //
 // Implementors must provide a singleton object that will be used to mark empty edges in a
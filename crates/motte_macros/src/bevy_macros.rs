@@ -1,14 +1,49 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{punctuated::Punctuated, Token, Type};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Ident, Token, Type,
+};
+
+/// A type to register, optionally prefixed with `res:` or `asset:`.
+struct Registration {
+    kind: Option<Ident>,
+    ty: Type,
+}
+
+impl Parse for Registration {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kind = if input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let kind = input.parse::<Ident>()?;
+            input.parse::<Token![:]>()?;
+            if kind != "res" && kind != "asset" {
+                return Err(syn::Error::new(kind.span(), "expected `res:` or `asset:`"));
+            }
+            Some(kind)
+        } else {
+            None
+        };
+        Ok(Self { kind, ty: input.parse()? })
+    }
+}
 
 pub(crate) fn app_register_types_impl(input: TokenStream) -> TokenStream {
     use syn::parse::Parser;
-    let types = Punctuated::<Type, Token![,]>::parse_separated_nonempty.parse(input).unwrap();
+    let registrations = match Punctuated::<Registration, Token![,]>::parse_separated_nonempty.parse(input) {
+        Ok(registrations) => registrations,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
-    let expanded = types.iter().map(|ty| {
+    let expanded = registrations.iter().map(|Registration { kind, ty }| {
+        let extra = match kind {
+            Some(kind) if kind == "res" => quote! { app.init_resource::<#ty>(); },
+            Some(_) => quote! { app.register_asset_reflect::<#ty>(); },
+            None => quote! {},
+        };
         quote! {
             app.register_type::<#ty>();
+            #extra
         }
     });
 
@@ -0,0 +1,90 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span};
+use proc_macro_crate::{crate_name, FoundCrate};
+use proc_macro_error::abort;
+use quote::quote;
+use syn::{DeriveInput, Fields, Path};
+
+const CRATE_IDENT: &str = "motte_lib";
+
+/// Tuples of more than this many systems don't implement `IntoSystemConfigs`.
+const MAX_TUPLE_LEN: usize = 16;
+
+struct Layer {
+    field: Ident,
+    gizmos: Path,
+    per_agent: bool,
+}
+
+pub(super) fn impl_debug_layer_derive(ast: &DeriveInput) -> TokenStream {
+    let crate_ident = match crate_name(CRATE_IDENT)
+        .unwrap_or_else(|_| panic!("expected {CRATE_IDENT:?} is present in `Cargo.toml`"))
+    {
+        FoundCrate::Itself => quote!(crate),
+        FoundCrate::Name(name) => {
+            let ident = Ident::new(&name, Span::call_site());
+            quote!( #ident )
+        }
+    };
+
+    let name = &ast.ident;
+    let systems = find_layers(ast).into_iter().map(|Layer { field, gizmos, per_agent }| {
+        if per_agent {
+            quote! {
+                #crate_ident::navigation::agent::for_each_agent!(|AGENT| #gizmos::<AGENT>
+                    .run_if(|layers: Res<#name>| layers.#field.enabled_for(AGENT)))
+            }
+        } else {
+            quote! { #gizmos.run_if(|layers: Res<#name>| layers.#field) }
+        }
+    });
+    let chunks = systems.collect::<Vec<_>>();
+    let chunks = chunks.chunks(MAX_TUPLE_LEN).map(|chunk| quote! { ( #( #chunk, )* ) });
+
+    let expanded = quote! {
+        impl #name {
+            /// Gizmo systems of all `#[debug_layer]` fields, each only runs while its layer is enabled.
+            pub(crate) fn gizmos() -> bevy::ecs::schedule::SystemConfigs {
+                use bevy::prelude::*;
+                ( #( #chunks, )* ).into_configs()
+            }
+        }
+    };
+    expanded.into()
+}
+
+fn find_layers(ast: &DeriveInput) -> Vec<Layer> {
+    let syn::Data::Struct(data) = &ast.data else {
+        abort!(ast.ident, "DebugLayer can only be derived for structs");
+    };
+    let Fields::Named(fields) = &data.fields else {
+        abort!(ast.ident, "DebugLayer can only be derived for structs with named fields");
+    };
+
+    let mut layers = Vec::new();
+    for field in &fields.named {
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("debug_layer")) {
+            let mut gizmos = None;
+            let mut per_agent = false;
+            let parsed = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("gizmos") {
+                    gizmos = Some(meta.value()?.parse::<Path>()?);
+                    Ok(())
+                } else if meta.path.is_ident("per_agent") {
+                    per_agent = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `gizmos = path` or `per_agent`"))
+                }
+            });
+            if let Err(err) = parsed {
+                abort!(err.span(), "{}", err);
+            }
+            let Some(gizmos) = gizmos else {
+                abort!(attr, "missing `gizmos = path` in #[debug_layer]");
+            };
+            layers.push(Layer { field: field.ident.clone().unwrap(), gizmos, per_agent });
+        }
+    }
+    layers
+}
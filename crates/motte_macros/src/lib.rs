@@ -1,6 +1,8 @@
 #![feature(concat_idents)]
 
 mod bevy_macros;
+mod debug_layer;
+mod ordered_sets;
 mod stat;
 
 extern crate proc_macro;
@@ -9,11 +11,41 @@ use proc_macro::TokenStream;
 use proc_macro_error::proc_macro_error;
 use syn::{parse_macro_input, DeriveInput};
 
+/// Registers the reflected types with `app`. Resources prefixed with `res:` are also initialized & assets prefixed with
+/// `asset:` also have their asset reflection registered, e.g. `app_register_types!(Agent, res: Settings)`.
 #[proc_macro]
 pub fn app_register_types(input: TokenStream) -> TokenStream {
     crate::bevy_macros::app_register_types_impl(input)
 }
 
+/// Declares a `SystemSet` enum & a `configure(app)` function that chains its sets in declaration order in a schedule.
+/// Each set can be followed by its own configuration & the enum by configuration for all sets:
+///
+/// ```ignore
+/// ordered_sets! {
+///     pub enum MovementSystems: FixedUpdate {
+///         Setup,
+///         Motor.before(PhysicsSet::Prepare),
+///         State,
+///     }
+///     .run_if(simulating)
+/// }
+/// ```
+#[proc_macro]
+pub fn ordered_sets(input: TokenStream) -> TokenStream {
+    crate::ordered_sets::ordered_sets_impl(input)
+}
+
+/// Derive macro generating a `gizmos()` function that returns the gizmo system of every field annotated with
+/// `#[debug_layer(gizmos = path)]`, run only while the field is `true`. With `per_agent` the gizmo system is generic
+/// over the agent size & runs while `field.enabled_for(AGENT)`.
+#[proc_macro_error]
+#[proc_macro_derive(DebugLayer, attributes(debug_layer))]
+pub fn debug_layer_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    crate::debug_layer::impl_debug_layer_derive(&ast)
+}
+
 /// Derive macro generating an impl of the trait `Stat`.
 #[proc_macro_error]
 #[proc_macro_derive(Stat, attributes(stat))]
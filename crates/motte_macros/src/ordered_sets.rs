@@ -0,0 +1,93 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    braced,
+    parse::{Parse, ParseStream},
+    parse_macro_input, Attribute, Ident, Path, Token, Visibility,
+};
+
+/// ```ignore
+/// ordered_sets! {
+///     pub enum NavigationSystems: FixedUpdate {
+///         Setup,
+///         Maintain.before(FlowFieldSystems::Maintain),
+///         Cleanup,
+///     }
+///     .run_if(simulating)
+/// }
+/// ```
+struct OrderedSets {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    schedule: Path,
+    sets: Vec<OrderedSet>,
+    /// Configuration applied to the chained sets, e.g. `.run_if(simulating)`.
+    config: TokenStream2,
+}
+
+struct OrderedSet {
+    attrs: Vec<Attribute>,
+    ident: Ident,
+    /// Configuration applied to this set only, e.g. `.before(OtherSet)`.
+    config: TokenStream2,
+}
+
+impl Parse for OrderedSets {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![enum]>()?;
+        let ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let schedule = input.parse()?;
+
+        let content;
+        braced!(content in input);
+        let mut sets = Vec::new();
+        while !content.is_empty() {
+            let attrs = content.call(Attribute::parse_outer)?;
+            let ident = content.parse()?;
+            let mut config = TokenStream2::new();
+            while !content.is_empty() && !content.peek(Token![,]) {
+                config.extend([content.parse::<proc_macro2::TokenTree>()?]);
+            }
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+            sets.push(OrderedSet { attrs, ident, config });
+        }
+
+        let config = input.parse()?;
+        Ok(Self { attrs, vis, ident, schedule, sets, config })
+    }
+}
+
+pub(crate) fn ordered_sets_impl(input: TokenStream) -> TokenStream {
+    let OrderedSets { attrs, vis, ident, schedule, sets, config } = parse_macro_input!(input as OrderedSets);
+
+    let variants = sets.iter().map(|OrderedSet { attrs, ident, .. }| quote! { #( #attrs )* #ident });
+    let configs = sets.iter().map(|OrderedSet { ident: set, config, .. }| quote! { #ident::#set #config });
+    let all = sets.iter().map(|OrderedSet { ident: set, .. }| quote! { #ident::#set });
+    let count = sets.len();
+
+    TokenStream::from(quote! {
+        #( #attrs )*
+        #[derive(bevy::ecs::schedule::SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #vis enum #ident {
+            #( #variants, )*
+        }
+
+        impl #ident {
+            /// Every set in declaration order.
+            #vis const ALL: [Self; #count] = [ #( #all, )* ];
+
+            /// Configures the sets to run in declaration order.
+            #vis fn configure(app: &mut bevy::app::App) {
+                use bevy::ecs::schedule::IntoSystemSetConfigs as _;
+                app.configure_sets(#schedule, ( #( #configs, )* ).chain() #config);
+            }
+        }
+    })
+}
@@ -2,7 +2,7 @@ use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use proc_macro_crate::{crate_name, FoundCrate};
 use quote::quote;
-use syn::{DeriveInput, Fields};
+use syn::{DeriveInput, Fields, LitStr, Member, Type};
 
 const CRATE_IDENT: &str = "motte_lib";
 
@@ -20,12 +20,13 @@ pub(super) fn impl_stat_derive(ast: &DeriveInput) -> TokenStream {
     let name = &ast.ident;
     let generics = &ast.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-    let value_field = find_stat_value_field(&ast);
+    let (value_field, other_fields) = find_stat_fields(ast);
+    let stat_name = impl_stat_name(ast);
 
-    let gen = quote! {
+    let expanded = quote! {
         impl #impl_generics Default for #name #ty_generics #where_clause {
             fn default() -> Self {
-                Self { #value_field: 0.0, ..Default::default() }
+                Self { #value_field: 0.0, #( #other_fields: Default::default(), )* }
             }
         }
 
@@ -34,6 +35,8 @@ pub(super) fn impl_stat_derive(ast: &DeriveInput) -> TokenStream {
                 Self { #value_field: value, ..Default::default() }
             }
 
+            #stat_name
+
             fn value(&self) -> f32 {
                 self.#value_field
             }
@@ -43,9 +46,15 @@ pub(super) fn impl_stat_derive(ast: &DeriveInput) -> TokenStream {
             }
         }
 
+        impl #impl_generics Clone for #name #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                Self { #value_field: self.#value_field, #( #other_fields: self.#other_fields.clone(), )* }
+            }
+        }
+
         impl #impl_generics Into<#name #ty_generics> for f32 #where_clause {
             fn into(self) -> #name #ty_generics {
-                #name::new(self)
+                <#name #ty_generics as #crate_ident::Stat>::new(self)
             }
         }
 
@@ -56,35 +65,73 @@ pub(super) fn impl_stat_derive(ast: &DeriveInput) -> TokenStream {
             }
         }
     };
-    gen.into()
+    expanded.into()
 }
 
-fn find_stat_value_field(ast: &DeriveInput) -> proc_macro2::TokenStream {
-    match &ast.data {
-        syn::Data::Struct(data) => {
-            match &data.fields {
-                // Handle tuple structs: Assumes single f32 field
-                Fields::Unnamed(fields_unnamed) if fields_unnamed.unnamed.len() == 1 => {
-                    let index = syn::Index::from(0);
-                    quote!(#index)
-                }
-                // Handle named fields that might be annotated or using single named field
-                Fields::Named(fields_named) => fields_named
-                    .named
-                    .iter()
-                    .find_map(|f| {
-                        if f.attrs.iter().any(|a| a.path().is_ident("stat")) {
-                            f.ident.as_ref().map(|ident| quote!(#ident))
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| {
-                        panic!("No field marked with #[stat(value)] and structure is not a simple tuple struct")
-                    }),
-                _ => panic!("Stat can only be derived for structs with exactly one field"),
+/// Implements `Stat::name` if the struct has a `#[stat(name = "...")]` attribute. The short type paths of the type
+/// parameters are appended to the name of generic stats, e.g. `affinity.fire` for `Affinity<Fire>`.
+fn impl_stat_name(ast: &DeriveInput) -> proc_macro2::TokenStream {
+    let mut name = None;
+    for attr in ast.attrs.iter().filter(|a| a.path().is_ident("stat")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
             }
+        })
+        .unwrap_or_else(|err| panic!("invalid #[stat] attribute: {err}"));
+    }
+    let Some(name) = name else {
+        return quote!();
+    };
+
+    let params = ast.generics.type_params().map(|param| &param.ident).collect::<Vec<_>>();
+    quote! {
+        fn name() -> String {
+            let mut name = String::from(#name);
+            #(
+                name.push('.');
+                name.push_str(&<#params as bevy::reflect::TypePath>::short_type_path().to_lowercase());
+            )*
+            name
         }
-        _ => panic!("Stat can only be derived for structs"),
     }
 }
+
+/// Returns the field holding the stat value & all other fields, which are expected to be markers like `PhantomData`.
+/// The value field is the one marked with `#[stat(value)]`, otherwise the only `f32` field.
+fn find_stat_fields(ast: &DeriveInput) -> (Member, Vec<Member>) {
+    let syn::Data::Struct(data) = &ast.data else {
+        panic!("Stat can only be derived for structs");
+    };
+    let fields = match &data.fields {
+        Fields::Named(fields) => &fields.named,
+        Fields::Unnamed(fields) => &fields.unnamed,
+        Fields::Unit => panic!("Stat can only be derived for structs with a field"),
+    };
+    let members = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        })
+        .collect::<Vec<_>>();
+
+    let is_f32 = |ty: &Type| matches!(ty, Type::Path(path) if path.path.is_ident("f32"));
+    let marked = fields.iter().position(|f| f.attrs.iter().any(|a| a.path().is_ident("stat")));
+    let value = marked.unwrap_or_else(|| {
+        let floats =
+            fields.iter().enumerate().filter(|(_, f)| is_f32(&f.ty)).map(|(index, _)| index).collect::<Vec<_>>();
+        match floats[..] {
+            [index] => index,
+            _ => panic!("No field marked with #[stat(value)] and structure doesn't have exactly one f32 field"),
+        }
+    });
+
+    let mut members = members;
+    let value = members.remove(value);
+    (value, members)
+}
@@ -0,0 +1,53 @@
+//! Dedicated server, simulates a scenario at the fixed tick rate without a window & replicates it to clients.
+//!
+//! `motte_server [--bind <addr>] [--scenario <file.ron>] [--lockstep [--input-delay <ticks>]]`
+
+use anyhow::{bail, Context, Result};
+use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin, prelude::*};
+use motte_lib::{
+    crash::{self, CrashReportPlugin},
+    headless::{HeadlessPlugin, Scenario},
+    net::{NetRole, DEFAULT_PORT},
+};
+
+fn main() {
+    if let Err(err) = run(std::env::args().skip(1)) {
+        eprintln!("{err:?}");
+        std::process::exit(1);
+    }
+}
+
+fn run(mut args: impl Iterator<Item = String>) -> Result<()> {
+    let mut bind = ([0, 0, 0, 0], DEFAULT_PORT).into();
+    let mut scenario = Scenario::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bind" => bind = args.next().context("missing value for --bind")?.parse()?,
+            "--scenario" => scenario = Scenario::load(&args.next().context("missing value for --scenario")?)?,
+            "--lockstep" | "--input-delay" if !cfg!(feature = "lockstep") => {
+                bail!("{arg} requires the `lockstep` feature")
+            }
+            // Read by the lockstep plugin from the arguments itself.
+            "--lockstep" => {}
+            "--input-delay" => {
+                args.next().context("missing value for --input-delay")?.parse::<u32>()?;
+            }
+            _ => bail!("unknown argument '{arg}'"),
+        }
+    }
+
+    crash::install(false);
+
+    let mut app = App::new();
+    // The runner is added once the tick rate is known.
+    let log = LogPlugin { update_subscriber: Some(crash::capture_logs), ..default() };
+    app.add_plugins((MinimalPlugins.build().disable::<ScheduleRunnerPlugin>(), log));
+    app.insert_resource(NetRole::Server { bind });
+    app.add_plugins((HeadlessPlugin { scenario }, CrashReportPlugin));
+
+    // Update once per fixed timestep instead of spinning, the simulation runs in real time.
+    let timestep = app.world.resource::<Time<Fixed>>().timestep();
+    app.add_plugins(ScheduleRunnerPlugin::run_loop(timestep));
+    app.run();
+    Ok(())
+}